@@ -0,0 +1,46 @@
+//! Disk cache frames: zstd, then AES-GCM when the store is encrypted
+//!
+//! Every notes read from the cache decodes a frame and every prefetch
+//! encodes one per slide, so both directions are measured, plain and sealed,
+//! for a typical slide's notes and a long script.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use cuecard_app_lib::fuzzing;
+
+const KEY: [u8; 32] = [7; 32];
+
+fn notes(words: usize) -> String {
+    (0..words)
+        .map(|i| {
+            [
+                "revenue", "grew", "in", "every", "region", "this", "quarter",
+            ][i % 7]
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn bench_frames(c: &mut Criterion) {
+    let mut group = c.benchmark_group("disk_cache");
+    for (name, words) in [("slide", 120), ("script", 4000)] {
+        let text = notes(words);
+        group.throughput(Throughput::Bytes(text.len() as u64));
+        for (mode, key) in [("plain", None), ("sealed", Some(&KEY))] {
+            let frame = fuzzing::encode_frame(&text, key).expect("frame encodes");
+            group.bench_with_input(
+                BenchmarkId::new(format!("encode/{}", mode), name),
+                &text,
+                |b, text| b.iter(|| fuzzing::encode_frame(black_box(text), key)),
+            );
+            group.bench_with_input(
+                BenchmarkId::new(format!("decode/{}", mode), name),
+                &frame,
+                |b, frame| b.iter(|| fuzzing::decode_frame(black_box(frame), key)),
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_frames);
+criterion_main!(benches);
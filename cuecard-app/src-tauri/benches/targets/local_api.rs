@@ -0,0 +1,66 @@
+//! Requests through the local API router
+//!
+//! Measures the router and its middleware (extension version check, body
+//! limit, JSON extraction) on requests that stay local: the health check, a
+//! slide update that's rejected as malformed, and an integration slide
+//! without a token. The server's panic and CORS layers aren't included.
+
+use axum::body::Body;
+use axum::http::header::CONTENT_TYPE;
+use axum::http::{Method, Request};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use cuecard_app_lib::fuzzing;
+use tower::ServiceExt;
+
+fn request(method: Method, path: &str, body: &'static str) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri(path)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .expect("valid request")
+}
+
+fn bench_requests(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("bench runtime");
+    let router = fuzzing::api_router();
+
+    let mut group = c.benchmark_group("local_api");
+    group.throughput(Throughput::Elements(1));
+    let cases: [(&str, Method, &str, &'static str); 3] = [
+        ("health", Method::GET, "/health", ""),
+        (
+            "slides/malformed",
+            Method::POST,
+            "/slides",
+            r#"{"presentation_id": 1"#,
+        ),
+        (
+            "integrations/unauthorized",
+            Method::POST,
+            "/integrations/slide",
+            r#"{"slideIndex": 1}"#,
+        ),
+    ];
+    for (name, method, path, body) in cases {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                runtime.block_on(async {
+                    let response = router
+                        .clone()
+                        .oneshot(request(method.clone(), path, body))
+                        .await
+                        .unwrap_or_else(|never| match never {});
+                    response.status()
+                })
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_requests);
+criterion_main!(benches);
@@ -0,0 +1,94 @@
+//! Notes extraction from a Slides `presentations.get` response
+//!
+//! The fixture is a large deck shaped like the API's response: each slide's
+//! notes page has a slide image, a BODY placeholder of several paragraphs
+//! split into styled text runs, and paragraph markers between them. A full
+//! deck fetch extracts every slide's notes, so that's what's measured, along
+//! with a single notes shape on its own.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use cuecard_app_lib::fuzzing;
+use serde_json::{json, Value};
+
+const SLIDES: usize = 300;
+const PARAGRAPHS: usize = 6;
+
+/// A paragraph of the notes shape's `textElements`: a marker, then runs
+fn paragraph(slide: usize, index: usize) -> Vec<Value> {
+    let start = index * 120;
+    vec![
+        json!({ "startIndex": start, "endIndex": start + 118, "paragraphMarker": { "style": {} } }),
+        json!({ "startIndex": start, "endIndex": start + 40, "textRun": {
+            "content": format!("Slide {} point {}: open with the number, ", slide, index),
+            "style": { "bold": true }
+        }}),
+        json!({ "startIndex": start + 40, "endIndex": start + 118, "textRun": {
+            "content": "then the story behind it, and pause before the next slide.\n",
+            "style": {}
+        }}),
+    ]
+}
+
+fn slide(index: usize) -> Value {
+    let text_elements: Vec<Value> = (0..PARAGRAPHS).flat_map(|p| paragraph(index, p)).collect();
+    json!({
+        "objectId": format!("g{}", index),
+        "pageElements": [],
+        "slideProperties": {
+            "layoutObjectId": "p1",
+            "masterObjectId": "m1",
+            "notesPage": {
+                "objectId": format!("g{}_notes", index),
+                "pageType": "NOTES",
+                "pageElements": [
+                    {
+                        "objectId": format!("g{}_image", index),
+                        "shape": { "placeholder": { "type": "SLIDE_IMAGE", "index": 0 } }
+                    },
+                    {
+                        "objectId": format!("g{}_body", index),
+                        "shape": {
+                            "shapeType": "TEXT_BOX",
+                            "placeholder": { "type": "BODY", "index": 1 },
+                            "text": { "textElements": text_elements }
+                        }
+                    }
+                ]
+            }
+        }
+    })
+}
+
+fn presentation() -> Value {
+    json!({
+        "presentationId": "bench",
+        "title": "Quarterly review",
+        "revisionId": "r1",
+        "slides": (0..SLIDES).map(slide).collect::<Vec<_>>()
+    })
+}
+
+fn bench_notes(c: &mut Criterion) {
+    let deck = presentation();
+    let slides = deck["slides"].as_array().expect("fixture has slides");
+    let body = &slides[0]["slideProperties"]["notesPage"]["pageElements"][1]["shape"]["text"];
+
+    let mut group = c.benchmark_group("notes");
+    group.throughput(Throughput::Elements(SLIDES as u64));
+    group.bench_function("extract_notes_from_slide/deck", |b| {
+        b.iter(|| {
+            slides
+                .iter()
+                .filter_map(|slide| fuzzing::extract_notes_from_slide(black_box(slide)))
+                .count()
+        })
+    });
+    group.finish();
+
+    c.bench_function("extract_text_from_text_elements", |b| {
+        b.iter(|| fuzzing::extract_text_from_text_elements(black_box(body)))
+    });
+}
+
+criterion_group!(benches, bench_notes);
+criterion_main!(benches);
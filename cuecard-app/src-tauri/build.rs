@@ -1,6 +1,14 @@
 use std::path::Path;
 
 fn main() {
+    // Headless builds (`--no-default-features`) only compile the backend logic:
+    // no window context, no bundled resources, so no Firebase config is needed
+    if std::env::var_os("CARGO_FEATURE_DESKTOP").is_none() {
+        println!("cargo:rustc-check-cfg=cfg(desktop)");
+        println!("cargo:rustc-check-cfg=cfg(mobile)");
+        return;
+    }
+
     // Verify firebase-config.json exists at build time
     let firebase_config_path = Path::new("firebase-config.json");
     if !firebase_config_path.exists() {
@@ -0,0 +1,186 @@
+//! Malformed requests against the local API
+//!
+//! Sends the router raw and truncated JSON, bodies past the size limit, odd
+//! headers and arbitrary query strings (the OAuth callback's parameters
+//! included). The router runs without the server's panic recovery, so a
+//! panicking handler fails the run, and a request that doesn't finish within
+//! `REQUEST_TIMEOUT` is reported as a hang. Nothing here has credentials, so
+//! handlers that would go to Google give up without touching the network.
+
+#![no_main]
+
+use axum::body::Body;
+use axum::http::header::{HeaderName, CONTENT_TYPE};
+use axum::http::{HeaderValue, Method, Request};
+use axum::Router;
+use cuecard_app_lib::fuzzing;
+use libfuzzer_sys::arbitrary::{self, Arbitrary};
+use libfuzzer_sys::fuzz_target;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tower::ServiceExt;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Arbitrary)]
+enum Route {
+    Health,
+    Slides,
+    OAuthCallback,
+    OAuthStatus,
+    IntegrationHandshake,
+    IntegrationSlide,
+}
+
+impl Route {
+    fn target(&self) -> (Method, &'static str) {
+        match self {
+            Route::Health => (Method::GET, "/health"),
+            Route::Slides => (Method::POST, "/slides"),
+            Route::OAuthCallback => (Method::GET, "/oauth/callback"),
+            Route::OAuthStatus => (Method::GET, "/oauth/status"),
+            Route::IntegrationHandshake => (Method::POST, "/integrations/handshake"),
+            Route::IntegrationSlide => (Method::POST, "/integrations/slide"),
+        }
+    }
+}
+
+/// The fields of a slide update, so deserialization gets past the first byte
+#[derive(Debug, Arbitrary)]
+struct SlideFields {
+    presentation_id: String,
+    slide_id: String,
+    slide_number: i64,
+    title: String,
+    mode: String,
+    timestamp: i64,
+    url: String,
+    force_refresh: Option<bool>,
+    client_id: Option<String>,
+    scraped_notes: Option<String>,
+}
+
+#[derive(Debug, Arbitrary)]
+enum Payload {
+    Raw(Vec<u8>),
+    /// A slide update, optionally cut short
+    Slide {
+        fields: SlideFields,
+        truncate_at: Option<u16>,
+    },
+    /// One byte past the body limit
+    Oversized {
+        fill: u8,
+    },
+}
+
+impl Payload {
+    fn bytes(self) -> Vec<u8> {
+        match self {
+            Payload::Raw(bytes) => bytes,
+            Payload::Slide {
+                fields,
+                truncate_at,
+            } => {
+                let mut bytes = serde_json::to_vec(&serde_json::json!({
+                    "presentation_id": fields.presentation_id,
+                    "slide_id": fields.slide_id,
+                    "slide_number": fields.slide_number,
+                    "title": fields.title,
+                    "mode": fields.mode,
+                    "timestamp": fields.timestamp,
+                    "url": fields.url,
+                    "force_refresh": fields.force_refresh,
+                    "client_id": fields.client_id,
+                    "scraped_notes": fields.scraped_notes,
+                }))
+                .unwrap_or_default();
+                if let Some(at) = truncate_at {
+                    bytes.truncate(at as usize);
+                }
+                bytes
+            }
+            Payload::Oversized { fill } => vec![fill; fuzzing::MAX_REQUEST_BODY_BYTES + 1],
+        }
+    }
+}
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    route: Route,
+    query: String,
+    extension_version: Option<String>,
+    json_content_type: bool,
+    headers: Vec<(Vec<u8>, Vec<u8>)>,
+    payload: Payload,
+}
+
+fn runtime() -> &'static tokio::runtime::Runtime {
+    static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("fuzz runtime")
+    })
+}
+
+fn router() -> Router {
+    static ROUTER: OnceLock<Router> = OnceLock::new();
+    ROUTER.get_or_init(fuzzing::api_router).clone()
+}
+
+fn build_request(input: Input) -> Option<(Request<Body>, bool)> {
+    let (method, path) = input.route.target();
+    let uri = if input.query.is_empty() {
+        path.to_string()
+    } else {
+        format!("{}?{}", path, input.query)
+    };
+
+    let mut builder = Request::builder().method(method.clone()).uri(uri);
+    if input.json_content_type {
+        builder = builder.header(CONTENT_TYPE, "application/json");
+    }
+    if let Some(version) = input.extension_version {
+        let value = HeaderValue::from_str(&version).ok()?;
+        builder = builder.header("x-cuecard-extension-version", value);
+    }
+    for (name, value) in input.headers {
+        // The HTTP layer rejects these before any handler sees them
+        let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(&name),
+            HeaderValue::from_bytes(&value),
+        ) else {
+            continue;
+        };
+        builder = builder.header(name, value);
+    }
+
+    let oversized = matches!(input.payload, Payload::Oversized { .. }) && method == Method::POST;
+    let request = builder.body(Body::from(input.payload.bytes())).ok()?;
+    Some((request, oversized))
+}
+
+fuzz_target!(|input: Input| {
+    let Some((request, oversized)) = build_request(input) else {
+        return;
+    };
+    let description = format!("{} {}", request.method(), request.uri());
+
+    runtime().block_on(async {
+        match tokio::time::timeout(REQUEST_TIMEOUT, router().oneshot(request)).await {
+            Ok(Ok(response)) => {
+                if oversized {
+                    assert!(
+                        !response.status().is_success(),
+                        "{} accepted a body past the limit",
+                        description
+                    );
+                }
+            }
+            Ok(Err(never)) => match never {},
+            Err(_) => panic!("{} didn't finish within {:?}", description, REQUEST_TIMEOUT),
+        }
+    });
+});
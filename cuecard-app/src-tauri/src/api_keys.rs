@@ -0,0 +1,163 @@
+//! API keys of the configurable notes services (`notes_summary`,
+//! `notes_translation`)
+//!
+//! A key is kept in the OS keychain on macOS and Windows, the Secret Service
+//! keyring on Linux, and in the (encrypted) store elsewhere, under an entry
+//! named after the service. Linux builds used to keep keys in the store;
+//! `migrate` moves one into the keyring. The key goes out with every request,
+//! so `check_endpoint` only lets it go over https or to this machine.
+
+#[cfg(target_os = "linux")]
+use once_cell::sync::Lazy;
+use tauri::AppHandle;
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+use tauri_plugin_store::StoreExt;
+
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+const KEYCHAIN_ACCOUNT: &str = "api-key";
+
+/// Where one service's key is kept
+pub struct ApiKey {
+    /// Keychain service name, e.g. "com.cuecard.summary"
+    service: &'static str,
+    /// Store key, where there's no keychain or before the keyring was used
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    store_key: &'static str,
+}
+
+impl ApiKey {
+    pub const fn new(service: &'static str, store_key: &'static str) -> Self {
+        #[cfg(any(target_os = "macos", target_os = "windows"))]
+        let _ = store_key;
+        Self {
+            service,
+            #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+            store_key,
+        }
+    }
+
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    fn entry(&self) -> Result<keyring::Entry, String> {
+        keyring::Entry::new(self.service, KEYCHAIN_ACCOUNT)
+            .map_err(|e| format!("Keychain unavailable: {}", e))
+    }
+
+    #[cfg(target_os = "linux")]
+    fn entry(&self) -> Result<keyring_core::Entry, String> {
+        static STORE: Lazy<Result<(), String>> = Lazy::new(|| {
+            let store = zbus_secret_service_keyring_store::Store::new()
+                .map_err(|e| format!("Keyring unavailable: {}", e))?;
+            keyring_core::set_default_store(store);
+            Ok(())
+        });
+        STORE.clone()?;
+        keyring_core::Entry::new(self.service, KEYCHAIN_ACCOUNT)
+            .map_err(|e| format!("Keyring unavailable: {}", e))
+    }
+
+    #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+    pub fn get(&self, _app: &AppHandle) -> Option<String> {
+        self.entry().ok()?.get_password().ok()
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    pub fn get(&self, app: &AppHandle) -> Option<String> {
+        let store = app.store("cuecard-store.json").ok()?;
+        store.get(self.store_key)?.as_str().map(|k| k.to_string())
+    }
+
+    /// Save the key, or delete it when `key` is empty
+    #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+    pub fn set(&self, _app: &AppHandle, key: &str) -> Result<(), String> {
+        let entry = self.entry()?;
+        if key.is_empty() {
+            let _ = entry.delete_credential();
+            return Ok(());
+        }
+        entry
+            .set_password(key)
+            .map_err(|e| format!("Failed to save API key to keychain: {}", e))
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    pub fn set(&self, app: &AppHandle, key: &str) -> Result<(), String> {
+        let store = app
+            .store("cuecard-store.json")
+            .map_err(|e| format!("Failed to open store: {}", e))?;
+        if key.is_empty() {
+            store.delete(self.store_key);
+        } else {
+            store.set(self.store_key, key);
+        }
+        store
+            .save()
+            .map_err(|e| format!("Failed to save API key: {}", e))
+    }
+
+    /// Move a key saved in the store by an earlier version into the keyring
+    #[cfg(target_os = "linux")]
+    pub fn migrate<R: tauri::Runtime>(&self, store: &tauri_plugin_store::Store<R>) {
+        let Some(key) = store
+            .get(self.store_key)
+            .and_then(|v| v.as_str().map(|k| k.to_string()))
+        else {
+            return;
+        };
+        let saved = self.entry().and_then(|entry| {
+            entry
+                .set_password(&key)
+                .map_err(|e| format!("Failed to save API key to keyring: {}", e))
+        });
+        match saved {
+            Ok(()) => {
+                store.delete(self.store_key);
+                let _ = store.save();
+            }
+            Err(e) => eprintln!("{} API key left in the store: {}", self.service, e),
+        }
+    }
+}
+
+/// The key and notes may only go out over https, or plain http to this
+/// machine (a local model or translation server). `what` names the
+/// endpoint in the error, e.g. "summary".
+pub fn check_endpoint(endpoint: &str, what: &str) -> Result<(), String> {
+    let url = reqwest::Url::parse(endpoint.trim())
+        .map_err(|e| format!("Invalid {} endpoint: {}", what, e))?;
+    let host = url.host_str().unwrap_or_default();
+    let loopback = host.eq_ignore_ascii_case("localhost")
+        || host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback());
+    match url.scheme() {
+        "https" => Ok(()),
+        "http" if loopback => Ok(()),
+        _ => Err(format!(
+            "The {} endpoint must use https unless it's on this machine",
+            what
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requires_https_off_this_machine() {
+        assert!(check_endpoint("https://api.openai.com/v1/chat/completions", "summary").is_ok());
+        assert!(check_endpoint("http://localhost:11434/v1/chat/completions", "summary").is_ok());
+        assert!(check_endpoint("http://127.0.0.1:5000/translate", "translation").is_ok());
+        assert!(check_endpoint("http://[::1]:8080/v1/chat/completions", "summary").is_ok());
+        assert!(
+            check_endpoint("http://api.example.com/translate", "translation")
+                .unwrap_err()
+                .starts_with("The translation endpoint")
+        );
+        assert!(check_endpoint("http://localhost.example.com/v1", "summary").is_err());
+        assert!(check_endpoint("ftp://localhost/v1", "summary").is_err());
+        assert!(check_endpoint("not a url", "summary").is_err());
+    }
+}
@@ -0,0 +1,343 @@
+//! Slides and Drive API usage, counted locally
+//!
+//! Google limits how many requests a user can make per minute, and thumbnail
+//! requests far more tightly than the rest. Every Slides and Drive call is
+//! counted here by kind: per day and signed-in account (days follow Pacific
+//! time, like Google's quota resets) for `get_api_usage`, and over the last
+//! minute to compare against the per-user limits. Once any kind passes
+//! `THROTTLE_AT_PERCENT` of its limit, optional requests (preload thumbnails,
+//! revalidating notes on focus) are skipped until the minute's count falls
+//! back; each change is sent as `api-usage-throttled`. Notes for the slide
+//! on screen are always fetched.
+
+use chrono_tz::America::Los_Angeles;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_store::StoreExt;
+
+use crate::{APP_HANDLE, FIREBASE_TOKENS};
+
+const API_USAGE_KEY: &str = "api_usage";
+const WINDOW_MS: i64 = 60_000;
+const THROTTLE_AT_PERCENT: u32 = 80;
+/// Days of counts kept
+const KEEP_DAYS: usize = 7;
+/// Counts are written to the store every this many calls
+const SAVE_EVERY: u32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiCall {
+    SlidesRead,
+    SlidesWrite,
+    /// `pages.getThumbnail`, an "expensive" read
+    SlidesThumbnail,
+    Drive,
+}
+
+impl ApiCall {
+    const ALL: [ApiCall; 4] = [
+        ApiCall::SlidesRead,
+        ApiCall::SlidesWrite,
+        ApiCall::SlidesThumbnail,
+        ApiCall::Drive,
+    ];
+
+    /// Google's default per-user, per-minute limit
+    fn per_minute_limit(self) -> u32 {
+        match self {
+            ApiCall::SlidesRead => 600,
+            ApiCall::SlidesWrite => 60,
+            ApiCall::SlidesThumbnail => 60,
+            ApiCall::Drive => 12_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DayUsage {
+    pub account: String,
+    /// YYYY-MM-DD, Pacific time
+    pub day: String,
+    pub slides_read: u32,
+    pub slides_write: u32,
+    pub slides_thumbnail: u32,
+    pub drive: u32,
+}
+
+impl DayUsage {
+    fn count_mut(&mut self, call: ApiCall) -> &mut u32 {
+        match call {
+            ApiCall::SlidesRead => &mut self.slides_read,
+            ApiCall::SlidesWrite => &mut self.slides_write,
+            ApiCall::SlidesThumbnail => &mut self.slides_thumbnail,
+            ApiCall::Drive => &mut self.drive,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MinuteUsage {
+    pub call: ApiCall,
+    pub count: u32,
+    pub limit: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiUsage {
+    /// The signed-in account's counts for today
+    pub today: DayUsage,
+    pub last_minute: Vec<MinuteUsage>,
+    /// Optional requests are being skipped
+    pub throttled: bool,
+    /// Earlier days, newest first, for every account
+    pub history: Vec<DayUsage>,
+}
+
+#[derive(Default)]
+struct UsageState {
+    days: Vec<DayUsage>,
+    /// Unix millis of each call in the last minute
+    recent: VecDeque<(i64, ApiCall)>,
+    throttled: bool,
+    unsaved: u32,
+}
+
+static USAGE: Lazy<Arc<RwLock<UsageState>>> =
+    Lazy::new(|| Arc::new(RwLock::new(UsageState::default())));
+
+pub fn load_api_usage_from_store(app: &AppHandle) {
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Some(days) = store
+            .get(API_USAGE_KEY)
+            .and_then(|v| serde_json::from_value::<Vec<DayUsage>>(v).ok())
+        {
+            USAGE.write().days = days;
+        }
+    }
+}
+
+fn save(days: &[DayUsage]) {
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        if let Ok(store) = app.store("cuecard-store.json") {
+            if let Ok(json) = serde_json::to_value(days) {
+                store.set(API_USAGE_KEY, json);
+                let _ = store.save();
+            }
+        }
+    }
+}
+
+fn account() -> String {
+    FIREBASE_TOKENS
+        .read()
+        .as_ref()
+        .map(|t| t.email.clone().unwrap_or_else(|| t.local_id.clone()))
+        .unwrap_or_else(|| "signed-out".to_string())
+}
+
+fn today() -> String {
+    pacific_day(chrono::Utc::now())
+}
+
+/// The day Google's quotas count `at` towards
+fn pacific_day(at: chrono::DateTime<chrono::Utc>) -> String {
+    at.with_timezone(&Los_Angeles)
+        .format("%Y-%m-%d")
+        .to_string()
+}
+
+/// Index of the account's counts for `day`, adding them (and dropping the
+/// account's oldest beyond `KEEP_DAYS`) if this is its first call that day;
+/// `true` with a new day
+fn day_entry(days: &mut Vec<DayUsage>, account: String, day: String) -> (usize, bool) {
+    if let Some(index) = days
+        .iter()
+        .position(|d| d.account == account && d.day == day)
+    {
+        return (index, false);
+    }
+    days.insert(
+        0,
+        DayUsage {
+            account,
+            day,
+            ..Default::default()
+        },
+    );
+    // Keep a week per account
+    let mut seen = Vec::new();
+    days.retain(|d| {
+        let kept = seen.iter().filter(|a| *a == &d.account).count() < KEEP_DAYS;
+        seen.push(d.account.clone());
+        kept
+    });
+    (0, true)
+}
+
+fn minute_counts(recent: &VecDeque<(i64, ApiCall)>) -> Vec<MinuteUsage> {
+    ApiCall::ALL
+        .iter()
+        .map(|&call| MinuteUsage {
+            call,
+            count: recent.iter().filter(|(_, c)| *c == call).count() as u32,
+            limit: call.per_minute_limit(),
+        })
+        .collect()
+}
+
+/// Drop calls older than a minute and work out whether to throttle.
+/// Returns the new state when it changed.
+fn update_throttle(state: &mut UsageState, now_ms: i64) -> Option<bool> {
+    while state
+        .recent
+        .front()
+        .is_some_and(|(at, _)| now_ms - at >= WINDOW_MS)
+    {
+        state.recent.pop_front();
+    }
+    let throttled = minute_counts(&state.recent)
+        .iter()
+        .any(|m| m.count * 100 >= m.limit * THROTTLE_AT_PERCENT);
+    if throttled == state.throttled {
+        return None;
+    }
+    state.throttled = throttled;
+    Some(throttled)
+}
+
+fn emit_throttled(throttled: bool) {
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        let _ = app.emit(
+            "api-usage-throttled",
+            serde_json::json!({ "throttled": throttled }),
+        );
+    }
+}
+
+/// Count a request about to be sent to Google
+pub fn record(call: ApiCall) {
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let (account, day) = (account(), today());
+
+    let (changed, to_save) = {
+        let mut state = USAGE.write();
+        let (index, new_day) = day_entry(&mut state.days, account, day);
+        if new_day {
+            state.unsaved = SAVE_EVERY;
+        }
+        *state.days[index].count_mut(call) += 1;
+        state.recent.push_back((now_ms, call));
+        let changed = update_throttle(&mut state, now_ms);
+
+        state.unsaved += 1;
+        let to_save = (state.unsaved >= SAVE_EVERY).then(|| {
+            state.unsaved = 0;
+            state.days.clone()
+        });
+        (changed, to_save)
+    };
+
+    if let Some(days) = to_save {
+        save(&days);
+    }
+    if let Some(throttled) = changed {
+        emit_throttled(throttled);
+    }
+}
+
+/// Whether optional requests should be skipped to stay clear of Google's limits
+pub fn throttle_optional() -> bool {
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let (throttled, changed) = {
+        let mut state = USAGE.write();
+        let changed = update_throttle(&mut state, now_ms);
+        (state.throttled, changed)
+    };
+    if let Some(throttled) = changed {
+        emit_throttled(throttled);
+    }
+    throttled
+}
+
+#[tauri::command]
+pub fn get_api_usage() -> ApiUsage {
+    let throttled = throttle_optional();
+    let (account, day) = (account(), today());
+    let state = USAGE.read();
+    let today = state
+        .days
+        .iter()
+        .find(|d| d.account == account && d.day == day)
+        .cloned()
+        .unwrap_or(DayUsage {
+            account,
+            day,
+            ..Default::default()
+        });
+    ApiUsage {
+        history: state
+            .days
+            .iter()
+            .filter(|d| !(d.account == today.account && d.day == today.day))
+            .cloned()
+            .collect(),
+        today,
+        last_minute: minute_counts(&state.recent),
+        throttled,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn throttles_near_the_per_minute_limit() {
+        let mut state = UsageState::default();
+        // 80% of the 60 thumbnails a minute
+        for i in 0..47 {
+            state.recent.push_back((i, ApiCall::SlidesThumbnail));
+        }
+        assert_eq!(update_throttle(&mut state, 100), None);
+        state.recent.push_back((100, ApiCall::SlidesThumbnail));
+        assert_eq!(update_throttle(&mut state, 100), Some(true));
+        assert_eq!(update_throttle(&mut state, 200), None);
+
+        // Eases off once the minute has passed
+        assert_eq!(update_throttle(&mut state, 100 + WINDOW_MS), Some(false));
+        assert!(state.recent.is_empty());
+    }
+
+    #[test]
+    fn keeps_a_week_per_account() {
+        let mut days = Vec::new();
+        for day in 1..=KEEP_DAYS + 1 {
+            let (index, new_day) = day_entry(&mut days, "a@x".to_string(), format!("d{}", day));
+            assert_eq!((index, new_day), (0, true));
+        }
+        day_entry(&mut days, "b@x".to_string(), "d8".to_string());
+        assert_eq!(
+            day_entry(&mut days, "a@x".to_string(), "d8".to_string()),
+            (1, false)
+        );
+
+        assert_eq!(
+            days.iter().filter(|d| d.account == "a@x").count(),
+            KEEP_DAYS
+        );
+        assert!(!days.iter().any(|d| d.day == "d1"));
+        assert!(days.iter().any(|d| d.account == "b@x"));
+    }
+
+    #[test]
+    fn days_follow_pacific_time() {
+        let at = |s: &str| s.parse::<chrono::DateTime<chrono::Utc>>().unwrap();
+        assert_eq!(pacific_day(at("2026-03-10T06:00:00Z")), "2026-03-09");
+        assert_eq!(pacific_day(at("2026-03-10T08:00:00Z")), "2026-03-10");
+    }
+}
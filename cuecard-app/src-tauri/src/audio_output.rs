@@ -0,0 +1,463 @@
+//! Output device for spoken prompts and audio cues
+//!
+//! Prompts meant for the presenter's ear (a Bluetooth earpiece, say) must
+//! never come out of the room speakers. The chosen device is remembered, and
+//! `cue_output_device` hands it to audio features only while it is connected;
+//! there is no fallback to the system default. The list is refreshed when the
+//! OS reports a device change (a CoreAudio property listener on macOS, an
+//! `IMMNotificationClient` on Windows), or every 30 s where it can't:
+//! `audio-devices-changed` carries the new list, and `audio-output-disconnected`
+//! fires when the chosen device goes away.
+//!
+//! Devices are listed with `system_profiler` on macOS and PowerShell on
+//! Windows.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use serde::Deserialize;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_store::StoreExt;
+use tokio::sync::Notify;
+
+use crate::error::CueCardError;
+use crate::APP_HANDLE;
+
+const AUDIO_OUTPUT_KEY: &str = "audio_output_device";
+/// Without device change notifications
+const DEVICE_POLL_SECS: u64 = 30;
+/// Plugging a device in sends several notifications; the list is read once
+/// they've settled
+const DEVICE_SETTLE_MS: u64 = 500;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AudioOutputDevice {
+    pub id: String,
+    pub name: String,
+    pub bluetooth: bool,
+    /// The system default output; not reported on Windows
+    pub is_default: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioOutputStatus {
+    pub selected: Option<String>,
+    pub connected: bool,
+}
+
+#[derive(Default)]
+struct AudioOutputState {
+    selected: Option<String>,
+    devices: Vec<AudioOutputDevice>,
+}
+
+impl AudioOutputState {
+    fn connected(&self) -> Option<&AudioOutputDevice> {
+        let selected = self.selected.as_ref()?;
+        self.devices.iter().find(|d| d.id == *selected)
+    }
+
+    /// Take a fresh device list: `None` when nothing changed, otherwise the
+    /// chosen device's id if this list lost it
+    fn replace_devices(&mut self, devices: Vec<AudioOutputDevice>) -> Option<Option<String>> {
+        if self.devices == devices {
+            return None;
+        }
+        let was_connected = self.connected().is_some();
+        self.devices = devices;
+        let lost = match self.selected {
+            Some(ref id) if was_connected && self.connected().is_none() => Some(id.clone()),
+            _ => None,
+        };
+        Some(lost)
+    }
+}
+
+static AUDIO_OUTPUT: Lazy<Arc<RwLock<AudioOutputState>>> =
+    Lazy::new(|| Arc::new(RwLock::new(AudioOutputState::default())));
+static DEVICES_CHANGED: Lazy<Notify> = Lazy::new(Notify::new);
+
+#[cfg(target_os = "macos")]
+#[derive(Debug, Deserialize)]
+struct ProfilerReport {
+    #[serde(rename = "SPAudioDataType", default)]
+    audio: Vec<ProfilerSection>,
+}
+
+#[cfg(target_os = "macos")]
+#[derive(Debug, Deserialize)]
+struct ProfilerSection {
+    #[serde(rename = "_items", default)]
+    items: Vec<ProfilerDevice>,
+}
+
+#[cfg(target_os = "macos")]
+#[derive(Debug, Deserialize)]
+struct ProfilerDevice {
+    #[serde(rename = "_name")]
+    name: String,
+    #[serde(default)]
+    coreaudio_device_output: Option<u32>,
+    #[serde(default)]
+    coreaudio_device_transport: Option<String>,
+    #[serde(default)]
+    coreaudio_default_audio_output_device: Option<String>,
+}
+
+#[cfg(target_os = "macos")]
+async fn query_devices() -> Result<Vec<AudioOutputDevice>, String> {
+    let output = tokio::process::Command::new("system_profiler")
+        .args(["SPAudioDataType", "-json"])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to list audio devices: {}", e))?;
+    let report: ProfilerReport = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Unreadable audio device list: {}", e))?;
+
+    Ok(report
+        .audio
+        .into_iter()
+        .flat_map(|section| section.items)
+        .filter(|d| {
+            d.coreaudio_device_output
+                .is_some_and(|channels| channels > 0)
+        })
+        .map(|d| AudioOutputDevice {
+            id: d.name.clone(),
+            bluetooth: d
+                .coreaudio_device_transport
+                .as_deref()
+                .is_some_and(|t| t.contains("bluetooth")),
+            is_default: d.coreaudio_default_audio_output_device.as_deref() == Some("spaudio_yes"),
+            name: d.name,
+        })
+        .collect())
+}
+
+#[cfg(target_os = "windows")]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Endpoint {
+    friendly_name: String,
+    instance_id: String,
+}
+
+#[cfg(target_os = "windows")]
+async fn query_devices() -> Result<Vec<AudioOutputDevice>, String> {
+    // Render endpoints only; capture endpoints are {0.0.1.*}
+    const SCRIPT: &str = r"@(Get-PnpDevice -Class AudioEndpoint -PresentOnly | Where-Object { $_.Status -eq 'OK' -and $_.InstanceId -like 'SWD\MMDEVAPI\{0.0.0.*' } | Select-Object FriendlyName, InstanceId) | ConvertTo-Json -Compress";
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let output = tokio::process::Command::new("powershell.exe")
+        .args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to list audio devices: {}", e))?;
+
+    let text = String::from_utf8_lossy(&output.stdout);
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(Vec::new());
+    }
+    // A single device comes back as an object rather than an array
+    let endpoints: Vec<Endpoint> = if text.starts_with('[') {
+        serde_json::from_str(text)
+    } else {
+        serde_json::from_str(text).map(|e| vec![e])
+    }
+    .map_err(|e| format!("Unreadable audio device list: {}", e))?;
+
+    Ok(endpoints
+        .into_iter()
+        .map(|e| AudioOutputDevice {
+            bluetooth: e.friendly_name.contains("Hands-Free")
+                || e.friendly_name.contains("Bluetooth"),
+            id: e.instance_id,
+            name: e.friendly_name,
+            is_default: false,
+        })
+        .collect())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+async fn query_devices() -> Result<Vec<AudioOutputDevice>, String> {
+    Ok(Vec::new())
+}
+
+/// The chosen device when it's connected; audio cues stay silent otherwise
+pub fn cue_output_device() -> Option<AudioOutputDevice> {
+    AUDIO_OUTPUT.read().connected().cloned()
+}
+
+pub fn load_audio_output_from_store(app: &AppHandle) {
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Some(id) = store
+            .get(AUDIO_OUTPUT_KEY)
+            .and_then(|v| serde_json::from_value::<String>(v).ok())
+        {
+            AUDIO_OUTPUT.write().selected = Some(id);
+        }
+    }
+}
+
+/// Record a fresh device list, emitting hot-plug events when it changed
+fn update_devices(devices: Vec<AudioOutputDevice>) {
+    let Some(lost) = AUDIO_OUTPUT.write().replace_devices(devices.clone()) else {
+        return;
+    };
+
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        let _ = app.emit("audio-devices-changed", devices);
+        if let Some(id) = lost {
+            let _ = app.emit("audio-output-disconnected", id);
+        }
+    }
+}
+
+#[cfg(all(any(target_os = "macos", target_os = "windows"), feature = "desktop"))]
+fn on_devices_changed() {
+    // Kept until the watcher waits again, so a change while it's listing
+    // devices isn't lost
+    DEVICES_CHANGED.notify_one();
+}
+
+/// Register for the OS's device change notifications, where it sends them
+#[cfg(all(target_os = "macos", feature = "desktop"))]
+fn listen() -> bool {
+    coreaudio::listen()
+}
+
+#[cfg(all(target_os = "windows", feature = "desktop"))]
+fn listen() -> bool {
+    win32::listen()
+}
+
+#[cfg(not(all(any(target_os = "macos", target_os = "windows"), feature = "desktop")))]
+fn listen() -> bool {
+    false
+}
+
+/// Refresh the device list on every change; runs for the lifetime of the app
+pub async fn run_device_watcher() {
+    let notified = listen();
+    loop {
+        match query_devices().await {
+            Ok(devices) => update_devices(devices),
+            Err(e) => eprintln!("{}", e),
+        }
+        if notified {
+            DEVICES_CHANGED.notified().await;
+            tokio::time::sleep(Duration::from_millis(DEVICE_SETTLE_MS)).await;
+        } else {
+            tokio::time::sleep(Duration::from_secs(DEVICE_POLL_SECS)).await;
+        }
+    }
+}
+
+#[cfg(all(target_os = "macos", feature = "desktop"))]
+mod coreaudio {
+    use std::ffi::c_void;
+
+    const SYSTEM_OBJECT: u32 = 1;
+    // Four-character codes: 'dev#', 'dOut' and 'glob'
+    const HARDWARE_DEVICES: u32 = 0x6465_7623;
+    const DEFAULT_OUTPUT_DEVICE: u32 = 0x644F_7574;
+    const SCOPE_GLOBAL: u32 = 0x676C_6F62;
+    const ELEMENT_MAIN: u32 = 0;
+
+    #[repr(C)]
+    struct PropertyAddress {
+        selector: u32,
+        scope: u32,
+        element: u32,
+    }
+
+    type Listener = extern "C" fn(u32, u32, *const PropertyAddress, *mut c_void) -> i32;
+
+    #[link(name = "CoreAudio", kind = "framework")]
+    extern "C" {
+        fn AudioObjectAddPropertyListener(
+            object: u32,
+            address: *const PropertyAddress,
+            listener: Listener,
+            client_data: *mut c_void,
+        ) -> i32;
+    }
+
+    // Called on a CoreAudio thread
+    extern "C" fn on_change(
+        _object: u32,
+        _count: u32,
+        _addresses: *const PropertyAddress,
+        _client_data: *mut c_void,
+    ) -> i32 {
+        super::on_devices_changed();
+        0
+    }
+
+    pub fn listen() -> bool {
+        [HARDWARE_DEVICES, DEFAULT_OUTPUT_DEVICE]
+            .into_iter()
+            .all(|selector| {
+                let address = PropertyAddress {
+                    selector,
+                    scope: SCOPE_GLOBAL,
+                    element: ELEMENT_MAIN,
+                };
+                let status = unsafe {
+                    AudioObjectAddPropertyListener(
+                        SYSTEM_OBJECT,
+                        &address,
+                        on_change,
+                        std::ptr::null_mut(),
+                    )
+                };
+                if status != 0 {
+                    eprintln!("Failed to listen for audio device changes: {}", status);
+                }
+                status == 0
+            })
+    }
+}
+
+#[cfg(all(target_os = "windows", feature = "desktop"))]
+mod win32 {
+    use windows::core::{implement, Result, PCWSTR};
+    use windows::Win32::Media::Audio::{
+        EDataFlow, ERole, IMMDeviceEnumerator, IMMNotificationClient, IMMNotificationClient_Impl,
+        MMDeviceEnumerator, DEVICE_STATE,
+    };
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_MULTITHREADED,
+    };
+    use windows::Win32::UI::Shell::PropertiesSystem::PROPERTYKEY;
+
+    #[implement(IMMNotificationClient)]
+    struct DeviceListener;
+
+    // Called on a COM thread
+    impl IMMNotificationClient_Impl for DeviceListener_Impl {
+        fn OnDeviceStateChanged(&self, _id: &PCWSTR, _state: DEVICE_STATE) -> Result<()> {
+            super::on_devices_changed();
+            Ok(())
+        }
+
+        fn OnDeviceAdded(&self, _id: &PCWSTR) -> Result<()> {
+            super::on_devices_changed();
+            Ok(())
+        }
+
+        fn OnDeviceRemoved(&self, _id: &PCWSTR) -> Result<()> {
+            super::on_devices_changed();
+            Ok(())
+        }
+
+        fn OnDefaultDeviceChanged(
+            &self,
+            _flow: EDataFlow,
+            _role: ERole,
+            _id: &PCWSTR,
+        ) -> Result<()> {
+            super::on_devices_changed();
+            Ok(())
+        }
+
+        // Names and formats; the list is the same
+        fn OnPropertyValueChanged(&self, _id: &PCWSTR, _key: &PROPERTYKEY) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    pub fn listen() -> bool {
+        let registered = unsafe {
+            // The process joins the multithreaded apartment, so the enumerator
+            // can be used from this thread
+            let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+            CoCreateInstance::<_, IMMDeviceEnumerator>(&MMDeviceEnumerator, None, CLSCTX_ALL)
+                .and_then(|enumerator| {
+                    let client: IMMNotificationClient = DeviceListener.into();
+                    enumerator.RegisterEndpointNotificationCallback(&client)?;
+                    // Registered for the life of the app
+                    std::mem::forget(client);
+                    std::mem::forget(enumerator);
+                    Ok(())
+                })
+        };
+        if let Err(e) = &registered {
+            eprintln!("Failed to listen for audio device changes: {}", e);
+        }
+        registered.is_ok()
+    }
+}
+
+#[tauri::command]
+pub async fn list_audio_output_devices() -> Result<Vec<AudioOutputDevice>, CueCardError> {
+    let devices = query_devices().await?;
+    update_devices(devices.clone());
+    Ok(devices)
+}
+
+/// Choose where prompts play; `None` turns audio cues off
+#[tauri::command]
+pub fn select_audio_output(app: AppHandle, device_id: Option<String>) {
+    AUDIO_OUTPUT.write().selected = device_id.clone();
+    if let Ok(store) = app.store("cuecard-store.json") {
+        match device_id {
+            Some(id) => store.set(AUDIO_OUTPUT_KEY, id),
+            None => {
+                store.delete(AUDIO_OUTPUT_KEY);
+            }
+        }
+        let _ = store.save();
+    }
+}
+
+#[tauri::command]
+pub fn get_audio_output() -> AudioOutputStatus {
+    let selected = AUDIO_OUTPUT.read().selected.clone();
+    AudioOutputStatus {
+        connected: cue_output_device().is_some(),
+        selected,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(id: &str) -> AudioOutputDevice {
+        AudioOutputDevice {
+            id: id.to_string(),
+            name: id.to_string(),
+            bluetooth: id == "earpiece",
+            is_default: false,
+        }
+    }
+
+    #[test]
+    fn reports_the_chosen_device_going_away() {
+        let mut state = AudioOutputState {
+            selected: Some("earpiece".to_string()),
+            devices: Vec::new(),
+        };
+        // Not connected yet, so there's nothing to lose
+        assert_eq!(state.replace_devices(vec![device("speakers")]), Some(None));
+        assert!(state.connected().is_none());
+
+        let both = vec![device("speakers"), device("earpiece")];
+        assert_eq!(state.replace_devices(both.clone()), Some(None));
+        assert_eq!(state.connected(), Some(&device("earpiece")));
+        assert_eq!(state.replace_devices(both), None);
+
+        assert_eq!(
+            state.replace_devices(vec![device("speakers")]),
+            Some(Some("earpiece".to_string()))
+        );
+        // No fallback to another device
+        assert!(state.connected().is_none());
+    }
+}
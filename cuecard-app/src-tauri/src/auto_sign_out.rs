@@ -0,0 +1,331 @@
+//! Signing out on a schedule, for shared podium laptops
+//!
+//! A machine left signed in at a lectern hands the next speaker the last
+//! one's Google account. When set, the app signs out after `idle_hours`
+//! without a slide change or the panel being focused, or at a clock time
+//! each day (`at`, in the event's time zone from `event_time`), whichever
+//! comes first. Signing out drops the tokens, the notes held in memory and
+//! the decks cached on disk. `auto-sign-out-warning` is sent
+//! `WARNING_SECS` ahead, so the speaker can `keep_signed_in` through an idle
+//! deadline, and `auto-signed-out` once it's done. Both settings can be made
+//! in the UI or, on machines IT manages, in `cuecard.toml`'s
+//! `[auto_sign_out]` section, which takes precedence.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_store::StoreExt;
+
+use crate::error::CueCardError;
+use crate::{
+    clock, config_file, disk_cache, event_time, notes_summary, notes_translation, public_export,
+    rich_notes, sleep_wake, APP_HANDLE, CANVA_TOKENS, CURRENT_PRESENTATION_ID, FIREBASE_TOKENS,
+    SLIDES_TOKENS, SLIDE_NOTES,
+};
+
+const AUTO_SIGN_OUT_KEY: &str = "auto_sign_out";
+const CHECK_INTERVAL_SECS: u64 = 30;
+const WARNING_SECS: i64 = 300;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AutoSignOutSettings {
+    /// Hours without activity before signing out
+    pub idle_hours: Option<u32>,
+    /// Time of day to sign out, "HH:MM"
+    pub at: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignOutReason {
+    Idle,
+    Scheduled,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AutoSignOutStatus {
+    /// The settings in effect, after `cuecard.toml`
+    pub settings: AutoSignOutSettings,
+    /// Whether `cuecard.toml` sets either, so the UI can't change it
+    pub managed: bool,
+    /// Unix seconds of the next sign-out while signed in
+    pub next_sign_out_at: Option<i64>,
+    pub reason: Option<SignOutReason>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct AutoSignOutEvent {
+    reason: SignOutReason,
+    sign_out_at: i64,
+}
+
+#[derive(Debug, Clone)]
+struct Scheduled {
+    at: String,
+    instant: i64,
+}
+
+static SETTINGS: Lazy<Arc<RwLock<AutoSignOutSettings>>> =
+    Lazy::new(|| Arc::new(RwLock::new(AutoSignOutSettings::default())));
+// Unix seconds of the last slide change or focus
+static LAST_ACTIVITY: Lazy<Arc<RwLock<i64>>> =
+    Lazy::new(|| Arc::new(RwLock::new(clock::now_secs())));
+// The `at` setting as resolved, until that instant passes
+static SCHEDULED: Lazy<Arc<RwLock<Option<Scheduled>>>> = Lazy::new(|| Arc::new(RwLock::new(None)));
+// The deadline a warning was sent for
+static WARNED: Lazy<Arc<RwLock<Option<i64>>>> = Lazy::new(|| Arc::new(RwLock::new(None)));
+
+pub fn load_auto_sign_out_from_store(app: &AppHandle) {
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Some(settings) = store
+            .get(AUTO_SIGN_OUT_KEY)
+            .and_then(|v| serde_json::from_value::<AutoSignOutSettings>(v).ok())
+        {
+            *SETTINGS.write() = settings;
+        }
+    }
+}
+
+/// The speaker did something, restarting the idle countdown
+pub fn record_activity() {
+    *LAST_ACTIVITY.write() = clock::now_secs();
+}
+
+/// Settings in effect, and whether the config file set any of them
+fn effective() -> (AutoSignOutSettings, bool) {
+    let file = config_file::auto_sign_out();
+    let managed = file.idle_hours.is_some() || file.at.is_some();
+    let ui = SETTINGS.read().clone();
+    let settings = AutoSignOutSettings {
+        idle_hours: file.idle_hours.or(ui.idle_hours).filter(|h| *h > 0),
+        at: file.at.or(ui.at),
+    };
+    (settings, managed)
+}
+
+/// The next time `at` comes around, kept until it has passed
+fn scheduled_at(at: &str) -> Option<i64> {
+    let mut scheduled = SCHEDULED.write();
+    if let Some(current) = scheduled.as_ref().filter(|s| s.at == at) {
+        return Some(current.instant);
+    }
+    let instant = event_time::resolve_local_time(at, None).ok()?;
+    *scheduled = Some(Scheduled {
+        at: at.to_string(),
+        instant,
+    });
+    Some(instant)
+}
+
+fn next_sign_out() -> Option<(i64, SignOutReason)> {
+    let (settings, _) = effective();
+    let idle = settings
+        .idle_hours
+        .map(|h| (*LAST_ACTIVITY.read() + h as i64 * 3600, SignOutReason::Idle));
+    let scheduled = settings
+        .at
+        .as_deref()
+        .and_then(scheduled_at)
+        .map(|at| (at, SignOutReason::Scheduled));
+    match (idle, scheduled) {
+        (Some(a), Some(b)) => Some(if b.0 <= a.0 { b } else { a }),
+        (a, b) => a.or(b),
+    }
+}
+
+fn is_signed_in() -> bool {
+    FIREBASE_TOKENS.read().is_some()
+        || SLIDES_TOKENS.read().is_some()
+        || CANVA_TOKENS.read().is_some()
+}
+
+/// Sign out and drop every deck's notes, in memory and on disk
+fn sign_out(app: &AppHandle, reason: SignOutReason, sign_out_at: i64) {
+    eprintln!("Signing out automatically ({:?})", reason);
+    crate::logout(app.clone());
+    *CURRENT_PRESENTATION_ID.write() = None;
+    SLIDE_NOTES.write().clear();
+    rich_notes::clear();
+    notes_summary::clear();
+    notes_translation::clear();
+    public_export::clear();
+    if let Err(e) = disk_cache::clear() {
+        eprintln!("Failed to clear the notes cache: {}", e);
+    }
+
+    let _ = app.emit(
+        "auth-status",
+        serde_json::json!({
+            "authenticated": false,
+            "user_name": null
+        }),
+    );
+    let _ = app.emit(
+        "auto-signed-out",
+        AutoSignOutEvent {
+            reason,
+            sign_out_at,
+        },
+    );
+}
+
+fn check(app: &AppHandle) {
+    if !is_signed_in() {
+        // Idle time starts counting from the next sign-in
+        record_activity();
+    }
+    let Some((deadline, reason)) = next_sign_out() else {
+        return;
+    };
+    let now = clock::now_secs();
+
+    if now >= deadline {
+        if is_signed_in() {
+            sign_out(app, reason, deadline);
+        }
+        if reason == SignOutReason::Scheduled {
+            // Resolved again to the next day on the following check
+            *SCHEDULED.write() = None;
+        }
+        record_activity();
+        return;
+    }
+
+    if is_signed_in() && deadline - now <= WARNING_SECS {
+        let mut warned = WARNED.write();
+        if *warned != Some(deadline) {
+            *warned = Some(deadline);
+            let _ = app.emit(
+                "auto-sign-out-warning",
+                AutoSignOutEvent {
+                    reason,
+                    sign_out_at: deadline,
+                },
+            );
+        }
+    }
+}
+
+/// Sign out when the idle or daily deadline passes, for the life of the app
+pub async fn run_scheduler() {
+    loop {
+        tokio::time::sleep(Duration::from_secs(CHECK_INTERVAL_SECS)).await;
+        // A deadline passed during sleep is handled on the first turn after
+        if sleep_wake::is_paused() {
+            continue;
+        }
+        let Some(app) = APP_HANDLE.read().clone() else {
+            continue;
+        };
+        check(&app);
+    }
+}
+
+fn status() -> AutoSignOutStatus {
+    let (settings, managed) = effective();
+    let next = next_sign_out().filter(|_| is_signed_in());
+    AutoSignOutStatus {
+        settings,
+        managed,
+        next_sign_out_at: next.map(|(at, _)| at),
+        reason: next.map(|(_, reason)| reason),
+    }
+}
+
+#[tauri::command]
+pub fn get_auto_sign_out() -> AutoSignOutStatus {
+    status()
+}
+
+/// Set when to sign out automatically; `None` for either turns it off
+#[tauri::command]
+pub fn set_auto_sign_out(
+    app: AppHandle,
+    settings: AutoSignOutSettings,
+) -> Result<AutoSignOutStatus, CueCardError> {
+    if let Some(at) = settings.at.as_deref() {
+        event_time::resolve_local_time(at, None)?;
+    }
+    let settings = AutoSignOutSettings {
+        idle_hours: settings.idle_hours.filter(|h| *h > 0),
+        at: settings.at.map(|at| at.trim().to_string()),
+    };
+
+    *SETTINGS.write() = settings.clone();
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Ok(json) = serde_json::to_value(&settings) {
+            store.set(AUTO_SIGN_OUT_KEY, json);
+            let _ = store.save();
+        }
+    }
+    Ok(status())
+}
+
+/// Answer an idle warning, starting the idle countdown again
+#[tauri::command]
+pub fn keep_signed_in() -> AutoSignOutStatus {
+    record_activity();
+    status()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::tests::with_mock;
+
+    fn set(idle_hours: Option<u32>, scheduled: Option<i64>) {
+        *SETTINGS.write() = AutoSignOutSettings {
+            idle_hours,
+            at: scheduled.map(|_| "18:00".to_string()),
+        };
+        *SCHEDULED.write() = scheduled.map(|instant| Scheduled {
+            at: "18:00".to_string(),
+            instant,
+        });
+    }
+
+    #[test]
+    fn signs_out_at_whichever_deadline_comes_first() {
+        let now = 1_700_000_000;
+        with_mock(now, |clock| {
+            set(Some(2), None);
+            record_activity();
+            assert_eq!(next_sign_out(), Some((now + 7200, SignOutReason::Idle)));
+
+            set(Some(2), Some(now + 3600));
+            assert_eq!(
+                next_sign_out(),
+                Some((now + 3600, SignOutReason::Scheduled))
+            );
+
+            // Activity moves only the idle deadline
+            clock.advance_millis(1_800_000);
+            record_activity();
+            set(Some(2), Some(now + 86_400));
+            assert_eq!(
+                next_sign_out(),
+                Some((now + 1800 + 7200, SignOutReason::Idle))
+            );
+
+            set(None, Some(now + 86_400));
+            assert_eq!(
+                next_sign_out(),
+                Some((now + 86_400, SignOutReason::Scheduled))
+            );
+            set(None, None);
+        });
+    }
+
+    #[test]
+    fn zero_idle_hours_is_off() {
+        with_mock(1_700_000_000, |_| {
+            set(Some(0), None);
+            assert_eq!(next_sign_out(), None);
+            assert_eq!(effective().0.idle_hours, None);
+            set(None, None);
+        });
+    }
+}
@@ -0,0 +1,624 @@
+//! Canva presentations, through the Canva Connect API
+//!
+//! This is the app side only: slide updates posted to `/slides` with
+//! `mode: "canva"`, the design id as the presentation and the page number as
+//! the slide, are handled here, but the browser extension doesn't detect
+//! Canva presenter tabs yet, so nothing sends them. The Connect API has no endpoint for presenter notes, but a design exported as
+//! `.pptx` carries them, so the deck's notes are read from an export with the
+//! same reader as local `.pptx` files. An export takes a few seconds: it's
+//! started in the background when a design is first shown (or a refresh is
+//! forced) and its notes replace the cache when it's ready.
+//!
+//! Canva has its own OAuth grant, kept in `CANVA_TOKENS` next to the Slides
+//! tokens. Sign-in uses PKCE. Canva only redirects to the exact URL
+//! registered for the app, so the callback is served on `CALLBACK_PORT`,
+//! bound for the duration of a sign-in, rather than on the local server's
+//! port, which can change. Refresh tokens are single use, so every refresh
+//! stores the new one.
+
+use axum::extract::Query;
+use axum::response::Html;
+use base64::Engine;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_opener::OpenerExt;
+use tauri_plugin_store::StoreExt;
+use uuid::Uuid;
+
+use crate::error::CueCardError;
+use crate::{http_client, CanvaTokens, OAuthCredentials, SlideData, APP_HANDLE, CANVA_TOKENS};
+
+/// Slide mode of Canva presenter tabs
+pub const MODE: &str = "canva";
+
+const CANVA_AUTH_URL: &str = "https://www.canva.com/api/oauth/authorize";
+const CANVA_TOKEN_URL: &str = "https://api.canva.com/rest/v1/oauth/token";
+const CANVA_API_URL: &str = "https://api.canva.com/rest/v1";
+const SCOPE_DESIGN_CONTENT: &str = "design:content:read";
+const CANVA_TOKENS_KEY: &str = "canva_tokens";
+
+// Registered with Canva as http://127.0.0.1:3661/oauth/canva/callback; clear
+// of the local server's and the share link's ports
+const CALLBACK_PORT: u16 = 3661;
+const CALLBACK_PATH: &str = "/oauth/canva/callback";
+const LOGIN_TIMEOUT_SECS: u64 = 600;
+
+const EXPORT_POLL_MS: u64 = 1000;
+const EXPORT_MAX_POLLS: u32 = 60;
+
+/// PKCE verifier and state of the sign-in in progress
+struct PendingLogin {
+    verifier: String,
+    state: String,
+    credentials: OAuthCredentials,
+}
+
+static PENDING_LOGIN: Lazy<Arc<RwLock<Option<PendingLogin>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(None)));
+// Stops the callback server of the sign-in in progress
+static CALLBACK_SERVER: Lazy<Arc<RwLock<Option<tokio::sync::oneshot::Sender<()>>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(None)));
+// Design whose notes were last exported, or are being exported
+static LOADED_DESIGN: Lazy<Arc<RwLock<Option<String>>>> = Lazy::new(|| Arc::new(RwLock::new(None)));
+
+#[derive(Debug, Deserialize)]
+pub struct CanvaCallback {
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CanvaTokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: Option<i64>,
+}
+
+fn presentation_id(design_id: &str) -> String {
+    format!("{}:{}", MODE, design_id)
+}
+
+fn redirect_uri() -> String {
+    format!("http://127.0.0.1:{}{}", CALLBACK_PORT, CALLBACK_PATH)
+}
+
+fn stop_callback_server() {
+    if let Some(stop) = CALLBACK_SERVER.write().take() {
+        let _ = stop.send(());
+    }
+}
+
+/// Serve the sign-in callback on the registered port until it's been
+/// answered, a new sign-in starts or `LOGIN_TIMEOUT_SECS` pass
+async fn start_callback_server() -> Result<(), String> {
+    stop_callback_server();
+    // The previous server lets go of the port once it sees the stop
+    let mut listener = None;
+    for _ in 0..10 {
+        match tokio::net::TcpListener::bind(("127.0.0.1", CALLBACK_PORT)).await {
+            Ok(bound) => {
+                listener = Some(bound);
+                break;
+            }
+            Err(_) => tokio::time::sleep(Duration::from_millis(100)).await,
+        }
+    }
+    let listener = listener.ok_or(format!(
+        "Port {} is in use, so Canva can't return to CueCard",
+        CALLBACK_PORT
+    ))?;
+
+    let app = axum::Router::new().route(CALLBACK_PATH, axum::routing::get(oauth_callback_handler));
+    let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+    *CALLBACK_SERVER.write() = Some(stop);
+    tauri::async_runtime::spawn(async move {
+        let shutdown = async move {
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(LOGIN_TIMEOUT_SECS)) => {}
+                _ = stopped => {}
+            }
+        };
+        if let Err(e) = axum::serve(listener, app)
+            .with_graceful_shutdown(shutdown)
+            .await
+        {
+            eprintln!("Canva callback server error: {}", e);
+        }
+    });
+    Ok(())
+}
+
+/// S256 code challenge for a PKCE verifier
+fn code_challenge(verifier: &str) -> String {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+fn basic_auth(credentials: &OAuthCredentials) -> String {
+    let pair = format!("{}:{}", credentials.client_id, credentials.client_secret);
+    format!(
+        "Basic {}",
+        base64::engine::general_purpose::STANDARD.encode(pair)
+    )
+}
+
+fn save_tokens_to_store(app: &AppHandle) {
+    if let Ok(store) = app.store("cuecard-store.json") {
+        let tokens = CANVA_TOKENS.read();
+        if let Some(ref t) = *tokens {
+            if let Ok(json) = serde_json::to_value(t) {
+                store.set(CANVA_TOKENS_KEY, json);
+                let _ = store.save();
+            }
+        }
+    }
+}
+
+fn store_token_response(response: CanvaTokenResponse) {
+    let expires_at = response
+        .expires_in
+        .map(|secs| chrono::Utc::now().timestamp() + secs);
+    {
+        let mut tokens = CANVA_TOKENS.write();
+        let refresh_token = response
+            .refresh_token
+            .or_else(|| tokens.as_ref().and_then(|t| t.refresh_token.clone()));
+        *tokens = Some(CanvaTokens {
+            access_token: response.access_token,
+            refresh_token,
+            expires_at,
+        });
+    }
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        save_tokens_to_store(app);
+    }
+}
+
+async fn request_tokens(
+    credentials: &OAuthCredentials,
+    form: &[(&str, &str)],
+) -> Result<CanvaTokenResponse, String> {
+    let response = http_client::client()
+        .post(CANVA_TOKEN_URL)
+        .header("Authorization", basic_auth(credentials))
+        .form(form)
+        .send()
+        .await
+        .map_err(|e| format!("Canva token request failed: {}", e))?;
+
+    if !response.status().is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        let revoked = serde_json::from_str::<serde_json::Value>(&error_text)
+            .ok()
+            .and_then(|v| v.get("error")?.as_str().map(|e| e == "invalid_grant"))
+            .unwrap_or(false);
+        if revoked {
+            clear_revoked_tokens();
+        }
+        return Err(format!("Canva token request failed: {}", error_text));
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse Canva token response: {}", e))
+}
+
+/// The Canva grant was revoked or expired for good: drop the tokens and ask
+/// the frontend to have the user connect Canva again
+fn clear_revoked_tokens() {
+    *CANVA_TOKENS.write() = None;
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        if let Ok(store) = app.store("cuecard-store.json") {
+            store.delete(CANVA_TOKENS_KEY);
+            let _ = store.save();
+        }
+        let _ = app.emit("canva-auth-required", "invalid_grant");
+    }
+}
+
+async fn refresh_token() -> Result<(), String> {
+    let refresh_token = CANVA_TOKENS
+        .read()
+        .as_ref()
+        .and_then(|t| t.refresh_token.clone())
+        .ok_or("No Canva refresh token available")?;
+    let credentials = crate::remote_config::canva_credentials().await?;
+
+    let response = request_tokens(
+        &credentials,
+        &[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token.as_str()),
+        ],
+    )
+    .await?;
+    store_token_response(response);
+    Ok(())
+}
+
+/// Get a valid Canva access token (refreshes if needed)
+async fn get_valid_token() -> Option<String> {
+    let (access_token, expires_at, has_refresh) = {
+        let tokens = CANVA_TOKENS.read();
+        let t = tokens.as_ref()?;
+        (
+            t.access_token.clone(),
+            t.expires_at,
+            t.refresh_token.is_some(),
+        )
+    };
+
+    // Same 5 minute margin as the Slides token
+    let now = chrono::Utc::now().timestamp();
+    let is_expired = expires_at.map(|exp| now >= exp - 300).unwrap_or(false);
+
+    if is_expired && has_refresh {
+        if let Err(e) = refresh_token().await {
+            eprintln!("Failed to refresh Canva token: {}", e);
+            return None;
+        }
+        return CANVA_TOKENS.read().as_ref().map(|t| t.access_token.clone());
+    }
+
+    Some(access_token)
+}
+
+/// Export a design as `.pptx` and read its notes as (page number, notes) pairs
+async fn fetch_design_notes(design_id: &str) -> Result<Vec<(String, String)>, String> {
+    let token = get_valid_token().await.ok_or("Canva isn't connected")?;
+    let client = http_client::client();
+
+    let response = client
+        .post(format!("{}/exports", CANVA_API_URL))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({
+            "design_id": design_id,
+            "format": { "type": "pptx" }
+        }))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to start Canva export: {}", e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!(
+            "Failed to start Canva export: {} - {}",
+            status, error_text
+        ));
+    }
+    let job: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse Canva export: {}", e))?;
+    let export_id = job
+        .pointer("/job/id")
+        .and_then(|v| v.as_str())
+        .ok_or("No export id in Canva response")?
+        .to_string();
+
+    let mut url = None;
+    for _ in 0..EXPORT_MAX_POLLS {
+        tokio::time::sleep(Duration::from_millis(EXPORT_POLL_MS)).await;
+        let job: serde_json::Value = client
+            .get(format!("{}/exports/{}", CANVA_API_URL, export_id))
+            .bearer_auth(&token)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to check Canva export: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse Canva export: {}", e))?;
+
+        url = export_url(&job)?;
+        if url.is_some() {
+            break;
+        }
+    }
+    let url = url.ok_or("Canva export didn't finish in time")?;
+
+    // The download link is pre-signed and takes no token
+    let bytes = client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to download Canva export: {}", e))?
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to download Canva export: {}", e))?;
+
+    design_notes(&bytes)
+}
+
+/// The download link of a finished export job, or None while it's running
+fn export_url(job: &serde_json::Value) -> Result<Option<String>, String> {
+    match job.pointer("/job/status").and_then(|v| v.as_str()) {
+        Some("success") => job
+            .pointer("/job/urls/0")
+            .and_then(|v| v.as_str())
+            .map(|u| Some(u.to_string()))
+            .ok_or_else(|| "No download link in Canva export".to_string()),
+        Some("failed") => {
+            let message = job
+                .pointer("/job/error/message")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown error");
+            Err(format!("Canva export failed: {}", message))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// (page number, notes) pairs of an exported `.pptx`
+fn design_notes(bytes: &[u8]) -> Result<Vec<(String, String)>, String> {
+    let slides = crate::providers::pptx::read_pptx_bytes(bytes)?;
+    Ok(slides
+        .into_iter()
+        .map(|slide| (slide.number.to_string(), slide.notes))
+        .collect())
+}
+
+/// Export the design's notes into the cache, if it's still the one shown
+async fn load_design(design_id: String) {
+    match fetch_design_notes(&design_id).await {
+        Ok(notes) => {
+            crate::providers::update_deck_notes(&presentation_id(&design_id), notes);
+        }
+        Err(e) => {
+            eprintln!("Failed to load Canva notes: {}", e);
+            // Let the next slide change try again
+            let mut loaded = LOADED_DESIGN.write();
+            if loaded.as_deref() == Some(design_id.as_str()) {
+                *loaded = None;
+            }
+        }
+    }
+}
+
+/// A slide change from a Canva tab: pages are keyed by number, and the
+/// design's notes are exported when it's first shown or a refresh is forced
+pub async fn on_slide_update(mut slide_data: SlideData) -> Option<String> {
+    let design_id = slide_data
+        .presentation_id
+        .strip_prefix("canva:")
+        .unwrap_or(&slide_data.presentation_id)
+        .to_string();
+    slide_data.presentation_id = presentation_id(&design_id);
+    slide_data.slide_id = slide_data.slide_number.to_string();
+
+    let needs_export = {
+        let mut loaded = LOADED_DESIGN.write();
+        let stale = loaded.as_deref() != Some(design_id.as_str());
+        if stale || slide_data.force_refresh.unwrap_or(false) {
+            *loaded = Some(design_id.clone());
+            true
+        } else {
+            false
+        }
+    };
+
+    let notes = crate::apply_slide_update(slide_data, None).await;
+
+    // After the slide is applied, so a new design's cache is cleared first
+    if needs_export {
+        if CANVA_TOKENS.read().is_none() {
+            if let Some(app) = APP_HANDLE.read().as_ref() {
+                let _ = app.emit("canva-auth-required", "missing");
+            }
+            *LOADED_DESIGN.write() = None;
+        } else {
+            tauri::async_runtime::spawn(load_design(design_id));
+        }
+    }
+    notes
+}
+
+/// Canva's redirect back to CueCard after sign-in
+async fn oauth_callback_handler(Query(params): Query<CanvaCallback>) -> Html<String> {
+    let result = match params.error {
+        Some(error) => Err(error),
+        None => finish_login(params.code, params.state).await,
+    };
+    // The page is still sent; graceful shutdown waits for it
+    stop_callback_server();
+
+    match result {
+        Ok(()) => Html(
+            r#"<!DOCTYPE html>
+            <html><head><title>Canva Connected</title>
+            <style>body { font-family: system-ui; padding: 40px; text-align: center; }</style>
+            </head><body>
+            <h1>Canva Connected</h1>
+            <p>CueCard can now read your Canva speaker notes. You can close this window.</p>
+            </body></html>"#
+                .to_string(),
+        ),
+        Err(e) => Html(format!(
+            r#"<!DOCTYPE html>
+            <html><head><title>Authentication Failed</title>
+            <style>body {{ font-family: system-ui; padding: 40px; text-align: center; }}</style>
+            </head><body>
+            <h1>Authentication Failed</h1>
+            <p>Error: {}</p>
+            <p>You can close this window.</p>
+            </body></html>"#,
+            crate::session_report::escape_html(&e)
+        )),
+    }
+}
+
+/// End the sign-in in progress if the callback carries its state.
+/// A mismatch still ends it, so a forged callback can't be retried.
+fn take_pending_login(state: Option<&str>) -> Result<PendingLogin, String> {
+    let pending = PENDING_LOGIN
+        .write()
+        .take()
+        .ok_or("No Canva sign-in in progress.")?;
+    if state != Some(pending.state.as_str()) {
+        return Err("Canva sign-in state didn't match.".to_string());
+    }
+    Ok(pending)
+}
+
+async fn finish_login(code: Option<String>, state: Option<String>) -> Result<(), String> {
+    let code = code.ok_or("No authorization code received.")?;
+    let pending = take_pending_login(state.as_deref())?;
+
+    let response = request_tokens(
+        &pending.credentials,
+        &[
+            ("grant_type", "authorization_code"),
+            ("code", code.as_str()),
+            ("code_verifier", pending.verifier.as_str()),
+            ("redirect_uri", redirect_uri().as_str()),
+        ],
+    )
+    .await?;
+    store_token_response(response);
+
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        let _ = app.emit(
+            "canva-auth-status",
+            serde_json::json!({ "authorized": true }),
+        );
+    }
+    // Export the design on screen now that it can be read
+    let current = crate::CURRENT_SLIDE.read().clone();
+    if let Some(slide) = current.filter(|s| s.mode == MODE) {
+        let design_id = slide
+            .presentation_id
+            .strip_prefix("canva:")
+            .unwrap_or(&slide.presentation_id)
+            .to_string();
+        *LOADED_DESIGN.write() = Some(design_id.clone());
+        tauri::async_runtime::spawn(load_design(design_id));
+    }
+    Ok(())
+}
+
+/// Open Canva's consent page to connect a Canva account
+#[tauri::command]
+pub async fn start_canva_login(app: AppHandle) -> Result<(), CueCardError> {
+    let credentials = crate::remote_config::canva_credentials().await?;
+    // Two v4 UUIDs give a 64 character verifier, within PKCE's 43-128
+    let verifier = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+    let state = Uuid::new_v4().simple().to_string();
+
+    let auth_url = format!(
+        "{}?code_challenge={}&code_challenge_method=s256&scope={}&response_type=code&client_id={}&state={}&redirect_uri={}",
+        CANVA_AUTH_URL,
+        code_challenge(&verifier),
+        urlencoding::encode(SCOPE_DESIGN_CONTENT),
+        urlencoding::encode(&credentials.client_id),
+        state,
+        urlencoding::encode(&redirect_uri())
+    );
+    start_callback_server().await?;
+    *PENDING_LOGIN.write() = Some(PendingLogin {
+        verifier,
+        state,
+        credentials,
+    });
+
+    app.opener()
+        .open_url(&auth_url, None::<&str>)
+        .map_err(|e| format!("Failed to open browser: {}", e))?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn is_canva_connected() -> bool {
+    CANVA_TOKENS.read().is_some()
+}
+
+/// Forget the Canva grant
+#[tauri::command]
+pub fn disconnect_canva(app: AppHandle) {
+    *CANVA_TOKENS.write() = None;
+    *LOADED_DESIGN.write() = None;
+    if let Ok(store) = app.store("cuecard-store.json") {
+        store.delete(CANVA_TOKENS_KEY);
+        let _ = store.save();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending(state: &str) {
+        *PENDING_LOGIN.write() = Some(PendingLogin {
+            verifier: "verifier".to_string(),
+            state: state.to_string(),
+            credentials: OAuthCredentials {
+                client_id: "id".to_string(),
+                client_secret: "secret".to_string(),
+            },
+        });
+    }
+
+    #[test]
+    fn reads_the_export_job_states() {
+        let running = serde_json::json!({ "job": { "id": "e1", "status": "in_progress" } });
+        assert_eq!(export_url(&running), Ok(None));
+
+        let done = serde_json::json!({
+            "job": { "id": "e1", "status": "success", "urls": ["https://export.canva.com/e1.pptx"] }
+        });
+        assert_eq!(
+            export_url(&done),
+            Ok(Some("https://export.canva.com/e1.pptx".to_string()))
+        );
+
+        let failed = serde_json::json!({
+            "job": { "id": "e1", "status": "failed", "error": { "message": "design too large" } }
+        });
+        assert_eq!(
+            export_url(&failed),
+            Err("Canva export failed: design too large".to_string())
+        );
+        let no_link = serde_json::json!({ "job": { "id": "e1", "status": "success", "urls": [] } });
+        assert!(export_url(&no_link).is_err());
+    }
+
+    #[test]
+    fn reads_notes_by_page_from_the_export() {
+        let bytes = crate::providers::pptx::tests::deck(&[
+            ("Intro", "Say hello", None),
+            ("Outro", "Thank everyone", None),
+        ]);
+        assert_eq!(
+            design_notes(&bytes).unwrap(),
+            vec![
+                ("1".to_string(), "Say hello".to_string()),
+                ("2".to_string(), "Thank everyone".to_string()),
+            ]
+        );
+        assert!(design_notes(b"<html>expired link</html>").is_err());
+    }
+
+    #[test]
+    fn callback_must_carry_the_sign_in_state() {
+        assert!(take_pending_login(Some("abc")).is_err());
+
+        pending("abc");
+        assert!(take_pending_login(Some("forged")).is_err());
+        // The mismatch ended the sign-in
+        assert!(take_pending_login(Some("abc")).is_err());
+
+        pending("abc");
+        assert!(take_pending_login(None).is_err());
+
+        pending("abc");
+        assert_eq!(
+            take_pending_login(Some("abc")).unwrap().verifier,
+            "verifier"
+        );
+        assert!(PENDING_LOGIN.read().is_none());
+    }
+}
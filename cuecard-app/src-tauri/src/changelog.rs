@@ -0,0 +1,222 @@
+//! What's new since the last version the user ran
+//!
+//! The version CueCard last ran as is kept in the store. When the app starts
+//! as a newer version, the bundled `changelog.json` entries for the versions
+//! in between are sent as `whats-new`, and kept for `get_whats_new` in case
+//! the panel wasn't listening yet. Entries tied to a feature are only shown
+//! to users who have it set up (e.g. Slides write access). A fresh install
+//! records its version and shows nothing; an install from before versions
+//! were recorded, told apart by the analytics first-open flag every version
+//! has set, counts as an update from `UNRECORDED_VERSION`.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_store::StoreExt;
+
+use crate::{canva, parse_version, SLIDES_TOKENS};
+
+const LAST_RUN_VERSION_KEY: &str = "last_run_version";
+const FIRST_OPEN_KEY: &str = "analytics_first_open_sent";
+// The last release that didn't record the version it ran as
+const UNRECORDED_VERSION: &str = "1.4.1";
+const CHANGELOG_JSON: &str = include_str!("changelog.json");
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangelogEntry {
+    pub title: String,
+    pub body: String,
+    /// Only relevant with this feature set up: "google_slides",
+    /// "slides_write", "drive" or "canva"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feature: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangelogRelease {
+    pub version: String,
+    pub entries: Vec<ChangelogEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WhatsNew {
+    pub previous_version: String,
+    pub current_version: String,
+    /// Newest release first
+    pub releases: Vec<ChangelogRelease>,
+}
+
+static WHATS_NEW: Lazy<Arc<RwLock<Option<WhatsNew>>>> = Lazy::new(|| Arc::new(RwLock::new(None)));
+
+fn feature_enabled(feature: &str) -> bool {
+    let has_scope = |scope: &str| {
+        SLIDES_TOKENS
+            .read()
+            .as_ref()
+            .is_some_and(|t| t.scopes.iter().any(|s| s == scope))
+    };
+    match feature {
+        "google_slides" => SLIDES_TOKENS.read().is_some(),
+        "slides_write" => has_scope(crate::SCOPE_SLIDES_WRITE),
+        "drive" => has_scope(crate::SCOPE_DRIVE),
+        "canva" => canva::is_canva_connected(),
+        _ => true,
+    }
+}
+
+/// Releases after `previous` up to `current`, with only the relevant entries
+fn releases_between(previous: (u64, u64, u64), current: (u64, u64, u64)) -> Vec<ChangelogRelease> {
+    let releases: Vec<ChangelogRelease> = match serde_json::from_str(CHANGELOG_JSON) {
+        Ok(releases) => releases,
+        Err(e) => {
+            eprintln!("Failed to parse bundled changelog: {}", e);
+            return Vec::new();
+        }
+    };
+    select_releases(releases, previous, current, feature_enabled)
+}
+
+/// Releases in `(previous, current]`, newest first, with the entries for
+/// features that aren't set up left out
+fn select_releases(
+    releases: Vec<ChangelogRelease>,
+    previous: (u64, u64, u64),
+    current: (u64, u64, u64),
+    enabled: impl Fn(&str) -> bool,
+) -> Vec<ChangelogRelease> {
+    let mut releases: Vec<ChangelogRelease> = releases
+        .into_iter()
+        .filter(|r| parse_version(&r.version).is_some_and(|v| v > previous && v <= current))
+        .map(|mut r| {
+            r.entries
+                .retain(|e| e.feature.as_deref().is_none_or(&enabled));
+            r
+        })
+        .filter(|r| !r.entries.is_empty())
+        .collect();
+    releases.sort_by_key(|r| std::cmp::Reverse(parse_version(&r.version)));
+    releases
+}
+
+/// The version to show changes since: the one recorded last run or, for an
+/// install from before versions were recorded, `UNRECORDED_VERSION`
+fn previous_version(recorded: Option<String>, opened_before: bool) -> Option<String> {
+    recorded.or_else(|| opened_before.then(|| UNRECORDED_VERSION.to_string()))
+}
+
+/// Compare the last run version with this one, record this one and send
+/// `whats-new` after an update. Runs once tokens are loaded, so entries can
+/// be matched to what the user has set up.
+pub fn check_for_update(app: &AppHandle) {
+    let Ok(store) = app.store("cuecard-store.json") else {
+        return;
+    };
+    let current_version = app.package_info().version.to_string();
+    let recorded_version = store
+        .get(LAST_RUN_VERSION_KEY)
+        .and_then(|v| v.as_str().map(str::to_string));
+    if recorded_version.as_deref() != Some(current_version.as_str()) {
+        store.set(LAST_RUN_VERSION_KEY, current_version.clone());
+        let _ = store.save();
+    }
+
+    let opened_before = store
+        .get(FIRST_OPEN_KEY)
+        .is_some_and(|v| v.as_bool().unwrap_or(false));
+    let Some(previous_version) = previous_version(recorded_version, opened_before) else {
+        return;
+    };
+    let (Some(previous), Some(current)) = (
+        parse_version(&previous_version),
+        parse_version(&current_version),
+    ) else {
+        return;
+    };
+    if current <= previous {
+        return;
+    }
+
+    let whats_new = WhatsNew {
+        previous_version,
+        current_version,
+        releases: releases_between(previous, current),
+    };
+    if whats_new.releases.is_empty() {
+        return;
+    }
+    let _ = app.emit("whats-new", &whats_new);
+    *WHATS_NEW.write() = Some(whats_new);
+}
+
+/// What's new in this launch's update, if it was one
+#[tauri::command]
+pub fn get_whats_new() -> Option<WhatsNew> {
+    WHATS_NEW.read().clone()
+}
+
+/// The user has seen it; stop offering it
+#[tauri::command]
+pub fn dismiss_whats_new() {
+    *WHATS_NEW.write() = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(version: &str, features: &[Option<&str>]) -> ChangelogRelease {
+        ChangelogRelease {
+            version: version.to_string(),
+            entries: features
+                .iter()
+                .map(|feature| ChangelogEntry {
+                    title: version.to_string(),
+                    body: String::new(),
+                    feature: feature.map(str::to_string),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn shows_releases_since_the_last_run_newest_first() {
+        let releases = vec![
+            release("1.4.0", &[None]),
+            release("1.5.0", &[None, Some("canva")]),
+            release("1.6.0", &[Some("canva")]),
+            release("1.7.0", &[None]),
+        ];
+        let shown = select_releases(releases, (1, 4, 0), (1, 6, 0), |f| f != "canva");
+        assert_eq!(shown.len(), 1);
+        assert_eq!(shown[0].version, "1.5.0");
+        assert_eq!(shown[0].entries.len(), 1);
+
+        let releases = vec![release("1.5.0", &[None]), release("1.6.0", &[None])];
+        let versions: Vec<_> = select_releases(releases, (1, 4, 0), (1, 6, 0), |_| true)
+            .into_iter()
+            .map(|r| r.version)
+            .collect();
+        assert_eq!(versions, vec!["1.6.0", "1.5.0"]);
+    }
+
+    #[test]
+    fn fresh_installs_have_nothing_new() {
+        assert_eq!(previous_version(None, false), None);
+        assert_eq!(
+            previous_version(None, true).as_deref(),
+            Some(UNRECORDED_VERSION)
+        );
+        assert_eq!(
+            previous_version(Some("1.5.0".to_string()), true).as_deref(),
+            Some("1.5.0")
+        );
+    }
+
+    #[test]
+    fn bundled_changelog_parses() {
+        let releases: Vec<ChangelogRelease> = serde_json::from_str(CHANGELOG_JSON).unwrap();
+        assert!(releases.iter().all(|r| parse_version(&r.version).is_some()));
+    }
+}
@@ -0,0 +1,166 @@
+//! Clipboard watch for Google Slides links (opt-in)
+//!
+//! When enabled, the clipboard is polled for a newly copied Slides URL. Each
+//! new link is offered to the panel as `slides-link-copied`, with a
+//! notification as well, so the deck's notes can be preloaded before the call
+//! starts. Whatever is on the clipboard when watching starts is not offered.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use regex::Regex;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_notification::NotificationExt;
+use tauri_plugin_store::StoreExt;
+
+use crate::APP_HANDLE;
+
+const CLIPBOARD_WATCH_KEY: &str = "clipboard_watch";
+const POLL_INTERVAL_MS: u64 = 1000;
+
+static SLIDES_URL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"https://docs\.google\.com/presentation/(?:u/\d+/)?d/([A-Za-z0-9_-]+)")
+        .expect("valid Slides URL regex")
+});
+
+static CLIPBOARD_WATCH: Lazy<Arc<RwLock<bool>>> = Lazy::new(|| Arc::new(RwLock::new(false)));
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SlidesLinkCopied {
+    pub presentation_id: String,
+    pub url: String,
+}
+
+fn find_slides_link(text: &str) -> Option<SlidesLinkCopied> {
+    let caps = SLIDES_URL.captures(text)?;
+    Some(SlidesLinkCopied {
+        presentation_id: caps[1].to_string(),
+        url: caps[0].to_string(),
+    })
+}
+
+/// What the watcher has seen since watching was turned on
+#[derive(Debug, Default)]
+struct Seen {
+    /// `None` until the first read
+    last_text: Option<String>,
+    last_offered: Option<String>,
+}
+
+impl Seen {
+    /// A link to offer from the clipboard's `text`: new since the last read,
+    /// not there when watching started, and not the deck offered last
+    fn read(&mut self, text: String) -> Option<SlidesLinkCopied> {
+        if self.last_text.as_deref() == Some(text.as_str()) {
+            return None;
+        }
+        let first_read = self.last_text.is_none();
+        let link = find_slides_link(&text);
+        self.last_text = Some(text);
+        if first_read {
+            return None;
+        }
+
+        let link = link?;
+        if self.last_offered.as_deref() == Some(link.presentation_id.as_str()) {
+            return None;
+        }
+        self.last_offered = Some(link.presentation_id.clone());
+        Some(link)
+    }
+}
+
+pub fn load_clipboard_watch_from_store(app: &AppHandle) {
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Some(enabled) = store.get(CLIPBOARD_WATCH_KEY).and_then(|v| v.as_bool()) {
+            *CLIPBOARD_WATCH.write() = enabled;
+        }
+    }
+}
+
+fn offer(app: &AppHandle, link: SlidesLinkCopied) {
+    if let Err(e) = app
+        .notification()
+        .builder()
+        .title("CueCard")
+        .body("Slides link copied. Open CueCard to preload its notes.")
+        .show()
+    {
+        eprintln!("Failed to show clipboard notification: {}", e);
+    }
+    let _ = app.emit("slides-link-copied", link);
+}
+
+/// Poll the clipboard while watching is on; runs for the lifetime of the app
+pub async fn run_watcher() {
+    let mut seen = Seen::default();
+
+    loop {
+        tokio::time::sleep(Duration::from_millis(POLL_INTERVAL_MS)).await;
+
+        if !*CLIPBOARD_WATCH.read() {
+            seen.last_text = None;
+            continue;
+        }
+        let Some(app) = APP_HANDLE.read().clone() else {
+            continue;
+        };
+
+        // Empty or non-text clipboards read as errors
+        let text = app.clipboard().read_text().unwrap_or_default();
+        if let Some(link) = seen.read(text) {
+            offer(&app, link);
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_clipboard_watch() -> bool {
+    *CLIPBOARD_WATCH.read()
+}
+
+#[tauri::command]
+pub fn set_clipboard_watch(app: AppHandle, enabled: bool) {
+    *CLIPBOARD_WATCH.write() = enabled;
+    if let Ok(store) = app.store("cuecard-store.json") {
+        store.set(CLIPBOARD_WATCH_KEY, enabled);
+        let _ = store.save();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_slides_links_in_copied_text() {
+        let link = find_slides_link(
+            "Deck for today: https://docs.google.com/presentation/u/1/d/1AbC_d-9/edit#slide=id.p",
+        )
+        .unwrap();
+        assert_eq!(link.presentation_id, "1AbC_d-9");
+        assert_eq!(
+            link.url,
+            "https://docs.google.com/presentation/u/1/d/1AbC_d-9"
+        );
+        assert!(find_slides_link("https://docs.google.com/document/d/1AbC/edit").is_none());
+    }
+
+    #[test]
+    fn offers_each_newly_copied_deck_once() {
+        let deck = |id: &str| format!("https://docs.google.com/presentation/d/{}/edit", id);
+        let mut seen = Seen::default();
+
+        // Already on the clipboard when watching started
+        assert!(seen.read(deck("a")).is_none());
+        assert_eq!(seen.read(deck("b")).unwrap().presentation_id, "b");
+        assert!(seen.read(deck("b")).is_none());
+        // Copied again after something else
+        assert!(seen.read("meeting notes".to_string()).is_none());
+        assert!(seen.read(deck("b")).is_none());
+        assert_eq!(seen.read(deck("a")).unwrap().presentation_id, "a");
+    }
+}
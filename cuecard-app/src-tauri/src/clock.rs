@@ -0,0 +1,135 @@
+//! The time token expiry checks and the session timer read
+//!
+//! Refresh decisions and the timer's elapsed time used to read the system
+//! clock directly, which left no way to run them at a chosen moment: just
+//! before an expiry, across a wake from sleep, or with the machine's clock
+//! skewed. They now read it through a swappable `Clock`. The app uses the
+//! system clock; the tests' `MockClock` holds a set time that only moves
+//! when advanced, for simulating those cases.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use std::sync::Arc;
+
+pub trait Clock: Send + Sync {
+    /// Unix milliseconds
+    fn now_millis(&self) -> i64;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_millis(&self) -> i64 {
+        chrono::Utc::now().timestamp_millis()
+    }
+}
+
+static CLOCK: Lazy<RwLock<Arc<dyn Clock>>> = Lazy::new(|| RwLock::new(Arc::new(SystemClock)));
+
+/// Read time from `clock` from now on
+#[cfg(test)]
+pub fn set_clock(clock: Arc<dyn Clock>) {
+    *CLOCK.write() = clock;
+}
+
+pub fn now_millis() -> i64 {
+    CLOCK.read().now_millis()
+}
+
+/// Unix seconds
+pub fn now_secs() -> i64 {
+    now_millis().div_euclid(1000)
+}
+
+/// Whether a token expiring at `expires_at` (Unix seconds) is due for a
+/// refresh, `margin_secs` ahead of time
+pub fn is_due(expires_at: i64, margin_secs: i64) -> bool {
+    now_secs() >= expires_at - margin_secs
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicI64, Ordering};
+
+    /// A clock that stands still until it's set or advanced
+    pub struct MockClock {
+        millis: AtomicI64,
+    }
+
+    impl MockClock {
+        fn at_secs(secs: i64) -> Self {
+            MockClock {
+                millis: AtomicI64::new(secs * 1000),
+            }
+        }
+
+        pub fn set_millis(&self, millis: i64) {
+            self.millis.store(millis, Ordering::SeqCst);
+        }
+
+        pub fn advance_millis(&self, millis: i64) {
+            self.millis.fetch_add(millis, Ordering::SeqCst);
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now_millis(&self) -> i64 {
+            self.millis.load(Ordering::SeqCst)
+        }
+    }
+
+    /// The clock is global, so tests that swap or read it take turns
+    pub static SERIAL: parking_lot::Mutex<()> = parking_lot::const_mutex(());
+
+    /// Run `test` with the clock stood still at `secs`
+    pub fn with_mock(secs: i64, test: impl FnOnce(&MockClock)) {
+        let _guard = SERIAL.lock();
+        let clock = Arc::new(MockClock::at_secs(secs));
+        set_clock(clock.clone());
+        test(&clock);
+        set_clock(Arc::new(SystemClock));
+    }
+
+    #[test]
+    fn token_is_due_from_the_margin_on() {
+        let expires_at = 1_700_003_600;
+        with_mock(expires_at - 301, |clock| {
+            assert!(!is_due(expires_at, 300));
+            clock.advance_millis(999);
+            assert!(!is_due(expires_at, 300));
+            clock.advance_millis(1);
+            assert!(is_due(expires_at, 300));
+            clock.advance_millis(300_000);
+            assert!(is_due(expires_at, 300));
+        });
+    }
+
+    #[test]
+    fn clock_set_back_keeps_a_token_fresh() {
+        with_mock(1_700_000_000, |clock| {
+            let expires_at = now_secs() + 3600;
+            clock.set_millis((expires_at - 7200) * 1000);
+            assert!(!is_due(expires_at, 300));
+        });
+    }
+
+    #[test]
+    fn clock_set_forward_past_expiry_refreshes() {
+        with_mock(1_700_000_000, |clock| {
+            let expires_at = now_secs() + 3600;
+            clock.advance_millis(24 * 3600 * 1000);
+            assert!(is_due(expires_at, 300));
+        });
+    }
+
+    #[test]
+    fn seconds_round_down_either_side_of_the_epoch() {
+        with_mock(0, |clock| {
+            clock.set_millis(1_999);
+            assert_eq!(now_secs(), 1);
+            clock.set_millis(-1);
+            assert_eq!(now_secs(), -1);
+        });
+    }
+}
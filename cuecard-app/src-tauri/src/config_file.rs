@@ -0,0 +1,300 @@
+//! Hand-edited `cuecard.toml` in the app config directory
+//!
+//! The file is optional. It's read at startup and again whenever it changes
+//! on disk; a file that doesn't parse or validate keeps the last good
+//! configuration and is reported as `config-error`, a good one as
+//! `config-reloaded`. Values set in the file take precedence over the same
+//! settings made in the UI, which apply again once the key is removed.
+//!
+//! ```toml
+//! [server]
+//! port = 3642
+//! allowed_origins = ["chrome-extension://abcdefghijklmnop"]
+//!
+//! [share]
+//! port = 3660
+//!
+//! [auto_sign_out]
+//! idle_hours = 4
+//! at = "18:00"
+//! ```
+//!
+//! Ports apply the next time the server they belong to starts; the origins
+//! allowlist applies to the next request, and the sign-out policy
+//! (`auto_sign_out`) to its next check.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, Url};
+
+use crate::providers::watcher::{self, FileWatcher};
+use crate::APP_HANDLE;
+
+const CONFIG_FILE_NAME: &str = "cuecard.toml";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FileConfig {
+    pub server: ServerConfig,
+    pub share: ShareConfig,
+    pub auto_sign_out: AutoSignOutConfig,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// Port for the local extension server; overrides the port set in the UI
+    pub port: Option<u16>,
+    /// Origins allowed to call the local server; any origin when unset
+    pub allowed_origins: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShareConfig {
+    /// Port for the LAN share link
+    pub port: Option<u16>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AutoSignOutConfig {
+    /// Hours without activity before signing out
+    pub idle_hours: Option<u32>,
+    /// Time of day to sign out, "HH:MM" in the event's time zone
+    pub at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigFileStatus {
+    pub path: Option<String>,
+    pub exists: bool,
+    /// Why the file on disk isn't in effect, if it isn't
+    pub error: Option<String>,
+    pub config: FileConfig,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ConfigErrorEvent {
+    path: String,
+    message: String,
+}
+
+static FILE_CONFIG: Lazy<Arc<RwLock<FileConfig>>> =
+    Lazy::new(|| Arc::new(RwLock::new(FileConfig::default())));
+static CONFIG_ERROR: Lazy<Arc<RwLock<Option<String>>>> = Lazy::new(|| Arc::new(RwLock::new(None)));
+static CONFIG_PATH: Lazy<Arc<RwLock<Option<PathBuf>>>> = Lazy::new(|| Arc::new(RwLock::new(None)));
+static CONFIG_WATCHER: Lazy<Arc<RwLock<Option<FileWatcher>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(None)));
+
+pub fn server_port() -> Option<u16> {
+    FILE_CONFIG.read().server.port
+}
+
+pub fn share_port() -> Option<u16> {
+    FILE_CONFIG.read().share.port
+}
+
+pub fn auto_sign_out() -> AutoSignOutConfig {
+    FILE_CONFIG.read().auto_sign_out.clone()
+}
+
+/// Whether a request from `origin` may reach the local server
+pub fn origin_allowed(origin: &str) -> bool {
+    FILE_CONFIG.read().server.allows(origin)
+}
+
+impl ServerConfig {
+    fn allows(&self, origin: &str) -> bool {
+        self.allowed_origins.as_ref().is_none_or(|allowed| {
+            allowed
+                .iter()
+                .any(|a| a.trim_end_matches('/').eq_ignore_ascii_case(origin))
+        })
+    }
+}
+
+fn validate(config: &FileConfig) -> Result<(), String> {
+    if let Some(origins) = &config.server.allowed_origins {
+        for origin in origins {
+            let Ok(url) = Url::parse(origin) else {
+                return Err(format!(
+                    "server.allowed_origins: \"{}\" is not a URL",
+                    origin
+                ));
+            };
+            if url.path() != "/" || url.query().is_some() || url.fragment().is_some() {
+                return Err(format!(
+                    "server.allowed_origins: \"{}\" should be a scheme and host only",
+                    origin
+                ));
+            }
+        }
+    }
+    if let (Some(server), Some(share)) = (config.server.port, config.share.port) {
+        if server == share && server != 0 {
+            return Err(format!(
+                "server.port and share.port are both {}; they need different ports",
+                server
+            ));
+        }
+    }
+    if config.auto_sign_out.idle_hours == Some(0) {
+        return Err("auto_sign_out.idle_hours should be at least 1".to_string());
+    }
+    if let Some(at) = &config.auto_sign_out.at {
+        if chrono::NaiveTime::parse_from_str(at.trim(), "%H:%M").is_err() {
+            return Err(format!(
+                "auto_sign_out.at: \"{}\" isn't a time like 18:00",
+                at
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn parse(text: &str) -> Result<FileConfig, String> {
+    let config: FileConfig = toml::from_str(text).map_err(|e| e.to_string())?;
+    validate(&config)?;
+    Ok(config)
+}
+
+/// Read the file into effect, keeping the current configuration if it's invalid
+fn reload(path: &Path) {
+    let result = match std::fs::read_to_string(path) {
+        Ok(text) => parse(&text),
+        // No file is the same as an empty one
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(FileConfig::default()),
+        Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+
+    let app = APP_HANDLE.read().clone();
+    match result {
+        Ok(config) => {
+            *FILE_CONFIG.write() = config.clone();
+            *CONFIG_ERROR.write() = None;
+            if let Some(app) = app {
+                let _ = app.emit("config-reloaded", config);
+            }
+        }
+        Err(message) => {
+            eprintln!("Ignoring {}: {}", path.display(), message);
+            *CONFIG_ERROR.write() = Some(message.clone());
+            if let Some(app) = app {
+                let _ = app.emit(
+                    "config-error",
+                    ConfigErrorEvent {
+                        path: path.display().to_string(),
+                        message,
+                    },
+                );
+            }
+        }
+    }
+}
+
+/// Load the config file and reload it whenever it changes
+pub fn init(app: &AppHandle) {
+    let dir = match app.path().app_config_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            eprintln!("Failed to find the config directory: {}", e);
+            return;
+        }
+    };
+    // The directory has to exist to be watched
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        eprintln!("Failed to create {}: {}", dir.display(), e);
+    }
+    let path = dir.join(CONFIG_FILE_NAME);
+    *CONFIG_PATH.write() = Some(path.clone());
+    reload(&path);
+
+    match watcher::watch_file(&path, reload) {
+        Ok(watcher) => *CONFIG_WATCHER.write() = Some(watcher),
+        Err(e) => eprintln!("Failed to watch {}: {}", path.display(), e),
+    }
+}
+
+/// Where the config file lives and whether it's in effect
+#[tauri::command]
+pub fn get_config_file_status() -> ConfigFileStatus {
+    let path = CONFIG_PATH.read().clone();
+    ConfigFileStatus {
+        exists: path.as_ref().is_some_and(|p| p.exists()),
+        path: path.map(|p| p.display().to_string()),
+        error: CONFIG_ERROR.read().clone(),
+        config: FILE_CONFIG.read().clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allows_listed_origins_only() {
+        let open = ServerConfig::default();
+        assert!(open.allows("https://anything.example"));
+
+        let listed = parse(
+            r#"
+            [server]
+            allowed_origins = ["chrome-extension://abcdefghijklmnop/", "https://Zoom.us"]
+            "#,
+        )
+        .unwrap()
+        .server;
+        assert!(listed.allows("chrome-extension://abcdefghijklmnop"));
+        assert!(listed.allows("https://zoom.us"));
+        assert!(!listed.allows("chrome-extension://ponmlkjihgfedcba"));
+        assert!(!listed.allows("https://zoom.us.example"));
+        assert!(!listed.allows("http://zoom.us"));
+
+        let closed = ServerConfig {
+            allowed_origins: Some(Vec::new()),
+            ..Default::default()
+        };
+        assert!(!closed.allows("chrome-extension://abcdefghijklmnop"));
+    }
+
+    #[test]
+    fn parses_the_file_and_defaults_missing_keys() {
+        let empty = parse("").unwrap();
+        assert_eq!(empty.server.port, None);
+        assert!(empty.server.allowed_origins.is_none());
+        assert_eq!(empty.share.port, None);
+        assert_eq!(empty.auto_sign_out.idle_hours, None);
+
+        let config = parse(
+            r#"
+            [server]
+            port = 3642
+
+            [auto_sign_out]
+            at = "18:00"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.server.port, Some(3642));
+        assert_eq!(config.share.port, None);
+        assert_eq!(config.auto_sign_out.at.as_deref(), Some("18:00"));
+        assert_eq!(config.auto_sign_out.idle_hours, None);
+    }
+
+    #[test]
+    fn rejects_invalid_files() {
+        // Typos aren't silently ignored
+        assert!(parse("[server]\nprot = 3642").is_err());
+        assert!(parse("[sever]\nport = 3642").is_err());
+        assert!(parse("[server]\nport = 70000").is_err());
+        assert!(parse("[server]\nallowed_origins = [\"not a url\"]").is_err());
+        assert!(parse("[server]\nallowed_origins = [\"https://zoom.us/app\"]").is_err());
+        assert!(parse("[server]\nport = 3642\n[share]\nport = 3642").is_err());
+        assert!(parse("[auto_sign_out]\nidle_hours = 0").is_err());
+        assert!(parse("[auto_sign_out]\nat = \"6pm\"").is_err());
+    }
+}
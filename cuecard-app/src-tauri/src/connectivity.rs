@@ -0,0 +1,199 @@
+//! Online, offline or stuck behind a captive portal
+//!
+//! Venue Wi-Fi tends to drop to a sign-in page partway through the day.
+//! Requests then get the portal's HTML back instead of Google's JSON, and
+//! the failures read like broken sign-ins. The monitor fetches a page that
+//! answers `204 No Content` when the internet is reachable. Any other answer
+//! suggests a portal is in the way, and no answer that the machine is
+//! offline. It checks every `CHECK_INTERVAL_SECS`, and more often while not
+//! online.
+//!
+//! The probe is only a hint: some networks block it while Google's APIs work
+//! fine. The state changes only after `FAILURES_BEFORE_CHANGE` probes in a
+//! row disagree with it, and any Google API call that gets an answer marks
+//! the machine online. Token refreshes and Slides calls the user is waiting
+//! on are always attempted, and fail with a network error that says why when
+//! the hint explains it; optional background work skips its turn while not
+//! online. Changes are sent as `connectivity-changed`, and coming back online
+//! refreshes tokens and the open deck (`sleep_wake`). The monitor rests while
+//! the machine sleeps.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::Emitter;
+
+use crate::error::CueCardError;
+use crate::{http_client, sleep_wake, APP_HANDLE};
+
+const PROBE_URL: &str = "http://connectivitycheck.gstatic.com/generate_204";
+const PROBE_TIMEOUT_SECS: u64 = 5;
+const CHECK_INTERVAL_SECS: u64 = 30;
+const RECHECK_INTERVAL_SECS: u64 = 10;
+// One lost probe on a flaky network shouldn't change anything
+const FAILURES_BEFORE_CHANGE: u32 = 3;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectivityState {
+    // Assumed until the first check says otherwise
+    #[default]
+    Online,
+    /// The network answers, but with a sign-in page
+    CaptivePortal,
+    Offline,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Connectivity {
+    pub state: ConnectivityState,
+    pub checked_at: Option<i64>,
+}
+
+static CONNECTIVITY: Lazy<Arc<RwLock<Connectivity>>> =
+    Lazy::new(|| Arc::new(RwLock::new(Connectivity::default())));
+// Probes in a row that didn't find the internet
+static FAILED_PROBES: Lazy<Arc<RwLock<u32>>> = Lazy::new(|| Arc::new(RwLock::new(0)));
+
+async fn probe() -> ConnectivityState {
+    let response = http_client::client()
+        .get(PROBE_URL)
+        .timeout(Duration::from_secs(PROBE_TIMEOUT_SECS))
+        .send()
+        .await;
+    match response {
+        Ok(r) if r.status() == reqwest::StatusCode::NO_CONTENT => ConnectivityState::Online,
+        // Portals answer with (or redirect to) their sign-in page
+        Ok(_) => ConnectivityState::CaptivePortal,
+        Err(_) => ConnectivityState::Offline,
+    }
+}
+
+/// Record the state, announcing a change
+fn set_state(state: ConnectivityState) {
+    let changed = {
+        let mut connectivity = CONNECTIVITY.write();
+        let changed = connectivity.state != state;
+        connectivity.state = state;
+        connectivity.checked_at = Some(chrono::Utc::now().timestamp());
+        changed
+    };
+    if changed {
+        eprintln!("Connectivity changed: {:?}", state);
+        if let Some(app) = APP_HANDLE.read().as_ref() {
+            let _ = app.emit("connectivity-changed", get_connectivity());
+        }
+        if state == ConnectivityState::Online {
+            sleep_wake::on_network_restored();
+        }
+    }
+}
+
+/// Probe now; the state only leaves online after several failed probes
+pub async fn check() -> ConnectivityState {
+    let probed = probe().await;
+    let settled = {
+        let mut failures = FAILED_PROBES.write();
+        let (count, settled) = settle(*failures, probed);
+        *failures = count;
+        settled
+    };
+    if let Some(state) = settled {
+        set_state(state);
+    }
+    state()
+}
+
+/// Failed probes in a row after this one, and the state it settles on once
+/// enough of them agree
+fn settle(failures: u32, probed: ConnectivityState) -> (u32, Option<ConnectivityState>) {
+    if probed == ConnectivityState::Online {
+        return (0, Some(probed));
+    }
+    let failures = failures.saturating_add(1);
+    (
+        failures,
+        (failures >= FAILURES_BEFORE_CHANGE).then_some(probed),
+    )
+}
+
+/// A Google API call got an answer, so the internet is reachable whatever
+/// the probe says
+pub fn record_success() {
+    *FAILED_PROBES.write() = 0;
+    if state() != ConnectivityState::Online {
+        set_state(ConnectivityState::Online);
+    }
+}
+
+pub async fn run_monitor() {
+    loop {
+        // A probe as the machine sleeps would only report it offline
+        if sleep_wake::is_paused() {
+            tokio::time::sleep(Duration::from_secs(RECHECK_INTERVAL_SECS)).await;
+            continue;
+        }
+        let interval = match check().await {
+            ConnectivityState::Online => CHECK_INTERVAL_SECS,
+            _ => RECHECK_INTERVAL_SECS,
+        };
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+    }
+}
+
+pub fn state() -> ConnectivityState {
+    CONNECTIVITY.read().state
+}
+
+/// A network error for a failed request, explained by the connectivity
+/// state when it isn't online
+pub fn network_error(detail: impl std::fmt::Display) -> CueCardError {
+    CueCardError::Network(explain(state(), detail))
+}
+
+fn explain(state: ConnectivityState, detail: impl std::fmt::Display) -> String {
+    match state {
+        ConnectivityState::Online => detail.to_string(),
+        ConnectivityState::CaptivePortal => format!(
+            "The network is showing a sign-in page; sign in to the Wi-Fi first ({})",
+            detail
+        ),
+        ConnectivityState::Offline => format!("No internet connection ({})", detail),
+    }
+}
+
+#[tauri::command]
+pub fn get_connectivity() -> Connectivity {
+    CONNECTIVITY.read().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ConnectivityState::*;
+
+    #[test]
+    fn changes_only_after_several_failed_probes() {
+        assert_eq!(settle(0, Offline), (1, None));
+        assert_eq!(settle(1, CaptivePortal), (2, None));
+        assert_eq!(
+            settle(FAILURES_BEFORE_CHANGE - 1, Offline),
+            (FAILURES_BEFORE_CHANGE, Some(Offline))
+        );
+        // One good probe is enough to be back online
+        assert_eq!(settle(FAILURES_BEFORE_CHANGE, Online), (0, Some(Online)));
+        assert_eq!(settle(u32::MAX, Offline), (u32::MAX, Some(Offline)));
+    }
+
+    #[test]
+    fn explains_failures_by_the_state() {
+        assert_eq!(explain(Online, "timed out"), "timed out");
+        assert_eq!(
+            explain(Offline, "timed out"),
+            "No internet connection (timed out)"
+        );
+        assert!(explain(CaptivePortal, "timed out").contains("sign in to the Wi-Fi"));
+    }
+}
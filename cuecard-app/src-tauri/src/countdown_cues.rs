@@ -0,0 +1,267 @@
+//! Inline countdowns from `[countdown mm:ss]` tags in notes
+//!
+//! A tag such as `[countdown 02:00] Discuss in pairs` marks a timed stretch of
+//! the talk, like an audience exercise. When the slide whose notes hold it
+//! becomes current, its first countdown starts; further tags on the same slide
+//! run one after another, and leaving the slide cancels whatever is left. The
+//! rest of the tag's line is the countdown's label.
+//!
+//! Every change is emitted as a `countdown-cue` event carrying the cue and the
+//! time it ends, so the panel and any other overlay count down from the same
+//! clock instead of keeping their own.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use regex::Regex;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::Emitter;
+
+use crate::{SlideData, APP_HANDLE};
+
+const TICK_MS: u64 = 250;
+
+static COUNTDOWN_TAG: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\[countdown\s+(\d{1,2}):(\d{2})\]([^\n]*)").expect("valid countdown regex")
+});
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CountdownCue {
+    /// Position among the slide's countdowns, from 0
+    pub index: usize,
+    pub duration_secs: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CountdownState {
+    Running,
+    Finished,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CountdownCueEvent {
+    pub presentation_id: String,
+    pub slide_id: String,
+    pub cue: CountdownCue,
+    pub state: CountdownState,
+    /// Unix millis the countdown ends (or ended) at
+    pub ends_at_ms: i64,
+    /// Countdowns still to run on this slide after this one
+    pub remaining_cues: usize,
+}
+
+/// The current slide's countdowns and the one running
+struct ActiveCountdowns {
+    presentation_id: String,
+    slide_id: String,
+    cues: Vec<CountdownCue>,
+    /// Index into `cues` and its end time
+    running: Option<(usize, i64)>,
+}
+
+impl ActiveCountdowns {
+    fn event(&self, index: usize, ends_at_ms: i64, state: CountdownState) -> CountdownCueEvent {
+        CountdownCueEvent {
+            presentation_id: self.presentation_id.clone(),
+            slide_id: self.slide_id.clone(),
+            cue: self.cues[index].clone(),
+            state,
+            ends_at_ms,
+            remaining_cues: self.cues.len().saturating_sub(index + 1),
+        }
+    }
+}
+
+static ACTIVE: Lazy<Arc<RwLock<Option<ActiveCountdowns>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(None)));
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+/// Countdown tags in notes, in order
+pub fn parse(notes: &str) -> Vec<CountdownCue> {
+    COUNTDOWN_TAG
+        .captures_iter(notes)
+        .filter_map(|caps| {
+            let minutes: u32 = caps[1].parse().ok()?;
+            let seconds: u32 = caps[2].parse().ok()?;
+            let duration_secs = minutes * 60 + seconds;
+            if seconds >= 60 || duration_secs == 0 {
+                return None;
+            }
+            let label = caps[3].trim();
+            Some((
+                duration_secs,
+                (!label.is_empty()).then(|| label.to_string()),
+            ))
+        })
+        .enumerate()
+        .map(|(index, (duration_secs, label))| CountdownCue {
+            index,
+            duration_secs,
+            label,
+        })
+        .collect()
+}
+
+fn emit(events: Vec<CountdownCueEvent>) {
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        for event in events {
+            let _ = app.emit("countdown-cue", event);
+        }
+    }
+}
+
+/// Start the countdowns of a slide that was just shown with `notes`. Showing
+/// the same slide again leaves its countdowns alone unless the tags changed.
+pub fn on_slide_shown(slide_data: &SlideData, notes: Option<&str>) {
+    let cues = notes.map(parse).unwrap_or_default();
+    let now = now_ms();
+    let mut events = Vec::new();
+    {
+        let mut active = ACTIVE.write();
+        if let Some(current) = active.as_ref() {
+            let same_slide = current.presentation_id == slide_data.presentation_id
+                && current.slide_id == slide_data.slide_id;
+            if same_slide && current.cues == cues {
+                return;
+            }
+            if let Some((index, _)) = current.running {
+                events.push(current.event(index, now, CountdownState::Cancelled));
+            }
+        }
+
+        *active = (!cues.is_empty()).then(|| {
+            let ends_at = now + cues[0].duration_secs as i64 * 1000;
+            let started = ActiveCountdowns {
+                presentation_id: slide_data.presentation_id.clone(),
+                slide_id: slide_data.slide_id.clone(),
+                cues,
+                running: Some((0, ends_at)),
+            };
+            events.push(started.event(0, ends_at, CountdownState::Running));
+            started
+        });
+    }
+    emit(events);
+}
+
+/// Finish due countdowns and start the next on the slide; runs for the
+/// lifetime of the app
+pub async fn run_countdown_loop() {
+    loop {
+        tokio::time::sleep(Duration::from_millis(TICK_MS)).await;
+
+        let now = now_ms();
+        let mut events = Vec::new();
+        {
+            let mut active = ACTIVE.write();
+            let Some(current) = active.as_mut() else {
+                continue;
+            };
+            let Some((index, ends_at)) = current.running else {
+                continue;
+            };
+            if now < ends_at {
+                continue;
+            }
+
+            events.push(current.event(index, ends_at, CountdownState::Finished));
+            current.running = current.cues.get(index + 1).map(|next| {
+                // Back to back, from when the last one ended
+                let next_ends_at = ends_at + next.duration_secs as i64 * 1000;
+                (index + 1, next_ends_at)
+            });
+            if let Some((next, next_ends_at)) = current.running {
+                events.push(current.event(next, next_ends_at, CountdownState::Running));
+            }
+        }
+        emit(events);
+    }
+}
+
+/// The countdown running on the current slide, for when the panel loads
+#[tauri::command]
+pub fn get_countdown_cue() -> Option<CountdownCueEvent> {
+    let active = ACTIVE.read();
+    let current = active.as_ref()?;
+    let (index, ends_at) = current.running?;
+    Some(current.event(index, ends_at, CountdownState::Running))
+}
+
+/// Cancel the running countdown and the rest of the slide's
+#[tauri::command]
+pub fn stop_countdown_cue() {
+    let event = {
+        let mut active = ACTIVE.write();
+        let Some(current) = active.as_mut() else {
+            return;
+        };
+        let Some((index, _)) = current.running.take() else {
+            return;
+        };
+        current.event(index, now_ms(), CountdownState::Cancelled)
+    };
+    emit(vec![event]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_tags_in_order_with_labels() {
+        let cues = parse("Intro\n[countdown 02:00] Discuss in pairs\n[COUNTDOWN 0:30]\nWrap up");
+        assert_eq!(
+            cues,
+            vec![
+                CountdownCue {
+                    index: 0,
+                    duration_secs: 120,
+                    label: Some("Discuss in pairs".to_string()),
+                },
+                CountdownCue {
+                    index: 1,
+                    duration_secs: 30,
+                    label: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_malformed_and_out_of_range_tags() {
+        // Out-of-range tags don't take up an index
+        let cues = parse(
+            "[countdown 1:75]\n[countdown 0:00]\n[countdown 2]\n[countdown 100:00]\n[countdown a:10]\n[countdown 1:05] go",
+        );
+        assert_eq!(cues.len(), 1);
+        assert_eq!(cues[0].index, 0);
+        assert_eq!(cues[0].duration_secs, 65);
+        assert_eq!(cues[0].label.as_deref(), Some("go"));
+    }
+
+    #[test]
+    fn counts_the_cues_left_after_one() {
+        let active = ActiveCountdowns {
+            presentation_id: "deck".to_string(),
+            slide_id: "p1".to_string(),
+            cues: parse("[countdown 0:10]\n[countdown 0:20]\n[countdown 0:30]"),
+            running: None,
+        };
+        assert_eq!(
+            active.event(0, 0, CountdownState::Running).remaining_cues,
+            2
+        );
+        assert_eq!(
+            active.event(2, 0, CountdownState::Finished).remaining_cues,
+            0
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//! Export of everything CueCard stores about the user
+//!
+//! `export_my_data` writes a zip archive with:
+//!
+//! - `manifest.json`: when and by which version the export was made
+//! - `store.json`: the local settings store, without credentials
+//! - `cache.json`: presentations cached on disk (titles, dates, slide counts)
+//! - `firestore/profile.json`: the signed-in user's profile document
+//!
+//! Firestore is skipped when signed out.
+
+use serde::Serialize;
+use std::io::Write;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+use zip::write::SimpleFileOptions;
+
+use crate::error::CueCardError;
+use crate::{disk_cache, http_client, FIREBASE_CONFIG, FIREBASE_TOKENS};
+
+// Settings and records that are the user's own. Keys not listed stay out, so
+// a setting added later is exported only once it's been checked for secrets:
+// sign-in tokens, API keys, the stage display password, proxy credentials in
+// the network settings.
+const EXPORTED_STORE_KEYS: &[&str] = &[
+    "active_profile",
+    "add_notes_content",
+    "analytics_client_id",
+    "analytics_first_open_sent",
+    "api_usage",
+    "audio_output_device",
+    "auto_sign_out",
+    "clipboard_watch",
+    "deck_screenshot_protection",
+    "deck_translation",
+    "event_timezone",
+    "glossary",
+    "interpreter_lookahead",
+    "interpreter_notes",
+    "last_run_version",
+    "last_session_summary",
+    "low_data_mode",
+    "masked_presentations",
+    "masking_config",
+    "notes_change_history",
+    "notes_fetch_mode",
+    "notes_merge_rules",
+    "notes_pipeline",
+    "notes_summary",
+    "notes_translation",
+    "panel_behavior",
+    "profiles",
+    "rehearsal_runs",
+    "retention_settings",
+    "revalidate_on_focus",
+    "revision_poll_secs",
+    "saved_notes",
+    "server_port",
+    "settings_auto_scroll_speed",
+    "settings_ghost_mode",
+    "settings_opacity",
+    "settings_shortcuts_enabled",
+    "settings_theme",
+    "slide_number_offsets",
+    "store_encryption_disabled",
+    "topmost_banding",
+];
+
+#[derive(Debug, Serialize)]
+struct ExportManifest {
+    exported_at: i64,
+    app_version: String,
+    files: Vec<String>,
+}
+
+fn store_contents(app: &AppHandle) -> serde_json::Map<String, serde_json::Value> {
+    let Ok(store) = app.store("cuecard-store.json") else {
+        return serde_json::Map::new();
+    };
+    exported_entries(store.entries())
+}
+
+fn exported_entries(
+    entries: Vec<(String, serde_json::Value)>,
+) -> serde_json::Map<String, serde_json::Value> {
+    entries
+        .into_iter()
+        .filter(|(key, _)| EXPORTED_STORE_KEYS.contains(&key.as_str()))
+        .collect()
+}
+
+/// The signed-in user's `Profiles/{email}` document, as Firestore returns it
+async fn fetch_profile_document() -> Result<Option<serde_json::Value>, String> {
+    let Some(email) = FIREBASE_TOKENS
+        .read()
+        .as_ref()
+        .and_then(|t| t.email.clone())
+    else {
+        return Ok(None);
+    };
+    let Some(token) = crate::get_valid_firebase_token().await else {
+        return Ok(None);
+    };
+    let config = FIREBASE_CONFIG
+        .read()
+        .clone()
+        .ok_or("Firebase config not loaded")?;
+
+    let url = format!(
+        "https://firestore.googleapis.com/v1/projects/{}/databases/(default)/documents/Profiles/{}",
+        config.project_id,
+        urlencoding::encode(&email)
+    );
+
+    let client = http_client::client();
+    let response = client
+        .get(&url)
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch profile: {}", e))?;
+
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!(
+            "Failed to fetch profile: {} - {}",
+            status, error_text
+        ));
+    }
+
+    let doc = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse Firestore response: {}", e))?;
+    Ok(Some(doc))
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<Vec<u8>, String> {
+    serde_json::to_vec_pretty(value).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn export_my_data(app: AppHandle, path: String) -> Result<(), CueCardError> {
+    let mut files: Vec<(String, Vec<u8>)> = vec![
+        ("store.json".to_string(), to_json(&store_contents(&app))?),
+        (
+            "cache.json".to_string(),
+            to_json(&disk_cache::list_cached_presentations())?,
+        ),
+    ];
+    if let Some(profile) = fetch_profile_document().await? {
+        files.push(("firestore/profile.json".to_string(), to_json(&profile)?));
+    }
+
+    let manifest = ExportManifest {
+        exported_at: chrono::Utc::now().timestamp(),
+        app_version: app.package_info().version.to_string(),
+        files: files.iter().map(|(name, _)| name.clone()).collect(),
+    };
+    files.insert(0, ("manifest.json".to_string(), to_json(&manifest)?));
+
+    let file =
+        std::fs::File::create(&path).map_err(|e| format!("Failed to create export file: {}", e))?;
+    write_archive(file, files)?;
+    Ok(())
+}
+
+fn write_archive<W: Write + std::io::Seek>(
+    writer: W,
+    files: Vec<(String, Vec<u8>)>,
+) -> Result<(), String> {
+    let mut archive = zip::ZipWriter::new(writer);
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    for (name, contents) in files {
+        archive
+            .start_file(name, options)
+            .map_err(|e| format!("Failed to write export: {}", e))?;
+        archive
+            .write_all(&contents)
+            .map_err(|e| format!("Failed to write export: {}", e))?;
+    }
+    archive
+        .finish()
+        .map_err(|e| format!("Failed to write export: {}", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Read;
+
+    #[test]
+    fn leaves_credentials_out_of_the_store() {
+        let entries = vec![
+            ("settings_theme".to_string(), json!("dark")),
+            ("glossary".to_string(), json!([{"term": "Kubernetes"}])),
+            ("firebase_tokens".to_string(), json!({"refresh_token": "r"})),
+            ("notes_summary_api_key".to_string(), json!("sk-123")),
+            (
+                "network_settings".to_string(),
+                json!({"proxy": "http://u:p@proxy"}),
+            ),
+            ("some_future_setting".to_string(), json!(true)),
+        ];
+        let exported = exported_entries(entries);
+        let mut keys: Vec<&str> = exported.keys().map(|k| k.as_str()).collect();
+        keys.sort();
+        assert_eq!(keys, vec!["glossary", "settings_theme"]);
+    }
+
+    #[test]
+    fn archive_holds_each_file() {
+        let mut buffer = std::io::Cursor::new(Vec::new());
+        write_archive(
+            &mut buffer,
+            vec![
+                ("manifest.json".to_string(), b"{}".to_vec()),
+                (
+                    "firestore/profile.json".to_string(),
+                    b"{\"name\":\"x\"}".to_vec(),
+                ),
+            ],
+        )
+        .unwrap();
+
+        let mut archive = zip::ZipArchive::new(buffer).unwrap();
+        assert_eq!(archive.len(), 2);
+        let mut profile = String::new();
+        archive
+            .by_name("firestore/profile.json")
+            .unwrap()
+            .read_to_string(&mut profile)
+            .unwrap();
+        assert_eq!(profile, r#"{"name":"x"}"#);
+    }
+}
@@ -0,0 +1,142 @@
+//! `cuecard://` links
+//!
+//! Links in calendar invites or wikis can set CueCard up for a talk:
+//!
+//! - `cuecard://load?presentation=<id or Slides URL>` preloads the deck's notes
+//! - `cuecard://timer?minutes=<n>` sets the panel's countdown
+//! - `cuecard://layout?name=<layout>` switches the panel layout
+//!
+//! Each link is emitted to the panel as `deep-link`; loading also starts the
+//! preload here, so notes are ready before the extension reports a slide.
+
+use serde::Serialize;
+use tauri::{Emitter, Url};
+
+use crate::APP_HANDLE;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum DeepLinkAction {
+    Load { presentation_id: String },
+    Timer { minutes: u32 },
+    Layout { name: String },
+}
+
+fn query_param(url: &Url, name: &str) -> Option<String> {
+    url.query_pairs()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Presentation id from a bare id or a Google Slides URL
+fn presentation_id(value: &str) -> String {
+    match value.split_once("/presentation/d/") {
+        Some((_, rest)) => rest
+            .split(['/', '?', '#'])
+            .next()
+            .unwrap_or(rest)
+            .to_string(),
+        None => value.to_string(),
+    }
+}
+
+pub fn parse(url: &Url) -> Result<DeepLinkAction, String> {
+    if url.scheme() != "cuecard" {
+        return Err(format!("Not a CueCard link: {}", url));
+    }
+
+    // cuecard://load?... puts the action in the host; cuecard:load?... in the path
+    let action = url
+        .host_str()
+        .unwrap_or_else(|| url.path())
+        .trim_matches('/')
+        .to_string();
+    match action.as_str() {
+        "load" => {
+            let value = query_param(url, "presentation").ok_or("Missing presentation")?;
+            Ok(DeepLinkAction::Load {
+                presentation_id: presentation_id(&value),
+            })
+        }
+        "timer" => {
+            let minutes = query_param(url, "minutes")
+                .and_then(|m| m.parse().ok())
+                .ok_or("Missing or invalid minutes")?;
+            Ok(DeepLinkAction::Timer { minutes })
+        }
+        "layout" => {
+            let name = query_param(url, "name").ok_or("Missing layout name")?;
+            Ok(DeepLinkAction::Layout { name })
+        }
+        other => Err(format!("Unknown CueCard link action: {}", other)),
+    }
+}
+
+pub fn handle_urls(urls: Vec<Url>) {
+    for url in urls {
+        let action = match parse(&url) {
+            Ok(a) => a,
+            Err(e) => {
+                eprintln!("Ignoring link: {}", e);
+                continue;
+            }
+        };
+
+        if let DeepLinkAction::Load {
+            ref presentation_id,
+        } = action
+        {
+            let ids = vec![presentation_id.clone()];
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = crate::preload::preload_presentations(ids).await {
+                    eprintln!("Failed to preload linked presentation: {}", e);
+                }
+            });
+        }
+
+        if let Some(app) = APP_HANDLE.read().as_ref() {
+            let _ = app.emit("deep-link", action);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(url: &str) -> Result<serde_json::Value, String> {
+        parse(&Url::parse(url).unwrap()).map(|action| serde_json::to_value(action).unwrap())
+    }
+
+    #[test]
+    fn reads_each_action() {
+        assert_eq!(
+            link("cuecard://load?presentation=1AbC").unwrap(),
+            serde_json::json!({ "action": "load", "presentation_id": "1AbC" })
+        );
+        assert_eq!(
+            link("cuecard:timer?minutes=20").unwrap(),
+            serde_json::json!({ "action": "timer", "minutes": 20 })
+        );
+        assert_eq!(
+            link("cuecard://layout/?name=compact").unwrap(),
+            serde_json::json!({ "action": "layout", "name": "compact" })
+        );
+    }
+
+    #[test]
+    fn takes_the_id_out_of_a_slides_url() {
+        let url = "cuecard://load?presentation=https%3A%2F%2Fdocs.google.com%2Fpresentation%2Fd%2F1AbC%2Fedit%23slide%3Did.p";
+        assert_eq!(link(url).unwrap()["presentation_id"], "1AbC");
+    }
+
+    #[test]
+    fn rejects_incomplete_or_foreign_links() {
+        assert!(link("cuecard://load").is_err());
+        assert!(link("cuecard://load?presentation=%20").is_err());
+        assert!(link("cuecard://timer?minutes=ten").is_err());
+        assert!(link("cuecard://present?deck=1").is_err());
+        assert!(link("https://example.com/load?presentation=1").is_err());
+    }
+}
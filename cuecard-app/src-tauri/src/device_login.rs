@@ -0,0 +1,237 @@
+//! Signing in with a code when no browser can be opened here
+//!
+//! Locked-down machines sometimes refuse to open the OAuth page. Google's
+//! device authorization grant works around that: the panel shows a short code
+//! and a URL to enter it at from any other device (a phone will do), while
+//! CueCard polls the token endpoint until the sign-in is approved, denied or
+//! the code expires. An approved sign-in completes like the browser flow and
+//! sends `auth-status`; anything else ends with `device-login-failed`.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::Emitter;
+
+use crate::{http_client, OAuthCredentials, APP_HANDLE, GOOGLE_TOKEN_URL};
+
+const GOOGLE_DEVICE_CODE_URL: &str = "https://oauth2.googleapis.com/device/code";
+const DEVICE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+const DEFAULT_POLL_SECS: u64 = 5;
+
+/// How `start_login` signs in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoginMode {
+    /// Open Google's sign-in page, falling back to a code if that fails
+    #[default]
+    Browser,
+    /// Show a code to enter on another device
+    Device,
+}
+
+/// What the panel shows while waiting for approval
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceLogin {
+    pub user_code: String,
+    pub verification_url: String,
+    pub expires_at: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeviceCodeResponse {
+    device_code: String,
+    user_code: String,
+    verification_url: String,
+    expires_in: i64,
+    interval: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeviceTokenError {
+    error: String,
+    error_description: Option<String>,
+}
+
+// The poll for the code shown last; a new sign-in replaces it
+static DEVICE_POLL: Lazy<Arc<RwLock<Option<tauri::async_runtime::JoinHandle<()>>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(None)));
+
+fn emit_failure(message: String) {
+    eprintln!("Device sign-in failed: {}", message);
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        let _ = app.emit("device-login-failed", message);
+    }
+}
+
+/// Ask Google for a user code and start polling for approval
+pub async fn start(credentials: &OAuthCredentials, scope: String) -> Result<DeviceLogin, String> {
+    let client = http_client::client();
+    let response = client
+        .post(GOOGLE_DEVICE_CODE_URL)
+        .form(&[
+            ("client_id", credentials.client_id.as_str()),
+            ("scope", crate::oauth_scopes(Some(&scope)).as_str()),
+        ])
+        .send()
+        .await
+        .map_err(|e| format!("Device code request failed: {}", e))?;
+
+    if !response.status().is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("Device code request failed: {}", error_text));
+    }
+
+    let code: DeviceCodeResponse = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse device code response: {}", e))?;
+
+    let login = DeviceLogin {
+        user_code: code.user_code,
+        verification_url: code.verification_url,
+        expires_at: chrono::Utc::now().timestamp() + code.expires_in,
+    };
+
+    let task = tauri::async_runtime::spawn(poll_for_tokens(
+        credentials.clone(),
+        code.device_code,
+        code.interval.unwrap_or(DEFAULT_POLL_SECS),
+        login.expires_at,
+        scope,
+    ));
+    if let Some(previous) = DEVICE_POLL.write().replace(task) {
+        previous.abort();
+    }
+    Ok(login)
+}
+
+async fn poll_for_tokens(
+    credentials: OAuthCredentials,
+    device_code: String,
+    mut interval: u64,
+    expires_at: i64,
+    scope: String,
+) {
+    let client = http_client::client();
+
+    loop {
+        tokio::time::sleep(std::time::Duration::from_secs(interval)).await;
+        if chrono::Utc::now().timestamp() >= expires_at {
+            emit_failure("The sign-in code expired".to_string());
+            return;
+        }
+
+        let response = match client
+            .post(GOOGLE_TOKEN_URL)
+            .form(&[
+                ("client_id", credentials.client_id.as_str()),
+                ("client_secret", credentials.client_secret.as_str()),
+                ("device_code", device_code.as_str()),
+                ("grant_type", DEVICE_GRANT_TYPE),
+            ])
+            .send()
+            .await
+        {
+            Ok(response) => response,
+            // Keep trying through network blips until the code expires
+            Err(e) => {
+                eprintln!("Device token request failed: {}", e);
+                continue;
+            }
+        };
+
+        if response.status().is_success() {
+            let result = match response.json::<crate::GoogleTokenResponse>().await {
+                Ok(tokens) => crate::finish_google_login(tokens, Some(scope)).await,
+                Err(e) => Err(format!("Failed to parse token response: {}", e)),
+            };
+            if let Err(e) = result {
+                emit_failure(e);
+            }
+            return;
+        }
+
+        let error = response
+            .json::<DeviceTokenError>()
+            .await
+            .map_err(|e| format!("Failed to parse token response: {}", e));
+        match error.and_then(keep_polling) {
+            Ok(backoff) => interval += backoff,
+            Err(e) => {
+                emit_failure(e);
+                return;
+            }
+        }
+    }
+}
+
+/// Seconds to add to the poll interval while the sign-in is still pending, or
+/// why it ended
+fn keep_polling(error: DeviceTokenError) -> Result<u64, String> {
+    match error.error.as_str() {
+        "authorization_pending" => Ok(0),
+        "slow_down" => Ok(DEFAULT_POLL_SECS),
+        "access_denied" => Err("Sign-in was declined".to_string()),
+        _ => Err(error.error_description.unwrap_or(error.error)),
+    }
+}
+
+/// Stop waiting for a code to be entered
+#[tauri::command]
+pub fn cancel_device_login() {
+    if let Some(task) = DEVICE_POLL.write().take() {
+        task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token_error(json: serde_json::Value) -> DeviceTokenError {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn polls_until_approved_or_declined() {
+        let pending = token_error(serde_json::json!({ "error": "authorization_pending" }));
+        assert_eq!(keep_polling(pending), Ok(0));
+        let slow_down = token_error(serde_json::json!({ "error": "slow_down" }));
+        assert_eq!(keep_polling(slow_down), Ok(DEFAULT_POLL_SECS));
+
+        let denied = token_error(serde_json::json!({ "error": "access_denied" }));
+        assert_eq!(
+            keep_polling(denied),
+            Err("Sign-in was declined".to_string())
+        );
+        let expired = token_error(serde_json::json!({
+            "error": "expired_token",
+            "error_description": "The device code has expired."
+        }));
+        assert_eq!(
+            keep_polling(expired),
+            Err("The device code has expired.".to_string())
+        );
+        let unknown = token_error(serde_json::json!({ "error": "invalid_grant" }));
+        assert_eq!(keep_polling(unknown), Err("invalid_grant".to_string()));
+    }
+
+    #[test]
+    fn reads_the_device_code_response() {
+        let code: DeviceCodeResponse = serde_json::from_value(serde_json::json!({
+            "device_code": "AH-1Ng",
+            "user_code": "GQVQ-JKEC",
+            "verification_url": "https://www.google.com/device",
+            "expires_in": 1800
+        }))
+        .unwrap();
+        assert_eq!(code.user_code, "GQVQ-JKEC");
+        assert_eq!(code.expires_in, 1800);
+        // Google may leave the interval out
+        assert_eq!(code.interval.unwrap_or(DEFAULT_POLL_SECS), 5);
+
+        let mode: LoginMode = serde_json::from_value(serde_json::json!("device")).unwrap();
+        assert_eq!(mode, LoginMode::Device);
+    }
+}
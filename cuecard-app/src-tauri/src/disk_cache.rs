@@ -0,0 +1,741 @@
+//! On-disk cache of presentation notes and thumbnails
+//!
+//! Lets decks preloaded ahead of time work without network access, and keeps
+//! the notes of every deck the prefetch fetched in full so a restart mid-talk
+//! doesn't fetch the deck again. Layout under the app cache directory:
+//!
+//! - `presentations/index.json`: titles, slide order and where each slide's
+//!   notes live; the only part kept in memory
+//! - `presentations/{id}/notes.bin`: one zstd frame per slide, back to back
+//! - `presentations/{id}/{slide_id}.png.zst`: slide thumbnails
+//!
+//! Notes are read and decompressed one slide at a time, when a slide is shown,
+//! or for the whole deck when it's opened. While the settings store is
+//! encrypted (`secure_store`), each frame is also sealed with the store's
+//! key, nothing is cached while it's locked, and `reseal` rewrites the cache
+//! when encryption is turned on or off. Entries carry the presentation's
+//! `revisionId` when the API reports one (only to editors), so an unchanged
+//! deck isn't written again. Prefetched decks expire after
+//! `PREFETCHED_TTL_SECS`, and the oldest of them are dropped once the cache
+//! grows past `MAX_CACHE_BYTES`; preloaded decks are left to the retention
+//! settings.
+
+use base64::Engine;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::Manager;
+
+use crate::{secure_store, APP_HANDLE};
+
+const ZSTD_LEVEL: i32 = 3;
+const PREFETCHED_TTL_SECS: i64 = 14 * 24 * 60 * 60;
+const MAX_CACHE_BYTES: u64 = 256 * 1024 * 1024;
+
+/// A slide handed to `write_presentation`
+#[derive(Debug, Clone)]
+pub struct CachedSlide {
+    pub slide_id: String,
+    pub slide_number: i32,
+    pub notes: Option<String>,
+    pub has_thumbnail: bool,
+    /// Left out of the slideshow
+    pub hidden: bool,
+}
+
+/// A whole presentation handed to `write_presentation`
+#[derive(Debug, Clone)]
+pub struct CachedPresentation {
+    pub presentation_id: String,
+    pub title: String,
+    pub cached_at: i64,
+    pub revision: Option<String>,
+    /// Preloaded by the user rather than cached by the notes prefetch
+    pub preloaded: bool,
+    pub slides: Vec<CachedSlide>,
+}
+
+/// A cached presentation's notes, from `read_presentation_notes`
+#[derive(Debug, Clone)]
+pub struct CachedNotes {
+    pub slide_ids: Vec<String>,
+    /// Slide id and notes, for slides that have notes
+    pub notes: Vec<(String, String)>,
+    /// Slides left out of the slideshow; none for decks cached before
+    /// that was recorded
+    pub hidden_slide_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexSlide {
+    slide_id: String,
+    slide_number: i32,
+    /// Byte range of the compressed notes in `notes.bin`
+    notes: Option<(u64, u64)>,
+    has_thumbnail: bool,
+    #[serde(default)]
+    hidden: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexEntry {
+    title: String,
+    cached_at: i64,
+    #[serde(default)]
+    revision: Option<String>,
+    #[serde(default = "default_preloaded")]
+    preloaded: bool,
+    /// Notes frames sealed with the store key
+    #[serde(default)]
+    sealed: bool,
+    slides: Vec<IndexSlide>,
+}
+
+/// Entries written before the prefetch cached decks all came from preloads
+fn default_preloaded() -> bool {
+    true
+}
+
+type CacheIndex = HashMap<String, IndexEntry>;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CachedPresentationSummary {
+    pub presentation_id: String,
+    pub title: String,
+    pub cached_at: i64,
+    pub slide_count: usize,
+}
+
+// Loaded from disk on first use
+static CACHE_INDEX: Lazy<Arc<RwLock<Option<CacheIndex>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(None)));
+
+fn cache_root() -> Option<PathBuf> {
+    let app = APP_HANDLE.read();
+    let dir = app.as_ref()?.path().app_cache_dir().ok()?;
+    Some(dir.join("presentations"))
+}
+
+/// Presentation and slide ids as file names
+fn safe_name(id: &str) -> String {
+    id.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn presentation_dir(presentation_id: &str) -> Option<PathBuf> {
+    Some(cache_root()?.join(safe_name(presentation_id)))
+}
+
+fn thumbnail_path(presentation_id: &str, slide_id: &str) -> Option<PathBuf> {
+    Some(presentation_dir(presentation_id)?.join(format!("{}.png.zst", safe_name(slide_id))))
+}
+
+fn read_index_file() -> CacheIndex {
+    cache_root()
+        .and_then(|root| std::fs::read(root.join("index.json")).ok())
+        .and_then(|data| serde_json::from_slice(&data).ok())
+        .unwrap_or_default()
+}
+
+/// Run `f` against the index, loading it first if needed
+fn with_index<T>(f: impl FnOnce(&CacheIndex) -> T) -> T {
+    if let Some(index) = CACHE_INDEX.read().as_ref() {
+        return f(index);
+    }
+    let mut index = CACHE_INDEX.write();
+    f(index.get_or_insert_with(read_index_file))
+}
+
+/// Change the index and write it back to disk
+fn update_index(f: impl FnOnce(&mut CacheIndex)) -> Result<(), String> {
+    let mut index = CACHE_INDEX.write();
+    let index = index.get_or_insert_with(read_index_file);
+    f(index);
+
+    let root = cache_root().ok_or("No cache directory")?;
+    std::fs::create_dir_all(&root)
+        .map_err(|e| format!("Failed to create cache directory: {}", e))?;
+    let json = serde_json::to_vec(&*index).map_err(|e| e.to_string())?;
+    std::fs::write(root.join("index.json"), json)
+        .map_err(|e| format!("Failed to write cache index: {}", e))
+}
+
+/// Key to seal new frames with; an error while the store is locked, so
+/// nothing is written that it couldn't protect
+fn write_key() -> Result<Option<[u8; 32]>, String> {
+    secure_store::cache_key().map_err(|_| "Store is locked; notes aren't cached".to_string())
+}
+
+pub fn encode_frame(text: &str, key: Option<&[u8; 32]>) -> Result<Vec<u8>, String> {
+    let frame = zstd::encode_all(text.as_bytes(), ZSTD_LEVEL)
+        .map_err(|e| format!("Failed to compress notes: {}", e))?;
+    match key {
+        Some(key) => secure_store::seal(&frame, key),
+        None => Ok(frame),
+    }
+}
+
+/// A frame's bytes in notes.bin of `file_len` bytes, from an index entry
+/// that may be corrupt
+fn frame_range((offset, len): (u64, u64), file_len: u64) -> Option<std::ops::Range<usize>> {
+    let end = offset.checked_add(len)?;
+    if end > file_len {
+        return None;
+    }
+    Some(usize::try_from(offset).ok()?..usize::try_from(end).ok()?)
+}
+
+pub fn decode_frame(frame: &[u8], key: Option<&[u8; 32]>) -> Option<String> {
+    let opened;
+    let frame = match key {
+        Some(key) => {
+            opened = secure_store::open(frame, key)?;
+            opened.as_slice()
+        }
+        None => frame,
+    };
+    String::from_utf8(zstd::decode_all(frame).ok()?).ok()
+}
+
+/// Key to read an entry's frames with; `None` inside when they're plain,
+/// and `None` outside when they're sealed and the store is locked or plain
+fn read_key(sealed: bool) -> Option<Option<[u8; 32]>> {
+    if !sealed {
+        return Some(None);
+    }
+    secure_store::cache_key().ok()?.map(Some)
+}
+
+/// Frames back to back in `notes.bin`, and each slide's range in it
+fn encode_slides(
+    slides: &[CachedSlide],
+    thumbnails: &HashSet<String>,
+    key: Option<&[u8; 32]>,
+) -> Result<(Vec<u8>, Vec<IndexSlide>), String> {
+    let mut blob = Vec::new();
+    let mut indexed = Vec::with_capacity(slides.len());
+    for slide in slides {
+        let notes = match slide.notes {
+            Some(ref text) => {
+                let frame = encode_frame(text, key)?;
+                let range = (blob.len() as u64, frame.len() as u64);
+                blob.extend_from_slice(&frame);
+                Some(range)
+            }
+            None => None,
+        };
+        indexed.push(IndexSlide {
+            slide_id: slide.slide_id.clone(),
+            slide_number: slide.slide_number,
+            notes,
+            has_thumbnail: slide.has_thumbnail || thumbnails.contains(&slide.slide_id),
+            hidden: slide.hidden,
+        });
+    }
+    Ok((blob, indexed))
+}
+
+pub async fn write_presentation(presentation: &CachedPresentation) -> Result<(), String> {
+    let key = write_key()?;
+    let dir = presentation_dir(&presentation.presentation_id).ok_or("No cache directory")?;
+    tokio::fs::create_dir_all(&dir)
+        .await
+        .map_err(|e| format!("Failed to create cache directory: {}", e))?;
+
+    // A prefetch rewriting a preloaded deck keeps its thumbnails and stays preloaded
+    let (was_preloaded, thumbnails) = with_index(|index| {
+        index
+            .get(&presentation.presentation_id)
+            .map(|entry| {
+                let thumbnails: HashSet<String> = entry
+                    .slides
+                    .iter()
+                    .filter(|s| s.has_thumbnail)
+                    .map(|s| s.slide_id.clone())
+                    .collect();
+                (entry.preloaded, thumbnails)
+            })
+            .unwrap_or_default()
+    });
+
+    let (blob, slides) = encode_slides(&presentation.slides, &thumbnails, key.as_ref())?;
+
+    tokio::fs::write(dir.join("notes.bin"), blob)
+        .await
+        .map_err(|e| format!("Failed to write cache: {}", e))?;
+
+    update_index(|index| {
+        index.insert(
+            presentation.presentation_id.clone(),
+            IndexEntry {
+                title: presentation.title.clone(),
+                cached_at: presentation.cached_at,
+                revision: presentation.revision.clone(),
+                preloaded: presentation.preloaded || was_preloaded,
+                sealed: key.is_some(),
+                slides,
+            },
+        );
+    })
+}
+
+pub async fn write_thumbnail(
+    presentation_id: &str,
+    slide_id: &str,
+    bytes: &[u8],
+) -> Result<(), String> {
+    let path = thumbnail_path(presentation_id, slide_id).ok_or("No cache directory")?;
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| format!("Failed to create cache directory: {}", e))?;
+    }
+
+    let compressed = zstd::encode_all(bytes, ZSTD_LEVEL)
+        .map_err(|e| format!("Failed to compress thumbnail: {}", e))?;
+    tokio::fs::write(&path, compressed)
+        .await
+        .map_err(|e| format!("Failed to write thumbnail: {}", e))
+}
+
+/// Replace one cached slide's notes, leaving the rest of the deck as it was.
+/// The new frame is appended to `notes.bin`; the old one stays until the deck
+/// is next written whole. Nothing happens for a slide that isn't cached.
+pub fn write_slide_notes(
+    presentation_id: &str,
+    slide_id: &str,
+    notes: Option<&str>,
+) -> Result<(), String> {
+    let key = write_key()?;
+    // An entry sealed otherwise is left to `reseal`
+    let cached = with_index(|index| {
+        index.get(presentation_id).is_some_and(|entry| {
+            entry.sealed == key.is_some() && entry.slides.iter().any(|s| s.slide_id == slide_id)
+        })
+    });
+    if !cached {
+        return Ok(());
+    }
+
+    let range = match notes {
+        Some(text) => {
+            let frame = encode_frame(text, key.as_ref())?;
+            let path = presentation_dir(presentation_id)
+                .ok_or("No cache directory")?
+                .join("notes.bin");
+            let mut file = std::fs::OpenOptions::new()
+                .append(true)
+                .open(&path)
+                .map_err(|e| format!("Failed to open cache: {}", e))?;
+            let offset = file
+                .seek(SeekFrom::End(0))
+                .map_err(|e| format!("Failed to write cache: {}", e))?;
+            file.write_all(&frame)
+                .map_err(|e| format!("Failed to write cache: {}", e))?;
+            Some((offset, frame.len() as u64))
+        }
+        None => None,
+    };
+
+    update_index(|index| {
+        if let Some(slide) = index
+            .get_mut(presentation_id)
+            .and_then(|entry| entry.slides.iter_mut().find(|s| s.slide_id == slide_id))
+        {
+            slide.notes = range;
+        }
+    })
+}
+
+/// Notes for one cached slide, read and decompressed on demand
+pub fn read_slide_notes(presentation_id: &str, slide_id: &str) -> Option<String> {
+    let (sealed, notes) = with_index(|index| {
+        let entry = index.get(presentation_id)?;
+        let range = entry
+            .slides
+            .iter()
+            .find(|s| s.slide_id == slide_id)?
+            .notes?;
+        Some((entry.sealed, range))
+    })?;
+    let key = read_key(sealed)?;
+
+    let mut file =
+        std::fs::File::open(presentation_dir(presentation_id)?.join("notes.bin")).ok()?;
+    let range = frame_range(notes, file.metadata().ok()?.len())?;
+    file.seek(SeekFrom::Start(range.start as u64)).ok()?;
+    let mut frame = vec![0; range.len()];
+    file.read_exact(&mut frame).ok()?;
+    decode_frame(&frame, key.as_ref())
+}
+
+/// Title of a cached presentation
+pub fn cached_title(presentation_id: &str) -> Option<String> {
+    with_index(|index| Some(index.get(presentation_id)?.title.clone()))
+}
+
+/// Revision a presentation was cached at, when the API reported one
+pub fn cached_revision(presentation_id: &str) -> Option<String> {
+    with_index(|index| index.get(presentation_id)?.revision.clone())
+}
+
+/// Unix seconds a presentation was cached at
+pub fn cached_at(presentation_id: &str) -> Option<i64> {
+    with_index(|index| Some(index.get(presentation_id)?.cached_at))
+}
+
+/// A cached presentation's slide order and the notes of each slide that has
+/// them, read in one go
+pub fn read_presentation_notes(presentation_id: &str) -> Option<CachedNotes> {
+    let (sealed, slides) = with_index(|index| {
+        let entry = index.get(presentation_id)?;
+        Some((entry.sealed, entry.slides.clone()))
+    })?;
+    let key = read_key(sealed)?;
+    let blob = std::fs::read(presentation_dir(presentation_id)?.join("notes.bin")).ok()?;
+
+    let notes = slides
+        .iter()
+        .filter_map(|slide| {
+            let frame = blob.get(frame_range(slide.notes?, blob.len() as u64)?)?;
+            Some((slide.slide_id.clone(), decode_frame(frame, key.as_ref())?))
+        })
+        .collect();
+    let hidden_slide_ids = slides
+        .iter()
+        .filter(|s| s.hidden)
+        .map(|s| s.slide_id.clone())
+        .collect();
+    let slide_ids = slides.into_iter().map(|s| s.slide_id).collect();
+    Some(CachedNotes {
+        slide_ids,
+        notes,
+        hidden_slide_ids,
+    })
+}
+
+/// Rewrite every deck sealed otherwise than the store now is: sealed with
+/// its key while it's encrypted, plain while it isn't. Sealed frames are
+/// opened with `previous_key`; decks that can't be are dropped.
+pub fn reseal(previous_key: Option<[u8; 32]>) -> Result<(), String> {
+    let key = write_key()?;
+    let stale: Vec<(String, IndexEntry)> = with_index(|index| {
+        index
+            .iter()
+            .filter(|(_, entry)| entry.sealed != key.is_some())
+            .map(|(id, entry)| (id.clone(), entry.clone()))
+            .collect()
+    });
+
+    let mut unreadable = Vec::new();
+    for (id, entry) in stale {
+        let old_key = if entry.sealed { previous_key } else { None };
+        if entry.sealed && old_key.is_none() {
+            unreadable.push(id);
+            continue;
+        }
+        let Some(path) = presentation_dir(&id).map(|dir| dir.join("notes.bin")) else {
+            continue;
+        };
+        let blob = std::fs::read(&path).unwrap_or_default();
+        let mut slides = Vec::with_capacity(entry.slides.len());
+        let mut readable = true;
+        for slide in &entry.slides {
+            let notes = match slide.notes {
+                Some(notes) => {
+                    let text = frame_range(notes, blob.len() as u64)
+                        .and_then(|range| blob.get(range))
+                        .and_then(|frame| decode_frame(frame, old_key.as_ref()));
+                    if text.is_none() {
+                        readable = false;
+                        break;
+                    }
+                    text
+                }
+                None => None,
+            };
+            slides.push(CachedSlide {
+                slide_id: slide.slide_id.clone(),
+                slide_number: slide.slide_number,
+                notes,
+                has_thumbnail: slide.has_thumbnail,
+                hidden: slide.hidden,
+            });
+        }
+        if !readable {
+            unreadable.push(id);
+            continue;
+        }
+
+        let (blob, slides) = encode_slides(&slides, &HashSet::new(), key.as_ref())?;
+        std::fs::write(&path, blob).map_err(|e| format!("Failed to write cache: {}", e))?;
+        update_index(|index| {
+            if let Some(current) = index.get_mut(&id) {
+                current.sealed = key.is_some();
+                current.slides = slides;
+            }
+        })?;
+    }
+    remove_presentations(&unreadable)
+}
+
+/// Total size of the files in a presentation's directory
+fn presentation_size(presentation_id: &str) -> u64 {
+    let Some(entries) = presentation_dir(presentation_id).and_then(|d| std::fs::read_dir(d).ok())
+    else {
+        return 0;
+    };
+    entries
+        .filter_map(|e| e.ok()?.metadata().ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// Drop presentations from the index and delete their files
+fn remove_presentations(ids: &[String]) -> Result<(), String> {
+    if ids.is_empty() {
+        return Ok(());
+    }
+    update_index(|index| index.retain(|id, _| !ids.contains(id)))?;
+
+    for id in ids {
+        if let Some(dir) = presentation_dir(id) {
+            if let Err(e) = std::fs::remove_dir_all(&dir) {
+                eprintln!("Failed to remove cached presentation {}: {}", id, e);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn cached_before(index: &CacheIndex, cutoff: i64) -> Vec<String> {
+    index
+        .iter()
+        .filter(|(_, entry)| entry.cached_at < cutoff)
+        .map(|(id, _)| id.clone())
+        .collect()
+}
+
+/// Drop presentations cached before `cutoff` (Unix seconds); returns how many
+pub fn remove_cached_before(cutoff: i64) -> Result<usize, String> {
+    let expired = with_index(|index| cached_before(index, cutoff));
+    remove_presentations(&expired)?;
+    Ok(expired.len())
+}
+
+/// Drop prefetched decks past their TTL, then the oldest prefetched decks
+/// until the cache fits in `MAX_CACHE_BYTES`; returns how many
+pub fn enforce_limits() -> Result<usize, String> {
+    let (ids, mut prefetched) = with_index(|index| {
+        let ids: Vec<String> = index.keys().cloned().collect();
+        let prefetched: Vec<(String, i64)> = index
+            .iter()
+            .filter(|(_, entry)| !entry.preloaded)
+            .map(|(id, entry)| (id.clone(), entry.cached_at))
+            .collect();
+        (ids, prefetched)
+    });
+    let sizes: HashMap<String, u64> = ids
+        .into_iter()
+        .map(|id| {
+            let size = presentation_size(&id);
+            (id, size)
+        })
+        .collect();
+    let mut total: u64 = sizes.values().sum();
+
+    let expires_before = chrono::Utc::now().timestamp() - PREFETCHED_TTL_SECS;
+    prefetched.sort_by_key(|(_, cached_at)| *cached_at);
+    let mut expired = Vec::new();
+    for (id, cached_at) in prefetched {
+        if cached_at >= expires_before && total <= MAX_CACHE_BYTES {
+            break;
+        }
+        total = total.saturating_sub(sizes.get(&id).copied().unwrap_or(0));
+        expired.push(id);
+    }
+
+    remove_presentations(&expired)?;
+    Ok(expired.len())
+}
+
+/// Delete everything cached on disk
+pub fn clear() -> Result<(), String> {
+    *CACHE_INDEX.write() = Some(CacheIndex::new());
+    let root = cache_root().ok_or("No cache directory")?;
+    match std::fs::remove_dir_all(&root) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to remove cache: {}", e)),
+    }
+}
+
+#[tauri::command]
+pub fn list_cached_presentations() -> Vec<CachedPresentationSummary> {
+    let mut list: Vec<CachedPresentationSummary> = with_index(|index| {
+        index
+            .iter()
+            .map(|(id, entry)| CachedPresentationSummary {
+                presentation_id: id.clone(),
+                title: entry.title.clone(),
+                cached_at: entry.cached_at,
+                slide_count: entry.slides.len(),
+            })
+            .collect()
+    });
+    list.sort_by(|a, b| a.title.cmp(&b.title));
+    list
+}
+
+/// Cached slide thumbnail as a PNG data URL
+#[tauri::command]
+pub fn get_cached_thumbnail(presentation_id: String, slide_id: String) -> Option<String> {
+    let compressed = std::fs::read(thumbnail_path(&presentation_id, &slide_id)?).ok()?;
+    let bytes = zstd::decode_all(compressed.as_slice()).ok()?;
+    Some(format!(
+        "data:image/png;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_frames_round_trip() {
+        let frame = encode_frame("Welcome everyone", None).unwrap();
+        assert_eq!(
+            decode_frame(&frame, None).as_deref(),
+            Some("Welcome everyone")
+        );
+    }
+
+    #[test]
+    fn sealed_frames_need_the_key() {
+        let key = [7u8; 32];
+        let frame = encode_frame("Q3 numbers are confidential", Some(&key)).unwrap();
+        assert!(!frame.windows(7).any(|w| w == b"numbers"));
+        assert_eq!(
+            decode_frame(&frame, Some(&key)).as_deref(),
+            Some("Q3 numbers are confidential")
+        );
+        assert_eq!(decode_frame(&frame, Some(&[8u8; 32])), None);
+        assert_eq!(decode_frame(&frame, None), None);
+    }
+
+    #[test]
+    fn each_slide_reads_back_from_its_own_range() {
+        let slide = |id: &str, notes: Option<&str>| CachedSlide {
+            slide_id: id.to_string(),
+            slide_number: 0,
+            notes: notes.map(str::to_string),
+            has_thumbnail: false,
+            hidden: false,
+        };
+        let slides = vec![
+            slide("p1", Some("Open with the headline number")),
+            slide("p2", None),
+            slide("p3", Some("Thank the team")),
+        ];
+        let thumbnails: HashSet<String> = ["p3".to_string()].into_iter().collect();
+
+        let (blob, indexed) = encode_slides(&slides, &thumbnails, None).unwrap();
+        let notes: Vec<Option<String>> = indexed
+            .iter()
+            .map(|s| {
+                let range = frame_range(s.notes?, blob.len() as u64)?;
+                decode_frame(&blob[range], None)
+            })
+            .collect();
+        assert_eq!(
+            notes,
+            vec![
+                Some("Open with the headline number".to_string()),
+                None,
+                Some("Thank the team".to_string()),
+            ]
+        );
+        assert_eq!(
+            indexed.iter().map(|s| s.has_thumbnail).collect::<Vec<_>>(),
+            vec![false, false, true]
+        );
+    }
+
+    #[test]
+    fn index_keeps_hidden_slides() {
+        let slides = vec![
+            CachedSlide {
+                slide_id: "p1".to_string(),
+                slide_number: 1,
+                notes: Some("Intro".to_string()),
+                has_thumbnail: false,
+                hidden: false,
+            },
+            CachedSlide {
+                slide_id: "p2".to_string(),
+                slide_number: 2,
+                notes: None,
+                has_thumbnail: false,
+                hidden: true,
+            },
+        ];
+        let (_, indexed) = encode_slides(&slides, &HashSet::new(), None).unwrap();
+        let json = serde_json::to_value(&indexed).unwrap();
+        let read: Vec<IndexSlide> = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            read.iter().map(|s| s.hidden).collect::<Vec<_>>(),
+            vec![false, true]
+        );
+
+        // Entries cached before hidden slides were recorded
+        let old: IndexSlide = serde_json::from_value(serde_json::json!({
+            "slide_id": "p1",
+            "slide_number": 1,
+            "notes": null,
+            "has_thumbnail": false
+        }))
+        .unwrap();
+        assert!(!old.hidden);
+    }
+
+    #[test]
+    fn finds_decks_cached_before_the_cutoff() {
+        let entry = |cached_at| IndexEntry {
+            title: String::new(),
+            cached_at,
+            revision: None,
+            preloaded: true,
+            sealed: false,
+            slides: Vec::new(),
+        };
+        let index: CacheIndex = [
+            ("old".to_string(), entry(999)),
+            ("edge".to_string(), entry(1000)),
+            ("new".to_string(), entry(1001)),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(cached_before(&index, 1000), vec!["old".to_string()]);
+        assert!(cached_before(&index, 0).is_empty());
+    }
+
+    #[test]
+    fn frame_ranges_stay_inside_the_file() {
+        assert_eq!(frame_range((4, 6), 10), Some(4..10));
+        assert_eq!(frame_range((4, 7), 10), None);
+        assert_eq!(frame_range((u64::MAX, 2), 10), None);
+        assert_eq!(frame_range((0, u64::MAX), 10), None);
+    }
+}
@@ -0,0 +1,191 @@
+//! Errors returned to the frontend and local API clients
+//!
+//! Commands used to fail with a bare message, which left the UI unable to tell
+//! an expired sign-in from a dropped connection or a deck the user can't
+//! open. `CueCardError` carries a stable code alongside the message: commands
+//! serialize it as `{ "code": "auth_required", "message": "..." }` and the
+//! local server answers with the matching status and
+//! `{ "error": "auth_required", "message": "..." }`, like its other errors.
+//!
+//! Helpers that don't know the cause still return `String`; it converts to
+//! `Other` through `?`.
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CueCardError {
+    /// Not signed in, or the sign-in was revoked and has to be done again
+    AuthRequired(String),
+    /// Signed in, but the Google scope this needs hasn't been granted
+    ScopeMissing(String),
+    /// Google refused access to the presentation
+    PermissionDenied(String),
+    NotFound(String),
+    /// Google's quota for the Slides or Drive API was hit
+    RateLimited(String),
+    /// The request didn't get through or no answer came back; worth retrying
+    Network(String),
+    Cancelled(String),
+    Other(String),
+}
+
+impl CueCardError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            CueCardError::AuthRequired(_) => "auth_required",
+            CueCardError::ScopeMissing(_) => "scope_missing",
+            CueCardError::PermissionDenied(_) => "permission_denied",
+            CueCardError::NotFound(_) => "not_found",
+            CueCardError::RateLimited(_) => "rate_limited",
+            CueCardError::Network(_) => "network",
+            CueCardError::Cancelled(_) => "cancelled",
+            CueCardError::Other(_) => "other",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            CueCardError::AuthRequired(m)
+            | CueCardError::ScopeMissing(m)
+            | CueCardError::PermissionDenied(m)
+            | CueCardError::NotFound(m)
+            | CueCardError::RateLimited(m)
+            | CueCardError::Network(m)
+            | CueCardError::Cancelled(m)
+            | CueCardError::Other(m) => m,
+        }
+    }
+
+    /// An error response from a Google API, classified by its status
+    pub fn from_status(status: reqwest::StatusCode, message: String) -> Self {
+        match status {
+            reqwest::StatusCode::UNAUTHORIZED => CueCardError::AuthRequired(message),
+            reqwest::StatusCode::FORBIDDEN => CueCardError::PermissionDenied(message),
+            reqwest::StatusCode::NOT_FOUND => CueCardError::NotFound(message),
+            reqwest::StatusCode::TOO_MANY_REQUESTS => CueCardError::RateLimited(message),
+            s if s.is_server_error() => CueCardError::Network(message),
+            _ => CueCardError::Other(message),
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            CueCardError::AuthRequired(_) => StatusCode::UNAUTHORIZED,
+            CueCardError::ScopeMissing(_) | CueCardError::PermissionDenied(_) => {
+                StatusCode::FORBIDDEN
+            }
+            CueCardError::NotFound(_) => StatusCode::NOT_FOUND,
+            CueCardError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            CueCardError::Network(_) => StatusCode::BAD_GATEWAY,
+            CueCardError::Cancelled(_) => StatusCode::CONFLICT,
+            CueCardError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl std::fmt::Display for CueCardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for CueCardError {}
+
+impl From<String> for CueCardError {
+    fn from(message: String) -> Self {
+        CueCardError::Other(message)
+    }
+}
+
+impl From<&str> for CueCardError {
+    fn from(message: &str) -> Self {
+        CueCardError::Other(message.to_string())
+    }
+}
+
+impl From<CueCardError> for String {
+    fn from(error: CueCardError) -> Self {
+        error.to_string()
+    }
+}
+
+impl From<reqwest::Error> for CueCardError {
+    fn from(error: reqwest::Error) -> Self {
+        if error.is_decode() || error.is_builder() {
+            CueCardError::Other(error.to_string())
+        } else {
+            crate::connectivity::network_error(error)
+        }
+    }
+}
+
+impl Serialize for CueCardError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("CueCardError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", self.message())?;
+        state.end()
+    }
+}
+
+impl IntoResponse for CueCardError {
+    fn into_response(self) -> Response {
+        (
+            self.status(),
+            Json(serde_json::json!({
+                "error": self.code(),
+                "message": self.message()
+            })),
+        )
+            .into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_see_a_code_and_a_message() {
+        let error = CueCardError::ScopeMissing("Drive access wasn't granted".to_string());
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({ "code": "scope_missing", "message": "Drive access wasn't granted" })
+        );
+        // Helpers' bare messages
+        assert_eq!(
+            CueCardError::from("Store is locked"),
+            CueCardError::Other("Store is locked".to_string())
+        );
+    }
+
+    #[test]
+    fn classifies_google_statuses() {
+        let code = |status| CueCardError::from_status(status, String::new()).code();
+        assert_eq!(code(reqwest::StatusCode::UNAUTHORIZED), "auth_required");
+        assert_eq!(code(reqwest::StatusCode::FORBIDDEN), "permission_denied");
+        assert_eq!(code(reqwest::StatusCode::NOT_FOUND), "not_found");
+        assert_eq!(code(reqwest::StatusCode::TOO_MANY_REQUESTS), "rate_limited");
+        assert_eq!(code(reqwest::StatusCode::BAD_GATEWAY), "network");
+        assert_eq!(code(reqwest::StatusCode::BAD_REQUEST), "other");
+    }
+
+    #[test]
+    fn local_api_answers_with_the_matching_status() {
+        let response = CueCardError::NotFound("No such deck".to_string()).into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let body = runtime
+            .block_on(axum::body::to_bytes(response.into_body(), usize::MAX))
+            .unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+            serde_json::json!({ "error": "not_found", "message": "No such deck" })
+        );
+    }
+}
@@ -0,0 +1,208 @@
+//! The time zone a talk happens in
+//!
+//! Times are stored as instants (Unix seconds) and only turned into clock
+//! times for display or when a clock time is typed in. By default that's the
+//! laptop's zone; a traveling speaker whose laptop is still on home time can
+//! set the event's zone with `set_event_timezone`, and reminders set for
+//! "14:30" and the session report's times then follow the venue's clock,
+//! across DST changes too.
+
+use chrono::{DateTime, Days, NaiveDate, NaiveTime, Offset, TimeZone};
+use chrono_tz::Tz;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_store::StoreExt;
+
+use crate::error::CueCardError;
+
+const EVENT_TIMEZONE_KEY: &str = "event_timezone";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EventTimezone {
+    /// IANA name, e.g. "Europe/Berlin"; `None` follows the laptop's zone
+    pub zone: Option<String>,
+    /// Current UTC offset in the zone in effect, e.g. "+02:00"
+    pub utc_offset: String,
+}
+
+static EVENT_TIMEZONE: Lazy<Arc<RwLock<Option<Tz>>>> = Lazy::new(|| Arc::new(RwLock::new(None)));
+
+pub fn load_event_timezone_from_store(app: &AppHandle) {
+    if let Ok(store) = app.store("cuecard-store.json") {
+        *EVENT_TIMEZONE.write() = store
+            .get(EVENT_TIMEZONE_KEY)
+            .and_then(|v| v.as_str().and_then(|name| name.parse::<Tz>().ok()));
+    }
+}
+
+/// An instant as a clock time in the event's zone
+pub fn format_instant(secs: i64, format: &str) -> String {
+    let Some(instant) = DateTime::from_timestamp(secs, 0) else {
+        return String::new();
+    };
+    match *EVENT_TIMEZONE.read() {
+        Some(zone) => instant.with_timezone(&zone).format(format).to_string(),
+        None => instant
+            .with_timezone(&chrono::Local)
+            .format(format)
+            .to_string(),
+    }
+}
+
+fn resolve_in<Z: TimeZone>(
+    zone: &Z,
+    date: Option<NaiveDate>,
+    time: NaiveTime,
+) -> Result<i64, String> {
+    let now = chrono::Utc::now().with_timezone(zone);
+    let day = date.unwrap_or_else(|| now.date_naive());
+
+    let at = |day: NaiveDate| {
+        zone.from_local_datetime(&day.and_time(time))
+            // When clocks go back the time happens twice; take the first
+            .earliest()
+            .map(|t| t.timestamp())
+            .ok_or_else(|| format!("{} doesn't exist on {}; the clocks change then", time, day))
+    };
+
+    let instant = at(day)?;
+    // A bare time that's already passed today means tomorrow
+    if date.is_none() && instant <= now.timestamp() {
+        let tomorrow = day
+            .checked_add_days(Days::new(1))
+            .ok_or("Date out of range")?;
+        return at(tomorrow);
+    }
+    Ok(instant)
+}
+
+/// The instant a clock time ("14:30", optionally on a "2026-10-15") refers to
+/// in the event's zone
+pub fn resolve_local_time(time: &str, date: Option<&str>) -> Result<i64, String> {
+    let time = NaiveTime::parse_from_str(time.trim(), "%H:%M")
+        .map_err(|_| format!("\"{}\" isn't a time like 14:30", time))?;
+    let date = date
+        .map(|d| {
+            NaiveDate::parse_from_str(d.trim(), "%Y-%m-%d")
+                .map_err(|_| format!("\"{}\" isn't a date like 2026-10-15", d))
+        })
+        .transpose()?;
+
+    match *EVENT_TIMEZONE.read() {
+        Some(zone) => resolve_in(&zone, date, time),
+        None => resolve_in(&chrono::Local, date, time),
+    }
+}
+
+fn current() -> EventTimezone {
+    let zone = *EVENT_TIMEZONE.read();
+    let now = chrono::Utc::now();
+    let offset = match zone {
+        Some(zone) => now.with_timezone(&zone).offset().fix(),
+        None => now.with_timezone(&chrono::Local).offset().fix(),
+    };
+    EventTimezone {
+        zone: zone.map(|z| z.name().to_string()),
+        utc_offset: offset.to_string(),
+    }
+}
+
+#[tauri::command]
+pub fn get_event_timezone() -> EventTimezone {
+    current()
+}
+
+/// Set the zone the talk is in, by IANA name, or `None` to follow the laptop
+#[tauri::command]
+pub fn set_event_timezone(
+    app: AppHandle,
+    zone: Option<String>,
+) -> Result<EventTimezone, CueCardError> {
+    let zone = zone
+        .map(|name| {
+            name.trim()
+                .parse::<Tz>()
+                .map_err(|_| format!("Unknown time zone: {}", name))
+        })
+        .transpose()?;
+
+    *EVENT_TIMEZONE.write() = zone;
+    if let Ok(store) = app.store("cuecard-store.json") {
+        match zone {
+            Some(zone) => store.set(EVENT_TIMEZONE_KEY, zone.name()),
+            None => {
+                store.delete(EVENT_TIMEZONE_KEY);
+            }
+        }
+        let _ = store.save();
+    }
+
+    let timezone = current();
+    let _ = app.emit("event-timezone-changed", timezone.clone());
+    Ok(timezone)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn utc(date: &str, time: &str) -> i64 {
+        let day = NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap();
+        let time = NaiveTime::parse_from_str(time, "%H:%M").unwrap();
+        Utc.from_utc_datetime(&day.and_time(time)).timestamp()
+    }
+
+    fn resolve(date: &str, time: &str) -> Result<i64, String> {
+        let zone: Tz = "Europe/Berlin".parse().unwrap();
+        resolve_in(
+            &zone,
+            Some(NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()),
+            NaiveTime::parse_from_str(time, "%H:%M").unwrap(),
+        )
+    }
+
+    #[test]
+    fn follows_the_zone_offset_across_dst() {
+        // CET in winter, CEST in summer
+        assert_eq!(
+            resolve("2026-01-15", "14:30"),
+            Ok(utc("2026-01-15", "13:30"))
+        );
+        assert_eq!(
+            resolve("2026-07-15", "14:30"),
+            Ok(utc("2026-07-15", "12:30"))
+        );
+    }
+
+    #[test]
+    fn skipped_time_is_an_error() {
+        // Clocks jump from 02:00 to 03:00
+        assert!(resolve("2026-03-29", "02:30").is_err());
+        assert_eq!(
+            resolve("2026-03-29", "03:30"),
+            Ok(utc("2026-03-29", "01:30"))
+        );
+    }
+
+    #[test]
+    fn repeated_time_takes_the_first() {
+        // Clocks go back from 03:00 to 02:00, so 02:30 happens in CEST first
+        assert_eq!(
+            resolve("2026-10-25", "02:30"),
+            Ok(utc("2026-10-25", "00:30"))
+        );
+    }
+
+    #[test]
+    fn passed_bare_time_means_tomorrow() {
+        let zone: Tz = "Europe/Berlin".parse().unwrap();
+        let now = Utc::now().timestamp();
+        let instant = resolve_in(&zone, None, NaiveTime::MIN).unwrap();
+        assert!(instant > now);
+        assert!(instant <= now + 25 * 3600);
+    }
+}
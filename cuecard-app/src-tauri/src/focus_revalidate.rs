@@ -0,0 +1,145 @@
+//! Revalidation when the app regains focus or the machine wakes
+//!
+//! Tokens can run out and a deck can be edited while the machine sleeps or
+//! the panel sits in the background. When a window regains focus, or the
+//! machine wakes, tokens that are due are refreshed and the current Google
+//! Slides deck's notes are checked against its revision in the background,
+//! so the first slide afterwards shows current notes without waiting on
+//! either. Wakes come through `sleep_wake`, which waits for the network
+//! first; the wall clock jumping ahead of a periodic tick is reported there
+//! too, for platforms that don't announce waking. Focus changes within
+//! `FOCUS_DEBOUNCE_SECS` of the last check are ignored, and notes aren't
+//! checked while close to Google's request limits (`api_usage`). On by
+//! default.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+use crate::{api_usage, CURRENT_SLIDE};
+
+const REVALIDATE_ON_FOCUS_KEY: &str = "revalidate_on_focus";
+const FOCUS_DEBOUNCE_SECS: i64 = 60;
+const WAKE_TICK_SECS: u64 = 10;
+/// How far the wall clock has to run ahead of a tick to count as a wake
+const WAKE_GAP_SECS: i64 = 30;
+
+static REVALIDATE_ON_FOCUS: Lazy<Arc<RwLock<bool>>> = Lazy::new(|| Arc::new(RwLock::new(true)));
+// Unix seconds of the last revalidation started
+static LAST_REVALIDATED: Lazy<Arc<RwLock<i64>>> = Lazy::new(|| Arc::new(RwLock::new(0)));
+
+pub fn load_revalidate_on_focus_from_store(app: &AppHandle) {
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Some(enabled) = store.get(REVALIDATE_ON_FOCUS_KEY).and_then(|v| v.as_bool()) {
+            *REVALIDATE_ON_FOCUS.write() = enabled;
+        }
+    }
+}
+
+/// Refresh tokens that are due, then the current deck's notes
+async fn revalidate() {
+    // Both refresh on their own when they're within a few minutes of expiring
+    let _ = crate::get_valid_firebase_token().await;
+    let _ = crate::get_valid_slides_token().await;
+
+    let Some(slide) = CURRENT_SLIDE.read().clone() else {
+        return;
+    };
+    if !crate::is_google_slides_mode(&slide.mode) || api_usage::throttle_optional() {
+        return;
+    }
+    if let Err(e) = crate::revalidate_deck_notes(&slide.presentation_id).await {
+        eprintln!("Failed to revalidate notes: {}", e);
+    }
+}
+
+/// Start a revalidation unless it's turned off, or one started recently and
+/// `debounce` is set
+fn start(debounce: bool) {
+    if !*REVALIDATE_ON_FOCUS.read() {
+        return;
+    }
+    let now = chrono::Utc::now().timestamp();
+    if !claim(&mut LAST_REVALIDATED.write(), now, debounce) {
+        return;
+    }
+    tauri::async_runtime::spawn(revalidate());
+}
+
+/// Whether to revalidate now, recording it in `last` if so
+fn claim(last: &mut i64, now: i64, debounce: bool) -> bool {
+    if debounce && now - *last < FOCUS_DEBOUNCE_SECS {
+        return false;
+    }
+    *last = now;
+    true
+}
+
+/// Whether the wall clock ran far enough past a tick for the machine to have slept
+fn slept_between(last_tick: i64, now: i64) -> bool {
+    now - last_tick > WAKE_TICK_SECS as i64 + WAKE_GAP_SECS
+}
+
+/// A window gained focus
+pub fn on_focus() {
+    start(true);
+}
+
+/// The machine woke and the network is back
+pub fn on_wake() {
+    start(false);
+}
+
+/// Watch for the machine waking from sleep; runs for the lifetime of the app
+pub async fn run_wake_watcher() {
+    let mut last_tick = chrono::Utc::now().timestamp();
+    loop {
+        tokio::time::sleep(Duration::from_secs(WAKE_TICK_SECS)).await;
+
+        // The sleep doesn't count time suspended, the wall clock does
+        let now = chrono::Utc::now().timestamp();
+        if slept_between(last_tick, now) {
+            crate::sleep_wake::on_wake();
+        }
+        last_tick = now;
+    }
+}
+
+#[tauri::command]
+pub fn get_revalidate_on_focus() -> bool {
+    *REVALIDATE_ON_FOCUS.read()
+}
+
+#[tauri::command]
+pub fn set_revalidate_on_focus(app: AppHandle, enabled: bool) {
+    *REVALIDATE_ON_FOCUS.write() = enabled;
+    if let Ok(store) = app.store("cuecard-store.json") {
+        store.set(REVALIDATE_ON_FOCUS_KEY, enabled);
+        let _ = store.save();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn focus_checks_are_debounced_but_wakes_are_not() {
+        let mut last = 0;
+        assert!(claim(&mut last, 1_000, true));
+        assert!(!claim(&mut last, 1_059, true));
+        assert!(claim(&mut last, 1_059, false));
+        assert_eq!(last, 1_059);
+        assert!(claim(&mut last, 1_119, true));
+    }
+
+    #[test]
+    fn a_late_tick_means_the_machine_slept() {
+        assert!(!slept_between(1_000, 1_010));
+        assert!(!slept_between(1_000, 1_040));
+        assert!(slept_between(1_000, 1_041));
+    }
+}
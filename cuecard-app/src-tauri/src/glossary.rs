@@ -0,0 +1,195 @@
+//! User glossary of product and proper names, with pronunciation hints
+//!
+//! Terms are written exactly as they should appear; `notes_check` flags
+//! spellings in notes that differ from them. A term can carry a phonetic hint
+//! ("Nguyen" -> "win"): displayed notes show it after each occurrence, and
+//! `shape_notes_for_speech` swaps it in for text read aloud. The glossary is
+//! kept in the local store.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+use crate::notes_masking;
+
+const GLOSSARY_KEY: &str = "glossary";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlossaryEntry {
+    pub term: String,
+    /// How to say it
+    #[serde(default)]
+    pub hint: Option<String>,
+}
+
+/// Whole-word matcher for terms with a hint, and lowercase term -> hint
+struct HintMatcher {
+    regex: Regex,
+    hints: HashMap<String, String>,
+}
+
+static GLOSSARY: Lazy<Arc<RwLock<Vec<GlossaryEntry>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(Vec::new())));
+static HINT_MATCHER: Lazy<Arc<RwLock<Option<HintMatcher>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(None)));
+
+pub fn entries() -> Vec<GlossaryEntry> {
+    GLOSSARY.read().clone()
+}
+
+fn build_matcher(entries: &[GlossaryEntry]) -> Option<HintMatcher> {
+    let hints: HashMap<String, String> = entries
+        .iter()
+        .filter_map(|e| Some((e.term.to_lowercase(), e.hint.clone()?)))
+        .collect();
+    if hints.is_empty() {
+        return None;
+    }
+
+    // Longest first, so "Acme Cloud" wins over "Acme"
+    let mut terms: Vec<&String> = hints.keys().collect();
+    terms.sort_by_key(|t| std::cmp::Reverse(t.len()));
+    let alternatives: Vec<String> = terms.iter().map(|t| notes_masking::whole_term(t)).collect();
+    match Regex::new(&format!("(?i){}", alternatives.join("|"))) {
+        Ok(regex) => Some(HintMatcher { regex, hints }),
+        Err(e) => {
+            eprintln!("Failed to build glossary matcher: {}", e);
+            None
+        }
+    }
+}
+
+fn set_entries(entries: Vec<GlossaryEntry>) {
+    *HINT_MATCHER.write() = build_matcher(&entries);
+    *GLOSSARY.write() = entries;
+}
+
+fn replace_terms(text: &str, replace: impl Fn(&str, &str) -> String) -> String {
+    let matcher = HINT_MATCHER.read();
+    let Some(matcher) = matcher.as_ref() else {
+        return text.to_string();
+    };
+    matcher
+        .regex
+        .replace_all(text, |caps: &regex::Captures| {
+            let found = &caps[0];
+            match matcher.hints.get(&found.to_lowercase()) {
+                Some(hint) => replace(found, hint),
+                None => found.to_string(),
+            }
+        })
+        .into_owned()
+}
+
+/// Notes as displayed, with each hinted term followed by its hint
+pub fn annotate_notes(notes: Option<String>) -> Option<String> {
+    notes.map(|n| replace_terms(&n, |term, hint| format!("{} [{}]", term, hint)))
+}
+
+pub fn load_glossary_from_store(app: &AppHandle) {
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Some(entries) = store
+            .get(GLOSSARY_KEY)
+            .and_then(|v| serde_json::from_value::<Vec<GlossaryEntry>>(v).ok())
+        {
+            set_entries(entries);
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_glossary() -> Vec<GlossaryEntry> {
+    entries()
+}
+
+#[tauri::command]
+pub fn set_glossary(app: AppHandle, entries: Vec<GlossaryEntry>) {
+    let entries: Vec<GlossaryEntry> = entries
+        .into_iter()
+        .map(|e| GlossaryEntry {
+            term: e.term.trim().to_string(),
+            hint: e
+                .hint
+                .map(|h| h.trim().to_string())
+                .filter(|h| !h.is_empty()),
+        })
+        .filter(|e| !e.term.is_empty())
+        .collect();
+    set_entries(entries.clone());
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Ok(json) = serde_json::to_value(&entries) {
+            store.set(GLOSSARY_KEY, json);
+            let _ = store.save();
+        }
+    }
+
+    let current = crate::CURRENT_SLIDE.read().clone();
+    if let Some(slide) = current {
+        crate::reemit_current_slide(&slide.presentation_id, None);
+    }
+}
+
+/// Text to read aloud, with hinted terms replaced by how they're said
+#[tauri::command]
+pub fn shape_notes_for_speech(text: String) -> String {
+    replace_terms(&text, |_, hint| hint.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn annotate(entries: &[(&str, &str)], text: &str) -> String {
+        let entries: Vec<GlossaryEntry> = entries
+            .iter()
+            .map(|(term, hint)| GlossaryEntry {
+                term: term.to_string(),
+                hint: Some(hint.to_string()),
+            })
+            .collect();
+        let matcher = build_matcher(&entries).unwrap();
+        matcher
+            .regex
+            .replace_all(text, |caps: &regex::Captures| {
+                format!("{} [{}]", &caps[0], matcher.hints[&caps[0].to_lowercase()])
+            })
+            .into_owned()
+    }
+
+    #[test]
+    fn hints_whole_words_longest_first() {
+        assert_eq!(
+            annotate(
+                &[("Acme", "ak-mee"), ("Acme Cloud", "ak-mee cloud")],
+                "acme cloud, Acmes"
+            ),
+            "acme cloud [ak-mee cloud], Acmes"
+        );
+    }
+
+    #[test]
+    fn hints_terms_with_symbols_at_the_edges() {
+        assert_eq!(
+            annotate(&[("C#", "see sharp")], "Port it to C#."),
+            "Port it to C# [see sharp]."
+        );
+        assert_eq!(
+            annotate(&[(".NET", "dot net")], "On .NET today"),
+            "On .NET [dot net] today"
+        );
+    }
+
+    #[test]
+    fn no_matcher_without_hints() {
+        let entries = vec![GlossaryEntry {
+            term: "Acme".to_string(),
+            hint: None,
+        }];
+        assert!(build_matcher(&entries).is_none());
+    }
+}
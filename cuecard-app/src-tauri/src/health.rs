@@ -0,0 +1,278 @@
+//! Periodic self-check of the pieces a talk depends on
+//!
+//! Every `CHECK_INTERVAL_SECS` the app checks that the local server still
+//! answers `/health` (the extension's slide updates go through it), that the
+//! Firebase and Slides tokens haven't been left expired by the refresh loop,
+//! and that the store file can still be written, by opening it for writing
+//! without changing it (skipped while an encrypted store is locked). A failed
+//! check is recovered where possible (restarting the server, refreshing the
+//! token) and checked again; any that still fails is sent as
+//! `health-degraded` with the details, and `health-restored` follows once
+//! everything passes again. Checks wait while the machine sleeps or waits on
+//! the network after waking (`sleep_wake`).
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::Emitter;
+
+use crate::{
+    clock, secure_store, sleep_wake, FirebaseTokens, SlidesTokens, APP_HANDLE, DEFAULT_SERVER_PORT,
+    FIREBASE_TOKENS, SERVER_PORT, SLIDES_TOKENS,
+};
+
+const CHECK_INTERVAL_SECS: u64 = 60;
+// First check once startup (server bind, session restore) has settled
+const FIRST_CHECK_DELAY_SECS: u64 = 20;
+const SERVER_PROBE_TIMEOUT_SECS: u64 = 3;
+// Time a restarted server gets to bind before it's checked again
+const SERVER_RESTART_WAIT_SECS: u64 = 3;
+const STORE_FILE: &str = "cuecard-store.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthCheck {
+    Server,
+    FirebaseToken,
+    SlidesToken,
+    Store,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthProblem {
+    pub check: HealthCheck,
+    pub detail: String,
+    /// What was tried to fix it, which didn't
+    pub recovery: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HealthReport {
+    pub checked_at: Option<i64>,
+    /// Checks that failed and couldn't be recovered; empty when healthy
+    pub problems: Vec<HealthProblem>,
+}
+
+static REPORT: Lazy<Arc<RwLock<HealthReport>>> =
+    Lazy::new(|| Arc::new(RwLock::new(HealthReport::default())));
+
+async fn check_server() -> Result<(), String> {
+    let port = SERVER_PORT.read().unwrap_or(DEFAULT_SERVER_PORT);
+    // Not the shared client: a configured proxy has no business with loopback
+    let client = reqwest::Client::builder()
+        .no_proxy()
+        .timeout(Duration::from_secs(SERVER_PROBE_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let response = client
+        .get(format!("http://127.0.0.1:{}/health", port))
+        .send()
+        .await
+        .map_err(|e| format!("Local server on port {} didn't answer: {}", port, e))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "Local server on port {} answered {}",
+            port,
+            response.status()
+        ));
+    }
+    Ok(())
+}
+
+/// Expired without a refresh having replaced it
+fn firebase_token_expired(tokens: Option<&FirebaseTokens>, now: i64) -> bool {
+    tokens.is_some_and(|t| t.expires_at <= now)
+}
+
+/// Only tokens that can be refreshed; the others are signed in again
+fn slides_token_expired(tokens: Option<&SlidesTokens>, now: i64) -> bool {
+    tokens
+        .filter(|t| t.refresh_token.is_some())
+        .and_then(|t| t.expires_at)
+        .is_some_and(|expires_at| expires_at <= now)
+}
+
+fn check_store() -> Result<(), String> {
+    // Nothing is saved until it's unlocked anyway
+    if secure_store::get_store_encryption_status().locked {
+        return Ok(());
+    }
+    let app = APP_HANDLE.read().clone().ok_or("App not initialized")?;
+    let path = tauri_plugin_store::resolve_store_path(&app, STORE_FILE)
+        .map_err(|e| format!("Failed to find store: {}", e))?;
+    if path.exists() {
+        return std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .map(|_| ())
+            .map_err(|e| format!("Store isn't writable: {}", e));
+    }
+    // Not saved yet; its directory has to take a file
+    let dir = path.parent().ok_or("Store has no directory")?;
+    std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create store directory: {}", e))?;
+    let probe = dir.join(format!(".{}.probe", STORE_FILE));
+    std::fs::write(&probe, b"").map_err(|e| format!("Store directory isn't writable: {}", e))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+async fn run_checks() -> Vec<HealthProblem> {
+    let mut problems = Vec::new();
+
+    if let Err(e) = check_server().await {
+        eprintln!("Health check: {}; restarting the local server", e);
+        crate::restart_server();
+        tokio::time::sleep(Duration::from_secs(SERVER_RESTART_WAIT_SECS)).await;
+        if let Err(e) = check_server().await {
+            problems.push(HealthProblem {
+                check: HealthCheck::Server,
+                detail: e,
+                recovery: Some("Restarted the local server".to_string()),
+            });
+        }
+    }
+
+    let now = clock::now_secs();
+    if firebase_token_expired(FIREBASE_TOKENS.read().as_ref(), now) {
+        if let Err(e) = crate::refresh_firebase_token().await {
+            problems.push(HealthProblem {
+                check: HealthCheck::FirebaseToken,
+                detail: e,
+                recovery: Some("Refreshed the session token".to_string()),
+            });
+        }
+    }
+    // A revoked grant is cleared and reported as `slides-auth-required` here
+    if slides_token_expired(SLIDES_TOKENS.read().as_ref(), now) {
+        if let Err(e) = crate::refresh_slides_token().await {
+            problems.push(HealthProblem {
+                check: HealthCheck::SlidesToken,
+                detail: e,
+                recovery: Some("Refreshed the Slides token".to_string()),
+            });
+        }
+    }
+
+    if let Err(e) = check_store() {
+        problems.push(HealthProblem {
+            check: HealthCheck::Store,
+            detail: e,
+            recovery: None,
+        });
+    }
+
+    problems
+}
+
+/// The event a check sends: on every failure, and on the first pass after
+fn health_event(was_healthy: bool, report: &HealthReport) -> Option<&'static str> {
+    if !report.problems.is_empty() {
+        Some("health-degraded")
+    } else if !was_healthy {
+        Some("health-restored")
+    } else {
+        None
+    }
+}
+
+/// Check health every `CHECK_INTERVAL_SECS` for the life of the app
+pub async fn run_health_loop() {
+    tokio::time::sleep(Duration::from_secs(FIRST_CHECK_DELAY_SECS)).await;
+    loop {
+        if sleep_wake::is_paused() {
+            tokio::time::sleep(Duration::from_secs(CHECK_INTERVAL_SECS)).await;
+            continue;
+        }
+        let problems = run_checks().await;
+        let was_healthy = REPORT.read().problems.is_empty();
+        let report = HealthReport {
+            checked_at: Some(chrono::Utc::now().timestamp()),
+            problems,
+        };
+        *REPORT.write() = report.clone();
+
+        for problem in &report.problems {
+            eprintln!(
+                "Health check failed ({:?}): {}",
+                problem.check, problem.detail
+            );
+        }
+        if let (Some(app), Some(event)) = (
+            APP_HANDLE.read().as_ref(),
+            health_event(was_healthy, &report),
+        ) {
+            let _ = app.emit(event, &report);
+        }
+
+        tokio::time::sleep(Duration::from_secs(CHECK_INTERVAL_SECS)).await;
+    }
+}
+
+/// The latest self-check
+#[tauri::command]
+pub fn get_health() -> HealthReport {
+    REPORT.read().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slides(refresh_token: Option<&str>, expires_at: Option<i64>) -> SlidesTokens {
+        SlidesTokens {
+            access_token: "access".to_string(),
+            refresh_token: refresh_token.map(str::to_string),
+            expires_at,
+            scopes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn flags_tokens_left_expired() {
+        let firebase = FirebaseTokens {
+            id_token: "id".to_string(),
+            refresh_token: "refresh".to_string(),
+            expires_at: 1000,
+            email: None,
+            local_id: "user".to_string(),
+            display_name: None,
+        };
+        assert!(!firebase_token_expired(Some(&firebase), 999));
+        assert!(firebase_token_expired(Some(&firebase), 1000));
+        assert!(!firebase_token_expired(None, 1000));
+
+        assert!(slides_token_expired(
+            Some(&slides(Some("r"), Some(1000))),
+            1000
+        ));
+        assert!(!slides_token_expired(
+            Some(&slides(Some("r"), Some(1001))),
+            1000
+        ));
+        // Nothing to refresh it with, or no known expiry
+        assert!(!slides_token_expired(Some(&slides(None, Some(1))), 1000));
+        assert!(!slides_token_expired(Some(&slides(Some("r"), None)), 1000));
+    }
+
+    #[test]
+    fn reports_degraded_then_restored_once() {
+        let failing = HealthReport {
+            checked_at: Some(1),
+            problems: vec![HealthProblem {
+                check: HealthCheck::Store,
+                detail: "Store isn't writable".to_string(),
+                recovery: None,
+            }],
+        };
+        let passing = HealthReport {
+            checked_at: Some(2),
+            problems: Vec::new(),
+        };
+        assert_eq!(health_event(true, &failing), Some("health-degraded"));
+        assert_eq!(health_event(false, &failing), Some("health-degraded"));
+        assert_eq!(health_event(false, &passing), Some("health-restored"));
+        assert_eq!(health_event(true, &passing), None);
+    }
+}
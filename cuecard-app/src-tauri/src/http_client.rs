@@ -0,0 +1,198 @@
+//! The HTTP client every outbound request goes through
+//!
+//! Corporate networks often need a proxy and trust a private root CA. A proxy
+//! set in CueCard is used for all requests; without one, `HTTPS_PROXY`,
+//! `HTTP_PROXY` and `NO_PROXY` from the environment apply. An optional PEM
+//! bundle adds root certificates on top of the system's. The client is built
+//! once and rebuilt when these settings change; clones share its connection
+//! pool. Idle connections to Google are kept open between slide changes, so
+//! a notes fetch on a slide change doesn't pay for a new TLS handshake.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+use crate::error::CueCardError;
+
+const NETWORK_SETTINGS_KEY: &str = "network_settings";
+const CONNECT_TIMEOUT_SECS: u64 = 10;
+// Between reads, not for the whole request: deck exports can take a while
+const READ_TIMEOUT_SECS: u64 = 30;
+// Kept idle longer than a slide usually stays up
+const POOL_IDLE_TIMEOUT_SECS: u64 = 300;
+const TCP_KEEPALIVE_SECS: u64 = 60;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetworkSettings {
+    /// e.g. "http://proxy.corp.example:8080", credentials included if needed
+    #[serde(default)]
+    pub proxy: Option<String>,
+    /// PEM file with extra root certificates
+    #[serde(default)]
+    pub ca_bundle_path: Option<String>,
+}
+
+static NETWORK_SETTINGS: Lazy<Arc<RwLock<NetworkSettings>>> =
+    Lazy::new(|| Arc::new(RwLock::new(NetworkSettings::default())));
+static HTTP_CLIENT: Lazy<Arc<RwLock<reqwest::Client>>> = Lazy::new(|| {
+    Arc::new(RwLock::new(
+        build(&NetworkSettings::default()).unwrap_or_default(),
+    ))
+});
+
+/// The shared client for outbound requests
+pub fn client() -> reqwest::Client {
+    HTTP_CLIENT.read().clone()
+}
+
+fn build(settings: &NetworkSettings) -> Result<reqwest::Client, String> {
+    let mut builder = reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(CONNECT_TIMEOUT_SECS))
+        .read_timeout(Duration::from_secs(READ_TIMEOUT_SECS))
+        .pool_idle_timeout(Duration::from_secs(POOL_IDLE_TIMEOUT_SECS))
+        .tcp_keepalive(Duration::from_secs(TCP_KEEPALIVE_SECS));
+
+    if let Some(proxy) = settings.proxy.as_deref().filter(|p| !p.trim().is_empty()) {
+        let proxy = reqwest::Proxy::all(proxy.trim())
+            .map_err(|e| format!("Invalid proxy {}: {}", proxy, e))?;
+        builder = builder.proxy(proxy);
+    }
+
+    if let Some(path) = settings
+        .ca_bundle_path
+        .as_deref()
+        .filter(|p| !p.trim().is_empty())
+    {
+        let pem = std::fs::read(path.trim())
+            .map_err(|e| format!("Failed to read CA bundle {}: {}", path, e))?;
+        let certificates = reqwest::Certificate::from_pem_bundle(&pem)
+            .map_err(|e| format!("Invalid CA bundle {}: {}", path, e))?;
+        if certificates.is_empty() {
+            return Err(format!("No certificates found in {}", path));
+        }
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    builder
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+pub fn load_network_settings_from_store(app: &AppHandle) {
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Some(settings) = store
+            .get(NETWORK_SETTINGS_KEY)
+            .and_then(|v| serde_json::from_value::<NetworkSettings>(v).ok())
+        {
+            // A bundle that's gone missing shouldn't stop CueCard from going online
+            match build(&settings) {
+                Ok(client) => *HTTP_CLIENT.write() = client,
+                Err(e) => eprintln!("Using default network settings: {}", e),
+            }
+            *NETWORK_SETTINGS.write() = settings;
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_network_settings() -> NetworkSettings {
+    NETWORK_SETTINGS.read().clone()
+}
+
+/// Use a proxy and extra root certificates for outbound requests
+#[tauri::command]
+pub fn set_network_settings(app: AppHandle, settings: NetworkSettings) -> Result<(), CueCardError> {
+    let client = build(&settings)?;
+    *HTTP_CLIENT.write() = client;
+    *NETWORK_SETTINGS.write() = settings;
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Ok(json) = serde_json::to_value(&*NETWORK_SETTINGS.read()) {
+            store.set(NETWORK_SETTINGS_KEY, json);
+            let _ = store.save();
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    #[test]
+    fn refuses_settings_it_cant_use() {
+        let settings = |proxy: Option<&str>, ca_bundle_path: Option<&str>| NetworkSettings {
+            proxy: proxy.map(str::to_string),
+            ca_bundle_path: ca_bundle_path.map(str::to_string),
+        };
+        assert!(build(&settings(Some(" "), Some(""))).is_ok());
+        assert!(build(&settings(Some(" http://proxy.corp.example:8080 "), None)).is_ok());
+        assert!(build(&settings(Some("http://[bad"), None)).is_err());
+
+        let missing = std::env::temp_dir().join(format!("cuecard-ca-{}", uuid::Uuid::new_v4()));
+        let path = missing.to_string_lossy().into_owned();
+        let error = build(&settings(None, Some(&path))).unwrap_err();
+        assert!(error.starts_with("Failed to read CA bundle"));
+
+        // A file with no certificates in it is as good as a wrong path
+        std::fs::write(&missing, "not a certificate\n").unwrap();
+        let error = build(&settings(None, Some(&path))).unwrap_err();
+        std::fs::remove_file(&missing).unwrap();
+        assert!(error.starts_with("No certificates found"), "{}", error);
+    }
+
+    /// A server answering `ok` to every request on a connection, and how many
+    /// connections it has accepted
+    async fn serve() -> (std::net::SocketAddr, Arc<AtomicUsize>) {
+        let listener = tokio::net::TcpListener::bind(("127.0.0.1", 0))
+            .await
+            .unwrap();
+        let address = listener.local_addr().unwrap();
+        let connections = Arc::new(AtomicUsize::new(0));
+        let accepted = connections.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                accepted.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    let (reader, mut writer) = stream.split();
+                    let mut lines = BufReader::new(reader).lines();
+                    // A blank line ends each request's headers
+                    while let Ok(Some(line)) = lines.next_line().await {
+                        if !line.is_empty() {
+                            continue;
+                        }
+                        let reply = b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok";
+                        if writer.write_all(reply).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        (address, connections)
+    }
+
+    #[test]
+    fn requests_reuse_the_open_connection() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let (address, connections) = serve().await;
+            let client = build(&NetworkSettings::default()).unwrap();
+            let url = format!("http://{}/", address);
+            for _ in 0..3 {
+                let body = client.get(&url).send().await.unwrap().text().await;
+                assert_eq!(body.unwrap(), "ok");
+                // The finished connection goes back to the pool in the background
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+            assert_eq!(connections.load(Ordering::SeqCst), 1);
+        });
+    }
+}
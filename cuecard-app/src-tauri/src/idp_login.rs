@@ -0,0 +1,263 @@
+//! Profile sign-in with Microsoft or Apple instead of Google
+//!
+//! Some organizations block personal Google sign-in, so the CueCard profile
+//! (the Firebase session) can also come from a Microsoft or Apple account.
+//! Either provider's ID token is exchanged through the same Firebase
+//! `signInWithIdp` call Google's goes through; only the OAuth dance differs.
+//! Slides access is a Google grant, so it's still requested from Google.
+//!
+//! The providers' OAuth clients come from the remote config document. Apple
+//! only redirects to HTTPS pages, and POSTs to them once a name or email is
+//! asked for, so its client names a page that forwards the `code` and `state`
+//! to this server's `/oauth/idp/callback` (as a query or a form). Apple's
+//! client secret has to be signed with the team's private key, which stays
+//! on that page's backend: it does Apple's code exchange itself and forwards
+//! only the resulting `id_token` with the `state`.
+
+use axum::extract::{Form, Query};
+use axum::response::Html;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::AppHandle;
+use tauri_plugin_opener::OpenerExt;
+use uuid::Uuid;
+
+use crate::remote_config::{self, IdpClient};
+use crate::{http_client, session_report};
+
+const MICROSOFT_AUTH_URL: &str = "https://login.microsoftonline.com/common/oauth2/v2.0/authorize";
+const MICROSOFT_TOKEN_URL: &str = "https://login.microsoftonline.com/common/oauth2/v2.0/token";
+const APPLE_AUTH_URL: &str = "https://appleid.apple.com/auth/authorize";
+
+/// Who vouches for the CueCard profile
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdentityProvider {
+    #[default]
+    Google,
+    Microsoft,
+    Apple,
+}
+
+impl IdentityProvider {
+    /// Firebase's `providerId` for `signInWithIdp`
+    pub fn firebase_provider_id(self) -> &'static str {
+        match self {
+            IdentityProvider::Google => "google.com",
+            IdentityProvider::Microsoft => "microsoft.com",
+            IdentityProvider::Apple => "apple.com",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            IdentityProvider::Google => "Google",
+            IdentityProvider::Microsoft => "Microsoft",
+            IdentityProvider::Apple => "Apple",
+        }
+    }
+
+    fn auth_url(self) -> Option<&'static str> {
+        match self {
+            IdentityProvider::Google => None,
+            IdentityProvider::Microsoft => Some(MICROSOFT_AUTH_URL),
+            IdentityProvider::Apple => Some(APPLE_AUTH_URL),
+        }
+    }
+
+    /// Where this app exchanges the code itself; Apple's is exchanged by
+    /// the backend behind its redirect page
+    fn token_url(self) -> Option<&'static str> {
+        match self {
+            IdentityProvider::Microsoft => Some(MICROSOFT_TOKEN_URL),
+            IdentityProvider::Google | IdentityProvider::Apple => None,
+        }
+    }
+}
+
+struct PendingLogin {
+    provider: IdentityProvider,
+    state: String,
+    client: IdpClient,
+}
+
+static PENDING_LOGIN: Lazy<Arc<RwLock<Option<PendingLogin>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(None)));
+
+#[derive(Debug, Deserialize)]
+pub struct IdpCallback {
+    code: Option<String>,
+    /// Set instead of `code` by a backend that did the exchange (Apple)
+    id_token: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IdpTokenResponse {
+    access_token: Option<String>,
+    id_token: Option<String>,
+}
+
+fn redirect_uri(client: &IdpClient) -> String {
+    client.redirect_uri.clone().unwrap_or_else(|| {
+        let port = crate::SERVER_PORT
+            .read()
+            .unwrap_or(crate::DEFAULT_SERVER_PORT);
+        format!("http://127.0.0.1:{}/oauth/idp/callback", port)
+    })
+}
+
+/// Open the provider's sign-in page for a CueCard profile
+pub async fn start(app: &AppHandle, provider: IdentityProvider) -> Result<(), String> {
+    let auth_url = provider
+        .auth_url()
+        .ok_or("Google sign-in goes through start_login's Google flow")?;
+    let client = remote_config::idp_client(provider).await?;
+    if provider == IdentityProvider::Apple && client.redirect_uri.is_none() {
+        return Err("Apple sign-in has no redirect page configured".to_string());
+    }
+    let state = Uuid::new_v4().simple().to_string();
+
+    let mut url = format!(
+        "{}?client_id={}&redirect_uri={}&response_type=code&state={}",
+        auth_url,
+        urlencoding::encode(&client.credentials.client_id),
+        urlencoding::encode(&redirect_uri(&client)),
+        state
+    );
+    match provider {
+        IdentityProvider::Microsoft => {
+            url.push_str("&response_mode=query&scope=");
+            url.push_str(&urlencoding::encode("openid email profile"));
+        }
+        // Apple's ID token only carries the email when it's asked for, and
+        // asking for it requires a form POST callback
+        IdentityProvider::Apple => {
+            url.push_str("&response_mode=form_post&scope=");
+            url.push_str(&urlencoding::encode("name email"));
+        }
+        IdentityProvider::Google => {}
+    }
+
+    *PENDING_LOGIN.write() = Some(PendingLogin {
+        provider,
+        state,
+        client,
+    });
+    app.opener()
+        .open_url(&url, None::<&str>)
+        .map_err(|e| format!("Failed to open browser: {}", e))
+}
+
+async fn finish_login(callback: IdpCallback) -> Result<(), String> {
+    let pending = PENDING_LOGIN
+        .write()
+        .take()
+        .ok_or("No sign-in in progress.")?;
+    if callback.state.as_deref() != Some(pending.state.as_str()) {
+        return Err("Sign-in state didn't match.".to_string());
+    }
+
+    let (id_token, access_token) = match pending.provider.token_url() {
+        Some(token_url) => {
+            let code = callback.code.ok_or("No authorization code received.")?;
+            let tokens = exchange_code(token_url, &pending.client, &code).await?;
+            (tokens.id_token, tokens.access_token)
+        }
+        None => (callback.id_token, None),
+    };
+    let id_token = id_token
+        .ok_or_else(|| format!("No ID token received from {}.", pending.provider.label()))?;
+    let firebase_tokens = crate::sign_in_with_idp(
+        pending.provider.firebase_provider_id(),
+        &id_token,
+        access_token.as_deref(),
+    )
+    .await
+    .map_err(|e| format!("Firebase authentication failed: {}", e))?;
+    crate::store_profile_session(
+        firebase_tokens,
+        pending.provider,
+        Some("profile".to_string()),
+    );
+    Ok(())
+}
+
+async fn exchange_code(
+    token_url: &str,
+    client: &IdpClient,
+    code: &str,
+) -> Result<IdpTokenResponse, String> {
+    let redirect = redirect_uri(client);
+    let response = http_client::client()
+        .post(token_url)
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", redirect.as_str()),
+            ("client_id", client.credentials.client_id.as_str()),
+            ("client_secret", client.credentials.client_secret.as_str()),
+        ])
+        .send()
+        .await
+        .map_err(|e| format!("Token exchange failed: {}", e))?;
+    if !response.status().is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("Token exchange failed: {}", error_text));
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse token response: {}", e))
+}
+
+/// The provider's redirect back to the local server after sign-in
+pub async fn oauth_callback_handler(Query(params): Query<IdpCallback>) -> Html<String> {
+    callback_page(params).await
+}
+
+/// The same callback forwarded as a form
+pub async fn oauth_form_callback_handler(Form(params): Form<IdpCallback>) -> Html<String> {
+    callback_page(params).await
+}
+
+async fn callback_page(params: IdpCallback) -> Html<String> {
+    let result = match params.error.clone() {
+        Some(error) => Err(error),
+        None => finish_login(params).await,
+    };
+
+    match result {
+        Ok(()) => Html(crate::PROFILE_PAGE_HTML.to_string()),
+        Err(e) => Html(format!(
+            r#"<!DOCTYPE html>
+            <html><head><title>Authentication Failed</title>
+            <style>body {{ font-family: system-ui; padding: 40px; text-align: center; }}</style>
+            </head><body>
+            <h1>Authentication Failed</h1>
+            <p>Error: {}</p>
+            <p>You can close this window.</p>
+            </body></html>"#,
+            session_report::escape_html(&e)
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_microsoft_codes_are_exchanged_here() {
+        assert_eq!(
+            IdentityProvider::Microsoft.token_url(),
+            Some(MICROSOFT_TOKEN_URL)
+        );
+        // Apple's secret is signed by the backend behind its redirect page
+        assert_eq!(IdentityProvider::Apple.token_url(), None);
+        assert_eq!(IdentityProvider::Google.auth_url(), None);
+    }
+}
@@ -0,0 +1,395 @@
+//! Zoom App / Teams add-in integration
+//!
+//! Meeting clients can't use the browser extension, so they pair with the app
+//! instead: the panel shows a short code (`create_integration_pairing`), the
+//! Zoom App or Teams add-in exchanges it at `/integrations/handshake` for a
+//! bearer token, then posts the slide it is sharing to `/integrations/slide`.
+//! Six digits are quick to guess through, so after `MAX_FAILED_HANDSHAKES`
+//! wrong codes every pending one is dropped and `integration-pairing-reset`
+//! tells the panel to show a new one.
+
+use axum::{
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+    routing::post,
+    Router,
+};
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::Emitter;
+use uuid::Uuid;
+
+use crate::error::CueCardError;
+use crate::{apply_slide_update, SlideData, APP_HANDLE};
+
+// Pairing codes are short-lived; the tokens they buy last for the app session
+const PAIRING_CODE_TTL_SECS: i64 = 300;
+const SUPPORTED_PLATFORMS: &[&str] = &["zoom", "teams"];
+const MAX_FAILED_HANDSHAKES: u32 = 5;
+
+#[derive(Debug, Clone)]
+struct PendingPairing {
+    platform: String,
+    expires_at: i64,
+}
+
+#[derive(Debug, Clone)]
+struct IntegrationSession {
+    id: String,
+    platform: String,
+    client_name: Option<String>,
+    connected_at: i64,
+    last_seen: i64,
+}
+
+/// Code the presenter types into the Zoom App / Teams add-in
+#[derive(Debug, Serialize, Clone)]
+pub struct IntegrationPairing {
+    pub code: String,
+    pub platform: String,
+    pub expires_at: i64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct IntegrationInfo {
+    pub id: String,
+    pub platform: String,
+    pub client_name: Option<String>,
+    pub connected_at: i64,
+    pub last_seen: i64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HandshakeRequest {
+    platform: String,
+    pairing_code: String,
+    client_name: Option<String>,
+}
+
+/// Slide context a meeting client can produce about the content it shares
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrationSlidePayload {
+    meeting_id: Option<String>,
+    presentation_id: Option<String>,
+    presentation_title: Option<String>,
+    /// 1-based position in the shared deck
+    slide_index: i32,
+    slide_id: Option<String>,
+    notes: Option<String>,
+    url: Option<String>,
+}
+
+static PENDING_PAIRINGS: Lazy<Arc<RwLock<HashMap<String, PendingPairing>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(HashMap::new())));
+// Wrong codes since the pending ones were created
+static FAILED_HANDSHAKES: Lazy<Arc<RwLock<u32>>> = Lazy::new(|| Arc::new(RwLock::new(0)));
+// Keyed by bearer token
+static INTEGRATION_SESSIONS: Lazy<Arc<RwLock<HashMap<String, IntegrationSession>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(HashMap::new())));
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/integrations/handshake", post(handshake_handler))
+        .route("/integrations/slide", post(slide_handler))
+}
+
+fn error_response(status: StatusCode, error: &str, message: &str) -> Response {
+    (
+        status,
+        Json(serde_json::json!({
+            "error": error,
+            "message": message
+        })),
+    )
+        .into_response()
+}
+
+fn integration_list() -> Vec<IntegrationInfo> {
+    let mut list: Vec<IntegrationInfo> = INTEGRATION_SESSIONS
+        .read()
+        .values()
+        .map(|s| IntegrationInfo {
+            id: s.id.clone(),
+            platform: s.platform.clone(),
+            client_name: s.client_name.clone(),
+            connected_at: s.connected_at,
+            last_seen: s.last_seen,
+        })
+        .collect();
+    list.sort_by_key(|i| i.connected_at);
+    list
+}
+
+fn emit_integrations_changed() {
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        let _ = app.emit("integrations-changed", integration_list());
+    }
+}
+
+/// Count a wrong code, dropping every pending code once there are too many.
+/// True when that dropped any.
+fn record_failed_handshake() -> bool {
+    let exhausted = {
+        let mut failed = FAILED_HANDSHAKES.write();
+        *failed += 1;
+        *failed >= MAX_FAILED_HANDSHAKES
+    };
+    if !exhausted {
+        return false;
+    }
+    *FAILED_HANDSHAKES.write() = 0;
+    let mut pending = PENDING_PAIRINGS.write();
+    let had_pending = !pending.is_empty();
+    pending.clear();
+    had_pending
+}
+
+enum Handshake {
+    Paired { token: String, platform: String },
+    Refused { codes_dropped: bool },
+}
+
+/// Trade a pairing code for a new session's bearer token
+fn redeem_pairing_code(request: HandshakeRequest, now: i64) -> Handshake {
+    let code = request.pairing_code.trim();
+    let pairing = {
+        let mut pending = PENDING_PAIRINGS.write();
+        pending.retain(|_, p| p.expires_at > now);
+        match pending.get(code) {
+            Some(p) if p.platform == request.platform => pending.remove(code),
+            _ => None,
+        }
+    };
+    let Some(pairing) = pairing else {
+        return Handshake::Refused {
+            codes_dropped: record_failed_handshake(),
+        };
+    };
+    *FAILED_HANDSHAKES.write() = 0;
+
+    let token = Uuid::new_v4().to_string();
+    INTEGRATION_SESSIONS.write().insert(
+        token.clone(),
+        IntegrationSession {
+            id: Uuid::new_v4().to_string(),
+            platform: pairing.platform.clone(),
+            client_name: request.client_name,
+            connected_at: now,
+            last_seen: now,
+        },
+    );
+    Handshake::Paired {
+        token,
+        platform: pairing.platform,
+    }
+}
+
+async fn handshake_handler(Json(request): Json<HandshakeRequest>) -> Response {
+    let now = chrono::Utc::now().timestamp();
+    let (token, platform) = match redeem_pairing_code(request, now) {
+        Handshake::Paired { token, platform } => (token, platform),
+        Handshake::Refused { codes_dropped } => {
+            if codes_dropped {
+                eprintln!("Too many wrong pairing codes; pending codes invalidated");
+                if let Some(app) = APP_HANDLE.read().as_ref() {
+                    let _ = app.emit("integration-pairing-reset", ());
+                }
+            }
+            return error_response(
+                StatusCode::UNAUTHORIZED,
+                "invalid_pairing_code",
+                "Pairing code is invalid or expired. Create a new one in CueCard.",
+            );
+        }
+    };
+    emit_integrations_changed();
+
+    Json(serde_json::json!({
+        "token": token,
+        "platform": platform,
+        "slideEndpoint": "/integrations/slide"
+    }))
+    .into_response()
+}
+
+/// The platform of the session the request's bearer token belongs to,
+/// marking the session as seen
+fn session_platform(headers: &HeaderMap) -> Option<String> {
+    let token = headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))?
+        .trim();
+    let mut sessions = INTEGRATION_SESSIONS.write();
+    let session = sessions.get_mut(token)?;
+    session.last_seen = chrono::Utc::now().timestamp();
+    Some(session.platform.clone())
+}
+
+async fn slide_handler(
+    headers: HeaderMap,
+    Json(payload): Json<IntegrationSlidePayload>,
+) -> Response {
+    let Some(platform) = session_platform(&headers) else {
+        return error_response(
+            StatusCode::UNAUTHORIZED,
+            "unauthorized",
+            "Missing or revoked integration token. Pair again from CueCard.",
+        );
+    };
+
+    let presentation_id = payload.presentation_id.unwrap_or_else(|| {
+        format!(
+            "{}:{}",
+            platform,
+            payload.meeting_id.as_deref().unwrap_or("shared")
+        )
+    });
+
+    let slide_data = SlideData {
+        presentation_id,
+        slide_id: payload
+            .slide_id
+            .unwrap_or_else(|| payload.slide_index.to_string()),
+        slide_number: payload.slide_index,
+        title: payload
+            .presentation_title
+            .unwrap_or_else(|| "Shared presentation".to_string()),
+        mode: platform,
+        timestamp: chrono::Utc::now().timestamp_millis(),
+        url: payload.url.unwrap_or_default(),
+        force_refresh: None,
+        client_id: None,
+        scraped_notes: None,
+    };
+
+    let notes = apply_slide_update(slide_data, payload.notes).await;
+
+    Json(serde_json::json!({
+        "received": true,
+        "notes": notes
+    }))
+    .into_response()
+}
+
+/// Create a pairing code for a Zoom App or Teams add-in
+#[tauri::command]
+pub fn create_integration_pairing(platform: String) -> Result<IntegrationPairing, CueCardError> {
+    if !SUPPORTED_PLATFORMS.contains(&platform.as_str()) {
+        return Err(format!("Unsupported integration platform: {}", platform).into());
+    }
+
+    let code = format!("{:06}", Uuid::new_v4().as_u128() % 1_000_000);
+    let expires_at = chrono::Utc::now().timestamp() + PAIRING_CODE_TTL_SECS;
+
+    *FAILED_HANDSHAKES.write() = 0;
+    PENDING_PAIRINGS.write().insert(
+        code.clone(),
+        PendingPairing {
+            platform: platform.clone(),
+            expires_at,
+        },
+    );
+
+    Ok(IntegrationPairing {
+        code,
+        platform,
+        expires_at,
+    })
+}
+
+#[tauri::command]
+pub fn list_integrations() -> Vec<IntegrationInfo> {
+    integration_list()
+}
+
+#[tauri::command]
+pub fn revoke_integration(id: String) -> Result<(), CueCardError> {
+    {
+        let mut sessions = INTEGRATION_SESSIONS.write();
+        let before = sessions.len();
+        sessions.retain(|_, s| s.id != id);
+        if sessions.len() == before {
+            return Err("Unknown integration".into());
+        }
+    }
+    emit_integrations_changed();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handshake(platform: &str, code: &str) -> Handshake {
+        let request = HandshakeRequest {
+            platform: platform.to_string(),
+            pairing_code: code.to_string(),
+            client_name: Some("Zoom".to_string()),
+        };
+        redeem_pairing_code(request, chrono::Utc::now().timestamp())
+    }
+
+    fn refused(handshake: Handshake) -> Option<bool> {
+        match handshake {
+            Handshake::Paired { .. } => None,
+            Handshake::Refused { codes_dropped } => Some(codes_dropped),
+        }
+    }
+
+    fn bearer(token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "authorization",
+            format!("Bearer {}", token).parse().unwrap(),
+        );
+        headers
+    }
+
+    // Pairing codes and sessions are shared state
+    #[test]
+    fn pairs_locks_out_and_checks_tokens() {
+        // A code buys a token once, and only for its platform
+        let pairing = create_integration_pairing("zoom".to_string()).unwrap();
+        assert_eq!(refused(handshake("teams", &pairing.code)), Some(false));
+        let Handshake::Paired { token, platform } =
+            handshake("zoom", &format!(" {} ", pairing.code))
+        else {
+            panic!("the pairing code was refused");
+        };
+        assert_eq!(platform, "zoom");
+        assert!(INTEGRATION_SESSIONS.read().contains_key(&token));
+        assert!(refused(handshake("zoom", &pairing.code)).is_some());
+
+        // Wrong codes drop every pending one, the right one included
+        let pairing = create_integration_pairing("teams".to_string()).unwrap();
+        let wrong = if pairing.code == "000000" {
+            "000001"
+        } else {
+            "000000"
+        };
+        for _ in 1..MAX_FAILED_HANDSHAKES {
+            assert_eq!(refused(handshake("teams", wrong)), Some(false));
+        }
+        assert_eq!(refused(handshake("teams", wrong)), Some(true));
+        assert!(PENDING_PAIRINGS.read().is_empty());
+        assert!(refused(handshake("teams", &pairing.code)).is_some());
+
+        // Unknown or missing bearer tokens are turned away
+        assert_eq!(session_platform(&bearer(&token)).as_deref(), Some("zoom"));
+        assert_eq!(session_platform(&bearer("not-a-token")), None);
+        assert_eq!(session_platform(&HeaderMap::new()), None);
+        INTEGRATION_SESSIONS.write().remove(&token);
+        assert_eq!(session_platform(&bearer(&token)), None);
+    }
+
+    #[test]
+    fn rejects_unsupported_platforms() {
+        assert!(create_integration_pairing("webex".to_string()).is_err());
+    }
+}
@@ -0,0 +1,186 @@
+//! Interpreter notes: a second notes channel for live translators
+//!
+//! Each slide can carry notes meant for the interpreter (terminology,
+//! pronunciation, what's coming). They're kept apart from the speaker notes
+//! and reach the interpreter's device read-only through an interpreter share
+//! link (`share_link`), which shows the current slide and the slide
+//! `lookahead` shown slides ahead in deck order, so the interpreter can
+//! prepare.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+use crate::error::CueCardError;
+use crate::{slide_mapping, CURRENT_SLIDE};
+
+const INTERPRETER_NOTES_KEY: &str = "interpreter_notes";
+const INTERPRETER_LOOKAHEAD_KEY: &str = "interpreter_lookahead";
+const DEFAULT_LOOKAHEAD: usize = 1;
+const MAX_LOOKAHEAD: usize = 5;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InterpreterSlide {
+    pub slide_id: String,
+    pub slide_number: i32,
+    pub notes: Option<String>,
+}
+
+/// What the interpreter's device shows
+#[derive(Debug, Clone, Serialize)]
+pub struct InterpreterView {
+    pub current: Option<InterpreterSlide>,
+    /// The slide `lookahead` positions ahead, when the deck order is known
+    pub ahead: Option<InterpreterSlide>,
+    pub lookahead: usize,
+}
+
+// Keyed by "{presentation_id}:{slide_id}"
+static INTERPRETER_NOTES: Lazy<Arc<RwLock<HashMap<String, String>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(HashMap::new())));
+static LOOKAHEAD: Lazy<Arc<RwLock<usize>>> = Lazy::new(|| Arc::new(RwLock::new(DEFAULT_LOOKAHEAD)));
+
+fn notes_for(presentation_id: &str, slide_id: &str) -> Option<String> {
+    INTERPRETER_NOTES
+        .read()
+        .get(&format!("{}:{}", presentation_id, slide_id))
+        .cloned()
+}
+
+pub fn load_interpreter_from_store(app: &AppHandle) {
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Some(notes) = store
+            .get(INTERPRETER_NOTES_KEY)
+            .and_then(|v| serde_json::from_value::<HashMap<String, String>>(v).ok())
+        {
+            *INTERPRETER_NOTES.write() = notes;
+        }
+        if let Some(lookahead) = store
+            .get(INTERPRETER_LOOKAHEAD_KEY)
+            .and_then(|v| v.as_u64())
+        {
+            *LOOKAHEAD.write() = (lookahead as usize).min(MAX_LOOKAHEAD);
+        }
+    }
+}
+
+/// Id and number of the slide `lookahead` shown slides after `slide_id`
+fn slide_ahead(order: &[String], slide_id: &str, lookahead: usize) -> Option<(String, i32)> {
+    if lookahead == 0 {
+        return None;
+    }
+    let index = order.iter().position(|id| id == slide_id)? + lookahead;
+    Some((order.get(index)?.clone(), index as i32 + 1))
+}
+
+/// Store trimmed notes under `key`, or clear them when there's no text left
+fn set_notes(notes: &mut HashMap<String, String>, key: String, text: Option<String>) {
+    match text.map(|t| t.trim().to_string()).filter(|t| !t.is_empty()) {
+        Some(text) => notes.insert(key, text),
+        None => notes.remove(&key),
+    };
+}
+
+/// The current slide and the one `lookahead` ahead, with their interpreter notes
+pub fn view() -> InterpreterView {
+    let lookahead = *LOOKAHEAD.read();
+    let Some(slide) = CURRENT_SLIDE.read().clone() else {
+        return InterpreterView {
+            current: None,
+            ahead: None,
+            lookahead,
+        };
+    };
+
+    let ahead = slide_ahead(&slide_mapping::shown_slides(), &slide.slide_id, lookahead).map(
+        |(slide_id, slide_number)| InterpreterSlide {
+            notes: notes_for(&slide.presentation_id, &slide_id),
+            slide_id,
+            slide_number,
+        },
+    );
+
+    InterpreterView {
+        current: Some(InterpreterSlide {
+            notes: notes_for(&slide.presentation_id, &slide.slide_id),
+            slide_id: slide.slide_id,
+            slide_number: slide.slide_number,
+        }),
+        ahead,
+        lookahead,
+    }
+}
+
+#[tauri::command]
+pub fn get_interpreter_notes(presentation_id: String, slide_id: String) -> Option<String> {
+    notes_for(&presentation_id, &slide_id)
+}
+
+/// Set or clear a slide's interpreter notes
+#[tauri::command]
+pub fn set_interpreter_notes(
+    app: AppHandle,
+    presentation_id: String,
+    slide_id: String,
+    text: Option<String>,
+) {
+    let key = format!("{}:{}", presentation_id, slide_id);
+    set_notes(&mut INTERPRETER_NOTES.write(), key, text);
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Ok(json) = serde_json::to_value(&*INTERPRETER_NOTES.read()) {
+            store.set(INTERPRETER_NOTES_KEY, json);
+            let _ = store.save();
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_interpreter_lookahead() -> usize {
+    *LOOKAHEAD.read()
+}
+
+/// How many slides ahead the interpreter sees, 0 for the current slide only
+#[tauri::command]
+pub fn set_interpreter_lookahead(app: AppHandle, lookahead: usize) -> Result<(), CueCardError> {
+    if lookahead > MAX_LOOKAHEAD {
+        return Err(format!("Lookahead can be at most {} slides", MAX_LOOKAHEAD).into());
+    }
+    *LOOKAHEAD.write() = lookahead;
+    if let Ok(store) = app.store("cuecard-store.json") {
+        store.set(INTERPRETER_LOOKAHEAD_KEY, lookahead);
+        let _ = store.save();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn looks_ahead_in_shown_slide_order() {
+        let order: Vec<String> = ["s1", "s2", "s4"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(slide_ahead(&order, "s1", 1), Some(("s2".to_string(), 2)));
+        assert_eq!(slide_ahead(&order, "s1", 2), Some(("s4".to_string(), 3)));
+        assert_eq!(slide_ahead(&order, "s2", 2), None);
+        assert_eq!(slide_ahead(&order, "s1", 0), None);
+        assert_eq!(slide_ahead(&order, "s3", 1), None);
+    }
+
+    #[test]
+    fn blank_notes_clear_the_slide() {
+        let mut notes = HashMap::new();
+        set_notes(
+            &mut notes,
+            "d:s1".to_string(),
+            Some("  Say \"Q-bit\"  ".to_string()),
+        );
+        assert_eq!(notes["d:s1"], "Say \"Q-bit\"");
+        set_notes(&mut notes, "d:s1".to_string(), Some(" ".to_string()));
+        assert!(notes.is_empty());
+    }
+}
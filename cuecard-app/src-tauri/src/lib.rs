@@ -4,13 +4,107 @@
 //! - Firebase Authentication with Google provider
 //! - Google Slides API integration
 //! - Local web server for browser extension communication
+//! - Zoom App / Teams add-in integration endpoints (`integrations`)
 //! - Tauri commands for frontend interaction
 //! - macOS window management (opacity, screenshot protection)
+//!
+//! The rest is split into modules, each described in its own header:
+//! - Sign-in: `idp_login`, `device_login`, `remote_config`, `scopes`,
+//!   `auto_sign_out`
+//! - Fetching and caching decks: `disk_cache`, `preload`, `focus_revalidate`,
+//!   `revision_poll`, `canva`, `public_export`, `providers`
+//! - Notes: `notes_pipeline`, `notes_sources`, `notes_freshness`,
+//!   `notes_audit`, `notes_masking`, `rich_notes`, `notes_summary`,
+//!   `notes_translation`, `api_keys`, `glossary`, `notes_check`,
+//!   `notes_export`, `script_audio`
+//! - Presenting: `session`, `timer`, `countdown_cues`, `rehearsal`,
+//!   `event_time`, `session_report`, `slide_skips`, `notes_history`,
+//!   `slide_mapping`, `slide_inference`
+//! - Sharing the notes: `share_link`, `interpreter`, `stage_display`
+//! - Local data: `retention`, `data_export`, `secure_store`, `config_file`
+//! - Network: `http_client`, `low_data`, `api_usage`, `connectivity`
+//! - Desktop: `deep_link`, `clipboard_watch`, `panel_behavior`, `topmost`,
+//!   `screenshot_protection`, `profiles`, `webview_watchdog`,
+//!   `single_instance`, `audio_output`
+//! - Upkeep: `health`, `sleep_wake`, `changelog`, `clock`, `error`
+//!
+//! Window, panel and global shortcut code is gated behind the `desktop`
+//! feature (on by default) so the rest builds and tests headlessly.
+
+mod api_keys;
+mod api_usage;
+mod audio_output;
+mod auto_sign_out;
+mod canva;
+mod changelog;
+#[cfg(feature = "desktop")]
+mod clipboard_watch;
+mod clock;
+mod config_file;
+mod connectivity;
+mod countdown_cues;
+mod data_export;
+#[cfg(feature = "desktop")]
+mod deep_link;
+mod device_login;
+mod disk_cache;
+mod error;
+mod event_time;
+mod focus_revalidate;
+mod glossary;
+mod health;
+mod http_client;
+mod idp_login;
+mod integrations;
+mod interpreter;
+mod low_data;
+mod notes_audit;
+mod notes_check;
+mod notes_export;
+mod notes_freshness;
+mod notes_history;
+mod notes_masking;
+mod notes_pipeline;
+mod notes_sources;
+mod notes_summary;
+mod notes_translation;
+#[cfg(feature = "desktop")]
+mod panel_behavior;
+mod preload;
+#[cfg(feature = "desktop")]
+mod profiles;
+mod providers;
+mod public_export;
+mod rehearsal;
+mod remote_config;
+mod retention;
+mod revision_poll;
+mod rich_notes;
+mod scopes;
+mod screenshot_protection;
+mod script_audio;
+mod secure_store;
+mod session;
+mod session_report;
+mod share_link;
+#[cfg(feature = "desktop")]
+mod single_instance;
+mod sleep_wake;
+mod slide_inference;
+mod slide_mapping;
+mod slide_skips;
+mod stage_display;
+mod timer;
+#[cfg(feature = "desktop")]
+mod topmost;
+#[cfg(feature = "desktop")]
+mod webview_watchdog;
 
 use axum::{
-    extract::Query,
+    extract::{DefaultBodyLimit, Query, Request},
     http::StatusCode,
-    response::{Html, Json, Redirect},
+    middleware::{self, Next},
+    response::{Html, IntoResponse, Json, Redirect, Response},
     routing::{get, post},
     Router,
 };
@@ -19,16 +113,21 @@ use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::net::IpAddr::V4;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-#[cfg(target_os = "macos")]
+#[cfg(all(target_os = "macos", feature = "desktop"))]
 use tauri::WebviewWindow;
 use tauri::{AppHandle, Emitter, Manager};
-#[cfg(target_os = "macos")]
-use tauri_nspanel::{tauri_panel, CollectionBehavior, PanelLevel, StyleMask, WebviewWindowExt};
+#[cfg(all(target_os = "macos", feature = "desktop"))]
+use tauri_nspanel::{tauri_panel, PanelLevel, StyleMask, WebviewWindowExt};
+#[cfg(feature = "desktop")]
 use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut};
 use tauri_plugin_opener::OpenerExt;
 use tauri_plugin_store::StoreExt;
-use tower_http::cors::{Any, CorsLayer};
+use tower_http::catch_panic::CatchPanicLayer;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+
+use error::CueCardError;
 use uuid::Uuid;
 
 // =============================================================================
@@ -38,7 +137,6 @@ use uuid::Uuid;
 // OAuth2 Configuration
 const GOOGLE_AUTH_URL: &str = "https://accounts.google.com/o/oauth2/v2/auth";
 const GOOGLE_TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
-const REDIRECT_URI: &str = "http://127.0.0.1:3642/oauth/callback";
 
 // Firebase REST API endpoints
 const FIREBASE_SIGNUP_URL: &str = "https://identitytoolkit.googleapis.com/v1/accounts:signUp";
@@ -51,9 +149,32 @@ const GA_COLLECT_URL: &str = "https://www.google-analytics.com/mp/collect";
 const ANALYTICS_CLIENT_ID_KEY: &str = "analytics_client_id";
 const ANALYTICS_FIRST_OPEN_KEY: &str = "analytics_first_open_sent";
 
+// Browser extension compatibility (supported: MIN <= version < MAX)
+const EXTENSION_VERSION_HEADER: &str = "x-cuecard-extension-version";
+const MIN_EXTENSION_VERSION: &str = "1.1.1";
+const MAX_EXTENSION_VERSION: &str = "2.0.0";
+
+// Browser clients (one per extension profile) not heard from in this long are dropped
+const CLIENT_STALE_SECS: i64 = 300;
+
+// Local server supervision
+const SERVER_RESTART_MAX_DELAY_SECS: u64 = 30;
+const SERVER_STABLE_RUN_SECS: u64 = 60;
+
+// Local server port. Without a configured port, the default and the next few are
+// tried in order; the extension discovers the app by probing `/health` on the same
+// range. A configured port of 0 asks the OS for any free port.
+const DEFAULT_SERVER_PORT: u16 = 3642;
+const SERVER_PORT_CANDIDATES: u16 = 10;
+const SERVER_PORT_KEY: &str = "server_port";
+
 // Scopes
 const SCOPE_PROFILE: &str = "openid profile email";
 const SCOPE_SLIDES: &str = "https://www.googleapis.com/auth/presentations.readonly";
+/// Opt-in, for editing speaker notes from the panel; covers reading too
+const SCOPE_SLIDES_WRITE: &str = "https://www.googleapis.com/auth/presentations";
+/// Opt-in, for listing recent decks in the presentation picker
+const SCOPE_DRIVE: &str = "https://www.googleapis.com/auth/drive.readonly";
 
 // =============================================================================
 // DATA TYPES
@@ -139,6 +260,42 @@ pub struct SlidesTokens {
     pub access_token: String,
     pub refresh_token: Option<String>,
     pub expires_at: Option<i64>,
+    /// Scopes Google reports as granted; empty for tokens saved before these
+    /// were tracked, which were always granted `SCOPE_SLIDES`
+    #[serde(default)]
+    pub scopes: Vec<String>,
+}
+
+/// Canva Connect tokens, from their own grant (see `canva`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanvaTokens {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    pub expires_at: Option<i64>,
+}
+
+/// Slides API scopes granted so far, and the ones still needed
+#[derive(Debug, Clone, Serialize)]
+pub struct SlidesScopes {
+    pub granted: Vec<String>,
+    pub missing: Vec<String>,
+}
+
+/// The signed-in session as stored, for the settings screen
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AuthDetails {
+    pub signed_in: bool,
+    pub email: Option<String>,
+    pub display_name: Option<String>,
+    /// Unix seconds the Firebase ID token expires at
+    pub firebase_expires_at: Option<i64>,
+    pub has_firebase_refresh_token: bool,
+    pub slides_authorized: bool,
+    /// Unix seconds the Slides access token expires at, when Google said
+    pub slides_expires_at: Option<i64>,
+    pub has_slides_refresh_token: bool,
+    /// Slides grant scopes, as last reported by Google
+    pub scopes: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -152,6 +309,11 @@ pub struct SlideData {
     pub timestamp: i64,
     pub url: String,
     pub force_refresh: Option<bool>,
+    /// Identifies the browser profile the extension runs in
+    pub client_id: Option<String>,
+    /// Notes the extension read from the page itself, merged per `notes_sources`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scraped_notes: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -159,12 +321,105 @@ pub struct ApiResponse {
     received: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     notes: Option<String>,
+    /// False when the update came from a browser profile that isn't being followed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    active: Option<bool>,
+}
+
+#[derive(Debug, Clone)]
+struct ClientState {
+    slide: SlideData,
+    last_seen: i64,
+}
+
+/// A browser profile currently sending slide updates
+#[derive(Debug, Serialize, Clone)]
+pub struct ConnectedClient {
+    pub client_id: String,
+    pub presentation_title: String,
+    pub last_seen: i64,
+    pub is_active: bool,
+    pub current_slide: SlideData,
 }
 
 #[derive(Debug, Serialize, Clone)]
 pub struct SlideUpdateEvent {
     pub slide_data: SlideData,
     pub notes: Option<String>,
+    /// Which sources the displayed notes were taken from, in display order
+    pub notes_provenance: Vec<notes_sources::NotesProvenance>,
+    /// Sensitive terms in `notes` have been masked
+    pub notes_masked: bool,
+    /// `notes` with their formatting, when they're the slide's own notes unchanged
+    pub rich_notes: Option<rich_notes::RichNotes>,
+    /// `notes` in the language the deck is translated into, once translated
+    pub translated_notes: Option<notes_translation::TranslatedNotes>,
+    /// Notes of the next slide shown in the deck, prepared like `notes`
+    pub next_slide_notes: Option<String>,
+    pub next_slide_title: Option<String>,
+    /// Session reminders shown on every slide
+    pub pinned_notes: Vec<session::PinnedNote>,
+    /// `[countdown mm:ss]` tags in `notes`, started as the slide is shown
+    pub countdown_cues: Vec<countdown_cues::CountdownCue>,
+    /// Google Slides can't be reached, so `notes` are the cached copy
+    pub offline: bool,
+    /// When and at which revision Google Slides notes were read
+    pub notes_freshness: Option<notes_freshness::NotesFreshness>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrefetchPhase {
+    Downloading,
+    Processing,
+    Done,
+    Cancelled,
+    Failed,
+}
+
+/// How whole-deck prefetches talk to the Slides API
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotesFetchMode {
+    /// One `presentations.get` for the entire deck
+    #[default]
+    Presentation,
+    /// `presentations.pages.get` per slide with bounded concurrency, current slide first
+    PerPage,
+}
+
+/// Progress of a whole-deck notes prefetch; `prefetch_id` is what `cancel_prefetch` takes
+#[derive(Debug, Serialize, Clone)]
+pub struct PrefetchProgressEvent {
+    pub prefetch_id: String,
+    pub presentation_id: String,
+    pub phase: PrefetchPhase,
+    pub slides_processed: usize,
+    pub slides_total: usize,
+    pub bytes_received: u64,
+    /// From Content-Length; Google often sends the response chunked without one
+    pub bytes_total: Option<u64>,
+}
+
+/// How the connected browser extension's version relates to the supported range
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtensionCompatibility {
+    Supported,
+    /// Older than MIN_EXTENSION_VERSION; requests are rejected until updated
+    Outdated,
+    /// Newer than this app understands; requests are served best-effort
+    AppOutdated,
+    /// Extension predates version reporting
+    Unknown,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ExtensionCompatibilityEvent {
+    pub version: Option<String>,
+    pub status: ExtensionCompatibility,
+    pub min_version: String,
+    pub max_version: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -228,7 +483,34 @@ static SLIDE_NOTES: Lazy<Arc<RwLock<HashMap<String, String>>>> =
     Lazy::new(|| Arc::new(RwLock::new(HashMap::new())));
 static CURRENT_PRESENTATION_ID: Lazy<Arc<RwLock<Option<String>>>> =
     Lazy::new(|| Arc::new(RwLock::new(None)));
+// Slide ids of the current presentation in deck order, once known
+static SLIDE_ORDER: Lazy<Arc<RwLock<Vec<String>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(Vec::new())));
 static APP_HANDLE: Lazy<Arc<RwLock<Option<AppHandle>>>> = Lazy::new(|| Arc::new(RwLock::new(None)));
+static CONNECTED_CLIENTS: Lazy<Arc<RwLock<HashMap<String, ClientState>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(HashMap::new())));
+static ACTIVE_CLIENT_ID: Lazy<Arc<RwLock<Option<String>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(None)));
+static NOTES_FETCH_MODE: Lazy<Arc<RwLock<NotesFetchMode>>> =
+    Lazy::new(|| Arc::new(RwLock::new(NotesFetchMode::default())));
+// Port from settings, and the port the local server actually bound
+static SERVER_PORT_SETTING: Lazy<Arc<RwLock<Option<u16>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(None)));
+static SERVER_PORT: Lazy<Arc<RwLock<Option<u16>>>> = Lazy::new(|| Arc::new(RwLock::new(None)));
+static SERVER_RESTART: Lazy<tokio::sync::Notify> = Lazy::new(tokio::sync::Notify::new);
+// Last set through the window commands; the panel starts protected
+// (tauri.conf.json) with its shortcuts registered
+static SCREENSHOT_PROTECTION: Lazy<Arc<RwLock<bool>>> = Lazy::new(|| Arc::new(RwLock::new(true)));
+#[cfg(feature = "desktop")]
+static SHORTCUTS_ENABLED: Lazy<Arc<RwLock<bool>>> = Lazy::new(|| Arc::new(RwLock::new(true)));
+
+type CancelFlag = Arc<AtomicBool>;
+
+// Running prefetches by id, with their cancellation flags
+static ACTIVE_PREFETCHES: Lazy<Arc<RwLock<HashMap<String, CancelFlag>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(HashMap::new())));
+static EXTENSION_STATUS: Lazy<Arc<RwLock<Option<ExtensionCompatibilityEvent>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(None)));
 
 // Firebase and OAuth state
 static FIREBASE_CONFIG: Lazy<Arc<RwLock<Option<FirebaseConfig>>>> =
@@ -243,6 +525,8 @@ static OAUTH_CREDENTIALS: Lazy<Arc<RwLock<Option<OAuthCredentials>>>> =
     Lazy::new(|| Arc::new(RwLock::new(None)));
 static SLIDES_TOKENS: Lazy<Arc<RwLock<Option<SlidesTokens>>>> =
     Lazy::new(|| Arc::new(RwLock::new(None)));
+static CANVA_TOKENS: Lazy<Arc<RwLock<Option<CanvaTokens>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(None)));
 static PENDING_OAUTH_SCOPE: Lazy<Arc<RwLock<Option<String>>>> =
     Lazy::new(|| Arc::new(RwLock::new(None)));
 
@@ -313,7 +597,7 @@ async fn sign_in_anonymously() -> Result<String, String> {
 
     let url = format!("{}?key={}", FIREBASE_SIGNUP_URL, config.api_key);
 
-    let client = reqwest::Client::new();
+    let client = http_client::client();
     let response = client
         .post(&url)
         .json(&serde_json::json!({"returnSecureToken": true}))
@@ -334,68 +618,12 @@ async fn sign_in_anonymously() -> Result<String, String> {
     Ok(sign_up_response.id_token)
 }
 
-/// Fetch OAuth credentials from Firestore Configs/v-1
-async fn fetch_oauth_credentials(firebase_token: &str) -> Result<OAuthCredentials, String> {
-    let config = FIREBASE_CONFIG
-        .read()
-        .clone()
-        .ok_or("Firebase config not loaded")?;
-
-    let url = format!(
-        "https://firestore.googleapis.com/v1/projects/{}/databases/(default)/documents/Configs/v-1",
-        config.project_id
-    );
-
-    let client = reqwest::Client::new();
-    let response = client
-        .get(&url)
-        .header("Authorization", format!("Bearer {}", firebase_token))
-        .send()
-        .await
-        .map_err(|e| format!("Failed to fetch OAuth credentials: {}", e))?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!(
-            "Failed to fetch Configs/v-1: {} - {}",
-            status, error_text
-        ));
-    }
-
-    let doc: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse Firestore response: {}", e))?;
-
-    // Extract fields from Firestore document format
-    let fields = doc
-        .get("fields")
-        .ok_or("No fields in Configs/v-1 document")?;
-
-    let client_id = fields
-        .get("googleClientId")
-        .and_then(|v| v.get("stringValue"))
-        .and_then(|v| v.as_str())
-        .ok_or("googleClientId not found in Configs/v-1")?
-        .to_string();
-
-    let client_secret = fields
-        .get("googleClientSecret")
-        .and_then(|v| v.get("stringValue"))
-        .and_then(|v| v.as_str())
-        .ok_or("googleClientSecret not found in Configs/v-1")?
-        .to_string();
-
-    Ok(OAuthCredentials {
-        client_id,
-        client_secret,
-    })
-}
-
-/// Exchange Google ID token for Firebase ID token
-async fn exchange_google_token_for_firebase(
-    google_id_token: &str,
+/// Exchange an identity provider's ID token (Firebase `providerId`, e.g.
+/// "google.com") for Firebase tokens
+async fn sign_in_with_idp(
+    provider_id: &str,
+    id_token: &str,
+    access_token: Option<&str>,
 ) -> Result<FirebaseTokens, String> {
     let config = FIREBASE_CONFIG
         .read()
@@ -404,9 +632,19 @@ async fn exchange_google_token_for_firebase(
 
     let url = format!("{}?key={}", FIREBASE_SIGNIN_IDP_URL, config.api_key);
 
-    let post_body = format!("id_token={}&providerId=google.com", google_id_token);
+    let mut post_body = format!(
+        "id_token={}&providerId={}",
+        urlencoding::encode(id_token),
+        provider_id
+    );
+    if let Some(access_token) = access_token {
+        post_body.push_str(&format!(
+            "&access_token={}",
+            urlencoding::encode(access_token)
+        ));
+    }
 
-    let client = reqwest::Client::new();
+    let client = http_client::client();
     let response = client
         .post(&url)
         .json(&serde_json::json!({
@@ -430,7 +668,7 @@ async fn exchange_google_token_for_firebase(
         .map_err(|e| format!("Failed to parse signInWithIdp response: {}", e))?;
 
     let expires_in: i64 = idp_response.expires_in.parse().unwrap_or(3600);
-    let expires_at = chrono::Utc::now().timestamp() + expires_in;
+    let expires_at = clock::now_secs() + expires_in;
 
     Ok(FirebaseTokens {
         id_token: idp_response.id_token,
@@ -459,7 +697,7 @@ async fn refresh_firebase_token() -> Result<(), String> {
 
     let url = format!("{}?key={}", FIREBASE_TOKEN_URL, config.api_key);
 
-    let client = reqwest::Client::new();
+    let client = http_client::client();
     let response = client
         .post(&url)
         .header("Content-Type", "application/x-www-form-urlencoded")
@@ -469,7 +707,10 @@ async fn refresh_firebase_token() -> Result<(), String> {
         ))
         .send()
         .await
-        .map_err(|e| format!("Firebase token refresh failed: {}", e))?;
+        .map_err(|e| {
+            connectivity::network_error(format!("Firebase token refresh failed: {}", e))
+        })?;
+    connectivity::record_success();
 
     if !response.status().is_success() {
         let error_text = response.text().await.unwrap_or_default();
@@ -482,7 +723,7 @@ async fn refresh_firebase_token() -> Result<(), String> {
         .map_err(|e| format!("Failed to parse refresh response: {}", e))?;
 
     let expires_in: i64 = refresh_response.expires_in.parse().unwrap_or(3600);
-    let expires_at = chrono::Utc::now().timestamp() + expires_in;
+    let expires_at = clock::now_secs() + expires_in;
 
     // Update tokens
     {
@@ -513,8 +754,7 @@ async fn get_valid_firebase_token() -> Option<String> {
     };
 
     // Check if token is expired or about to expire (within 5 minutes)
-    let now = chrono::Utc::now().timestamp();
-    let is_expired = now >= expires_at - 300;
+    let is_expired = clock::is_due(expires_at, 300);
 
     if is_expired {
         if let Err(e) = refresh_firebase_token().await {
@@ -540,14 +780,14 @@ async fn exchange_code_for_google_tokens(code: &str) -> Result<GoogleTokenRespon
         .clone()
         .ok_or("OAuth credentials not available")?;
 
-    let client = reqwest::Client::new();
+    let client = http_client::client();
     let response = client
         .post(GOOGLE_TOKEN_URL)
         .form(&[
             ("code", code),
             ("client_id", &credentials.client_id),
             ("client_secret", &credentials.client_secret),
-            ("redirect_uri", REDIRECT_URI),
+            ("redirect_uri", redirect_uri().as_str()),
             ("grant_type", "authorization_code"),
         ])
         .send()
@@ -582,7 +822,7 @@ async fn refresh_slides_token() -> Result<(), String> {
             .ok_or("No Slides refresh token available")?
     };
 
-    let client = reqwest::Client::new();
+    let client = http_client::client();
     let response = client
         .post(GOOGLE_TOKEN_URL)
         .form(&[
@@ -593,10 +833,18 @@ async fn refresh_slides_token() -> Result<(), String> {
         ])
         .send()
         .await
-        .map_err(|e| format!("Token refresh failed: {}", e))?;
+        .map_err(|e| connectivity::network_error(format!("Token refresh failed: {}", e)))?;
+    connectivity::record_success();
 
     if !response.status().is_success() {
         let error_text = response.text().await.unwrap_or_default();
+        let revoked = serde_json::from_str::<serde_json::Value>(&error_text)
+            .ok()
+            .and_then(|v| v.get("error")?.as_str().map(|e| e == "invalid_grant"))
+            .unwrap_or(false);
+        if revoked {
+            clear_revoked_slides_tokens();
+        }
         return Err(format!("Token refresh failed: {}", error_text));
     }
 
@@ -607,7 +855,7 @@ async fn refresh_slides_token() -> Result<(), String> {
 
     let expires_at = token_response
         .expires_in
-        .map(|secs| chrono::Utc::now().timestamp() + secs);
+        .map(|secs| clock::now_secs() + secs);
 
     // Update tokens
     {
@@ -618,6 +866,9 @@ async fn refresh_slides_token() -> Result<(), String> {
                 t.refresh_token = token_response.refresh_token;
             }
             t.expires_at = expires_at;
+            if let Some(scope) = &token_response.scope {
+                t.scopes = scope.split_whitespace().map(str::to_string).collect();
+            }
         }
     }
 
@@ -629,6 +880,19 @@ async fn refresh_slides_token() -> Result<(), String> {
     Ok(())
 }
 
+/// The Slides grant was revoked or expired for good: drop the tokens and ask
+/// the frontend to have the user authorize Slides access again
+fn clear_revoked_slides_tokens() {
+    *SLIDES_TOKENS.write() = None;
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        if let Ok(store) = app.store("cuecard-store.json") {
+            store.delete("slides_tokens");
+            let _ = store.save();
+        }
+        let _ = app.emit("slides-auth-required", "invalid_grant");
+    }
+}
+
 /// Get valid Slides API access token (refreshes if needed)
 async fn get_valid_slides_token() -> Option<String> {
     let (access_token, expires_at, has_refresh) = {
@@ -644,8 +908,7 @@ async fn get_valid_slides_token() -> Option<String> {
     };
 
     // Check if token is expired or about to expire (within 5 minutes)
-    let now = chrono::Utc::now().timestamp();
-    let is_expired = expires_at.map(|exp| now >= exp - 300).unwrap_or(false);
+    let is_expired = expires_at.is_some_and(|exp| clock::is_due(exp, 300));
 
     if is_expired && has_refresh {
         if let Err(e) = refresh_slides_token().await {
@@ -660,6 +923,222 @@ async fn get_valid_slides_token() -> Option<String> {
     Some(access_token)
 }
 
+/// `get_valid_slides_token`, saying why there's no token
+async fn slides_access_token() -> Result<String, CueCardError> {
+    if SLIDES_TOKENS.read().is_none() {
+        return Err(CueCardError::ScopeMissing(
+            "Slides access hasn't been authorized".to_string(),
+        ));
+    }
+    match get_valid_slides_token().await {
+        Some(token) => Ok(token),
+        // A revoked grant clears the tokens; otherwise the refresh didn't get through
+        None if SLIDES_TOKENS.read().is_none() => Err(CueCardError::AuthRequired(
+            "Slides access was revoked; authorize it again".to_string(),
+        )),
+        None => Err(CueCardError::Network(
+            "Couldn't refresh Slides access".to_string(),
+        )),
+    }
+}
+
+/// Google Slides can't be reached for now: there's no connection, or the
+/// access token ran out and couldn't be refreshed. Cached notes are served
+/// as they are.
+fn slides_offline() -> bool {
+    if connectivity::state() != connectivity::ConnectivityState::Online {
+        return true;
+    }
+    let now = clock::now_secs();
+    SLIDES_TOKENS
+        .read()
+        .as_ref()
+        .and_then(|t| t.expires_at)
+        .is_some_and(|expires_at| expires_at <= now)
+}
+
+// Background refresh runs ahead of the 5 minute margin used on demand
+const TOKEN_REFRESH_AHEAD_SECS: i64 = 600;
+const TOKEN_REFRESH_CHECK_SECS: u64 = 60;
+
+#[derive(Debug, Clone, Serialize)]
+struct TokenEvent {
+    /// "firebase" or "slides"
+    kind: &'static str,
+    expires_at: Option<i64>,
+}
+
+fn emit_token_event(event: &str, kind: &'static str, expires_at: Option<i64>) {
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        let _ = app.emit(event, TokenEvent { kind, expires_at });
+    }
+}
+
+/// Whether a token expiring at `expires_at` is due for a background refresh
+fn refresh_due(expires_at: i64, now: i64) -> bool {
+    expires_at - now <= TOKEN_REFRESH_AHEAD_SECS
+}
+
+/// Sends `token-expired` once per token running out, until it's refreshed again
+#[derive(Debug, Default)]
+struct ExpiryNotice {
+    notified: bool,
+}
+
+impl ExpiryNotice {
+    fn refreshed(&mut self) {
+        self.notified = false;
+    }
+
+    /// After a failed refresh: whether to report the token as expired now
+    fn failed(&mut self, expires_at: i64, now: i64) -> bool {
+        if now < expires_at || self.notified {
+            return false;
+        }
+        self.notified = true;
+        true
+    }
+}
+
+/// Firebase's answers to a refresh token that will never work again
+fn is_firebase_refresh_rejected(error: &str) -> bool {
+    [
+        "INVALID_REFRESH_TOKEN",
+        "TOKEN_EXPIRED",
+        "USER_DISABLED",
+        "USER_NOT_FOUND",
+    ]
+    .iter()
+    .any(|code| error.contains(code))
+}
+
+/// Bring restored tokens up to date and send `auth-status` with the real
+/// sign-in state. A session Firebase rejects is signed out; one that can't be
+/// refreshed for now (offline) is kept for the refresh loop to retry.
+async fn restore_session() {
+    let now = clock::now_secs();
+
+    let firebase_expires_at = FIREBASE_TOKENS.read().as_ref().map(|t| t.expires_at);
+    if let Some(expires_at) = firebase_expires_at {
+        if refresh_due(expires_at, now) {
+            match refresh_firebase_token().await {
+                Ok(()) => {}
+                Err(e) if is_firebase_refresh_rejected(&e) => {
+                    eprintln!("Stored session was rejected, signing out: {}", e);
+                    *FIREBASE_TOKENS.write() = None;
+                    if let Some(app) = APP_HANDLE.read().as_ref() {
+                        if let Ok(store) = app.store("cuecard-store.json") {
+                            store.delete("firebase_tokens");
+                            let _ = store.save();
+                        }
+                    }
+                }
+                Err(e) => eprintln!("Couldn't refresh the stored session yet: {}", e),
+            }
+        }
+    }
+
+    // A revoked grant is cleared and reported by the refresh itself
+    let slides_expires_at = SLIDES_TOKENS
+        .read()
+        .as_ref()
+        .filter(|t| t.refresh_token.is_some())
+        .and_then(|t| t.expires_at);
+    if let Some(expires_at) = slides_expires_at {
+        if refresh_due(expires_at, now) {
+            if let Err(e) = refresh_slides_token().await {
+                eprintln!("Couldn't refresh the stored Slides access yet: {}", e);
+            }
+        }
+    }
+
+    let status = match FIREBASE_TOKENS.read().as_ref() {
+        Some(tokens) => serde_json::json!({
+            "authenticated": true,
+            "user_name": tokens.display_name,
+            "user_email": tokens.email,
+            "slides_authorized": SLIDES_TOKENS.read().is_some(),
+            "restored": true
+        }),
+        None => serde_json::json!({
+            "authenticated": false,
+            "user_name": null,
+            "restored": true
+        }),
+    };
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        let _ = app.emit("auth-status", status);
+    }
+}
+
+/// Refresh tokens a few minutes before they expire so the first request after a
+/// long idle doesn't wait on it. Emits `token-refreshed`, or `token-expired` once
+/// a token has run out without a successful refresh. Runs for the lifetime of the
+/// app, after restoring the session from the store.
+async fn run_token_refresh_loop() {
+    restore_session().await;
+
+    let mut firebase_expiry = ExpiryNotice::default();
+    let mut slides_expiry = ExpiryNotice::default();
+
+    loop {
+        // Waking handles tokens itself once the network is back
+        if sleep_wake::is_paused() {
+            tokio::time::sleep(std::time::Duration::from_secs(TOKEN_REFRESH_CHECK_SECS)).await;
+            continue;
+        }
+        let now = clock::now_secs();
+
+        let firebase_expires_at = FIREBASE_TOKENS.read().as_ref().map(|t| t.expires_at);
+        if let Some(expires_at) = firebase_expires_at {
+            if refresh_due(expires_at, now) {
+                match refresh_firebase_token().await {
+                    Ok(()) => {
+                        firebase_expiry.refreshed();
+                        let expires_at = FIREBASE_TOKENS.read().as_ref().map(|t| t.expires_at);
+                        emit_token_event("token-refreshed", "firebase", expires_at);
+                    }
+                    Err(e) => {
+                        eprintln!("Background Firebase token refresh failed: {}", e);
+                        if firebase_expiry.failed(expires_at, now) {
+                            emit_token_event("token-expired", "firebase", Some(expires_at));
+                        }
+                    }
+                }
+            }
+        }
+
+        let slides = SLIDES_TOKENS
+            .read()
+            .as_ref()
+            .map(|t| (t.expires_at, t.refresh_token.is_some()));
+        if let Some((Some(expires_at), has_refresh)) = slides {
+            if refresh_due(expires_at, now) {
+                let refreshed = if has_refresh {
+                    refresh_slides_token().await
+                } else {
+                    Err("No Slides refresh token available".to_string())
+                };
+                match refreshed {
+                    Ok(()) => {
+                        slides_expiry.refreshed();
+                        let expires_at = SLIDES_TOKENS.read().as_ref().and_then(|t| t.expires_at);
+                        emit_token_event("token-refreshed", "slides", expires_at);
+                    }
+                    Err(e) => {
+                        eprintln!("Background Slides token refresh failed: {}", e);
+                        if slides_expiry.failed(expires_at, now) {
+                            emit_token_event("token-expired", "slides", Some(expires_at));
+                        }
+                    }
+                }
+            }
+        }
+
+        tokio::time::sleep(std::time::Duration::from_secs(TOKEN_REFRESH_CHECK_SECS)).await;
+    }
+}
+
 // =============================================================================
 // TOKEN STORAGE
 // =============================================================================
@@ -704,27 +1183,161 @@ fn clear_all_tokens_from_store(app: &AppHandle) {
     if let Ok(store) = app.store("cuecard-store.json") {
         let _ = store.delete("firebase_tokens");
         let _ = store.delete("slides_tokens");
+        let _ = store.delete("canva_tokens");
         let _ = store.delete("oauth_credentials");
         let _ = store.save();
     }
 }
 
+/// Everything restored from the store at startup (and again once an encrypted
+/// store is unlocked)
+fn load_settings_from_store(app: &AppHandle) {
+    load_tokens_from_store(app);
+    remote_config::load_config_cache_from_store(app);
+    api_usage::load_api_usage_from_store(app);
+    http_client::load_network_settings_from_store(app);
+    notes_sources::load_merge_rules_from_store(app);
+    notes_audit::load_history_from_store(app);
+    notes_pipeline::load_pipeline_from_store(app);
+    notes_masking::load_masking_from_store(app);
+    glossary::load_glossary_from_store(app);
+    notes_summary::load_summary_settings_from_store(app);
+    notes_translation::load_translation_from_store(app);
+    interpreter::load_interpreter_from_store(app);
+    stage_display::load_stage_display_from_store(app);
+    retention::load_retention_from_store(app);
+    audio_output::load_audio_output_from_store(app);
+    low_data::load_low_data_from_store(app);
+    event_time::load_event_timezone_from_store(app);
+    slide_mapping::load_offsets_from_store(app);
+    screenshot_protection::load_deck_protection_from_store(app);
+    auto_sign_out::load_auto_sign_out_from_store(app);
+    revision_poll::load_revision_poll_from_store(app);
+    focus_revalidate::load_revalidate_on_focus_from_store(app);
+    #[cfg(feature = "desktop")]
+    {
+        topmost::load_banding_from_store(app);
+        panel_behavior::load_behavior_from_store(app);
+        clipboard_watch::load_clipboard_watch_from_store(app);
+        profiles::load_profiles_from_store(app);
+    }
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Some(port) = store
+            .get(SERVER_PORT_KEY)
+            .and_then(|v| serde_json::from_value::<u16>(v).ok())
+        {
+            *SERVER_PORT_SETTING.write() = Some(port);
+        }
+        if let Some(mode) = store
+            .get(NOTES_FETCH_MODE_KEY)
+            .and_then(|v| serde_json::from_value::<NotesFetchMode>(v).ok())
+        {
+            *NOTES_FETCH_MODE.write() = mode;
+        }
+    }
+}
+
+// Longer than any token CueCard is issued; a later expiry means a bad clock or a bad write
+const MAX_TOKEN_LIFETIME_SECS: i64 = 24 * 60 * 60;
+
+/// Check restored Firebase tokens. Tokens that can't be refreshed are
+/// rejected; an id token that looks wrong is marked expired so it's refreshed.
+fn validate_firebase_tokens(mut tokens: FirebaseTokens) -> Result<FirebaseTokens, String> {
+    if tokens.refresh_token.trim().is_empty() {
+        return Err("missing refresh token".to_string());
+    }
+    if tokens.local_id.trim().is_empty() {
+        return Err("missing user id".to_string());
+    }
+
+    let now = clock::now_secs();
+    // ID tokens are JWTs: header.payload.signature
+    if tokens.id_token.split('.').count() != 3 || tokens.expires_at > now + MAX_TOKEN_LIFETIME_SECS
+    {
+        tokens.expires_at = 0;
+    }
+    Ok(tokens)
+}
+
+/// Check restored OAuth tokens' fields, the same way as
+/// `validate_firebase_tokens`: blank refresh tokens are dropped, and tokens
+/// without an access token or with an implausible expiry count as expired
+fn validate_oauth_tokens(
+    access_token: &str,
+    refresh_token: &mut Option<String>,
+    expires_at: &mut Option<i64>,
+) -> Result<(), String> {
+    *refresh_token = refresh_token.take().filter(|t| !t.trim().is_empty());
+    if access_token.trim().is_empty() {
+        if refresh_token.is_none() {
+            return Err("missing access and refresh token".to_string());
+        }
+        *expires_at = Some(0);
+    }
+
+    let now = clock::now_secs();
+    if expires_at.is_some_and(|exp| exp > now + MAX_TOKEN_LIFETIME_SECS) {
+        *expires_at = Some(0);
+    }
+    Ok(())
+}
+
+fn validate_slides_tokens(mut tokens: SlidesTokens) -> Result<SlidesTokens, String> {
+    validate_oauth_tokens(
+        &tokens.access_token,
+        &mut tokens.refresh_token,
+        &mut tokens.expires_at,
+    )?;
+    Ok(tokens)
+}
+
+fn validate_canva_tokens(mut tokens: CanvaTokens) -> Result<CanvaTokens, String> {
+    validate_oauth_tokens(
+        &tokens.access_token,
+        &mut tokens.refresh_token,
+        &mut tokens.expires_at,
+    )?;
+    Ok(tokens)
+}
+
+/// A stored value that fails to parse or validate is dropped from the store
+fn restore_tokens<T: serde::de::DeserializeOwned>(
+    store: &tauri_plugin_store::Store<tauri::Wry>,
+    key: &str,
+    validate: fn(T) -> Result<T, String>,
+) -> Option<T> {
+    let json = store.get(key)?;
+    let restored = serde_json::from_value::<T>(json)
+        .map_err(|e| e.to_string())
+        .and_then(validate);
+    match restored {
+        Ok(tokens) => Some(tokens),
+        Err(e) => {
+            eprintln!("Discarding stored {}: {}", key, e);
+            store.delete(key);
+            let _ = store.save();
+            None
+        }
+    }
+}
+
 fn load_tokens_from_store(app: &AppHandle) {
     if let Ok(store) = app.store("cuecard-store.json") {
         // Load Firebase tokens
-        if let Some(tokens_json) = store.get("firebase_tokens") {
-            if let Ok(tokens) = serde_json::from_value::<FirebaseTokens>(tokens_json.clone()) {
-                let mut firebase = FIREBASE_TOKENS.write();
-                *firebase = Some(tokens);
-            }
+        if let Some(tokens) = restore_tokens(&store, "firebase_tokens", validate_firebase_tokens) {
+            let mut firebase = FIREBASE_TOKENS.write();
+            *firebase = Some(tokens);
         }
 
         // Load Slides tokens
-        if let Some(tokens_json) = store.get("slides_tokens") {
-            if let Ok(tokens) = serde_json::from_value::<SlidesTokens>(tokens_json.clone()) {
-                let mut slides = SLIDES_TOKENS.write();
-                *slides = Some(tokens);
-            }
+        if let Some(tokens) = restore_tokens(&store, "slides_tokens", validate_slides_tokens) {
+            let mut slides = SLIDES_TOKENS.write();
+            *slides = Some(tokens);
+        }
+
+        // Load Canva tokens
+        if let Some(tokens) = restore_tokens(&store, "canva_tokens", validate_canva_tokens) {
+            *CANVA_TOKENS.write() = Some(tokens);
         }
 
         // Load OAuth credentials
@@ -810,6 +1423,183 @@ fn hash_string(value: &str) -> String {
     format!("user_{:x}", hash.wrapping_abs() as u32)
 }
 
+// =============================================================================
+// EXTENSION COMPATIBILITY
+// =============================================================================
+
+/// Parse a "major.minor.patch" version; missing components count as 0
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let mut parts = version.trim().trim_start_matches('v').split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().map(|p| p.parse()).unwrap_or(Ok(0)).ok()?;
+    let patch = parts.next().map(|p| p.parse()).unwrap_or(Ok(0)).ok()?;
+    Some((major, minor, patch))
+}
+
+fn extension_compatibility(version: Option<&str>) -> ExtensionCompatibility {
+    let parsed = match version.and_then(parse_version) {
+        Some(v) => v,
+        None => return ExtensionCompatibility::Unknown,
+    };
+
+    let min = parse_version(MIN_EXTENSION_VERSION).unwrap_or((0, 0, 0));
+    let max = parse_version(MAX_EXTENSION_VERSION).unwrap_or((u64::MAX, 0, 0));
+
+    if parsed < min {
+        ExtensionCompatibility::Outdated
+    } else if parsed >= max {
+        ExtensionCompatibility::AppOutdated
+    } else {
+        ExtensionCompatibility::Supported
+    }
+}
+
+/// Remember the latest extension status and notify the panel when it changes
+fn record_extension_status(version: Option<String>, status: ExtensionCompatibility) {
+    let event = ExtensionCompatibilityEvent {
+        version,
+        status,
+        min_version: MIN_EXTENSION_VERSION.to_string(),
+        max_version: MAX_EXTENSION_VERSION.to_string(),
+    };
+
+    {
+        let mut current = EXTENSION_STATUS.write();
+        let unchanged = current
+            .as_ref()
+            .map(|c| c.version == event.version && c.status == event.status)
+            .unwrap_or(false);
+        if unchanged {
+            return;
+        }
+        *current = Some(event.clone());
+    }
+
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        let _ = app.emit("extension-compatibility", event);
+    }
+}
+
+/// Validate the extension version header on extension-facing routes
+async fn check_extension_version(request: Request, next: Next) -> Response {
+    let path = request.uri().path();
+    if path != "/health" && path != "/slides" {
+        return next.run(request).await;
+    }
+
+    let version = request
+        .headers()
+        .get(EXTENSION_VERSION_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim().to_string());
+    let status = extension_compatibility(version.as_deref());
+    record_extension_status(version.clone(), status);
+
+    if status == ExtensionCompatibility::Outdated {
+        return (
+            StatusCode::UPGRADE_REQUIRED,
+            Json(serde_json::json!({
+                "error": "upgrade_required",
+                "message": "This version of the CueCard extension is no longer supported. Please update it.",
+                "extension_version": version,
+                "min_version": MIN_EXTENSION_VERSION,
+                "max_version": MAX_EXTENSION_VERSION
+            })),
+        )
+            .into_response();
+    }
+
+    next.run(request).await
+}
+
+// =============================================================================
+// BROWSER CLIENTS
+// =============================================================================
+
+/// Record a client's slide; returns whether that client is the one being followed
+fn track_client_slide(client_id: &str, slide_data: &SlideData) -> bool {
+    let (is_active, changed) = track_client(
+        &mut CONNECTED_CLIENTS.write(),
+        &mut ACTIVE_CLIENT_ID.write(),
+        client_id,
+        slide_data,
+        clock::now_secs(),
+    );
+    if changed {
+        emit_clients_changed();
+    }
+    is_active
+}
+
+/// Whether the client is the one followed, and whether the client list changed
+fn track_client(
+    clients: &mut HashMap<String, ClientState>,
+    active: &mut Option<String>,
+    client_id: &str,
+    slide_data: &SlideData,
+    now: i64,
+) -> (bool, bool) {
+    let mut changed = false;
+
+    let before = clients.len();
+    clients.retain(|id, c| id == client_id || now - c.last_seen < CLIENT_STALE_SECS);
+    changed |= clients.len() != before;
+
+    if active.as_ref().is_some_and(|id| !clients.contains_key(id)) {
+        *active = None;
+        changed = true;
+    }
+
+    let previous = clients.insert(
+        client_id.to_string(),
+        ClientState {
+            slide: slide_data.clone(),
+            last_seen: now,
+        },
+    );
+    changed |= previous.is_none();
+
+    // The first profile to report becomes the one we follow
+    if active.is_none() {
+        *active = Some(client_id.to_string());
+        changed = true;
+    }
+
+    (active.as_deref() == Some(client_id), changed)
+}
+
+fn connected_clients() -> Vec<ConnectedClient> {
+    client_list(
+        &CONNECTED_CLIENTS.read(),
+        ACTIVE_CLIENT_ID.read().as_deref(),
+    )
+}
+
+/// Most recently heard from first
+fn client_list(
+    clients: &HashMap<String, ClientState>,
+    active: Option<&str>,
+) -> Vec<ConnectedClient> {
+    let mut list: Vec<ConnectedClient> = clients
+        .iter()
+        .map(|(id, c)| ConnectedClient {
+            client_id: id.clone(),
+            presentation_title: c.slide.title.clone(),
+            last_seen: c.last_seen,
+            is_active: active == Some(id.as_str()),
+            current_slide: c.slide.clone(),
+        })
+        .collect();
+    list.sort_by_key(|c| std::cmp::Reverse(c.last_seen));
+    list
+}
+
+fn emit_clients_changed() {
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        let _ = app.emit("clients-changed", connected_clients());
+    }
+}
+
 // =============================================================================
 // WEB SERVER HANDLERS
 // =============================================================================
@@ -819,15 +1609,62 @@ async fn health_handler() -> Json<serde_json::Value> {
     Json(serde_json::json!({
         "status": "ok",
         "server": "cuecard-app",
-        "authenticated": is_authenticated
+        "authenticated": is_authenticated,
+        "port": *SERVER_PORT.read()
     }))
 }
 
 async fn slides_handler(
     Json(slide_data): Json<SlideData>,
 ) -> Result<Json<ApiResponse>, StatusCode> {
-    let force_refresh = slide_data.force_refresh.unwrap_or(false);
-
+    // Updates from a browser profile that isn't being followed are only tracked
+    let active = match slide_data.client_id.clone() {
+        Some(client_id) => {
+            let is_active = track_client_slide(&client_id, &slide_data);
+            if !is_active {
+                return Ok(Json(ApiResponse {
+                    received: true,
+                    notes: None,
+                    active: Some(false),
+                }));
+            }
+            Some(true)
+        }
+        None => None,
+    };
+
+    let notes = if slide_data.mode == canva::MODE {
+        canva::on_slide_update(slide_data).await
+    } else if slide_data.mode == providers::powerpoint::MODE {
+        providers::powerpoint::on_slide_update(slide_data).await
+    } else if slide_data.mode == providers::pdf::MODE {
+        providers::pdf::on_slide_update(slide_data).await
+    } else {
+        apply_slide_update(slide_data, None).await
+    };
+
+    Ok(Json(ApiResponse {
+        received: true,
+        notes,
+        active,
+    }))
+}
+
+/// Slide modes reported by the browser extension for Google Slides tabs
+fn is_google_slides_mode(mode: &str) -> bool {
+    matches!(mode, "slideshow" | "edit" | "published" | "unknown")
+}
+
+/// Make a slide current: switch presentations if needed, resolve notes, notify the panel.
+/// Sources other than Google Slides pass their notes in `provided_notes`.
+async fn apply_slide_update(
+    slide_data: SlideData,
+    provided_notes: Option<String>,
+) -> Option<String> {
+    let force_refresh = slide_data.force_refresh.unwrap_or(false);
+    let from_google = is_google_slides_mode(&slide_data.mode);
+    auto_sign_out::record_activity();
+
     // Check if presentation changed
     let presentation_changed = {
         let current_pres = CURRENT_PRESENTATION_ID.read();
@@ -843,18 +1680,64 @@ async fn slides_handler(
             let mut notes_cache = SLIDE_NOTES.write();
             notes_cache.clear();
         }
-        let presentation_id = slide_data.presentation_id.clone();
-        tokio::spawn(async move {
-            let _ = prefetch_all_notes(&presentation_id).await;
-        });
+        rich_notes::clear();
+        notes_summary::clear();
+        notes_translation::clear();
+        SLIDE_ORDER.write().clear();
+        slide_skips::reset();
+        slide_mapping::reset();
+        screenshot_protection::on_presentation_changed();
+        if from_google {
+            // The previous deck's prefetch is no longer useful
+            cancel_prefetches();
+            // Notes cached on disk stand in until the prefetch answers
+            load_cached_deck(&slide_data.presentation_id).await;
+
+            let presentation_id = slide_data.presentation_id.clone();
+            tokio::spawn(async move {
+                if let Err(e) = prefetch_all_notes(&presentation_id).await {
+                    // A deck shared by link can still be read without Slides access
+                    if public_export::should_fall_back(&e) {
+                        public_export::start(&presentation_id);
+                    }
+                }
+            });
+        }
     }
 
-    {
-        let mut current = CURRENT_SLIDE.write();
-        *current = Some(slide_data.clone());
+    // Slide numbers from a deck with hidden slides need placing
+    let slide_data = slide_mapping::place(slide_data);
+
+    if let Some(ref scraped) = slide_data.scraped_notes {
+        notes_sources::set_source_notes(
+            &slide_data.presentation_id,
+            &slide_data.slide_id,
+            notes_sources::NotesSource::Scraped,
+            Some(scraped.clone()),
+        );
+    }
+
+    if let Some(text) = provided_notes {
+        let mut notes_cache = SLIDE_NOTES.write();
+        let key = format!("{}:{}", slide_data.presentation_id, slide_data.slide_id);
+        notes_cache.insert(key, notes_pipeline::process(&text));
     }
 
-    let notes = if force_refresh {
+    let previous = {
+        let mut current = CURRENT_SLIDE.write();
+        current.replace(slide_data.clone())
+    };
+    timer::ensure_started();
+    session::record_slide_visit(&slide_data);
+    slide_skips::on_slide_shown(previous.as_ref(), &slide_data);
+
+    let mut cache_hit = true;
+    let notes = if !from_google {
+        let notes_cache = SLIDE_NOTES.read();
+        let key = format!("{}:{}", slide_data.presentation_id, slide_data.slide_id);
+        notes_cache.get(&key).cloned()
+    } else if force_refresh && !slides_offline() {
+        cache_hit = false;
         let fetched = fetch_slide_notes(&slide_data.presentation_id, &slide_data.slide_id).await;
         if let Some(ref note_text) = fetched {
             let mut notes_cache = SLIDE_NOTES.write();
@@ -872,8 +1755,24 @@ async fn slides_handler(
         match notes {
             Some(n) => Some(n),
             None => {
-                let fetched =
-                    fetch_slide_notes(&slide_data.presentation_id, &slide_data.slide_id).await;
+                // Decks cached on disk answer even offline; the prefetch refreshes them
+                let (presentation_id, slide_id) = (
+                    slide_data.presentation_id.clone(),
+                    slide_data.slide_id.clone(),
+                );
+                let cached = tauri::async_runtime::spawn_blocking(move || {
+                    disk_cache::read_slide_notes(&presentation_id, &slide_id)
+                })
+                .await
+                .ok()
+                .flatten();
+                let fetched = match cached {
+                    Some(cached) => Some(cached),
+                    None => {
+                        cache_hit = false;
+                        fetch_slide_notes(&slide_data.presentation_id, &slide_data.slide_id).await
+                    }
+                };
                 if let Some(ref note_text) = fetched {
                     let mut notes_cache = SLIDE_NOTES.write();
                     let key = format!("{}:{}", slide_data.presentation_id, slide_data.slide_id);
@@ -884,43 +1783,331 @@ async fn slides_handler(
         }
     };
 
+    emit_slide_update(&slide_data, notes, cache_hit)
+}
+
+/// Fill the notes cache and deck order from the disk cache; false when the
+/// deck isn't cached. Read off the async runtime, as a whole deck can take a
+/// while to decompress.
+async fn load_cached_deck(presentation_id: &str) -> bool {
+    let id = presentation_id.to_string();
+    let cached =
+        tauri::async_runtime::spawn_blocking(move || disk_cache::read_presentation_notes(&id))
+            .await
+            .ok()
+            .flatten();
+    let Some(cached) = cached else {
+        return false;
+    };
+    {
+        let mut notes_cache = SLIDE_NOTES.write();
+        notes_cache.extend(
+            cached
+                .notes
+                .into_iter()
+                .map(|(slide_id, text)| (format!("{}:{}", presentation_id, slide_id), text)),
+        );
+    }
+    set_slide_order(presentation_id, cached.slide_ids);
+    slide_mapping::record_hidden_slide_ids(presentation_id, cached.hidden_slide_ids);
+    notes_freshness::record_disk_load(presentation_id);
+    true
+}
+
+/// Write the deck's notes cache to disk, unless the cached copy is already at
+/// `revision` or the presentation changed in the meantime
+async fn persist_deck_notes(presentation_id: &str, title: &str, revision: Option<String>) {
+    if revision.is_some() && disk_cache::cached_revision(presentation_id) == revision {
+        return;
+    }
+    if CURRENT_PRESENTATION_ID.read().as_deref() != Some(presentation_id) {
+        return;
+    }
+
+    let slides: Vec<disk_cache::CachedSlide> = {
+        let order = SLIDE_ORDER.read();
+        let notes_cache = SLIDE_NOTES.read();
+        order
+            .iter()
+            .enumerate()
+            .map(|(i, slide_id)| disk_cache::CachedSlide {
+                slide_id: slide_id.clone(),
+                slide_number: i as i32 + 1,
+                notes: notes_cache
+                    .get(&format!("{}:{}", presentation_id, slide_id))
+                    .cloned(),
+                has_thumbnail: false,
+                hidden: slide_mapping::is_hidden(slide_id),
+            })
+            .collect()
+    };
+    if slides.is_empty() {
+        return;
+    }
+
+    let cached = disk_cache::CachedPresentation {
+        presentation_id: presentation_id.to_string(),
+        title: title.to_string(),
+        cached_at: chrono::Utc::now().timestamp(),
+        revision,
+        preloaded: false,
+        slides,
+    };
+    if let Err(e) = disk_cache::write_presentation(&cached).await {
+        eprintln!("Failed to cache notes on disk: {}", e);
+        return;
+    }
+    if let Err(e) = disk_cache::enforce_limits() {
+        eprintln!("Failed to trim presentation cache: {}", e);
+    }
+}
+
+/// Fetch the deck's notes again, unless its revision matches the copy cached
+/// on disk, and show the current slide's notes
+async fn revalidate_deck_notes(presentation_id: &str) -> Result<(), CueCardError> {
+    let access_token = slides_access_token().await?;
+    let client = http_client::client();
+    let outline = fetch_deck_outline(&client, &access_token, presentation_id).await?;
+
+    let unchanged = outline.revision.is_some()
+        && disk_cache::cached_revision(presentation_id) == outline.revision;
+    if unchanged && load_cached_deck(presentation_id).await {
+        notes_freshness::record_verified(presentation_id);
+    } else {
+        prefetch_all_notes(presentation_id).await?;
+    }
+    reemit_current_slide(presentation_id, None);
+    Ok(())
+}
+
+/// Title and revision of a Slides API presentation
+fn presentation_version(json: &serde_json::Value) -> (String, Option<String>) {
+    let title = json
+        .get("title")
+        .and_then(|t| t.as_str())
+        .unwrap_or("Untitled presentation")
+        .to_string();
+    let revision = json
+        .get("revisionId")
+        .and_then(|r| r.as_str())
+        .map(|r| r.to_string());
+    (title, revision)
+}
+
+/// Record deck order, unless the presentation changed in the meantime
+fn set_slide_order(presentation_id: &str, slide_ids: Vec<String>) {
+    if CURRENT_PRESENTATION_ID.read().as_deref() == Some(presentation_id) {
+        *SLIDE_ORDER.write() = slide_ids;
+    }
+}
+
+/// Name of the source a slide's own notes come from, for `notes_provenance`
+fn primary_provider(mode: &str) -> &str {
+    if is_google_slides_mode(mode) {
+        "google"
+    } else {
+        mode
+    }
+}
+
+/// Merge the slide's own notes with the other sources, mask them if enabled and emit
+/// `slide-update`. Returns the notes as displayed.
+fn emit_slide_update(
+    slide_data: &SlideData,
+    primary_notes: Option<String>,
+    cache_hit: bool,
+) -> Option<String> {
+    let mut provider = primary_provider(&slide_data.mode);
+    let primary_notes = match primary_notes {
+        None if is_google_slides_mode(&slide_data.mode) => {
+            let exported = public_export::notes_for_slide(
+                &slide_data.presentation_id,
+                slide_data.slide_number,
+            );
+            if exported.is_some() {
+                provider = public_export::PROVIDER;
+            }
+            exported
+        }
+        notes => notes,
+    };
+    let (notes, notes_provenance) = notes_sources::resolve_notes(
+        &slide_data.presentation_id,
+        &slide_data.slide_id,
+        primary_notes,
+        provider,
+    );
+    let notes = glossary::annotate_notes(notes_masking::mask_notes(
+        &slide_data.presentation_id,
+        notes,
+    ));
+
+    notes_history::record(slide_data, notes.clone());
+    countdown_cues::on_slide_shown(slide_data, notes.as_deref());
+
     if let Some(app) = APP_HANDLE.read().as_ref() {
+        notes_translation::ensure_translated(slide_data, notes.as_deref());
+        let (next_slide_title, next_slide_notes) = match next_slide_id(slide_data) {
+            Some(next_id) => (
+                slide_skips::slide_title(&next_id),
+                display_notes(slide_data, &next_id),
+            ),
+            None => (None, None),
+        };
         let event = SlideUpdateEvent {
             slide_data: slide_data.clone(),
             notes: notes.clone(),
+            notes_provenance,
+            notes_masked: notes_masking::is_masked(&slide_data.presentation_id),
+            rich_notes: rich_notes::for_display(
+                &slide_data.presentation_id,
+                &slide_data.slide_id,
+                notes.as_deref(),
+            ),
+            translated_notes: notes_translation::for_display(
+                &slide_data.presentation_id,
+                &slide_data.slide_id,
+                notes.as_deref(),
+            ),
+            next_slide_notes,
+            next_slide_title,
+            pinned_notes: session::pinned_notes(),
+            countdown_cues: notes
+                .as_deref()
+                .map(countdown_cues::parse)
+                .unwrap_or_default(),
+            offline: is_google_slides_mode(&slide_data.mode) && slides_offline(),
+            notes_freshness: is_google_slides_mode(&slide_data.mode).then(|| {
+                notes_freshness::for_slide(
+                    &slide_data.presentation_id,
+                    &slide_data.slide_id,
+                    cache_hit,
+                )
+            }),
         };
         let _ = app.emit("slide-update", event);
     }
 
-    Ok(Json(ApiResponse {
-        received: true,
-        notes,
-    }))
+    notes
 }
 
-// OAuth login handler - redirects to Google
-async fn oauth_login_handler() -> Result<Redirect, StatusCode> {
-    let credentials = match OAUTH_CREDENTIALS.read().clone() {
-        Some(c) => c,
-        None => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+/// The slide after this one in the deck, skipping hidden slides
+fn next_slide_id(slide_data: &SlideData) -> Option<String> {
+    let order = slide_mapping::shown_slides();
+    let index = order.iter().position(|id| *id == slide_data.slide_id)?;
+    order.get(index + 1).cloned()
+}
+
+/// Notes of a slide in `slide_data`'s deck, resolved, masked and annotated as
+/// they're displayed
+fn display_notes(slide_data: &SlideData, slide_id: &str) -> Option<String> {
+    let primary = {
+        let notes_cache = SLIDE_NOTES.read();
+        let key = format!("{}:{}", slide_data.presentation_id, slide_id);
+        notes_cache.get(&key).cloned()
     };
+    prepare_notes(
+        &slide_data.presentation_id,
+        slide_id,
+        primary,
+        primary_provider(&slide_data.mode),
+    )
+}
 
-    let scope_url = {
-        let pending = PENDING_OAUTH_SCOPE.read();
-        match pending.as_deref() {
-            Some("profile") => SCOPE_PROFILE.to_string(),
-            Some("slides") => SCOPE_SLIDES.to_string(),
-            _ => format!("{} {}", SCOPE_PROFILE, SCOPE_SLIDES),
-        }
+/// A slide's own notes merged with the other sources, masked and annotated
+fn prepare_notes(
+    presentation_id: &str,
+    slide_id: &str,
+    primary: Option<String>,
+    provider: &str,
+) -> Option<String> {
+    let (notes, _) = notes_sources::resolve_notes(presentation_id, slide_id, primary, provider);
+    glossary::annotate_notes(notes_masking::mask_notes(presentation_id, notes))
+}
+
+/// Emit the current slide again if it belongs to the given presentation (and slide)
+fn reemit_current_slide(presentation_id: &str, slide_id: Option<&str>) {
+    let current = CURRENT_SLIDE.read().clone();
+    let Some(slide_data) = current else {
+        return;
+    };
+    if slide_data.presentation_id != presentation_id
+        || slide_id.is_some_and(|id| id != slide_data.slide_id)
+    {
+        return;
+    }
+
+    let primary = {
+        let notes_cache = SLIDE_NOTES.read();
+        let key = format!("{}:{}", slide_data.presentation_id, slide_data.slide_id);
+        notes_cache.get(&key).cloned()
     };
+    emit_slide_update(&slide_data, primary, true);
+}
+
+// Shown in the browser after the OAuth redirect
+const PROFILE_PAGE_HTML: &str = r#"<!doctype html><html lang="en"><head><meta charset="utf-8"><meta name="viewport" content="width=device-width,initial-scale=1"><title>CueCard Authentication</title><style>:root{--bg0:#0b0b0c;--bg1:#121214;--text-strong:rgba(255,255,255,.7);--text-soft:rgba(255,255,255,.55)}html,body{height:100%;margin:0;font-family:ui-sans-serif,system-ui,-apple-system,Segoe UI,Roboto,Helvetica,Arial,"Apple Color Emoji","Segoe UI Emoji"}body{background:radial-gradient(1200px 600px at 50% 45%,#1a1a1f 0%,#0f0f12 55%,#0a0a0b 100%),linear-gradient(180deg,var(--bg1),var(--bg0));display:grid;place-items:center;color:#fff}.wrap{text-align:center;padding:48px 24px;max-width:900px}h1{margin:0 0 26px;font-weight:600;letter-spacing:-.02em;color:var(--text-strong);font-size:clamp(44px,6vw,78px);line-height:1.08}p{margin:0;font-size:clamp(16px,2vw,26px);line-height:1.5;color:var(--text-soft)}</style></head><body><main class="wrap" role="main">
+    <h1>Speak Confidently</h1><p>You're all set up for CueCard. You can now close this window.</p></main></body></html>"#;
+const SLIDES_PAGE_HTML: &str = r#"<!doctype html><html lang="en"><head><meta charset="utf-8"><meta name="viewport" content="width=device-width,initial-scale=1"><title>CueCard Authentication</title><style>:root{--bg0:#0b0b0c;--bg1:#121214;--text-strong:rgba(255,255,255,.7);--text-soft:rgba(255,255,255,.55)}html,body{height:100%;margin:0;font-family:ui-sans-serif,system-ui,-apple-system,Segoe UI,Roboto,Helvetica,Arial,"Apple Color Emoji","Segoe UI Emoji"}body{background:radial-gradient(1200px 600px at 50% 45%,#1a1a1f 0%,#0f0f12 55%,#0a0a0b 100%),linear-gradient(180deg,var(--bg1),var(--bg0));display:grid;place-items:center;color:#fff}.wrap{text-align:center;padding:48px 24px;max-width:900px}h1{margin:0 0 26px;font-weight:600;letter-spacing:-.02em;color:var(--text-strong);font-size:clamp(44px,6vw,78px);line-height:1.08}p{margin:0;font-size:clamp(16px,2vw,26px);line-height:1.5;color:var(--text-soft)}</style></head><body><main class="wrap" role="main">
+    <h1>Speak Confidently</h1><p>You're all set up for Slides Access. You can now close this window.</p></main></body></html>"#;
+
+/// Google scopes for a requested scope ("profile", "slides", "slides_write",
+/// "drive", or profile and read-only Slides)
+fn oauth_scopes(scope: Option<&str>) -> String {
+    match scope {
+        Some("profile") => SCOPE_PROFILE.to_string(),
+        Some("slides") => SCOPE_SLIDES.to_string(),
+        Some("slides_write") => SCOPE_SLIDES_WRITE.to_string(),
+        Some("drive") => SCOPE_DRIVE.to_string(),
+        _ => format!("{} {}", SCOPE_PROFILE, SCOPE_SLIDES),
+    }
+}
+
+/// Google's consent page for a requested scope. Adding Slides access (or write
+/// or Drive access) to a signed-in profile is an incremental grant: the
+/// account is hinted from the Firebase email and only the new scope is asked
+/// for, on top of the ones already granted. Anything else asks for full
+/// consent to get a refresh token.
+fn google_auth_url(credentials: &OAuthCredentials, scope: Option<&str>) -> String {
+    let email = FIREBASE_TOKENS
+        .read()
+        .as_ref()
+        .and_then(|t| t.email.clone());
+    // Google only hands out a refresh token on consent; without one held, a
+    // silent incremental grant would leave Slides access ending with the hour
+    let has_refresh_token = SLIDES_TOKENS
+        .read()
+        .as_ref()
+        .is_some_and(|t| t.refresh_token.is_some());
+    let incremental = matches!(scope, Some("slides" | "slides_write" | "drive"))
+        && email.is_some()
+        && has_refresh_token;
 
-    let auth_url = format!(
-        "{}?client_id={}&redirect_uri={}&response_type=code&scope={}&access_type=offline&prompt=consent&include_granted_scopes=true",
+    let mut auth_url = format!(
+        "{}?client_id={}&redirect_uri={}&response_type=code&scope={}&access_type=offline&include_granted_scopes=true",
         GOOGLE_AUTH_URL,
         urlencoding::encode(&credentials.client_id),
-        urlencoding::encode(REDIRECT_URI),
-        urlencoding::encode(&scope_url)
+        urlencoding::encode(&redirect_uri()),
+        urlencoding::encode(&oauth_scopes(scope))
     );
+    if let Some(email) = email {
+        auth_url.push_str(&format!("&login_hint={}", urlencoding::encode(&email)));
+    }
+    if !incremental {
+        auth_url.push_str("&prompt=consent");
+    }
+    auth_url
+}
+
+// OAuth login handler - redirects to Google
+async fn oauth_login_handler() -> Result<Redirect, CueCardError> {
+    let credentials = OAUTH_CREDENTIALS
+        .read()
+        .clone()
+        .ok_or("OAuth credentials not available")?;
+
+    let scope = PENDING_OAUTH_SCOPE.read().clone();
+    let auth_url = google_auth_url(&credentials, scope.as_deref());
 
     Ok(Redirect::temporary(&auth_url))
 }
@@ -937,7 +2124,7 @@ async fn oauth_callback_handler(Query(params): Query<OAuthCallback>) -> Html<Str
             <p>Error: {}</p>
             <p>You can close this window.</p>
             </body></html>"#,
-            error
+            session_report::escape_html(&error)
         ));
     }
 
@@ -963,115 +2150,17 @@ async fn oauth_callback_handler(Query(params): Query<OAuthCallback>) -> Html<Str
         let mut pending = PENDING_OAUTH_SCOPE.write();
         pending.take()
     };
+    let is_profile_scope = pending_scope.as_deref() == Some("profile");
 
     // Exchange code for Google tokens
-    match exchange_code_for_google_tokens(&code).await {
-        Ok(google_tokens) => {
-            let is_profile_scope = pending_scope.as_deref() == Some("profile");
-
-            if is_profile_scope {
-                // For profile scope, exchange Google ID token for Firebase token
-                if let Some(google_id_token) = &google_tokens.id_token {
-                    match exchange_google_token_for_firebase(google_id_token).await {
-                        Ok(firebase_tokens) => {
-                            let user_name = firebase_tokens.display_name.clone();
-                            let user_email = firebase_tokens.email.clone();
-
-                            // Store Firebase tokens
-                            {
-                                let mut tokens = FIREBASE_TOKENS.write();
-                                *tokens = Some(firebase_tokens);
-                            }
-
-                            // Save to persistent storage
-                            if let Some(app) = APP_HANDLE.read().as_ref() {
-                                save_firebase_tokens_to_store(app);
-                                save_oauth_credentials_to_store(app);
-                            }
-
-                            // Notify frontend
-                            if let Some(app) = APP_HANDLE.read().as_ref() {
-                                let _ = app.emit(
-                                    "auth-status",
-                                    serde_json::json!({
-                                        "authenticated": true,
-                                        "user_name": user_name,
-                                        "user_email": user_email,
-                                        "requested_scope": pending_scope
-                                    }),
-                                );
-                            }
-
-                            Html(
-                                r#"<!doctype html><html lang="en"><head><meta charset="utf-8"><meta name="viewport" content="width=device-width,initial-scale=1"><title>CueCard Authentication</title><style>:root{--bg0:#0b0b0c;--bg1:#121214;--text-strong:rgba(255,255,255,.7);--text-soft:rgba(255,255,255,.55)}html,body{height:100%;margin:0;font-family:ui-sans-serif,system-ui,-apple-system,Segoe UI,Roboto,Helvetica,Arial,"Apple Color Emoji","Segoe UI Emoji"}body{background:radial-gradient(1200px 600px at 50% 45%,#1a1a1f 0%,#0f0f12 55%,#0a0a0b 100%),linear-gradient(180deg,var(--bg1),var(--bg0));display:grid;place-items:center;color:#fff}.wrap{text-align:center;padding:48px 24px;max-width:900px}h1{margin:0 0 26px;font-weight:600;letter-spacing:-.02em;color:var(--text-strong);font-size:clamp(44px,6vw,78px);line-height:1.08}p{margin:0;font-size:clamp(16px,2vw,26px);line-height:1.5;color:var(--text-soft)}</style></head><body><main class="wrap" role="main">
-                                <h1>Speak Confidently</h1><p>You're all set up for CueCard. You can now close this window.</p></main></body></html>"#
-                                    .to_string(),
-                            )
-                        }
-                        Err(e) => Html(format!(
-                            r#"<!DOCTYPE html>
-                            <html><head><title>Authentication Failed</title>
-                            <style>body {{ font-family: system-ui; padding: 40px; text-align: center; }}</style>
-                            </head><body>
-                            <h1>Firebase Authentication Failed</h1>
-                            <p>Error: {}</p>
-                            <p>You can close this window.</p>
-                            </body></html>"#,
-                            e
-                        )),
-                    }
-                } else {
-                    Html(
-                        r#"<!DOCTYPE html>
-                        <html><head><title>Authentication Failed</title>
-                        <style>body { font-family: system-ui; padding: 40px; text-align: center; }</style>
-                        </head><body>
-                        <h1>Authentication Failed</h1>
-                        <p>No ID token received from Google.</p>
-                        <p>You can close this window.</p>
-                        </body></html>"#
-                            .to_string(),
-                    )
-                }
-            } else {
-                // For slides scope, store the access token for Slides API
-                let expires_at = google_tokens
-                    .expires_in
-                    .map(|secs| chrono::Utc::now().timestamp() + secs);
-
-                {
-                    let mut tokens = SLIDES_TOKENS.write();
-                    *tokens = Some(SlidesTokens {
-                        access_token: google_tokens.access_token,
-                        refresh_token: google_tokens.refresh_token,
-                        expires_at,
-                    });
-                }
-
-                // Save to persistent storage
-                if let Some(app) = APP_HANDLE.read().as_ref() {
-                    save_slides_tokens_to_store(app);
-                }
-
-                // Notify frontend
-                if let Some(app) = APP_HANDLE.read().as_ref() {
-                    let _ = app.emit(
-                        "auth-status",
-                        serde_json::json!({
-                            "authenticated": true,
-                            "slides_authorized": true,
-                            "requested_scope": pending_scope
-                        }),
-                    );
-                }
+    let result = match exchange_code_for_google_tokens(&code).await {
+        Ok(google_tokens) => finish_google_login(google_tokens, pending_scope).await,
+        Err(e) => Err(e),
+    };
 
-                Html(
-                    r#"<!doctype html><html lang="en"><head><meta charset="utf-8"><meta name="viewport" content="width=device-width,initial-scale=1"><title>CueCard Authentication</title><style>:root{--bg0:#0b0b0c;--bg1:#121214;--text-strong:rgba(255,255,255,.7);--text-soft:rgba(255,255,255,.55)}html,body{height:100%;margin:0;font-family:ui-sans-serif,system-ui,-apple-system,Segoe UI,Roboto,Helvetica,Arial,"Apple Color Emoji","Segoe UI Emoji"}body{background:radial-gradient(1200px 600px at 50% 45%,#1a1a1f 0%,#0f0f12 55%,#0a0a0b 100%),linear-gradient(180deg,var(--bg1),var(--bg0));display:grid;place-items:center;color:#fff}.wrap{text-align:center;padding:48px 24px;max-width:900px}h1{margin:0 0 26px;font-weight:600;letter-spacing:-.02em;color:var(--text-strong);font-size:clamp(44px,6vw,78px);line-height:1.08}p{margin:0;font-size:clamp(16px,2vw,26px);line-height:1.5;color:var(--text-soft)}</style></head><body><main class="wrap" role="main">
-                    <h1>Speak Confidently</h1><p>You're all set up for Slides Access. You can now close this window.</p></main></body></html>"#
-                        .to_string(),
-                )
-            }
-        }
+    match result {
+        Ok(()) if is_profile_scope => Html(PROFILE_PAGE_HTML.to_string()),
+        Ok(()) => Html(SLIDES_PAGE_HTML.to_string()),
         Err(e) => Html(format!(
             r#"<!DOCTYPE html>
             <html><head><title>Authentication Failed</title>
@@ -1081,11 +2170,117 @@ async fn oauth_callback_handler(Query(params): Query<OAuthCallback>) -> Html<Str
             <p>Error: {}</p>
             <p>You can close this window.</p>
             </body></html>"#,
-            e
+            session_report::escape_html(&e)
         )),
     }
 }
 
+/// Keep the tokens from a completed Google sign-in and tell the frontend: a
+/// Firebase session for the profile scope, Slides API tokens otherwise
+async fn finish_google_login(
+    google_tokens: GoogleTokenResponse,
+    pending_scope: Option<String>,
+) -> Result<(), String> {
+    if pending_scope.as_deref() == Some("profile") {
+        // For profile scope, exchange Google ID token for Firebase token
+        let google_id_token = google_tokens
+            .id_token
+            .as_deref()
+            .ok_or("No ID token received from Google.")?;
+        let firebase_tokens = sign_in_with_idp(
+            idp_login::IdentityProvider::Google.firebase_provider_id(),
+            google_id_token,
+            None,
+        )
+        .await
+        .map_err(|e| format!("Firebase authentication failed: {}", e))?;
+        store_profile_session(
+            firebase_tokens,
+            idp_login::IdentityProvider::Google,
+            pending_scope,
+        );
+    } else {
+        // For slides scope, store the access token for Slides API
+        let expires_at = google_tokens
+            .expires_in
+            .map(|secs| clock::now_secs() + secs);
+
+        {
+            let mut tokens = SLIDES_TOKENS.write();
+            // An incremental grant may not come with a new refresh token
+            let refresh_token = google_tokens
+                .refresh_token
+                .or_else(|| tokens.as_ref().and_then(|t| t.refresh_token.clone()));
+            *tokens = Some(SlidesTokens {
+                access_token: google_tokens.access_token,
+                refresh_token,
+                expires_at,
+                scopes: google_tokens
+                    .scope
+                    .as_deref()
+                    .unwrap_or(SCOPE_SLIDES)
+                    .split_whitespace()
+                    .map(str::to_string)
+                    .collect(),
+            });
+        }
+
+        // Save to persistent storage
+        if let Some(app) = APP_HANDLE.read().as_ref() {
+            save_slides_tokens_to_store(app);
+        }
+
+        // Notify frontend
+        if let Some(app) = APP_HANDLE.read().as_ref() {
+            let _ = app.emit(
+                "auth-status",
+                serde_json::json!({
+                    "authenticated": true,
+                    "slides_authorized": true,
+                    "requested_scope": pending_scope
+                }),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Keep a new Firebase session for the CueCard profile and tell the frontend
+fn store_profile_session(
+    firebase_tokens: FirebaseTokens,
+    provider: idp_login::IdentityProvider,
+    pending_scope: Option<String>,
+) {
+    let user_name = firebase_tokens.display_name.clone();
+    let user_email = firebase_tokens.email.clone();
+
+    // Store Firebase tokens
+    {
+        let mut tokens = FIREBASE_TOKENS.write();
+        *tokens = Some(firebase_tokens);
+    }
+
+    // Save to persistent storage
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        save_firebase_tokens_to_store(app);
+        save_oauth_credentials_to_store(app);
+    }
+
+    // Notify frontend
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        let _ = app.emit(
+            "auth-status",
+            serde_json::json!({
+                "authenticated": true,
+                "user_name": user_name,
+                "user_email": user_email,
+                "provider": provider,
+                "requested_scope": pending_scope
+            }),
+        );
+    }
+}
+
 async fn auth_status_handler() -> Json<serde_json::Value> {
     let is_authenticated = FIREBASE_TOKENS.read().is_some();
     Json(serde_json::json!({
@@ -1102,6 +2297,10 @@ async fn logout_handler() -> Json<serde_json::Value> {
         let mut tokens = SLIDES_TOKENS.write();
         *tokens = None;
     }
+    {
+        let mut tokens = CANVA_TOKENS.write();
+        *tokens = None;
+    }
 
     if let Some(app) = APP_HANDLE.read().as_ref() {
         clear_all_tokens_from_store(app);
@@ -1120,44 +2319,291 @@ async fn logout_handler() -> Json<serde_json::Value> {
     }))
 }
 
-async fn start_server() {
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
+/// Convert a panicking handler into a 500 response instead of killing the server
+fn handle_server_panic(err: Box<dyn std::any::Any + Send + 'static>) -> Response {
+    let message = if let Some(s) = err.downcast_ref::<String>() {
+        s.clone()
+    } else if let Some(s) = err.downcast_ref::<&str>() {
+        s.to_string()
+    } else {
+        "unknown panic".to_string()
+    };
+    eprintln!("Local server handler panicked: {}", message);
+
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(serde_json::json!({
+            "error": "internal_error",
+            "message": "CueCard hit an internal error handling this request"
+        })),
+    )
+        .into_response()
+}
+
+/// Log the request context of every failed response
+async fn log_server_errors(request: Request, next: Next) -> Response {
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+
+    let response = next.run(request).await;
+    if response.status().is_server_error() {
+        eprintln!(
+            "Local server error: {} {} -> {}",
+            method,
+            path,
+            response.status()
+        );
+    }
+    response
+}
+
+// Slide updates, scraped notes included, are far smaller than this
+const MAX_REQUEST_BODY_BYTES: usize = 1024 * 1024;
 
-    let app = Router::new()
+/// The local API's routes with the body limit and extension version check,
+/// without the serving layers `start_server` adds (panic recovery, logging,
+/// CORS) so the fuzz targets see panics as panics
+fn api_router() -> Router {
+    Router::new()
         .route("/health", get(health_handler))
         .route("/slides", post(slides_handler))
         .route("/oauth/login", get(oauth_login_handler))
         .route("/oauth/callback", get(oauth_callback_handler))
+        .route(
+            "/oauth/idp/callback",
+            get(idp_login::oauth_callback_handler).post(idp_login::oauth_form_callback_handler),
+        )
         .route("/oauth/status", get(auth_status_handler))
         .route("/oauth/logout", post(logout_handler))
+        .merge(integrations::router())
+        .layer(DefaultBodyLimit::max(MAX_REQUEST_BODY_BYTES))
+        .layer(middleware::from_fn(check_extension_version))
+}
+
+/// Entry points for the fuzz targets in `fuzz/` and the benchmarks in `benches/`
+#[cfg(feature = "fuzzing")]
+#[doc(hidden)]
+pub mod fuzzing {
+    pub const MAX_REQUEST_BODY_BYTES: usize = super::MAX_REQUEST_BODY_BYTES;
+
+    pub fn api_router() -> axum::Router {
+        super::api_router()
+    }
+
+    pub fn extract_notes_from_slide(slide: &serde_json::Value) -> Option<String> {
+        super::extract_notes_from_slide(slide)
+    }
+
+    pub fn extract_text_from_text_elements(text: &serde_json::Value) -> Option<String> {
+        super::extract_text_from_text_elements(text)
+    }
+
+    pub fn encode_frame(text: &str, key: Option<&[u8; 32]>) -> Result<Vec<u8>, String> {
+        super::disk_cache::encode_frame(text, key)
+    }
+
+    pub fn decode_frame(frame: &[u8], key: Option<&[u8; 32]>) -> Option<String> {
+        super::disk_cache::decode_frame(frame, key)
+    }
+}
+
+async fn start_server() -> Result<(), String> {
+    // Checked per request so edits to the config file's allowlist apply at once
+    let cors = CorsLayer::new()
+        .allow_origin(AllowOrigin::predicate(|origin, _| {
+            origin.to_str().is_ok_and(config_file::origin_allowed)
+        }))
+        .allow_methods(Any)
+        .allow_headers(Any);
+
+    let app = api_router()
+        .layer(CatchPanicLayer::custom(handle_server_panic))
+        .layer(middleware::from_fn(log_server_errors))
         .layer(cors);
 
-    let listener = tokio::net::TcpListener::bind("127.0.0.1:3642")
+    let listener = bind_server_listener().await?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to read server address: {}", e))?
+        .port();
+    if SERVER_PORT.write().replace(port) != Some(port) {
+        if let Some(app) = APP_HANDLE.read().as_ref() {
+            let _ = app.emit("server-port-changed", port);
+        }
+    }
+
+    axum::serve(listener, app)
         .await
-        .expect("Failed to bind to port 3642");
+        .map_err(|e| format!("Server error: {}", e))
+}
+
+/// Bind the configured port (config file first, then settings), or the first
+/// free one of the default candidates
+async fn bind_server_listener() -> Result<tokio::net::TcpListener, String> {
+    let configured = config_file::server_port().or(*SERVER_PORT_SETTING.read());
+    let ports: Vec<u16> = match configured {
+        Some(port) => vec![port],
+        None => (DEFAULT_SERVER_PORT..DEFAULT_SERVER_PORT + SERVER_PORT_CANDIDATES).collect(),
+    };
+
+    let mut last_error = None;
+    for port in ports {
+        match tokio::net::TcpListener::bind(("127.0.0.1", port)).await {
+            Ok(listener) => return Ok(listener),
+            Err(e) => last_error = Some(format!("Failed to bind to port {}: {}", port, e)),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| "No port to bind".to_string()))
+}
+
+/// OAuth redirect to the local server, on whichever port it's listening
+fn redirect_uri() -> String {
+    let port = SERVER_PORT.read().unwrap_or(DEFAULT_SERVER_PORT);
+    format!("http://127.0.0.1:{}/oauth/callback", port)
+}
+
+/// Restart the local server now, e.g. when it stopped answering
+fn restart_server() {
+    SERVER_RESTART.notify_waiters();
+}
+
+/// Keep the local server running, restarting it with backoff after fatal errors
+async fn run_server_supervisor() {
+    let mut failures: u32 = 0;
+
+    loop {
+        let started = std::time::Instant::now();
+
+        let mut task = tokio::spawn(start_server());
+        let result = tokio::select! {
+            result = &mut task => result,
+            _ = SERVER_RESTART.notified() => {
+                task.abort();
+                let _ = task.await;
+                eprintln!("Restarting local server");
+                continue;
+            }
+        };
+        match result {
+            Ok(Ok(())) => eprintln!("Local server stopped unexpectedly"),
+            Ok(Err(e)) => eprintln!("Local server failed: {}", e),
+            Err(e) => eprintln!("Local server task panicked: {}", e),
+        }
+
+        // A server that ran for a while before failing starts over with a short delay
+        if started.elapsed().as_secs() >= SERVER_STABLE_RUN_SECS {
+            failures = 0;
+        }
+        failures = failures.saturating_add(1);
 
-    axum::serve(listener, app).await.expect("Server error");
+        let delay = 2u64
+            .saturating_pow(failures.min(5))
+            .min(SERVER_RESTART_MAX_DELAY_SECS);
+        eprintln!("Restarting local server in {}s", delay);
+        tokio::time::sleep(std::time::Duration::from_secs(delay)).await;
+    }
 }
 
 // =============================================================================
 // GOOGLE SLIDES API
 // =============================================================================
 
-async fn prefetch_all_notes(presentation_id: &str) -> Result<(), String> {
-    let access_token = match get_valid_slides_token().await {
-        Some(token) => token,
-        None => return Err("Not authenticated for Slides".to_string()),
-    };
+// Emit progress at most once per this many slides / bytes
+const PREFETCH_PROGRESS_EVERY: usize = 10;
+const PREFETCH_PROGRESS_BYTES: u64 = 256 * 1024;
+
+// Per-page fetching: requests in flight, and retries when rate limited
+const PAGE_FETCH_CONCURRENCY: usize = 6;
+const PAGE_FETCH_MAX_RETRIES: u32 = 3;
+const NOTES_FETCH_MODE_KEY: &str = "notes_fetch_mode";
+
+/// Progress reporting and cancellation for one prefetch; unregisters on drop
+struct PrefetchTracker {
+    id: String,
+    presentation_id: String,
+    cancelled: Arc<AtomicBool>,
+    bytes_received: u64,
+    bytes_total: Option<u64>,
+}
+
+impl PrefetchTracker {
+    fn register(presentation_id: &str) -> Self {
+        let id = Uuid::new_v4().to_string();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        ACTIVE_PREFETCHES
+            .write()
+            .insert(id.clone(), cancelled.clone());
+        Self {
+            id,
+            presentation_id: presentation_id.to_string(),
+            cancelled,
+            bytes_received: 0,
+            bytes_total: None,
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    fn emit(&self, phase: PrefetchPhase, slides_processed: usize, slides_total: usize) {
+        if let Some(app) = APP_HANDLE.read().as_ref() {
+            let _ = app.emit(
+                "prefetch-progress",
+                PrefetchProgressEvent {
+                    prefetch_id: self.id.clone(),
+                    presentation_id: self.presentation_id.clone(),
+                    phase,
+                    slides_processed,
+                    slides_total,
+                    bytes_received: self.bytes_received,
+                    bytes_total: self.bytes_total,
+                },
+            );
+        }
+    }
+}
+
+impl Drop for PrefetchTracker {
+    fn drop(&mut self) {
+        ACTIVE_PREFETCHES.write().remove(&self.id);
+    }
+}
+
+/// Cancel every running prefetch
+fn cancel_prefetches() {
+    for flag in ACTIVE_PREFETCHES.read().values() {
+        flag.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Fetch a whole presentation (slides and notes pages) from the Slides API
+async fn fetch_presentation(presentation_id: &str) -> Result<serde_json::Value, CueCardError> {
+    fetch_presentation_tracked(presentation_id, false, None).await
+}
+
+/// `fetch_presentation`, streaming the body so progress can be reported and
+/// cancelled. With `notes_only`, only titles, notes and hidden flags are
+/// downloaded, not every shape and image on the slides.
+async fn fetch_presentation_tracked(
+    presentation_id: &str,
+    notes_only: bool,
+    mut tracker: Option<&mut PrefetchTracker>,
+) -> Result<serde_json::Value, CueCardError> {
+    let access_token = slides_access_token().await?;
 
     let url = format!(
         "https://slides.googleapis.com/v1/presentations/{}",
         presentation_id
     );
+    let url = if notes_only {
+        low_data::with_field_mask(url, low_data::PRESENTATION_FIELDS)
+    } else {
+        low_data::with_fields(url, low_data::PRESENTATION_FIELDS)
+    };
 
-    let client = reqwest::Client::new();
+    let client = http_client::client();
+    api_usage::record(api_usage::ApiCall::SlidesRead);
     let response = match client
         .get(&url)
         .header("Authorization", format!("Bearer {}", access_token))
@@ -1167,9 +2613,10 @@ async fn prefetch_all_notes(presentation_id: &str) -> Result<(), String> {
         Ok(r) => r,
         Err(e) => {
             eprintln!("Error fetching slides API for prefetch: {}", e);
-            return Err(e.to_string());
+            return Err(e.into());
         }
     };
+    connectivity::record_success();
 
     if !response.status().is_success() {
         let status = response.status();
@@ -1178,37 +2625,143 @@ async fn prefetch_all_notes(presentation_id: &str) -> Result<(), String> {
             "Slides API error during prefetch: {} - {}",
             status, error_body
         );
-        return Err(format!("API error: {}", status));
+        return Err(CueCardError::from_status(
+            status,
+            format!("API error: {}", status),
+        ));
     }
 
-    let json: serde_json::Value = match response.json().await {
-        Ok(j) => j,
+    let mut response = response;
+    let mut body = Vec::new();
+    if let Some(t) = tracker.as_deref_mut() {
+        t.bytes_total = response.content_length();
+        t.emit(PrefetchPhase::Downloading, 0, 0);
+    }
+    loop {
+        let chunk = match response.chunk().await {
+            Ok(Some(c)) => c,
+            Ok(None) => break,
+            Err(e) => {
+                eprintln!("Error reading slides response during prefetch: {}", e);
+                return Err(e.into());
+            }
+        };
+        body.extend_from_slice(&chunk);
+
+        if let Some(t) = tracker.as_deref_mut() {
+            if t.is_cancelled() {
+                return Err(CueCardError::Cancelled("Prefetch cancelled".to_string()));
+            }
+            let previous = t.bytes_received;
+            t.bytes_received = body.len() as u64;
+            if t.bytes_received / PREFETCH_PROGRESS_BYTES != previous / PREFETCH_PROGRESS_BYTES {
+                t.emit(PrefetchPhase::Downloading, 0, 0);
+            }
+        }
+    }
+
+    match serde_json::from_slice(&body) {
+        Ok(j) => Ok(j),
         Err(e) => {
             eprintln!("Failed to parse slides response during prefetch: {}", e);
-            return Err(e.to_string());
+            Err(e.to_string().into())
+        }
+    }
+}
+
+async fn prefetch_all_notes(presentation_id: &str) -> Result<(), CueCardError> {
+    let mut tracker = PrefetchTracker::register(presentation_id);
+
+    if *NOTES_FETCH_MODE.read() == NotesFetchMode::PerPage {
+        let result = prefetch_notes_per_page(presentation_id, &tracker).await;
+        let phase = match result {
+            Ok(()) => PrefetchPhase::Done,
+            Err(_) if tracker.is_cancelled() => PrefetchPhase::Cancelled,
+            Err(_) => PrefetchPhase::Failed,
+        };
+        if phase != PrefetchPhase::Done {
+            tracker.emit(phase, 0, 0);
+        }
+        return result;
+    }
+
+    let json = match fetch_presentation_tracked(presentation_id, true, Some(&mut tracker)).await {
+        Ok(j) => j,
+        Err(e) => {
+            let phase = if tracker.is_cancelled() {
+                PrefetchPhase::Cancelled
+            } else {
+                PrefetchPhase::Failed
+            };
+            tracker.emit(phase, 0, 0);
+            return Err(e);
         }
     };
 
     let slides = match json.get("slides").and_then(|s| s.as_array()) {
         Some(s) => s,
-        None => return Ok(()),
+        None => {
+            tracker.emit(PrefetchPhase::Done, 0, 0);
+            return Ok(());
+        }
     };
-
-    let mut notes_cache = SLIDE_NOTES.write();
-
-    for slide in slides {
+    let total = slides.len();
+    set_slide_order(
+        presentation_id,
+        slides
+            .iter()
+            .filter_map(|s| s.get("objectId")?.as_str().map(|id| id.to_string()))
+            .collect(),
+    );
+    slide_skips::record_deck_titles(slides);
+    slide_mapping::record_hidden_slides(presentation_id, slides);
+
+    // Extract first so the cache lock isn't held while emitting
+    let mut extracted = Vec::with_capacity(total);
+    for (index, slide) in slides.iter().enumerate() {
+        if tracker.is_cancelled() {
+            tracker.emit(PrefetchPhase::Cancelled, index, total);
+            return Err(CueCardError::Cancelled("Prefetch cancelled".to_string()));
+        }
         if let Some(obj_id) = slide.get("objectId").and_then(|o| o.as_str()) {
-            if let Some(notes_text) = extract_notes_from_slide(slide) {
-                let key = format!("{}:{}", presentation_id, obj_id);
-                notes_cache.insert(key, notes_text);
-            }
+            let notes = extract_notes_from_slide(slide);
+            rich_notes::record(presentation_id, obj_id, slide, notes.as_deref());
+            extracted.push((format!("{}:{}", presentation_id, obj_id), notes));
+        }
+        if (index + 1) % PREFETCH_PROGRESS_EVERY == 0 {
+            tracker.emit(PrefetchPhase::Processing, index + 1, total);
+        }
+    }
+
+    {
+        // Notes deleted from a slide don't linger from an earlier fetch
+        let mut notes_cache = SLIDE_NOTES.write();
+        for (key, notes) in extracted {
+            match notes {
+                Some(text) => notes_cache.insert(key, text),
+                None => notes_cache.remove(&key),
+            };
         }
     }
+    tracker.emit(PrefetchPhase::Done, total, total);
+    let (title, revision) = presentation_version(&json);
+    notes_freshness::record_deck_fetch(presentation_id, revision.clone());
+    if let Some(revision) = &revision {
+        revision_poll::record_revision(presentation_id, revision.clone());
+    }
+    // Notes shown from the disk cache come back with their formatting
+    reemit_current_slide(presentation_id, None);
 
+    persist_deck_notes(presentation_id, &title, revision).await;
     Ok(())
 }
 
 fn extract_notes_from_slide(slide: &serde_json::Value) -> Option<String> {
+    extract_text_from_text_elements(notes_body_text(slide)?)
+}
+
+/// The `text` of a slide's speaker notes shape (the notes page's BODY placeholder)
+fn notes_body_text(slide: &serde_json::Value) -> Option<&serde_json::Value> {
     let notes = slide
         .get("slideProperties")?
         .get("notesPage")?
@@ -1220,79 +2773,342 @@ fn extract_notes_from_slide(slide: &serde_json::Value) -> Option<String> {
             if let Some(placeholder) = shape.get("placeholder") {
                 if placeholder.get("type")?.as_str()? == "BODY" {
                     if let Some(text) = shape.get("text") {
-                        return extract_text_from_text_elements(text);
+                        return Some(text);
                     }
                 }
             }
         }
     }
-
-    None
+
+    None
+}
+
+/// A presentation's title, revision and slide object ids in order, without
+/// the page contents
+struct DeckOutline {
+    title: String,
+    revision: Option<String>,
+    slide_ids: Vec<String>,
+}
+
+async fn fetch_deck_outline(
+    client: &reqwest::Client,
+    access_token: &str,
+    presentation_id: &str,
+) -> Result<DeckOutline, CueCardError> {
+    let url = format!(
+        "https://slides.googleapis.com/v1/presentations/{}?fields=title,revisionId,slides(objectId,slideProperties(isSkipped))",
+        presentation_id
+    );
+
+    api_usage::record(api_usage::ApiCall::SlidesRead);
+    let response = client
+        .get(&url)
+        .header("Authorization", format!("Bearer {}", access_token))
+        .send()
+        .await?;
+    connectivity::record_success();
+    let status = response.status();
+    if !status.is_success() {
+        return Err(CueCardError::from_status(
+            status,
+            format!("API error: {}", status),
+        ));
+    }
+
+    let json: serde_json::Value = response.json().await?;
+    let slides = json
+        .get("slides")
+        .and_then(|s| s.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    slide_mapping::record_hidden_slides(presentation_id, slides);
+    let (title, revision) = presentation_version(&json);
+    Ok(DeckOutline {
+        title,
+        revision,
+        slide_ids: slides
+            .iter()
+            .filter_map(|s| s.get("objectId")?.as_str().map(|id| id.to_string()))
+            .collect(),
+    })
+}
+
+/// Notes of a single slide via `presentations.pages.get`, backing off when rate limited
+async fn fetch_page_notes(
+    client: &reqwest::Client,
+    access_token: &str,
+    presentation_id: &str,
+    slide_id: &str,
+) -> Result<Option<String>, CueCardError> {
+    let url = low_data::with_field_mask(
+        format!(
+            "https://slides.googleapis.com/v1/presentations/{}/pages/{}",
+            presentation_id, slide_id
+        ),
+        low_data::PAGE_FIELDS,
+    );
+
+    let mut attempt = 0;
+    loop {
+        api_usage::record(api_usage::ApiCall::SlidesRead);
+        let response = client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", access_token))
+            .send()
+            .await?;
+        connectivity::record_success();
+
+        let status = response.status();
+        if let Some(delay) = page_retry_delay(status, attempt) {
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+            continue;
+        }
+        if !status.is_success() {
+            return Err(CueCardError::from_status(
+                status,
+                format!("API error: {}", status),
+            ));
+        }
+
+        let page: serde_json::Value = response.json().await?;
+        let notes = extract_notes_from_slide(&page);
+        rich_notes::record(presentation_id, slide_id, &page, notes.as_deref());
+        return Ok(notes);
+    }
+}
+
+/// How long to back off before retrying a rate-limited or unavailable page
+/// fetch, or `None` once it's out of retries or the status isn't retryable
+fn page_retry_delay(status: StatusCode, attempt: u32) -> Option<std::time::Duration> {
+    let retryable =
+        status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::SERVICE_UNAVAILABLE;
+    (retryable && attempt < PAGE_FETCH_MAX_RETRIES)
+        .then(|| std::time::Duration::from_millis(500 << attempt))
+}
+
+/// Move the current slide to the front of the fetch order, the next slide
+/// right behind it
+fn current_slides_first(slide_ids: &mut Vec<String>, current: Option<&str>, next: Option<&str>) {
+    for first in [next, current].into_iter().flatten() {
+        if let Some(pos) = slide_ids.iter().position(|id| id == first) {
+            let id = slide_ids.remove(pos);
+            slide_ids.insert(0, id);
+        }
+    }
+}
+
+/// Prefetch a deck page by page, current and next slide first, filling the cache as
+/// pages arrive
+async fn prefetch_notes_per_page(
+    presentation_id: &str,
+    tracker: &PrefetchTracker,
+) -> Result<(), CueCardError> {
+    let access_token = slides_access_token().await?;
+    let client = http_client::client();
+
+    let outline = fetch_deck_outline(&client, &access_token, presentation_id).await?;
+    let mut slide_ids = outline.slide_ids;
+    set_slide_order(presentation_id, slide_ids.clone());
+    let total = slide_ids.len();
+
+    // An unchanged revision means the notes cached on disk are current
+    if outline.revision.is_some()
+        && disk_cache::cached_revision(presentation_id) == outline.revision
+        && load_cached_deck(presentation_id).await
+    {
+        if let Some(revision) = &outline.revision {
+            revision_poll::record_revision(presentation_id, revision.clone());
+        }
+        reemit_current_slide(presentation_id, None);
+        tracker.emit(PrefetchPhase::Done, total, total);
+        return Ok(());
+    }
+
+    let current_slide = CURRENT_SLIDE
+        .read()
+        .as_ref()
+        .filter(|s| s.presentation_id == presentation_id)
+        .cloned();
+    let current_slide_id = current_slide.as_ref().map(|s| s.slide_id.clone());
+    // The next slide comes second, for the preview in `slide-update`
+    let next_id = current_slide.as_ref().and_then(next_slide_id);
+    current_slides_first(
+        &mut slide_ids,
+        current_slide_id.as_deref(),
+        next_id.as_deref(),
+    );
+    tracker.emit(PrefetchPhase::Processing, 0, total);
+
+    let semaphore = Arc::new(tokio::sync::Semaphore::new(PAGE_FETCH_CONCURRENCY));
+    let mut tasks = tokio::task::JoinSet::new();
+    for slide_id in slide_ids {
+        let semaphore = semaphore.clone();
+        let client = client.clone();
+        let access_token = access_token.clone();
+        let presentation_id = presentation_id.to_string();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let notes = fetch_page_notes(&client, &access_token, &presentation_id, &slide_id).await;
+            (slide_id, notes)
+        });
+    }
+
+    let mut processed = 0;
+    let mut complete = true;
+    while let Some(result) = tasks.join_next().await {
+        if tracker.is_cancelled() {
+            tasks.abort_all();
+            return Err(CueCardError::Cancelled("Prefetch cancelled".to_string()));
+        }
+        processed += 1;
+
+        let Ok((slide_id, notes)) = result else {
+            complete = false;
+            continue;
+        };
+        match notes {
+            Ok(Some(text)) => {
+                notes_freshness::record_slide_fetch(presentation_id, &slide_id);
+                {
+                    let mut notes_cache = SLIDE_NOTES.write();
+                    notes_cache.insert(format!("{}:{}", presentation_id, slide_id), text);
+                }
+                if current_slide_id.as_deref() == Some(slide_id.as_str())
+                    || next_id.as_deref() == Some(slide_id.as_str())
+                {
+                    reemit_current_slide(presentation_id, current_slide_id.as_deref());
+                }
+            }
+            Ok(None) => {
+                notes_freshness::record_slide_fetch(presentation_id, &slide_id);
+                SLIDE_NOTES
+                    .write()
+                    .remove(&format!("{}:{}", presentation_id, slide_id));
+            }
+            Err(e) => {
+                eprintln!("Failed to fetch notes page {}: {}", slide_id, e);
+                complete = false;
+            }
+        }
+
+        if processed % PREFETCH_PROGRESS_EVERY == 0 || processed == total {
+            tracker.emit(PrefetchPhase::Processing, processed, total);
+        }
+    }
+
+    tracker.emit(PrefetchPhase::Done, total, total);
+    if let Some(revision) = &outline.revision {
+        revision_poll::record_revision(presentation_id, revision.clone());
+    }
+    // A deck with missing pages would read as current next time
+    if complete {
+        persist_deck_notes(presentation_id, &outline.title, outline.revision).await;
+    }
+    Ok(())
+}
+
+/// Notes of one slide missing from the cache, through the pages endpoint so a
+/// miss on a large deck doesn't download the whole presentation
+async fn fetch_slide_notes(presentation_id: &str, slide_id: &str) -> Option<String> {
+    let access_token = get_valid_slides_token().await?;
+
+    let client = http_client::client();
+    match fetch_page_notes(&client, &access_token, presentation_id, slide_id).await {
+        Ok(notes) => {
+            notes_freshness::record_slide_fetch(presentation_id, slide_id);
+            notes
+        }
+        Err(e) => {
+            eprintln!("Error fetching notes page: {}", e);
+            None
+        }
+    }
 }
 
-async fn fetch_slide_notes(presentation_id: &str, slide_id: &str) -> Option<String> {
-    let access_token = match get_valid_slides_token().await {
-        Some(token) => token,
-        None => return None,
-    };
+/// Replace a slide's speaker notes with `text` through `batchUpdate`
+async fn write_slide_notes(
+    presentation_id: &str,
+    slide_id: &str,
+    text: &str,
+) -> Result<(), CueCardError> {
+    let access_token = slides_access_token().await?;
+    let client = http_client::client();
 
+    // The notes shape's id, and whether it has text to delete
     let url = format!(
-        "https://slides.googleapis.com/v1/presentations/{}",
-        presentation_id
+        "https://slides.googleapis.com/v1/presentations/{}/pages/{}?fields={}",
+        presentation_id,
+        slide_id,
+        urlencoding::encode(
+            "slideProperties(notesPage(notesProperties,pageElements(shape(placeholder(type),text))))"
+        )
     );
-
-    let client = reqwest::Client::new();
-    let response = match client
+    api_usage::record(api_usage::ApiCall::SlidesRead);
+    let response = client
         .get(&url)
         .header("Authorization", format!("Bearer {}", access_token))
         .send()
         .await
-    {
-        Ok(r) => r,
-        Err(e) => {
-            eprintln!("Error fetching slides API: {}", e);
-            return None;
-        }
-    };
-
-    if !response.status().is_success() {
-        eprintln!("Slides API error: {}", response.status());
-        return None;
+        .map_err(|e| connectivity::network_error(format!("Failed to read notes page: {}", e)))?;
+    connectivity::record_success();
+    let status = response.status();
+    if !status.is_success() {
+        return Err(CueCardError::from_status(
+            status,
+            format!("Slides API error: {}", status),
+        ));
     }
+    let page: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse notes page: {}", e))?;
 
-    let json: serde_json::Value = match response.json().await {
-        Ok(j) => j,
-        Err(e) => {
-            eprintln!("Failed to parse slides response: {}", e);
-            return None;
-        }
-    };
-
-    let slides = json.get("slides")?.as_array()?;
-    for slide in slides {
-        let obj_id = slide.get("objectId")?.as_str()?;
-        if obj_id == slide_id {
-            let notes = slide
-                .get("slideProperties")?
-                .get("notesPage")?
-                .get("pageElements")?
-                .as_array()?;
-
-            for element in notes {
-                if let Some(shape) = element.get("shape") {
-                    if let Some(placeholder) = shape.get("placeholder") {
-                        if placeholder.get("type")?.as_str()? == "BODY" {
-                            if let Some(text) = shape.get("text") {
-                                return extract_text_from_text_elements(text);
-                            }
-                        }
-                    }
-                }
-            }
-        }
+    // Slides creates the shape under this id if the slide has no notes yet
+    let object_id = page
+        .pointer("/slideProperties/notesPage/notesProperties/speakerNotesObjectId")
+        .and_then(|v| v.as_str())
+        .ok_or("Slide has no speaker notes shape")?;
+    let has_text = notes_body_text(&page)
+        .and_then(extract_text_from_text_elements)
+        .is_some();
+
+    let mut requests = Vec::new();
+    if has_text {
+        requests.push(serde_json::json!({
+            "deleteText": { "objectId": object_id, "textRange": { "type": "ALL" } }
+        }));
+    }
+    if !text.is_empty() {
+        requests.push(serde_json::json!({
+            "insertText": { "objectId": object_id, "insertionIndex": 0, "text": text }
+        }));
+    }
+    if requests.is_empty() {
+        return Ok(());
     }
 
-    None
+    api_usage::record(api_usage::ApiCall::SlidesWrite);
+    let response = client
+        .post(format!(
+            "https://slides.googleapis.com/v1/presentations/{}:batchUpdate",
+            presentation_id
+        ))
+        .header("Authorization", format!("Bearer {}", access_token))
+        .json(&serde_json::json!({ "requests": requests }))
+        .send()
+        .await
+        .map_err(|e| CueCardError::Network(format!("Failed to update notes: {}", e)))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(CueCardError::from_status(
+            status,
+            format!("Failed to update notes: {} - {}", status, error_text),
+        ));
+    }
+    Ok(())
 }
 
 fn extract_text_from_text_elements(text: &serde_json::Value) -> Option<String> {
@@ -1310,7 +3126,7 @@ fn extract_text_from_text_elements(text: &serde_json::Value) -> Option<String> {
     if result.is_empty() {
         None
     } else {
-        Some(result.trim().to_string())
+        Some(notes_pipeline::process(result.trim()))
     }
 }
 
@@ -1325,13 +3141,107 @@ fn get_current_slide() -> Option<SlideData> {
 
 #[tauri::command]
 fn get_current_notes() -> Option<String> {
-    let current = CURRENT_SLIDE.read();
-    if let Some(ref slide) = *current {
-        let notes = SLIDE_NOTES.read();
-        let key = format!("{}:{}", slide.presentation_id, slide.slide_id);
-        notes.get(&key).cloned()
-    } else {
-        None
+    let slide = CURRENT_SLIDE.read().clone()?;
+    display_notes(&slide, &slide.slide_id)
+}
+
+/// Notes of the next slide shown in the deck
+#[tauri::command]
+fn get_next_notes() -> Option<String> {
+    let slide = CURRENT_SLIDE.read().clone()?;
+    display_notes(&slide, &next_slide_id(&slide)?)
+}
+
+#[tauri::command]
+fn get_connected_clients() -> Vec<ConnectedClient> {
+    connected_clients()
+}
+
+/// Follow a different browser profile and show its current slide
+#[tauri::command]
+async fn set_active_client(client_id: String) -> Result<(), CueCardError> {
+    let slide = CONNECTED_CLIENTS
+        .read()
+        .get(&client_id)
+        .map(|c| c.slide.clone())
+        .ok_or("Unknown client")?;
+
+    {
+        let mut active = ACTIVE_CLIENT_ID.write();
+        *active = Some(client_id);
+    }
+    emit_clients_changed();
+
+    apply_slide_update(
+        SlideData {
+            force_refresh: None,
+            ..slide
+        },
+        None,
+    )
+    .await;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_notes_fetch_mode() -> NotesFetchMode {
+    *NOTES_FETCH_MODE.read()
+}
+
+/// Switch between whole-deck and per-page notes fetching
+#[tauri::command]
+fn set_notes_fetch_mode(app: AppHandle, mode: NotesFetchMode) {
+    *NOTES_FETCH_MODE.write() = mode;
+    if let Ok(store) = app.store("cuecard-store.json") {
+        store.set(NOTES_FETCH_MODE_KEY, serde_json::json!(mode));
+        let _ = store.save();
+    }
+}
+
+/// Abort a running notes prefetch (by `prefetch-progress` id), or all of them
+#[tauri::command]
+fn cancel_prefetch(prefetch_id: Option<String>) -> bool {
+    match prefetch_id {
+        Some(id) => match ACTIVE_PREFETCHES.read().get(&id) {
+            Some(flag) => {
+                flag.store(true, Ordering::SeqCst);
+                true
+            }
+            None => false,
+        },
+        None => {
+            let any_running = !ACTIVE_PREFETCHES.read().is_empty();
+            cancel_prefetches();
+            any_running
+        }
+    }
+}
+
+#[tauri::command]
+fn get_extension_compatibility() -> Option<ExtensionCompatibilityEvent> {
+    EXTENSION_STATUS.read().clone()
+}
+
+/// Port the local server is listening on, once it's up
+#[tauri::command]
+fn get_server_port() -> Option<u16> {
+    *SERVER_PORT.read()
+}
+
+/// Pin the local server to a port (0 for any free port), or `None` for the
+/// default candidates. Takes effect the next time CueCard starts.
+#[tauri::command]
+fn set_server_port(app: AppHandle, port: Option<u16>) {
+    *SERVER_PORT_SETTING.write() = port;
+    if let Ok(store) = app.store("cuecard-store.json") {
+        match port {
+            Some(port) => store.set(SERVER_PORT_KEY, port),
+            None => {
+                store.delete(SERVER_PORT_KEY);
+            }
+        }
+        let _ = store.save();
     }
 }
 
@@ -1354,7 +3264,7 @@ async fn init_analytics(
     app: AppHandle,
     platform: Option<String>,
     operating_system: Option<String>,
-) -> Result<(), String> {
+) -> Result<(), CueCardError> {
     if get_or_init_analytics_state(&app).is_none() {
         return Ok(());
     }
@@ -1384,7 +3294,10 @@ async fn send_event(
     app: AppHandle,
     event_name: String,
     params: Option<HashMap<String, serde_json::Value>>,
-) -> Result<(), String> {
+) -> Result<(), CueCardError> {
+    if low_data::is_active() {
+        return Ok(());
+    }
     let state = match get_or_init_analytics_state(&app) {
         Some(state) => state,
         None => return Ok(()),
@@ -1465,7 +3378,7 @@ async fn send_event(
         GA_COLLECT_URL, measurement_id, api_secret
     );
 
-    let client = reqwest::Client::new();
+    let client = http_client::client();
     let response = client.post(&url).json(&payload).send().await;
 
     match response {
@@ -1483,7 +3396,7 @@ async fn send_event(
 }
 
 #[tauri::command]
-fn set_analytics_user_id(app: AppHandle, email: String) -> Result<(), String> {
+fn set_analytics_user_id(app: AppHandle, email: String) -> Result<(), CueCardError> {
     if get_or_init_analytics_state(&app).is_none() {
         return Ok(());
     }
@@ -1497,7 +3410,7 @@ fn set_analytics_user_id(app: AppHandle, email: String) -> Result<(), String> {
 }
 
 #[tauri::command]
-fn clear_analytics_user_id() -> Result<(), String> {
+fn clear_analytics_user_id() -> Result<(), CueCardError> {
     let mut analytics_state = ANALYTICS_STATE.write();
     if let Some(ref mut state) = *analytics_state {
         state.user_id = None;
@@ -1524,19 +3437,60 @@ fn check_and_mark_first_open(app: AppHandle) -> bool {
 }
 
 #[tauri::command]
-async fn get_firebase_id_token() -> Result<String, String> {
+async fn get_firebase_id_token() -> Result<String, CueCardError> {
     get_valid_firebase_token()
         .await
-        .ok_or_else(|| "Not authenticated".to_string())
+        .ok_or_else(|| CueCardError::AuthRequired("Not authenticated".to_string()))
 }
 
 #[tauri::command]
 fn has_slides_scope() -> bool {
-    SLIDES_TOKENS.read().is_some()
+    get_slides_scopes().missing.is_empty()
+}
+
+/// Which Slides scopes the UI still has to ask for
+#[tauri::command]
+fn get_slides_scopes() -> SlidesScopes {
+    let granted: Vec<String> = match SLIDES_TOKENS.read().as_ref() {
+        Some(t) if t.scopes.is_empty() => SCOPE_SLIDES
+            .split_whitespace()
+            .map(str::to_string)
+            .collect(),
+        Some(t) => t.scopes.clone(),
+        None => Vec::new(),
+    };
+    let missing = SCOPE_SLIDES
+        .split_whitespace()
+        .filter(|scope| {
+            !granted
+                .iter()
+                .any(|g| g == scope || g == SCOPE_SLIDES_WRITE)
+        })
+        .map(str::to_string)
+        .collect();
+    SlidesScopes { granted, missing }
+}
+
+/// Whether notes can be edited, i.e. the opt-in write scope was granted
+#[tauri::command]
+fn has_slides_write_scope() -> bool {
+    SLIDES_TOKENS
+        .read()
+        .as_ref()
+        .is_some_and(|t| t.scopes.iter().any(|s| s == SCOPE_SLIDES_WRITE))
+}
+
+/// Whether recent decks can be listed, i.e. the opt-in Drive scope was granted
+#[tauri::command]
+fn has_drive_scope() -> bool {
+    SLIDES_TOKENS
+        .read()
+        .as_ref()
+        .is_some_and(|t| t.scopes.iter().any(|s| s == SCOPE_DRIVE))
 }
 
 #[tauri::command]
-async fn get_user_info() -> Result<serde_json::Value, String> {
+async fn get_user_info() -> Result<serde_json::Value, CueCardError> {
     let tokens = FIREBASE_TOKENS.read();
     match tokens.as_ref() {
         Some(t) => Ok(serde_json::json!({
@@ -1544,31 +3498,65 @@ async fn get_user_info() -> Result<serde_json::Value, String> {
             "name": t.display_name,
             "local_id": t.local_id
         })),
-        None => Err("Not authenticated".to_string()),
+        None => Err(CueCardError::AuthRequired("Not authenticated".to_string())),
+    }
+}
+
+/// Who's signed in, when their tokens expire and what Slides grants, without
+/// contacting Google (see `scopes::get_granted_scopes` for a live check)
+#[tauri::command]
+fn get_auth_details() -> AuthDetails {
+    let mut details = AuthDetails::default();
+    if let Some(t) = FIREBASE_TOKENS.read().as_ref() {
+        details.signed_in = true;
+        details.email = t.email.clone();
+        details.display_name = t.display_name.clone();
+        details.firebase_expires_at = Some(t.expires_at);
+        details.has_firebase_refresh_token = !t.refresh_token.is_empty();
+    }
+    if let Some(t) = SLIDES_TOKENS.read().as_ref() {
+        details.slides_authorized = true;
+        details.slides_expires_at = t.expires_at;
+        details.has_slides_refresh_token = t.refresh_token.is_some();
     }
+    details.scopes = get_slides_scopes().granted;
+    details
 }
 
+/// Start signing in. Opens Google's sign-in page, or with the device mode
+/// (and when no browser can be opened) returns a code to show instead. The
+/// profile can also be signed in with Microsoft or Apple (`provider`); Slides
+/// access always comes from Google.
 #[tauri::command]
-async fn start_login(app: AppHandle, scope: String) -> Result<(), String> {
+async fn start_login(
+    app: AppHandle,
+    scope: String,
+    mode: Option<device_login::LoginMode>,
+    provider: Option<idp_login::IdentityProvider>,
+) -> Result<Option<device_login::DeviceLogin>, CueCardError> {
+    let provider = provider.unwrap_or_default();
+    if provider != idp_login::IdentityProvider::Google {
+        if scope != "profile" {
+            return Err(format!("{} can only sign in the profile", provider.label()).into());
+        }
+        if mode.unwrap_or_default() == device_login::LoginMode::Device {
+            return Err("Signing in with a code needs a Google account".into());
+        }
+        idp_login::start(&app, provider).await?;
+        return Ok(None);
+    }
+
     // Set pending scope
     {
         let mut pending = PENDING_OAUTH_SCOPE.write();
         *pending = Some(scope.clone());
     }
 
-    // Check if we have OAuth credentials
-    let has_credentials = OAUTH_CREDENTIALS.read().is_some();
-
-    if !has_credentials {
-        // Bootstrap: sign in anonymously and fetch credentials
-        let anon_token = sign_in_anonymously().await?;
-        let credentials = fetch_oauth_credentials(&anon_token).await?;
-
-        // Store credentials
-        {
-            let mut creds = OAUTH_CREDENTIALS.write();
-            *creds = Some(credentials.clone());
-        }
+    // Cached between sign-ins and refreshed once a day
+    let credentials = remote_config::oauth_credentials().await?;
+    {
+        let mut creds = OAUTH_CREDENTIALS.write();
+        *creds = Some(credentials);
     }
 
     // Now build the OAuth URL
@@ -1577,25 +3565,18 @@ async fn start_login(app: AppHandle, scope: String) -> Result<(), String> {
         .clone()
         .ok_or("OAuth credentials not available")?;
 
-    let scope_url = match scope.as_str() {
-        "profile" => SCOPE_PROFILE.to_string(),
-        "slides" => SCOPE_SLIDES.to_string(),
-        _ => format!("{} {}", SCOPE_PROFILE, SCOPE_SLIDES),
-    };
+    if mode.unwrap_or_default() == device_login::LoginMode::Device {
+        return Ok(Some(device_login::start(&credentials, scope).await?));
+    }
 
-    let auth_url = format!(
-        "{}?client_id={}&redirect_uri={}&response_type=code&scope={}&access_type=offline&prompt=consent&include_granted_scopes=true",
-        GOOGLE_AUTH_URL,
-        urlencoding::encode(&credentials.client_id),
-        urlencoding::encode(REDIRECT_URI),
-        urlencoding::encode(&scope_url)
-    );
+    let auth_url = google_auth_url(&credentials, Some(&scope));
 
-    app.opener()
-        .open_url(&auth_url, None::<&str>)
-        .map_err(|e| format!("Failed to open browser: {}", e))?;
+    if let Err(e) = app.opener().open_url(&auth_url, None::<&str>) {
+        eprintln!("Failed to open browser, signing in with a code: {}", e);
+        return Ok(Some(device_login::start(&credentials, scope).await?));
+    }
 
-    Ok(())
+    Ok(None)
 }
 
 #[tauri::command]
@@ -1608,25 +3589,50 @@ fn logout(app: AppHandle) {
         let mut tokens = SLIDES_TOKENS.write();
         *tokens = None;
     }
+    {
+        let mut tokens = CANVA_TOKENS.write();
+        *tokens = None;
+    }
 
     clear_all_tokens_from_store(&app);
 }
 
+/// Sign in to Slides again after `slides-auth-required`
+#[tauri::command]
+async fn reauthorize_slides(
+    app: AppHandle,
+    mode: Option<device_login::LoginMode>,
+) -> Result<Option<device_login::DeviceLogin>, CueCardError> {
+    start_login(app, "slides".to_string(), mode, None).await
+}
+
 #[tauri::command]
-async fn refresh_notes(app: AppHandle) -> Result<Option<String>, String> {
+async fn refresh_notes() -> Result<Option<String>, CueCardError> {
     let current_slide = { CURRENT_SLIDE.read().clone() };
 
     let slide_data = match current_slide {
         Some(s) => s,
-        None => return Err("No current slide".to_string()),
+        None => return Err("No current slide".into()),
     };
+    // Keep the cached notes when there's no getting new ones
+    if is_google_slides_mode(&slide_data.mode) {
+        slides_access_token().await?;
+    }
 
     {
         let mut notes_cache = SLIDE_NOTES.write();
         notes_cache.retain(|k, _| !k.starts_with(&format!("{}:", slide_data.presentation_id)));
     }
-
-    let _ = prefetch_all_notes(&slide_data.presentation_id).await;
+    rich_notes::clear();
+    notes_summary::clear();
+    notes_translation::clear();
+
+    public_export::clear();
+    if let Err(e) = prefetch_all_notes(&slide_data.presentation_id).await {
+        if public_export::should_fall_back(&e) {
+            public_export::start(&slide_data.presentation_id);
+        }
+    }
 
     let notes = {
         let notes_cache = SLIDE_NOTES.read();
@@ -1634,11 +3640,47 @@ async fn refresh_notes(app: AppHandle) -> Result<Option<String>, String> {
         notes_cache.get(&key).cloned()
     };
 
-    let event = SlideUpdateEvent {
-        slide_data: slide_data.clone(),
-        notes: notes.clone(),
-    };
-    let _ = app.emit("slide-update", event);
+    Ok(emit_slide_update(&slide_data, notes, false))
+}
+
+/// Write a slide's speaker notes back to Google Slides (needs the
+/// "slides_write" scope), then replace the cached copies with the saved notes
+#[tauri::command]
+async fn update_slide_notes(
+    presentation_id: String,
+    slide_id: String,
+    text: String,
+) -> Result<Option<String>, CueCardError> {
+    if !has_slides_write_scope() {
+        return Err(CueCardError::ScopeMissing(
+            "Editing notes needs Slides write access".to_string(),
+        ));
+    }
+    write_slide_notes(&presentation_id, &slide_id, &text).await?;
+
+    let key = format!("{}:{}", presentation_id, slide_id);
+    SLIDE_NOTES.write().remove(&key);
+    // Read back as saved, falling back to what was sent
+    let notes = fetch_slide_notes(&presentation_id, &slide_id)
+        .await
+        .or_else(|| Some(notes_pipeline::process(text.trim())).filter(|n| !n.is_empty()));
+    if let Some(ref n) = notes {
+        SLIDE_NOTES.write().insert(key, n.clone());
+    }
+
+    // The disk copy would otherwise answer with the old notes. Only this
+    // slide's entry changes, as the notes held for the rest of the deck may
+    // not all be in yet.
+    let (cached_id, cached_slide, cached_notes) =
+        (presentation_id.clone(), slide_id.clone(), notes.clone());
+    let cached = tauri::async_runtime::spawn_blocking(move || {
+        disk_cache::write_slide_notes(&cached_id, &cached_slide, cached_notes.as_deref())
+    })
+    .await;
+    if let Ok(Err(e)) = cached {
+        eprintln!("Failed to cache saved notes: {}", e);
+    }
+    reemit_current_slide(&presentation_id, Some(&slide_id));
 
     Ok(notes)
 }
@@ -1647,19 +3689,18 @@ async fn refresh_notes(app: AppHandle) -> Result<Option<String>, String> {
 // WINDOW MANAGEMENT
 // =============================================================================
 
+/// Global screenshot protection, for decks without a setting of their own
 #[tauri::command]
-fn set_screenshot_protection(app: AppHandle, enabled: bool) -> Result<(), String> {
-    let window = app
-        .get_webview_window("main")
-        .ok_or("Failed to get main window")?;
-    window
-        .set_content_protected(enabled)
-        .map_err(|e| format!("Failed to update content protection: {}", e))?;
+fn set_screenshot_protection(app: AppHandle, enabled: bool) -> Result<(), CueCardError> {
+    *SCREENSHOT_PROTECTION.write() = enabled;
+    // The current deck's own setting, if it has one, stays in effect
+    screenshot_protection::apply(&app)?;
     Ok(())
 }
 
+#[cfg(feature = "desktop")]
 #[tauri::command]
-fn set_shortcuts_enabled(app: AppHandle, enabled: bool) -> Result<(), String> {
+fn set_shortcuts_enabled(app: AppHandle, enabled: bool) -> Result<(), CueCardError> {
     let shortcuts = [
         // General controls: Control+Option (Mac) / Control+Alt (Windows)
         Shortcut::new(Some(Modifiers::ALT | Modifiers::CONTROL), Code::KeyC),
@@ -1667,6 +3708,8 @@ fn set_shortcuts_enabled(app: AppHandle, enabled: bool) -> Result<(), String> {
         Shortcut::new(Some(Modifiers::ALT | Modifiers::CONTROL), Code::Equal),
         Shortcut::new(Some(Modifiers::ALT | Modifiers::CONTROL), Code::Space),
         Shortcut::new(Some(Modifiers::ALT | Modifiers::CONTROL), Code::Digit0),
+        Shortcut::new(Some(Modifiers::ALT | Modifiers::CONTROL), Code::KeyB),
+        Shortcut::new(Some(Modifiers::ALT | Modifiers::CONTROL), Code::KeyP),
         // Movement: Control+Option+Arrow (Mac) / Control+Alt+Arrow (Windows)
         Shortcut::new(Some(Modifiers::ALT | Modifiers::CONTROL), Code::ArrowLeft),
         Shortcut::new(Some(Modifiers::ALT | Modifiers::CONTROL), Code::ArrowRight),
@@ -1686,6 +3729,7 @@ fn set_shortcuts_enabled(app: AppHandle, enabled: bool) -> Result<(), String> {
             let _ = app.global_shortcut().unregister(shortcut);
         }
     }
+    *SHORTCUTS_ENABLED.write() = enabled;
     Ok(())
 }
 
@@ -1693,7 +3737,7 @@ fn set_shortcuts_enabled(app: AppHandle, enabled: bool) -> Result<(), String> {
 // MACOS SCREENSHOT PROTECTION
 // =============================================================================
 
-#[cfg(target_os = "macos")]
+#[cfg(all(target_os = "macos", feature = "desktop"))]
 #[allow(deprecated, unexpected_cfgs)]
 fn init_nspanel(app_handle: &AppHandle) {
     tauri_panel! {
@@ -1715,13 +3759,8 @@ fn init_nspanel(app_handle: &AppHandle) {
     // Prevent panel from activating the app (required for fullscreen display)
     panel.set_style_mask(StyleMask::empty().nonactivating_panel().resizable().into());
 
-    // Allow panel to display over fullscreen windows and join all spaces
-    panel.set_collection_behavior(
-        CollectionBehavior::new()
-            .full_screen_auxiliary()
-            .can_join_all_spaces()
-            .into(),
-    );
+    // Spaces, Stage Manager and full-screen behavior, adjustable at runtime
+    panel.set_collection_behavior(panel_behavior::current().collection_behavior().into());
 
     // Prevent panel from hiding when app deactivates
     panel.set_hides_on_deactivate(false);
@@ -1731,18 +3770,75 @@ fn init_nspanel(app_handle: &AppHandle) {
 // APPLICATION ENTRY POINT
 // =============================================================================
 
+#[cfg(feature = "desktop")]
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    #[cfg_attr(not(target_os = "macos"), allow(unused_mut))]
-    let mut builder = tauri::Builder::default()
+    if single_instance::forward_to_running() {
+        return;
+    }
+    single_instance::claim();
+
+    builder()
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|_app, _event| {
+            if let tauri::RunEvent::Exit = _event {
+                single_instance::release();
+            }
+            // Files opened with CueCard from Finder
+            #[cfg(target_os = "macos")]
+            if let tauri::RunEvent::Opened { urls } = _event {
+                let paths = urls
+                    .into_iter()
+                    .filter_map(|u| u.to_file_path().ok())
+                    .collect();
+                providers::local_file::open_paths(paths);
+            }
+        });
+}
+
+/// The app's plugins, setup, commands and window handlers, without the window
+/// context `run()` adds. Headless builds leave out the desktop integrations.
+pub fn builder() -> tauri::Builder<tauri::Wry> {
+    let builder = tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .plugin(tauri_plugin_store::Builder::default().build())
+        .plugin(
+            tauri_plugin_store::Builder::default()
+                .default_serialize_fn(secure_store::serialize)
+                .default_deserialize_fn(secure_store::deserialize)
+                .build(),
+        )
         .plugin(tauri_plugin_updater::Builder::default().build())
         .plugin(tauri_plugin_process::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_deep_link::init());
+
+    #[cfg(feature = "desktop")]
+    let builder = builder
+        .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(
             tauri_plugin_global_shortcut::Builder::new()
                 .with_handler(|app, shortcut, event| {
+                    // Push-to-talk into the parking lot: Control+Option+P (Mac) / Control+Alt+P (Windows), held
+                    if shortcut.id() == Shortcut::new(Some(Modifiers::ALT | Modifiers::CONTROL), Code::KeyP).id() {
+                        match event.state() {
+                            tauri_plugin_global_shortcut::ShortcutState::Pressed => {
+                                session::start_voice_capture()
+                            }
+                            tauri_plugin_global_shortcut::ShortcutState::Released => {
+                                session::stop_voice_capture();
+                            }
+                        }
+                        return;
+                    }
                     if event.state() == tauri_plugin_global_shortcut::ShortcutState::Pressed {
+                        // Hotkeys set on presenter profiles
+                        if let Some(name) = profiles::profile_for_shortcut(shortcut.id()) {
+                            if let Err(e) = profiles::apply_profile(app.clone(), name) {
+                                eprintln!("{}", e);
+                            }
+                            return;
+                        }
                         let action = match shortcut.id() {
                             // General controls: Control+Option (Mac) / Control+Alt (Windows)
                             id if id == Shortcut::new(Some(Modifiers::ALT | Modifiers::CONTROL), Code::KeyC).id() => "toggle-visibility",
@@ -1750,6 +3846,7 @@ pub fn run() {
                             id if id == Shortcut::new(Some(Modifiers::ALT | Modifiers::CONTROL), Code::Equal).id() => "opacity-up",
                             id if id == Shortcut::new(Some(Modifiers::ALT | Modifiers::CONTROL), Code::Space).id() => "timer-toggle",
                             id if id == Shortcut::new(Some(Modifiers::ALT | Modifiers::CONTROL), Code::Digit0).id() => "timer-reset",
+                            id if id == Shortcut::new(Some(Modifiers::ALT | Modifiers::CONTROL), Code::KeyB).id() => "previous-notes",
                             // Movement: Control+Option+Arrow (Mac) / Control+Alt+Arrow (Windows)
                             id if id == Shortcut::new(Some(Modifiers::ALT | Modifiers::CONTROL), Code::ArrowLeft).id() => "move-left",
                             id if id == Shortcut::new(Some(Modifiers::ALT | Modifiers::CONTROL), Code::ArrowRight).id() => "move-right",
@@ -1760,16 +3857,17 @@ pub fn run() {
                             id if id == Shortcut::new(Some(Modifiers::SHIFT | Modifiers::ALT | Modifiers::CONTROL), Code::ArrowDown).id() => "height-up",
                             _ => return,
                         };
+                        if action == "previous-notes" {
+                            notes_history::show_previous_notes();
+                        }
                         let _ = app.emit("shortcut-triggered", action);
                     }
                 })
                 .build(),
         );
 
-    #[cfg(target_os = "macos")]
-    {
-        builder = builder.plugin(tauri_nspanel::init());
-    }
+    #[cfg(all(target_os = "macos", feature = "desktop"))]
+    let builder = builder.plugin(tauri_nspanel::init());
 
     builder
         .setup(|app| {
@@ -1794,16 +3892,25 @@ pub fn run() {
                 }
             }
 
-            // Load stored tokens from persistent storage
-            load_tokens_from_store(app.handle());
+            // Load stored tokens and settings from persistent storage; an
+            // encrypted store stays empty until unlocked
+            secure_store::init(app.handle());
+            load_settings_from_store(app.handle());
+            // cuecard.toml, which overrides the stored settings
+            config_file::init(app.handle());
+            // What's new, after an update
+            changelog::check_for_update(app.handle());
 
             // Platform-specific window initialization
-            #[cfg(target_os = "macos")]
+            #[cfg(all(target_os = "macos", feature = "desktop"))]
             init_nspanel(app.app_handle());
+            #[cfg(feature = "desktop")]
+            topmost::start_watcher(app.handle());
 
             // Register global shortcuts
             // All shortcuts use Control+Option (Mac) / Control+Alt (Windows)
             // Height adjustments add Shift modifier
+            #[cfg(feature = "desktop")]
             let shortcuts = [
                 // General controls: Control+Option (Mac) / Control+Alt (Windows)
                 Shortcut::new(Some(Modifiers::ALT | Modifiers::CONTROL), Code::KeyC),       // Toggle visibility
@@ -1811,6 +3918,8 @@ pub fn run() {
                 Shortcut::new(Some(Modifiers::ALT | Modifiers::CONTROL), Code::Equal),      // Opacity up
                 Shortcut::new(Some(Modifiers::ALT | Modifiers::CONTROL), Code::Space),      // Timer toggle
                 Shortcut::new(Some(Modifiers::ALT | Modifiers::CONTROL), Code::Digit0),     // Timer reset
+                Shortcut::new(Some(Modifiers::ALT | Modifiers::CONTROL), Code::KeyB),       // Previous slide's notes
+                Shortcut::new(Some(Modifiers::ALT | Modifiers::CONTROL), Code::KeyP),       // Push-to-talk parking lot
                 // Movement: Control+Option+Arrow (Mac) / Control+Alt+Arrow (Windows)
                 Shortcut::new(Some(Modifiers::ALT | Modifiers::CONTROL), Code::ArrowLeft),  // Move left
                 Shortcut::new(Some(Modifiers::ALT | Modifiers::CONTROL), Code::ArrowRight), // Move right
@@ -1821,14 +3930,87 @@ pub fn run() {
                 Shortcut::new(Some(Modifiers::SHIFT | Modifiers::ALT | Modifiers::CONTROL), Code::ArrowDown), // Height up
             ];
 
+            #[cfg(feature = "desktop")]
             if let Err(e) = app.global_shortcut().register_multiple(shortcuts) {
                 eprintln!("Failed to register global shortcuts: {}", e);
             }
 
+            // cuecard:// links, both the one that launched the app and later ones
+            #[cfg(feature = "desktop")]
+            {
+                use tauri_plugin_deep_link::DeepLinkExt;
+
+                app.deep_link()
+                    .on_open_url(|event| deep_link::handle_urls(event.urls()));
+                if let Ok(Some(urls)) = app.deep_link().get_current() {
+                    deep_link::handle_urls(urls);
+                }
+            }
+
+            // Arguments handed over by later launches
+            #[cfg(feature = "desktop")]
+            single_instance::listen();
+
+            // Files opened with CueCard from Explorer arrive as arguments
+            #[cfg(target_os = "windows")]
+            providers::local_file::open_paths(
+                std::env::args()
+                    .skip(1)
+                    .map(std::path::PathBuf::from)
+                    .collect(),
+            );
+
+            // Fire session reminders
+            tauri::async_runtime::spawn(timer::run_reminder_loop());
+
+            // Finish inline countdowns from notes
+            tauri::async_runtime::spawn(countdown_cues::run_countdown_loop());
+
+            // Serve the stage display protocol again if it was left on
+            tauri::async_runtime::spawn(stage_display::restore());
+
+            // Restore the stored session, then refresh auth tokens before they expire
+            tauri::async_runtime::spawn(run_token_refresh_loop());
+
+            // Delete data past its retention period
+            tauri::async_runtime::spawn(retention::run_cleanup_loop());
+
+            // Watch for audio output devices coming and going
+            tauri::async_runtime::spawn(audio_output::run_device_watcher());
+
+            // Follow the OS's metered flag for low-data mode
+            tauri::async_runtime::spawn(low_data::run_metered_watcher());
+
+            // Offer to preload Slides links copied to the clipboard, when enabled
+            #[cfg(feature = "desktop")]
+            tauri::async_runtime::spawn(clipboard_watch::run_watcher());
+
+            // Check tokens and the deck's notes again after the machine sleeps
+            tauri::async_runtime::spawn(focus_revalidate::run_wake_watcher());
+
+            // Pause pollers before the machine sleeps and resume them in order on wake
+            sleep_wake::listen();
+
+            // Show the notes natively if the panel's webview stops responding
+            #[cfg(feature = "desktop")]
+            tauri::async_runtime::spawn(webview_watchdog::run_watchdog(app.handle().clone()));
+
+            // Pick up notes edited by others while a deck is open
+            tauri::async_runtime::spawn(revision_poll::run_poller());
+
+            // Tell offline and captive portal networks apart from auth failures
+            tauri::async_runtime::spawn(connectivity::run_monitor());
+
+            // Check the server, tokens and store, recovering what can be
+            tauri::async_runtime::spawn(health::run_health_loop());
+
+            // Sign out shared machines after inactivity or at the set time
+            tauri::async_runtime::spawn(auto_sign_out::run_scheduler());
+
             // Start the web server in a background thread
-            std::thread::spawn(|| {
-                let rt = tokio::runtime::Runtime::new().unwrap();
-                rt.block_on(start_server());
+            std::thread::spawn(|| match tokio::runtime::Runtime::new() {
+                Ok(rt) => rt.block_on(run_server_supervisor()),
+                Err(e) => eprintln!("Failed to start server runtime: {}", e),
             });
 
             Ok(())
@@ -1836,7 +4018,163 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             get_current_slide,
             get_current_notes,
+            get_next_notes,
+            rich_notes::get_current_rich_notes,
+            get_connected_clients,
+            set_active_client,
+            get_extension_compatibility,
+            cancel_prefetch,
+            get_notes_fetch_mode,
+            set_notes_fetch_mode,
+            integrations::create_integration_pairing,
+            integrations::list_integrations,
+            integrations::revoke_integration,
+            providers::powerpoint::start_powerpoint_tracking,
+            providers::powerpoint::stop_powerpoint_tracking,
+            providers::powerpoint::is_powerpoint_tracking,
+            providers::powerpoint::load_pptx_notes,
+            providers::pdf::load_pdf_deck,
+            providers::pdf::set_current_page,
+            providers::keynote::start_keynote_tracking,
+            providers::keynote::stop_keynote_tracking,
+            providers::keynote::is_keynote_tracking,
+            preload::preload_presentations,
+            preload::list_presentations,
+            disk_cache::list_cached_presentations,
+            disk_cache::get_cached_thumbnail,
+            api_usage::get_api_usage,
+            notes_pipeline::get_notes_pipeline,
+            notes_pipeline::set_notes_pipeline,
+            notes_pipeline::preview_notes_pipeline,
+            notes_masking::get_masking_config,
+            notes_masking::set_masking_config,
+            notes_masking::is_presentation_masked,
+            notes_masking::set_presentation_masking,
+            glossary::get_glossary,
+            glossary::set_glossary,
+            glossary::shape_notes_for_speech,
+            notes_summary::summarize_notes,
+            notes_summary::get_notes_summary,
+            notes_summary::get_summary_settings,
+            notes_summary::set_summary_settings,
+            notes_translation::get_translation_settings,
+            notes_translation::set_translation_settings,
+            notes_translation::get_deck_translation,
+            notes_translation::set_deck_translation,
+            notes_history::show_previous_notes,
+            notes_history::show_current_notes,
+            notes_check::check_notes,
+            notes_sources::get_notes_merge_rules,
+            notes_sources::set_notes_merge_rules,
+            notes_sources::set_notes_override,
+            notes_audit::get_change_history,
+            notes_audit::revert_note_change,
+            session::start_session,
+            session::get_session,
+            session::pin_note,
+            session::unpin_note,
+            session::clear_pinned_notes,
+            session::add_reminder,
+            session::add_local_time_reminder,
+            session::remove_reminder,
+            session::list_reminders,
+            event_time::get_event_timezone,
+            event_time::set_event_timezone,
+            session::end_session,
+            session::log_question,
+            session::park_note,
+            session::list_parked_notes,
+            session::start_voice_capture,
+            session::stop_voice_capture,
+            session_report::get_last_session_summary,
+            session_report::export_session_report,
+            notes_export::export_notes,
+            notes_freshness::refetch_slide,
+            script_audio::export_script_audio,
+            share_link::start_notes_share,
+            share_link::stop_notes_share,
+            share_link::get_notes_share,
+            stage_display::start_stage_display,
+            stage_display::stop_stage_display,
+            stage_display::get_stage_display,
+            health::get_health,
+            connectivity::get_connectivity,
+            interpreter::get_interpreter_notes,
+            interpreter::set_interpreter_notes,
+            interpreter::get_interpreter_lookahead,
+            interpreter::set_interpreter_lookahead,
+            timer::timer_start,
+            timer::timer_pause,
+            timer::timer_reset,
+            timer::get_timer_state,
+            timer::start_section,
+            timer::lap_section,
+            timer::stop_section,
+            timer::get_sections,
+            countdown_cues::get_countdown_cue,
+            countdown_cues::stop_countdown_cue,
+            rehearsal::get_section_history,
+            retention::get_retention_settings,
+            retention::set_retention_settings,
+            retention::purge_all_data,
+            auto_sign_out::get_auto_sign_out,
+            auto_sign_out::set_auto_sign_out,
+            auto_sign_out::keep_signed_in,
+            data_export::export_my_data,
+            secure_store::get_store_encryption_status,
+            secure_store::unlock_store,
+            secure_store::enable_store_encryption,
+            secure_store::disable_store_encryption,
+            #[cfg(feature = "desktop")]
+            topmost::get_topmost_banding,
+            #[cfg(feature = "desktop")]
+            topmost::set_topmost_banding,
+            #[cfg(feature = "desktop")]
+            panel_behavior::get_panel_behavior,
+            #[cfg(feature = "desktop")]
+            panel_behavior::set_panel_behavior,
+            #[cfg(feature = "desktop")]
+            profiles::list_profiles,
+            #[cfg(feature = "desktop")]
+            profiles::get_active_profile,
+            #[cfg(feature = "desktop")]
+            profiles::set_profiles,
+            #[cfg(feature = "desktop")]
+            profiles::apply_profile,
+            audio_output::list_audio_output_devices,
+            audio_output::select_audio_output,
+            audio_output::get_audio_output,
+            http_client::get_network_settings,
+            http_client::set_network_settings,
+            low_data::get_low_data_status,
+            low_data::set_low_data_mode,
+            #[cfg(feature = "desktop")]
+            clipboard_watch::get_clipboard_watch,
+            #[cfg(feature = "desktop")]
+            clipboard_watch::set_clipboard_watch,
+            focus_revalidate::get_revalidate_on_focus,
+            revision_poll::get_revision_poll_interval,
+            revision_poll::set_revision_poll_interval,
+            focus_revalidate::set_revalidate_on_focus,
+            slide_mapping::get_slide_mapping,
+            slide_mapping::set_slide_number_offset,
+            #[cfg(feature = "desktop")]
+            webview_watchdog::webview_heartbeat,
+            slide_inference::submit_transcript,
+            slide_inference::set_slide_inference_enabled,
+            slide_inference::is_slide_inference_enabled,
+            slide_inference::accept_slide_suggestion,
+            providers::local_file::load_local_notes,
+            providers::local_file::set_local_slide,
+            providers::accessibility::check_accessibility_permission,
+            providers::accessibility::open_accessibility_settings,
+            providers::accessibility::start_presenter_tracking,
+            providers::accessibility::stop_presenter_tracking,
+            providers::accessibility::is_presenter_tracking,
             get_auth_status,
+            get_server_port,
+            set_server_port,
+            config_file::get_config_file_status,
             get_firestore_project_id,
             init_analytics,
             send_event,
@@ -1845,13 +4183,262 @@ pub fn run() {
             check_and_mark_first_open,
             get_firebase_id_token,
             has_slides_scope,
+            get_slides_scopes,
+            has_slides_write_scope,
+            has_drive_scope,
+            scopes::get_granted_scopes,
+            scopes::revoke_scope,
             get_user_info,
+            get_auth_details,
+            changelog::get_whats_new,
+            changelog::dismiss_whats_new,
             start_login,
+            reauthorize_slides,
+            canva::start_canva_login,
+            canva::is_canva_connected,
+            canva::disconnect_canva,
+            device_login::cancel_device_login,
             logout,
             refresh_notes,
+            update_slide_notes,
             set_screenshot_protection,
+            screenshot_protection::get_screenshot_protection,
+            screenshot_protection::set_deck_screenshot_protection,
+            #[cfg(feature = "desktop")]
             set_shortcuts_enabled
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .on_window_event(|_window, event| match event {
+            // Files dropped on the panel
+            tauri::WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. }) => {
+                providers::local_file::open_paths(paths.clone());
+            }
+            tauri::WindowEvent::Focused(true) => {
+                auto_sign_out::record_activity();
+                focus_revalidate::on_focus();
+            }
+            _ => {}
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Held by tests in any module that set the deck order or notes cache
+    pub static DECK_LOCK: parking_lot::Mutex<()> = parking_lot::const_mutex(());
+
+    #[test]
+    fn restored_oauth_tokens_are_checked() {
+        let _clock = clock::tests::SERIAL.lock();
+        let mut refresh = Some(" ".to_string());
+        let mut expires_at = Some(1);
+        assert!(validate_oauth_tokens("", &mut refresh, &mut expires_at).is_err());
+
+        let mut refresh = Some("refresh".to_string());
+        let mut expires_at = Some(clock::now_secs() + 600);
+        validate_oauth_tokens("", &mut refresh, &mut expires_at).unwrap();
+        assert_eq!(expires_at, Some(0));
+
+        let mut refresh = Some(String::new());
+        let mut expires_at = Some(clock::now_secs() + 10 * MAX_TOKEN_LIFETIME_SECS);
+        validate_oauth_tokens("access", &mut refresh, &mut expires_at).unwrap();
+        assert_eq!(refresh, None);
+        assert_eq!(expires_at, Some(0));
+
+        let expiry = clock::now_secs() + 600;
+        let mut expires_at = Some(expiry);
+        validate_oauth_tokens("access", &mut None, &mut expires_at).unwrap();
+        assert_eq!(expires_at, Some(expiry));
+    }
+
+    fn client_slide(client_id: &str, slide_number: i32) -> SlideData {
+        SlideData {
+            presentation_id: format!("deck-{}", client_id),
+            slide_id: format!("p{}", slide_number),
+            slide_number,
+            title: format!("{}'s deck", client_id),
+            mode: "google".to_string(),
+            timestamp: 0,
+            url: String::new(),
+            force_refresh: None,
+            client_id: Some(client_id.to_string()),
+            scraped_notes: None,
+        }
+    }
+
+    #[test]
+    fn follows_the_first_client_until_it_goes_quiet() {
+        let mut clients = HashMap::new();
+        let mut active = None;
+        let now = 1_700_000_000;
+
+        let (is_active, changed) = track_client(
+            &mut clients,
+            &mut active,
+            "work",
+            &client_slide("work", 1),
+            now,
+        );
+        assert!(is_active && changed);
+        let (is_active, changed) = track_client(
+            &mut clients,
+            &mut active,
+            "personal",
+            &client_slide("personal", 4),
+            now + 1,
+        );
+        assert!(!is_active && changed);
+        // Another slide from a known client doesn't change the list
+        let (is_active, changed) = track_client(
+            &mut clients,
+            &mut active,
+            "work",
+            &client_slide("work", 2),
+            now + 2,
+        );
+        assert!(is_active && !changed);
+
+        let list = client_list(&clients, active.as_deref());
+        assert_eq!(
+            list.iter()
+                .map(|c| (
+                    c.client_id.as_str(),
+                    c.is_active,
+                    c.current_slide.slide_number
+                ))
+                .collect::<Vec<_>>(),
+            vec![("work", true, 2), ("personal", false, 4)]
+        );
+
+        // "work" stops reporting; the next update from "personal" takes over
+        let later = now + 2 + CLIENT_STALE_SECS;
+        let (is_active, changed) = track_client(
+            &mut clients,
+            &mut active,
+            "personal",
+            &client_slide("personal", 5),
+            later,
+        );
+        assert!(is_active && changed);
+        assert_eq!(active.as_deref(), Some("personal"));
+        assert_eq!(clients.len(), 1);
+    }
+
+    #[test]
+    fn cancels_one_prefetch_or_all_of_them() {
+        let first = PrefetchTracker::register("deck-a");
+        let second = PrefetchTracker::register("deck-b");
+
+        assert!(cancel_prefetch(Some(first.id.clone())));
+        assert!(first.is_cancelled());
+        assert!(!second.is_cancelled());
+        assert!(!cancel_prefetch(Some("unknown".to_string())));
+
+        assert!(cancel_prefetch(None));
+        assert!(second.is_cancelled());
+
+        // Finished prefetches unregister themselves
+        let (first_id, second_id) = (first.id.clone(), second.id.clone());
+        drop(first);
+        drop(second);
+        let active = ACTIVE_PREFETCHES.read();
+        assert!(!active.contains_key(&first_id) && !active.contains_key(&second_id));
+    }
+
+    #[test]
+    fn fetches_the_current_then_the_next_slide_first() {
+        let ids = |names: &[&str]| names.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        let mut order = ids(&["s1", "s2", "s3", "s4", "s5"]);
+        current_slides_first(&mut order, Some("s3"), Some("s4"));
+        assert_eq!(order, ids(&["s3", "s4", "s1", "s2", "s5"]));
+
+        // The last slide has no next one, and a slide that left the deck is ignored
+        let mut order = ids(&["s1", "s2", "s3"]);
+        current_slides_first(&mut order, Some("s3"), None);
+        assert_eq!(order, ids(&["s3", "s1", "s2"]));
+        current_slides_first(&mut order, Some("gone"), None);
+        assert_eq!(order, ids(&["s3", "s1", "s2"]));
+
+        assert_eq!(
+            serde_json::to_value(NotesFetchMode::PerPage).unwrap(),
+            "per_page"
+        );
+    }
+
+    #[test]
+    fn backs_off_rate_limited_pages_a_few_times() {
+        use std::time::Duration;
+
+        assert_eq!(
+            page_retry_delay(StatusCode::TOO_MANY_REQUESTS, 0),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(
+            page_retry_delay(StatusCode::SERVICE_UNAVAILABLE, 2),
+            Some(Duration::from_millis(2000))
+        );
+        assert_eq!(
+            page_retry_delay(StatusCode::TOO_MANY_REQUESTS, PAGE_FETCH_MAX_RETRIES),
+            None
+        );
+        assert_eq!(page_retry_delay(StatusCode::NOT_FOUND, 0), None);
+        assert_eq!(page_retry_delay(StatusCode::OK, 0), None);
+    }
+
+    #[test]
+    fn refreshes_ahead_and_reports_expiry_once() {
+        assert!(refresh_due(1_600, 1_000));
+        assert!(!refresh_due(1_601, 1_000));
+        assert!(refresh_due(900, 1_000));
+
+        let mut notice = ExpiryNotice::default();
+        // Failing while the token still works isn't worth reporting
+        assert!(!notice.failed(1_000, 999));
+        assert!(notice.failed(1_000, 1_000));
+        assert!(!notice.failed(1_000, 1_060));
+        // A later refresh arms it again for the next expiry
+        notice.refreshed();
+        assert!(notice.failed(4_600, 4_600));
+    }
+
+    #[test]
+    fn parses_versions() {
+        assert_eq!(parse_version("1.4.2"), Some((1, 4, 2)));
+        assert_eq!(parse_version(" v2.0 "), Some((2, 0, 0)));
+        assert_eq!(parse_version("3"), Some((3, 0, 0)));
+        assert_eq!(parse_version("1.4.2.9"), Some((1, 4, 2)));
+        assert_eq!(parse_version("1.4.2-beta"), None);
+        assert_eq!(parse_version("1..2"), None);
+        assert_eq!(parse_version(""), None);
+    }
+
+    #[test]
+    fn compares_versions_numerically() {
+        assert!(parse_version("1.10.0") > parse_version("1.9.9"));
+        assert_eq!(
+            extension_compatibility(Some("1.1.0")),
+            ExtensionCompatibility::Outdated
+        );
+        assert_eq!(
+            extension_compatibility(Some("1.1.1")),
+            ExtensionCompatibility::Supported
+        );
+        assert_eq!(
+            extension_compatibility(Some("1.12.0")),
+            ExtensionCompatibility::Supported
+        );
+        assert_eq!(
+            extension_compatibility(Some("2.0.0")),
+            ExtensionCompatibility::AppOutdated
+        );
+        assert_eq!(
+            extension_compatibility(Some("latest")),
+            ExtensionCompatibility::Unknown
+        );
+        assert_eq!(
+            extension_compatibility(None),
+            ExtensionCompatibility::Unknown
+        );
+    }
 }
@@ -0,0 +1,228 @@
+//! Low-data mode for tethered and metered connections
+//!
+//! While active, preloads skip slide thumbnails, analytics events aren't
+//! uploaded, and whole-deck fetches for preloads and notes checks ask only for
+//! the fields notes and titles are read from (`PRESENTATION_FIELDS`), so text
+//! in tables and groups isn't fetched. Fetching notes for the panel always
+//! uses these masks. The mode can be turned on by hand or left on `auto`,
+//! which follows the OS's metered flag: the connection cost on Windows and
+//! NetworkManager's metered state on Linux. macOS doesn't report one to
+//! command-line tools, so `auto` stays off there. Changes are sent as
+//! `low-data-changed`.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_store::StoreExt;
+
+use crate::APP_HANDLE;
+
+const LOW_DATA_MODE_KEY: &str = "low_data_mode";
+const METERED_POLL_SECS: u64 = 30;
+
+/// Field mask for `presentations.get`: titles, revision, slide text, formatted notes and
+/// hidden flags
+pub const PRESENTATION_FIELDS: &str = "title,revisionId,slides(objectId,pageElements(shape(placeholder(type),text(textElements(textRun(content))))),slideProperties(isSkipped,notesPage(pageElements(shape(placeholder(type),text(textElements(paragraphMarker(bullet(nestingLevel,glyph)),textRun(content,style(bold,italic,underline,strikethrough,foregroundColor,link)))))))))";
+/// Field mask for `presentations.pages.get`: the notes and their formatting only
+pub const PAGE_FIELDS: &str = "slideProperties(notesPage(pageElements(shape(placeholder(type),text(textElements(paragraphMarker(bullet(nestingLevel,glyph)),textRun(content,style(bold,italic,underline,strikethrough,foregroundColor,link))))))))";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LowDataMode {
+    Off,
+    On,
+    /// On while the connection is metered
+    #[default]
+    Auto,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct LowDataStatus {
+    pub mode: LowDataMode,
+    /// Whether the OS reports the connection as metered
+    pub metered: bool,
+    pub active: bool,
+}
+
+impl LowDataStatus {
+    /// Apply a new mode or metered state; whether low-data mode flipped
+    fn apply(&mut self, change: impl FnOnce(&mut LowDataStatus)) -> bool {
+        let was_active = self.active;
+        change(self);
+        self.active = match self.mode {
+            LowDataMode::Off => false,
+            LowDataMode::On => true,
+            LowDataMode::Auto => self.metered,
+        };
+        self.active != was_active
+    }
+}
+
+static LOW_DATA: Lazy<Arc<RwLock<LowDataStatus>>> =
+    Lazy::new(|| Arc::new(RwLock::new(LowDataStatus::default())));
+
+pub fn is_active() -> bool {
+    LOW_DATA.read().active
+}
+
+/// `url` with the field mask appended
+pub fn with_field_mask(url: String, fields: &str) -> String {
+    let separator = if url.contains('?') { '&' } else { '?' };
+    format!("{}{}fields={}", url, separator, urlencoding::encode(fields))
+}
+
+/// `url` with the field mask appended while low-data mode is on
+pub fn with_fields(url: String, fields: &str) -> String {
+    if !is_active() {
+        return url;
+    }
+    with_field_mask(url, fields)
+}
+
+/// Apply a new mode or metered state, announcing it if low-data mode flips
+fn update(change: impl FnOnce(&mut LowDataStatus)) {
+    let (status, flipped) = {
+        let mut status = LOW_DATA.write();
+        let flipped = status.apply(change);
+        (*status, flipped)
+    };
+    if flipped {
+        if let Some(app) = APP_HANDLE.read().as_ref() {
+            let _ = app.emit("low-data-changed", status);
+        }
+    }
+}
+
+pub fn load_low_data_from_store(app: &AppHandle) {
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Some(mode) = store
+            .get(LOW_DATA_MODE_KEY)
+            .and_then(|v| serde_json::from_value::<LowDataMode>(v).ok())
+        {
+            update(|status| status.mode = mode);
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+async fn query_metered() -> Result<bool, String> {
+    // Unrestricted, Fixed, Variable or Unknown
+    const SCRIPT: &str = "[Windows.Networking.Connectivity.NetworkInformation,Windows.Networking.Connectivity,ContentType=WindowsRuntime] | Out-Null; $p = [Windows.Networking.Connectivity.NetworkInformation]::GetInternetConnectionProfile(); if ($p) { $p.GetConnectionCost().NetworkCostType }";
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let output = tokio::process::Command::new("powershell.exe")
+        .args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to read connection cost: {}", e))?;
+    let cost = String::from_utf8_lossy(&output.stdout);
+    Ok(matches!(cost.trim(), "Fixed" | "Variable"))
+}
+
+#[cfg(target_os = "linux")]
+async fn query_metered() -> Result<bool, String> {
+    let output = tokio::process::Command::new("nmcli")
+        .args(["-t", "-f", "GENERAL.METERED", "dev", "show"])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to read metered state: {}", e))?;
+    Ok(nmcli_metered(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Whether any device in `nmcli`'s output is metered, going by
+/// "GENERAL.METERED:yes (guessed)" per device
+#[cfg(target_os = "linux")]
+fn nmcli_metered(output: &str) -> bool {
+    output
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .any(|(_, value)| value.starts_with("yes"))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+async fn query_metered() -> Result<bool, String> {
+    Ok(false)
+}
+
+/// Follow the OS's metered flag; runs for the lifetime of the app
+pub async fn run_metered_watcher() {
+    let mut reported_error = false;
+    loop {
+        if LOW_DATA.read().mode == LowDataMode::Auto {
+            match query_metered().await {
+                Ok(metered) => update(|status| status.metered = metered),
+                // Logged once; the check keeps running in case it recovers
+                Err(e) if !reported_error => {
+                    reported_error = true;
+                    eprintln!("{}", e);
+                }
+                Err(_) => {}
+            }
+        }
+        tokio::time::sleep(Duration::from_secs(METERED_POLL_SECS)).await;
+    }
+}
+
+#[tauri::command]
+pub fn get_low_data_status() -> LowDataStatus {
+    *LOW_DATA.read()
+}
+
+#[tauri::command]
+pub fn set_low_data_mode(app: AppHandle, mode: LowDataMode) -> LowDataStatus {
+    update(|status| status.mode = mode);
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Ok(json) = serde_json::to_value(mode) {
+            store.set(LOW_DATA_MODE_KEY, json);
+            let _ = store.save();
+        }
+    }
+    *LOW_DATA.read()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auto_follows_the_metered_flag() {
+        let mut status = LowDataStatus::default();
+        assert!(!status.apply(|_| {}));
+        assert!(status.apply(|s| s.metered = true));
+        assert!(status.active);
+
+        // A mode set by hand wins over the connection
+        assert!(status.apply(|s| s.mode = LowDataMode::Off));
+        assert!(!status.apply(|s| s.metered = false));
+        assert!(status.apply(|s| s.mode = LowDataMode::On));
+        assert!(status.active);
+    }
+
+    #[test]
+    fn appends_field_masks() {
+        assert_eq!(
+            with_field_mask("https://x/p/1".to_string(), "title,slides(objectId)"),
+            "https://x/p/1?fields=title%2Cslides%28objectId%29"
+        );
+        assert_eq!(
+            with_field_mask("https://x/p/1?a=b".to_string(), "title"),
+            "https://x/p/1?a=b&fields=title"
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn reads_nmclis_metered_state() {
+        assert!(nmcli_metered(
+            "GENERAL.METERED:no (guessed)\nGENERAL.METERED:yes (guessed)\n"
+        ));
+        assert!(!nmcli_metered(
+            "GENERAL.METERED:no\nGENERAL.METERED:unknown\n"
+        ));
+        assert!(!nmcli_metered(""));
+    }
+}
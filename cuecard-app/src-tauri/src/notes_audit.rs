@@ -0,0 +1,198 @@
+//! Change history for notes typed in CueCard
+//!
+//! Every change to a slide's override records who made it (the signed-in
+//! email, or "local"), when, and the text before and after. The history is
+//! kept in the store, up to `MAX_CHANGES_PER_SLIDE` per slide, and any earlier
+//! version can be restored with `revert_note_change`, which is itself recorded.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+use uuid::Uuid;
+
+use crate::error::CueCardError;
+use crate::notes_sources::NotesSource;
+use crate::FIREBASE_TOKENS;
+
+const CHANGE_HISTORY_KEY: &str = "notes_change_history";
+const MAX_CHANGES_PER_SLIDE: usize = 50;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteChange {
+    pub id: String,
+    pub presentation_id: String,
+    pub slide_id: String,
+    pub source: NotesSource,
+    pub author: String,
+    pub changed_at: i64,
+    pub previous: Option<String>,
+    /// The version after this change; `None` when the notes were cleared
+    pub text: Option<String>,
+}
+
+static CHANGE_HISTORY: Lazy<Arc<RwLock<Vec<NoteChange>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(Vec::new())));
+
+fn current_author() -> String {
+    FIREBASE_TOKENS
+        .read()
+        .as_ref()
+        .and_then(|t| t.email.clone())
+        .unwrap_or_else(|| "local".to_string())
+}
+
+fn save_to_store(app: &AppHandle) {
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Ok(json) = serde_json::to_value(&*CHANGE_HISTORY.read()) {
+            store.set(CHANGE_HISTORY_KEY, json);
+            let _ = store.save();
+        }
+    }
+}
+
+pub fn load_history_from_store(app: &AppHandle) {
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Some(changes) = store
+            .get(CHANGE_HISTORY_KEY)
+            .and_then(|v| serde_json::from_value::<Vec<NoteChange>>(v).ok())
+        {
+            *CHANGE_HISTORY.write() = changes;
+        }
+    }
+}
+
+/// Log a change; nothing is recorded when the text stays the same
+pub fn record_change(
+    app: &AppHandle,
+    presentation_id: &str,
+    slide_id: &str,
+    source: NotesSource,
+    previous: Option<String>,
+    text: Option<String>,
+) {
+    let change = NoteChange {
+        id: Uuid::new_v4().to_string(),
+        presentation_id: presentation_id.to_string(),
+        slide_id: slide_id.to_string(),
+        source,
+        author: current_author(),
+        changed_at: chrono::Utc::now().timestamp(),
+        previous,
+        text,
+    };
+    if push_change(&mut CHANGE_HISTORY.write(), change) {
+        save_to_store(app);
+    }
+}
+
+/// Add a change, dropping the slide's oldest beyond `MAX_CHANGES_PER_SLIDE`;
+/// false when it didn't change the text
+fn push_change(history: &mut Vec<NoteChange>, change: NoteChange) -> bool {
+    if change.previous == change.text {
+        return false;
+    }
+    let same_slide = |c: &NoteChange| {
+        c.presentation_id == change.presentation_id && c.slide_id == change.slide_id
+    };
+    if history.iter().filter(|c| same_slide(c)).count() >= MAX_CHANGES_PER_SLIDE {
+        if let Some(oldest) = history.iter().position(same_slide) {
+            history.remove(oldest);
+        }
+    }
+    history.push(change);
+    true
+}
+
+fn find_change(history: &[NoteChange], change_id: &str) -> Option<NoteChange> {
+    history.iter().find(|c| c.id == change_id).cloned()
+}
+
+/// Changes to a slide's notes, newest first
+#[tauri::command]
+pub fn get_change_history(presentation_id: String, slide_id: String) -> Vec<NoteChange> {
+    CHANGE_HISTORY
+        .read()
+        .iter()
+        .rev()
+        .filter(|c| c.presentation_id == presentation_id && c.slide_id == slide_id)
+        .cloned()
+        .collect()
+}
+
+/// Restore the notes as they were right after the given change
+#[tauri::command]
+pub fn revert_note_change(app: AppHandle, change_id: String) -> Result<NoteChange, CueCardError> {
+    let change = find_change(&CHANGE_HISTORY.read(), &change_id).ok_or("Unknown note change")?;
+
+    crate::notes_sources::set_notes_override(
+        app,
+        change.presentation_id.clone(),
+        change.slide_id.clone(),
+        change.text.clone(),
+    );
+    Ok(change)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(id: &str, slide_id: &str, previous: Option<&str>, text: Option<&str>) -> NoteChange {
+        NoteChange {
+            id: id.to_string(),
+            presentation_id: "deck".to_string(),
+            slide_id: slide_id.to_string(),
+            source: NotesSource::LocalOverride,
+            author: "local".to_string(),
+            changed_at: 0,
+            previous: previous.map(str::to_string),
+            text: text.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn records_only_changes_to_the_text() {
+        let mut history = Vec::new();
+        assert!(push_change(
+            &mut history,
+            change("1", "p1", None, Some("Intro"))
+        ));
+        assert!(!push_change(
+            &mut history,
+            change("2", "p1", Some("Intro"), Some("Intro"))
+        ));
+        assert!(push_change(
+            &mut history,
+            change("3", "p1", Some("Intro"), None)
+        ));
+        assert_eq!(history.len(), 2);
+    }
+
+    #[test]
+    fn caps_each_slide_separately() {
+        let mut history = vec![change("other", "p2", None, Some("Kept"))];
+        for i in 0..=MAX_CHANGES_PER_SLIDE {
+            let id = i.to_string();
+            push_change(&mut history, change(&id, "p1", None, Some(id.as_str())));
+        }
+        assert_eq!(history.len(), MAX_CHANGES_PER_SLIDE + 1);
+        assert_eq!(history[0].id, "other");
+        assert_eq!(history[1].id, "1");
+    }
+
+    #[test]
+    fn reverts_to_the_text_after_a_change() {
+        let mut history = Vec::new();
+        push_change(&mut history, change("a", "p1", None, Some("First draft")));
+        push_change(
+            &mut history,
+            change("b", "p1", Some("First draft"), Some("Second")),
+        );
+        let target = find_change(&history, "a").unwrap();
+        assert_eq!(target.text.as_deref(), Some("First draft"));
+        assert!(find_change(&history, "missing").is_none());
+    }
+}
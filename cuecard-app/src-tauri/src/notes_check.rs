@@ -0,0 +1,404 @@
+//! Pre-talk check of a deck's notes
+//!
+//! `check_notes` fetches the presentation and flags, per slide:
+//!
+//! - likely typos: repeated words and common misspellings
+//! - product names spelled differently from the user's glossary ("Cue Card"
+//!   for "CueCard"), including one-letter slips in longer names
+//! - numbers in the notes close to, but different from, a number on the slide
+//!   ("42%" in the notes, "45%" on the slide)
+//!
+//! These are heuristics; findings are suggestions to look at, not errors.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use std::ops::Range;
+
+use crate::error::CueCardError;
+use crate::{extract_notes_from_slide, fetch_presentation, glossary};
+
+static WORD: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"[\p{L}\p{N}][\p{L}\p{N}'’]*").expect("valid word regex"));
+static NUMBER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\b\d[\d,]*(?:\.\d+)?(\s?%)?").expect("valid number regex"));
+
+/// Misspelling -> correction, lowercase
+const COMMON_MISSPELLINGS: &[(&str, &str)] = &[
+    ("accross", "across"),
+    ("acheive", "achieve"),
+    ("adress", "address"),
+    ("alot", "a lot"),
+    ("begining", "beginning"),
+    ("beleive", "believe"),
+    ("calender", "calendar"),
+    ("definately", "definitely"),
+    ("enviroment", "environment"),
+    ("existance", "existence"),
+    ("goverment", "government"),
+    ("independant", "independent"),
+    ("occured", "occurred"),
+    ("occurence", "occurrence"),
+    ("publically", "publicly"),
+    ("recieve", "receive"),
+    ("recieved", "received"),
+    ("seperate", "separate"),
+    ("succesful", "successful"),
+    ("teh", "the"),
+    ("thier", "their"),
+    ("tommorow", "tomorrow"),
+    ("untill", "until"),
+    ("wich", "which"),
+];
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingKind {
+    Typo,
+    ProductName,
+    Number,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NoteFinding {
+    pub kind: FindingKind,
+    /// The flagged text as it appears in the notes
+    pub text: String,
+    pub suggestion: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SlideFindings {
+    pub slide_id: String,
+    pub slide_number: i32,
+    pub findings: Vec<NoteFinding>,
+}
+
+/// Text of every shape, group and table on a slide
+fn collect_slide_text(elements: &[serde_json::Value], out: &mut String) {
+    let push_runs = |text: &serde_json::Value, out: &mut String| {
+        for run in text
+            .get("textElements")
+            .and_then(|e| e.as_array())
+            .into_iter()
+            .flatten()
+        {
+            if let Some(content) = run
+                .get("textRun")
+                .and_then(|r| r.get("content"))
+                .and_then(|c| c.as_str())
+            {
+                out.push_str(content);
+            }
+        }
+        out.push('\n');
+    };
+
+    for element in elements {
+        if let Some(text) = element.get("shape").and_then(|s| s.get("text")) {
+            push_runs(text, out);
+        }
+        if let Some(children) = element
+            .get("elementGroup")
+            .and_then(|g| g.get("children"))
+            .and_then(|c| c.as_array())
+        {
+            collect_slide_text(children, out);
+        }
+        let rows = element
+            .get("table")
+            .and_then(|t| t.get("tableRows"))
+            .and_then(|r| r.as_array());
+        for row in rows.into_iter().flatten() {
+            for cell in row
+                .get("tableCells")
+                .and_then(|c| c.as_array())
+                .into_iter()
+                .flatten()
+            {
+                if let Some(text) = cell.get("text") {
+                    push_runs(text, out);
+                }
+            }
+        }
+    }
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous + usize::from(ca != *cb);
+            previous = row[j + 1];
+            row[j + 1] = substitution.min(previous + 1).min(row[j] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Lowercase letters and digits only, so "Cue-Card" and "cuecard" compare equal
+fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn typos(notes: &str, words: &[Range<usize>]) -> Vec<NoteFinding> {
+    let mut findings = Vec::new();
+
+    for pair in words.windows(2) {
+        let (first, second) = (&notes[pair[0].clone()], &notes[pair[1].clone()]);
+        let gap = &notes[pair[0].end..pair[1].start];
+        if first.eq_ignore_ascii_case(second)
+            && first.chars().any(|c| c.is_alphabetic())
+            && gap.chars().all(char::is_whitespace)
+        {
+            findings.push(NoteFinding {
+                kind: FindingKind::Typo,
+                text: notes[pair[0].start..pair[1].end].to_string(),
+                suggestion: Some(first.to_string()),
+                message: format!("\"{}\" is repeated", first),
+            });
+        }
+    }
+
+    for range in words {
+        let word = &notes[range.clone()];
+        let lower = word.to_lowercase();
+        if let Some((_, correction)) = COMMON_MISSPELLINGS.iter().find(|(w, _)| *w == lower) {
+            findings.push(NoteFinding {
+                kind: FindingKind::Typo,
+                text: word.to_string(),
+                suggestion: Some(correction.to_string()),
+                message: format!("\"{}\" looks like a typo", word),
+            });
+        }
+    }
+
+    findings
+}
+
+fn product_names(notes: &str, words: &[Range<usize>]) -> Vec<NoteFinding> {
+    let mut findings = Vec::new();
+    let mut flagged: Vec<Range<usize>> = Vec::new();
+
+    for entry in glossary::entries() {
+        let term = entry.term;
+        let target = normalize(&term);
+        let term_words = WORD.find_iter(&term).count().max(1);
+        if target.is_empty() {
+            continue;
+        }
+
+        // "Cue Card" and "Cuecard" both count as spellings of "CueCard"
+        for size in term_words.saturating_sub(1).max(1)..=term_words + 1 {
+            for window in words.windows(size) {
+                let span = window[0].start..window[size - 1].end;
+                let text = &notes[span.clone()];
+                // Only words joined by spaces or hyphens form one name
+                let joined = window.windows(2).all(|pair| {
+                    notes[pair[0].end..pair[1].start]
+                        .chars()
+                        .all(|c| c == ' ' || c == '-')
+                });
+                if !joined || text == term {
+                    continue;
+                }
+
+                let found = normalize(text);
+                let matches = found == target
+                    || (size == term_words
+                        && target.chars().count() >= 6
+                        && found.get(..2) == target.get(..2)
+                        && found.trim_end_matches('s') != target.trim_end_matches('s')
+                        && levenshtein(&found, &target) == 1);
+                if !matches
+                    || flagged
+                        .iter()
+                        .any(|f| f.start < span.end && span.start < f.end)
+                {
+                    continue;
+                }
+
+                flagged.push(span);
+                findings.push(NoteFinding {
+                    kind: FindingKind::ProductName,
+                    text: text.to_string(),
+                    suggestion: Some(term.clone()),
+                    message: format!("\"{}\" is spelled \"{}\" in your glossary", text, term),
+                });
+            }
+        }
+    }
+
+    findings
+}
+
+/// Value and whether it's a percentage, leaving out years and small counts
+fn numbers(text: &str) -> Vec<(String, f64, bool)> {
+    NUMBER
+        .captures_iter(text)
+        .filter_map(|caps| {
+            let raw = caps[0].trim().to_string();
+            let percent = caps.get(1).is_some();
+            let value: f64 = raw
+                .trim_end_matches('%')
+                .trim()
+                .replace(',', "")
+                .parse()
+                .ok()?;
+            let is_year = !percent && value.fract() == 0.0 && (1900.0..=2100.0).contains(&value);
+            if is_year || (!percent && value < 10.0) {
+                return None;
+            }
+            Some((raw, value, percent))
+        })
+        .collect()
+}
+
+fn mismatched_numbers(notes: &str, slide_text: &str) -> Vec<NoteFinding> {
+    let on_slide = numbers(slide_text);
+    let mut findings = Vec::new();
+
+    for (raw, value, percent) in numbers(notes) {
+        if on_slide
+            .iter()
+            .any(|(_, v, p)| *p == percent && *v == value)
+        {
+            continue;
+        }
+        // The closest number of the same kind and magnitude is probably the same figure
+        let closest = on_slide
+            .iter()
+            .filter(|(_, v, p)| *p == percent && *v >= value / 2.0 && *v <= value * 2.0)
+            .min_by(|a, b| (a.1 - value).abs().total_cmp(&(b.1 - value).abs()));
+        if let Some((slide_raw, _, _)) = closest {
+            findings.push(NoteFinding {
+                kind: FindingKind::Number,
+                text: raw.clone(),
+                suggestion: Some(slide_raw.clone()),
+                message: format!("Notes say {} but the slide says {}", raw, slide_raw),
+            });
+        }
+    }
+
+    findings
+}
+
+/// Flag typos, glossary misspellings and mismatched numbers, slide by slide
+#[tauri::command]
+pub async fn check_notes(presentation_id: String) -> Result<Vec<SlideFindings>, CueCardError> {
+    let json = fetch_presentation(&presentation_id).await?;
+    let slides = json
+        .get("slides")
+        .and_then(|s| s.as_array())
+        .ok_or("Presentation has no slides")?;
+
+    let mut results = Vec::new();
+    for (i, slide) in slides.iter().enumerate() {
+        let Some(notes) = extract_notes_from_slide(slide) else {
+            continue;
+        };
+        let mut slide_text = String::new();
+        if let Some(elements) = slide.get("pageElements").and_then(|e| e.as_array()) {
+            collect_slide_text(elements, &mut slide_text);
+        }
+
+        let words: Vec<Range<usize>> = WORD.find_iter(&notes).map(|m| m.range()).collect();
+        let mut findings = typos(&notes, &words);
+        findings.extend(product_names(&notes, &words));
+        findings.extend(mismatched_numbers(&notes, &slide_text));
+
+        if !findings.is_empty() {
+            results.push(SlideFindings {
+                slide_id: slide
+                    .get("objectId")
+                    .and_then(|id| id.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                slide_number: i as i32 + 1,
+                findings,
+            });
+        }
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(notes: &str) -> Vec<Range<usize>> {
+        WORD.find_iter(notes).map(|m| m.range()).collect()
+    }
+
+    #[test]
+    fn levenshtein_counts_edits() {
+        assert_eq!(levenshtein("cuecard", "cuecard"), 0);
+        assert_eq!(levenshtein("cuecard", "cuecrd"), 1);
+        assert_eq!(levenshtein("cuecard", "quecard"), 1);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+    }
+
+    #[test]
+    fn normalize_keeps_lowercase_alphanumerics() {
+        assert_eq!(normalize("Cue-Card"), "cuecard");
+        assert_eq!(normalize("Cue Card 2"), "cuecard2");
+    }
+
+    #[test]
+    fn typos_flags_repeats_and_misspellings() {
+        let notes = "We will recieve the the results, 10 10 times.";
+        let findings = typos(notes, &words(notes));
+        let found: Vec<(&str, Option<&str>)> = findings
+            .iter()
+            .map(|f| (f.text.as_str(), f.suggestion.as_deref()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![("the the", Some("the")), ("recieve", Some("receive"))]
+        );
+    }
+
+    #[test]
+    fn typos_ignores_repeats_across_punctuation() {
+        let notes = "Is it that. That is the point.";
+        assert!(typos(notes, &words(notes)).is_empty());
+    }
+
+    #[test]
+    fn numbers_skip_years_and_small_counts() {
+        let found: Vec<(String, f64, bool)> = numbers("In 2024 we had 3 teams, 1,200 users and 5%");
+        assert_eq!(
+            found,
+            vec![
+                ("1,200".to_string(), 1200.0, false),
+                ("5%".to_string(), 5.0, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn mismatched_numbers_suggest_the_closest_figure() {
+        let findings = mismatched_numbers(
+            "Growth was 42% across 300 stores",
+            "Growth: 45%\nStores: 300\nRegions: 12%",
+        );
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].text, "42%");
+        assert_eq!(findings[0].suggestion.as_deref(), Some("45%"));
+    }
+
+    #[test]
+    fn mismatched_numbers_ignore_unrelated_magnitudes() {
+        assert!(mismatched_numbers("About 20 people", "Revenue 900").is_empty());
+        assert!(mismatched_numbers("About 20 people", "Up 25%").is_empty());
+    }
+}
@@ -0,0 +1,251 @@
+//! Export of a presentation's speaker notes as a cue sheet
+//!
+//! `export_notes` writes every slide's number, title and notes as Markdown,
+//! plain text or JSON, for a printed backup before going on stage. Slides
+//! are numbered as in the slideshow, without hidden slides. The current
+//! presentation is read from the notes fetched for it; other presentations
+//! from the disk cache, where slides have no titles. Notes are merged,
+//! masked and annotated as they're displayed. A slide whose notes haven't
+//! been loaded yet is written and reported as missing rather than as having
+//! none.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::error::CueCardError;
+use crate::{disk_cache, notes_freshness, slide_mapping, slide_skips, CURRENT_SLIDE, SLIDE_NOTES};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotesExportFormat {
+    Markdown,
+    Text,
+    Json,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportedSlide {
+    pub slide_number: i32,
+    pub slide_id: String,
+    pub title: Option<String>,
+    pub notes: Option<String>,
+    /// The notes haven't been loaded, so `None` doesn't mean there are none
+    pub missing: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportedNotes {
+    pub presentation_id: String,
+    pub title: String,
+    pub exported_at: i64,
+    pub slides: Vec<ExportedSlide>,
+}
+
+/// What `export_notes` wrote
+#[derive(Debug, Clone, Serialize)]
+pub struct NotesExportSummary {
+    pub slides: usize,
+    /// Numbers of the slides whose notes weren't loaded
+    pub missing_notes: Vec<i32>,
+}
+
+/// `shown_ids` in slideshow order; `loaded` tells a slide without notes from
+/// one whose notes haven't been read
+fn exported_slides(
+    presentation_id: &str,
+    shown_ids: Vec<String>,
+    mut notes: HashMap<String, String>,
+    with_titles: bool,
+    loaded: impl Fn(&str) -> bool,
+) -> Vec<ExportedSlide> {
+    shown_ids
+        .into_iter()
+        .enumerate()
+        .map(|(i, slide_id)| {
+            let own = notes.remove(&slide_id);
+            let missing = own.is_none() && !loaded(&slide_id);
+            let notes = crate::prepare_notes(presentation_id, &slide_id, own, "google");
+            ExportedSlide {
+                slide_number: i as i32 + 1,
+                title: with_titles
+                    .then(|| slide_skips::slide_title(&slide_id))
+                    .flatten(),
+                missing: missing && notes.is_none(),
+                notes,
+                slide_id,
+            }
+        })
+        .collect()
+}
+
+/// The presentation's slides and notes, from memory if it's current, or from disk
+pub fn collect(presentation_id: &str) -> Result<ExportedNotes, String> {
+    let current = CURRENT_SLIDE
+        .read()
+        .clone()
+        .filter(|s| s.presentation_id == presentation_id);
+    let shown = slide_mapping::shown_slides();
+
+    let (title, slides) = match current {
+        Some(slide) if !shown.is_empty() => {
+            let prefix = format!("{}:", presentation_id);
+            let notes: HashMap<String, String> = SLIDE_NOTES
+                .read()
+                .iter()
+                .filter_map(|(key, text)| {
+                    Some((key.strip_prefix(&prefix)?.to_string(), text.clone()))
+                })
+                .collect();
+            (
+                slide.title,
+                exported_slides(presentation_id, shown, notes, true, |slide_id| {
+                    notes_freshness::notes_loaded(presentation_id, slide_id)
+                }),
+            )
+        }
+        _ => {
+            let cached = disk_cache::read_presentation_notes(presentation_id)
+                .ok_or("This presentation's notes haven't been fetched or cached")?;
+            let shown = cached
+                .slide_ids
+                .into_iter()
+                .filter(|id| !cached.hidden_slide_ids.contains(id))
+                .collect();
+            // A cached deck has every slide's notes
+            (
+                disk_cache::cached_title(presentation_id).unwrap_or_default(),
+                exported_slides(
+                    presentation_id,
+                    shown,
+                    cached.notes.into_iter().collect(),
+                    false,
+                    |_| true,
+                ),
+            )
+        }
+    };
+
+    let title = if title.trim().is_empty() {
+        "Untitled presentation".to_string()
+    } else {
+        title
+    };
+    Ok(ExportedNotes {
+        presentation_id: presentation_id.to_string(),
+        title,
+        exported_at: chrono::Utc::now().timestamp(),
+        slides,
+    })
+}
+
+fn slide_heading(slide: &ExportedSlide) -> String {
+    match slide.title {
+        Some(ref title) => format!("Slide {}: {}", slide.slide_number, title),
+        None => format!("Slide {}", slide.slide_number),
+    }
+}
+
+fn render_markdown(export: &ExportedNotes) -> String {
+    let mut out = format!("# {}\n", export.title);
+    for slide in &export.slides {
+        out.push_str(&format!("\n## {}\n\n", slide_heading(slide)));
+        match slide.notes {
+            Some(ref notes) => out.push_str(notes.trim_end()),
+            None if slide.missing => out.push_str("_Notes not loaded_"),
+            None => out.push_str("_No notes_"),
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn render_text(export: &ExportedNotes) -> String {
+    let mut out = format!(
+        "{}\n{}\n",
+        export.title,
+        "=".repeat(export.title.chars().count())
+    );
+    for slide in &export.slides {
+        let heading = slide_heading(slide);
+        out.push_str(&format!(
+            "\n{}\n{}\n",
+            heading,
+            "-".repeat(heading.chars().count())
+        ));
+        match slide.notes {
+            Some(ref notes) => out.push_str(notes.trim_end()),
+            None if slide.missing => out.push_str("(notes not loaded)"),
+            None => out.push_str("(no notes)"),
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Write a presentation's notes to `path`; returns how many slides were
+/// written and which of them had notes missing
+#[tauri::command]
+pub fn export_notes(
+    presentation_id: String,
+    format: NotesExportFormat,
+    path: String,
+) -> Result<NotesExportSummary, CueCardError> {
+    let export = collect(&presentation_id)?;
+    let contents = match format {
+        NotesExportFormat::Markdown => render_markdown(&export),
+        NotesExportFormat::Text => render_text(&export),
+        NotesExportFormat::Json => serde_json::to_string_pretty(&export)
+            .map_err(|e| format!("Failed to serialize notes: {}", e))?,
+    };
+
+    std::fs::write(&path, contents).map_err(|e| format!("Failed to write notes: {}", e))?;
+    Ok(NotesExportSummary {
+        slides: export.slides.len(),
+        missing_notes: export
+            .slides
+            .iter()
+            .filter(|s| s.missing)
+            .map(|s| s.slide_number)
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn export() -> ExportedNotes {
+        let slide = |number: i32, notes: Option<&str>, missing: bool| ExportedSlide {
+            slide_number: number,
+            slide_id: format!("p{}", number),
+            title: None,
+            notes: notes.map(|n| n.to_string()),
+            missing,
+        };
+        ExportedNotes {
+            presentation_id: "deck".to_string(),
+            title: "Quarterly review".to_string(),
+            exported_at: 0,
+            slides: vec![
+                slide(1, Some("Welcome"), false),
+                slide(2, None, false),
+                slide(3, None, true),
+            ],
+        }
+    }
+
+    #[test]
+    fn markdown_tells_missing_notes_from_none() {
+        assert_eq!(
+            render_markdown(&export()),
+            "# Quarterly review\n\n## Slide 1\n\nWelcome\n\n## Slide 2\n\n_No notes_\n\n## Slide 3\n\n_Notes not loaded_\n"
+        );
+    }
+
+    #[test]
+    fn text_tells_missing_notes_from_none() {
+        let text = render_text(&export());
+        assert!(text.contains("Slide 2\n-------\n(no notes)\n"));
+        assert!(text.contains("Slide 3\n-------\n(notes not loaded)\n"));
+    }
+}
@@ -0,0 +1,234 @@
+//! How current the displayed Google Slides notes are
+//!
+//! Every `slide-update` for a Google Slides deck carries when its notes were
+//! read from Google, the deck revision they were read at, whether they were
+//! served from the cache rather than fetched for that slide change, and
+//! whether they may be stale: neither read nor confirmed current within
+//! `STALE_AFTER_SECS`, or only the disk copy and never confirmed. A revision
+//! poll or focus revalidation that finds the deck's revision unchanged
+//! confirms the notes held (`verified_at`) without reading them again. The
+//! provider is in `notes_provenance`. The UI can show a badge for stale
+//! notes and fetch one slide's notes again with `refetch_slide`.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::error::CueCardError;
+use crate::{clock, disk_cache, http_client, CURRENT_PRESENTATION_ID, SLIDE_NOTES};
+
+const STALE_AFTER_SECS: i64 = 600;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotesOrigin {
+    /// Read from the Slides API this session
+    Google,
+    /// The copy kept on disk from an earlier session
+    DiskCache,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NotesFreshness {
+    pub origin: Option<NotesOrigin>,
+    /// Unix seconds the notes were read from Google
+    pub fetched_at: Option<i64>,
+    /// Unix seconds the deck's revision was last found unchanged
+    pub verified_at: Option<i64>,
+    pub revision: Option<String>,
+    /// Served without a request for this slide change
+    pub cache_hit: bool,
+    pub stale: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Fetch {
+    origin: NotesOrigin,
+    fetched_at: Option<i64>,
+    verified_at: Option<i64>,
+    revision: Option<String>,
+}
+
+// Keyed by presentation id
+static DECK_FETCHES: Lazy<Arc<RwLock<HashMap<String, Fetch>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(HashMap::new())));
+// Single slides read after their deck, keyed by "{presentation_id}:{slide_id}"
+static SLIDE_FETCHES: Lazy<Arc<RwLock<HashMap<String, i64>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(HashMap::new())));
+
+/// The whole deck's notes were just read from Google
+pub fn record_deck_fetch(presentation_id: &str, revision: Option<String>) {
+    let prefix = format!("{}:", presentation_id);
+    SLIDE_FETCHES
+        .write()
+        .retain(|key, _| !key.starts_with(&prefix));
+    DECK_FETCHES.write().insert(
+        presentation_id.to_string(),
+        Fetch {
+            origin: NotesOrigin::Google,
+            fetched_at: Some(clock::now_secs()),
+            verified_at: None,
+            revision,
+        },
+    );
+}
+
+/// The deck's notes were filled in from the disk cache
+pub fn record_disk_load(presentation_id: &str) {
+    DECK_FETCHES.write().insert(
+        presentation_id.to_string(),
+        Fetch {
+            origin: NotesOrigin::DiskCache,
+            fetched_at: disk_cache::cached_at(presentation_id),
+            verified_at: None,
+            revision: disk_cache::cached_revision(presentation_id),
+        },
+    );
+}
+
+/// The deck's revision was just found unchanged, so the notes held are
+/// still current
+pub fn record_verified(presentation_id: &str) {
+    if let Some(deck) = DECK_FETCHES.write().get_mut(presentation_id) {
+        deck.verified_at = Some(clock::now_secs());
+    }
+}
+
+/// One slide's notes were just read from Google
+pub fn record_slide_fetch(presentation_id: &str, slide_id: &str) {
+    SLIDE_FETCHES.write().insert(
+        format!("{}:{}", presentation_id, slide_id),
+        clock::now_secs(),
+    );
+}
+
+/// Whether a slide's notes have been read from Google or the disk cache,
+/// so a slide without any has none rather than hasn't been loaded
+pub fn notes_loaded(presentation_id: &str, slide_id: &str) -> bool {
+    DECK_FETCHES.read().contains_key(presentation_id)
+        || SLIDE_FETCHES
+            .read()
+            .contains_key(&format!("{}:{}", presentation_id, slide_id))
+}
+
+/// Freshness of a slide's notes as they're about to be shown
+pub fn for_slide(presentation_id: &str, slide_id: &str, cache_hit: bool) -> NotesFreshness {
+    let deck = DECK_FETCHES.read().get(presentation_id).cloned();
+    let slide_fetched_at = SLIDE_FETCHES
+        .read()
+        .get(&format!("{}:{}", presentation_id, slide_id))
+        .copied();
+    freshness(deck, slide_fetched_at, cache_hit, clock::now_secs())
+}
+
+/// A single slide read after its deck counts over the deck's read; a
+/// revision check counts as a read that found nothing new
+fn freshness(
+    deck: Option<Fetch>,
+    slide_fetched_at: Option<i64>,
+    cache_hit: bool,
+    now: i64,
+) -> NotesFreshness {
+    let (origin, fetched_at) = match (slide_fetched_at, deck.as_ref()) {
+        (Some(at), _) => (Some(NotesOrigin::Google), Some(at)),
+        (None, Some(deck)) => (Some(deck.origin), deck.fetched_at),
+        (None, None) => (None, None),
+    };
+    let verified_at = deck.as_ref().and_then(|d| d.verified_at);
+    let checked_at = fetched_at.max(verified_at);
+    let too_old = checked_at.is_none_or(|at| now - at > STALE_AFTER_SECS);
+    let unconfirmed_disk_copy = origin == Some(NotesOrigin::DiskCache) && verified_at.is_none();
+    NotesFreshness {
+        origin,
+        fetched_at,
+        verified_at,
+        revision: deck.and_then(|d| d.revision),
+        cache_hit,
+        stale: unconfirmed_disk_copy || too_old,
+    }
+}
+
+/// Read one slide's notes from Google again and show them if it's on screen
+#[tauri::command]
+pub async fn refetch_slide(slide_id: String) -> Result<Option<String>, CueCardError> {
+    let presentation_id = CURRENT_PRESENTATION_ID
+        .read()
+        .clone()
+        .ok_or("No presentation is open")?;
+    let access_token = crate::slides_access_token().await?;
+    let notes = crate::fetch_page_notes(
+        &http_client::client(),
+        &access_token,
+        &presentation_id,
+        &slide_id,
+    )
+    .await?;
+
+    let key = format!("{}:{}", presentation_id, slide_id);
+    match notes {
+        Some(ref n) => SLIDE_NOTES.write().insert(key, n.clone()),
+        None => SLIDE_NOTES.write().remove(&key),
+    };
+    record_slide_fetch(&presentation_id, &slide_id);
+
+    // The disk copy would otherwise answer with the old notes. Only this
+    // slide's entry changes: the notes held for the rest of the deck may be
+    // partial and mustn't replace the full copy.
+    if let Err(e) = disk_cache::write_slide_notes(&presentation_id, &slide_id, notes.as_deref()) {
+        eprintln!("Failed to cache refetched notes: {}", e);
+    }
+    crate::reemit_current_slide(&presentation_id, Some(&slide_id));
+    Ok(notes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deck(origin: NotesOrigin, fetched_at: Option<i64>, verified_at: Option<i64>) -> Fetch {
+        Fetch {
+            origin,
+            fetched_at,
+            verified_at,
+            revision: Some("rev-1".to_string()),
+        }
+    }
+
+    #[test]
+    fn notes_go_stale_unless_read_or_confirmed_lately() {
+        let fetched = Some(deck(NotesOrigin::Google, Some(1_000), None));
+        let fresh = freshness(fetched.clone(), None, true, 1_000 + STALE_AFTER_SECS);
+        assert!(!fresh.stale && fresh.cache_hit);
+        assert_eq!(fresh.origin, Some(NotesOrigin::Google));
+        assert_eq!(fresh.revision.as_deref(), Some("rev-1"));
+        assert!(freshness(fetched, None, true, 1_001 + STALE_AFTER_SECS).stale);
+
+        // An unchanged revision keeps them current without reading them again
+        let verified = Some(deck(NotesOrigin::Google, Some(1_000), Some(5_000)));
+        assert!(!freshness(verified, None, true, 5_000 + STALE_AFTER_SECS).stale);
+
+        // Nothing read at all
+        let unknown = freshness(None, None, false, 1_000);
+        assert!(unknown.stale && unknown.origin.is_none());
+    }
+
+    #[test]
+    fn the_disk_copy_is_stale_until_confirmed() {
+        let disk = Some(deck(NotesOrigin::DiskCache, Some(1_000), None));
+        let shown = freshness(disk, None, true, 1_010);
+        assert!(shown.stale);
+        assert_eq!(shown.origin, Some(NotesOrigin::DiskCache));
+
+        let confirmed = Some(deck(NotesOrigin::DiskCache, Some(1_000), Some(1_005)));
+        assert!(!freshness(confirmed, None, true, 1_010).stale);
+
+        // A slide fetched again is Google's, even over the disk copy
+        let disk = Some(deck(NotesOrigin::DiskCache, Some(1_000), None));
+        let refetched = freshness(disk, Some(1_008), false, 1_010);
+        assert_eq!(refetched.origin, Some(NotesOrigin::Google));
+        assert_eq!(refetched.fetched_at, Some(1_008));
+        assert!(!refetched.stale);
+    }
+}
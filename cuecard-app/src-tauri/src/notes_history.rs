@@ -0,0 +1,171 @@
+//! Recently shown notes, for going back without moving the deck
+//!
+//! The last few slides' notes are kept as displayed. `show_previous_notes`
+//! steps back through them one slide per call and sends each as
+//! `previous-notes`; the tracked current slide doesn't change, and the next
+//! slide change (or `show_current_notes`) brings the panel back.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tauri::Emitter;
+
+use crate::{SlideData, APP_HANDLE, CURRENT_SLIDE};
+
+const HISTORY_LEN: usize = 10;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecentNotes {
+    pub presentation_id: String,
+    pub slide_id: String,
+    pub slide_number: i32,
+    pub title: String,
+    pub notes: Option<String>,
+    /// Slides back from the current one
+    pub steps_back: usize,
+}
+
+#[derive(Default)]
+struct NotesHistory {
+    /// Oldest first; the last entry is the current slide
+    entries: VecDeque<RecentNotes>,
+    /// How far back the panel is looking, 0 when showing the current slide
+    position: usize,
+}
+
+static NOTES_HISTORY: Lazy<Arc<RwLock<NotesHistory>>> =
+    Lazy::new(|| Arc::new(RwLock::new(NotesHistory::default())));
+
+impl NotesHistory {
+    fn record(&mut self, slide_data: &SlideData, notes: Option<String>) {
+        let same_slide = self.entries.back().is_some_and(|last| {
+            last.presentation_id == slide_data.presentation_id
+                && last.slide_id == slide_data.slide_id
+        });
+        if same_slide {
+            if let Some(last) = self.entries.back_mut() {
+                last.notes = notes;
+            }
+            return;
+        }
+
+        self.entries.push_back(RecentNotes {
+            presentation_id: slide_data.presentation_id.clone(),
+            slide_id: slide_data.slide_id.clone(),
+            slide_number: slide_data.slide_number,
+            title: slide_data.title.clone(),
+            notes,
+            steps_back: 0,
+        });
+        if self.entries.len() > HISTORY_LEN {
+            self.entries.pop_front();
+        }
+        self.position = 0;
+    }
+
+    /// One slide further back, or `None` at the oldest entry
+    fn step_back(&mut self) -> Option<RecentNotes> {
+        let steps_back = self.position + 1;
+        let index = self.entries.len().checked_sub(steps_back + 1)?;
+        self.position = steps_back;
+        Some(RecentNotes {
+            steps_back,
+            ..self.entries[index].clone()
+        })
+    }
+}
+
+/// Record notes shown for a slide; a re-emit of the same slide updates its entry
+pub fn record(slide_data: &SlideData, notes: Option<String>) {
+    NOTES_HISTORY.write().record(slide_data, notes);
+}
+
+/// Show the notes of the slide before the one on screen in the panel
+#[tauri::command]
+pub fn show_previous_notes() -> Option<RecentNotes> {
+    let entry = NOTES_HISTORY.write().step_back()?;
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        let _ = app.emit("previous-notes", entry.clone());
+    }
+    Some(entry)
+}
+
+/// Return the panel to the current slide's notes
+#[tauri::command]
+pub fn show_current_notes() {
+    NOTES_HISTORY.write().position = 0;
+    let current = CURRENT_SLIDE.read().clone();
+    if let Some(slide) = current {
+        crate::reemit_current_slide(&slide.presentation_id, None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slide(number: i32) -> SlideData {
+        SlideData {
+            presentation_id: "history".to_string(),
+            slide_id: format!("p{}", number),
+            slide_number: number,
+            title: String::new(),
+            mode: "google".to_string(),
+            timestamp: 0,
+            url: String::new(),
+            force_refresh: None,
+            client_id: None,
+            scraped_notes: None,
+        }
+    }
+
+    fn notes(number: i32) -> Option<String> {
+        Some(format!("Notes for {}", number))
+    }
+
+    #[test]
+    fn steps_back_one_slide_at_a_time() {
+        let mut history = NotesHistory::default();
+        for number in 1..=3 {
+            history.record(&slide(number), notes(number));
+        }
+
+        let back = history.step_back().unwrap();
+        assert_eq!((back.slide_number, back.steps_back), (2, 1));
+        let back = history.step_back().unwrap();
+        assert_eq!((back.slide_number, back.steps_back), (1, 2));
+        assert!(history.step_back().is_none());
+
+        // Moving on in the deck starts over from the new current slide
+        history.record(&slide(4), notes(4));
+        assert_eq!(history.step_back().unwrap().slide_number, 3);
+    }
+
+    #[test]
+    fn re_emits_update_the_current_entry() {
+        let mut history = NotesHistory::default();
+        history.record(&slide(1), notes(1));
+        history.record(&slide(2), None);
+        history.record(&slide(2), notes(2));
+        history.record(&slide(3), notes(3));
+        assert_eq!(history.entries.len(), 3);
+        let back = history.step_back().unwrap();
+        assert_eq!(back.notes, notes(2));
+    }
+
+    #[test]
+    fn keeps_the_last_few_slides() {
+        let mut history = NotesHistory::default();
+        for number in 1..=HISTORY_LEN as i32 + 5 {
+            history.record(&slide(number), notes(number));
+        }
+        assert_eq!(history.entries.len(), HISTORY_LEN);
+        let mut oldest = None;
+        while let Some(back) = history.step_back() {
+            oldest = Some(back.slide_number);
+        }
+        assert_eq!(oldest, Some(6));
+    }
+}
@@ -0,0 +1,220 @@
+//! Masking of sensitive terms in displayed notes
+//!
+//! For presenting in open rooms: configured terms (client names and the like,
+//! matched as whole words, case-insensitively) and regex patterns (account
+//! numbers, phone numbers) are replaced in the notes the panel shows. The
+//! cache keeps the original text. Masking is switched on per presentation.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Arc;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+use crate::error::CueCardError;
+
+const MASKING_CONFIG_KEY: &str = "masking_config";
+const MASKED_PRESENTATIONS_KEY: &str = "masked_presentations";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaskingConfig {
+    pub terms: Vec<String>,
+    pub patterns: Vec<String>,
+    /// Text shown in place of each match
+    pub mask: String,
+}
+
+impl Default for MaskingConfig {
+    fn default() -> Self {
+        Self {
+            terms: Vec::new(),
+            patterns: Vec::new(),
+            mask: "\u{2022}\u{2022}\u{2022}".to_string(),
+        }
+    }
+}
+
+static MASKING_CONFIG: Lazy<Arc<RwLock<MaskingConfig>>> =
+    Lazy::new(|| Arc::new(RwLock::new(MaskingConfig::default())));
+static MASKING_REGEXES: Lazy<Arc<RwLock<Vec<Regex>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(Vec::new())));
+static MASKED_PRESENTATIONS: Lazy<Arc<RwLock<HashSet<String>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(HashSet::new())));
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// A term as a whole word: word boundaries only where the term itself starts
+/// or ends with a word character, so "C++", "@client" and "$4.2M" still match
+pub fn whole_term(term: &str) -> String {
+    let starts_word = term.chars().next().is_some_and(is_word_char);
+    let ends_word = term.chars().last().is_some_and(is_word_char);
+    format!(
+        "{}{}{}",
+        if starts_word { r"\b" } else { "" },
+        regex::escape(term),
+        if ends_word { r"\b" } else { "" }
+    )
+}
+
+fn compile(config: &MaskingConfig) -> Result<Vec<Regex>, String> {
+    let mut regexes = Vec::new();
+
+    let terms: Vec<String> = config
+        .terms
+        .iter()
+        .map(|t| t.trim())
+        .filter(|t| !t.is_empty())
+        .map(whole_term)
+        .collect();
+    if !terms.is_empty() {
+        let regex = Regex::new(&format!("(?i){}", terms.join("|")))
+            .map_err(|e| format!("Invalid masked terms: {}", e))?;
+        regexes.push(regex);
+    }
+
+    for pattern in &config.patterns {
+        let regex = Regex::new(pattern)
+            .map_err(|e| format!("Invalid masking pattern \"{}\": {}", pattern, e))?;
+        regexes.push(regex);
+    }
+
+    Ok(regexes)
+}
+
+pub fn is_masked(presentation_id: &str) -> bool {
+    MASKED_PRESENTATIONS.read().contains(presentation_id)
+}
+
+/// Notes as they should be displayed for this presentation
+pub fn mask_notes(presentation_id: &str, notes: Option<String>) -> Option<String> {
+    let notes = notes?;
+    if !is_masked(presentation_id) {
+        return Some(notes);
+    }
+
+    let mask = MASKING_CONFIG.read().mask.clone();
+    let mut text = notes;
+    for regex in MASKING_REGEXES.read().iter() {
+        text = regex.replace_all(&text, mask.as_str()).into_owned();
+    }
+    Some(text)
+}
+
+fn save_to_store(app: &AppHandle) {
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Ok(json) = serde_json::to_value(&*MASKING_CONFIG.read()) {
+            store.set(MASKING_CONFIG_KEY, json);
+        }
+        if let Ok(json) = serde_json::to_value(&*MASKED_PRESENTATIONS.read()) {
+            store.set(MASKED_PRESENTATIONS_KEY, json);
+        }
+        let _ = store.save();
+    }
+}
+
+pub fn load_masking_from_store(app: &AppHandle) {
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Some(config_json) = store.get(MASKING_CONFIG_KEY) {
+            if let Ok(config) = serde_json::from_value::<MaskingConfig>(config_json) {
+                match compile(&config) {
+                    Ok(regexes) => {
+                        *MASKING_REGEXES.write() = regexes;
+                        *MASKING_CONFIG.write() = config;
+                    }
+                    Err(e) => eprintln!("Ignoring stored masking config: {}", e),
+                }
+            }
+        }
+        if let Some(ids_json) = store.get(MASKED_PRESENTATIONS_KEY) {
+            if let Ok(ids) = serde_json::from_value::<HashSet<String>>(ids_json) {
+                *MASKED_PRESENTATIONS.write() = ids;
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_masking_config() -> MaskingConfig {
+    MASKING_CONFIG.read().clone()
+}
+
+#[tauri::command]
+pub fn set_masking_config(app: AppHandle, config: MaskingConfig) -> Result<(), CueCardError> {
+    let regexes = compile(&config)?;
+    *MASKING_REGEXES.write() = regexes;
+    *MASKING_CONFIG.write() = config;
+    save_to_store(&app);
+
+    let current = crate::CURRENT_SLIDE.read().clone();
+    if let Some(slide) = current {
+        crate::reemit_current_slide(&slide.presentation_id, None);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn is_presentation_masked(presentation_id: String) -> bool {
+    is_masked(&presentation_id)
+}
+
+/// Turn masking on or off for one presentation
+#[tauri::command]
+pub fn set_presentation_masking(app: AppHandle, presentation_id: String, enabled: bool) {
+    {
+        let mut masked = MASKED_PRESENTATIONS.write();
+        if enabled {
+            masked.insert(presentation_id.clone());
+        } else {
+            masked.remove(&presentation_id);
+        }
+    }
+    save_to_store(&app);
+    crate::reemit_current_slide(&presentation_id, None);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mask(terms: &[&str], text: &str) -> String {
+        let config = MaskingConfig {
+            terms: terms.iter().map(|t| t.to_string()).collect(),
+            patterns: Vec::new(),
+            mask: "***".to_string(),
+        };
+        let mut text = text.to_string();
+        for regex in compile(&config).unwrap() {
+            text = regex.replace_all(&text, "***").into_owned();
+        }
+        text
+    }
+
+    #[test]
+    fn masks_whole_words_only() {
+        assert_eq!(mask(&["Acme"], "acme and Acmeville"), "*** and Acmeville");
+    }
+
+    #[test]
+    fn masks_terms_with_symbols_at_the_edges() {
+        assert_eq!(mask(&["C++"], "Written in C++."), "Written in ***.");
+        assert_eq!(mask(&["@client"], "cc @client today"), "cc *** today");
+        assert_eq!(
+            mask(&["$4.2M"], "Raised $4.2M in seed"),
+            "Raised *** in seed"
+        );
+    }
+
+    #[test]
+    fn rejects_an_invalid_pattern() {
+        let config = MaskingConfig {
+            patterns: vec!["(".to_string()],
+            ..MaskingConfig::default()
+        };
+        assert!(compile(&config).is_err());
+    }
+}
@@ -0,0 +1,323 @@
+//! Notes text post-processing
+//!
+//! A configurable list of steps applied to notes as they are fetched or read,
+//! before they reach the cache, so house-style notes can be cleaned up
+//! automatically. Steps run in order:
+//!
+//! - `regex_replace`: replace every match of `pattern` (regex crate syntax)
+//! - `strip_speaker_initials`: drop leading "AB:", "[AB]" or "(AB)" markers
+//! - `expand_abbreviations`: replace whole-word abbreviations
+//! - `normalize_quotes`: make quotes all straight or all curly
+//!
+//! The configuration lives in the store. Changes apply to notes fetched
+//! afterwards; `refresh_notes` re-fetches the current deck.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+use crate::error::CueCardError;
+
+const PIPELINE_STORE_KEY: &str = "notes_pipeline";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuoteStyle {
+    Straight,
+    Curly,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PipelineStep {
+    RegexReplace {
+        pattern: String,
+        replacement: String,
+    },
+    StripSpeakerInitials,
+    ExpandAbbreviations {
+        abbreviations: HashMap<String, String>,
+    },
+    NormalizeQuotes {
+        style: QuoteStyle,
+    },
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotesPipelineConfig {
+    pub enabled: bool,
+    pub steps: Vec<PipelineStep>,
+}
+
+/// A step with its regexes built once
+enum CompiledStep {
+    Replace(Regex, String),
+    Abbreviations(Regex, HashMap<String, String>),
+    Quotes(QuoteStyle),
+}
+
+static PIPELINE_CONFIG: Lazy<Arc<RwLock<NotesPipelineConfig>>> =
+    Lazy::new(|| Arc::new(RwLock::new(NotesPipelineConfig::default())));
+static COMPILED_STEPS: Lazy<Arc<RwLock<Vec<CompiledStep>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(Vec::new())));
+
+static SPEAKER_INITIALS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?m)^[ \t]*(?:\[[A-Z]{1,3}\]|\([A-Z]{1,3}\)|[A-Z]{1,3}:)[ \t]*")
+        .expect("valid speaker initials regex")
+});
+
+fn compile(config: &NotesPipelineConfig) -> Result<Vec<CompiledStep>, String> {
+    let mut compiled = Vec::with_capacity(config.steps.len());
+    for (index, step) in config.steps.iter().enumerate() {
+        let step = match step {
+            PipelineStep::RegexReplace {
+                pattern,
+                replacement,
+            } => {
+                let regex = Regex::new(pattern)
+                    .map_err(|e| format!("Step {}: invalid pattern: {}", index + 1, e))?;
+                CompiledStep::Replace(regex, replacement.clone())
+            }
+            PipelineStep::StripSpeakerInitials => {
+                CompiledStep::Replace(SPEAKER_INITIALS.clone(), String::new())
+            }
+            PipelineStep::ExpandAbbreviations { abbreviations } => {
+                if abbreviations.is_empty() {
+                    continue;
+                }
+                // Longest first so "Q&A" wins over "Q"; word boundaries only where the
+                // abbreviation itself starts/ends with a word character
+                let mut keys: Vec<&String> = abbreviations.keys().collect();
+                keys.sort_by_key(|k| std::cmp::Reverse(k.len()));
+                let alternatives: Vec<String> = keys
+                    .iter()
+                    .map(|k| {
+                        let starts_word = k.chars().next().is_some_and(|c| c.is_alphanumeric());
+                        let ends_word = k.chars().last().is_some_and(|c| c.is_alphanumeric());
+                        format!(
+                            "{}{}{}",
+                            if starts_word { r"\b" } else { "" },
+                            regex::escape(k),
+                            if ends_word { r"\b" } else { "" }
+                        )
+                    })
+                    .collect();
+                let regex = Regex::new(&alternatives.join("|"))
+                    .map_err(|e| format!("Step {}: {}", index + 1, e))?;
+                CompiledStep::Abbreviations(regex, abbreviations.clone())
+            }
+            PipelineStep::NormalizeQuotes { style } => CompiledStep::Quotes(*style),
+        };
+        compiled.push(step);
+    }
+    Ok(compiled)
+}
+
+fn normalize_quotes(text: &str, style: QuoteStyle) -> String {
+    match style {
+        QuoteStyle::Straight => text
+            .chars()
+            .map(|c| match c {
+                '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' => '\'',
+                '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' => '"',
+                other => other,
+            })
+            .collect(),
+        QuoteStyle::Curly => {
+            let mut result = String::with_capacity(text.len());
+            let mut previous: Option<char> = None;
+            for c in text.chars() {
+                // Opening after start, whitespace or an opening bracket; closing otherwise
+                let opening = previous.is_none_or(|p| p.is_whitespace() || "([{".contains(p));
+                let converted = match (c, opening) {
+                    ('"', true) => '\u{201C}',
+                    ('"', false) => '\u{201D}',
+                    ('\'', true) => '\u{2018}',
+                    ('\'', false) => '\u{2019}',
+                    (other, _) => other,
+                };
+                result.push(converted);
+                previous = Some(c);
+            }
+            result
+        }
+    }
+}
+
+fn run_steps(text: &str, steps: &[CompiledStep]) -> String {
+    let mut text = text.to_string();
+    for step in steps {
+        text = match step {
+            CompiledStep::Replace(regex, replacement) => {
+                regex.replace_all(&text, replacement.as_str()).into_owned()
+            }
+            CompiledStep::Abbreviations(regex, expansions) => regex
+                .replace_all(&text, |caps: &regex::Captures| {
+                    expansions
+                        .get(&caps[0])
+                        .cloned()
+                        .unwrap_or_else(|| caps[0].to_string())
+                })
+                .into_owned(),
+            CompiledStep::Quotes(style) => normalize_quotes(&text, *style),
+        };
+    }
+    text.trim().to_string()
+}
+
+/// Apply the configured pipeline to freshly fetched notes
+pub fn process(text: &str) -> String {
+    if !PIPELINE_CONFIG.read().enabled {
+        return text.to_string();
+    }
+    run_steps(text, &COMPILED_STEPS.read())
+}
+
+fn apply_config(config: NotesPipelineConfig) -> Result<(), String> {
+    let compiled = compile(&config)?;
+    *COMPILED_STEPS.write() = compiled;
+    *PIPELINE_CONFIG.write() = config;
+    Ok(())
+}
+
+pub fn load_pipeline_from_store(app: &AppHandle) {
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Some(config_json) = store.get(PIPELINE_STORE_KEY) {
+            if let Ok(config) = serde_json::from_value::<NotesPipelineConfig>(config_json) {
+                if let Err(e) = apply_config(config) {
+                    eprintln!("Ignoring stored notes pipeline: {}", e);
+                }
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_notes_pipeline() -> NotesPipelineConfig {
+    PIPELINE_CONFIG.read().clone()
+}
+
+#[tauri::command]
+pub fn set_notes_pipeline(app: AppHandle, config: NotesPipelineConfig) -> Result<(), CueCardError> {
+    apply_config(config.clone())?;
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Ok(json) = serde_json::to_value(&config) {
+            store.set(PIPELINE_STORE_KEY, json);
+            let _ = store.save();
+        }
+    }
+    Ok(())
+}
+
+/// Run a pipeline over sample text without saving it, for the settings screen
+#[tauri::command]
+pub fn preview_notes_pipeline(
+    text: String,
+    config: NotesPipelineConfig,
+) -> Result<String, CueCardError> {
+    Ok(run_steps(&text, &compile(&config)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(text: &str, steps: Vec<PipelineStep>) -> String {
+        let config = NotesPipelineConfig {
+            enabled: true,
+            steps,
+        };
+        run_steps(text, &compile(&config).unwrap())
+    }
+
+    #[test]
+    fn replaces_regex_matches() {
+        let steps = vec![PipelineStep::RegexReplace {
+            pattern: r"\s*\(draft\)".to_string(),
+            replacement: String::new(),
+        }];
+        assert_eq!(
+            run("Intro (draft)\nPricing (draft)", steps),
+            "Intro\nPricing"
+        );
+    }
+
+    #[test]
+    fn reports_the_step_with_a_bad_pattern() {
+        let config = NotesPipelineConfig {
+            enabled: true,
+            steps: vec![
+                PipelineStep::StripSpeakerInitials,
+                PipelineStep::RegexReplace {
+                    pattern: "(unclosed".to_string(),
+                    replacement: String::new(),
+                },
+            ],
+        };
+        let error = compile(&config).err().unwrap();
+        assert!(error.starts_with("Step 2:"), "{}", error);
+    }
+
+    #[test]
+    fn strips_speaker_initials_at_line_starts() {
+        let text = "AB: Welcome\n  [CD] Pricing\n(EF)Demo\nNote: keep\nWHO: asks";
+        assert_eq!(
+            run(text, vec![PipelineStep::StripSpeakerInitials]),
+            "Welcome\nPricing\nDemo\nNote: keep\nasks"
+        );
+    }
+
+    #[test]
+    fn expands_whole_abbreviations_longest_first() {
+        let abbreviations = HashMap::from([
+            ("Q".to_string(), "quarter".to_string()),
+            ("Q&A".to_string(), "questions and answers".to_string()),
+            ("ARR".to_string(), "annual recurring revenue".to_string()),
+        ]);
+        assert_eq!(
+            run(
+                "Q&A after ARR for the Q. ARRAY stays, so does QA.",
+                vec![PipelineStep::ExpandAbbreviations { abbreviations }]
+            ),
+            "questions and answers after annual recurring revenue for the quarter. ARRAY stays, so does QA."
+        );
+    }
+
+    #[test]
+    fn normalizes_quotes_both_ways() {
+        let straight = vec![PipelineStep::NormalizeQuotes {
+            style: QuoteStyle::Straight,
+        }];
+        assert_eq!(
+            run("\u{201C}It\u{2019}s live\u{201D}", straight),
+            "\"It's live\""
+        );
+
+        let curly = vec![PipelineStep::NormalizeQuotes {
+            style: QuoteStyle::Curly,
+        }];
+        assert_eq!(
+            run("She said \"it's ('fine')\"", curly),
+            "She said \u{201C}it\u{2019}s (\u{2018}fine\u{2019})\u{201D}"
+        );
+    }
+
+    #[test]
+    fn runs_steps_in_order() {
+        let abbreviations = HashMap::from([("KPI".to_string(), "key metric".to_string())]);
+        let steps = vec![
+            PipelineStep::StripSpeakerInitials,
+            PipelineStep::ExpandAbbreviations { abbreviations },
+            PipelineStep::RegexReplace {
+                pattern: "key metric".to_string(),
+                replacement: "north star".to_string(),
+            },
+        ];
+        assert_eq!(run("JS: Our KPI\n", steps), "Our north star");
+    }
+}
@@ -0,0 +1,344 @@
+//! Notes source precedence and merging
+//!
+//! A slide can have notes from several places at once. The slide's own source
+//! (Google Slides, or whichever local provider/integration reported the slide)
+//! fills `SLIDE_NOTES`; everything else is layered on top here:
+//!
+//! - `local_override`: text the presenter typed in CueCard for this slide
+//! - `primary`: the slide's own source
+//! - `scraped`: notes the browser extension read from the page DOM
+//! - `ai_summary`: generated summaries
+//!
+//! Each presentation can configure the order and whether the first available
+//! source wins (`first_available`, the default) or all available sources are
+//! concatenated in order (`concatenate`). Rules are persisted in the store.
+//! Every `slide-update` carries `notes_provenance`, the sources that make up
+//! the displayed text.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+use crate::error::CueCardError;
+use crate::public_export;
+
+const MERGE_RULES_STORE_KEY: &str = "notes_merge_rules";
+const CONCATENATE_SEPARATOR: &str = "\n\n";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotesSource {
+    LocalOverride,
+    Primary,
+    Scraped,
+    AiSummary,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeStrategy {
+    FirstAvailable,
+    Concatenate,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotesMergeRules {
+    pub order: Vec<NotesSource>,
+    pub strategy: MergeStrategy,
+}
+
+impl Default for NotesMergeRules {
+    fn default() -> Self {
+        Self {
+            order: vec![
+                NotesSource::LocalOverride,
+                NotesSource::Primary,
+                NotesSource::Scraped,
+                NotesSource::AiSummary,
+            ],
+            strategy: MergeStrategy::FirstAvailable,
+        }
+    }
+}
+
+/// Where one part of the displayed notes came from
+#[derive(Debug, Clone, Serialize)]
+pub struct NotesProvenance {
+    pub source: NotesSource,
+    /// For `primary`: "google" or the provider/integration mode that reported the slide
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// The provider is a fallback whose notes may be incomplete or out of date
+    pub degraded: bool,
+}
+
+type SourceNotes = HashMap<NotesSource, String>;
+
+// Non-primary notes, keyed by "{presentation_id}:{slide_id}"
+static SECONDARY_NOTES: Lazy<Arc<RwLock<HashMap<String, SourceNotes>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(HashMap::new())));
+// Per-presentation rules; presentations without an entry use the defaults
+static MERGE_RULES: Lazy<Arc<RwLock<HashMap<String, NotesMergeRules>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(HashMap::new())));
+
+fn notes_key(presentation_id: &str, slide_id: &str) -> String {
+    format!("{}:{}", presentation_id, slide_id)
+}
+
+pub fn rules_for(presentation_id: &str) -> NotesMergeRules {
+    MERGE_RULES
+        .read()
+        .get(presentation_id)
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Notes currently held for a non-primary source
+pub fn source_notes(presentation_id: &str, slide_id: &str, source: NotesSource) -> Option<String> {
+    SECONDARY_NOTES
+        .read()
+        .get(&notes_key(presentation_id, slide_id))
+        .and_then(|sources| sources.get(&source).cloned())
+}
+
+/// Record (or with `None`, clear) notes from a non-primary source
+pub fn set_source_notes(
+    presentation_id: &str,
+    slide_id: &str,
+    source: NotesSource,
+    text: Option<String>,
+) {
+    let key = notes_key(presentation_id, slide_id);
+    let mut secondary = SECONDARY_NOTES.write();
+    match text.map(|t| t.trim().to_string()).filter(|t| !t.is_empty()) {
+        Some(text) => {
+            secondary.entry(key).or_default().insert(source, text);
+        }
+        None => {
+            if let Some(sources) = secondary.get_mut(&key) {
+                sources.remove(&source);
+                if sources.is_empty() {
+                    secondary.remove(&key);
+                }
+            }
+        }
+    }
+}
+
+/// Combine the primary notes with the other sources according to the presentation's rules
+pub fn resolve_notes(
+    presentation_id: &str,
+    slide_id: &str,
+    primary: Option<String>,
+    primary_provider: &str,
+) -> (Option<String>, Vec<NotesProvenance>) {
+    let rules = rules_for(presentation_id);
+    let secondary = SECONDARY_NOTES
+        .read()
+        .get(&notes_key(presentation_id, slide_id))
+        .cloned()
+        .unwrap_or_default();
+
+    let mut parts = Vec::new();
+    let mut provenance = Vec::new();
+    for source in &rules.order {
+        let text = match source {
+            NotesSource::Primary => primary.clone(),
+            other => secondary.get(other).cloned(),
+        };
+        let Some(text) = text.filter(|t| !t.trim().is_empty()) else {
+            continue;
+        };
+
+        parts.push(text);
+        provenance.push(NotesProvenance {
+            source: *source,
+            provider: (*source == NotesSource::Primary).then(|| primary_provider.to_string()),
+            degraded: *source == NotesSource::Primary
+                && primary_provider == public_export::PROVIDER,
+        });
+        if rules.strategy == MergeStrategy::FirstAvailable {
+            break;
+        }
+    }
+
+    if parts.is_empty() {
+        (None, provenance)
+    } else {
+        (Some(parts.join(CONCATENATE_SEPARATOR)), provenance)
+    }
+}
+
+pub fn load_merge_rules_from_store(app: &AppHandle) {
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Some(rules_json) = store.get(MERGE_RULES_STORE_KEY) {
+            if let Ok(rules) =
+                serde_json::from_value::<HashMap<String, NotesMergeRules>>(rules_json.clone())
+            {
+                *MERGE_RULES.write() = rules;
+            }
+        }
+    }
+}
+
+fn save_merge_rules_to_store(app: &AppHandle) {
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Ok(json) = serde_json::to_value(&*MERGE_RULES.read()) {
+            store.set(MERGE_RULES_STORE_KEY, json);
+            let _ = store.save();
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_notes_merge_rules(presentation_id: String) -> NotesMergeRules {
+    rules_for(&presentation_id)
+}
+
+/// Set the rules for one presentation; `None` goes back to the defaults
+#[tauri::command]
+pub fn set_notes_merge_rules(
+    app: AppHandle,
+    presentation_id: String,
+    rules: Option<NotesMergeRules>,
+) -> Result<(), CueCardError> {
+    if let Some(ref rules) = rules {
+        if rules.order.is_empty() {
+            return Err("Notes source order can't be empty".into());
+        }
+    }
+
+    {
+        let mut all_rules = MERGE_RULES.write();
+        match rules {
+            Some(rules) => all_rules.insert(presentation_id.clone(), rules),
+            None => all_rules.remove(&presentation_id),
+        };
+    }
+    save_merge_rules_to_store(&app);
+
+    crate::reemit_current_slide(&presentation_id, None);
+    Ok(())
+}
+
+/// Set or clear the presenter's own notes for a slide, taking precedence by default.
+/// Changes are recorded in `notes_audit`.
+#[tauri::command]
+pub fn set_notes_override(
+    app: AppHandle,
+    presentation_id: String,
+    slide_id: String,
+    text: Option<String>,
+) {
+    let previous = source_notes(&presentation_id, &slide_id, NotesSource::LocalOverride);
+    set_source_notes(
+        &presentation_id,
+        &slide_id,
+        NotesSource::LocalOverride,
+        text,
+    );
+    crate::notes_audit::record_change(
+        &app,
+        &presentation_id,
+        &slide_id,
+        NotesSource::LocalOverride,
+        previous,
+        source_notes(&presentation_id, &slide_id, NotesSource::LocalOverride),
+    );
+    crate::reemit_current_slide(&presentation_id, Some(&slide_id));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sources(provenance: &[NotesProvenance]) -> Vec<NotesSource> {
+        provenance.iter().map(|p| p.source).collect()
+    }
+
+    #[test]
+    fn first_available_source_wins() {
+        set_source_notes(
+            "first",
+            "s1",
+            NotesSource::Scraped,
+            Some("From the page".to_string()),
+        );
+        let (notes, provenance) =
+            resolve_notes("first", "s1", Some("From Slides".to_string()), "google");
+        assert_eq!(notes.as_deref(), Some("From Slides"));
+        assert_eq!(sources(&provenance), vec![NotesSource::Primary]);
+        assert_eq!(provenance[0].provider.as_deref(), Some("google"));
+        assert!(!provenance[0].degraded);
+
+        // Blank primary notes fall through to the next source
+        let (notes, provenance) = resolve_notes("first", "s1", Some("  \n".to_string()), "google");
+        assert_eq!(notes.as_deref(), Some("From the page"));
+        assert_eq!(sources(&provenance), vec![NotesSource::Scraped]);
+        assert_eq!(provenance[0].provider, None);
+    }
+
+    #[test]
+    fn local_override_comes_first_by_default() {
+        set_source_notes(
+            "override",
+            "s1",
+            NotesSource::LocalOverride,
+            Some(" Typed in CueCard ".to_string()),
+        );
+        let (notes, provenance) =
+            resolve_notes("override", "s1", Some("From Slides".to_string()), "google");
+        assert_eq!(notes.as_deref(), Some("Typed in CueCard"));
+        assert_eq!(sources(&provenance), vec![NotesSource::LocalOverride]);
+
+        set_source_notes("override", "s1", NotesSource::LocalOverride, None);
+        let (notes, _) = resolve_notes("override", "s1", Some("From Slides".to_string()), "google");
+        assert_eq!(notes.as_deref(), Some("From Slides"));
+    }
+
+    #[test]
+    fn concatenates_in_the_configured_order() {
+        MERGE_RULES.write().insert(
+            "concat".to_string(),
+            NotesMergeRules {
+                order: vec![NotesSource::Scraped, NotesSource::Primary],
+                strategy: MergeStrategy::Concatenate,
+            },
+        );
+        set_source_notes(
+            "concat",
+            "s1",
+            NotesSource::Scraped,
+            Some("Scraped".to_string()),
+        );
+        set_source_notes(
+            "concat",
+            "s1",
+            NotesSource::AiSummary,
+            Some("Not in the order".to_string()),
+        );
+        let (notes, provenance) = resolve_notes(
+            "concat",
+            "s1",
+            Some("Exported".to_string()),
+            public_export::PROVIDER,
+        );
+        assert_eq!(notes.as_deref(), Some("Scraped\n\nExported"));
+        assert_eq!(
+            sources(&provenance),
+            vec![NotesSource::Scraped, NotesSource::Primary]
+        );
+        assert!(provenance[1].degraded);
+    }
+
+    #[test]
+    fn nothing_to_show_without_notes() {
+        let (notes, provenance) = resolve_notes("empty", "s1", None, "google");
+        assert_eq!(notes, None);
+        assert!(provenance.is_empty());
+    }
+}
@@ -0,0 +1,283 @@
+//! Notes condensed into a few bullet cues by a language model
+//!
+//! Paragraphs are hard to scan on stage, so `summarize_notes` sends a slide's
+//! notes, as displayed, to a configurable chat completions endpoint (OpenAI's
+//! request and response format, which most providers accept) and asks for
+//! 3 to 5 short cues. Results are cached per slide until the slide's notes
+//! change, and sent as `notes-summary` with the raw notes alongside.
+//!
+//! The endpoint and model are kept in the local store; the API key is kept
+//! in the OS keychain on macOS and Windows, the Secret Service keyring on
+//! Linux, and in the (encrypted) store elsewhere. The endpoint must be https
+//! unless it's on this machine, since the key and notes go with every
+//! request.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_store::StoreExt;
+
+use crate::api_keys::{self, ApiKey};
+use crate::error::CueCardError;
+use crate::{http_client, CURRENT_SLIDE};
+
+const SUMMARY_SETTINGS_KEY: &str = "notes_summary";
+static API_KEY: ApiKey = ApiKey::new("com.cuecard.summary", "notes_summary_api_key");
+
+const MAX_CUES: usize = 5;
+const SYSTEM_PROMPT: &str = "You turn a presenter's speaker notes into 3 to 5 short bullet cues they can glance at on stage. Keep names, numbers and the order of points. Each cue is at most 8 words. Reply with the cues only, one per line, each starting with \"- \".";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SummarySettings {
+    /// Chat completions URL, e.g. https://api.openai.com/v1/chat/completions
+    pub endpoint: Option<String>,
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SummarySettingsStatus {
+    #[serde(flatten)]
+    pub settings: SummarySettings,
+    pub has_api_key: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NotesSummary {
+    pub presentation_id: String,
+    pub slide_id: String,
+    pub raw: String,
+    pub cues: Vec<String>,
+}
+
+static SETTINGS: Lazy<Arc<RwLock<SummarySettings>>> =
+    Lazy::new(|| Arc::new(RwLock::new(SummarySettings::default())));
+// Keyed by "presentation_id:slide_id"
+static SUMMARIES: Lazy<Arc<RwLock<HashMap<String, NotesSummary>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(HashMap::new())));
+
+pub fn load_summary_settings_from_store(app: &AppHandle) {
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Some(settings) = store
+            .get(SUMMARY_SETTINGS_KEY)
+            .and_then(|v| serde_json::from_value::<SummarySettings>(v).ok())
+        {
+            *SETTINGS.write() = settings;
+        }
+        #[cfg(target_os = "linux")]
+        API_KEY.migrate(&store);
+    }
+}
+
+/// Bullet lines from the model's reply, without their markers
+fn parse_cues(reply: &str) -> Vec<String> {
+    reply
+        .lines()
+        .map(|line| {
+            let line = line.trim();
+            // One "- " / "* " / "• " marker, so "**bold**" cues keep theirs
+            let line = ["- ", "* ", "• "]
+                .iter()
+                .find_map(|marker| line.strip_prefix(marker))
+                .unwrap_or(line);
+            // "1." / "2)" numbering
+            let line = match line.split_once(['.', ')']) {
+                Some((number, rest))
+                    if !number.is_empty()
+                        && number.chars().all(|c| c.is_ascii_digit())
+                        && rest.starts_with(' ') =>
+                {
+                    rest
+                }
+                _ => line,
+            };
+            line.trim().to_string()
+        })
+        .filter(|line| !line.is_empty())
+        .take(MAX_CUES)
+        .collect()
+}
+
+async fn request_summary(
+    settings: &SummarySettings,
+    api_key: &str,
+    notes: &str,
+) -> Result<Vec<String>, String> {
+    let endpoint = settings
+        .endpoint
+        .as_deref()
+        .filter(|e| !e.trim().is_empty())
+        .ok_or("No summary endpoint configured")?;
+    api_keys::check_endpoint(endpoint, "summary")?;
+    let model = settings
+        .model
+        .as_deref()
+        .filter(|m| !m.trim().is_empty())
+        .ok_or("No summary model configured")?;
+
+    let response = http_client::client()
+        .post(endpoint)
+        .bearer_auth(api_key)
+        .json(&serde_json::json!({
+            "model": model,
+            "messages": [
+                { "role": "system", "content": SYSTEM_PROMPT },
+                { "role": "user", "content": notes }
+            ]
+        }))
+        .send()
+        .await
+        .map_err(|e| format!("Summary request failed: {}", e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!(
+            "Summary request failed: {} - {}",
+            status, error_text
+        ));
+    }
+
+    let json: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse summary response: {}", e))?;
+    let reply = json
+        .pointer("/choices/0/message/content")
+        .and_then(|c| c.as_str())
+        .ok_or("No summary in response")?;
+
+    let cues = parse_cues(reply);
+    if cues.is_empty() {
+        return Err("Summary had no cues".to_string());
+    }
+    Ok(cues)
+}
+
+/// Forget summaries when the presentation changes or is refreshed
+pub fn clear() {
+    SUMMARIES.write().clear();
+}
+
+/// Delete the API key and cached cues, for a full data wipe
+pub fn reset(app: &AppHandle) {
+    let _ = API_KEY.set(app, "");
+    clear();
+}
+
+/// Summarize a slide's notes as displayed (the current slide by default)
+#[tauri::command]
+pub async fn summarize_notes(
+    app: AppHandle,
+    presentation_id: Option<String>,
+    slide_id: Option<String>,
+) -> Result<NotesSummary, CueCardError> {
+    let slide = CURRENT_SLIDE.read().clone().ok_or("No current slide")?;
+    let presentation_id = presentation_id.unwrap_or_else(|| slide.presentation_id.clone());
+    let slide_id = slide_id.unwrap_or_else(|| slide.slide_id.clone());
+    if presentation_id != slide.presentation_id {
+        return Err("Only the current presentation's notes can be summarized".into());
+    }
+
+    let raw = crate::display_notes(&slide, &slide_id)
+        .filter(|n| !n.trim().is_empty())
+        .ok_or("Slide has no notes")?;
+    let key = format!("{}:{}", presentation_id, slide_id);
+
+    let cached = SUMMARIES.read().get(&key).filter(|s| s.raw == raw).cloned();
+    let summary = match cached {
+        Some(summary) => summary,
+        None => {
+            let api_key = API_KEY.get(&app).ok_or("No summary API key set")?;
+            let settings = SETTINGS.read().clone();
+            let cues = request_summary(&settings, &api_key, &raw).await?;
+            let summary = NotesSummary {
+                presentation_id,
+                slide_id,
+                raw,
+                cues,
+            };
+            SUMMARIES.write().insert(key, summary.clone());
+            summary
+        }
+    };
+
+    let _ = app.emit("notes-summary", &summary);
+    Ok(summary)
+}
+
+/// A cached summary of a slide's notes, without asking the model
+#[tauri::command]
+pub fn get_notes_summary(presentation_id: String, slide_id: String) -> Option<NotesSummary> {
+    SUMMARIES
+        .read()
+        .get(&format!("{}:{}", presentation_id, slide_id))
+        .cloned()
+}
+
+#[tauri::command]
+pub fn get_summary_settings(app: AppHandle) -> SummarySettingsStatus {
+    SummarySettingsStatus {
+        settings: SETTINGS.read().clone(),
+        has_api_key: API_KEY.get(&app).is_some(),
+    }
+}
+
+/// Save the endpoint and model; `api_key` replaces the stored key when given,
+/// and an empty one removes it
+#[tauri::command]
+pub fn set_summary_settings(
+    app: AppHandle,
+    settings: SummarySettings,
+    api_key: Option<String>,
+) -> Result<(), CueCardError> {
+    if let Some(endpoint) = settings
+        .endpoint
+        .as_deref()
+        .filter(|e| !e.trim().is_empty())
+    {
+        api_keys::check_endpoint(endpoint, "summary")?;
+    }
+    if let Some(key) = api_key {
+        API_KEY.set(&app, key.trim())?;
+    }
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Ok(json) = serde_json::to_value(&settings) {
+            store.set(SUMMARY_SETTINGS_KEY, json);
+            let _ = store.save();
+        }
+    }
+    *SETTINGS.write() = settings;
+    // Cached cues came from the old endpoint or model
+    clear();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_one_marker_per_cue() {
+        let reply = "- Open with the churn number\n* **Three** regions\n• Q3 target\n1. Thank the team\n\n2) Questions";
+        assert_eq!(
+            parse_cues(reply),
+            vec![
+                "Open with the churn number",
+                "**Three** regions",
+                "Q3 target",
+                "Thank the team",
+                "Questions",
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_cues_that_start_with_markup() {
+        assert_eq!(
+            parse_cues("**Revenue** up 12%\n-3 degrees"),
+            vec!["**Revenue** up 12%", "-3 degrees"]
+        );
+    }
+}
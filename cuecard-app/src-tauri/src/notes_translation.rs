@@ -0,0 +1,484 @@
+//! Notes translated into the language a deck is delivered in
+//!
+//! A deck can be given a target language (`set_deck_translation`); its notes,
+//! as displayed, are then translated in the background once they've been
+//! fetched, the whole deck at a time, and each `slide-update` carries the
+//! translation next to the original notes. Until a slide's translation is in,
+//! or when it fails, the original is shown alone. The backend is the Google
+//! Cloud Translation API or any endpoint that accepts LibreTranslate's
+//! request and response format, so a self-hosted or in-house service can be
+//! plugged in. Translations are cached per slide until its notes change.
+//!
+//! The backend settings are kept in the local store; the API key is kept in
+//! the OS keychain on macOS and Windows, the Secret Service keyring on Linux,
+//! and in the (encrypted) store elsewhere (`api_keys`). A custom endpoint
+//! must be https unless it's on this machine, since the key and notes go
+//! with every request.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_store::StoreExt;
+
+use crate::api_keys::{self, ApiKey};
+use crate::error::CueCardError;
+use crate::{http_client, SlideData, APP_HANDLE, CURRENT_PRESENTATION_ID, SLIDE_NOTES};
+
+const TRANSLATION_SETTINGS_KEY: &str = "notes_translation";
+const DECK_TRANSLATION_KEY: &str = "deck_translation";
+static API_KEY: ApiKey = ApiKey::new("com.cuecard.translation", "notes_translation_api_key");
+
+const GOOGLE_TRANSLATE_URL: &str = "https://translation.googleapis.com/language/translate/v2";
+// Google takes at most 128 texts per request
+const BATCH_SIZE: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranslationBackend {
+    /// Google Cloud Translation API (v2)
+    #[default]
+    Google,
+    /// A LibreTranslate-compatible `/translate` endpoint
+    Endpoint,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TranslationSettings {
+    pub backend: TranslationBackend,
+    /// URL of the `/translate` endpoint, for `TranslationBackend::Endpoint`
+    pub endpoint: Option<String>,
+    /// Language the notes are written in; detected when unset
+    pub source_language: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TranslationSettingsStatus {
+    #[serde(flatten)]
+    pub settings: TranslationSettings,
+    pub has_api_key: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TranslatedNotes {
+    /// Target language code, e.g. "es"
+    pub language: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct TranslationErrorEvent {
+    presentation_id: String,
+    message: String,
+}
+
+#[derive(Debug, Clone)]
+struct Translation {
+    /// The displayed notes that were translated
+    source: String,
+    language: String,
+    /// `None` when translating failed, so it isn't retried for the same notes
+    text: Option<String>,
+}
+
+static SETTINGS: Lazy<Arc<RwLock<TranslationSettings>>> =
+    Lazy::new(|| Arc::new(RwLock::new(TranslationSettings::default())));
+// Target language keyed by presentation id
+static DECK_LANGUAGES: Lazy<Arc<RwLock<HashMap<String, String>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(HashMap::new())));
+// Keyed by "presentation_id:slide_id"
+static TRANSLATIONS: Lazy<Arc<RwLock<HashMap<String, Translation>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(HashMap::new())));
+// Presentations being translated
+static IN_FLIGHT: Lazy<Arc<RwLock<HashSet<String>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(HashSet::new())));
+
+pub fn load_translation_from_store(app: &AppHandle) {
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Some(settings) = store
+            .get(TRANSLATION_SETTINGS_KEY)
+            .and_then(|v| serde_json::from_value::<TranslationSettings>(v).ok())
+        {
+            *SETTINGS.write() = settings;
+        }
+        if let Some(decks) = store
+            .get(DECK_TRANSLATION_KEY)
+            .and_then(|v| serde_json::from_value::<HashMap<String, String>>(v).ok())
+        {
+            *DECK_LANGUAGES.write() = decks;
+        }
+        #[cfg(target_os = "linux")]
+        API_KEY.migrate(&store);
+    }
+}
+
+fn deck_language(presentation_id: &str) -> Option<String> {
+    DECK_LANGUAGES.read().get(presentation_id).cloned()
+}
+
+/// The translation of a slide's notes, if `displayed` is what was translated
+pub fn for_display(
+    presentation_id: &str,
+    slide_id: &str,
+    displayed: Option<&str>,
+) -> Option<TranslatedNotes> {
+    let language = deck_language(presentation_id)?;
+    let displayed = displayed?;
+    let translations = TRANSLATIONS.read();
+    let translation = translations
+        .get(&format!("{}:{}", presentation_id, slide_id))
+        .filter(|t| t.source == displayed && t.language == language)?;
+    Some(TranslatedNotes {
+        language,
+        text: translation.text.clone()?,
+    })
+}
+
+/// Translate the deck's notes that have no translation yet, in the
+/// background, when the deck has a target language. `displayed` is the
+/// current slide's notes as shown, which go first.
+pub fn ensure_translated(slide_data: &SlideData, displayed: Option<&str>) {
+    let presentation_id = &slide_data.presentation_id;
+    let Some(language) = deck_language(presentation_id) else {
+        return;
+    };
+
+    let prefix = format!("{}:", presentation_id);
+    // Collected first: `display_notes` reads the notes cache itself
+    let slide_ids: Vec<String> = SLIDE_NOTES
+        .read()
+        .keys()
+        .filter_map(|key| key.strip_prefix(&prefix))
+        .filter(|id| *id != slide_data.slide_id)
+        .map(|id| id.to_string())
+        .collect();
+    let others = slide_ids.into_iter().filter_map(|id| {
+        let notes = crate::display_notes(slide_data, &id)?;
+        Some((id, notes))
+    });
+    let current = displayed.map(|n| (slide_data.slide_id.clone(), n.to_string()));
+    let pending: Vec<(String, String)> = {
+        let translations = TRANSLATIONS.read();
+        current
+            .into_iter()
+            .chain(others)
+            .filter(|(slide_id, notes)| {
+                !notes.trim().is_empty()
+                    && !translations
+                        .get(&format!("{}{}", prefix, slide_id))
+                        .is_some_and(|t| t.source == *notes && t.language == language)
+            })
+            .collect()
+    };
+    if pending.is_empty() || !IN_FLIGHT.write().insert(presentation_id.clone()) {
+        return;
+    }
+
+    let presentation_id = presentation_id.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = translate_deck(&presentation_id, &language, pending).await;
+        IN_FLIGHT.write().remove(&presentation_id);
+        if let Err(message) = result {
+            eprintln!("Failed to translate notes: {}", message);
+            if let Some(app) = APP_HANDLE.read().as_ref() {
+                let _ = app.emit(
+                    "notes-translation-error",
+                    TranslationErrorEvent {
+                        presentation_id: presentation_id.clone(),
+                        message,
+                    },
+                );
+            }
+        }
+        crate::reemit_current_slide(&presentation_id, None);
+    });
+}
+
+async fn translate_deck(
+    presentation_id: &str,
+    language: &str,
+    pending: Vec<(String, String)>,
+) -> Result<(), String> {
+    let app = APP_HANDLE.read().clone().ok_or("App not ready")?;
+    let settings = SETTINGS.read().clone();
+    let api_key = API_KEY.get(&app);
+
+    let mut failure = None;
+    for batch in pending.chunks(BATCH_SIZE) {
+        let texts: Vec<&str> = batch.iter().map(|(_, notes)| notes.as_str()).collect();
+        let translated =
+            match request_translation(&settings, api_key.as_deref(), language, &texts).await {
+                Ok(t) => t.into_iter().map(Some).collect(),
+                Err(e) => {
+                    failure = Some(e);
+                    vec![None; batch.len()]
+                }
+            };
+        // The deck may have been switched off or closed meanwhile
+        if deck_language(presentation_id).as_deref() != Some(language) {
+            return Ok(());
+        }
+        let mut translations = TRANSLATIONS.write();
+        for ((slide_id, notes), text) in batch.iter().zip(translated) {
+            translations.insert(
+                format!("{}:{}", presentation_id, slide_id),
+                Translation {
+                    source: notes.clone(),
+                    language: language.to_string(),
+                    text,
+                },
+            );
+        }
+    }
+    failure.map_or(Ok(()), Err)
+}
+
+async fn request_translation(
+    settings: &TranslationSettings,
+    api_key: Option<&str>,
+    language: &str,
+    texts: &[&str],
+) -> Result<Vec<String>, String> {
+    let source = settings
+        .source_language
+        .as_deref()
+        .filter(|s| !s.trim().is_empty());
+    let request = match settings.backend {
+        TranslationBackend::Google => {
+            let api_key = api_key.ok_or("No translation API key set")?;
+            let mut body = serde_json::json!({
+                "q": texts,
+                "target": language,
+                "format": "text",
+            });
+            if let Some(source) = source {
+                body["source"] = source.into();
+            }
+            http_client::client()
+                .post(GOOGLE_TRANSLATE_URL)
+                .query(&[("key", api_key)])
+                .json(&body)
+        }
+        TranslationBackend::Endpoint => {
+            let endpoint = settings
+                .endpoint
+                .as_deref()
+                .filter(|e| !e.trim().is_empty())
+                .ok_or("No translation endpoint configured")?;
+            api_keys::check_endpoint(endpoint, "translation")?;
+            let mut body = serde_json::json!({
+                "q": texts,
+                "source": source.unwrap_or("auto"),
+                "target": language,
+                "format": "text",
+            });
+            if let Some(api_key) = api_key {
+                body["api_key"] = api_key.into();
+            }
+            http_client::client().post(endpoint).json(&body)
+        }
+    };
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Translation request failed: {}", e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!(
+            "Translation request failed: {} - {}",
+            status, error_text
+        ));
+    }
+
+    let json: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse translation response: {}", e))?;
+    parse_translations(settings.backend, &json, texts.len())
+}
+
+/// The translated texts of a response, in the order they were sent
+fn parse_translations(
+    backend: TranslationBackend,
+    json: &serde_json::Value,
+    sent: usize,
+) -> Result<Vec<String>, String> {
+    let translated: Vec<String> = match backend {
+        TranslationBackend::Google => json
+            .pointer("/data/translations")
+            .and_then(|t| t.as_array())
+            .map(|t| {
+                t.iter()
+                    .filter_map(|t| t.get("translatedText")?.as_str().map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        TranslationBackend::Endpoint => json
+            .get("translatedText")
+            .and_then(|t| t.as_array())
+            .map(|t| {
+                t.iter()
+                    .filter_map(|t| t.as_str().map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    };
+    if translated.len() != sent {
+        return Err("Translation response didn't match the notes sent".to_string());
+    }
+    Ok(translated)
+}
+
+/// Forget translations when the presentation changes or is refreshed
+pub fn clear() {
+    TRANSLATIONS.write().clear();
+}
+
+/// Delete the API key and cached translations, for a full data wipe
+pub fn reset(app: &AppHandle) {
+    let _ = API_KEY.set(app, "");
+    clear();
+}
+
+#[tauri::command]
+pub fn get_translation_settings(app: AppHandle) -> TranslationSettingsStatus {
+    TranslationSettingsStatus {
+        settings: SETTINGS.read().clone(),
+        has_api_key: API_KEY.get(&app).is_some(),
+    }
+}
+
+/// Save the backend settings; `api_key` replaces the stored key when given,
+/// and an empty one removes it
+#[tauri::command]
+pub fn set_translation_settings(
+    app: AppHandle,
+    settings: TranslationSettings,
+    api_key: Option<String>,
+) -> Result<(), CueCardError> {
+    if let Some(endpoint) = settings
+        .endpoint
+        .as_deref()
+        .filter(|e| !e.trim().is_empty())
+    {
+        api_keys::check_endpoint(endpoint, "translation")?;
+    }
+    if let Some(key) = api_key {
+        API_KEY.set(&app, key.trim())?;
+    }
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Ok(json) = serde_json::to_value(&settings) {
+            store.set(TRANSLATION_SETTINGS_KEY, json);
+            let _ = store.save();
+        }
+    }
+    *SETTINGS.write() = settings;
+    // Cached translations, and failures, came from the old backend
+    clear();
+    if let Some(id) = CURRENT_PRESENTATION_ID.read().clone() {
+        crate::reemit_current_slide(&id, None);
+    }
+    Ok(())
+}
+
+/// The language a deck's notes are translated into (the current deck when
+/// none is named)
+#[tauri::command]
+pub fn get_deck_translation(presentation_id: Option<String>) -> Option<String> {
+    presentation_id
+        .or_else(|| CURRENT_PRESENTATION_ID.read().clone())
+        .and_then(|id| deck_language(&id))
+}
+
+/// Translate a deck's notes (the current deck when none is named) into
+/// `language`, e.g. "es", or stop translating them with `None`
+#[tauri::command]
+pub fn set_deck_translation(
+    app: AppHandle,
+    presentation_id: Option<String>,
+    language: Option<String>,
+) -> Result<(), CueCardError> {
+    let presentation_id = presentation_id
+        .or_else(|| CURRENT_PRESENTATION_ID.read().clone())
+        .ok_or("No presentation is open")?;
+    let language = language
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty());
+    if let Some(language) = &language {
+        let valid = language.len() <= 12
+            && language
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(format!("\"{}\" isn't a language code like es", language).into());
+        }
+    }
+
+    {
+        let mut decks = DECK_LANGUAGES.write();
+        match language {
+            Some(language) => decks.insert(presentation_id.clone(), language),
+            None => decks.remove(&presentation_id),
+        };
+    }
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Ok(json) = serde_json::to_value(&*DECK_LANGUAGES.read()) {
+            store.set(DECK_TRANSLATION_KEY, json);
+            let _ = store.save();
+        }
+    }
+
+    crate::reemit_current_slide(&presentation_id, None);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reads_google_translations() {
+        let response = json!({ "data": { "translations": [
+            { "translatedText": "Hola a todos", "detectedSourceLanguage": "en" },
+            { "translatedText": "Gracias" },
+        ]}});
+        assert_eq!(
+            parse_translations(TranslationBackend::Google, &response, 2).unwrap(),
+            vec!["Hola a todos", "Gracias"]
+        );
+        // LibreTranslate's shape isn't Google's
+        let libre = json!({ "translatedText": ["Hola a todos", "Gracias"] });
+        assert!(parse_translations(TranslationBackend::Google, &libre, 2).is_err());
+    }
+
+    #[test]
+    fn reads_libretranslate_translations() {
+        let response = json!({ "translatedText": ["Hola a todos", "Gracias"] });
+        assert_eq!(
+            parse_translations(TranslationBackend::Endpoint, &response, 2).unwrap(),
+            vec!["Hola a todos", "Gracias"]
+        );
+        // A single text comes back as a string, not a list of one
+        let single = json!({ "translatedText": "Hola" });
+        assert!(parse_translations(TranslationBackend::Endpoint, &single, 1).is_err());
+    }
+
+    #[test]
+    fn rejects_a_response_of_another_length() {
+        let short = json!({ "translatedText": ["Hola a todos"] });
+        assert_eq!(
+            parse_translations(TranslationBackend::Endpoint, &short, 2).unwrap_err(),
+            "Translation response didn't match the notes sent"
+        );
+        let skipped = json!({ "data": { "translations": [
+            { "translatedText": "Hola a todos" },
+            { "detectedSourceLanguage": "en" },
+        ]}});
+        assert!(parse_translations(TranslationBackend::Google, &skipped, 2).is_err());
+    }
+}
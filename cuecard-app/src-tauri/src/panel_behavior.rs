@@ -0,0 +1,118 @@
+//! Where the panel shows up on macOS: Spaces, Stage Manager and full screen
+//!
+//! The NSPanel's collection behavior is built from these settings at startup
+//! and rebuilt whenever they change, so no restart is needed. Saved on other
+//! platforms too but only applied on macOS.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+use crate::error::CueCardError;
+
+const PANEL_BEHAVIOR_KEY: &str = "panel_behavior";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpacesMode {
+    /// Visible on every Space
+    #[default]
+    AllSpaces,
+    /// Moves to whichever Space is active when shown
+    CurrentSpace,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PanelBehavior {
+    pub spaces: SpacesMode,
+    /// Belong to Stage Manager sets like a regular window; otherwise macOS
+    /// treats the panel as floating over them
+    pub stage_manager: bool,
+    /// Show over full-screen apps such as Keynote
+    pub over_full_screen: bool,
+}
+
+impl Default for PanelBehavior {
+    fn default() -> Self {
+        Self {
+            spaces: SpacesMode::AllSpaces,
+            stage_manager: false,
+            over_full_screen: true,
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl PanelBehavior {
+    pub fn collection_behavior(&self) -> tauri_nspanel::CollectionBehavior {
+        let mut behavior = tauri_nspanel::CollectionBehavior::new();
+        behavior = match self.spaces {
+            SpacesMode::AllSpaces => behavior.can_join_all_spaces(),
+            SpacesMode::CurrentSpace => behavior.move_to_active_space(),
+        };
+        if self.stage_manager {
+            behavior = behavior.primary();
+        }
+        if self.over_full_screen {
+            behavior = behavior.full_screen_auxiliary();
+        } else {
+            behavior = behavior.full_screen_none();
+        }
+        behavior
+    }
+}
+
+static PANEL_BEHAVIOR: Lazy<Arc<RwLock<PanelBehavior>>> =
+    Lazy::new(|| Arc::new(RwLock::new(PanelBehavior::default())));
+
+pub fn current() -> PanelBehavior {
+    *PANEL_BEHAVIOR.read()
+}
+
+pub fn load_behavior_from_store(app: &AppHandle) {
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Some(behavior) = store
+            .get(PANEL_BEHAVIOR_KEY)
+            .and_then(|v| serde_json::from_value::<PanelBehavior>(v).ok())
+        {
+            *PANEL_BEHAVIOR.write() = behavior;
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn apply(app: &AppHandle, behavior: &PanelBehavior) -> Result<(), String> {
+    use tauri_nspanel::ManagerExt;
+
+    let panel = app
+        .get_webview_panel("main")
+        .map_err(|e| format!("Failed to get panel: {}", e))?;
+    panel.set_collection_behavior(behavior.collection_behavior().into());
+    Ok(())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn apply(_app: &AppHandle, _behavior: &PanelBehavior) -> Result<(), String> {
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_panel_behavior() -> PanelBehavior {
+    current()
+}
+
+#[tauri::command]
+pub fn set_panel_behavior(app: AppHandle, behavior: PanelBehavior) -> Result<(), CueCardError> {
+    apply(&app, &behavior)?;
+    *PANEL_BEHAVIOR.write() = behavior;
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Ok(json) = serde_json::to_value(behavior) {
+            store.set(PANEL_BEHAVIOR_KEY, json);
+            let _ = store.save();
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,497 @@
+//! Bulk preload of Google Slides decks
+//!
+//! `preload_presentations` fetches notes and thumbnails for several decks ahead
+//! of time and writes them to the disk cache, so they keep working if the
+//! network goes away. Progress is reported through `preload-progress` events.
+//!
+//! `list_presentations` lists the user's recent decks from Drive to pick them
+//! from, which needs the incremental "drive" scope (`drive.readonly`).
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tauri::Emitter;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::disk_cache::{self, CachedPresentation, CachedSlide};
+use crate::error::CueCardError;
+use crate::{api_usage, http_client, low_data, slide_mapping};
+use crate::{extract_notes_from_slide, fetch_presentation, slides_access_token, APP_HANDLE};
+
+// Decks fetched at once, and thumbnail downloads at once per deck
+const PRELOAD_CONCURRENCY: usize = 3;
+const THUMBNAIL_CONCURRENCY: usize = 4;
+
+const DRIVE_FILES_URL: &str = "https://www.googleapis.com/drive/v3/files";
+const DEFAULT_LIST_LIMIT: u32 = 20;
+const MAX_LIST_LIMIT: u32 = 100;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreloadStatus {
+    Started,
+    Slide,
+    Done,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PreloadProgressEvent {
+    pub presentation_id: String,
+    pub status: PreloadStatus,
+    pub title: Option<String>,
+    pub slides_done: usize,
+    pub slides_total: usize,
+    pub presentations_done: usize,
+    pub presentations_total: usize,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PreloadResult {
+    pub presentation_id: String,
+    pub title: Option<String>,
+    pub slide_count: usize,
+    pub thumbnail_count: usize,
+    pub error: Option<String>,
+}
+
+/// A deck from Drive, for the presentation picker
+#[derive(Debug, Clone, Serialize)]
+pub struct PresentationSummary {
+    pub presentation_id: String,
+    pub title: String,
+    /// RFC 3339, as Drive reports it
+    pub modified_time: Option<String>,
+    pub viewed_by_me_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail_link: Option<String>,
+    /// Already in the disk cache
+    pub cached: bool,
+}
+
+struct PreloadContext {
+    access_token: String,
+    client: reqwest::Client,
+    presentations_done: AtomicUsize,
+    presentations_total: usize,
+}
+
+impl PreloadContext {
+    fn emit(
+        &self,
+        presentation_id: &str,
+        status: PreloadStatus,
+        title: Option<&str>,
+        slides_done: usize,
+        slides_total: usize,
+        error: Option<String>,
+    ) {
+        if let Some(app) = APP_HANDLE.read().as_ref() {
+            let _ = app.emit(
+                "preload-progress",
+                PreloadProgressEvent {
+                    presentation_id: presentation_id.to_string(),
+                    status,
+                    title: title.map(|t| t.to_string()),
+                    slides_done,
+                    slides_total,
+                    presentations_done: self.presentations_done.load(Ordering::SeqCst),
+                    presentations_total: self.presentations_total,
+                    error,
+                },
+            );
+        }
+    }
+}
+
+async fn fetch_thumbnail(
+    ctx: &PreloadContext,
+    presentation_id: &str,
+    slide_id: &str,
+) -> Result<Vec<u8>, String> {
+    let url = format!(
+        "https://slides.googleapis.com/v1/presentations/{}/pages/{}/thumbnail?thumbnailProperties.mimeType=PNG&thumbnailProperties.thumbnailSize=MEDIUM",
+        presentation_id, slide_id
+    );
+
+    api_usage::record(api_usage::ApiCall::SlidesThumbnail);
+    let response = ctx
+        .client
+        .get(&url)
+        .header("Authorization", format!("Bearer {}", ctx.access_token))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("Thumbnail API error: {}", response.status()));
+    }
+
+    let json: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+    let content_url = json
+        .get("contentUrl")
+        .and_then(|u| u.as_str())
+        .ok_or("Thumbnail response has no contentUrl")?;
+
+    let image = ctx
+        .client
+        .get(content_url)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !image.status().is_success() {
+        return Err(format!("Thumbnail download error: {}", image.status()));
+    }
+    image
+        .bytes()
+        .await
+        .map(|b| b.to_vec())
+        .map_err(|e| e.to_string())
+}
+
+/// A `presentations.get` response's title and slides, without thumbnails yet
+fn deck_slides(json: &serde_json::Value) -> (String, Vec<CachedSlide>) {
+    let title = json
+        .get("title")
+        .and_then(|t| t.as_str())
+        .unwrap_or("Untitled presentation")
+        .to_string();
+    let slides = json
+        .get("slides")
+        .and_then(|s| s.as_array())
+        .map(|slides| {
+            slides
+                .iter()
+                .enumerate()
+                .filter_map(|(i, slide)| {
+                    Some(CachedSlide {
+                        slide_id: slide.get("objectId")?.as_str()?.to_string(),
+                        slide_number: i as i32 + 1,
+                        notes: extract_notes_from_slide(slide),
+                        has_thumbnail: false,
+                        hidden: slide_mapping::is_skipped(slide),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    (title, slides)
+}
+
+async fn preload_one(ctx: Arc<PreloadContext>, presentation_id: String) -> PreloadResult {
+    let json = match fetch_presentation(&presentation_id).await {
+        Ok(j) => j,
+        Err(e) => {
+            ctx.presentations_done.fetch_add(1, Ordering::SeqCst);
+            ctx.emit(
+                &presentation_id,
+                PreloadStatus::Failed,
+                None,
+                0,
+                0,
+                Some(e.to_string()),
+            );
+            return PreloadResult {
+                presentation_id,
+                title: None,
+                slide_count: 0,
+                thumbnail_count: 0,
+                error: Some(e.to_string()),
+            };
+        }
+    };
+
+    let (title, mut slides) = deck_slides(&json);
+
+    let total = slides.len();
+    ctx.emit(
+        &presentation_id,
+        PreloadStatus::Started,
+        Some(&title),
+        0,
+        total,
+        None,
+    );
+
+    // Thumbnails are best-effort; a deck with notes but no images is still usable offline.
+    // Low-data mode skips them entirely, as does nearing Google's thumbnail quota.
+    let semaphore = Arc::new(Semaphore::new(THUMBNAIL_CONCURRENCY));
+    let slides_done = Arc::new(AtomicUsize::new(0));
+    let mut tasks = JoinSet::new();
+    let with_thumbnails = !low_data::is_active() && !api_usage::throttle_optional();
+    for (index, slide) in slides.iter().enumerate().filter(|_| with_thumbnails) {
+        let ctx = ctx.clone();
+        let semaphore = semaphore.clone();
+        let slides_done = slides_done.clone();
+        let presentation_id = presentation_id.clone();
+        let slide_id = slide.slide_id.clone();
+        let title = title.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await.ok()?;
+            let saved = match fetch_thumbnail(&ctx, &presentation_id, &slide_id).await {
+                Ok(bytes) => disk_cache::write_thumbnail(&presentation_id, &slide_id, &bytes)
+                    .await
+                    .is_ok(),
+                Err(e) => {
+                    eprintln!("Thumbnail for {} failed: {}", slide_id, e);
+                    false
+                }
+            };
+            let done = slides_done.fetch_add(1, Ordering::SeqCst) + 1;
+            ctx.emit(
+                &presentation_id,
+                PreloadStatus::Slide,
+                Some(&title),
+                done,
+                total,
+                None,
+            );
+            saved.then_some(index)
+        });
+    }
+    while let Some(result) = tasks.join_next().await {
+        if let Ok(Some(index)) = result {
+            slides[index].has_thumbnail = true;
+        }
+    }
+
+    let thumbnail_count = slides.iter().filter(|s| s.has_thumbnail).count();
+    let cached = CachedPresentation {
+        presentation_id: presentation_id.clone(),
+        title: title.clone(),
+        cached_at: chrono::Utc::now().timestamp(),
+        revision: json
+            .get("revisionId")
+            .and_then(|r| r.as_str())
+            .map(|r| r.to_string()),
+        preloaded: true,
+        slides,
+    };
+    let error = disk_cache::write_presentation(&cached).await.err();
+
+    ctx.presentations_done.fetch_add(1, Ordering::SeqCst);
+    let status = if error.is_some() {
+        PreloadStatus::Failed
+    } else {
+        PreloadStatus::Done
+    };
+    ctx.emit(
+        &presentation_id,
+        status,
+        Some(&title),
+        total,
+        total,
+        error.clone(),
+    );
+
+    PreloadResult {
+        presentation_id,
+        title: Some(title),
+        slide_count: total,
+        thumbnail_count,
+        error,
+    }
+}
+
+/// Fetch and disk-cache notes and thumbnails for several presentations
+#[tauri::command]
+pub async fn preload_presentations(
+    presentation_ids: Vec<String>,
+) -> Result<Vec<PreloadResult>, CueCardError> {
+    let access_token = slides_access_token().await?;
+
+    let mut seen = std::collections::HashSet::new();
+    let ids: Vec<String> = presentation_ids
+        .into_iter()
+        .filter(|id| seen.insert(id.clone()))
+        .collect();
+
+    let ctx = Arc::new(PreloadContext {
+        access_token,
+        client: http_client::client(),
+        presentations_done: AtomicUsize::new(0),
+        presentations_total: ids.len(),
+    });
+
+    let semaphore = Arc::new(Semaphore::new(PRELOAD_CONCURRENCY));
+    let mut tasks = JoinSet::new();
+    for (index, presentation_id) in ids.into_iter().enumerate() {
+        let ctx = ctx.clone();
+        let semaphore = semaphore.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            (index, preload_one(ctx, presentation_id).await)
+        });
+    }
+
+    let mut results = Vec::new();
+    while let Some(result) = tasks.join_next().await {
+        if let Ok(r) = result {
+            results.push(r);
+        }
+    }
+    results.sort_by_key(|(index, _)| *index);
+    Ok(results.into_iter().map(|(_, r)| r).collect())
+}
+
+/// Drive query for presentations, optionally with `name` in their title
+fn drive_query(name: Option<&str>) -> String {
+    let mut query =
+        "mimeType='application/vnd.google-apps.presentation' and trashed=false".to_string();
+    if let Some(name) = name.map(str::trim).filter(|n| !n.is_empty()) {
+        let escaped = name.replace('\\', "\\\\").replace('\'', "\\'");
+        query.push_str(&format!(" and name contains '{}'", escaped));
+    }
+    query
+}
+
+/// The user's Google Slides decks, most recently viewed first
+#[tauri::command]
+pub async fn list_presentations(
+    query: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<PresentationSummary>, CueCardError> {
+    if !crate::has_drive_scope() {
+        return Err(CueCardError::ScopeMissing(
+            "Listing presentations needs Drive access".to_string(),
+        ));
+    }
+    let access_token = slides_access_token().await?;
+    let limit = limit
+        .unwrap_or(DEFAULT_LIST_LIMIT)
+        .clamp(1, MAX_LIST_LIMIT)
+        .to_string();
+
+    api_usage::record(api_usage::ApiCall::Drive);
+    let response = http_client::client()
+        .get(DRIVE_FILES_URL)
+        .bearer_auth(&access_token)
+        .query(&[
+            ("q", drive_query(query.as_deref()).as_str()),
+            ("orderBy", "viewedByMeTime desc,modifiedTime desc"),
+            ("pageSize", limit.as_str()),
+            (
+                "fields",
+                "files(id,name,modifiedTime,viewedByMeTime,thumbnailLink)",
+            ),
+        ])
+        .send()
+        .await
+        .map_err(|e| CueCardError::Network(format!("Failed to list presentations: {}", e)))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(CueCardError::from_status(
+            status,
+            format!("Failed to list presentations: {} - {}", status, error_text),
+        ));
+    }
+
+    let json: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse Drive response: {}", e))?;
+    let string = |file: &serde_json::Value, name: &str| {
+        file.get(name)
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string())
+    };
+
+    Ok(json
+        .get("files")
+        .and_then(|f| f.as_array())
+        .map(|files| {
+            files
+                .iter()
+                .filter_map(|file| {
+                    let presentation_id = string(file, "id")?;
+                    Some(PresentationSummary {
+                        cached: disk_cache::cached_title(&presentation_id).is_some(),
+                        title: string(file, "name").unwrap_or_default(),
+                        modified_time: string(file, "modifiedTime"),
+                        viewed_by_me_time: string(file, "viewedByMeTime"),
+                        thumbnail_link: string(file, "thumbnailLink"),
+                        presentation_id,
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn slide(object_id: &str, notes: Option<&str>, skipped: bool) -> serde_json::Value {
+        let page_elements = match notes {
+            Some(notes) => json!([{
+                "shape": {
+                    "placeholder": { "type": "BODY" },
+                    "text": { "textElements": [{ "textRun": { "content": notes } }] }
+                }
+            }]),
+            None => json!([]),
+        };
+        json!({
+            "objectId": object_id,
+            "slideProperties": {
+                "isSkipped": skipped,
+                "notesPage": { "pageElements": page_elements }
+            }
+        })
+    }
+
+    #[test]
+    fn reads_slides_in_deck_order() {
+        let deck = json!({
+            "title": "Quarterly review",
+            "slides": [
+                slide("p1", Some("Welcome everyone\n"), false),
+                slide("p2", None, true),
+                { "pageElements": [] },
+                slide("p4", Some("Questions\n"), false)
+            ]
+        });
+        let (title, slides) = deck_slides(&deck);
+        assert_eq!(title, "Quarterly review");
+        assert_eq!(
+            slides
+                .iter()
+                .map(|s| (s.slide_id.as_str(), s.slide_number, s.hidden))
+                .collect::<Vec<_>>(),
+            vec![("p1", 1, false), ("p2", 2, true), ("p4", 4, false)]
+        );
+        assert!(slides[0]
+            .notes
+            .as_deref()
+            .unwrap()
+            .contains("Welcome everyone"));
+        assert_eq!(slides[1].notes, None);
+        assert!(slides.iter().all(|s| !s.has_thumbnail));
+    }
+
+    #[test]
+    fn drive_query_escapes_the_title() {
+        let base = "mimeType='application/vnd.google-apps.presentation' and trashed=false";
+        assert_eq!(drive_query(None), base);
+        assert_eq!(drive_query(Some("  ")), base);
+        assert_eq!(
+            drive_query(Some(" Q3 review ")),
+            format!("{} and name contains 'Q3 review'", base)
+        );
+        assert_eq!(
+            drive_query(Some(r"Ada's \ deck")),
+            format!(r"{} and name contains 'Ada\'s \\ deck'", base)
+        );
+    }
+
+    #[test]
+    fn untitled_or_empty_decks() {
+        let (title, slides) = deck_slides(&json!({}));
+        assert_eq!(title, "Untitled presentation");
+        assert!(slides.is_empty());
+    }
+}
@@ -0,0 +1,463 @@
+//! Named presenter setups ("Webinar", "On-stage", "Recording")
+//!
+//! A profile bundles the panel's position and size, opacity, global
+//! shortcuts, screenshot protection, macOS panel behavior, Windows topmost
+//! banding and which presentation app is followed. Settings a profile leaves
+//! unset are kept as they are. `apply_profile` applies everything or nothing:
+//! if a step fails, the settings it already changed are put back. A profile
+//! can carry its own hotkey, which stays registered while global shortcuts
+//! are turned off so profiles can always be switched back.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, PhysicalPosition, PhysicalSize};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+use tauri_plugin_store::StoreExt;
+
+use crate::error::CueCardError;
+use crate::panel_behavior::{self, PanelBehavior};
+use crate::providers::{accessibility, keynote, powerpoint};
+use crate::topmost::{self, TopmostBanding};
+
+const PROFILES_KEY: &str = "profiles";
+const ACTIVE_PROFILE_KEY: &str = "active_profile";
+
+/// Panel bounds in physical pixels
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ProfileWindow {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Presentation app whose presenter view is followed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PresenterTracking {
+    Off,
+    /// Keynote presenter window (macOS)
+    Keynote,
+    /// PowerPoint presenter window (macOS)
+    Powerpoint,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfileIntegrations {
+    /// Follow the slideshow in desktop PowerPoint (Windows)
+    #[serde(default)]
+    pub powerpoint_tracking: Option<bool>,
+    /// Follow the slideshow playing in Keynote (macOS)
+    #[serde(default)]
+    pub keynote_tracking: Option<bool>,
+    #[serde(default)]
+    pub presenter_tracking: Option<PresenterTracking>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    /// e.g. "Ctrl+Alt+1"
+    #[serde(default)]
+    pub hotkey: Option<String>,
+    #[serde(default)]
+    pub window: Option<ProfileWindow>,
+    /// Panel opacity in percent (10-100), applied by the panel itself
+    #[serde(default)]
+    pub opacity: Option<u8>,
+    #[serde(default)]
+    pub shortcuts_enabled: Option<bool>,
+    #[serde(default)]
+    pub screenshot_protection: Option<bool>,
+    #[serde(default)]
+    pub panel_behavior: Option<PanelBehavior>,
+    #[serde(default)]
+    pub topmost_banding: Option<TopmostBanding>,
+    #[serde(default)]
+    pub integrations: ProfileIntegrations,
+}
+
+static PROFILES: Lazy<Arc<RwLock<Vec<Profile>>>> = Lazy::new(|| Arc::new(RwLock::new(Vec::new())));
+static ACTIVE_PROFILE: Lazy<Arc<RwLock<Option<String>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(None)));
+// Registered profile hotkeys: shortcut id -> (shortcut, profile name)
+type ProfileHotkeys = HashMap<u32, (Shortcut, String)>;
+static PROFILE_HOTKEYS: Lazy<Arc<RwLock<ProfileHotkeys>>> =
+    Lazy::new(|| Arc::new(RwLock::new(HashMap::new())));
+
+fn parse_hotkey(hotkey: &str) -> Result<Shortcut, String> {
+    Shortcut::from_str(hotkey).map_err(|e| format!("Invalid hotkey \"{}\": {}", hotkey, e))
+}
+
+/// Swap the registered profile hotkeys for the current profiles'
+fn register_hotkeys(app: &AppHandle) {
+    let mut hotkeys = PROFILE_HOTKEYS.write();
+    for (shortcut, _) in hotkeys.values() {
+        let _ = app.global_shortcut().unregister(*shortcut);
+    }
+    hotkeys.clear();
+
+    for profile in PROFILES.read().iter() {
+        let Some(hotkey) = &profile.hotkey else {
+            continue;
+        };
+        let shortcut = match parse_hotkey(hotkey) {
+            Ok(shortcut) => shortcut,
+            Err(e) => {
+                eprintln!("{}", e);
+                continue;
+            }
+        };
+        match app.global_shortcut().register(shortcut) {
+            Ok(()) => {
+                hotkeys.insert(shortcut.id(), (shortcut, profile.name.clone()));
+            }
+            Err(e) => eprintln!("Failed to register hotkey {}: {}", hotkey, e),
+        }
+    }
+}
+
+pub fn load_profiles_from_store(app: &AppHandle) {
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Some(profiles) = store
+            .get(PROFILES_KEY)
+            .and_then(|v| serde_json::from_value::<Vec<Profile>>(v).ok())
+        {
+            *PROFILES.write() = profiles;
+        }
+        *ACTIVE_PROFILE.write() = store
+            .get(ACTIVE_PROFILE_KEY)
+            .and_then(|v| v.as_str().map(str::to_string));
+    }
+    register_hotkeys(app);
+}
+
+/// The profile a hotkey switches to, if it's a profile hotkey
+pub fn profile_for_shortcut(id: u32) -> Option<String> {
+    PROFILE_HOTKEYS
+        .read()
+        .get(&id)
+        .map(|(_, name)| name.clone())
+}
+
+fn validate(profiles: &[Profile]) -> Result<(), String> {
+    let mut names = std::collections::HashSet::new();
+    let mut hotkeys = HashMap::new();
+    for profile in profiles {
+        let name = profile.name.trim();
+        if name.is_empty() {
+            return Err("Profiles need a name".to_string());
+        }
+        if !names.insert(name.to_lowercase()) {
+            return Err(format!("There's more than one profile named \"{}\"", name));
+        }
+        if let Some(opacity) = profile.opacity {
+            if !(10..=100).contains(&opacity) {
+                return Err(format!("{}: opacity must be between 10 and 100", name));
+            }
+        }
+        if let Some(window) = profile.window {
+            if window.width == 0 || window.height == 0 {
+                return Err(format!("{}: the panel needs a size", name));
+            }
+        }
+        if let Some(hotkey) = &profile.hotkey {
+            let shortcut = parse_hotkey(hotkey)?;
+            if let Some(other) = hotkeys.insert(shortcut.id(), name) {
+                return Err(format!("{} and {} use the same hotkey", other, name));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The current setup, in the shape of a profile that restores it
+fn capture(app: &AppHandle) -> Profile {
+    let window = app.get_webview_window("main").and_then(|window| {
+        let position = window.outer_position().ok()?;
+        let size = window.outer_size().ok()?;
+        Some(ProfileWindow {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+        })
+    });
+    Profile {
+        name: String::new(),
+        hotkey: None,
+        window,
+        opacity: None,
+        shortcuts_enabled: Some(*crate::SHORTCUTS_ENABLED.read()),
+        screenshot_protection: Some(*crate::SCREENSHOT_PROTECTION.read()),
+        panel_behavior: Some(panel_behavior::current()),
+        topmost_banding: Some(topmost::get_topmost_banding()),
+        integrations: ProfileIntegrations {
+            powerpoint_tracking: Some(powerpoint::is_powerpoint_tracking()),
+            keynote_tracking: Some(keynote::is_keynote_tracking()),
+            // Which app is being followed isn't known, so a running tracker is left alone
+            presenter_tracking: (!accessibility::is_presenter_tracking())
+                .then_some(PresenterTracking::Off),
+        },
+    }
+}
+
+fn apply_settings(app: &AppHandle, profile: &Profile) -> Result<(), String> {
+    if let Some(bounds) = profile.window {
+        let window = app
+            .get_webview_window("main")
+            .ok_or("Failed to get main window")?;
+        window
+            .set_size(PhysicalSize::new(bounds.width, bounds.height))
+            .map_err(|e| format!("Failed to resize the panel: {}", e))?;
+        window
+            .set_position(PhysicalPosition::new(bounds.x, bounds.y))
+            .map_err(|e| format!("Failed to move the panel: {}", e))?;
+    }
+    if let Some(enabled) = profile.screenshot_protection {
+        crate::set_screenshot_protection(app.clone(), enabled)?;
+    }
+    if let Some(enabled) = profile.shortcuts_enabled {
+        if enabled != *crate::SHORTCUTS_ENABLED.read() {
+            crate::set_shortcuts_enabled(app.clone(), enabled)?;
+        }
+    }
+    if let Some(behavior) = profile.panel_behavior {
+        panel_behavior::set_panel_behavior(app.clone(), behavior)?;
+    }
+    if let Some(banding) = profile.topmost_banding {
+        topmost::set_topmost_banding(app.clone(), banding);
+    }
+    match profile.integrations.powerpoint_tracking {
+        Some(true) => powerpoint::start_powerpoint_tracking()?,
+        Some(false) => powerpoint::stop_powerpoint_tracking(),
+        None => {}
+    }
+    match profile.integrations.keynote_tracking {
+        Some(true) => keynote::start_keynote_tracking()?,
+        Some(false) => keynote::stop_keynote_tracking(),
+        None => {}
+    }
+    match profile.integrations.presenter_tracking {
+        Some(PresenterTracking::Off) => accessibility::stop_presenter_tracking(),
+        Some(PresenterTracking::Keynote) => {
+            accessibility::start_presenter_tracking("keynote".to_string())?
+        }
+        Some(PresenterTracking::Powerpoint) => {
+            accessibility::start_presenter_tracking("powerpoint".to_string())?
+        }
+        None => {}
+    }
+    Ok(())
+}
+
+/// Apply `profile`, or put `previous` (the setup before) back if a step fails
+fn apply_or_restore(
+    profile: &Profile,
+    previous: &Profile,
+    mut apply: impl FnMut(&Profile) -> Result<(), String>,
+) -> Result<(), String> {
+    if let Err(e) = apply(profile) {
+        if let Err(restore_error) = apply(previous) {
+            eprintln!("Failed to restore settings: {}", restore_error);
+        }
+        return Err(format!("Failed to apply profile {}: {}", profile.name, e));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_profiles() -> Vec<Profile> {
+    PROFILES.read().clone()
+}
+
+#[tauri::command]
+pub fn get_active_profile() -> Option<String> {
+    ACTIVE_PROFILE.read().clone()
+}
+
+/// Replace the saved profiles and re-register their hotkeys
+#[tauri::command]
+pub fn set_profiles(app: AppHandle, profiles: Vec<Profile>) -> Result<(), CueCardError> {
+    validate(&profiles)?;
+    // A hotkey taken by CueCard's own shortcuts or another app can't be used
+    for profile in &profiles {
+        if let Some(hotkey) = &profile.hotkey {
+            let shortcut = parse_hotkey(hotkey)?;
+            let ours = PROFILE_HOTKEYS.read().contains_key(&shortcut.id());
+            if !ours && app.global_shortcut().is_registered(shortcut) {
+                return Err(format!("{} is already in use", hotkey).into());
+            }
+        }
+    }
+
+    *PROFILES.write() = profiles;
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Ok(json) = serde_json::to_value(&*PROFILES.read()) {
+            store.set(PROFILES_KEY, json);
+            let _ = store.save();
+        }
+    }
+    register_hotkeys(&app);
+    Ok(())
+}
+
+/// Switch to a profile; if any of its settings can't be applied, none are
+#[tauri::command]
+pub fn apply_profile(app: AppHandle, name: String) -> Result<Profile, CueCardError> {
+    let profile = PROFILES
+        .read()
+        .iter()
+        .find(|p| p.name.eq_ignore_ascii_case(name.trim()))
+        .cloned()
+        .ok_or_else(|| format!("No profile named \"{}\"", name))?;
+
+    let previous = capture(&app);
+    apply_or_restore(&profile, &previous, |p| apply_settings(&app, p))?;
+
+    *ACTIVE_PROFILE.write() = Some(profile.name.clone());
+    if let Ok(store) = app.store("cuecard-store.json") {
+        store.set(ACTIVE_PROFILE_KEY, profile.name.clone());
+        let _ = store.save();
+    }
+    let _ = app.emit("profile-applied", profile.clone());
+    Ok(profile)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(name: &str) -> Profile {
+        Profile {
+            name: name.to_string(),
+            hotkey: None,
+            window: None,
+            opacity: None,
+            shortcuts_enabled: None,
+            screenshot_protection: None,
+            panel_behavior: None,
+            topmost_banding: None,
+            integrations: ProfileIntegrations::default(),
+        }
+    }
+
+    /// Settings applied in order, like `apply_settings`; Keynote tracking
+    /// fails as it does on a machine without Keynote
+    #[derive(Debug, Default, Clone, PartialEq)]
+    struct Setup {
+        screenshot_protection: bool,
+        shortcuts_enabled: bool,
+        keynote_tracking: bool,
+    }
+
+    impl Setup {
+        fn apply(&mut self, profile: &Profile) -> Result<(), String> {
+            if let Some(enabled) = profile.screenshot_protection {
+                self.screenshot_protection = enabled;
+            }
+            if let Some(enabled) = profile.shortcuts_enabled {
+                self.shortcuts_enabled = enabled;
+            }
+            match profile.integrations.keynote_tracking {
+                Some(true) => return Err("Keynote isn't installed".to_string()),
+                Some(false) => self.keynote_tracking = false,
+                None => {}
+            }
+            Ok(())
+        }
+
+        fn captured(&self) -> Profile {
+            Profile {
+                screenshot_protection: Some(self.screenshot_protection),
+                shortcuts_enabled: Some(self.shortcuts_enabled),
+                integrations: ProfileIntegrations {
+                    keynote_tracking: Some(self.keynote_tracking),
+                    ..Default::default()
+                },
+                ..profile("")
+            }
+        }
+    }
+
+    #[test]
+    fn a_failed_step_puts_everything_back() {
+        let mut setup = Setup {
+            shortcuts_enabled: true,
+            ..Default::default()
+        };
+        let before = setup.clone();
+        let on_stage = Profile {
+            screenshot_protection: Some(true),
+            shortcuts_enabled: Some(false),
+            integrations: ProfileIntegrations {
+                keynote_tracking: Some(true),
+                ..Default::default()
+            },
+            ..profile("On-stage")
+        };
+
+        let previous = setup.captured();
+        let error = apply_or_restore(&on_stage, &previous, |p| setup.apply(p)).unwrap_err();
+        assert!(error.contains("On-stage") && error.contains("Keynote"));
+        assert_eq!(setup, before);
+    }
+
+    #[test]
+    fn settings_a_profile_leaves_unset_are_kept() {
+        let mut setup = Setup {
+            shortcuts_enabled: true,
+            ..Default::default()
+        };
+        let webinar = Profile {
+            screenshot_protection: Some(true),
+            ..profile("Webinar")
+        };
+        let previous = setup.captured();
+        apply_or_restore(&webinar, &previous, |p| setup.apply(p)).unwrap();
+        assert!(setup.screenshot_protection);
+        assert!(setup.shortcuts_enabled);
+    }
+
+    #[test]
+    fn validates_names_opacity_and_hotkeys() {
+        assert!(validate(&[profile("Webinar"), profile("Recording")]).is_ok());
+        assert!(validate(&[profile(" ")]).is_err());
+        assert!(validate(&[profile("Webinar"), profile("webinar ")]).is_err());
+        assert!(validate(&[Profile {
+            opacity: Some(5),
+            ..profile("Dim")
+        }])
+        .is_err());
+        assert!(validate(&[Profile {
+            window: Some(ProfileWindow {
+                x: 0,
+                y: 0,
+                width: 0,
+                height: 400,
+            }),
+            ..profile("Tiny")
+        }])
+        .is_err());
+
+        let with_hotkey = |name: &str, hotkey: &str| Profile {
+            hotkey: Some(hotkey.to_string()),
+            ..profile(name)
+        };
+        assert!(validate(&[
+            with_hotkey("A", "Ctrl+Alt+1"),
+            with_hotkey("B", "Ctrl+Alt+2")
+        ])
+        .is_ok());
+        assert!(validate(&[
+            with_hotkey("A", "Ctrl+Alt+1"),
+            with_hotkey("B", "Alt+Ctrl+1")
+        ])
+        .is_err());
+        assert!(validate(&[with_hotkey("A", "Ctrl+Alt+Nope")]).is_err());
+    }
+}
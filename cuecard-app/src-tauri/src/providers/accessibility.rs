@@ -0,0 +1,252 @@
+//! Keynote / PowerPoint presenter-window tracking (macOS)
+//!
+//! Neither app exposes its slideshow position to the browser extension, but
+//! both show a "Slide 3 of 20" style counter in the presenter window. This
+//! provider polls that counter through the Accessibility API (System Events UI
+//! scripting) and shows the matching slide of the loaded local notes file.
+//! Accessibility access must be granted first; the commands check it up front.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::sync::Arc;
+use tauri::Emitter;
+
+use crate::error::CueCardError;
+
+#[cfg(target_os = "macos")]
+const POLL_INTERVAL_MS: u64 = 750;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PresenterTrackingStatus {
+    pub running: bool,
+    pub app: Option<String>,
+    pub slide: Option<i32>,
+    pub total: Option<i32>,
+}
+
+static TRACKER_TASK: Lazy<Arc<RwLock<Option<tauri::async_runtime::JoinHandle<()>>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(None)));
+
+#[cfg(target_os = "macos")]
+#[link(name = "ApplicationServices", kind = "framework")]
+extern "C" {
+    fn AXIsProcessTrusted() -> bool;
+}
+
+/// Process name System Events knows the presenting app by
+fn process_name(app: &str) -> Option<&'static str> {
+    match app {
+        "keynote" => Some("Keynote"),
+        "powerpoint" => Some("Microsoft PowerPoint"),
+        _ => None,
+    }
+}
+
+/// Collect every static text in the app's windows, one per line
+#[cfg(target_os = "macos")]
+fn counter_script(process: &str) -> String {
+    format!(
+        r#"tell application "System Events"
+  if not (exists process "{process}") then return ""
+  set out to ""
+  repeat with w in windows of process "{process}"
+    try
+      repeat with e in (entire contents of w)
+        try
+          if role of e is "AXStaticText" then set out to out & (value of e as text) & linefeed
+        end try
+      end repeat
+    end try
+  end repeat
+  return out
+end tell"#
+    )
+}
+
+/// Find a "Slide 3 of 20" / "3 of 20" / "3 / 20" counter in window text
+#[cfg(any(target_os = "macos", test))]
+pub fn parse_slide_counter(text: &str) -> Option<(i32, i32)> {
+    for line in text.lines() {
+        let words: Vec<&str> = line
+            .split(|c: char| c.is_whitespace())
+            .filter(|w| !w.is_empty())
+            .collect();
+
+        for window in words.windows(3) {
+            let separator = window[1].eq_ignore_ascii_case("of") || window[1] == "/";
+            if !separator {
+                continue;
+            }
+            if let (Ok(current), Ok(total)) = (window[0].parse::<i32>(), window[2].parse::<i32>()) {
+                if current >= 1 && current <= total {
+                    return Some((current, total));
+                }
+            }
+        }
+    }
+    None
+}
+
+fn emit_tracking_status(status: PresenterTrackingStatus) {
+    if let Some(app) = crate::APP_HANDLE.read().as_ref() {
+        let _ = app.emit("presenter-tracking", status);
+    }
+}
+
+#[cfg(target_os = "macos")]
+async fn read_counter(process: &str) -> Option<(i32, i32)> {
+    let output = tokio::process::Command::new("osascript")
+        .args(["-e", &counter_script(process)])
+        .output()
+        .await
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+    parse_slide_counter(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(target_os = "macos")]
+async fn run_tracker(app: String, process: &'static str) {
+    let mut last: Option<(i32, i32)> = None;
+    emit_tracking_status(PresenterTrackingStatus {
+        running: true,
+        app: Some(app.clone()),
+        slide: None,
+        total: None,
+    });
+
+    loop {
+        let counter = read_counter(process).await;
+        if counter.is_some() && counter != last {
+            if let Some((slide, total)) = counter {
+                if let Err(e) = super::local_file::show_local_slide(slide).await {
+                    eprintln!("Presenter tracking: {}", e);
+                }
+                emit_tracking_status(PresenterTrackingStatus {
+                    running: true,
+                    app: Some(app.clone()),
+                    slide: Some(slide),
+                    total: Some(total),
+                });
+            }
+            last = counter;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(POLL_INTERVAL_MS)).await;
+    }
+}
+
+/// Whether the app has been granted Accessibility access
+#[tauri::command]
+pub fn check_accessibility_permission() -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        unsafe { AXIsProcessTrusted() }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        false
+    }
+}
+
+/// Open System Settings at the Accessibility privacy pane
+#[tauri::command]
+pub fn open_accessibility_settings() -> Result<(), CueCardError> {
+    #[cfg(target_os = "macos")]
+    {
+        std::process::Command::new("open")
+            .arg("x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility")
+            .spawn()
+            .map_err(|e| format!("Failed to open System Settings: {}", e))?;
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        Err("Accessibility settings are only available on macOS".into())
+    }
+}
+
+/// Follow the presenter window of Keynote or PowerPoint ("keynote" / "powerpoint")
+#[tauri::command]
+pub fn start_presenter_tracking(app: String) -> Result<(), CueCardError> {
+    let process =
+        process_name(&app).ok_or_else(|| format!("Unsupported presentation app: {}", app))?;
+
+    #[cfg(target_os = "macos")]
+    {
+        if !check_accessibility_permission() {
+            return Err("Accessibility access is required to read the presenter window".into());
+        }
+        if super::local_file::LOCAL_DECK.read().is_none() {
+            return Err("Load a notes file before tracking the presenter window".into());
+        }
+
+        let mut task = TRACKER_TASK.write();
+        if let Some(existing) = task.take() {
+            existing.abort();
+        }
+        *task = Some(tauri::async_runtime::spawn(run_tracker(app, process)));
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = process;
+        Err("Presenter window tracking is only available on macOS".into())
+    }
+}
+
+#[tauri::command]
+pub fn stop_presenter_tracking() {
+    if let Some(task) = TRACKER_TASK.write().take() {
+        task.abort();
+        emit_tracking_status(PresenterTrackingStatus {
+            running: false,
+            app: None,
+            slide: None,
+            total: None,
+        });
+    }
+}
+
+#[tauri::command]
+pub fn is_presenter_tracking() -> bool {
+    TRACKER_TASK.read().is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_counter_in_window_text() {
+        assert_eq!(parse_slide_counter("Slide 3 of 20"), Some((3, 20)));
+        assert_eq!(
+            parse_slide_counter("Elapsed 04:12\n7 OF 12\n"),
+            Some((7, 12))
+        );
+        assert_eq!(parse_slide_counter("Notes\n  5 / 9  "), Some((5, 9)));
+    }
+
+    #[test]
+    fn ignores_text_that_isnt_a_counter() {
+        assert_eq!(parse_slide_counter(""), None);
+        assert_eq!(parse_slide_counter("one of many"), None);
+        // Past the end, or before the first slide
+        assert_eq!(parse_slide_counter("21 of 20"), None);
+        assert_eq!(parse_slide_counter("0 of 20"), None);
+        assert_eq!(parse_slide_counter("3/20"), None);
+    }
+
+    #[test]
+    fn takes_the_first_valid_counter() {
+        assert_eq!(
+            parse_slide_counter("Q3 of 2024 results\n25 of 10\n4 of 10\n5 of 10"),
+            Some((4, 10))
+        );
+    }
+}
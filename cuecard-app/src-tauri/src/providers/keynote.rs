@@ -0,0 +1,294 @@
+//! Keynote slideshow tracking (macOS)
+//!
+//! Keynote's AppleScript dictionary exposes the playing document, its current
+//! slide and every slide's presenter notes, so unlike the presenter-window
+//! tracker in `accessibility` this needs no notes file and no Accessibility
+//! access, only the Automation prompt macOS shows the first time. `osascript`
+//! is polled for the current slide; the deck's notes are read when a
+//! slideshow starts, retried on the next poll if that fails, and re-read
+//! every `NOTES_REFRESH_POLLS` polls to pick up edits. Keynote slides have no
+//! stable id, so slide numbers stand in for them.
+//!
+//! Keynote doesn't say which document is playing, and the front document
+//! can change while a slideshow runs on another display. The document in
+//! front when the slideshow starts is taken as the playing one and followed
+//! by id until the slideshow ends.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use std::sync::Arc;
+use tauri::Emitter;
+
+use crate::error::CueCardError;
+
+/// Presentation modes reported for slides coming from this provider
+#[cfg(target_os = "macos")]
+pub const MODE: &str = "keynote";
+
+#[cfg(target_os = "macos")]
+const POLL_INTERVAL_MS: u64 = 400;
+#[cfg(target_os = "macos")]
+const NOTES_REFRESH_POLLS: u32 = 25;
+
+// Fields are split by ASCII unit separators and slides by record separators,
+// neither of which turns up in presenter notes
+#[cfg(any(target_os = "macos", test))]
+const UNIT_SEPARATOR: char = '\u{1f}';
+#[cfg(any(target_os = "macos", test))]
+const RECORD_SEPARATOR: char = '\u{1e}';
+
+static TRACKER_TASK: Lazy<Arc<RwLock<Option<tauri::async_runtime::JoinHandle<()>>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(None)));
+
+/// The slideshow's document and current slide
+#[cfg(any(target_os = "macos", test))]
+#[derive(Debug, Clone, PartialEq)]
+struct KeynoteSlide {
+    document_id: String,
+    name: String,
+    number: i32,
+}
+
+// Checking `running` outside the tell block keeps the script from launching
+// Keynote. Takes the playing document's id once known; the front document
+// is used until then, or if that one has been closed.
+#[cfg(target_os = "macos")]
+const SLIDE_SCRIPT: &str = r#"on run argv
+if application "Keynote" is not running then return ""
+tell application "Keynote"
+  if not playing then return ""
+  try
+    set doc to document id (item 1 of argv)
+    get id of doc
+  on error
+    set doc to front document
+  end try
+  set sep to ASCII character 31
+  return (id of doc) & sep & (name of doc) & sep & (slide number of current slide of doc as text)
+end tell
+end run"#;
+
+// Takes the playing document's id
+#[cfg(target_os = "macos")]
+const NOTES_SCRIPT: &str = r#"on run argv
+if application "Keynote" is not running then return ""
+tell application "Keynote"
+  if not playing then return ""
+  set sep to ASCII character 31
+  set rec to ASCII character 30
+  set out to ""
+  repeat with s in slides of document id (item 1 of argv)
+    set out to out & (slide number of s as text) & sep & (presenter notes of s) & rec
+  end repeat
+  return out
+end tell
+end run"#;
+
+/// Parse `SLIDE_SCRIPT` output; empty when no slideshow is playing
+#[cfg(any(target_os = "macos", test))]
+fn parse_slide(output: &str) -> Option<KeynoteSlide> {
+    let mut fields = output.trim_end_matches('\n').split(UNIT_SEPARATOR);
+    let document_id = fields.next()?.trim();
+    let name = fields.next()?.trim();
+    let number = fields.next()?.trim().parse().ok()?;
+    if document_id.is_empty() {
+        return None;
+    }
+    Some(KeynoteSlide {
+        document_id: document_id.to_string(),
+        name: name.to_string(),
+        number,
+    })
+}
+
+/// Parse `NOTES_SCRIPT` output into (slide number, notes) pairs
+#[cfg(any(target_os = "macos", test))]
+fn parse_notes(output: &str) -> Vec<(String, String)> {
+    output
+        .trim_end_matches('\n')
+        .split(RECORD_SEPARATOR)
+        .filter_map(|record| {
+            let (number, notes) = record.split_once(UNIT_SEPARATOR)?;
+            let number = number.trim();
+            number.parse::<i32>().ok()?;
+            // AppleScript returns classic Mac line endings
+            Some((number.to_string(), notes.replace('\r', "\n")))
+        })
+        .collect()
+}
+
+#[cfg(target_os = "macos")]
+fn presentation_id(document_id: &str) -> String {
+    format!("{}:{}", MODE, document_id)
+}
+
+fn emit_tracking_status(running: bool, in_slideshow: bool) {
+    if let Some(app) = crate::APP_HANDLE.read().as_ref() {
+        let _ = app.emit(
+            "keynote-tracking",
+            serde_json::json!({
+                "running": running,
+                "in_slideshow": in_slideshow
+            }),
+        );
+    }
+}
+
+#[cfg(target_os = "macos")]
+async fn run_script(script: &str, args: &[&str]) -> Option<String> {
+    let output = tokio::process::Command::new("osascript")
+        .args(["-e", script])
+        .args(args)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(target_os = "macos")]
+async fn run_tracker() {
+    let mut last: Option<KeynoteSlide> = None;
+    // Document whose notes were loaded for the running slideshow
+    let mut notes_loaded_for: Option<String> = None;
+    let mut polls_since_notes = 0;
+    emit_tracking_status(true, false);
+
+    loop {
+        let playing: Vec<&str> = last.iter().map(|s| s.document_id.as_str()).collect();
+        let slide = run_script(SLIDE_SCRIPT, &playing)
+            .await
+            .as_deref()
+            .and_then(parse_slide);
+
+        match (&slide, &last) {
+            (Some(current), previous) => {
+                let pid = presentation_id(&current.document_id);
+                let loaded = notes_loaded_for.as_deref() == Some(current.document_id.as_str());
+                polls_since_notes += 1;
+
+                if !loaded || polls_since_notes >= NOTES_REFRESH_POLLS {
+                    match run_script(NOTES_SCRIPT, &[&current.document_id]).await {
+                        Some(output) => {
+                            let notes = parse_notes(&output);
+                            if loaded {
+                                super::update_deck_notes(&pid, notes);
+                            } else {
+                                super::load_deck_notes(&pid, notes);
+                                emit_tracking_status(true, true);
+                                notes_loaded_for = Some(current.document_id.clone());
+                            }
+                            polls_since_notes = 0;
+                        }
+                        // A deck's first read is retried on the next poll
+                        None if !loaded => {}
+                        None => polls_since_notes = 0,
+                    }
+                }
+
+                if previous.as_ref() != Some(current) {
+                    super::publish_slide(
+                        &pid,
+                        &current.number.to_string(),
+                        current.number,
+                        &current.name,
+                        MODE,
+                    )
+                    .await;
+                }
+            }
+            (None, Some(_)) => {
+                notes_loaded_for = None;
+                emit_tracking_status(true, false);
+            }
+            (None, None) => {}
+        }
+        last = slide;
+
+        tokio::time::sleep(std::time::Duration::from_millis(POLL_INTERVAL_MS)).await;
+    }
+}
+
+/// Follow the slideshow playing in Keynote
+#[tauri::command]
+pub fn start_keynote_tracking() -> Result<(), CueCardError> {
+    #[cfg(target_os = "macos")]
+    {
+        let mut task = TRACKER_TASK.write();
+        if task.is_some() {
+            return Ok(());
+        }
+        *task = Some(tauri::async_runtime::spawn(run_tracker()));
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        Err("Keynote tracking is only available on macOS".into())
+    }
+}
+
+#[tauri::command]
+pub fn stop_keynote_tracking() {
+    if let Some(task) = TRACKER_TASK.write().take() {
+        // Dropping the task kills a running osascript (kill_on_drop)
+        task.abort();
+        emit_tracking_status(false, false);
+    }
+}
+
+#[tauri::command]
+pub fn is_keynote_tracking() -> bool {
+    TRACKER_TASK.read().is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_playing_slide() {
+        assert_eq!(
+            parse_slide("ABC-123\u{1f}Launch.key\u{1f}4\n"),
+            Some(KeynoteSlide {
+                document_id: "ABC-123".to_string(),
+                name: "Launch.key".to_string(),
+                number: 4,
+            })
+        );
+    }
+
+    #[test]
+    fn no_slide_without_a_slideshow() {
+        assert_eq!(parse_slide(""), None);
+        assert_eq!(parse_slide("\n"), None);
+        assert_eq!(parse_slide("\u{1f}Launch.key\u{1f}4"), None);
+        assert_eq!(parse_slide("ABC-123\u{1f}Launch.key\u{1f}four"), None);
+    }
+
+    #[test]
+    fn parses_notes_with_mac_line_endings() {
+        let output = "1\u{1f}Welcome\rEveryone\u{1e}2\u{1f}\u{1e}3\u{1f}Close\u{1e}\n";
+        assert_eq!(
+            parse_notes(output),
+            vec![
+                ("1".to_string(), "Welcome\nEveryone".to_string()),
+                ("2".to_string(), String::new()),
+                ("3".to_string(), "Close".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_records_without_a_slide_number() {
+        assert_eq!(parse_notes(""), Vec::<(String, String)>::new());
+        assert_eq!(
+            parse_notes("x\u{1f}stray\u{1e}2\u{1f}Kept\u{1e}"),
+            vec![("2".to_string(), "Kept".to_string())]
+        );
+    }
+}
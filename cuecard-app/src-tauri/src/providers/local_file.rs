@@ -0,0 +1,338 @@
+//! Notes loaded from a local file
+//!
+//! Text and Markdown files are split into slides on lines containing only
+//! `---` (the reveal.js/Marp convention), or on `## Slide N` (or
+//! `## Page N`) headings when the file has them, each heading starting
+//! slide N with the text under it as its notes; `.pptx` files supply their
+//! speaker notes (`pptx`). Slide position comes from elsewhere: the
+//! presenter-window tracker or an explicit `set_local_slide` call. The file
+//! is watched while loaded, so edits show up without reloading.
+//!
+//! Files opened with CueCard or dropped on the panel go through `open_file`,
+//! which also switches the panel to the first slide.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use regex::Regex;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::Emitter;
+
+use crate::error::CueCardError;
+use crate::APP_HANDLE;
+
+pub const MODE: &str = "local";
+/// Extensions accepted from open-with and drag and drop
+pub const SUPPORTED_EXTENSIONS: &[&str] = &["md", "markdown", "txt", "pptx"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LocalSlide {
+    pub number: i32,
+    pub title: String,
+    pub notes: String,
+    /// Left out of the slideshow (PowerPoint's "Hide Slide")
+    pub hidden: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct LocalDeck {
+    pub path: String,
+    pub name: String,
+    pub slides: Vec<LocalSlide>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LocalDeckSummary {
+    pub presentation_id: String,
+    pub path: String,
+    pub name: String,
+    pub slide_count: usize,
+}
+
+// "## Slide 3" or "## Page 3", optionally followed by a title ("## Slide 3: Pricing")
+static SLIDE_HEADING: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^##\s+(?:slide|page)\s+(\d+)\b[\s:.\-]*(.*)$")
+        .expect("valid slide heading regex")
+});
+
+pub static LOCAL_DECK: Lazy<Arc<RwLock<Option<LocalDeck>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(None)));
+static DECK_WATCHER: Lazy<Arc<RwLock<Option<super::watcher::FileWatcher>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(None)));
+
+pub fn presentation_id(path: &str) -> String {
+    format!("{}:{}", MODE, path)
+}
+
+/// Split text into slides on `## Slide N` headings, or else on `---`
+/// separator lines
+pub fn parse_text_deck(content: &str) -> Vec<LocalSlide> {
+    if let Some(slides) = parse_slide_headings(content) {
+        return slides;
+    }
+
+    let mut chunks: Vec<Vec<&str>> = vec![Vec::new()];
+    for line in content.lines() {
+        if line.trim() == "---" {
+            chunks.push(Vec::new());
+        } else if let Some(chunk) = chunks.last_mut() {
+            chunk.push(line);
+        }
+    }
+
+    chunks
+        .into_iter()
+        .map(|lines| lines.join("\n").trim().to_string())
+        .filter(|text| !text.is_empty())
+        .enumerate()
+        .map(|(i, text)| LocalSlide {
+            number: i as i32 + 1,
+            title: slide_title(&text),
+            notes: text,
+            hidden: false,
+        })
+        .collect()
+}
+
+/// Slides from `## Slide N` headings; text before the first one is left out
+fn parse_slide_headings(content: &str) -> Option<Vec<LocalSlide>> {
+    let mut slides: Vec<(i32, String, Vec<&str>)> = Vec::new();
+    for line in content.lines() {
+        let heading = SLIDE_HEADING.captures(line.trim()).and_then(|caps| {
+            let number = caps[1].parse::<i32>().ok()?;
+            Some((number, caps[2].trim().to_string()))
+        });
+        if let Some((number, title)) = heading {
+            slides.push((number, title, Vec::new()));
+        } else if let Some((_, _, body)) = slides.last_mut() {
+            // Separators between slides aren't notes
+            if line.trim() != "---" {
+                body.push(line);
+            }
+        }
+    }
+    if slides.is_empty() {
+        return None;
+    }
+
+    Some(
+        slides
+            .into_iter()
+            .map(|(number, title, body)| {
+                let notes = body.join("\n").trim().to_string();
+                LocalSlide {
+                    number,
+                    title: if title.is_empty() {
+                        slide_title(&notes)
+                    } else {
+                        title
+                    },
+                    notes,
+                    hidden: false,
+                }
+            })
+            .collect(),
+    )
+}
+
+/// First non-empty line, without Markdown heading markers
+fn slide_title(text: &str) -> String {
+    text.lines()
+        .map(|l| l.trim().trim_start_matches('#').trim())
+        .find(|l| !l.is_empty())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Make a parsed deck the active local source and load its notes into the cache
+pub fn set_local_deck(deck: LocalDeck) -> LocalDeckSummary {
+    let id = presentation_id(&deck.path);
+    super::load_deck_notes(
+        &id,
+        deck.slides
+            .iter()
+            .map(|s| (s.number.to_string(), s.notes.clone())),
+    );
+
+    let summary = LocalDeckSummary {
+        presentation_id: id,
+        path: deck.path.clone(),
+        name: deck.name.clone(),
+        slide_count: deck.slides.len(),
+    };
+    *LOCAL_DECK.write() = Some(deck);
+    summary
+}
+
+/// Show a slide of the loaded local deck
+pub async fn show_local_slide(number: i32) -> Result<Option<String>, String> {
+    let (id, title) = {
+        let deck = LOCAL_DECK.read();
+        let deck = deck.as_ref().ok_or("No local notes file loaded")?;
+        (presentation_id(&deck.path), deck.name.clone())
+    };
+
+    Ok(super::publish_slide(&id, &number.to_string(), number, &title, MODE).await)
+}
+
+fn is_pptx(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("pptx"))
+}
+
+pub fn is_supported(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()).is_some_and(|e| {
+        SUPPORTED_EXTENSIONS
+            .iter()
+            .any(|s| e.eq_ignore_ascii_case(s))
+    })
+}
+
+fn read_deck(path: &Path) -> Result<LocalDeck, String> {
+    let slides = if is_pptx(path) {
+        super::pptx::read_pptx_slides(path)?
+    } else {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read notes file: {}", e))?;
+        parse_text_deck(&content)
+    };
+    if slides.is_empty() {
+        return Err("Notes file is empty".to_string());
+    }
+
+    Ok(LocalDeck {
+        path: path.to_string_lossy().to_string(),
+        name: path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "Local notes".to_string()),
+        slides,
+    })
+}
+
+/// Re-read the loaded file after it changed on disk
+fn reload_local_deck(path: &Path) {
+    let deck = match read_deck(path) {
+        Ok(d) => d,
+        // Mid-save or deleted; keep the last good notes
+        Err(e) => {
+            eprintln!("Keeping previous local notes: {}", e);
+            return;
+        }
+    };
+
+    {
+        let mut current = LOCAL_DECK.write();
+        if current.as_ref().map(|d| d.path.as_str()) != Some(deck.path.as_str()) {
+            return;
+        }
+        *current = Some(deck.clone());
+    }
+
+    super::update_deck_notes(
+        &presentation_id(&deck.path),
+        deck.slides
+            .into_iter()
+            .map(|s| (s.number.to_string(), s.notes)),
+    );
+}
+
+/// Load a file as the local deck and show its first slide
+pub async fn open_file(path: &Path) -> Result<LocalDeckSummary, String> {
+    if !is_supported(path) {
+        return Err(format!(
+            "Unsupported file type: {}",
+            path.file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default()
+        ));
+    }
+
+    let summary = load_local_notes(path.to_string_lossy().to_string())?;
+    show_local_slide(1).await?;
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        let _ = app.emit("local-file-opened", summary.clone());
+    }
+    Ok(summary)
+}
+
+/// Open the first supported file of an open-with or drop event
+pub fn open_paths(paths: Vec<PathBuf>) {
+    let Some(path) = paths.into_iter().find(|p| is_supported(p)) else {
+        return;
+    };
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = open_file(&path).await {
+            eprintln!("Failed to open {}: {}", path.display(), e);
+        }
+    });
+}
+
+#[tauri::command]
+pub fn load_local_notes(path: String) -> Result<LocalDeckSummary, CueCardError> {
+    let path = Path::new(&path);
+    let deck = read_deck(path)?;
+
+    // Watching is best-effort; the notes are usable without it
+    let watcher = match super::watcher::watch_file(path, reload_local_deck) {
+        Ok(w) => Some(w),
+        Err(e) => {
+            eprintln!("Local notes won't update on change: {}", e);
+            None
+        }
+    };
+    *DECK_WATCHER.write() = watcher;
+
+    Ok(set_local_deck(deck))
+}
+
+#[tauri::command]
+pub async fn set_local_slide(number: i32) -> Result<Option<String>, CueCardError> {
+    Ok(show_local_slide(number).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(slides: &[LocalSlide]) -> Vec<(i32, &str, &str)> {
+        slides
+            .iter()
+            .map(|s| (s.number, s.title.as_str(), s.notes.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn splits_on_separator_lines() {
+        let slides = parse_text_deck(
+            "# Welcome\nThanks for coming\n---\n\n---\nPricing\n  ---  \nQuestions\n",
+        );
+        assert_eq!(
+            summary(&slides),
+            vec![
+                (1, "Welcome", "# Welcome\nThanks for coming"),
+                (2, "Pricing", "Pricing"),
+                (3, "Questions", "Questions"),
+            ]
+        );
+    }
+
+    #[test]
+    fn numbers_slides_from_their_headings() {
+        let slides = parse_text_deck(
+            "Preamble isn't a slide\n## Slide 2: Pricing\nThree tiers\n---\n## page 5\nDemo time\n",
+        );
+        assert_eq!(
+            summary(&slides),
+            vec![(2, "Pricing", "Three tiers"), (5, "Demo time", "Demo time")]
+        );
+    }
+
+    #[test]
+    fn an_empty_file_has_no_slides() {
+        assert!(parse_text_deck("").is_empty());
+        assert!(parse_text_deck("---\n---\n").is_empty());
+    }
+}
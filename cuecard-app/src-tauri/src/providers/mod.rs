@@ -0,0 +1,217 @@
+//! Notes providers that don't go through the browser extension
+//!
+//! Each provider reads slide position (and usually notes) from a local source
+//! and feeds it into the same `CURRENT_SLIDE` / `SLIDE_NOTES` / `slide-update`
+//! pipeline the Google Slides extension uses.
+
+pub mod accessibility;
+pub mod keynote;
+pub mod local_file;
+pub mod pdf;
+pub mod powerpoint;
+pub mod pptx;
+pub mod watcher;
+
+use serde::Serialize;
+use std::collections::HashMap;
+use tauri::Emitter;
+
+use crate::{apply_slide_update, SlideData, APP_HANDLE, CURRENT_PRESENTATION_ID, SLIDE_NOTES};
+
+/// Slides whose notes changed after a source was re-read
+#[derive(Debug, Clone, Serialize)]
+pub struct NotesChangedEvent {
+    pub presentation_id: String,
+    pub slide_ids: Vec<String>,
+}
+
+/// Replace the notes cache with a whole deck read from a local source
+pub fn load_deck_notes<I>(presentation_id: &str, notes: I)
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+    {
+        let mut current_pres = CURRENT_PRESENTATION_ID.write();
+        *current_pres = Some(presentation_id.to_string());
+    }
+
+    let mut order = Vec::new();
+    {
+        let mut notes_cache = SLIDE_NOTES.write();
+        notes_cache.clear();
+        for (slide_id, text) in notes {
+            let text = crate::notes_pipeline::process(text.trim());
+            if !text.is_empty() {
+                notes_cache.insert(format!("{}:{}", presentation_id, slide_id), text);
+            }
+            order.push(slide_id);
+        }
+    }
+    crate::set_slide_order(presentation_id, order);
+}
+
+/// Apply re-read notes for a deck, emitting `notes-changed` for the slides that differ.
+/// Only touches the cache when the deck is the current presentation.
+pub fn update_deck_notes<I>(presentation_id: &str, notes: I) -> Vec<String>
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+    if CURRENT_PRESENTATION_ID.read().as_deref() != Some(presentation_id) {
+        return Vec::new();
+    }
+
+    let prefix = format!("{}:", presentation_id);
+    let notes: Vec<(String, String)> = notes.into_iter().collect();
+    crate::set_slide_order(
+        presentation_id,
+        notes.iter().map(|(slide_id, _)| slide_id.clone()).collect(),
+    );
+    let fresh: HashMap<String, String> = notes
+        .into_iter()
+        .map(|(slide_id, text)| (slide_id, crate::notes_pipeline::process(text.trim())))
+        .filter(|(_, text)| !text.is_empty())
+        .collect();
+
+    let changed = replace_notes(&mut SLIDE_NOTES.write(), &prefix, fresh);
+    if changed.is_empty() {
+        return changed;
+    }
+
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        let _ = app.emit(
+            "notes-changed",
+            NotesChangedEvent {
+                presentation_id: presentation_id.to_string(),
+                slide_ids: changed.clone(),
+            },
+        );
+    }
+
+    let current_slide_id = crate::CURRENT_SLIDE
+        .read()
+        .as_ref()
+        .map(|s| s.slide_id.clone());
+    if current_slide_id.is_some_and(|id| changed.contains(&id)) {
+        crate::reemit_current_slide(presentation_id, None);
+    }
+
+    changed
+}
+
+/// Swap in a deck's fresh notes under `prefix`; the slides that changed, sorted
+fn replace_notes(
+    notes_cache: &mut HashMap<String, String>,
+    prefix: &str,
+    fresh: HashMap<String, String>,
+) -> Vec<String> {
+    let mut changed = Vec::new();
+    let stale: Vec<String> = notes_cache
+        .keys()
+        .filter_map(|k| k.strip_prefix(prefix))
+        .filter(|slide_id| !fresh.contains_key(*slide_id))
+        .map(|slide_id| slide_id.to_string())
+        .collect();
+    for slide_id in stale {
+        notes_cache.remove(&format!("{}{}", prefix, slide_id));
+        changed.push(slide_id);
+    }
+
+    for (slide_id, text) in fresh {
+        let key = format!("{}{}", prefix, slide_id);
+        if notes_cache.get(&key) != Some(&text) {
+            notes_cache.insert(key, text);
+            changed.push(slide_id);
+        }
+    }
+    changed.sort();
+    changed
+}
+
+/// Feed a slide change from a local provider through the standard pipeline
+pub async fn publish_slide(
+    presentation_id: &str,
+    slide_id: &str,
+    slide_number: i32,
+    title: &str,
+    mode: &str,
+) -> Option<String> {
+    let slide_data = SlideData {
+        presentation_id: presentation_id.to_string(),
+        slide_id: slide_id.to_string(),
+        slide_number,
+        title: title.to_string(),
+        mode: mode.to_string(),
+        timestamp: chrono::Utc::now().timestamp_millis(),
+        url: String::new(),
+        force_refresh: None,
+        client_id: None,
+        scraped_notes: None,
+    };
+
+    apply_slide_update(slide_data, None).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SLIDE_ORDER;
+
+    #[test]
+    fn re_reads_report_the_slides_that_changed() {
+        let entry = |key: &str, text: &str| (key.to_string(), text.to_string());
+        let mut cache: HashMap<String, String> = [
+            entry("local:talk.md:1", "Intro"),
+            entry("local:talk.md:2", "Agenda"),
+            entry("local:talk.md:3", "Numbers"),
+            entry("other:1", "Another deck"),
+        ]
+        .into_iter()
+        .collect();
+        let fresh = [
+            entry("1", "Intro"),
+            entry("2", "Shorter agenda"),
+            entry("4", "Q&A"),
+        ]
+        .into_iter()
+        .collect();
+
+        let changed = replace_notes(&mut cache, "local:talk.md:", fresh);
+        assert_eq!(changed, vec!["2", "3", "4"]);
+        assert_eq!(cache.len(), 4);
+        assert!(!cache.contains_key("local:talk.md:3"));
+        assert_eq!(cache["local:talk.md:2"], "Shorter agenda");
+        assert_eq!(cache["other:1"], "Another deck");
+    }
+
+    #[test]
+    fn loads_a_whole_deck_in_place_of_the_last() {
+        let _deck = crate::tests::DECK_LOCK.lock();
+        SLIDE_NOTES
+            .write()
+            .insert("earlier-deck:p1".to_string(), "Earlier".to_string());
+
+        let deck = "powerpoint:C:\\Talks\\launch.pptx";
+        load_deck_notes(
+            deck,
+            vec![
+                ("256".to_string(), "  Open with the story \n".to_string()),
+                ("257".to_string(), "   ".to_string()),
+                ("258".to_string(), "Thank the team".to_string()),
+            ],
+        );
+
+        assert_eq!(CURRENT_PRESENTATION_ID.read().as_deref(), Some(deck));
+        assert_eq!(*SLIDE_ORDER.read(), vec!["256", "257", "258"]);
+        let notes = SLIDE_NOTES.read().clone();
+        assert_eq!(notes.len(), 2);
+        assert_eq!(
+            notes.get(&format!("{}:256", deck)).map(String::as_str),
+            Some("Open with the story")
+        );
+        assert!(!notes.contains_key(&format!("{}:257", deck)));
+
+        SLIDE_NOTES.write().clear();
+        SLIDE_ORDER.write().clear();
+        *CURRENT_PRESENTATION_ID.write() = None;
+    }
+}
@@ -0,0 +1,280 @@
+//! PDF decks with their notes in a separate file
+//!
+//! Conferences often accept only a PDF of the slides, which carries no
+//! speaker notes. `load_pdf_deck` pairs the PDF with a notes file, split on
+//! `## Page N` (or `## Slide N`) headings or `---` lines as local notes
+//! files are (`local_file`), and keys the notes by page number. The page
+//! shown comes from `set_current_page`, or from a slide update posted to
+//! `/slides` with `mode: "pdf"` and the page as the slide number; the browser
+//! extension doesn't send those, so that's for other clients. The page count
+//! is the page tree's `/Count`, found from the trailer's `/Root`. The notes
+//! file is watched while loaded.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use regex::bytes::Regex;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Arc;
+
+use super::local_file::{self, LocalSlide};
+use crate::error::CueCardError;
+use crate::{SlideData, CURRENT_PRESENTATION_ID};
+
+/// Presentation mode reported for pages of a PDF deck
+pub const MODE: &str = "pdf";
+
+static ROOT_REF: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"/Root\s+(\d+)\s+(\d+)\s+R").expect("valid root regex"));
+static PAGES_REF: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"/Pages\s+(\d+)\s+(\d+)\s+R").expect("valid pages regex"));
+static PAGE_COUNT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"/Count\s+(\d+)").expect("valid count regex"));
+
+#[derive(Debug, Clone)]
+struct PdfDeck {
+    pdf_path: String,
+    notes_path: String,
+    name: String,
+    page_count: usize,
+    slides: Vec<LocalSlide>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PdfDeckSummary {
+    pub presentation_id: String,
+    pub pdf_path: String,
+    pub notes_path: String,
+    pub name: String,
+    pub page_count: usize,
+    pub pages_with_notes: usize,
+}
+
+static PDF_DECK: Lazy<Arc<RwLock<Option<PdfDeck>>>> = Lazy::new(|| Arc::new(RwLock::new(None)));
+static NOTES_WATCHER: Lazy<Arc<RwLock<Option<super::watcher::FileWatcher>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(None)));
+
+fn presentation_id(pdf_path: &str) -> String {
+    format!("{}:{}", MODE, pdf_path)
+}
+
+/// Body of object `number generation`, its latest definition when an
+/// incremental update redefined it
+fn object_body<'a>(bytes: &'a [u8], number: &[u8], generation: &[u8]) -> Option<&'a [u8]> {
+    let header = format!(
+        r"(?:^|[^0-9]){}\s+{}\s+obj\b",
+        std::str::from_utf8(number).ok()?,
+        std::str::from_utf8(generation).ok()?
+    );
+    let start = Regex::new(&header).ok()?.find_iter(bytes).last()?.end();
+    let body = &bytes[start..];
+    let end = body
+        .windows(6)
+        .position(|w| w == b"endobj")
+        .unwrap_or(body.len());
+    Some(&body[..end])
+}
+
+/// `/Count` of the page tree's root, reached from the last trailer's
+/// `/Root`; `None` when the catalog or page tree sits in a compressed object
+/// stream and can't be read without decompressing
+fn page_tree_count(bytes: &[u8]) -> Option<usize> {
+    let root = ROOT_REF.captures_iter(bytes).last()?;
+    let catalog = object_body(bytes, &root[1], &root[2])?;
+    let pages = PAGES_REF.captures(catalog)?;
+    let tree = object_body(bytes, &pages[1], &pages[2])?;
+    let count = PAGE_COUNT.captures(tree)?;
+    std::str::from_utf8(&count[1]).ok()?.parse().ok()
+}
+
+/// Pages in the PDF, or `None` when they can't be counted
+fn count_pages(pdf_path: &Path) -> Result<Option<usize>, String> {
+    let bytes = std::fs::read(pdf_path).map_err(|e| format!("Failed to read PDF: {}", e))?;
+    if !bytes.starts_with(b"%PDF") {
+        return Err("Not a PDF file".to_string());
+    }
+    Ok(page_tree_count(&bytes).filter(|pages| *pages > 0))
+}
+
+fn read_notes(notes_path: &Path) -> Result<Vec<LocalSlide>, String> {
+    let content = std::fs::read_to_string(notes_path)
+        .map_err(|e| format!("Failed to read notes file: {}", e))?;
+    let slides = local_file::parse_text_deck(&content);
+    if slides.is_empty() {
+        return Err("Notes file is empty".to_string());
+    }
+    Ok(slides)
+}
+
+/// Every page in order, with its notes or none
+fn page_notes(deck: &PdfDeck) -> Vec<(String, String)> {
+    (1..=deck.page_count as i32)
+        .map(|page| {
+            let notes = deck
+                .slides
+                .iter()
+                .find(|s| s.number == page)
+                .map(|s| s.notes.clone())
+                .unwrap_or_default();
+            (page.to_string(), notes)
+        })
+        .collect()
+}
+
+fn summary(deck: &PdfDeck) -> PdfDeckSummary {
+    PdfDeckSummary {
+        presentation_id: presentation_id(&deck.pdf_path),
+        pdf_path: deck.pdf_path.clone(),
+        notes_path: deck.notes_path.clone(),
+        name: deck.name.clone(),
+        page_count: deck.page_count,
+        pages_with_notes: deck.slides.iter().filter(|s| !s.notes.is_empty()).count(),
+    }
+}
+
+/// Re-read the notes file after it changed on disk
+fn reload_notes(path: &Path) {
+    let slides = match read_notes(path) {
+        Ok(s) => s,
+        // Mid-save or deleted; keep the last good notes
+        Err(e) => {
+            eprintln!("Keeping previous PDF notes: {}", e);
+            return;
+        }
+    };
+
+    let deck = {
+        let mut current = PDF_DECK.write();
+        let Some(deck) = current
+            .as_mut()
+            .filter(|d| Path::new(&d.notes_path) == path)
+        else {
+            return;
+        };
+        let last_page = slides.iter().map(|s| s.number.max(0) as usize).max();
+        deck.page_count = deck.page_count.max(last_page.unwrap_or(0));
+        deck.slides = slides;
+        deck.clone()
+    };
+
+    super::update_deck_notes(&presentation_id(&deck.pdf_path), page_notes(&deck));
+}
+
+/// Load the deck's notes again if another presentation was shown since
+fn ensure_current(deck: &PdfDeck) -> String {
+    let id = presentation_id(&deck.pdf_path);
+    if CURRENT_PRESENTATION_ID.read().as_deref() != Some(id.as_str()) {
+        super::load_deck_notes(&id, page_notes(deck));
+    }
+    id
+}
+
+/// A page change posted to `/slides`, placed on the loaded PDF by its number
+pub async fn on_slide_update(mut slide_data: SlideData) -> Option<String> {
+    let deck = PDF_DECK.read().clone();
+    if let Some(deck) = deck {
+        slide_data.presentation_id = ensure_current(&deck);
+        slide_data.slide_id = slide_data.slide_number.to_string();
+        if slide_data.title.is_empty() {
+            slide_data.title = deck.name;
+        }
+    }
+    crate::apply_slide_update(slide_data, None).await
+}
+
+/// Pair a PDF with the file holding its notes
+#[tauri::command]
+pub fn load_pdf_deck(pdf_path: String, notes_path: String) -> Result<PdfDeckSummary, CueCardError> {
+    let pdf = Path::new(&pdf_path);
+    let notes = Path::new(&notes_path);
+    let pages = count_pages(pdf)?;
+    let slides = read_notes(notes)?;
+    let last_page = slides.iter().map(|s| s.number.max(0) as usize).max();
+
+    let deck = PdfDeck {
+        pdf_path: pdf.to_string_lossy().to_string(),
+        notes_path: notes.to_string_lossy().to_string(),
+        name: pdf
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "PDF".to_string()),
+        page_count: pages.unwrap_or(0).max(last_page.unwrap_or(0)),
+        slides,
+    };
+    super::load_deck_notes(&presentation_id(&deck.pdf_path), page_notes(&deck));
+
+    // Watching is best-effort; the notes are usable without it
+    let watcher = match super::watcher::watch_file(notes, reload_notes) {
+        Ok(w) => Some(w),
+        Err(e) => {
+            eprintln!("PDF notes won't update on change: {}", e);
+            None
+        }
+    };
+    *NOTES_WATCHER.write() = watcher;
+
+    let summary = summary(&deck);
+    *PDF_DECK.write() = Some(deck);
+    Ok(summary)
+}
+
+/// Show the notes for a page (1-based) of the loaded PDF
+#[tauri::command]
+pub async fn set_current_page(page: i32) -> Result<Option<String>, CueCardError> {
+    let deck = PDF_DECK.read().clone().ok_or("No PDF deck loaded")?;
+    if page < 1 || page as usize > deck.page_count {
+        return Err(format!("The PDF has no page {}", page).into());
+    }
+    let id = ensure_current(&deck);
+    Ok(super::publish_slide(&id, &page.to_string(), page, &deck.name, MODE).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TWO_PAGES: &[u8] = b"%PDF-1.4
+1 0 obj << /Type /Catalog /Pages 2 0 R >> endobj
+2 0 obj << /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 >> endobj
+3 0 obj << /Type /Page /Parent 2 0 R >> endobj
+4 0 obj << /Type /Page /Parent 2 0 R >> endobj
+5 0 obj << /Type /Page /Parent 2 0 R >> endobj
+trailer << /Root 1 0 R /Size 6 >>
+%%EOF";
+
+    #[test]
+    fn counts_from_the_page_tree() {
+        // The orphaned page object 5 isn't in the tree
+        assert_eq!(page_tree_count(TWO_PAGES), Some(2));
+    }
+
+    #[test]
+    fn follows_an_incremental_update() {
+        let mut bytes = TWO_PAGES.to_vec();
+        bytes.extend_from_slice(
+            b"
+2 0 obj << /Type /Pages /Kids [3 0 R 4 0 R 5 0 R] /Count 3 >> endobj
+trailer << /Root 1 0 R /Size 6 /Prev 9 >>
+%%EOF",
+        );
+        assert_eq!(page_tree_count(&bytes), Some(3));
+    }
+
+    #[test]
+    fn object_numbers_match_whole() {
+        let bytes = b"%PDF-1.4
+11 0 obj << /Type /Pages /Count 9 >> endobj
+1 0 obj << /Type /Catalog /Pages 21 0 R >> endobj
+21 0 obj << /Type /Pages /Count 4 >> endobj
+trailer << /Root 1 0 R >>";
+        assert_eq!(page_tree_count(bytes), Some(4));
+    }
+
+    #[test]
+    fn none_without_a_readable_catalog() {
+        let bytes = b"%PDF-1.5
+7 0 obj << /Type /XRef /Root 1 0 R /Filter /FlateDecode >> stream
+endstream endobj";
+        assert_eq!(page_tree_count(bytes), None);
+    }
+}
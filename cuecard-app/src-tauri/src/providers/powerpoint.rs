@@ -0,0 +1,368 @@
+//! PowerPoint desktop slideshow tracking (Windows) and `.pptx` notes for
+//! PowerPoint slide updates
+//!
+//! A long-running PowerShell process attaches to the running PowerPoint
+//! instance through COM automation and prints one JSON line per change: the
+//! deck's notes when a slideshow starts, then the active slide as it moves.
+//!
+//! Slide updates posted to `/slides` with `mode: "powerpoint"` carry only
+//! the slide number. The notes then come from the deck's `.pptx`, loaded with
+//! `load_pptx_notes` and keyed by slide number; updates are placed on the
+//! loaded deck by their number. The file is watched while loaded. Neither
+//! the browser extension nor any Office add-in sends such updates yet: this
+//! is the app side for a client that reports PowerPoint for the web.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+#[cfg(target_os = "windows")]
+use serde::Deserialize;
+use std::path::Path;
+use std::sync::Arc;
+use tauri::Emitter;
+
+use super::local_file::{LocalDeck, LocalDeckSummary};
+use crate::error::CueCardError;
+use crate::{SlideData, CURRENT_PRESENTATION_ID};
+
+/// Presentation modes reported for slides coming from this provider
+pub const MODE: &str = "powerpoint";
+
+#[cfg(target_os = "windows")]
+#[derive(Debug, Deserialize)]
+struct PowerPointSlide {
+    id: i64,
+    #[serde(default)]
+    notes: String,
+}
+
+#[cfg(target_os = "windows")]
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum PowerPointMessage {
+    Deck {
+        path: String,
+        slides: Vec<PowerPointSlide>,
+    },
+    Slide {
+        path: String,
+        name: String,
+        index: i32,
+        id: i64,
+    },
+    Ended,
+}
+
+static TRACKER_TASK: Lazy<Arc<RwLock<Option<tauri::async_runtime::JoinHandle<()>>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(None)));
+// The `.pptx` slide updates are placed on
+static PPTX_DECK: Lazy<Arc<RwLock<Option<LocalDeck>>>> = Lazy::new(|| Arc::new(RwLock::new(None)));
+static PPTX_WATCHER: Lazy<Arc<RwLock<Option<super::watcher::FileWatcher>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(None)));
+
+#[cfg(target_os = "windows")]
+const TRACKER_SCRIPT: &str = r#"
+$ErrorActionPreference = 'SilentlyContinue'
+$lastDeck = ''
+$lastKey = ''
+while ($true) {
+  $app = $null
+  try { $app = [Runtime.InteropServices.Marshal]::GetActiveObject('PowerPoint.Application') } catch {}
+  if ($app -and $app.SlideShowWindows.Count -gt 0) {
+    $window = $app.SlideShowWindows.Item(1)
+    $pres = $window.Presentation
+    if ($pres.FullName -ne $lastDeck) {
+      $lastDeck = $pres.FullName
+      $slides = @()
+      foreach ($s in $pres.Slides) {
+        $notes = ''
+        try { $notes = $s.NotesPage.Shapes.Placeholders.Item(2).TextFrame.TextRange.Text } catch {}
+        $slides += @{ id = $s.SlideID; notes = $notes }
+      }
+      @{ type = 'deck'; path = $pres.FullName; slides = $slides } | ConvertTo-Json -Compress -Depth 4
+    }
+    $slide = $window.View.Slide
+    if ($slide) {
+      $key = "$($pres.FullName)|$($slide.SlideIndex)"
+      if ($key -ne $lastKey) {
+        $lastKey = $key
+        @{ type = 'slide'; path = $pres.FullName; name = $pres.Name; index = $slide.SlideIndex; id = $slide.SlideID } | ConvertTo-Json -Compress
+      }
+    }
+  } elseif ($lastDeck -ne '') {
+    $lastDeck = ''
+    $lastKey = ''
+    @{ type = 'ended' } | ConvertTo-Json -Compress
+  }
+  [Console]::Out.Flush()
+  Start-Sleep -Milliseconds 400
+}
+"#;
+
+fn presentation_id(path: &str) -> String {
+    format!("{}:{}", MODE, path)
+}
+
+#[cfg(target_os = "windows")]
+async fn handle_message(message: PowerPointMessage) {
+    match message {
+        PowerPointMessage::Deck { path, slides } => {
+            super::load_deck_notes(
+                &presentation_id(&path),
+                slides.into_iter().map(|s| (s.id.to_string(), s.notes)),
+            );
+        }
+        PowerPointMessage::Slide {
+            path,
+            name,
+            index,
+            id,
+        } => {
+            super::publish_slide(&presentation_id(&path), &id.to_string(), index, &name, MODE)
+                .await;
+        }
+        PowerPointMessage::Ended => {
+            emit_tracking_status(true, false);
+        }
+    }
+}
+
+fn emit_tracking_status(running: bool, in_slideshow: bool) {
+    if let Some(app) = crate::APP_HANDLE.read().as_ref() {
+        let _ = app.emit(
+            "powerpoint-tracking",
+            serde_json::json!({
+                "running": running,
+                "in_slideshow": in_slideshow
+            }),
+        );
+    }
+}
+
+#[cfg(target_os = "windows")]
+async fn run_tracker() {
+    use base64::Engine;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    // -EncodedCommand takes base64 of the UTF-16LE script
+    let utf16: Vec<u8> = TRACKER_SCRIPT
+        .encode_utf16()
+        .flat_map(|unit| unit.to_le_bytes())
+        .collect();
+    let encoded = base64::engine::general_purpose::STANDARD.encode(utf16);
+
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    let child = tokio::process::Command::new("powershell.exe")
+        .args(["-NoProfile", "-NonInteractive", "-EncodedCommand", &encoded])
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .creation_flags(CREATE_NO_WINDOW)
+        .kill_on_drop(true)
+        .spawn();
+
+    let mut child = match child {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("Failed to start PowerPoint tracker: {}", e);
+            emit_tracking_status(false, false);
+            return;
+        }
+    };
+
+    let stdout = match child.stdout.take() {
+        Some(s) => s,
+        None => return,
+    };
+    emit_tracking_status(true, false);
+
+    let mut lines = BufReader::new(stdout).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        match serde_json::from_str::<PowerPointMessage>(line.trim()) {
+            Ok(message) => {
+                let in_slideshow = !matches!(message, PowerPointMessage::Ended);
+                handle_message(message).await;
+                if in_slideshow {
+                    emit_tracking_status(true, true);
+                }
+            }
+            Err(e) => eprintln!("Unreadable PowerPoint tracker output: {}", e),
+        }
+    }
+
+    eprintln!("PowerPoint tracker exited");
+    TRACKER_TASK.write().take();
+    emit_tracking_status(false, false);
+}
+
+/// Follow the slideshow running in desktop PowerPoint
+#[tauri::command]
+pub fn start_powerpoint_tracking() -> Result<(), CueCardError> {
+    #[cfg(target_os = "windows")]
+    {
+        let mut task = TRACKER_TASK.write();
+        if task.is_some() {
+            return Ok(());
+        }
+        *task = Some(tauri::async_runtime::spawn(run_tracker()));
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        Err("PowerPoint tracking is only available on Windows".into())
+    }
+}
+
+#[tauri::command]
+pub fn stop_powerpoint_tracking() {
+    if let Some(task) = TRACKER_TASK.write().take() {
+        // Dropping the task kills the PowerShell child (kill_on_drop)
+        task.abort();
+        emit_tracking_status(false, false);
+    }
+}
+
+#[tauri::command]
+pub fn is_powerpoint_tracking() -> bool {
+    TRACKER_TASK.read().is_some()
+}
+
+fn read_pptx(path: &Path) -> Result<LocalDeck, String> {
+    let is_pptx = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("pptx"));
+    if !is_pptx {
+        return Err("Not a .pptx file".to_string());
+    }
+    let slides = super::pptx::read_pptx_slides(path)?;
+    if slides.is_empty() {
+        return Err("The presentation has no slides".to_string());
+    }
+
+    Ok(LocalDeck {
+        path: path.to_string_lossy().to_string(),
+        name: path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "PowerPoint".to_string()),
+        slides,
+    })
+}
+
+fn slide_notes(deck: &LocalDeck) -> impl Iterator<Item = (String, String)> + '_ {
+    deck.slides
+        .iter()
+        .map(|s| (s.number.to_string(), s.notes.clone()))
+}
+
+/// Re-read the loaded `.pptx` after it changed on disk
+fn reload_pptx(path: &Path) {
+    let deck = match read_pptx(path) {
+        Ok(d) => d,
+        // Mid-save or deleted; keep the last good notes
+        Err(e) => {
+            eprintln!("Keeping previous PowerPoint notes: {}", e);
+            return;
+        }
+    };
+
+    {
+        let mut current = PPTX_DECK.write();
+        if current.as_ref().map(|d| d.path.as_str()) != Some(deck.path.as_str()) {
+            return;
+        }
+        *current = Some(deck.clone());
+    }
+
+    super::update_deck_notes(&presentation_id(&deck.path), slide_notes(&deck));
+}
+
+/// A PowerPoint slide update posted to `/slides`, placed on the loaded
+/// `.pptx` by its number
+pub async fn on_slide_update(mut slide_data: SlideData) -> Option<String> {
+    let deck = PPTX_DECK.read().clone();
+    if let Some(deck) = deck {
+        let id = presentation_id(&deck.path);
+        // Another deck was shown since; its switch cleared these notes
+        if CURRENT_PRESENTATION_ID.read().as_deref() != Some(id.as_str()) {
+            super::load_deck_notes(&id, slide_notes(&deck));
+        }
+        slide_data.presentation_id = id;
+        slide_data.slide_id = slide_data.slide_number.to_string();
+        if slide_data.title.is_empty() {
+            slide_data.title = deck.name;
+        }
+    }
+    crate::apply_slide_update(slide_data, None).await
+}
+
+/// Read the speaker notes of a `.pptx` for PowerPoint slide updates
+#[tauri::command]
+pub fn load_pptx_notes(path: String) -> Result<LocalDeckSummary, CueCardError> {
+    let path = Path::new(&path);
+    let deck = read_pptx(path)?;
+    let id = presentation_id(&deck.path);
+    super::load_deck_notes(&id, slide_notes(&deck));
+
+    // Watching is best-effort; the notes are usable without it
+    let watcher = match super::watcher::watch_file(path, reload_pptx) {
+        Ok(w) => Some(w),
+        Err(e) => {
+            eprintln!("PowerPoint notes won't update on change: {}", e);
+            None
+        }
+    };
+    *PPTX_WATCHER.write() = watcher;
+
+    let summary = LocalDeckSummary {
+        presentation_id: id,
+        path: deck.path.clone(),
+        name: deck.name.clone(),
+        slide_count: deck.slides.len(),
+    };
+    *PPTX_DECK.write() = Some(deck);
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decks_are_keyed_by_their_path() {
+        assert_eq!(
+            presentation_id("C:\\Talks\\launch.pptx"),
+            "powerpoint:C:\\Talks\\launch.pptx"
+        );
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn reads_the_trackers_lines() {
+        let deck = r#"{"type":"deck","path":"C:\\Talks\\launch.pptx","slides":[{"id":256,"notes":"Open with the story"},{"id":257}]}"#;
+        match serde_json::from_str::<PowerPointMessage>(deck).unwrap() {
+            PowerPointMessage::Deck { path, slides } => {
+                assert_eq!(path, "C:\\Talks\\launch.pptx");
+                assert_eq!(slides.len(), 2);
+                assert_eq!(slides[1].id, 257);
+                assert_eq!(slides[1].notes, "");
+            }
+            other => panic!("read as {:?}", other),
+        }
+
+        let slide = r#"{"type":"slide","path":"C:\\Talks\\launch.pptx","name":"launch.pptx","index":2,"id":257}"#;
+        assert!(matches!(
+            serde_json::from_str::<PowerPointMessage>(slide).unwrap(),
+            PowerPointMessage::Slide {
+                index: 2,
+                id: 257,
+                ..
+            }
+        ));
+        assert!(matches!(
+            serde_json::from_str::<PowerPointMessage>(r#"{"type":"ended"}"#).unwrap(),
+            PowerPointMessage::Ended
+        ));
+        assert!(serde_json::from_str::<PowerPointMessage>(r#"{"type":"other"}"#).is_err());
+    }
+}
@@ -0,0 +1,287 @@
+//! Speaker notes read straight from a `.pptx` file
+//!
+//! A `.pptx` is a zip of XML parts. Slide order comes from
+//! `ppt/presentation.xml`; each slide's relationships point at its notes
+//! slide, whose body placeholder holds the notes text. Slides without notes
+//! are kept so numbering matches the deck, and hidden ones (`show="0"`) are
+//! kept but marked.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+use std::io::{Read, Seek};
+use std::path::Path;
+
+use super::local_file::LocalSlide;
+
+static ATTRIBUTE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"([\w:]+)="([^"]*)""#).expect("valid attribute regex"));
+static RELATIONSHIP: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"<Relationship\b[^>]*>").expect("valid relationship regex"));
+static SLIDE_ROOT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"<p:sld\b[^>]*>").expect("valid slide root regex"));
+static SLIDE_ID: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"<p:sldId\b[^>]*>").expect("valid slide id regex"));
+static SHAPE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?s)<p:sp>.*?</p:sp>").expect("valid shape regex"));
+static PLACEHOLDER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"<p:ph\b[^>]*\btype="([^"]*)""#).expect("valid placeholder regex"));
+static PARAGRAPH: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?s)<a:p>.*?</a:p>").expect("valid paragraph regex"));
+static RUN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?s)<a:t>(.*?)</a:t>|<a:br/>").expect("valid run regex"));
+
+fn read_part<R: Read + Seek>(archive: &mut zip::ZipArchive<R>, name: &str) -> Option<String> {
+    let mut file = archive.by_name(name).ok()?;
+    let mut text = String::new();
+    file.read_to_string(&mut text).ok()?;
+    Some(text)
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    ATTRIBUTE
+        .captures_iter(tag)
+        .find(|caps| &caps[1] == name)
+        .map(|caps| caps[2].to_string())
+}
+
+/// Relationship id -> (type, target) from a `.rels` part
+fn relationships(xml: &str) -> HashMap<String, (String, String)> {
+    RELATIONSHIP
+        .find_iter(xml)
+        .filter_map(|m| {
+            let tag = m.as_str();
+            Some((
+                attribute(tag, "Id")?,
+                (attribute(tag, "Type")?, attribute(tag, "Target")?),
+            ))
+        })
+        .collect()
+}
+
+/// Resolve a relationship target against the folder of the part it came from
+fn resolve_part(base_dir: &str, target: &str) -> String {
+    if let Some(absolute) = target.strip_prefix('/') {
+        return absolute.to_string();
+    }
+    let mut parts: Vec<&str> = base_dir.split('/').filter(|p| !p.is_empty()).collect();
+    for segment in target.split('/') {
+        match segment {
+            ".." => {
+                parts.pop();
+            }
+            "." | "" => {}
+            s => parts.push(s),
+        }
+    }
+    parts.join("/")
+}
+
+fn rels_path(part: &str) -> String {
+    match part.rsplit_once('/') {
+        Some((dir, file)) => format!("{}/_rels/{}.rels", dir, file),
+        None => format!("_rels/{}.rels", part),
+    }
+}
+
+/// Text of the first shape whose placeholder type is one of `types`, one line per paragraph
+fn placeholder_text(xml: &str, types: &[&str]) -> Option<String> {
+    let body = SHAPE.find_iter(xml).map(|m| m.as_str()).find(|sp| {
+        PLACEHOLDER
+            .captures(sp)
+            .is_some_and(|caps| types.contains(&&caps[1]))
+    })?;
+
+    let lines: Vec<String> = PARAGRAPH
+        .find_iter(body)
+        .map(|p| {
+            RUN.captures_iter(p.as_str())
+                .map(|caps| match caps.get(1) {
+                    Some(text) => unescape_xml(text.as_str()),
+                    None => "\n".to_string(),
+                })
+                .collect()
+        })
+        .collect();
+    Some(lines.join("\n").trim().to_string())
+}
+
+pub fn read_pptx_slides(path: &Path) -> Result<Vec<LocalSlide>, String> {
+    let file = std::fs::File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+    read_slides(file)
+}
+
+/// Slides of a `.pptx` held in memory, such as a downloaded export
+pub fn read_pptx_bytes(bytes: &[u8]) -> Result<Vec<LocalSlide>, String> {
+    read_slides(std::io::Cursor::new(bytes))
+}
+
+fn read_slides<R: Read + Seek>(reader: R) -> Result<Vec<LocalSlide>, String> {
+    let mut archive =
+        zip::ZipArchive::new(reader).map_err(|e| format!("Not a valid .pptx file: {}", e))?;
+
+    let presentation =
+        read_part(&mut archive, "ppt/presentation.xml").ok_or("Not a valid .pptx file")?;
+    let presentation_rels = relationships(
+        &read_part(&mut archive, "ppt/_rels/presentation.xml.rels").unwrap_or_default(),
+    );
+
+    let slide_parts: Vec<String> = SLIDE_ID
+        .find_iter(&presentation)
+        .filter_map(|m| attribute(m.as_str(), "r:id"))
+        .filter_map(|rid| presentation_rels.get(&rid))
+        .map(|(_, target)| resolve_part("ppt", target))
+        .collect();
+
+    let mut slides = Vec::with_capacity(slide_parts.len());
+    for (i, part) in slide_parts.iter().enumerate() {
+        let number = i as i32 + 1;
+        let slide_dir = part.rsplit_once('/').map_or("", |(dir, _)| dir);
+
+        let slide_xml = read_part(&mut archive, part);
+        let hidden = slide_xml
+            .as_deref()
+            .and_then(|xml| SLIDE_ROOT.find(xml))
+            .and_then(|root| attribute(root.as_str(), "show"))
+            .is_some_and(|show| show == "0" || show == "false");
+        let title = slide_xml
+            .as_deref()
+            .and_then(|xml| placeholder_text(xml, &["title", "ctrTitle"]))
+            .map(|t| t.replace('\n', " "))
+            .filter(|t| !t.is_empty())
+            .unwrap_or_else(|| format!("Slide {}", number));
+
+        let notes_part = read_part(&mut archive, &rels_path(part)).and_then(|rels| {
+            relationships(&rels)
+                .into_values()
+                .find(|(kind, _)| kind.ends_with("/notesSlide"))
+                .map(|(_, target)| resolve_part(slide_dir, &target))
+        });
+        let notes = notes_part
+            .and_then(|p| read_part(&mut archive, &p))
+            .and_then(|xml| placeholder_text(&xml, &["body"]))
+            .unwrap_or_default();
+
+        slides.push(LocalSlide {
+            number,
+            title,
+            notes,
+            hidden,
+        });
+    }
+
+    if slides.is_empty() {
+        return Err("Presentation has no slides".to_string());
+    }
+    Ok(slides)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn slide_xml(title: &str, show: Option<&str>) -> String {
+        let show = show
+            .map(|s| format!(r#" show="{}""#, s))
+            .unwrap_or_default();
+        format!(
+            r#"<p:sld xmlns:p="p"{}><p:cSld><p:spTree><p:sp><p:nvSpPr><p:nvPr><p:ph type="title"/></p:nvPr></p:nvSpPr><p:txBody><a:p><a:r><a:t>{}</a:t></a:r></a:p></p:txBody></p:sp></p:spTree></p:cSld></p:sld>"#,
+            show, title
+        )
+    }
+
+    fn notes_xml(text: &str) -> String {
+        format!(
+            r#"<p:notes><p:cSld><p:spTree><p:sp><p:nvSpPr><p:nvPr><p:ph type="body" idx="1"/></p:nvPr></p:nvSpPr><p:txBody><a:p><a:r><a:t>{}</a:t></a:r></a:p></p:txBody></p:sp></p:spTree></p:cSld></p:notes>"#,
+            text
+        )
+    }
+
+    /// A deck of (title, notes, show attribute) slides
+    pub fn deck(slides: &[(&str, &str, Option<&str>)]) -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default();
+        let mut put = |name: &str, body: &str| {
+            zip.start_file(name, options).unwrap();
+            zip.write_all(body.as_bytes()).unwrap();
+        };
+
+        let ids: String = (1..=slides.len())
+            .map(|i| format!(r#"<p:sldId id="{}" r:id="rId{}"/>"#, 255 + i, i))
+            .collect();
+        put(
+            "ppt/presentation.xml",
+            &format!(
+                "<p:presentation><p:sldIdLst>{}</p:sldIdLst></p:presentation>",
+                ids
+            ),
+        );
+        let rels: String = (1..=slides.len())
+            .map(|i| {
+                format!(
+                    r#"<Relationship Id="rId{0}" Type="http://schemas/slide" Target="slides/slide{0}.xml"/>"#,
+                    i
+                )
+            })
+            .collect();
+        put(
+            "ppt/_rels/presentation.xml.rels",
+            &format!("<Relationships>{}</Relationships>", rels),
+        );
+        for (i, (title, notes, show)) in slides.iter().enumerate() {
+            let n = i + 1;
+            put(
+                &format!("ppt/slides/slide{}.xml", n),
+                &slide_xml(title, *show),
+            );
+            put(
+                &format!("ppt/slides/_rels/slide{}.xml.rels", n),
+                &format!(
+                    r#"<Relationships><Relationship Id="rId1" Type="http://schemas/notesSlide" Target="../notesSlides/notesSlide{}.xml"/></Relationships>"#,
+                    n
+                ),
+            );
+            put(
+                &format!("ppt/notesSlides/notesSlide{}.xml", n),
+                &notes_xml(notes),
+            );
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn reads_notes_in_deck_order_and_marks_hidden_slides() {
+        let bytes = deck(&[
+            ("Intro", "Welcome &amp; thanks", None),
+            ("Backup", "Only if asked", Some("0")),
+            ("Close", "Questions", Some("1")),
+        ]);
+        let slides = read_pptx_bytes(&bytes).unwrap();
+        let summary: Vec<(i32, &str, &str, bool)> = slides
+            .iter()
+            .map(|s| (s.number, s.title.as_str(), s.notes.as_str(), s.hidden))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1, "Intro", "Welcome & thanks", false),
+                (2, "Backup", "Only if asked", true),
+                (3, "Close", "Questions", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_a_file_that_isnt_a_deck() {
+        assert!(read_pptx_bytes(b"not a zip").is_err());
+    }
+}
@@ -0,0 +1,119 @@
+//! Debounced file watching for local notes sources
+//!
+//! Editors often save by writing a temp file and renaming it over the
+//! original, so the parent directory is watched and events are matched by file
+//! name. Bursts of events within `DEBOUNCE_MS` collapse into one callback.
+
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const DEBOUNCE_MS: u64 = 300;
+
+/// Keeps a watch alive; dropping it stops watching
+pub struct FileWatcher {
+    _watcher: notify::RecommendedWatcher,
+    task: tauri::async_runtime::JoinHandle<()>,
+}
+
+impl Drop for FileWatcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Call `on_change` (debounced) whenever the file at `path` is written, replaced or removed
+pub fn watch_file<F>(path: &Path, on_change: F) -> Result<FileWatcher, String>
+where
+    F: Fn(&Path) + Send + 'static,
+{
+    let target: PathBuf = path.to_path_buf();
+    let file_name = target
+        .file_name()
+        .map(|n| n.to_os_string())
+        .ok_or("Not a file path")?;
+    let dir = target
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."))
+        .to_path_buf();
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<()>();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        let Ok(event) = res else {
+            return;
+        };
+        let relevant = matches!(
+            event.kind,
+            EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+        ) && event
+            .paths
+            .iter()
+            .any(|p| p.file_name() == Some(file_name.as_os_str()));
+        if relevant {
+            let _ = tx.send(());
+        }
+    })
+    .map_err(|e| format!("Failed to create file watcher: {}", e))?;
+
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("Failed to watch {}: {}", dir.display(), e))?;
+
+    let task = tauri::async_runtime::spawn(async move {
+        while rx.recv().await.is_some() {
+            // Wait for the burst to settle before re-reading
+            loop {
+                match tokio::time::timeout(Duration::from_millis(DEBOUNCE_MS), rx.recv()).await {
+                    Ok(Some(())) => continue,
+                    Ok(None) => return,
+                    Err(_) => break,
+                }
+            }
+            on_change(&target);
+        }
+    });
+
+    Ok(FileWatcher {
+        _watcher: watcher,
+        task,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn a_burst_of_saves_is_one_change() {
+        let dir = std::env::temp_dir().join(format!("cuecard-watch-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("talk.md");
+        std::fs::write(&path, "Intro").unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        let watcher = watch_file(&path, move |changed| {
+            let _ = tx.send(changed.to_path_buf());
+        })
+        .unwrap();
+
+        for version in 0..5 {
+            std::fs::write(&path, format!("Intro, take {}", version)).unwrap();
+        }
+        // Saved the way many editors do: a temp file renamed over the original
+        let temp = dir.join(".talk.md.swp");
+        std::fs::write(&temp, "Intro, final").unwrap();
+        std::fs::rename(&temp, &path).unwrap();
+        // Other files in the directory don't count
+        std::fs::write(dir.join("other.md"), "Unrelated").unwrap();
+
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), path);
+        assert!(rx
+            .recv_timeout(Duration::from_millis(DEBOUNCE_MS * 3))
+            .is_err());
+
+        drop(watcher);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
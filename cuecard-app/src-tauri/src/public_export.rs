@@ -0,0 +1,211 @@
+//! Notes from a deck's public export when Slides access is missing
+//!
+//! Without the Slides read scope, or when Google refuses the API call, a deck
+//! shared as "anyone with the link" can still be downloaded as `.pptx` from
+//! its export URL without signing in. The export doesn't carry Slides'
+//! object ids, so notes are matched by slideshow number: the slide at the
+//! same position once hidden slides are left out. They're a fallback:
+//! provenance names the provider `google_public_export` and marks it
+//! degraded, and the export isn't refreshed until the deck is opened again.
+//! A deck whose export couldn't be read is tried again after a while.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::error::CueCardError;
+use crate::http_client;
+use crate::providers::local_file::LocalSlide;
+use crate::providers::pptx;
+
+/// Provider named in `notes_provenance` for notes from the export
+pub const PROVIDER: &str = "google_public_export";
+
+const EXPORT_URL: &str = "https://docs.google.com/presentation/d";
+// Far larger than any notes need; a deck past it is mostly media
+const MAX_EXPORT_BYTES: u64 = 100 * 1024 * 1024;
+// Sharing can change, so a failed download isn't final
+const UNAVAILABLE_RETRY_AFTER: Duration = Duration::from_secs(10 * 60);
+
+enum Export {
+    Pending,
+    /// Notes of the shown slides in slideshow order
+    Notes(Vec<String>),
+    /// When the download failed
+    Unavailable(Instant),
+}
+
+static EXPORTS: Lazy<Arc<RwLock<HashMap<String, Export>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(HashMap::new())));
+
+/// Whether a failed Slides fetch is worth retrying through the public export
+pub fn should_fall_back(error: &CueCardError) -> bool {
+    matches!(
+        error,
+        CueCardError::ScopeMissing(_)
+            | CueCardError::PermissionDenied(_)
+            | CueCardError::AuthRequired(_)
+    )
+}
+
+async fn download_notes(presentation_id: &str) -> Result<Vec<String>, CueCardError> {
+    let response = http_client::client()
+        .get(format!(
+            "{}/{}/export/pptx",
+            EXPORT_URL,
+            urlencoding::encode(presentation_id)
+        ))
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        return Err(CueCardError::from_status(
+            status,
+            format!("Public export failed: {}", status),
+        ));
+    }
+    let too_large = || {
+        CueCardError::Other(format!(
+            "The public export is larger than {} MB",
+            MAX_EXPORT_BYTES / (1024 * 1024)
+        ))
+    };
+    if response
+        .content_length()
+        .is_some_and(|len| len > MAX_EXPORT_BYTES)
+    {
+        return Err(too_large());
+    }
+    let mut response = response;
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if (bytes.len() + chunk.len()) as u64 > MAX_EXPORT_BYTES {
+            return Err(too_large());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    // A deck that isn't shared publicly answers with a sign-in page
+    if !bytes.starts_with(b"PK") {
+        return Err(CueCardError::PermissionDenied(
+            "The presentation isn't shared publicly".to_string(),
+        ));
+    }
+
+    let mut slides = pptx::read_pptx_bytes(&bytes)?;
+    slides.sort_by_key(|s| s.number);
+    Ok(shown_notes(slides))
+}
+
+/// Notes in slideshow order; hidden slides don't get a number there
+fn shown_notes(slides: Vec<LocalSlide>) -> Vec<String> {
+    slides
+        .into_iter()
+        .filter(|s| !s.hidden)
+        .map(|s| s.notes)
+        .collect()
+}
+
+fn should_download(export: Option<&Export>, now: Instant) -> bool {
+    match export {
+        Some(Export::Unavailable(at)) => now.duration_since(*at) >= UNAVAILABLE_RETRY_AFTER,
+        Some(_) => false,
+        None => true,
+    }
+}
+
+/// Download the deck's public export in the background, once per deck
+/// unless it was unavailable a while ago
+pub fn start(presentation_id: &str) {
+    {
+        let mut exports = EXPORTS.write();
+        if !should_download(exports.get(presentation_id), Instant::now()) {
+            return;
+        }
+        exports.insert(presentation_id.to_string(), Export::Pending);
+    }
+
+    let presentation_id = presentation_id.to_string();
+    tauri::async_runtime::spawn(async move {
+        let export = match download_notes(&presentation_id).await {
+            Ok(notes) => Export::Notes(notes),
+            Err(e) => {
+                eprintln!("No public export for {}: {}", presentation_id, e);
+                Export::Unavailable(Instant::now())
+            }
+        };
+        let ready = matches!(export, Export::Notes(_));
+        EXPORTS.write().insert(presentation_id.clone(), export);
+        if ready {
+            crate::reemit_current_slide(&presentation_id, None);
+        }
+    });
+}
+
+/// Notes of the slide at `slide_number` (1-based, slideshow numbering) in
+/// the deck's export
+pub fn notes_for_slide(presentation_id: &str, slide_number: i32) -> Option<String> {
+    let index = usize::try_from(slide_number).ok()?.checked_sub(1)?;
+    match EXPORTS.read().get(presentation_id)? {
+        Export::Notes(notes) => notes.get(index).cloned(),
+        _ => None,
+    }
+}
+
+/// Forget downloaded exports, so the next fallback downloads again
+pub fn clear() {
+    EXPORTS.write().clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slide(number: i32, notes: &str, hidden: bool) -> LocalSlide {
+        LocalSlide {
+            number,
+            title: format!("Slide {}", number),
+            notes: notes.to_string(),
+            hidden,
+        }
+    }
+
+    #[test]
+    fn numbers_notes_like_the_slideshow() {
+        let notes = shown_notes(vec![
+            slide(1, "one", false),
+            slide(2, "backup", true),
+            slide(3, "three", false),
+        ]);
+        EXPORTS
+            .write()
+            .insert("deck".to_string(), Export::Notes(notes));
+
+        assert_eq!(notes_for_slide("deck", 1).as_deref(), Some("one"));
+        assert_eq!(notes_for_slide("deck", 2).as_deref(), Some("three"));
+        assert_eq!(notes_for_slide("deck", 3), None);
+        assert_eq!(notes_for_slide("deck", 0), None);
+        clear();
+    }
+
+    #[test]
+    fn retries_an_unavailable_export_later() {
+        let failed = Instant::now();
+        let unavailable = Export::Unavailable(failed);
+        assert!(should_download(None, failed));
+        assert!(!should_download(Some(&Export::Pending), failed));
+        assert!(!should_download(
+            Some(&Export::Notes(Vec::new())),
+            failed + UNAVAILABLE_RETRY_AFTER
+        ));
+        assert!(!should_download(
+            Some(&unavailable),
+            failed + Duration::from_secs(60)
+        ));
+        assert!(should_download(
+            Some(&unavailable),
+            failed + UNAVAILABLE_RETRY_AFTER
+        ));
+    }
+}
@@ -0,0 +1,183 @@
+//! Rehearsal analytics kept on this machine
+//!
+//! One record per session ("delivery") with the timings collected during it,
+//! stored in `cuecard-store.json` so a talk can be compared across rehearsals
+//! and deliveries. Nothing here is sent anywhere.
+
+use serde::{Deserialize, Serialize};
+use tauri_plugin_store::StoreExt;
+
+use crate::APP_HANDLE;
+
+const REHEARSAL_STORE_KEY: &str = "rehearsal_runs";
+// Oldest deliveries are dropped beyond this
+const MAX_STORED_RUNS: usize = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectionRecord {
+    pub name: String,
+    pub total_ms: i64,
+    pub laps_ms: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RehearsalRun {
+    pub session_id: String,
+    pub started_at: i64,
+    pub sections: Vec<SectionRecord>,
+}
+
+/// One section's timing in one delivery
+#[derive(Debug, Clone, Serialize)]
+pub struct SectionDelivery {
+    pub session_id: String,
+    pub started_at: i64,
+    pub section: SectionRecord,
+}
+
+pub fn load_runs() -> Vec<RehearsalRun> {
+    let app = APP_HANDLE.read();
+    let Some(app) = app.as_ref() else {
+        return Vec::new();
+    };
+    app.store("cuecard-store.json")
+        .ok()
+        .and_then(|store| store.get(REHEARSAL_STORE_KEY))
+        .and_then(|runs| serde_json::from_value(runs).ok())
+        .unwrap_or_default()
+}
+
+fn save_runs(runs: &[RehearsalRun]) {
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        if let Ok(store) = app.store("cuecard-store.json") {
+            if let Ok(json) = serde_json::to_value(runs) {
+                store.set(REHEARSAL_STORE_KEY, json);
+                let _ = store.save();
+            }
+        }
+    }
+}
+
+/// Save a section's timing into the current session's record, replacing earlier
+/// timings of the same section in that session
+pub fn record_section(session_id: &str, started_at: i64, section: SectionRecord) {
+    let mut runs = load_runs();
+    add_section(&mut runs, session_id, started_at, section);
+    save_runs(&runs);
+}
+
+fn add_section(
+    runs: &mut Vec<RehearsalRun>,
+    session_id: &str,
+    started_at: i64,
+    section: SectionRecord,
+) {
+    let run = match runs.iter().position(|r| r.session_id == session_id) {
+        Some(i) => &mut runs[i],
+        None => {
+            runs.push(RehearsalRun {
+                session_id: session_id.to_string(),
+                started_at,
+                sections: Vec::new(),
+            });
+            let last = runs.len() - 1;
+            &mut runs[last]
+        }
+    };
+
+    match run.sections.iter_mut().find(|s| s.name == section.name) {
+        Some(existing) => *existing = section,
+        None => run.sections.push(section),
+    }
+
+    if runs.len() > MAX_STORED_RUNS {
+        let excess = runs.len() - MAX_STORED_RUNS;
+        runs.drain(..excess);
+    }
+}
+
+/// Drop deliveries started before `cutoff` (Unix seconds); returns how many
+pub fn remove_runs_before(cutoff: i64) -> usize {
+    let mut runs = load_runs();
+    let removed = drop_runs_before(&mut runs, cutoff);
+    if removed > 0 {
+        save_runs(&runs);
+    }
+    removed
+}
+
+pub fn drop_runs_before(runs: &mut Vec<RehearsalRun>, cutoff: i64) -> usize {
+    let before = runs.len();
+    runs.retain(|r| r.started_at >= cutoff);
+    before - runs.len()
+}
+
+/// Timings of a section (or every section) across deliveries, oldest first
+#[tauri::command]
+pub fn get_section_history(section: Option<String>) -> Vec<SectionDelivery> {
+    section_history(load_runs(), section)
+}
+
+fn section_history(runs: Vec<RehearsalRun>, section: Option<String>) -> Vec<SectionDelivery> {
+    runs.into_iter()
+        .flat_map(|run| {
+            let session_id = run.session_id;
+            let started_at = run.started_at;
+            run.sections.into_iter().map(move |s| SectionDelivery {
+                session_id: session_id.clone(),
+                started_at,
+                section: s,
+            })
+        })
+        .filter(|d| section.as_ref().is_none_or(|name| d.section.name == *name))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(name: &str, total_ms: i64) -> SectionRecord {
+        SectionRecord {
+            name: name.to_string(),
+            total_ms,
+            laps_ms: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn a_section_stopped_again_replaces_its_timing() {
+        let mut runs = Vec::new();
+        add_section(&mut runs, "s1", 100, section("intro", 1_000));
+        add_section(&mut runs, "s1", 100, section("demo", 5_000));
+        add_section(&mut runs, "s1", 100, section("intro", 1_500));
+        add_section(&mut runs, "s2", 200, section("intro", 900));
+
+        assert_eq!(runs.len(), 2);
+        let totals: Vec<_> = runs[0]
+            .sections
+            .iter()
+            .map(|s| (s.name.as_str(), s.total_ms))
+            .collect();
+        assert_eq!(totals, vec![("intro", 1_500), ("demo", 5_000)]);
+
+        let intro: Vec<_> = section_history(runs, Some("intro".to_string()))
+            .into_iter()
+            .map(|d| (d.session_id, d.section.total_ms))
+            .collect();
+        assert_eq!(
+            intro,
+            vec![("s1".to_string(), 1_500), ("s2".to_string(), 900)]
+        );
+    }
+
+    #[test]
+    fn keeps_the_latest_runs() {
+        let mut runs = Vec::new();
+        for i in 0..=MAX_STORED_RUNS {
+            add_section(&mut runs, &format!("s{}", i), i as i64, section("intro", 1));
+        }
+        assert_eq!(runs.len(), MAX_STORED_RUNS);
+        assert_eq!(runs[0].session_id, "s1");
+    }
+}
@@ -0,0 +1,174 @@
+//! Data retention and local data wipe
+//!
+//! Each category of accumulated data can be given a retention period in days;
+//! a background task deletes anything older once at startup and hourly after
+//! that. Categories:
+//!
+//! - analytics: rehearsal runs and the last session report
+//! - caches: presentations preloaded to disk
+//!
+//! Decks the notes prefetch cached on disk also expire on their own, and are
+//! trimmed to the cache's size limit, in the same pass.
+//!
+//! `purge_all_data` deletes everything CueCard has stored on this machine.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+use crate::error::CueCardError;
+use crate::{clock, disk_cache, rehearsal, secure_store, session_report};
+
+const RETENTION_STORE_KEY: &str = "retention_settings";
+const CLEANUP_INTERVAL_SECS: u64 = 60 * 60;
+
+/// Days to keep each category; `None` keeps it indefinitely
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetentionSettings {
+    #[serde(default)]
+    pub analytics_days: Option<u32>,
+    #[serde(default)]
+    pub cache_days: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CleanupResult {
+    pub analytics_removed: usize,
+    pub caches_removed: usize,
+}
+
+static RETENTION_SETTINGS: Lazy<Arc<RwLock<RetentionSettings>>> =
+    Lazy::new(|| Arc::new(RwLock::new(RetentionSettings::default())));
+
+pub fn load_retention_from_store(app: &AppHandle) {
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Some(json) = store.get(RETENTION_STORE_KEY) {
+            if let Ok(settings) = serde_json::from_value::<RetentionSettings>(json) {
+                *RETENTION_SETTINGS.write() = settings;
+            }
+        }
+    }
+}
+
+fn cutoff(days: u32) -> i64 {
+    clock::now_secs() - i64::from(days) * 24 * 60 * 60
+}
+
+/// Delete whatever is past its retention period
+pub fn enforce_retention() -> CleanupResult {
+    let settings = RETENTION_SETTINGS.read().clone();
+    let mut result = CleanupResult::default();
+
+    if let Some(days) = settings.analytics_days {
+        let cutoff = cutoff(days);
+        result.analytics_removed = rehearsal::remove_runs_before(cutoff);
+        if session_report::remove_summary_before(cutoff) {
+            result.analytics_removed += 1;
+        }
+    }
+
+    if let Some(days) = settings.cache_days {
+        match disk_cache::remove_cached_before(cutoff(days)) {
+            Ok(removed) => result.caches_removed = removed,
+            Err(e) => eprintln!("Failed to clean up presentation cache: {}", e),
+        }
+    }
+    match disk_cache::enforce_limits() {
+        Ok(removed) => result.caches_removed += removed,
+        Err(e) => eprintln!("Failed to trim presentation cache: {}", e),
+    }
+
+    result
+}
+
+/// Apply retention settings; runs for the lifetime of the app
+pub async fn run_cleanup_loop() {
+    loop {
+        enforce_retention();
+        tokio::time::sleep(Duration::from_secs(CLEANUP_INTERVAL_SECS)).await;
+    }
+}
+
+#[tauri::command]
+pub fn get_retention_settings() -> RetentionSettings {
+    RETENTION_SETTINGS.read().clone()
+}
+
+/// Save retention settings and apply them right away
+#[tauri::command]
+pub fn set_retention_settings(
+    app: AppHandle,
+    settings: RetentionSettings,
+) -> Result<CleanupResult, CueCardError> {
+    if let Ok(store) = app.store("cuecard-store.json") {
+        let json = serde_json::to_value(&settings).map_err(|e| e.to_string())?;
+        store.set(RETENTION_STORE_KEY, json);
+        let _ = store.save();
+    }
+    *RETENTION_SETTINGS.write() = settings;
+    Ok(enforce_retention())
+}
+
+/// Delete all local data (sign-in, settings, analytics and caches) and restart
+/// the app so nothing lingers in memory
+#[tauri::command]
+pub fn purge_all_data(app: AppHandle) -> Result<(), CueCardError> {
+    crate::logout(app.clone());
+    disk_cache::clear()?;
+    secure_store::reset();
+    crate::notes_summary::reset(&app);
+    crate::notes_translation::reset(&app);
+
+    let store = app
+        .store("cuecard-store.json")
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+    store.clear();
+    store
+        .save()
+        .map_err(|e| format!("Failed to save store: {}", e))?;
+
+    app.restart()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::tests::with_mock;
+    use crate::rehearsal::RehearsalRun;
+
+    #[test]
+    fn cutoff_is_whole_days_back_from_now() {
+        with_mock(1_700_000_000, |_| {
+            assert_eq!(cutoff(0), 1_700_000_000);
+            assert_eq!(cutoff(30), 1_700_000_000 - 30 * 86_400);
+        });
+    }
+
+    #[test]
+    fn keeps_runs_from_the_cutoff_on() {
+        let run = |session_id: &str, started_at| RehearsalRun {
+            session_id: session_id.to_string(),
+            started_at,
+            sections: Vec::new(),
+        };
+        with_mock(1_700_000_000, |_| {
+            let cutoff = cutoff(7);
+            let mut runs = vec![
+                run("too-old", cutoff - 1),
+                run("on-the-day", cutoff),
+                run("yesterday", 1_700_000_000 - 86_400),
+            ];
+            assert_eq!(rehearsal::drop_runs_before(&mut runs, cutoff), 1);
+            assert_eq!(
+                runs.iter()
+                    .map(|r| r.session_id.as_str())
+                    .collect::<Vec<_>>(),
+                vec!["on-the-day", "yesterday"]
+            );
+        });
+    }
+}
@@ -0,0 +1,235 @@
+//! Picking up notes edited by someone else while a deck is open
+//!
+//! Every few seconds (30 by default, 0 turns it off) the current Google
+//! Slides deck's `revisionId` is read with the outline request, a single
+//! small read. When it differs from the last one seen, the deck's notes are
+//! fetched again, the current slide is shown again and the slides whose notes
+//! changed are sent as `notes-updated`. Polls are skipped while offline or
+//! asleep (`sleep_wake`), in low-data mode, or close to Google's request
+//! limits (`api_usage`).
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_store::StoreExt;
+
+use crate::{
+    api_usage, connectivity, disk_cache, http_client, low_data, notes_freshness, sleep_wake,
+    APP_HANDLE, CURRENT_PRESENTATION_ID, CURRENT_SLIDE, SLIDE_NOTES, SLIDE_ORDER,
+};
+
+const REVISION_POLL_KEY: &str = "revision_poll_secs";
+const DEFAULT_INTERVAL_SECS: u64 = 30;
+// Google allows a few hundred reads a minute; keep polling well clear of that
+const MIN_INTERVAL_SECS: u64 = 10;
+// How often a turned-off poller looks at the setting again
+const IDLE_CHECK_SECS: u64 = 5;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NotesUpdatedEvent {
+    pub presentation_id: String,
+    pub revision: Option<String>,
+    /// Slides whose notes were added, changed or removed
+    pub slide_ids: Vec<String>,
+}
+
+static INTERVAL_SECS: Lazy<Arc<RwLock<u64>>> =
+    Lazy::new(|| Arc::new(RwLock::new(DEFAULT_INTERVAL_SECS)));
+// presentation id -> last revision seen
+static REVISIONS: Lazy<Arc<RwLock<HashMap<String, String>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(HashMap::new())));
+
+pub fn load_revision_poll_from_store(app: &AppHandle) {
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Some(secs) = store.get(REVISION_POLL_KEY).and_then(|v| v.as_u64()) {
+            *INTERVAL_SECS.write() = secs;
+        }
+    }
+}
+
+fn deck_notes(presentation_id: &str) -> HashMap<String, String> {
+    let prefix = format!("{}:", presentation_id);
+    SLIDE_NOTES
+        .read()
+        .iter()
+        .filter_map(|(key, text)| Some((key.strip_prefix(&prefix)?.to_string(), text.clone())))
+        .collect()
+}
+
+/// A fetch of the deck's notes saw this revision, whether or not the disk
+/// copy was written
+pub fn record_revision(presentation_id: &str, revision: String) {
+    REVISIONS
+        .write()
+        .insert(presentation_id.to_string(), revision);
+}
+
+/// Fetch the deck's notes again; the slides whose notes differ
+async fn refetch(presentation_id: &str) -> Result<Vec<String>, String> {
+    let before = deck_notes(presentation_id);
+    // The prefetch replaces each slide's notes in place, so the old ones stay
+    // on screen until the new ones are in
+    crate::prefetch_all_notes(presentation_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Slides taken out of the deck since; the order is the current deck's
+    let prefix = format!("{}:", presentation_id);
+    let order: HashSet<String> = SLIDE_ORDER.read().iter().cloned().collect();
+    let current = CURRENT_PRESENTATION_ID.read().as_deref() == Some(presentation_id);
+    if current && !order.is_empty() {
+        SLIDE_NOTES.write().retain(|key, _| {
+            key.strip_prefix(&prefix)
+                .is_none_or(|slide_id| order.contains(slide_id))
+        });
+    }
+
+    Ok(changed_slides(&before, &deck_notes(presentation_id)))
+}
+
+/// Slides whose notes were added, changed or removed, sorted
+fn changed_slides(
+    before: &HashMap<String, String>,
+    after: &HashMap<String, String>,
+) -> Vec<String> {
+    let mut changed: Vec<String> = before
+        .keys()
+        .chain(after.keys())
+        .filter(|slide_id| before.get(*slide_id) != after.get(*slide_id))
+        .cloned()
+        .collect();
+    changed.sort();
+    changed.dedup();
+    changed
+}
+
+async fn poll_once() -> Result<(), String> {
+    let Some(slide) = CURRENT_SLIDE.read().clone() else {
+        return Ok(());
+    };
+    if !crate::is_google_slides_mode(&slide.mode)
+        || connectivity::state() != connectivity::ConnectivityState::Online
+        || low_data::is_active()
+        || sleep_wake::is_paused()
+        || api_usage::throttle_optional()
+    {
+        return Ok(());
+    }
+    let presentation_id = slide.presentation_id;
+
+    let access_token = crate::slides_access_token().await?;
+    let outline =
+        crate::fetch_deck_outline(&http_client::client(), &access_token, &presentation_id).await?;
+    let Some(revision) = outline.revision else {
+        return Ok(());
+    };
+
+    // Every prefetch records its revision here, including ones whose disk
+    // write was skipped; the disk copy only stands in before the first
+    let known = REVISIONS
+        .read()
+        .get(&presentation_id)
+        .cloned()
+        .or_else(|| disk_cache::cached_revision(&presentation_id));
+    // The first look at a deck only records where it stands
+    if known.is_none() {
+        record_revision(&presentation_id, revision);
+        return Ok(());
+    }
+    if known.as_deref() == Some(revision.as_str()) {
+        notes_freshness::record_verified(&presentation_id);
+        return Ok(());
+    }
+
+    // Recorded only once the notes at this revision are in, so a failed
+    // refetch is tried again on the next poll
+    let slide_ids = refetch(&presentation_id).await?;
+    record_revision(&presentation_id, revision.clone());
+    crate::reemit_current_slide(&presentation_id, None);
+    if slide_ids.is_empty() {
+        return Ok(());
+    }
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        let _ = app.emit(
+            "notes-updated",
+            NotesUpdatedEvent {
+                presentation_id,
+                revision: Some(revision),
+                slide_ids,
+            },
+        );
+    }
+    Ok(())
+}
+
+pub async fn run_poller() {
+    loop {
+        let interval = *INTERVAL_SECS.read();
+        if interval == 0 {
+            tokio::time::sleep(Duration::from_secs(IDLE_CHECK_SECS)).await;
+            continue;
+        }
+        tokio::time::sleep(Duration::from_secs(interval.max(MIN_INTERVAL_SECS))).await;
+        if let Err(e) = poll_once().await {
+            eprintln!("Revision poll failed: {}", e);
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_revision_poll_interval() -> u64 {
+    *INTERVAL_SECS.read()
+}
+
+/// Seconds between checks for edits to the open deck; 0 turns checking off
+#[tauri::command]
+pub fn set_revision_poll_interval(app: AppHandle, secs: u64) {
+    let secs = if secs == 0 {
+        0
+    } else {
+        secs.max(MIN_INTERVAL_SECS)
+    };
+    *INTERVAL_SECS.write() = secs;
+    if let Ok(store) = app.store("cuecard-store.json") {
+        store.set(REVISION_POLL_KEY, secs);
+        let _ = store.save();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notes(slides: &[(&str, &str)]) -> HashMap<String, String> {
+        slides
+            .iter()
+            .map(|(id, text)| (id.to_string(), text.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn reports_added_changed_and_removed_notes() {
+        let before = notes(&[("p1", "Intro"), ("p2", "Agenda"), ("p3", "Numbers")]);
+        let after = notes(&[("p1", "Intro"), ("p2", "Agenda, shorter"), ("p4", "Q&A")]);
+        assert_eq!(changed_slides(&before, &after), vec!["p2", "p3", "p4"]);
+        assert!(changed_slides(&before, &before).is_empty());
+    }
+
+    #[test]
+    fn keeps_only_this_decks_notes() {
+        let _deck = crate::tests::DECK_LOCK.lock();
+        {
+            let mut cache = SLIDE_NOTES.write();
+            cache.insert("poll-a:p1".to_string(), "Mine".to_string());
+            cache.insert("poll-b:p1".to_string(), "Someone else's".to_string());
+        }
+        assert_eq!(deck_notes("poll-a"), notes(&[("p1", "Mine")]));
+        SLIDE_NOTES
+            .write()
+            .retain(|key, _| !key.starts_with("poll-"));
+    }
+}
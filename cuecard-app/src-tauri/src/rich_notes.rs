@@ -0,0 +1,276 @@
+//! Speaker notes with their formatting
+//!
+//! `notes` in `slide-update` is plain text. Alongside it, Google Slides notes
+//! are kept as paragraphs of styled runs (bold, italic, underline,
+//! strikethrough, color and links) with each paragraph's bullet nesting level
+//! and glyph, so the panel can render them as written. They're sent as
+//! `rich_notes` only while the notes shown are the slide's own notes
+//! unchanged: notes merged from other sources, masked, annotated or rewritten
+//! by the notes pipeline fall back to the plain text, as do notes read from
+//! the disk cache until the deck has been fetched. Colors set from the deck's
+//! theme rather than as RGB are left out.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::CURRENT_SLIDE;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RichRun {
+    pub text: String,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub strikethrough: bool,
+    /// `#rrggbb`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Bullet {
+    /// 0 for top-level items
+    pub nesting_level: u32,
+    /// As rendered in the deck, e.g. "●" or "2."
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub glyph: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RichParagraph {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bullet: Option<Bullet>,
+    pub runs: Vec<RichRun>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RichNotes {
+    pub paragraphs: Vec<RichParagraph>,
+}
+
+/// Formatted notes and the plain text they were recorded with
+struct Entry {
+    plain: String,
+    notes: RichNotes,
+}
+
+// Keyed by "presentation_id:slide_id", for the current presentation
+static RICH_NOTES: Lazy<Arc<RwLock<HashMap<String, Entry>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(HashMap::new())));
+
+/// `#rrggbb` for an `OptionalColor` given as RGB
+fn rgb_hex(color: &serde_json::Value) -> Option<String> {
+    let rgb = color.get("opaqueColor")?.get("rgbColor")?;
+    // Channels left out of the response are 0
+    let channel = |name: &str| {
+        let value = rgb.get(name).and_then(|v| v.as_f64()).unwrap_or(0.0);
+        (value.clamp(0.0, 1.0) * 255.0).round() as u8
+    };
+    Some(format!(
+        "#{:02x}{:02x}{:02x}",
+        channel("red"),
+        channel("green"),
+        channel("blue")
+    ))
+}
+
+fn run_from(text: &str, style: Option<&serde_json::Value>) -> RichRun {
+    let flag = |name: &str| {
+        style
+            .and_then(|s| s.get(name))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+    };
+    RichRun {
+        text: text.to_string(),
+        bold: flag("bold"),
+        italic: flag("italic"),
+        underline: flag("underline"),
+        strikethrough: flag("strikethrough"),
+        color: style
+            .and_then(|s| s.get("foregroundColor"))
+            .and_then(rgb_hex),
+        link: style
+            .and_then(|s| s.get("link"))
+            .and_then(|l| l.get("url"))
+            .and_then(|u| u.as_str())
+            .map(|u| u.to_string()),
+    }
+}
+
+/// Paragraphs and runs from a shape's `text`, with the plain text of its runs
+/// as `extract_text_from_text_elements` reads it
+fn parse(text: &serde_json::Value) -> Option<(RichNotes, String)> {
+    let elements = text.get("textElements")?.as_array()?;
+    let mut paragraphs: Vec<RichParagraph> = Vec::new();
+    let mut plain = String::new();
+
+    for element in elements {
+        if let Some(marker) = element.get("paragraphMarker") {
+            let bullet = marker.get("bullet").map(|b| Bullet {
+                nesting_level: b.get("nestingLevel").and_then(|n| n.as_u64()).unwrap_or(0) as u32,
+                glyph: b
+                    .get("glyph")
+                    .and_then(|g| g.as_str())
+                    .map(|g| g.to_string()),
+            });
+            paragraphs.push(RichParagraph {
+                bullet,
+                runs: Vec::new(),
+            });
+        } else if let Some(text_run) = element.get("textRun") {
+            let Some(content) = text_run.get("content").and_then(|c| c.as_str()) else {
+                continue;
+            };
+            plain.push_str(content);
+
+            // Paragraphs end in a newline; the paragraph itself stands for it
+            let content = content.strip_suffix('\n').unwrap_or(content);
+            if content.is_empty() {
+                continue;
+            }
+            if paragraphs.is_empty() {
+                paragraphs.push(RichParagraph::default());
+            }
+            if let Some(paragraph) = paragraphs.last_mut() {
+                paragraph
+                    .runs
+                    .push(run_from(content, text_run.get("style")));
+            }
+        }
+    }
+
+    // Plain text is trimmed, so blank paragraphs at either end go too
+    let is_blank = |p: &RichParagraph| p.runs.iter().all(|r| r.text.trim().is_empty());
+    while paragraphs.last().is_some_and(is_blank) {
+        paragraphs.pop();
+    }
+    let leading = paragraphs.iter().take_while(|p| is_blank(p)).count();
+    paragraphs.drain(..leading);
+
+    if paragraphs.is_empty() {
+        return None;
+    }
+    Some((RichNotes { paragraphs }, plain))
+}
+
+/// Record a slide's formatted notes next to `notes`, the plain text cached for
+/// it. Nothing is kept when the notes pipeline changed the text.
+pub fn record(
+    presentation_id: &str,
+    slide_id: &str,
+    slide: &serde_json::Value,
+    notes: Option<&str>,
+) {
+    let key = format!("{}:{}", presentation_id, slide_id);
+    let parsed = notes.and_then(|plain| {
+        let (rich, raw) = parse(crate::notes_body_text(slide)?)?;
+        (raw.trim() == plain).then(|| Entry {
+            plain: plain.to_string(),
+            notes: rich,
+        })
+    });
+
+    let mut cache = RICH_NOTES.write();
+    match parsed {
+        Some(entry) => {
+            cache.insert(key, entry);
+        }
+        None => {
+            cache.remove(&key);
+        }
+    }
+}
+
+/// Forget formatted notes when the presentation changes or is refreshed
+pub fn clear() {
+    RICH_NOTES.write().clear();
+}
+
+/// Formatted notes for a slide, if `displayed` is still their plain text
+pub fn for_display(
+    presentation_id: &str,
+    slide_id: &str,
+    displayed: Option<&str>,
+) -> Option<RichNotes> {
+    let displayed = displayed?;
+    let cache = RICH_NOTES.read();
+    let entry = cache.get(&format!("{}:{}", presentation_id, slide_id))?;
+    (entry.plain == displayed).then(|| entry.notes.clone())
+}
+
+/// Formatted notes for the current slide, for when the panel loads
+#[tauri::command]
+pub fn get_current_rich_notes() -> Option<RichNotes> {
+    let slide = CURRENT_SLIDE.read().clone()?;
+    for_display(
+        &slide.presentation_id,
+        &slide.slide_id,
+        crate::get_current_notes().as_deref(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn rgb_hex_fills_missing_channels() {
+        let color = json!({ "opaqueColor": { "rgbColor": { "red": 1.0, "blue": 0.5 } } });
+        assert_eq!(rgb_hex(&color).as_deref(), Some("#ff0080"));
+        let themed = json!({ "opaqueColor": { "themeColor": "ACCENT1" } });
+        assert_eq!(rgb_hex(&themed), None);
+    }
+
+    #[test]
+    fn parses_paragraphs_bullets_and_styles() {
+        let text = json!({ "textElements": [
+            { "paragraphMarker": {} },
+            { "textRun": { "content": "\n" } },
+            { "paragraphMarker": { "bullet": { "nestingLevel": 1, "glyph": "●" } } },
+            { "textRun": { "content": "Say ", "style": {} } },
+            { "textRun": {
+                "content": "this\n",
+                "style": { "bold": true, "link": { "url": "https://example.com" } }
+            } },
+            { "paragraphMarker": {} },
+            { "textRun": { "content": "Then pause\n" } },
+        ]});
+
+        let (notes, plain) = parse(&text).unwrap();
+        assert_eq!(plain, "\nSay this\nThen pause\n");
+        assert_eq!(notes.paragraphs.len(), 2);
+
+        let first = &notes.paragraphs[0];
+        assert_eq!(
+            first.bullet,
+            Some(Bullet {
+                nesting_level: 1,
+                glyph: Some("●".to_string()),
+            })
+        );
+        assert_eq!(first.runs[0].text, "Say ");
+        assert!(!first.runs[0].bold);
+        assert_eq!(first.runs[1].text, "this");
+        assert!(first.runs[1].bold);
+        assert_eq!(first.runs[1].link.as_deref(), Some("https://example.com"));
+
+        assert_eq!(notes.paragraphs[1].bullet, None);
+        assert_eq!(notes.paragraphs[1].runs[0].text, "Then pause");
+    }
+
+    #[test]
+    fn blank_notes_parse_to_nothing() {
+        let text = json!({ "textElements": [
+            { "paragraphMarker": {} },
+            { "textRun": { "content": " \n" } },
+        ]});
+        assert_eq!(parse(&text), None);
+    }
+}
@@ -0,0 +1,209 @@
+//! What CueCard can access in the user's Google account, and dropping it
+//!
+//! Slides access, notes editing and the Drive deck list all share one Google
+//! grant, kept as `SLIDES_TOKENS`; the profile sign-in is a Firebase session
+//! of its own. `get_granted_scopes` asks Google's tokeninfo endpoint what the
+//! token can do, and falls back to the scopes stored with it when Google
+//! can't be reached. Google only revokes a grant as a whole, so
+//! `revoke_scope` revokes the Slides grant and reports every scope that went
+//! with it; the Firebase session is kept, so the user stays signed in.
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_store::StoreExt;
+
+use crate::error::CueCardError;
+use crate::{http_client, APP_HANDLE, FIREBASE_TOKENS, SLIDES_TOKENS};
+
+const GOOGLE_TOKENINFO_URL: &str = "https://oauth2.googleapis.com/tokeninfo";
+const GOOGLE_REVOKE_URL: &str = "https://oauth2.googleapis.com/revoke";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GrantedScope {
+    pub scope: String,
+    /// The name `start_login` asks for it by, for scopes CueCard requests
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<&'static str>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GrantedScopes {
+    /// Signed in to CueCard with a Google profile
+    pub profile: bool,
+    pub scopes: Vec<GrantedScope>,
+    /// False when Google couldn't be asked and the stored scopes are shown
+    pub verified: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenInfo {
+    scope: Option<String>,
+}
+
+impl TokenInfo {
+    fn scopes(self) -> Vec<String> {
+        self.scope
+            .unwrap_or_default()
+            .split_whitespace()
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+fn scope_name(scope: &str) -> Option<&'static str> {
+    match scope {
+        crate::SCOPE_SLIDES => Some("slides"),
+        crate::SCOPE_SLIDES_WRITE => Some("slides_write"),
+        crate::SCOPE_DRIVE => Some("drive"),
+        _ => None,
+    }
+}
+
+fn granted(scopes: Vec<String>, verified: bool) -> GrantedScopes {
+    GrantedScopes {
+        profile: FIREBASE_TOKENS.read().is_some(),
+        scopes: scopes
+            .into_iter()
+            .map(|scope| GrantedScope {
+                name: scope_name(&scope),
+                scope,
+            })
+            .collect(),
+        verified,
+    }
+}
+
+/// Scopes Google reports for an access token
+async fn token_scopes(access_token: &str) -> Result<Vec<String>, String> {
+    let response = http_client::client()
+        .get(GOOGLE_TOKENINFO_URL)
+        .query(&[("access_token", access_token)])
+        .send()
+        .await
+        .map_err(|e| format!("Tokeninfo request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Tokeninfo error: {}", response.status()));
+    }
+    let info: TokenInfo = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse tokeninfo: {}", e))?;
+    Ok(info.scopes())
+}
+
+/// Whether `scope`, by URL or by name, is among `granted`
+fn is_granted(granted: &[String], scope: &str) -> bool {
+    granted
+        .iter()
+        .any(|s| s == scope || scope_name(s) == Some(scope))
+}
+
+/// What the Slides grant can access, checked with Google when possible
+#[tauri::command]
+pub async fn get_granted_scopes() -> GrantedScopes {
+    let Some(access_token) = crate::get_valid_slides_token().await else {
+        return granted(Vec::new(), true);
+    };
+
+    match token_scopes(&access_token).await {
+        Ok(scopes) => {
+            // Keep the stored list in step for `get_slides_scopes`
+            if let Some(t) = SLIDES_TOKENS.write().as_mut() {
+                t.scopes = scopes.clone();
+            }
+            if let Some(app) = APP_HANDLE.read().as_ref() {
+                crate::save_slides_tokens_to_store(app);
+            }
+            granted(scopes, true)
+        }
+        Err(e) => {
+            eprintln!("Showing stored scopes: {}", e);
+            let stored = crate::get_slides_scopes().granted;
+            granted(stored, false)
+        }
+    }
+}
+
+/// Drop a scope from the Slides grant by revoking the grant with Google.
+/// Returns every scope that was revoked along with it.
+#[tauri::command]
+pub async fn revoke_scope(app: AppHandle, scope: String) -> Result<Vec<String>, CueCardError> {
+    if scope == "profile" {
+        return Err("Sign out to remove profile access".into());
+    }
+    let tokens = SLIDES_TOKENS.read().clone();
+    let Some(tokens) = tokens else {
+        return Ok(Vec::new());
+    };
+    let revoked = crate::get_slides_scopes().granted;
+    if !is_granted(&revoked, &scope) {
+        return Err(CueCardError::ScopeMissing(format!(
+            "{} isn't granted",
+            scope
+        )));
+    }
+
+    // Revoking the refresh token ends the whole grant, access tokens included
+    let token = tokens.refresh_token.unwrap_or(tokens.access_token);
+    let response = http_client::client()
+        .post(GOOGLE_REVOKE_URL)
+        .form(&[("token", token.as_str())])
+        .send()
+        .await
+        .map_err(|e| CueCardError::Network(format!("Revoke request failed: {}", e)))?;
+    // A token Google no longer knows is as good as revoked
+    if !response.status().is_success() && response.status() != reqwest::StatusCode::BAD_REQUEST {
+        return Err(CueCardError::from_status(
+            response.status(),
+            format!("Revoke failed: {}", response.status()),
+        ));
+    }
+
+    *SLIDES_TOKENS.write() = None;
+    if let Ok(store) = app.store("cuecard-store.json") {
+        store.delete("slides_tokens");
+        let _ = store.save();
+    }
+    let _ = app.emit(
+        "auth-status",
+        serde_json::json!({
+            "authenticated": FIREBASE_TOKENS.read().is_some(),
+            "slides_authorized": false
+        }),
+    );
+
+    Ok(revoked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_scopes_google_reports() {
+        let info: TokenInfo = serde_json::from_value(serde_json::json!({
+            "aud": "client",
+            "scope": "openid  https://www.googleapis.com/auth/presentations.readonly\nhttps://www.googleapis.com/auth/drive.readonly",
+            "expires_in": "3599"
+        }))
+        .unwrap();
+        let scopes = info.scopes();
+        assert_eq!(
+            scopes.iter().map(|s| scope_name(s)).collect::<Vec<_>>(),
+            vec![None, Some("slides"), Some("drive")]
+        );
+
+        let info: TokenInfo = serde_json::from_value(serde_json::json!({})).unwrap();
+        assert!(info.scopes().is_empty());
+    }
+
+    #[test]
+    fn revokes_a_scope_by_url_or_name() {
+        let granted = vec![crate::SCOPE_SLIDES.to_string(), "openid".to_string()];
+        assert!(is_granted(&granted, "slides"));
+        assert!(is_granted(&granted, crate::SCOPE_SLIDES));
+        assert!(is_granted(&granted, "openid"));
+        assert!(!is_granted(&granted, "drive"));
+        assert!(!is_granted(&granted, crate::SCOPE_DRIVE));
+    }
+}
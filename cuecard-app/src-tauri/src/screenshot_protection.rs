@@ -0,0 +1,161 @@
+//! Screenshot protection set per presentation
+//!
+//! Some decks are public and fine to show in a screen share; others are
+//! confidential. A deck can carry its own setting, which takes over from the
+//! global one (`set_screenshot_protection`) while that deck is the current
+//! presentation and hands back when another deck opens. Decks without one
+//! follow the global setting. Whenever the protection in effect changes it is
+//! sent as `screenshot-protection-changed`.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_store::StoreExt;
+
+use crate::error::CueCardError;
+use crate::{CURRENT_PRESENTATION_ID, SCREENSHOT_PROTECTION};
+
+const DECK_PROTECTION_KEY: &str = "deck_screenshot_protection";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ScreenshotProtection {
+    /// Whether the panel is hidden from screenshots and screen shares now
+    pub enabled: bool,
+    pub global: bool,
+    pub presentation_id: Option<String>,
+    /// The current deck's own setting, when it has one
+    pub presentation_override: Option<bool>,
+}
+
+// Keyed by presentation id
+static DECK_PROTECTION: Lazy<Arc<RwLock<HashMap<String, bool>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(HashMap::new())));
+// What was last applied to the window
+static APPLIED: Lazy<Arc<RwLock<Option<ScreenshotProtection>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(None)));
+
+pub fn load_deck_protection_from_store(app: &AppHandle) {
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Some(decks) = store
+            .get(DECK_PROTECTION_KEY)
+            .and_then(|v| serde_json::from_value::<HashMap<String, bool>>(v).ok())
+        {
+            *DECK_PROTECTION.write() = decks;
+        }
+    }
+}
+
+fn current() -> ScreenshotProtection {
+    resolve(
+        *SCREENSHOT_PROTECTION.read(),
+        CURRENT_PRESENTATION_ID.read().clone(),
+        &DECK_PROTECTION.read(),
+    )
+}
+
+/// The protection in effect: the deck's own setting if it has one, the
+/// global one otherwise
+fn resolve(
+    global: bool,
+    presentation_id: Option<String>,
+    decks: &HashMap<String, bool>,
+) -> ScreenshotProtection {
+    let presentation_override = presentation_id
+        .as_ref()
+        .and_then(|id| decks.get(id).copied());
+    ScreenshotProtection {
+        enabled: presentation_override.unwrap_or(global),
+        global,
+        presentation_id,
+        presentation_override,
+    }
+}
+
+/// Protect the panel as the global and current deck's settings say,
+/// announcing a change
+pub fn apply(app: &AppHandle) -> Result<(), String> {
+    let protection = current();
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Failed to get main window")?;
+    window
+        .set_content_protected(protection.enabled)
+        .map_err(|e| format!("Failed to update content protection: {}", e))?;
+
+    let previous = APPLIED.write().replace(protection.clone());
+    if previous.as_ref() != Some(&protection) {
+        let _ = app.emit("screenshot-protection-changed", &protection);
+    }
+    Ok(())
+}
+
+/// Another deck became the current presentation
+pub fn on_presentation_changed() {
+    let Some(app) = crate::APP_HANDLE.read().clone() else {
+        return;
+    };
+    if let Err(e) = apply(&app) {
+        eprintln!("Failed to apply the deck's screenshot protection: {}", e);
+    }
+}
+
+#[tauri::command]
+pub fn get_screenshot_protection() -> ScreenshotProtection {
+    current()
+}
+
+/// Give a deck (the current one when none is named) its own protection, or
+/// `None` to have it follow the global setting again
+#[tauri::command]
+pub fn set_deck_screenshot_protection(
+    app: AppHandle,
+    presentation_id: Option<String>,
+    enabled: Option<bool>,
+) -> Result<ScreenshotProtection, CueCardError> {
+    let presentation_id = presentation_id
+        .or_else(|| CURRENT_PRESENTATION_ID.read().clone())
+        .ok_or("No presentation is open")?;
+
+    {
+        let mut decks = DECK_PROTECTION.write();
+        match enabled {
+            Some(enabled) => decks.insert(presentation_id, enabled),
+            None => decks.remove(&presentation_id),
+        };
+    }
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Ok(json) = serde_json::to_value(&*DECK_PROTECTION.read()) {
+            store.set(DECK_PROTECTION_KEY, json);
+            let _ = store.save();
+        }
+    }
+
+    apply(&app)?;
+    Ok(current())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_decks_own_setting_wins_while_its_open() {
+        let decks: HashMap<String, bool> =
+            [("public".to_string(), false), ("board".to_string(), true)]
+                .into_iter()
+                .collect();
+
+        let public = resolve(true, Some("public".to_string()), &decks);
+        assert!(!public.enabled && public.global);
+        assert_eq!(public.presentation_override, Some(false));
+        assert!(resolve(false, Some("board".to_string()), &decks).enabled);
+
+        // Other decks, and no deck at all, follow the global setting
+        let other = resolve(true, Some("other".to_string()), &decks);
+        assert!(other.enabled && other.presentation_override.is_none());
+        assert!(!resolve(false, None, &decks).enabled);
+    }
+}
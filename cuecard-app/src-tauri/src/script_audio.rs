@@ -0,0 +1,633 @@
+//! A deck's notes read aloud into an audio file
+//!
+//! `export_script_audio` renders every slide's notes with the system's speech
+//! engine (`say` on macOS, System.Speech through PowerShell on Windows,
+//! `espeak-ng` or `espeak` elsewhere) into one WAV file to listen to away
+//! from the desk. Notes are read at the chosen words per minute, a
+//! `[pause]` in the notes becomes a short silence and each slide is followed
+//! by a longer one. A `[time mm:ss]` tag gives the text up to the next one a
+//! budget, as the panel's section timers do: text that would run over it is
+//! read faster, up to `MAX_WORDS_PER_MINUTE`, and text that ends early is
+//! followed by silence until it's used up, so the recording keeps the talk's
+//! pacing. `[note ...]` delivery cues and `[countdown mm:ss]` tags aren't
+//! read out. Each slide starts a chapter, written as a labelled cue
+//! point in the file and returned with its start time. Progress is sent as
+//! `script-audio-progress`.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::Emitter;
+
+use crate::error::CueCardError;
+use crate::{notes_export, APP_HANDLE};
+
+const DEFAULT_WORDS_PER_MINUTE: u32 = 150;
+const MIN_WORDS_PER_MINUTE: u32 = 80;
+const MAX_WORDS_PER_MINUTE: u32 = 300;
+const DEFAULT_SLIDE_GAP_MS: u32 = 1500;
+const PAUSE_MS: u32 = 800;
+// Format asked of every engine, so the pieces can be joined as they are
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+const SAMPLE_RATE: u32 = 22050;
+
+// Overrunning by less than this isn't worth speaking a segment again for
+const BUDGET_TOLERANCE_PERCENT: u64 = 5;
+
+static UNSPOKEN_TAG: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\[(?:note|countdown)\b[^\]]*\]").expect("valid unspoken tag regex")
+});
+// Same syntax the panel uses for section timers
+static TIME_TAG: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)\[time\s+(\d{1,2}):(\d{2})\]").expect("valid time tag regex"));
+static PAUSE_TAG: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)\[pause\]").expect("valid pause tag regex"));
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ScriptAudioOptions {
+    pub words_per_minute: Option<u32>,
+    /// Silence after each slide
+    pub slide_gap_ms: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioChapter {
+    pub slide_number: i32,
+    pub title: String,
+    pub start_ms: u64,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScriptAudioExport {
+    pub path: String,
+    pub duration_ms: u64,
+    pub chapters: Vec<AudioChapter>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ScriptAudioProgress {
+    done: usize,
+    total: usize,
+}
+
+/// 16-bit PCM samples and their format
+struct Pcm {
+    sample_rate: u32,
+    channels: u16,
+    data: Vec<u8>,
+}
+
+impl Pcm {
+    fn frame_bytes(&self) -> usize {
+        self.channels as usize * 2
+    }
+
+    fn frames(&self) -> usize {
+        self.data.len() / self.frame_bytes()
+    }
+
+    fn millis(&self, frames: usize) -> u64 {
+        frames as u64 * 1000 / self.sample_rate as u64
+    }
+
+    fn push_silence(&mut self, ms: u32) {
+        let frames = self.sample_rate as usize * ms as usize / 1000;
+        self.data
+            .resize(self.data.len() + frames * self.frame_bytes(), 0);
+    }
+
+    fn append(&mut self, other: Pcm) -> Result<(), String> {
+        if other.sample_rate != self.sample_rate || other.channels != self.channels {
+            return Err("The speech engine changed audio format partway through".to_string());
+        }
+        self.data.extend(other.data);
+        Ok(())
+    }
+}
+
+/// Text read as one stretch, with the time it should take if it has a budget
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Segment {
+    budget_ms: Option<u64>,
+    /// The pieces to read, split where it pauses
+    parts: Vec<String>,
+}
+
+impl Segment {
+    fn words(&self) -> usize {
+        self.parts
+            .iter()
+            .map(|p| p.split_whitespace().count())
+            .sum()
+    }
+
+    fn pauses_ms(&self) -> u64 {
+        self.parts.len().saturating_sub(1) as u64 * PAUSE_MS as u64
+    }
+}
+
+/// The pieces of some notes to read, split where they pause
+fn spoken_parts(notes: &str) -> Vec<String> {
+    let text = UNSPOKEN_TAG.replace_all(notes, "");
+    PAUSE_TAG
+        .split(&text)
+        .map(|part| part.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect()
+}
+
+/// A slide's notes split at `[time]` tags; text before the first has no
+/// budget
+fn segments(notes: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut budget_ms = None;
+    let mut start = 0;
+    for caps in TIME_TAG.captures_iter(notes) {
+        let tag = caps.get(0).expect("whole match");
+        segments.push(Segment {
+            budget_ms,
+            parts: spoken_parts(&notes[start..tag.start()]),
+        });
+        let minutes: u64 = caps[1].parse().unwrap_or(0);
+        let seconds: u64 = caps[2].parse().unwrap_or(0);
+        budget_ms = Some((minutes * 60 + seconds) * 1000);
+        start = tag.end();
+    }
+    segments.push(Segment {
+        budget_ms,
+        parts: spoken_parts(&notes[start..]),
+    });
+    segments.retain(|s| s.budget_ms.is_some() || s.words() > 0);
+    segments
+}
+
+/// Words per minute that fit `words` into `speaking_ms`, never slower than
+/// `words_per_minute`
+fn fitted_rate(words: usize, speaking_ms: u64, words_per_minute: u32) -> u32 {
+    if speaking_ms == 0 {
+        return MAX_WORDS_PER_MINUTE;
+    }
+    let needed = (words as u64 * 60_000).div_ceil(speaking_ms);
+    (needed.min(MAX_WORDS_PER_MINUTE as u64) as u32).max(words_per_minute)
+}
+
+fn read_u16(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// The samples of a 16-bit PCM WAV file
+fn read_wav(bytes: &[u8]) -> Result<Pcm, String> {
+    if bytes.get(0..4) != Some(b"RIFF") || bytes.get(8..12) != Some(b"WAVE") {
+        return Err("The speech engine didn't write a WAV file".to_string());
+    }
+
+    let mut format = None;
+    let mut at = 12;
+    while let (Some(id), Some(size)) = (bytes.get(at..at + 4), read_u32(bytes, at + 4)) {
+        let body = at + 8;
+        let end = (body + size as usize).min(bytes.len());
+        match id {
+            b"fmt " => {
+                let bits = read_u16(bytes, body + 14);
+                if bits != Some(16) {
+                    return Err("The speech engine didn't write 16-bit audio".to_string());
+                }
+                format = Some((
+                    read_u16(bytes, body + 2).unwrap_or(1),
+                    read_u32(bytes, body + 4).unwrap_or(0),
+                ));
+            }
+            b"data" => {
+                let (channels, sample_rate) = format.ok_or("WAV data before its format")?;
+                if channels == 0 || sample_rate == 0 {
+                    return Err("Unreadable WAV format".to_string());
+                }
+                let mut data = bytes[body..end].to_vec();
+                // A file cut short can end partway through a frame
+                data.truncate(data.len() - data.len() % (channels as usize * 2));
+                return Ok(Pcm {
+                    sample_rate,
+                    channels,
+                    data,
+                });
+            }
+            _ => {}
+        }
+        // Chunks are padded to an even length
+        at = body + size as usize + (size as usize & 1);
+    }
+    Err("The speech engine's WAV file has no audio".to_string())
+}
+
+fn chunk(out: &mut Vec<u8>, id: &[u8; 4], body: &[u8]) {
+    out.extend_from_slice(id);
+    out.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    out.extend_from_slice(body);
+    if body.len() % 2 == 1 {
+        out.push(0);
+    }
+}
+
+/// A WAV file of `pcm` with a labelled cue point at each chapter start
+fn write_wav(pcm: &Pcm, markers: &[(usize, String)]) -> Vec<u8> {
+    let block_align = pcm.frame_bytes() as u16;
+    let mut format = Vec::new();
+    format.extend_from_slice(&1u16.to_le_bytes());
+    format.extend_from_slice(&pcm.channels.to_le_bytes());
+    format.extend_from_slice(&pcm.sample_rate.to_le_bytes());
+    format.extend_from_slice(&(pcm.sample_rate * block_align as u32).to_le_bytes());
+    format.extend_from_slice(&block_align.to_le_bytes());
+    format.extend_from_slice(&16u16.to_le_bytes());
+
+    let mut cues = (markers.len() as u32).to_le_bytes().to_vec();
+    let mut labels = b"adtl".to_vec();
+    for (i, (frame, label)) in markers.iter().enumerate() {
+        let id = (i as u32 + 1).to_le_bytes();
+        let frame = (*frame as u32).to_le_bytes();
+        cues.extend_from_slice(&id);
+        cues.extend_from_slice(&frame);
+        cues.extend_from_slice(b"data");
+        cues.extend_from_slice(&0u32.to_le_bytes());
+        cues.extend_from_slice(&0u32.to_le_bytes());
+        cues.extend_from_slice(&frame);
+
+        let mut label_body = id.to_vec();
+        label_body.extend_from_slice(label.as_bytes());
+        label_body.push(0);
+        chunk(&mut labels, b"labl", &label_body);
+    }
+
+    let mut body = b"WAVE".to_vec();
+    chunk(&mut body, b"fmt ", &format);
+    chunk(&mut body, b"data", &pcm.data);
+    if !markers.is_empty() {
+        chunk(&mut body, b"cue ", &cues);
+        chunk(&mut body, b"LIST", &labels);
+    }
+    let mut out = Vec::new();
+    chunk(&mut out, b"RIFF", &body);
+    out
+}
+
+/// Speak `text_file` into `wav_file`
+async fn synthesize(
+    text_file: &Path,
+    wav_file: &Path,
+    words_per_minute: u32,
+) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    let mut command = {
+        let mut command = tokio::process::Command::new("say");
+        command
+            .arg("-r")
+            .arg(words_per_minute.to_string())
+            .arg("-f")
+            .arg(text_file)
+            .arg("-o")
+            .arg(wav_file)
+            .arg("--file-format=WAVE")
+            .arg(format!("--data-format=LEI16@{}", SAMPLE_RATE));
+        command
+    };
+
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        // System.Speech rates run from -10 to 10 around roughly 150 wpm
+        let rate = ((words_per_minute as i32 - 150) / 15).clamp(-10, 10);
+        let script = format!(
+            "Add-Type -AssemblyName System.Speech; \
+             $s = New-Object System.Speech.Synthesis.SpeechSynthesizer; \
+             $s.Rate = {}; \
+             $f = New-Object System.Speech.AudioFormat.SpeechAudioFormatInfo({}, \
+               [System.Speech.AudioFormat.AudioBitsPerSample]::Sixteen, \
+               [System.Speech.AudioFormat.AudioChannel]::Mono); \
+             $s.SetOutputToWaveFile($env:CUECARD_TTS_WAV, $f); \
+             $s.Speak([IO.File]::ReadAllText($env:CUECARD_TTS_TEXT)); \
+             $s.Dispose()",
+            rate, SAMPLE_RATE
+        );
+        let mut command = tokio::process::Command::new("powershell.exe");
+        command
+            .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+            .env("CUECARD_TTS_TEXT", text_file)
+            .env("CUECARD_TTS_WAV", wav_file)
+            .creation_flags(CREATE_NO_WINDOW);
+        command
+    };
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let mut command = {
+        let engine = if which_engine("espeak-ng") {
+            "espeak-ng"
+        } else {
+            "espeak"
+        };
+        let mut command = tokio::process::Command::new(engine);
+        command
+            .arg("-s")
+            .arg(words_per_minute.to_string())
+            .arg("-f")
+            .arg(text_file)
+            .arg("-w")
+            .arg(wav_file);
+        command
+    };
+
+    let output = command
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to start the speech engine: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "The speech engine failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn which_engine(name: &str) -> bool {
+    std::process::Command::new(name)
+        .arg("--version")
+        .output()
+        .is_ok_and(|o| o.status.success())
+}
+
+async fn speak(dir: &Path, text: &str, words_per_minute: u32) -> Result<Pcm, String> {
+    let id = uuid::Uuid::new_v4();
+    let text_file = dir.join(format!("{}.txt", id));
+    let wav_file = dir.join(format!("{}.wav", id));
+    std::fs::write(&text_file, text).map_err(|e| format!("Failed to write script: {}", e))?;
+    let result = synthesize(&text_file, &wav_file, words_per_minute).await;
+    let _ = std::fs::remove_file(&text_file);
+    result?;
+    let bytes = std::fs::read(&wav_file).map_err(|e| format!("Failed to read speech: {}", e));
+    let _ = std::fs::remove_file(&wav_file);
+    read_wav(&bytes?)
+}
+
+/// Read a segment's parts with pauses between them; `None` when there's
+/// nothing to say
+async fn speak_parts(
+    dir: &Path,
+    parts: &[String],
+    words_per_minute: u32,
+) -> Result<Option<Pcm>, String> {
+    let mut pcm: Option<Pcm> = None;
+    for (i, part) in parts.iter().enumerate() {
+        if i > 0 {
+            if let Some(pcm) = pcm.as_mut() {
+                pcm.push_silence(PAUSE_MS);
+            }
+        }
+        if part.is_empty() {
+            continue;
+        }
+        let spoken = speak(dir, part, words_per_minute).await?;
+        match pcm.as_mut() {
+            Some(pcm) => pcm.append(spoken)?,
+            None => pcm = Some(spoken),
+        }
+    }
+    Ok(pcm)
+}
+
+/// Read a segment within its budget: faster when it would run over, then
+/// once more at a corrected rate if the engine's pace was off
+async fn speak_segment(
+    dir: &Path,
+    segment: &Segment,
+    words_per_minute: u32,
+) -> Result<Option<Pcm>, String> {
+    let Some(budget_ms) = segment.budget_ms else {
+        return speak_parts(dir, &segment.parts, words_per_minute).await;
+    };
+    let words = segment.words();
+    let speaking_ms = budget_ms.saturating_sub(segment.pauses_ms());
+    let rate = fitted_rate(words, speaking_ms, words_per_minute);
+    let Some(pcm) = speak_parts(dir, &segment.parts, rate).await? else {
+        return Ok(None);
+    };
+
+    let took_ms = pcm.millis(pcm.frames());
+    let over = took_ms * 100 > budget_ms * (100 + BUDGET_TOLERANCE_PERCENT);
+    if !over || rate >= MAX_WORDS_PER_MINUTE {
+        return Ok(Some(pcm));
+    }
+    let spoken_ms = took_ms.saturating_sub(segment.pauses_ms()).max(1);
+    let corrected = ((rate as u64 * spoken_ms).div_ceil(speaking_ms.max(1)))
+        .min(MAX_WORDS_PER_MINUTE as u64) as u32;
+    Ok(speak_parts(dir, &segment.parts, corrected)
+        .await?
+        .or(Some(pcm)))
+}
+
+fn emit_progress(done: usize, total: usize) {
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        let _ = app.emit("script-audio-progress", ScriptAudioProgress { done, total });
+    }
+}
+
+async fn render(
+    dir: &Path,
+    presentation_id: &str,
+    words_per_minute: u32,
+    slide_gap_ms: u32,
+) -> Result<(Pcm, Vec<(usize, AudioChapter)>), String> {
+    let export = notes_export::collect(presentation_id)?;
+    let slides: Vec<_> = export
+        .slides
+        .into_iter()
+        .filter_map(|slide| {
+            let segments = segments(slide.notes.as_deref()?);
+            segments
+                .iter()
+                .any(|s| s.words() > 0)
+                .then_some((slide, segments))
+        })
+        .collect();
+    if slides.is_empty() {
+        return Err("This presentation has no notes to read".to_string());
+    }
+
+    let mut pcm: Option<Pcm> = None;
+    let mut chapters = Vec::new();
+    let total = slides.len();
+    for (done, (slide, segments)) in slides.into_iter().enumerate() {
+        emit_progress(done, total);
+        let start = pcm.as_ref().map(Pcm::frames).unwrap_or(0);
+        for segment in &segments {
+            let spoken = speak_segment(dir, segment, words_per_minute).await?;
+            let took_ms = spoken.as_ref().map_or(0, |p| p.millis(p.frames()));
+            match (pcm.as_mut(), spoken) {
+                (Some(pcm), Some(spoken)) => pcm.append(spoken)?,
+                (None, spoken) => pcm = spoken,
+                (Some(_), None) => {}
+            }
+            // Whatever's left of the budget is kept as silence
+            if let (Some(pcm), Some(budget_ms)) = (pcm.as_mut(), segment.budget_ms) {
+                let rest = budget_ms.saturating_sub(took_ms);
+                pcm.push_silence(rest.min(u32::MAX as u64) as u32);
+            }
+        }
+        let Some(pcm) = pcm.as_mut() else {
+            continue;
+        };
+        let end = pcm.frames();
+        chapters.push((
+            start,
+            AudioChapter {
+                slide_number: slide.slide_number,
+                title: match slide.title {
+                    Some(title) => format!("Slide {}: {}", slide.slide_number, title),
+                    None => format!("Slide {}", slide.slide_number),
+                },
+                start_ms: pcm.millis(start),
+                duration_ms: pcm.millis(end - start),
+            },
+        ));
+        pcm.push_silence(slide_gap_ms);
+    }
+    emit_progress(total, total);
+
+    let pcm = pcm.ok_or("The speech engine produced no audio")?;
+    Ok((pcm, chapters))
+}
+
+/// Read a presentation's notes aloud into a WAV file at `path`, with a
+/// chapter per slide
+#[tauri::command]
+pub async fn export_script_audio(
+    presentation_id: String,
+    path: String,
+    options: Option<ScriptAudioOptions>,
+) -> Result<ScriptAudioExport, CueCardError> {
+    let options = options.unwrap_or_default();
+    let words_per_minute = options
+        .words_per_minute
+        .unwrap_or(DEFAULT_WORDS_PER_MINUTE)
+        .clamp(MIN_WORDS_PER_MINUTE, MAX_WORDS_PER_MINUTE);
+    let slide_gap_ms = options.slide_gap_ms.unwrap_or(DEFAULT_SLIDE_GAP_MS);
+
+    let dir: PathBuf = std::env::temp_dir().join(format!("cuecard-tts-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create temp folder: {}", e))?;
+    let rendered = render(&dir, &presentation_id, words_per_minute, slide_gap_ms).await;
+    let _ = std::fs::remove_dir_all(&dir);
+    let (pcm, chapters) = rendered?;
+
+    let markers: Vec<(usize, String)> = chapters
+        .iter()
+        .map(|(frame, chapter)| (*frame, chapter.title.clone()))
+        .collect();
+    std::fs::write(&path, write_wav(&pcm, &markers))
+        .map_err(|e| format!("Failed to write audio: {}", e))?;
+
+    Ok(ScriptAudioExport {
+        path,
+        duration_ms: pcm.millis(pcm.frames()),
+        chapters: chapters.into_iter().map(|(_, chapter)| chapter).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_segments_at_time_tags() {
+        let notes =
+            "Hello all. [time 0:30] First point [pause] more. [note smile] [time 1:05] Last.";
+        assert_eq!(
+            segments(notes),
+            vec![
+                Segment {
+                    budget_ms: None,
+                    parts: vec!["Hello all.".to_string()],
+                },
+                Segment {
+                    budget_ms: Some(30_000),
+                    parts: vec!["First point".to_string(), "more.".to_string()],
+                },
+                Segment {
+                    budget_ms: Some(65_000),
+                    parts: vec!["Last.".to_string()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_a_budget_with_nothing_to_say() {
+        let segments = segments("[time 0:10][countdown 0:10]");
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].budget_ms, Some(10_000));
+        assert_eq!(segments[0].words(), 0);
+    }
+
+    #[test]
+    fn speeds_up_only_to_fit() {
+        // 150 words in a minute is already the chosen pace
+        assert_eq!(fitted_rate(150, 60_000, 150), 150);
+        assert_eq!(fitted_rate(100, 60_000, 150), 150);
+        assert_eq!(fitted_rate(200, 60_000, 150), 200);
+        assert_eq!(fitted_rate(1000, 60_000, 150), MAX_WORDS_PER_MINUTE);
+        assert_eq!(fitted_rate(10, 0, 150), MAX_WORDS_PER_MINUTE);
+    }
+
+    #[test]
+    fn wav_round_trips_with_markers() {
+        let pcm = Pcm {
+            sample_rate: 22_050,
+            channels: 1,
+            data: (0..=255u8).collect(),
+        };
+        let bytes = write_wav(
+            &pcm,
+            &[(0, "Slide 1".to_string()), (64, "Slide 2".to_string())],
+        );
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(read_u32(&bytes, 4), Some(bytes.len() as u32 - 8));
+        assert!(bytes.windows(7).any(|w| w == b"Slide 2"));
+
+        let read = read_wav(&bytes).unwrap();
+        assert_eq!(read.sample_rate, 22_050);
+        assert_eq!(read.channels, 1);
+        assert_eq!(read.data, pcm.data);
+    }
+
+    #[test]
+    fn reads_a_wav_cut_short_to_whole_frames() {
+        let pcm = Pcm {
+            sample_rate: 44_100,
+            channels: 2,
+            data: vec![1; 16],
+        };
+        let mut bytes = write_wav(&pcm, &[]);
+        // Drop part of the last frame
+        bytes.truncate(bytes.len() - 3);
+        assert_eq!(read_wav(&bytes).unwrap().data.len(), 12);
+    }
+
+    #[test]
+    fn rejects_files_that_are_not_16_bit_wav() {
+        assert!(read_wav(b"not a wav file").is_err());
+
+        let pcm = Pcm {
+            sample_rate: 8_000,
+            channels: 1,
+            data: vec![0; 4],
+        };
+        let mut bytes = write_wav(&pcm, &[]);
+        // Bits per sample of the fmt chunk
+        bytes[34] = 8;
+        assert!(matches!(read_wav(&bytes), Err(e) if e.contains("16-bit")));
+    }
+}
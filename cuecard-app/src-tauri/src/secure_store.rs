@@ -0,0 +1,681 @@
+//! Encryption of `cuecard-store.json` at rest
+//!
+//! The store plugin's (de)serializers are replaced with ones that encrypt the
+//! whole store with AES-256-GCM. The key comes from a passphrase (Argon2id,
+//! salt kept in the file header), from a random secret kept in the OS keychain
+//! (macOS and Windows), or from this machine's id (Argon2id with the same
+//! salt), which ties the file to the machine without asking for anything.
+//!
+//! Stores are encrypted by default: a plaintext store found at startup is
+//! rewritten encrypted with the keychain key, or the machine key where there's
+//! no keychain, and a new store is encrypted from its first save. Turning
+//! encryption off with `disable_store_encryption` is remembered in the
+//! plaintext store and stops that.
+//!
+//! An encrypted store found at startup stays locked, and nothing is read from
+//! or written to it, until `unlock_store` is called; keychain- and
+//! machine-backed stores unlock themselves.
+//!
+//! File layout: magic, key source (1 byte), salt (16), nonce (12), ciphertext.
+//!
+//! The notes `disk_cache` keeps are sealed with the same key (`seal`/`open`),
+//! and rewritten whenever encryption is turned on or off.
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_store::{JsonValue, StoreExt};
+
+use crate::error::CueCardError;
+
+const STORE_FILE: &str = "cuecard-store.json";
+const MAGIC: &[u8] = b"CUECARD-ENC1\n";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const HEADER_LEN: usize = MAGIC.len() + 1 + SALT_LEN + NONCE_LEN;
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+const KEYCHAIN_SERVICE: &str = "com.cuecard.store";
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+const KEYCHAIN_ACCOUNT: &str = "store-key";
+// Set in a plaintext store once encryption has been turned off
+const ENCRYPTION_DISABLED_KEY: &str = "store_encryption_disabled";
+// Mixed into the machine id so the key is CueCard's own
+const MACHINE_KEY_CONTEXT: &str = "com.thisisnsh.cuecard/store";
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Where the store key comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeySource {
+    Passphrase,
+    Keychain,
+    Machine,
+}
+
+impl KeySource {
+    fn to_byte(self) -> u8 {
+        match self {
+            KeySource::Passphrase => 0,
+            KeySource::Keychain => 1,
+            KeySource::Machine => 2,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(KeySource::Passphrase),
+            1 => Some(KeySource::Keychain),
+            2 => Some(KeySource::Machine),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone)]
+enum EncryptionState {
+    Plaintext,
+    Locked {
+        source: KeySource,
+    },
+    Unlocked {
+        source: KeySource,
+        key: [u8; 32],
+        salt: [u8; SALT_LEN],
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StoreEncryptionStatus {
+    pub encrypted: bool,
+    pub locked: bool,
+    pub key_source: Option<KeySource>,
+}
+
+static ENCRYPTION_STATE: Lazy<Arc<RwLock<EncryptionState>>> =
+    Lazy::new(|| Arc::new(RwLock::new(EncryptionState::Plaintext)));
+
+struct Header {
+    source: KeySource,
+    salt: [u8; SALT_LEN],
+}
+
+fn parse_header(bytes: &[u8]) -> Option<Header> {
+    if bytes.len() < HEADER_LEN || !bytes.starts_with(MAGIC) {
+        return None;
+    }
+    let source = KeySource::from_byte(bytes[MAGIC.len()])?;
+    let mut salt = [0u8; SALT_LEN];
+    salt.copy_from_slice(&bytes[MAGIC.len() + 1..MAGIC.len() + 1 + SALT_LEN]);
+    Some(Header { source, salt })
+}
+
+fn derive_key(passphrase: &str, salt: &[u8; SALT_LEN]) -> Result<[u8; 32], String> {
+    let mut key = [0u8; 32];
+    argon2::Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Failed to derive key: {}", e))?;
+    Ok(key)
+}
+
+fn encrypt(
+    plaintext: &[u8],
+    source: KeySource,
+    key: &[u8; 32],
+    salt: &[u8; SALT_LEN],
+) -> Result<Vec<u8>, BoxError> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| "Failed to encrypt store")?;
+
+    let mut out = Vec::with_capacity(HEADER_LEN + ciphertext.len());
+    out.extend_from_slice(MAGIC);
+    out.push(source.to_byte());
+    out.extend_from_slice(salt);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+fn decrypt(bytes: &[u8], key: &[u8; 32]) -> Option<Vec<u8>> {
+    let nonce_start = HEADER_LEN - NONCE_LEN;
+    let nonce = Nonce::from_slice(bytes.get(nonce_start..HEADER_LEN)?);
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    cipher.decrypt(nonce, &bytes[HEADER_LEN..]).ok()
+}
+
+/// The key the notes cache on disk is sealed with: `None` for a plaintext
+/// store, an error while the store is locked
+pub fn cache_key() -> Result<Option<[u8; 32]>, String> {
+    match &*ENCRYPTION_STATE.read() {
+        EncryptionState::Plaintext => Ok(None),
+        EncryptionState::Locked { .. } => Err("Store is locked".to_string()),
+        EncryptionState::Unlocked { key, .. } => Ok(Some(*key)),
+    }
+}
+
+/// Encrypt with `key`: nonce, then ciphertext
+pub fn seal(plaintext: &[u8], key: &[u8; 32]) -> Result<Vec<u8>, String> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| "Failed to encrypt".to_string())?;
+    let mut out = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+/// Decrypt what `seal` wrote
+pub fn open(bytes: &[u8], key: &[u8; 32]) -> Option<Vec<u8>> {
+    let nonce = Nonce::from_slice(bytes.get(..NONCE_LEN)?);
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    cipher.decrypt(nonce, &bytes[NONCE_LEN..]).ok()
+}
+
+/// Rewrite the notes cache for the current key, in the background
+fn reseal_cache(previous_key: Option<[u8; 32]>) {
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(e) = crate::disk_cache::reseal(previous_key) {
+            eprintln!("Failed to reseal the notes cache: {}", e);
+        }
+    });
+}
+
+/// Store plugin serializer
+pub fn serialize(cache: &HashMap<String, JsonValue>) -> Result<Vec<u8>, BoxError> {
+    let json = serde_json::to_vec_pretty(cache)?;
+    match &*ENCRYPTION_STATE.read() {
+        EncryptionState::Plaintext => Ok(json),
+        // Don't overwrite a store we couldn't read
+        EncryptionState::Locked { .. } => Err("Store is locked".into()),
+        EncryptionState::Unlocked { source, key, salt } => encrypt(&json, *source, key, salt),
+    }
+}
+
+/// Store plugin deserializer
+pub fn deserialize(bytes: &[u8]) -> Result<HashMap<String, JsonValue>, BoxError> {
+    if !bytes.starts_with(MAGIC) {
+        return Ok(serde_json::from_slice(bytes)?);
+    }
+    let EncryptionState::Unlocked { key, .. } = &*ENCRYPTION_STATE.read() else {
+        return Err("Store is locked".into());
+    };
+    let plaintext = decrypt(bytes, key).ok_or("Failed to decrypt store")?;
+    Ok(serde_json::from_slice(&plaintext)?)
+}
+
+fn read_store_file(app: &AppHandle) -> Option<Vec<u8>> {
+    let path = tauri_plugin_store::resolve_store_path(app, STORE_FILE).ok()?;
+    std::fs::read(path).ok()
+}
+
+#[cfg(target_os = "linux")]
+fn machine_id() -> Result<String, String> {
+    ["/etc/machine-id", "/var/lib/dbus/machine-id"]
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .map(|id| id.trim().to_string())
+        .find(|id| !id.is_empty())
+        .ok_or_else(|| "Machine id unavailable".to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn machine_id() -> Result<String, String> {
+    let output = std::process::Command::new("ioreg")
+        .args(["-rd1", "-c", "IOPlatformExpertDevice"])
+        .output()
+        .map_err(|e| format!("Failed to read machine id: {}", e))?;
+    // "IOPlatformUUID" = "XXXXXXXX-..."
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find(|line| line.contains("IOPlatformUUID"))
+        .and_then(|line| line.split('"').nth(3))
+        .map(str::to_string)
+        .ok_or_else(|| "Machine id unavailable".to_string())
+}
+
+#[cfg(target_os = "windows")]
+fn machine_id() -> Result<String, String> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    let output = std::process::Command::new("reg")
+        .args([
+            "query",
+            r"HKLM\SOFTWARE\Microsoft\Cryptography",
+            "/v",
+            "MachineGuid",
+        ])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| format!("Failed to read machine id: {}", e))?;
+    // "    MachineGuid    REG_SZ    xxxxxxxx-..."
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find(|line| line.contains("MachineGuid"))
+        .and_then(|line| line.split_whitespace().last())
+        .map(str::to_string)
+        .ok_or_else(|| "Machine id unavailable".to_string())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn machine_id() -> Result<String, String> {
+    Err("Machine keys aren't supported on this platform".to_string())
+}
+
+fn machine_key(salt: &[u8; SALT_LEN]) -> Result<[u8; 32], String> {
+    derive_key(&format!("{}:{}", MACHINE_KEY_CONTEXT, machine_id()?), salt)
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn keychain_entry() -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT)
+        .map_err(|e| format!("Keychain unavailable: {}", e))
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn keychain_key() -> Result<[u8; 32], String> {
+    let secret = keychain_entry()?
+        .get_secret()
+        .map_err(|e| format!("Failed to read store key from keychain: {}", e))?;
+    secret
+        .try_into()
+        .map_err(|_| "Keychain store key is malformed".to_string())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn keychain_key() -> Result<[u8; 32], String> {
+    Err("Keychain storage isn't supported on this platform".to_string())
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn create_keychain_key() -> Result<[u8; 32], String> {
+    let mut key = [0u8; 32];
+    OsRng.fill_bytes(&mut key);
+    keychain_entry()?
+        .set_secret(&key)
+        .map_err(|e| format!("Failed to save store key to keychain: {}", e))?;
+    Ok(key)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn create_keychain_key() -> Result<[u8; 32], String> {
+    Err("Keychain storage isn't supported on this platform".to_string())
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn delete_keychain_key() {
+    if let Ok(entry) = keychain_entry() {
+        let _ = entry.delete_credential();
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn delete_keychain_key() {}
+
+/// A fresh key from the keychain, or the machine key where there's no keychain
+fn default_key(salt: &[u8; SALT_LEN]) -> Result<(KeySource, [u8; 32]), String> {
+    match create_keychain_key() {
+        Ok(key) => Ok((KeySource::Keychain, key)),
+        Err(_) => Ok((KeySource::Machine, machine_key(salt)?)),
+    }
+}
+
+/// Whether encryption was turned off for this plaintext store
+fn encryption_disabled(bytes: &[u8]) -> bool {
+    serde_json::from_slice::<HashMap<String, JsonValue>>(bytes)
+        .ok()
+        .and_then(|cache| cache.get(ENCRYPTION_DISABLED_KEY)?.as_bool())
+        .unwrap_or(false)
+}
+
+/// Check the store file before anything reads it. Keychain- and machine-backed
+/// stores are unlocked here, passphrase ones wait for `unlock_store`, and
+/// plaintext stores are encrypted unless encryption was turned off.
+pub fn init(app: &AppHandle) {
+    let Some(bytes) = read_store_file(app) else {
+        // Encrypt a new store from its first save
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        match default_key(&salt) {
+            Ok((source, key)) => {
+                *ENCRYPTION_STATE.write() = EncryptionState::Unlocked { source, key, salt };
+                reseal_cache(None);
+            }
+            Err(e) => eprintln!("Store will be saved unencrypted: {}", e),
+        }
+        return;
+    };
+    let Some(header) = parse_header(&bytes) else {
+        if !encryption_disabled(&bytes) {
+            if let Err(e) = encrypt_store(app, None) {
+                eprintln!("Failed to encrypt store: {}", e);
+            }
+        }
+        return;
+    };
+
+    *ENCRYPTION_STATE.write() = EncryptionState::Locked {
+        source: header.source,
+    };
+    let key = match header.source {
+        KeySource::Passphrase => return,
+        KeySource::Keychain => keychain_key(),
+        KeySource::Machine => machine_key(&header.salt),
+    };
+    match key {
+        Ok(key) if decrypt(&bytes, &key).is_some() => {
+            *ENCRYPTION_STATE.write() = EncryptionState::Unlocked {
+                source: header.source,
+                key,
+                salt: header.salt,
+            };
+            reseal_cache(None);
+        }
+        Ok(_) => eprintln!("Store key doesn't match the store"),
+        Err(e) => eprintln!("{}", e),
+    }
+}
+
+/// Forget the store key, for a full data wipe; the next save writes plaintext
+pub fn reset() {
+    let previous = std::mem::replace(&mut *ENCRYPTION_STATE.write(), EncryptionState::Plaintext);
+    if matches!(
+        previous,
+        EncryptionState::Locked {
+            source: KeySource::Keychain
+        } | EncryptionState::Unlocked {
+            source: KeySource::Keychain,
+            ..
+        }
+    ) {
+        delete_keychain_key();
+    }
+}
+
+fn status() -> StoreEncryptionStatus {
+    match &*ENCRYPTION_STATE.read() {
+        EncryptionState::Plaintext => StoreEncryptionStatus {
+            encrypted: false,
+            locked: false,
+            key_source: None,
+        },
+        EncryptionState::Locked { source } => StoreEncryptionStatus {
+            encrypted: true,
+            locked: true,
+            key_source: Some(*source),
+        },
+        EncryptionState::Unlocked { source, .. } => StoreEncryptionStatus {
+            encrypted: true,
+            locked: false,
+            key_source: Some(*source),
+        },
+    }
+}
+
+#[tauri::command]
+pub fn get_store_encryption_status() -> StoreEncryptionStatus {
+    status()
+}
+
+/// Unlock an encrypted store and load the settings it holds
+#[tauri::command]
+pub fn unlock_store(app: AppHandle, passphrase: Option<String>) -> Result<(), CueCardError> {
+    let EncryptionState::Locked { source } = ENCRYPTION_STATE.read().clone() else {
+        return Err("Store isn't locked".into());
+    };
+    let bytes = read_store_file(&app).ok_or("Failed to read store")?;
+    let header = parse_header(&bytes).ok_or("Store isn't encrypted")?;
+
+    let key = match source {
+        KeySource::Passphrase => {
+            let passphrase = passphrase.ok_or("Passphrase required")?;
+            derive_key(&passphrase, &header.salt)?
+        }
+        KeySource::Keychain => keychain_key()?,
+        KeySource::Machine => machine_key(&header.salt)?,
+    };
+    if decrypt(&bytes, &key).is_none() {
+        return Err(match source {
+            KeySource::Passphrase => "Incorrect passphrase".into(),
+            KeySource::Keychain => "Keychain store key doesn't match the store".into(),
+            KeySource::Machine => "Store was encrypted on another machine".into(),
+        });
+    }
+
+    *ENCRYPTION_STATE.write() = EncryptionState::Unlocked {
+        source,
+        key,
+        salt: header.salt,
+    };
+    reseal_cache(None);
+
+    let store = app
+        .store(STORE_FILE)
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+    store
+        .reload()
+        .map_err(|e| format!("Failed to load store: {}", e))?;
+    crate::load_settings_from_store(&app);
+    tauri::async_runtime::spawn(crate::restore_session());
+
+    let _ = app.emit("store-unlocked", ());
+    Ok(())
+}
+
+/// Rewrite the plaintext store encrypted, with the given key or the default one
+fn encrypt_store(
+    app: &AppHandle,
+    source: Option<(KeySource, Option<String>)>,
+) -> Result<(), String> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let (key_source, key) = match source {
+        Some((KeySource::Passphrase, passphrase)) => {
+            let passphrase = passphrase.ok_or("Passphrase required")?;
+            if passphrase.is_empty() {
+                return Err("Passphrase can't be empty".to_string());
+            }
+            (KeySource::Passphrase, derive_key(&passphrase, &salt)?)
+        }
+        Some((KeySource::Keychain, _)) => (KeySource::Keychain, create_keychain_key()?),
+        Some((KeySource::Machine, _)) => (KeySource::Machine, machine_key(&salt)?),
+        None => default_key(&salt)?,
+    };
+
+    *ENCRYPTION_STATE.write() = EncryptionState::Unlocked {
+        source: key_source,
+        key,
+        salt,
+    };
+    let result = app
+        .store(STORE_FILE)
+        .map_err(|e| format!("Failed to open store: {}", e))
+        .and_then(|store| {
+            store.delete(ENCRYPTION_DISABLED_KEY);
+            store
+                .save()
+                .map_err(|e| format!("Failed to save store: {}", e))
+        });
+    if let Err(e) = result {
+        *ENCRYPTION_STATE.write() = EncryptionState::Plaintext;
+        if key_source == KeySource::Keychain {
+            delete_keychain_key();
+        }
+        return Err(e);
+    }
+    reseal_cache(None);
+    Ok(())
+}
+
+/// Encrypt the plaintext store, with a passphrase, a keychain secret or the machine key
+#[tauri::command]
+pub fn enable_store_encryption(
+    app: AppHandle,
+    key_source: KeySource,
+    passphrase: Option<String>,
+) -> Result<StoreEncryptionStatus, CueCardError> {
+    if !matches!(*ENCRYPTION_STATE.read(), EncryptionState::Plaintext) {
+        return Err("Store is already encrypted".into());
+    }
+    encrypt_store(&app, Some((key_source, passphrase)))?;
+    Ok(status())
+}
+
+/// Go back to a plaintext store; the store must be unlocked
+#[tauri::command]
+pub fn disable_store_encryption(app: AppHandle) -> Result<StoreEncryptionStatus, CueCardError> {
+    let previous = ENCRYPTION_STATE.read().clone();
+    let EncryptionState::Unlocked { source, key, .. } = previous else {
+        return Err("Store isn't unlocked".into());
+    };
+
+    *ENCRYPTION_STATE.write() = EncryptionState::Plaintext;
+    let result = app
+        .store(STORE_FILE)
+        .map_err(|e| format!("Failed to open store: {}", e))
+        .and_then(|store| {
+            // Keep startup from encrypting it again
+            store.set(ENCRYPTION_DISABLED_KEY, true);
+            store
+                .save()
+                .map_err(|e| format!("Failed to save store: {}", e))
+        });
+    if let Err(e) = result {
+        *ENCRYPTION_STATE.write() = previous;
+        return Err(e.into());
+    }
+    reseal_cache(Some(key));
+    if source == KeySource::Keychain {
+        delete_keychain_key();
+    }
+    Ok(status())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SALT: [u8; SALT_LEN] = [7; SALT_LEN];
+
+    // Serializes tests that set the global encryption state
+    static STATE: parking_lot::Mutex<()> = parking_lot::const_mutex(());
+
+    #[test]
+    fn header_format_round_trips() {
+        let key = derive_key("correct horse", &SALT).unwrap();
+        let file = encrypt(b"{\"a\":1}", KeySource::Passphrase, &key, &SALT).unwrap();
+
+        assert!(file.starts_with(MAGIC));
+        let header = parse_header(&file).unwrap();
+        assert_eq!(header.source, KeySource::Passphrase);
+        assert_eq!(header.salt, SALT);
+        // The salt in the header gives back the same key
+        assert_eq!(derive_key("correct horse", &header.salt).unwrap(), key);
+        assert_eq!(decrypt(&file, &key).unwrap(), b"{\"a\":1}");
+    }
+
+    #[test]
+    fn the_wrong_key_doesnt_decrypt() {
+        let key = derive_key("correct horse", &SALT).unwrap();
+        let wrong = derive_key("battery staple", &SALT).unwrap();
+        let file = encrypt(b"notes", KeySource::Passphrase, &key, &SALT).unwrap();
+        assert!(decrypt(&file, &wrong).is_none());
+
+        let sealed = seal(b"notes", &key).unwrap();
+        assert!(open(&sealed, &wrong).is_none());
+        assert_eq!(open(&sealed, &key).unwrap(), b"notes");
+    }
+
+    #[test]
+    fn truncated_or_garbage_files_dont_panic() {
+        let key = [1u8; 32];
+        let file = encrypt(b"notes", KeySource::Machine, &key, &SALT).unwrap();
+
+        for len in [0, MAGIC.len(), MAGIC.len() + 1, HEADER_LEN - 1, HEADER_LEN] {
+            assert!(decrypt(&file[..len], &key).is_none(), "{} bytes", len);
+        }
+        assert!(parse_header(&file[..HEADER_LEN - 1]).is_none());
+        // Unknown key source
+        let mut garbage = file.clone();
+        garbage[MAGIC.len()] = 9;
+        assert!(parse_header(&garbage).is_none());
+        // Flipped ciphertext fails authentication
+        let mut tampered = file.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(decrypt(&tampered, &key).is_none());
+
+        assert!(open(&[0u8; NONCE_LEN - 1], &key).is_none());
+        assert!(open(&[0u8; NONCE_LEN], &key).is_none());
+        assert!(deserialize(b"\x00\x01 not json").is_err());
+    }
+
+    #[test]
+    fn reports_whether_the_store_is_locked() {
+        let _state = STATE.lock();
+
+        *ENCRYPTION_STATE.write() = EncryptionState::Locked {
+            source: KeySource::Passphrase,
+        };
+        let locked = serde_json::to_value(status()).unwrap();
+        assert_eq!(
+            locked,
+            serde_json::json!({ "encrypted": true, "locked": true, "key_source": "passphrase" })
+        );
+
+        *ENCRYPTION_STATE.write() = EncryptionState::Unlocked {
+            source: KeySource::Keychain,
+            key: [2u8; 32],
+            salt: SALT,
+        };
+        let unlocked = status();
+        assert!(unlocked.encrypted && !unlocked.locked);
+        assert_eq!(unlocked.key_source, Some(KeySource::Keychain));
+        assert_eq!(cache_key(), Ok(Some([2u8; 32])));
+
+        *ENCRYPTION_STATE.write() = EncryptionState::Plaintext;
+        let plain = status();
+        assert!(!plain.encrypted && !plain.locked && plain.key_source.is_none());
+        assert_eq!(cache_key(), Ok(None));
+    }
+
+    #[test]
+    fn serializer_follows_the_lock_state() {
+        let _state = STATE.lock();
+        let mut cache = HashMap::new();
+        cache.insert("key".to_string(), serde_json::json!("value"));
+        let key = [3u8; 32];
+
+        *ENCRYPTION_STATE.write() = EncryptionState::Unlocked {
+            source: KeySource::Machine,
+            key,
+            salt: SALT,
+        };
+        let file = serialize(&cache).unwrap();
+        assert_eq!(parse_header(&file).unwrap().source, KeySource::Machine);
+        assert_eq!(deserialize(&file).unwrap(), cache);
+
+        // A locked store is neither read nor overwritten
+        *ENCRYPTION_STATE.write() = EncryptionState::Locked {
+            source: KeySource::Machine,
+        };
+        assert!(serialize(&cache).is_err());
+        assert!(deserialize(&file).is_err());
+        assert!(cache_key().is_err());
+
+        *ENCRYPTION_STATE.write() = EncryptionState::Plaintext;
+        assert_eq!(
+            serialize(&cache).unwrap(),
+            serde_json::to_vec_pretty(&cache).unwrap()
+        );
+    }
+}
@@ -0,0 +1,606 @@
+//! Presenting session state
+//!
+//! A session is one talk: it starts with the app (or `start_session`) and
+//! holds presenter-specific state that should outlive slide changes, such as
+//! pinned notes and reminders. Pins are sent with every `slide-update` and on
+//! their own as `pins-changed`. Reminders are fired by the `timer` module.
+//! Slide changes and questions are logged for the end-of-session report
+//! (`session_report`), as is the parking lot: tangents to come back to, typed
+//! with `park_note` or spoken while push-to-talk capture is on (the panel's
+//! speech recognizer feeds `submit_transcript`, which hands speech here
+//! instead of to slide inference). From the first slide until the session
+//! ends, the display is kept from sleeping.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::Emitter;
+use uuid::Uuid;
+
+use crate::error::CueCardError;
+use crate::APP_HANDLE;
+
+/// A reminder pinned to the panel across slides
+#[derive(Debug, Clone, Serialize)]
+pub struct PinnedNote {
+    pub id: String,
+    pub text: String,
+    pub created_at: i64,
+}
+
+/// When a reminder fires
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ReminderTrigger {
+    /// Seconds into the talk, by the session clock
+    Elapsed { seconds: i64 },
+    /// Unix seconds
+    WallClock { at: i64 },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Reminder {
+    pub id: String,
+    pub message: String,
+    pub trigger: ReminderTrigger,
+    /// Also show an OS notification
+    pub notify: bool,
+    pub fired: bool,
+}
+
+/// A question from the audience, logged during the talk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Question {
+    pub id: String,
+    pub text: String,
+    pub asked_at: i64,
+    /// Slide on screen when the question was logged
+    pub slide_number: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParkedSource {
+    Typed,
+    Voice,
+}
+
+/// An audience tangent parked for later
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParkedItem {
+    pub id: String,
+    pub text: String,
+    pub parked_at: i64,
+    /// Slide on screen when it was parked
+    pub slide_number: Option<i32>,
+    pub source: ParkedSource,
+}
+
+/// Arrival on a slide
+#[derive(Debug, Clone)]
+pub struct SlideVisit {
+    pub presentation_id: String,
+    pub slide_id: String,
+    pub slide_number: i32,
+    /// Unix millis
+    pub entered_at: i64,
+}
+
+/// What the session report is built from
+#[derive(Debug, Clone)]
+pub struct SessionSnapshot {
+    pub id: String,
+    pub started_at: i64,
+    pub visits: Vec<SlideVisit>,
+    pub questions: Vec<Question>,
+    pub parked: Vec<ParkedItem>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionInfo {
+    pub id: String,
+    pub started_at: i64,
+    pub pinned_notes: Vec<PinnedNote>,
+    pub reminders: Vec<Reminder>,
+}
+
+struct Session {
+    id: String,
+    started_at: i64,
+    pins: Vec<PinnedNote>,
+    reminders: Vec<Reminder>,
+    visits: Vec<SlideVisit>,
+    questions: Vec<Question>,
+    parked: Vec<ParkedItem>,
+}
+
+impl Session {
+    fn new() -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            started_at: chrono::Utc::now().timestamp(),
+            pins: Vec::new(),
+            reminders: Vec::new(),
+            visits: Vec::new(),
+            questions: Vec::new(),
+            parked: Vec::new(),
+        }
+    }
+}
+
+static SESSION: Lazy<Arc<RwLock<Session>>> = Lazy::new(|| Arc::new(RwLock::new(Session::new())));
+// Held while presenting
+static DISPLAY_WAKE: Lazy<Arc<RwLock<Option<display_sleep::Inhibitor>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(None)));
+// Speech heard since push-to-talk started; None when not capturing
+static VOICE_CAPTURE: Lazy<Arc<RwLock<Option<Vec<String>>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(None)));
+
+/// Display sleep and screensaver inhibition, released on drop
+mod display_sleep {
+    #[cfg(target_os = "macos")]
+    mod ffi {
+        use std::ffi::{c_char, c_void};
+
+        pub const UTF8_ENCODING: u32 = 0x0800_0100;
+        pub const ASSERTION_LEVEL_ON: u32 = 255;
+
+        #[link(name = "CoreFoundation", kind = "framework")]
+        extern "C" {
+            pub fn CFStringCreateWithCString(
+                alloc: *const c_void,
+                c_str: *const c_char,
+                encoding: u32,
+            ) -> *const c_void;
+            pub fn CFRelease(cf: *const c_void);
+        }
+
+        #[link(name = "IOKit", kind = "framework")]
+        extern "C" {
+            pub fn IOPMAssertionCreateWithName(
+                assertion_type: *const c_void,
+                level: u32,
+                name: *const c_void,
+                id: *mut u32,
+            ) -> i32;
+            pub fn IOPMAssertionRelease(id: u32) -> i32;
+        }
+    }
+
+    /// An IOKit power assertion
+    #[cfg(target_os = "macos")]
+    pub struct Inhibitor(u32);
+
+    #[cfg(target_os = "macos")]
+    impl Inhibitor {
+        pub fn acquire() -> Option<Self> {
+            unsafe {
+                let kind = ffi::CFStringCreateWithCString(
+                    std::ptr::null(),
+                    c"PreventUserIdleDisplaySleep".as_ptr(),
+                    ffi::UTF8_ENCODING,
+                );
+                let name = ffi::CFStringCreateWithCString(
+                    std::ptr::null(),
+                    c"CueCard presentation".as_ptr(),
+                    ffi::UTF8_ENCODING,
+                );
+                let mut id = 0;
+                let result =
+                    ffi::IOPMAssertionCreateWithName(kind, ffi::ASSERTION_LEVEL_ON, name, &mut id);
+                ffi::CFRelease(kind);
+                ffi::CFRelease(name);
+                if result != 0 {
+                    eprintln!("Failed to prevent display sleep: IOReturn {}", result);
+                    return None;
+                }
+                Some(Self(id))
+            }
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    impl Drop for Inhibitor {
+        fn drop(&mut self) {
+            unsafe {
+                ffi::IOPMAssertionRelease(self.0);
+            }
+        }
+    }
+
+    /// Execution state is per thread, so a thread holds it until the sender drops
+    #[cfg(all(target_os = "windows", feature = "desktop"))]
+    pub struct Inhibitor {
+        _release: std::sync::mpsc::Sender<()>,
+    }
+
+    #[cfg(all(target_os = "windows", feature = "desktop"))]
+    impl Inhibitor {
+        pub fn acquire() -> Option<Self> {
+            use windows::Win32::System::Power::{
+                SetThreadExecutionState, ES_CONTINUOUS, ES_DISPLAY_REQUIRED, ES_SYSTEM_REQUIRED,
+            };
+
+            let (release, released) = std::sync::mpsc::channel::<()>();
+            std::thread::spawn(move || unsafe {
+                if SetThreadExecutionState(ES_CONTINUOUS | ES_DISPLAY_REQUIRED | ES_SYSTEM_REQUIRED)
+                    .0
+                    == 0
+                {
+                    eprintln!("Failed to prevent display sleep");
+                    return;
+                }
+                // Returns once the inhibitor is dropped
+                let _ = released.recv();
+                SetThreadExecutionState(ES_CONTINUOUS);
+            });
+            Some(Self { _release: release })
+        }
+    }
+
+    #[cfg(not(any(target_os = "macos", all(target_os = "windows", feature = "desktop"))))]
+    pub struct Inhibitor;
+
+    #[cfg(not(any(target_os = "macos", all(target_os = "windows", feature = "desktop"))))]
+    impl Inhibitor {
+        pub fn acquire() -> Option<Self> {
+            None
+        }
+    }
+}
+
+/// Id and start time of the current session
+pub fn current_session() -> (String, i64) {
+    let session = SESSION.read();
+    (session.id.clone(), session.started_at)
+}
+
+pub fn snapshot() -> SessionSnapshot {
+    let session = SESSION.read();
+    SessionSnapshot {
+        id: session.id.clone(),
+        started_at: session.started_at,
+        visits: session.visits.clone(),
+        questions: session.questions.clone(),
+        parked: session.parked.clone(),
+    }
+}
+
+/// Log arrival on a slide; repeated updates for the same slide are ignored
+pub fn record_slide_visit(slide: &crate::SlideData) {
+    let mut session = SESSION.write();
+    let same_slide = session.visits.last().is_some_and(|v| {
+        v.presentation_id == slide.presentation_id && v.slide_id == slide.slide_id
+    });
+    if same_slide {
+        return;
+    }
+    if session.visits.is_empty() {
+        let mut wake = DISPLAY_WAKE.write();
+        if wake.is_none() {
+            *wake = display_sleep::Inhibitor::acquire();
+        }
+    }
+    session.visits.push(SlideVisit {
+        presentation_id: slide.presentation_id.clone(),
+        slide_id: slide.slide_id.clone(),
+        slide_number: slide.slide_number,
+        entered_at: chrono::Utc::now().timestamp_millis(),
+    });
+}
+
+pub fn pinned_notes() -> Vec<PinnedNote> {
+    SESSION.read().pins.clone()
+}
+
+/// Mark and return reminders that are due; elapsed triggers wait for the clock to start
+pub fn take_due_reminders(elapsed_secs: Option<i64>, now: i64) -> Vec<Reminder> {
+    let mut session = SESSION.write();
+    let mut due = Vec::new();
+    for reminder in session.reminders.iter_mut().filter(|r| !r.fired) {
+        let is_due = match reminder.trigger {
+            ReminderTrigger::Elapsed { seconds } => elapsed_secs.is_some_and(|e| e >= seconds),
+            ReminderTrigger::WallClock { at } => now >= at,
+        };
+        if is_due {
+            reminder.fired = true;
+            due.push(reminder.clone());
+        }
+    }
+    due
+}
+
+/// Let elapsed-time reminders fire again after the clock is reset
+pub fn rearm_elapsed_reminders() {
+    for reminder in SESSION.write().reminders.iter_mut() {
+        if matches!(reminder.trigger, ReminderTrigger::Elapsed { .. }) {
+            reminder.fired = false;
+        }
+    }
+}
+
+fn emit_pins_changed() {
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        let _ = app.emit("pins-changed", pinned_notes());
+    }
+}
+
+fn session_info() -> SessionInfo {
+    let session = SESSION.read();
+    SessionInfo {
+        id: session.id.clone(),
+        started_at: session.started_at,
+        pinned_notes: session.pins.clone(),
+        reminders: session.reminders.clone(),
+    }
+}
+
+fn emit_parking_lot_changed() {
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        let _ = app.emit("parking-lot-changed", SESSION.read().parked.clone());
+    }
+}
+
+fn park(text: String, source: ParkedSource) -> ParkedItem {
+    let item = ParkedItem {
+        id: Uuid::new_v4().to_string(),
+        text,
+        parked_at: chrono::Utc::now().timestamp(),
+        slide_number: crate::CURRENT_SLIDE.read().as_ref().map(|s| s.slide_number),
+        source,
+    };
+    SESSION.write().parked.push(item.clone());
+    emit_parking_lot_changed();
+    item
+}
+
+/// Keep recognized speech while push-to-talk is on; false when not capturing
+pub fn capture_transcript(text: &str) -> bool {
+    match VOICE_CAPTURE.write().as_mut() {
+        Some(heard) => {
+            heard.push(text.trim().to_string());
+            true
+        }
+        None => false,
+    }
+}
+
+/// Summarize the current session (if anything was presented) and replace it
+fn finish_session() -> Option<crate::session_report::SessionSummary> {
+    let summary = crate::session_report::finish(snapshot());
+    *SESSION.write() = Session::new();
+    *DISPLAY_WAKE.write() = None;
+    *VOICE_CAPTURE.write() = None;
+    crate::timer::reset();
+    crate::timer::reset_sections();
+    emit_pins_changed();
+    emit_parking_lot_changed();
+    summary
+}
+
+/// End the current session and start a fresh one
+#[tauri::command]
+pub fn start_session() -> SessionInfo {
+    finish_session();
+    session_info()
+}
+
+/// End the current session, returning its report summary
+#[tauri::command]
+pub fn end_session() -> Option<crate::session_report::SessionSummary> {
+    finish_session()
+}
+
+#[tauri::command]
+pub fn get_session() -> SessionInfo {
+    session_info()
+}
+
+#[tauri::command]
+pub fn pin_note(text: String) -> Result<PinnedNote, CueCardError> {
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Err("Pinned note can't be empty".into());
+    }
+
+    let pin = PinnedNote {
+        id: Uuid::new_v4().to_string(),
+        text,
+        created_at: chrono::Utc::now().timestamp(),
+    };
+    SESSION.write().pins.push(pin.clone());
+    emit_pins_changed();
+    Ok(pin)
+}
+
+#[tauri::command]
+pub fn unpin_note(id: String) -> Result<(), CueCardError> {
+    {
+        let mut session = SESSION.write();
+        let before = session.pins.len();
+        session.pins.retain(|p| p.id != id);
+        if session.pins.len() == before {
+            return Err("Unknown pinned note".into());
+        }
+    }
+    emit_pins_changed();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn clear_pinned_notes() {
+    SESSION.write().pins.clear();
+    emit_pins_changed();
+}
+
+#[tauri::command]
+pub fn add_reminder(
+    message: String,
+    trigger: ReminderTrigger,
+    notify: bool,
+) -> Result<Reminder, CueCardError> {
+    let message = message.trim().to_string();
+    if message.is_empty() {
+        return Err("Reminder message can't be empty".into());
+    }
+
+    let reminder = Reminder {
+        id: Uuid::new_v4().to_string(),
+        message,
+        trigger,
+        notify,
+        fired: false,
+    };
+    SESSION.write().reminders.push(reminder.clone());
+    Ok(reminder)
+}
+
+/// A reminder for a clock time ("14:30", optionally on a "2026-10-15") in the
+/// event's time zone, stored as the instant it refers to
+#[tauri::command]
+pub fn add_local_time_reminder(
+    message: String,
+    time: String,
+    date: Option<String>,
+    notify: bool,
+) -> Result<Reminder, CueCardError> {
+    let at = crate::event_time::resolve_local_time(&time, date.as_deref())?;
+    add_reminder(message, ReminderTrigger::WallClock { at }, notify)
+}
+
+#[tauri::command]
+pub fn remove_reminder(id: String) -> Result<(), CueCardError> {
+    let mut session = SESSION.write();
+    let before = session.reminders.len();
+    session.reminders.retain(|r| r.id != id);
+    if session.reminders.len() == before {
+        return Err("Unknown reminder".into());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_reminders() -> Vec<Reminder> {
+    SESSION.read().reminders.clone()
+}
+
+#[tauri::command]
+pub fn log_question(text: String) -> Result<Question, CueCardError> {
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Err("Question can't be empty".into());
+    }
+
+    let slide_number = crate::CURRENT_SLIDE.read().as_ref().map(|s| s.slide_number);
+    let question = Question {
+        id: Uuid::new_v4().to_string(),
+        text,
+        asked_at: chrono::Utc::now().timestamp(),
+        slide_number,
+    };
+    SESSION.write().questions.push(question.clone());
+    Ok(question)
+}
+
+#[tauri::command]
+pub fn park_note(text: String) -> Result<ParkedItem, CueCardError> {
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Err("Parked note can't be empty".into());
+    }
+    Ok(park(text, ParkedSource::Typed))
+}
+
+#[tauri::command]
+pub fn list_parked_notes() -> Vec<ParkedItem> {
+    SESSION.read().parked.clone()
+}
+
+/// Start push-to-talk: speech goes to the parking lot until capture stops
+#[tauri::command]
+pub fn start_voice_capture() {
+    {
+        let mut capture = VOICE_CAPTURE.write();
+        if capture.is_some() {
+            return;
+        }
+        *capture = Some(Vec::new());
+    }
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        let _ = app.emit("voice-capture-changed", true);
+    }
+}
+
+/// Stop push-to-talk and park what was heard, if anything
+#[tauri::command]
+pub fn stop_voice_capture() -> Option<ParkedItem> {
+    let heard = VOICE_CAPTURE.write().take()?;
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        let _ = app.emit("voice-capture-changed", false);
+    }
+
+    let text = heard
+        .iter()
+        .filter(|t| !t.is_empty())
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if text.is_empty() {
+        return None;
+    }
+    Some(park(text, ParkedSource::Voice))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reminder(id: &str, trigger: ReminderTrigger) -> Reminder {
+        Reminder {
+            id: id.to_string(),
+            message: format!("Reminder {}", id),
+            trigger,
+            notify: false,
+            fired: false,
+        }
+    }
+
+    fn ids(reminders: &[Reminder]) -> Vec<&str> {
+        reminders.iter().map(|r| r.id.as_str()).collect()
+    }
+
+    #[test]
+    fn reminders_fire_once_when_due() {
+        SESSION.write().reminders = vec![
+            reminder("five-minutes", ReminderTrigger::Elapsed { seconds: 300 }),
+            reminder("ten-minutes", ReminderTrigger::Elapsed { seconds: 600 }),
+            reminder("at-noon", ReminderTrigger::WallClock { at: 1_000 }),
+        ];
+
+        // Elapsed triggers wait for the clock to start
+        assert_eq!(ids(&take_due_reminders(None, 999)), Vec::<&str>::new());
+        assert_eq!(ids(&take_due_reminders(None, 1_000)), vec!["at-noon"]);
+        assert_eq!(
+            ids(&take_due_reminders(Some(299), 2_000)),
+            Vec::<&str>::new()
+        );
+        assert_eq!(
+            ids(&take_due_reminders(Some(300), 2_000)),
+            vec!["five-minutes"]
+        );
+        assert_eq!(
+            ids(&take_due_reminders(Some(900), 2_000)),
+            vec!["ten-minutes"]
+        );
+        assert!(take_due_reminders(Some(900), 3_000).is_empty());
+
+        // A clock reset lets elapsed reminders fire again, not wall clock ones
+        rearm_elapsed_reminders();
+        assert_eq!(
+            ids(&take_due_reminders(Some(900), 3_000)),
+            vec!["five-minutes", "ten-minutes"]
+        );
+        SESSION.write().reminders.clear();
+    }
+}
@@ -0,0 +1,417 @@
+//! End-of-session report
+//!
+//! When a session ends (`end_session`, or `start_session` replacing it), what
+//! was presented is summarized: talk duration, time spent per slide against
+//! the `[time mm:ss]` budgets in its notes, questions logged, the parking lot,
+//! and slides with notes that were never shown. The last summary is kept in
+//! the store and can be exported as Markdown or HTML.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+use tauri::Emitter;
+use tauri_plugin_store::StoreExt;
+
+use crate::error::CueCardError;
+use crate::session::{ParkedItem, Question, SessionSnapshot};
+use crate::{event_time, slide_mapping, timer, APP_HANDLE, SLIDE_NOTES};
+
+const LAST_SUMMARY_KEY: &str = "last_session_summary";
+
+// Same syntax the panel uses for section timers
+static TIME_TAG: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)\[time\s+(\d{1,2}):(\d{2})\]").expect("valid time tag regex"));
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlideTime {
+    pub presentation_id: String,
+    pub slide_id: String,
+    pub slide_number: i32,
+    pub time_secs: i64,
+    /// Sum of the `[time]` tags in the slide's notes
+    pub planned_secs: Option<i64>,
+    /// Positive when over budget
+    pub deviation_secs: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedSlide {
+    pub presentation_id: String,
+    pub slide_id: String,
+    pub slide_number: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub session_id: String,
+    pub started_at: i64,
+    pub ended_at: i64,
+    /// By the session clock
+    pub duration_secs: i64,
+    /// In order of first visit
+    pub slides: Vec<SlideTime>,
+    pub questions: Vec<Question>,
+    /// Summaries stored before the parking lot existed have none
+    #[serde(default)]
+    pub parked: Vec<ParkedItem>,
+    pub skipped_slides: Vec<SkippedSlide>,
+}
+
+static LAST_SUMMARY: Lazy<Arc<RwLock<Option<SessionSummary>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(None)));
+
+fn planned_secs(notes: &str) -> Option<i64> {
+    let mut total = None;
+    for caps in TIME_TAG.captures_iter(notes) {
+        let minutes: i64 = caps[1].parse().unwrap_or(0);
+        let seconds: i64 = caps[2].parse().unwrap_or(0);
+        *total.get_or_insert(0) += minutes * 60 + seconds;
+    }
+    total
+}
+
+fn summarize(snapshot: SessionSnapshot, duration_secs: i64) -> SessionSummary {
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let notes = SLIDE_NOTES.read();
+    let notes_for = |presentation_id: &str, slide_id: &str| {
+        notes
+            .get(&format!("{}:{}", presentation_id, slide_id))
+            .cloned()
+    };
+
+    // Each visit lasts until the next one; the last until now
+    let mut slides: Vec<SlideTime> = Vec::new();
+    for (i, visit) in snapshot.visits.iter().enumerate() {
+        let left_at = snapshot
+            .visits
+            .get(i + 1)
+            .map_or(now_ms, |next| next.entered_at);
+        let secs = (left_at - visit.entered_at).max(0) / 1000;
+        let existing = slides
+            .iter_mut()
+            .find(|s| s.presentation_id == visit.presentation_id && s.slide_id == visit.slide_id);
+        match existing {
+            Some(slide) => slide.time_secs += secs,
+            None => slides.push(SlideTime {
+                presentation_id: visit.presentation_id.clone(),
+                slide_id: visit.slide_id.clone(),
+                slide_number: visit.slide_number,
+                time_secs: secs,
+                planned_secs: None,
+                deviation_secs: None,
+            }),
+        }
+    }
+    for slide in slides.iter_mut() {
+        slide.planned_secs =
+            notes_for(&slide.presentation_id, &slide.slide_id).and_then(|n| planned_secs(&n));
+        slide.deviation_secs = slide.planned_secs.map(|planned| slide.time_secs - planned);
+    }
+
+    // Deck order is only known for the presentation that is still current
+    let mut skipped_slides = Vec::new();
+    if let Some(last) = snapshot.visits.last() {
+        let visited: HashSet<&str> = snapshot
+            .visits
+            .iter()
+            .filter(|v| v.presentation_id == last.presentation_id)
+            .map(|v| v.slide_id.as_str())
+            .collect();
+        for (i, slide_id) in slide_mapping::shown_slides().iter().enumerate() {
+            let has_notes =
+                notes_for(&last.presentation_id, slide_id).is_some_and(|n| !n.trim().is_empty());
+            if has_notes && !visited.contains(slide_id.as_str()) {
+                skipped_slides.push(SkippedSlide {
+                    presentation_id: last.presentation_id.clone(),
+                    slide_id: slide_id.clone(),
+                    slide_number: i as i32 + 1,
+                });
+            }
+        }
+    }
+
+    SessionSummary {
+        session_id: snapshot.id,
+        started_at: snapshot.started_at,
+        ended_at: now_ms / 1000,
+        duration_secs,
+        slides,
+        questions: snapshot.questions,
+        parked: snapshot.parked,
+        skipped_slides,
+    }
+}
+
+/// Summarize an ending session and keep it as the last summary. Sessions where
+/// nothing was presented leave the previous summary in place.
+pub fn finish(snapshot: SessionSnapshot) -> Option<SessionSummary> {
+    if snapshot.visits.is_empty() {
+        return None;
+    }
+    let summary = summarize(snapshot, timer::elapsed_secs().unwrap_or(0));
+    *LAST_SUMMARY.write() = Some(summary.clone());
+
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        if let Ok(store) = app.store("cuecard-store.json") {
+            if let Ok(json) = serde_json::to_value(&summary) {
+                store.set(LAST_SUMMARY_KEY, json);
+                let _ = store.save();
+            }
+        }
+        let _ = app.emit("session-summary", summary.clone());
+    }
+    Some(summary)
+}
+
+fn last_summary() -> Option<SessionSummary> {
+    if let Some(summary) = LAST_SUMMARY.read().clone() {
+        return Some(summary);
+    }
+    // From an earlier run of the app
+    let app = APP_HANDLE.read();
+    let summary: SessionSummary = app
+        .as_ref()?
+        .store("cuecard-store.json")
+        .ok()?
+        .get(LAST_SUMMARY_KEY)
+        .and_then(|json| serde_json::from_value(json).ok())?;
+    *LAST_SUMMARY.write() = Some(summary.clone());
+    Some(summary)
+}
+
+/// Forget the last summary if its session ended before `cutoff` (Unix seconds)
+pub fn remove_summary_before(cutoff: i64) -> bool {
+    if last_summary().is_none_or(|s| s.ended_at >= cutoff) {
+        return false;
+    }
+    *LAST_SUMMARY.write() = None;
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        if let Ok(store) = app.store("cuecard-store.json") {
+            let _ = store.delete(LAST_SUMMARY_KEY);
+            let _ = store.save();
+        }
+    }
+    true
+}
+
+fn format_duration(secs: i64) -> String {
+    let sign = if secs < 0 { "-" } else { "" };
+    let secs = secs.abs();
+    if secs >= 3600 {
+        format!(
+            "{}{}:{:02}:{:02}",
+            sign,
+            secs / 3600,
+            secs / 60 % 60,
+            secs % 60
+        )
+    } else {
+        format!("{}{}:{:02}", sign, secs / 60, secs % 60)
+    }
+}
+
+fn format_deviation(secs: i64) -> String {
+    if secs > 0 {
+        format!("+{}", format_duration(secs))
+    } else {
+        format_duration(secs)
+    }
+}
+
+/// In the event's time zone, which may not be the laptop's
+fn format_timestamp(secs: i64) -> String {
+    event_time::format_instant(secs, "%Y-%m-%d %H:%M %Z")
+}
+
+fn render_markdown(summary: &SessionSummary) -> String {
+    let mut out = String::from("# Session report\n\n");
+    out.push_str(&format!(
+        "- Started: {}\n- Ended: {}\n- Duration: {}\n",
+        format_timestamp(summary.started_at),
+        format_timestamp(summary.ended_at),
+        format_duration(summary.duration_secs)
+    ));
+
+    out.push_str(
+        "\n## Time per slide\n\n| Slide | Time | Planned | Deviation |\n|---|---|---|---|\n",
+    );
+    for slide in &summary.slides {
+        out.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            slide.slide_number,
+            format_duration(slide.time_secs),
+            slide.planned_secs.map(format_duration).unwrap_or_default(),
+            slide
+                .deviation_secs
+                .map(format_deviation)
+                .unwrap_or_default()
+        ));
+    }
+
+    out.push_str("\n## Questions\n\n");
+    if summary.questions.is_empty() {
+        out.push_str("None\n");
+    }
+    for question in &summary.questions {
+        match question.slide_number {
+            Some(n) => out.push_str(&format!("- {} (slide {})\n", question.text, n)),
+            None => out.push_str(&format!("- {}\n", question.text)),
+        }
+    }
+
+    out.push_str("\n## Parking lot\n\n");
+    if summary.parked.is_empty() {
+        out.push_str("None\n");
+    }
+    for item in &summary.parked {
+        match item.slide_number {
+            Some(n) => out.push_str(&format!("- {} (slide {})\n", item.text, n)),
+            None => out.push_str(&format!("- {}\n", item.text)),
+        }
+    }
+
+    out.push_str("\n## Skipped slides\n\n");
+    if summary.skipped_slides.is_empty() {
+        out.push_str("None\n");
+    }
+    for slide in &summary.skipped_slides {
+        out.push_str(&format!("- Slide {}\n", slide.slide_number));
+    }
+    out
+}
+
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn render_html(summary: &SessionSummary) -> String {
+    let mut out = String::from(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>Session report</title></head>\n<body>\n<h1>Session report</h1>\n",
+    );
+    out.push_str(&format!(
+        "<ul>\n<li>Started: {}</li>\n<li>Ended: {}</li>\n<li>Duration: {}</li>\n</ul>\n",
+        format_timestamp(summary.started_at),
+        format_timestamp(summary.ended_at),
+        format_duration(summary.duration_secs)
+    ));
+
+    out.push_str("<h2>Time per slide</h2>\n<table>\n<tr><th>Slide</th><th>Time</th><th>Planned</th><th>Deviation</th></tr>\n");
+    for slide in &summary.slides {
+        out.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            slide.slide_number,
+            format_duration(slide.time_secs),
+            slide.planned_secs.map(format_duration).unwrap_or_default(),
+            slide
+                .deviation_secs
+                .map(format_deviation)
+                .unwrap_or_default()
+        ));
+    }
+    out.push_str("</table>\n");
+
+    out.push_str("<h2>Questions</h2>\n");
+    if summary.questions.is_empty() {
+        out.push_str("<p>None</p>\n");
+    } else {
+        out.push_str("<ul>\n");
+        for question in &summary.questions {
+            match question.slide_number {
+                Some(n) => out.push_str(&format!(
+                    "<li>{} (slide {})</li>\n",
+                    escape_html(&question.text),
+                    n
+                )),
+                None => out.push_str(&format!("<li>{}</li>\n", escape_html(&question.text))),
+            }
+        }
+        out.push_str("</ul>\n");
+    }
+
+    out.push_str("<h2>Parking lot</h2>\n");
+    if summary.parked.is_empty() {
+        out.push_str("<p>None</p>\n");
+    } else {
+        out.push_str("<ul>\n");
+        for item in &summary.parked {
+            match item.slide_number {
+                Some(n) => out.push_str(&format!(
+                    "<li>{} (slide {})</li>\n",
+                    escape_html(&item.text),
+                    n
+                )),
+                None => out.push_str(&format!("<li>{}</li>\n", escape_html(&item.text))),
+            }
+        }
+        out.push_str("</ul>\n");
+    }
+
+    out.push_str("<h2>Skipped slides</h2>\n");
+    if summary.skipped_slides.is_empty() {
+        out.push_str("<p>None</p>\n");
+    } else {
+        out.push_str("<ul>\n");
+        for slide in &summary.skipped_slides {
+            out.push_str(&format!("<li>Slide {}</li>\n", slide.slide_number));
+        }
+        out.push_str("</ul>\n");
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+#[tauri::command]
+pub fn get_last_session_summary() -> Option<SessionSummary> {
+    last_summary()
+}
+
+/// Write the last session's report; `.html`/`.htm` paths get HTML, anything else Markdown
+#[tauri::command]
+pub fn export_session_report(path: String) -> Result<(), CueCardError> {
+    let summary = last_summary().ok_or("No finished session to report on")?;
+
+    let is_html = Path::new(&path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("html") || e.eq_ignore_ascii_case("htm"));
+    let report = if is_html {
+        render_html(&summary)
+    } else {
+        render_markdown(&summary)
+    };
+
+    std::fs::write(&path, report).map_err(|e| format!("Failed to write session report: {}", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sums_the_time_tags() {
+        assert_eq!(
+            planned_secs("[time 1:30] Intro [TIME 0:45] Demo"),
+            Some(135)
+        );
+        assert_eq!(planned_secs("[time 12:05]"), Some(725));
+        // A zero budget is still a plan
+        assert_eq!(planned_secs("[time 0:00]"), Some(0));
+    }
+
+    #[test]
+    fn no_plan_without_well_formed_tags() {
+        assert_eq!(planned_secs("Just notes"), None);
+        assert_eq!(planned_secs("[time 90]"), None);
+        assert_eq!(planned_secs("[time 1:5]"), None);
+        assert_eq!(planned_secs("[countdown 1:00]"), None);
+    }
+}
@@ -0,0 +1,309 @@
+//! Read-only share link for live notes
+//!
+//! `start_notes_share` opens a small web view on the local network so a co-host
+//! can follow the current slide and its notes from a browser. It runs on its
+//! own port, separate from the extension server on 127.0.0.1, and serves only
+//! `/share/{token}`: a page that polls `/share/{token}/state`. The token is
+//! random and the share stops by itself when it expires. The `notes` channel
+//! shows the notes as displayed in the panel, masking included; the
+//! `interpreter` channel shows interpreter notes instead, for the current
+//! slide and the one coming up (`interpreter`).
+
+use axum::{
+    extract::Path,
+    http::StatusCode,
+    response::{Html, IntoResponse, Json, Response},
+    routing::get,
+    Router,
+};
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::config_file;
+use crate::error::CueCardError;
+use crate::interpreter::{self, InterpreterView};
+use crate::CURRENT_SLIDE;
+
+// Clear of the local server's port range, so the two never collide
+const SHARE_PORT: u16 = 3660;
+const DEFAULT_SHARE_MINUTES: u32 = 60;
+const MAX_SHARE_MINUTES: u32 = 240;
+
+/// What a share link shows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShareChannel {
+    /// The speaker notes
+    #[default]
+    Notes,
+    /// Interpreter notes, with the slide coming up
+    Interpreter,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NotesShare {
+    pub url: String,
+    pub channel: ShareChannel,
+    pub expires_at: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct ShareState {
+    presentation_id: Option<String>,
+    slide_number: Option<i32>,
+    title: Option<String>,
+    notes: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    interpreter: Option<InterpreterView>,
+    expires_at: i64,
+}
+
+struct ActiveShare {
+    token: String,
+    info: NotesShare,
+    stop: tokio::sync::oneshot::Sender<()>,
+    server: tauri::async_runtime::JoinHandle<()>,
+}
+
+impl ActiveShare {
+    /// Ask the server to shut down, returning its task to wait on
+    fn stop(self) -> tauri::async_runtime::JoinHandle<()> {
+        let _ = self.stop.send(());
+        self.server
+    }
+}
+
+static ACTIVE_SHARE: Lazy<Arc<RwLock<Option<ActiveShare>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(None)));
+
+const SHARE_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>CueCard notes</title>
+<style>
+body { font-family: -apple-system, "Segoe UI", sans-serif; margin: 0; padding: 24px; background: #111; color: #eee; }
+#slide { color: #999; margin-bottom: 16px; }
+#notes, #ahead { font-size: 1.4em; line-height: 1.5; white-space: pre-wrap; }
+#ahead-label { color: #999; margin: 32px 0 16px; }
+#ahead { opacity: 0.7; }
+</style>
+</head>
+<body>
+<div id="slide">Waiting for the presenter...</div>
+<div id="notes"></div>
+<div id="ahead-label"></div>
+<div id="ahead"></div>
+<script>
+async function refresh() {
+  try {
+    const response = await fetch(location.pathname.replace(/\/$/, "") + "/state");
+    if (!response.ok) {
+      document.getElementById("slide").textContent = "This share link has ended.";
+      document.getElementById("notes").textContent = "";
+      return;
+    }
+    const state = await response.json();
+    if (state.slide_number) {
+      document.getElementById("slide").textContent =
+        "Slide " + state.slide_number + (state.title ? " — " + state.title : "");
+    }
+    if (state.interpreter) {
+      const current = state.interpreter.current;
+      const ahead = state.interpreter.ahead;
+      document.getElementById("notes").textContent = (current && current.notes) || "";
+      document.getElementById("ahead-label").textContent = ahead ? "Coming up: slide " + ahead.slide_number : "";
+      document.getElementById("ahead").textContent = (ahead && ahead.notes) || "";
+    } else {
+      document.getElementById("notes").textContent = state.notes || "";
+    }
+  } catch (e) {}
+  setTimeout(refresh, 2000);
+}
+refresh();
+</script>
+</body>
+</html>
+"#;
+
+fn is_valid(token: &str) -> bool {
+    let now = chrono::Utc::now().timestamp();
+    ACTIVE_SHARE
+        .read()
+        .as_ref()
+        .is_some_and(|s| s.token == token && s.info.expires_at > now)
+}
+
+async fn page_handler(Path(token): Path<String>) -> Response {
+    if !is_valid(&token) {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    Html(SHARE_PAGE).into_response()
+}
+
+async fn state_handler(Path(token): Path<String>) -> Response {
+    if !is_valid(&token) {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let (channel, expires_at) = ACTIVE_SHARE
+        .read()
+        .as_ref()
+        .map_or((ShareChannel::Notes, 0), |s| {
+            (s.info.channel, s.info.expires_at)
+        });
+    let slide = CURRENT_SLIDE.read().clone();
+    let (notes, interpreter) = match channel {
+        ShareChannel::Notes => (crate::get_current_notes(), None),
+        ShareChannel::Interpreter => (None, Some(interpreter::view())),
+    };
+    Json(ShareState {
+        presentation_id: slide.as_ref().map(|s| s.presentation_id.clone()),
+        slide_number: slide.as_ref().map(|s| s.slide_number),
+        title: slide.as_ref().map(|s| s.title.clone()),
+        notes,
+        interpreter,
+        expires_at,
+    })
+    .into_response()
+}
+
+/// This machine's address on the LAN; connecting a UDP socket sends nothing
+fn lan_address() -> Result<std::net::IpAddr, String> {
+    let socket = std::net::UdpSocket::bind("0.0.0.0:0")
+        .map_err(|e| format!("Failed to find LAN address: {}", e))?;
+    socket
+        .connect("8.8.8.8:80")
+        .map_err(|e| format!("Not connected to a network: {}", e))?;
+    socket
+        .local_addr()
+        .map(|a| a.ip())
+        .map_err(|e| format!("Failed to find LAN address: {}", e))
+}
+
+/// Publish the live notes to a token-protected page on the LAN, replacing any earlier share
+#[tauri::command]
+pub async fn start_notes_share(
+    minutes: Option<u32>,
+    channel: Option<ShareChannel>,
+) -> Result<NotesShare, CueCardError> {
+    // Wait for an earlier share to release the port
+    let previous = ACTIVE_SHARE.write().take();
+    if let Some(share) = previous {
+        let _ = share.stop().await;
+    }
+
+    let minutes = minutes
+        .unwrap_or(DEFAULT_SHARE_MINUTES)
+        .clamp(1, MAX_SHARE_MINUTES);
+    let address = lan_address()?;
+    let port = config_file::share_port().unwrap_or(SHARE_PORT);
+    let listener = tokio::net::TcpListener::bind(("0.0.0.0", port))
+        .await
+        .map_err(|e| format!("Failed to bind to port {}: {}", port, e))?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to read share address: {}", e))?
+        .port();
+
+    let token = Uuid::new_v4().simple().to_string();
+    let info = NotesShare {
+        url: format!("http://{}:{}/share/{}", address, port, token),
+        channel: channel.unwrap_or_default(),
+        expires_at: chrono::Utc::now().timestamp() + i64::from(minutes) * 60,
+    };
+
+    let app = Router::new()
+        .route("/share/:token", get(page_handler))
+        .route("/share/:token/state", get(state_handler));
+    let lifetime = std::time::Duration::from_secs(u64::from(minutes) * 60);
+    let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+    let server = tauri::async_runtime::spawn(async move {
+        let shutdown = async move {
+            tokio::select! {
+                _ = tokio::time::sleep(lifetime) => {}
+                _ = stopped => {}
+            }
+        };
+        if let Err(e) = axum::serve(listener, app)
+            .with_graceful_shutdown(shutdown)
+            .await
+        {
+            eprintln!("Share server error: {}", e);
+        }
+    });
+
+    *ACTIVE_SHARE.write() = Some(ActiveShare {
+        token,
+        info: info.clone(),
+        stop,
+        server,
+    });
+    Ok(info)
+}
+
+#[tauri::command]
+pub fn stop_notes_share() {
+    if let Some(share) = ACTIVE_SHARE.write().take() {
+        share.stop();
+    }
+}
+
+#[tauri::command]
+pub fn get_notes_share() -> Option<NotesShare> {
+    let now = chrono::Utc::now().timestamp();
+    ACTIVE_SHARE
+        .read()
+        .as_ref()
+        .map(|s| s.info.clone())
+        .filter(|info| info.expires_at > now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn share(token: &str, expires_at: i64) -> ActiveShare {
+        let (stop, _) = tokio::sync::oneshot::channel();
+        ActiveShare {
+            token: token.to_string(),
+            info: NotesShare {
+                url: format!("http://192.0.2.1:{}/share/{}", SHARE_PORT, token),
+                channel: ShareChannel::Notes,
+                expires_at,
+            },
+            stop,
+            server: tauri::async_runtime::spawn(async {}),
+        }
+    }
+
+    fn status(token: &str) -> (StatusCode, StatusCode) {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let page = runtime.block_on(page_handler(Path(token.to_string())));
+        let state = runtime.block_on(state_handler(Path(token.to_string())));
+        (page.status(), state.status())
+    }
+
+    #[test]
+    fn serves_only_the_live_token() {
+        let now = chrono::Utc::now().timestamp();
+        let not_found = (StatusCode::NOT_FOUND, StatusCode::NOT_FOUND);
+
+        *ACTIVE_SHARE.write() = None;
+        assert_eq!(status("abc123"), not_found);
+
+        *ACTIVE_SHARE.write() = Some(share("abc123", now + 600));
+        assert_eq!(status("abc123"), (StatusCode::OK, StatusCode::OK));
+        assert_eq!(status("abc124"), not_found);
+        assert_eq!(status(""), not_found);
+
+        *ACTIVE_SHARE.write() = Some(share("abc123", now - 1));
+        assert_eq!(status("abc123"), not_found);
+        assert!(get_notes_share().is_none());
+
+        *ACTIVE_SHARE.write() = None;
+    }
+}
@@ -0,0 +1,260 @@
+//! One running CueCard at a time
+//!
+//! A second CueCard fought the first over the local server port, global
+//! shortcuts and the store. Now the first instance listens on a loopback port
+//! written to a lock file; a later launch finds it there, hands over its
+//! arguments (`cuecard://` links, files opened with CueCard) and quits, and
+//! the running instance handles them and comes to the front.
+//!
+//! The lock file sits in the per-user app data directory and holds the port
+//! and a random token; a launch has to send the token before its arguments
+//! are acted on, so another user or a local page can't open links or files
+//! through the port. One left behind by a crash is noticed when nothing
+//! answering as CueCard is listening there, and replaced.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{Emitter, Manager, Url};
+use uuid::Uuid;
+
+use crate::{deep_link, providers, APP_HANDLE};
+
+const LOCK_FILE_NAME: &str = "cuecard-instance.lock";
+/// The app data directory's name, as Tauri names it from the identifier
+const APP_DIR_NAME: &str = "com.thisisnsh.cuecard";
+/// Sent back by the running instance once it has the arguments
+const ACK: &str = "cuecard-activated";
+const CONNECT_TIMEOUT_MS: u64 = 500;
+const REPLY_TIMEOUT_MS: u64 = 2000;
+
+/// The port and token in the lock file and the listener behind them
+struct Claim {
+    port: u16,
+    token: String,
+    listener: TcpListener,
+}
+
+/// What the lock file says about the running instance
+#[derive(PartialEq, Eq)]
+struct Lock {
+    port: u16,
+    token: String,
+}
+
+static CLAIM: Lazy<Arc<RwLock<Option<Claim>>>> = Lazy::new(|| Arc::new(RwLock::new(None)));
+
+fn lock_path() -> Option<PathBuf> {
+    Some(
+        dirs::data_local_dir()?
+            .join(APP_DIR_NAME)
+            .join(LOCK_FILE_NAME),
+    )
+}
+
+fn running_instance() -> Option<Lock> {
+    let contents = std::fs::read_to_string(lock_path()?).ok()?;
+    let mut lines = contents.lines();
+    let port = lines.next()?.trim().parse().ok()?;
+    let token = lines.next()?.trim().to_string();
+    if token.is_empty() {
+        return None;
+    }
+    Some(Lock { port, token })
+}
+
+/// Launch arguments, with relative file paths made absolute for the running
+/// instance, whose working directory may differ
+fn launch_args() -> Vec<String> {
+    let cwd = std::env::current_dir().ok();
+    std::env::args()
+        .skip(1)
+        .map(|arg| {
+            if arg.contains("://") || arg.starts_with('-') {
+                return arg;
+            }
+            match &cwd {
+                Some(cwd) => cwd.join(&arg).to_string_lossy().into_owned(),
+                None => arg,
+            }
+        })
+        .collect()
+}
+
+/// Send our arguments to the running instance; false if none answered
+fn forward(lock: &Lock, args: &[String]) -> bool {
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, lock.port));
+    let Ok(mut stream) =
+        TcpStream::connect_timeout(&address, Duration::from_millis(CONNECT_TIMEOUT_MS))
+    else {
+        return false;
+    };
+    let _ = stream.set_read_timeout(Some(Duration::from_millis(REPLY_TIMEOUT_MS)));
+    let Ok(message) = serde_json::to_string(args) else {
+        return false;
+    };
+    if writeln!(stream, "{}\n{}", lock.token, message).is_err() {
+        return false;
+    }
+    let mut reply = String::new();
+    let _ = BufReader::new(stream).read_line(&mut reply);
+    reply.trim() == ACK
+}
+
+/// Hand our arguments to an instance that's already running. True when one
+/// took them, and this launch should quit.
+pub fn forward_to_running() -> bool {
+    let Some(lock) = running_instance() else {
+        return false;
+    };
+    let forwarded = forward(&lock, &launch_args());
+    if forwarded {
+        eprintln!("CueCard is already running; activated it instead");
+    }
+    forwarded
+}
+
+/// Become the running instance. Later launches can connect right away and
+/// wait in the listener's backlog until `listen` runs.
+pub fn claim() {
+    let listener = match TcpListener::bind((Ipv4Addr::LOCALHOST, 0)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Failed to listen for other instances: {}", e);
+            return;
+        }
+    };
+    let port = match listener.local_addr() {
+        Ok(address) => address.port(),
+        Err(e) => {
+            eprintln!("Failed to read instance listener address: {}", e);
+            return;
+        }
+    };
+    let Some(path) = lock_path() else {
+        eprintln!("No app data directory for the instance lock file");
+        return;
+    };
+    let token = Uuid::new_v4().simple().to_string();
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    if let Err(e) = std::fs::write(&path, format!("{}\n{}\n", port, token)) {
+        eprintln!("Failed to write instance lock file: {}", e);
+        return;
+    }
+    *CLAIM.write() = Some(Claim {
+        port,
+        token,
+        listener,
+    });
+}
+
+/// Handle arguments forwarded by later launches
+pub fn listen() {
+    let Some((listener, token)) = CLAIM.read().as_ref().and_then(|c| {
+        c.listener
+            .try_clone()
+            .ok()
+            .map(|listener| (listener, c.token.clone()))
+    }) else {
+        return;
+    };
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            if let Some(args) = stream.ok().and_then(|stream| receive(stream, &token)) {
+                activate(args);
+            }
+        }
+    });
+}
+
+/// A later launch's arguments, acknowledged, if it sent the right token
+fn receive(mut stream: TcpStream, token: &str) -> Option<Vec<String>> {
+    let _ = stream.set_read_timeout(Some(Duration::from_millis(REPLY_TIMEOUT_MS)));
+    let mut reader = BufReader::new(&stream);
+    let mut sent_token = String::new();
+    if reader.read_line(&mut sent_token).is_err() || sent_token.trim() != token {
+        return None;
+    }
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let args = serde_json::from_str::<Vec<String>>(&line).ok()?;
+    let _ = writeln!(stream, "{}", ACK);
+    Some(args)
+}
+
+/// Bring the panel forward and act on another launch's arguments
+fn activate(args: Vec<String>) {
+    let Some(app) = APP_HANDLE.read().clone() else {
+        return;
+    };
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    let _ = app.emit("second-instance", &args);
+
+    let (links, paths): (Vec<String>, Vec<String>) =
+        args.into_iter().partition(|arg| arg.contains("://"));
+    let urls: Vec<Url> = links.iter().filter_map(|l| Url::parse(l).ok()).collect();
+    if !urls.is_empty() {
+        deep_link::handle_urls(urls);
+    }
+    providers::local_file::open_paths(
+        paths
+            .into_iter()
+            .filter(|p| !p.starts_with('-'))
+            .map(PathBuf::from)
+            .collect(),
+    );
+}
+
+/// Remove the lock file on exit, unless another instance has taken it over
+pub fn release() {
+    let ours = CLAIM.read().as_ref().map(|c| Lock {
+        port: c.port,
+        token: c.token.clone(),
+    });
+    if ours.is_some() && running_instance() == ours {
+        if let Some(path) = lock_path() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Forward `args` with `token` to a listener expecting `expected`; what
+    /// the launch was told and what the running instance received
+    fn handoff(token: &str, expected: &str, args: &[String]) -> (bool, Option<Vec<String>>) {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let expected = expected.to_string();
+        let running = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            receive(stream, &expected)
+        });
+        let lock = Lock {
+            port,
+            token: token.to_string(),
+        };
+        let forwarded = forward(&lock, args);
+        (forwarded, running.join().unwrap())
+    }
+
+    #[test]
+    fn ignores_a_launch_with_the_wrong_token() {
+        let args = vec!["cuecard://open?deck=abc".to_string()];
+        assert_eq!(handoff("guess", "s3cret", &args), (false, None));
+        assert_eq!(handoff("", "s3cret", &args), (false, None));
+        assert_eq!(handoff("s3cret", "s3cret", &args), (true, Some(args)));
+    }
+}
@@ -0,0 +1,281 @@
+//! Pausing background work across sleep and resuming it in order on wake
+//!
+//! Pollers that fire as the machine goes to sleep, or the moment it wakes
+//! while Wi-Fi is still rejoining, fail one after another and leave a trail
+//! of errors and `token-expired` events behind. The OS says when it's about
+//! to sleep and when it has woken (IOKit on macOS, suspend/resume
+//! notifications on Windows; elsewhere a wake is spotted by
+//! `focus_revalidate`'s wall-clock check). From sleep until the wake has been
+//! handled, the token refresh loop, revision poller, connectivity monitor and
+//! health check skip their turns. On wake the network is checked first, a few
+//! times while it comes back, and only once it's online are tokens refreshed
+//! and the deck revalidated. A network that comes back after being offline or
+//! behind a captive portal is recovered the same way. Sent as `system-sleep`
+//! and `system-wake`.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::Emitter;
+
+use crate::connectivity::{self, ConnectivityState};
+use crate::{clock, APP_HANDLE};
+
+// Wakes reported twice (OS notification and wall-clock gap) count once
+const WAKE_DEBOUNCE_SECS: i64 = 60;
+const WAKE_NETWORK_CHECKS: u32 = 6;
+const WAKE_NETWORK_RETRY_SECS: u64 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerState {
+    Awake,
+    Asleep,
+    /// Woken, waiting on the network before background work starts again
+    Resuming,
+}
+
+static STATE: Lazy<Arc<RwLock<PowerState>>> =
+    Lazy::new(|| Arc::new(RwLock::new(PowerState::Awake)));
+// Unix seconds of the last wake handled
+static LAST_WAKE: Lazy<Arc<RwLock<i64>>> = Lazy::new(|| Arc::new(RwLock::new(0)));
+
+/// Whether background pollers should skip their turn
+pub fn is_paused() -> bool {
+    *STATE.read() != PowerState::Awake
+}
+
+fn emit(event: &str, state: PowerState) {
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        let _ = app.emit(event, state);
+    }
+}
+
+/// The machine is about to sleep
+#[cfg(all(any(target_os = "macos", target_os = "windows"), feature = "desktop"))]
+pub fn on_will_sleep() {
+    *STATE.write() = PowerState::Asleep;
+    eprintln!("System going to sleep; pausing background work");
+    emit("system-sleep", PowerState::Asleep);
+}
+
+/// A sleep announced with `on_will_sleep` was called off
+#[cfg(all(target_os = "macos", feature = "desktop"))]
+pub fn on_sleep_cancelled() {
+    let mut state = STATE.write();
+    if *state == PowerState::Asleep {
+        *state = PowerState::Awake;
+    }
+}
+
+/// The machine woke from sleep
+pub fn on_wake() {
+    let now = clock::now_secs();
+    {
+        let mut last = LAST_WAKE.write();
+        if !is_new_wake(*STATE.read(), *last, now) {
+            return;
+        }
+        *last = now;
+    }
+    *STATE.write() = PowerState::Resuming;
+    eprintln!("System woke; waiting on the network");
+    emit("system-wake", PowerState::Resuming);
+    tauri::async_runtime::spawn(resume());
+}
+
+/// A wake from sleep always counts; otherwise only one a while after the
+/// last, so a wake seen by both the OS and the wall clock is handled once
+fn is_new_wake(state: PowerState, last_wake: i64, now: i64) -> bool {
+    state == PowerState::Asleep || now - last_wake >= WAKE_DEBOUNCE_SECS
+}
+
+/// The network is back after being offline or behind a captive portal
+pub fn on_network_restored() {
+    // A wake in progress recovers once it sees the network itself
+    if is_paused() {
+        return;
+    }
+    tauri::async_runtime::spawn(recover());
+}
+
+async fn resume() {
+    for _ in 0..WAKE_NETWORK_CHECKS {
+        if connectivity::check().await == ConnectivityState::Online {
+            break;
+        }
+        tokio::time::sleep(Duration::from_secs(WAKE_NETWORK_RETRY_SECS)).await;
+    }
+    {
+        let mut state = STATE.write();
+        // Asleep again before the network came back
+        if *state != PowerState::Resuming {
+            return;
+        }
+        *state = PowerState::Awake;
+    }
+    emit("system-wake", PowerState::Awake);
+    // Still offline: the connectivity monitor recovers once the network is back
+    if connectivity::state() == ConnectivityState::Online {
+        recover().await;
+    }
+}
+
+/// Refresh tokens that are due, then check the open deck's notes
+async fn recover() {
+    let _ = crate::get_valid_firebase_token().await;
+    let _ = crate::get_valid_slides_token().await;
+    crate::focus_revalidate::on_wake();
+}
+
+/// Listen for the OS's sleep and wake notifications, where it sends them
+pub fn listen() {
+    #[cfg(all(target_os = "macos", feature = "desktop"))]
+    iokit::listen();
+    #[cfg(all(target_os = "windows", feature = "desktop"))]
+    win32::listen();
+}
+
+#[cfg(all(target_os = "macos", feature = "desktop"))]
+mod iokit {
+    use std::ffi::c_void;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    const CAN_SYSTEM_SLEEP: u32 = 0xE000_0270;
+    const SYSTEM_WILL_SLEEP: u32 = 0xE000_0280;
+    const SYSTEM_WILL_NOT_SLEEP: u32 = 0xE000_0290;
+    const SYSTEM_HAS_POWERED_ON: u32 = 0xE000_0300;
+
+    type Callback = extern "C" fn(*mut c_void, u32, u32, *mut c_void);
+
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        fn IORegisterForSystemPower(
+            refcon: *mut c_void,
+            port: *mut *mut c_void,
+            callback: Callback,
+            notifier: *mut u32,
+        ) -> u32;
+        fn IONotificationPortGetRunLoopSource(port: *mut c_void) -> *mut c_void;
+        fn IOAllowPowerChange(kernel_port: u32, notification_id: isize) -> i32;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        static kCFRunLoopDefaultMode: *const c_void;
+        fn CFRunLoopGetCurrent() -> *mut c_void;
+        fn CFRunLoopAddSource(run_loop: *mut c_void, source: *mut c_void, mode: *const c_void);
+        fn CFRunLoopRun();
+    }
+
+    // Connection to the root power domain, which sleep is acknowledged on
+    static ROOT_PORT: AtomicU32 = AtomicU32::new(0);
+
+    extern "C" fn on_power(
+        _refcon: *mut c_void,
+        _service: u32,
+        message: u32,
+        argument: *mut c_void,
+    ) {
+        let allow = || unsafe {
+            IOAllowPowerChange(ROOT_PORT.load(Ordering::SeqCst), argument as isize);
+        };
+        match message {
+            // Idle sleep could be vetoed here; presenting already holds an assertion
+            CAN_SYSTEM_SLEEP => allow(),
+            SYSTEM_WILL_SLEEP => {
+                super::on_will_sleep();
+                allow();
+            }
+            SYSTEM_WILL_NOT_SLEEP => super::on_sleep_cancelled(),
+            SYSTEM_HAS_POWERED_ON => super::on_wake(),
+            _ => {}
+        }
+    }
+
+    pub fn listen() {
+        std::thread::spawn(|| unsafe {
+            let mut port = std::ptr::null_mut();
+            let mut notifier = 0;
+            let root =
+                IORegisterForSystemPower(std::ptr::null_mut(), &mut port, on_power, &mut notifier);
+            if root == 0 {
+                eprintln!("Failed to register for sleep and wake notifications");
+                return;
+            }
+            ROOT_PORT.store(root, Ordering::SeqCst);
+            CFRunLoopAddSource(
+                CFRunLoopGetCurrent(),
+                IONotificationPortGetRunLoopSource(port),
+                kCFRunLoopDefaultMode,
+            );
+            CFRunLoopRun();
+        });
+    }
+}
+
+#[cfg(all(target_os = "windows", feature = "desktop"))]
+mod win32 {
+    use std::ffi::c_void;
+    use windows::Win32::Foundation::HANDLE;
+    use windows::Win32::System::Power::{
+        RegisterSuspendResumeNotification, DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{
+        DEVICE_NOTIFY_CALLBACK, PBT_APMRESUMEAUTOMATIC, PBT_APMSUSPEND,
+    };
+
+    unsafe extern "system" fn on_power(
+        _context: *const c_void,
+        kind: u32,
+        _setting: *const c_void,
+    ) -> u32 {
+        match kind {
+            PBT_APMSUSPEND => super::on_will_sleep(),
+            PBT_APMRESUMEAUTOMATIC => super::on_wake(),
+            _ => {}
+        }
+        0
+    }
+
+    pub fn listen() {
+        // Windows reads the parameters for as long as the registration lives,
+        // which is the life of the app
+        let params = Box::leak(Box::new(DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS {
+            Callback: Some(on_power),
+            Context: std::ptr::null_mut(),
+        }));
+        let recipient = HANDLE(params as *mut DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS as *mut c_void);
+        if let Err(e) =
+            unsafe { RegisterSuspendResumeNotification(recipient, DEVICE_NOTIFY_CALLBACK) }
+        {
+            eprintln!("Failed to register for sleep and wake notifications: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_a_wake_once() {
+        let wake = 1_700_000_000;
+        assert!(is_new_wake(PowerState::Asleep, wake, wake + 1));
+        // The wall-clock check spotting the same wake
+        assert!(!is_new_wake(PowerState::Resuming, wake, wake + 10));
+        assert!(!is_new_wake(
+            PowerState::Awake,
+            wake,
+            wake + WAKE_DEBOUNCE_SECS - 1
+        ));
+        assert!(is_new_wake(
+            PowerState::Awake,
+            wake,
+            wake + WAKE_DEBOUNCE_SECS
+        ));
+        assert!(is_new_wake(PowerState::Awake, 0, wake));
+    }
+}
@@ -0,0 +1,292 @@
+//! Experimental slide inference from spoken text
+//!
+//! When the browser extension stops reporting mid-talk, the panel can keep up
+//! by listening instead: a speech recognizer running in the panel passes
+//! recognized text to `submit_transcript`. The last few seconds of speech are
+//! compared with the notes of the slides around the current one. When another
+//! slide matches clearly better, a `slide-suggestion` event is emitted ("you
+//! appear to be on slide 14"), which the panel can accept with
+//! `accept_slide_suggestion`. Off by default.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+use tauri::Emitter;
+
+use crate::error::CueCardError;
+use crate::{slide_mapping, SlideData, APP_HANDLE, CURRENT_SLIDE, SLIDE_NOTES};
+
+// Spoken words considered, and how far from the current slide to look
+const TRANSCRIPT_WINDOW_WORDS: usize = 40;
+const NEARBY_SLIDES: usize = 3;
+// A suggestion needs this many shared words, this share of the spoken words,
+// and this lead over the current slide
+const MIN_SHARED_WORDS: usize = 4;
+const MIN_CONFIDENCE: f64 = 0.5;
+const MIN_MARGIN: f64 = 0.2;
+// Don't repeat the same suggestion more often than this
+const SUGGESTION_COOLDOWN_SECS: i64 = 15;
+
+/// "You appear to be on slide N"
+#[derive(Debug, Clone, Serialize)]
+pub struct SlideSuggestion {
+    pub presentation_id: String,
+    pub slide_id: String,
+    /// 1-based, from deck order
+    pub slide_number: i32,
+    /// Share of recently spoken words found in that slide's notes
+    pub confidence: f64,
+    pub current_slide_id: String,
+}
+
+#[derive(Default)]
+struct InferenceState {
+    enabled: bool,
+    words: VecDeque<String>,
+    last_suggestion: Option<(String, i64)>,
+}
+
+static INFERENCE_STATE: Lazy<Arc<RwLock<InferenceState>>> =
+    Lazy::new(|| Arc::new(RwLock::new(InferenceState::default())));
+
+/// Lowercase words of three or more letters; shorter ones match everything
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .map(|w| w.trim_matches('\'').to_lowercase())
+        .filter(|w| w.chars().count() >= 3)
+        .collect()
+}
+
+fn score(spoken: &HashSet<String>, notes: &str) -> (usize, f64) {
+    if spoken.is_empty() {
+        return (0, 0.0);
+    }
+    let note_words: HashSet<String> = tokenize(notes).into_iter().collect();
+    let shared = spoken.intersection(&note_words).count();
+    (shared, shared as f64 / spoken.len() as f64)
+}
+
+fn infer(current: &SlideData, spoken: &HashSet<String>) -> Option<SlideSuggestion> {
+    let order = slide_mapping::shown_slides();
+    let notes = SLIDE_NOTES.read();
+    let notes_for = |slide_id: &str| {
+        notes
+            .get(&format!("{}:{}", current.presentation_id, slide_id))
+            .cloned()
+    };
+
+    // Slides around the current one when deck order is known, otherwise every cached slide
+    let candidates: Vec<(usize, String)> = match order.iter().position(|id| *id == current.slide_id)
+    {
+        Some(pos) => {
+            let start = pos.saturating_sub(NEARBY_SLIDES);
+            let end = (pos + NEARBY_SLIDES + 1).min(order.len());
+            (start..end).map(|i| (i, order[i].clone())).collect()
+        }
+        None => order.iter().cloned().enumerate().collect(),
+    };
+
+    let current_score = notes_for(&current.slide_id)
+        .map(|n| score(spoken, &n).1)
+        .unwrap_or(0.0);
+
+    let (best_index, best_id, best_shared, best_score) = candidates
+        .into_iter()
+        .filter(|(_, id)| *id != current.slide_id)
+        .filter_map(|(i, id)| {
+            let (shared, s) = score(spoken, &notes_for(&id)?);
+            Some((i, id, shared, s))
+        })
+        .max_by(|a, b| a.3.total_cmp(&b.3))?;
+
+    if best_shared < MIN_SHARED_WORDS
+        || best_score < MIN_CONFIDENCE
+        || best_score - current_score < MIN_MARGIN
+    {
+        return None;
+    }
+
+    Some(SlideSuggestion {
+        presentation_id: current.presentation_id.clone(),
+        slide_id: best_id,
+        slide_number: best_index as i32 + 1,
+        confidence: best_score,
+        current_slide_id: current.slide_id.clone(),
+    })
+}
+
+/// Feed recognized speech; returns (and emits) a suggestion when confident
+#[tauri::command]
+pub fn submit_transcript(text: String) -> Option<SlideSuggestion> {
+    // Push-to-talk speech is meant for the parking lot
+    if crate::session::capture_transcript(&text) {
+        return None;
+    }
+
+    let spoken: HashSet<String> = {
+        let mut state = INFERENCE_STATE.write();
+        if !state.enabled {
+            return None;
+        }
+        state.words.extend(tokenize(&text));
+        while state.words.len() > TRANSCRIPT_WINDOW_WORDS {
+            state.words.pop_front();
+        }
+        state.words.iter().cloned().collect()
+    };
+
+    let current = CURRENT_SLIDE.read().clone()?;
+    let suggestion = infer(&current, &spoken)?;
+
+    let now = chrono::Utc::now().timestamp();
+    {
+        let mut state = INFERENCE_STATE.write();
+        if let Some((ref slide_id, at)) = state.last_suggestion {
+            if *slide_id == suggestion.slide_id && now - at < SUGGESTION_COOLDOWN_SECS {
+                return None;
+            }
+        }
+        state.last_suggestion = Some((suggestion.slide_id.clone(), now));
+    }
+
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        let _ = app.emit("slide-suggestion", suggestion.clone());
+    }
+    Some(suggestion)
+}
+
+#[tauri::command]
+pub fn set_slide_inference_enabled(enabled: bool) {
+    let mut state = INFERENCE_STATE.write();
+    state.enabled = enabled;
+    state.words.clear();
+    state.last_suggestion = None;
+}
+
+#[tauri::command]
+pub fn is_slide_inference_enabled() -> bool {
+    INFERENCE_STATE.read().enabled
+}
+
+/// Move the panel to a suggested slide of the current presentation
+#[tauri::command]
+pub async fn accept_slide_suggestion(
+    slide_id: String,
+    slide_number: i32,
+) -> Result<Option<String>, CueCardError> {
+    let current = CURRENT_SLIDE.read().clone().ok_or("No current slide")?;
+
+    // What was said belonged to the old slide
+    INFERENCE_STATE.write().words.clear();
+
+    let notes = crate::apply_slide_update(
+        SlideData {
+            slide_id,
+            slide_number,
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            force_refresh: None,
+            scraped_notes: None,
+            ..current
+        },
+        None,
+    )
+    .await;
+    Ok(notes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spoken(text: &str) -> HashSet<String> {
+        tokenize(text).into_iter().collect()
+    }
+
+    fn slide(slide_id: &str) -> SlideData {
+        SlideData {
+            presentation_id: "inference".to_string(),
+            slide_id: slide_id.to_string(),
+            slide_number: 0,
+            title: String::new(),
+            mode: "google".to_string(),
+            timestamp: 0,
+            url: String::new(),
+            force_refresh: None,
+            client_id: None,
+            scraped_notes: None,
+        }
+    }
+
+    /// Deck p1..p8, with notes on the given slides
+    fn load_deck(notes: &[(&str, &str)]) {
+        *crate::SLIDE_ORDER.write() = (1..=8).map(|i| format!("p{}", i)).collect();
+        let mut cache = SLIDE_NOTES.write();
+        cache.retain(|key, _| !key.starts_with("inference:"));
+        for (slide_id, text) in notes {
+            cache.insert(format!("inference:{}", slide_id), text.to_string());
+        }
+    }
+
+    #[test]
+    fn tokenizes_words_of_three_letters_or_more() {
+        assert_eq!(
+            tokenize("It's the Q3 roadmap, 'really' on-track!"),
+            vec!["it's", "the", "roadmap", "really", "track"]
+        );
+    }
+
+    #[test]
+    fn scores_the_share_of_spoken_words_in_the_notes() {
+        // "at" is too short to count
+        let words = spoken("pricing tiers start at ten dollars");
+        assert_eq!(
+            score(&words, "Three pricing tiers, starting at ten"),
+            (3, 0.6)
+        );
+        assert_eq!(score(&HashSet::new(), "anything"), (0, 0.0));
+    }
+
+    #[test]
+    fn suggests_a_nearby_slide_that_matches_clearly_better() {
+        let _deck = crate::tests::DECK_LOCK.lock();
+        load_deck(&[
+            ("p2", "Welcome everyone to the quarterly review"),
+            (
+                "p4",
+                "Churn dropped after the onboarding redesign shipped in March",
+            ),
+        ]);
+        let words = spoken("so churn dropped once the onboarding redesign shipped");
+
+        let suggestion = infer(&slide("p2"), &words).expect("a suggestion");
+        assert_eq!(suggestion.slide_id, "p4");
+        assert_eq!(suggestion.slide_number, 4);
+        assert_eq!(suggestion.current_slide_id, "p2");
+        assert!(suggestion.confidence >= MIN_CONFIDENCE);
+    }
+
+    #[test]
+    fn no_suggestion_without_a_clear_lead() {
+        let _deck = crate::tests::DECK_LOCK.lock();
+        load_deck(&[
+            ("p2", "Churn dropped after the onboarding redesign"),
+            ("p3", "Churn dropped after the onboarding redesign shipped"),
+            // Too far from the current slide to be considered
+            ("p8", "Hiring plan for engineering and design next year"),
+        ]);
+        assert!(infer(
+            &slide("p2"),
+            &spoken("churn dropped after the onboarding redesign")
+        )
+        .is_none());
+        assert!(infer(
+            &slide("p2"),
+            &spoken("hiring plan for engineering and design")
+        )
+        .is_none());
+        // Too few words to go on
+        assert!(infer(&slide("p1"), &spoken("churn dropped")).is_none());
+    }
+}
@@ -0,0 +1,280 @@
+//! Matching the extension's slide numbers to the deck's slides
+//!
+//! Hidden slides (`slideProperties.isSkipped`) are left out of the slideshow,
+//! so from the first hidden slide on, a slideshow's slide numbers no longer
+//! line up with positions in the API's slide order. Hidden slides are picked
+//! up whenever the deck order is fetched. Updates that carry a slide id the
+//! deck knows are trusted as they are; updates with an id it doesn't know are
+//! placed by their number among the slides that are shown, plus a per-deck
+//! offset that can be set by hand for decks that still come out wrong. Slide
+//! skip warnings and the interpreter's look-ahead count shown slides too.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+use crate::error::CueCardError;
+use crate::{SlideData, CURRENT_PRESENTATION_ID, CURRENT_SLIDE, SLIDE_ORDER};
+
+const SLIDE_NUMBER_OFFSETS_KEY: &str = "slide_number_offsets";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SlideMapping {
+    pub presentation_id: Option<String>,
+    /// Added to reported slide numbers before they're placed in the deck
+    pub offset: i32,
+    pub hidden_slide_ids: Vec<String>,
+}
+
+/// A slide placed by its number, kept so a new offset can place it again
+struct PlacedSlide {
+    presentation_id: String,
+    slide_id: String,
+    /// The id and number in the update, before placing
+    reported_slide_id: String,
+    reported_number: i32,
+}
+
+// Hidden slide ids of the current presentation
+static HIDDEN_SLIDES: Lazy<Arc<RwLock<HashSet<String>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(HashSet::new())));
+// Keyed by presentation id
+static SLIDE_NUMBER_OFFSETS: Lazy<Arc<RwLock<HashMap<String, i32>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(HashMap::new())));
+static LAST_PLACED: Lazy<Arc<RwLock<Option<PlacedSlide>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(None)));
+
+pub fn load_offsets_from_store(app: &AppHandle) {
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Some(offsets) = store
+            .get(SLIDE_NUMBER_OFFSETS_KEY)
+            .and_then(|v| serde_json::from_value::<HashMap<String, i32>>(v).ok())
+        {
+            *SLIDE_NUMBER_OFFSETS.write() = offsets;
+        }
+    }
+}
+
+/// Forget hidden slides when the presentation changes
+pub fn reset() {
+    HIDDEN_SLIDES.write().clear();
+    *LAST_PLACED.write() = None;
+}
+
+/// Hidden slides from a Slides API `slides` array, unless the presentation
+/// changed in the meantime
+pub fn record_hidden_slides(presentation_id: &str, slides: &[serde_json::Value]) {
+    if CURRENT_PRESENTATION_ID.read().as_deref() != Some(presentation_id) {
+        return;
+    }
+    *HIDDEN_SLIDES.write() = slides
+        .iter()
+        .filter(|slide| is_skipped(slide))
+        .filter_map(|slide| slide.get("objectId")?.as_str().map(|id| id.to_string()))
+        .collect();
+}
+
+/// Hidden slides kept with a cached deck, unless the presentation changed
+/// in the meantime
+pub fn record_hidden_slide_ids(presentation_id: &str, slide_ids: Vec<String>) {
+    if CURRENT_PRESENTATION_ID.read().as_deref() != Some(presentation_id) {
+        return;
+    }
+    *HIDDEN_SLIDES.write() = slide_ids.into_iter().collect();
+}
+
+/// Whether a Slides API slide is hidden from the slideshow
+pub fn is_skipped(slide: &serde_json::Value) -> bool {
+    slide
+        .get("slideProperties")
+        .and_then(|p| p.get("isSkipped"))
+        .and_then(|s| s.as_bool())
+        .unwrap_or(false)
+}
+
+pub fn is_hidden(slide_id: &str) -> bool {
+    HIDDEN_SLIDES.read().contains(slide_id)
+}
+
+/// The deck's slide ids in order, without hidden slides
+pub fn shown_slides() -> Vec<String> {
+    let hidden = HIDDEN_SLIDES.read();
+    SLIDE_ORDER
+        .read()
+        .iter()
+        .filter(|id| !hidden.contains(*id))
+        .cloned()
+        .collect()
+}
+
+fn offset_for(presentation_id: &str) -> i32 {
+    SLIDE_NUMBER_OFFSETS
+        .read()
+        .get(presentation_id)
+        .copied()
+        .unwrap_or(0)
+}
+
+/// The slide on screen for an update: its own id when the deck knows it,
+/// otherwise the shown slide at its number
+pub fn place(mut slide: SlideData) -> SlideData {
+    {
+        let order = SLIDE_ORDER.read();
+        if order.is_empty() || order.contains(&slide.slide_id) {
+            return slide;
+        }
+    }
+
+    let reported_number = slide.slide_number;
+    let Some(number) = reported_number.checked_add(offset_for(&slide.presentation_id)) else {
+        return slide;
+    };
+    let shown = shown_slides();
+    let Some(slide_id) = usize::try_from(number)
+        .ok()
+        .and_then(|number| number.checked_sub(1))
+        .and_then(|index| shown.get(index))
+    else {
+        return slide;
+    };
+
+    let reported_slide_id = std::mem::replace(&mut slide.slide_id, slide_id.clone());
+    slide.slide_number = number;
+    *LAST_PLACED.write() = Some(PlacedSlide {
+        presentation_id: slide.presentation_id.clone(),
+        slide_id: slide.slide_id.clone(),
+        reported_slide_id,
+        reported_number,
+    });
+    slide
+}
+
+fn current_mapping() -> SlideMapping {
+    let presentation_id = CURRENT_PRESENTATION_ID.read().clone();
+    let offset = presentation_id.as_deref().map(offset_for).unwrap_or(0);
+    let hidden = HIDDEN_SLIDES.read();
+    SlideMapping {
+        presentation_id,
+        offset,
+        hidden_slide_ids: SLIDE_ORDER
+            .read()
+            .iter()
+            .filter(|id| hidden.contains(*id))
+            .cloned()
+            .collect(),
+    }
+}
+
+#[tauri::command]
+pub fn get_slide_mapping() -> SlideMapping {
+    current_mapping()
+}
+
+/// Shift reported slide numbers for the current presentation, e.g. -1 when
+/// the notes shown are one slide ahead
+#[tauri::command]
+pub async fn set_slide_number_offset(
+    app: AppHandle,
+    offset: i32,
+) -> Result<SlideMapping, CueCardError> {
+    let presentation_id = CURRENT_PRESENTATION_ID
+        .read()
+        .clone()
+        .ok_or("No presentation is open")?;
+
+    {
+        let mut offsets = SLIDE_NUMBER_OFFSETS.write();
+        if offset == 0 {
+            offsets.remove(&presentation_id);
+        } else {
+            offsets.insert(presentation_id.clone(), offset);
+        }
+    }
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Ok(json) = serde_json::to_value(&*SLIDE_NUMBER_OFFSETS.read()) {
+            store.set(SLIDE_NUMBER_OFFSETS_KEY, json);
+            let _ = store.save();
+        }
+    }
+
+    // Place the slide on screen again if it was placed by its number
+    let replace = {
+        let placed = LAST_PLACED.read();
+        let current = CURRENT_SLIDE.read();
+        match (placed.as_ref(), current.as_ref()) {
+            (Some(placed), Some(current))
+                if placed.presentation_id == current.presentation_id
+                    && placed.slide_id == current.slide_id =>
+            {
+                let mut slide = current.clone();
+                slide.slide_id = placed.reported_slide_id.clone();
+                slide.slide_number = placed.reported_number;
+                Some(slide)
+            }
+            _ => None,
+        }
+    };
+    if let Some(slide) = replace {
+        crate::apply_slide_update(slide, None).await;
+    }
+
+    Ok(current_mapping())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reported(presentation_id: &str, slide_number: i32) -> SlideData {
+        SlideData {
+            presentation_id: presentation_id.to_string(),
+            slide_id: format!("unknown-{}", slide_number),
+            slide_number,
+            title: String::new(),
+            mode: "google".to_string(),
+            timestamp: 0,
+            url: String::new(),
+            force_refresh: None,
+            client_id: None,
+            scraped_notes: None,
+        }
+    }
+
+    #[test]
+    fn places_unknown_ids_by_number_plus_offset() {
+        let _deck = crate::tests::DECK_LOCK.lock();
+        *SLIDE_ORDER.write() = (1..=4).map(|i| format!("p{}", i)).collect();
+        SLIDE_NUMBER_OFFSETS
+            .write()
+            .insert("mapping-offset".to_string(), -1);
+
+        let placed = place(reported("mapping-offset", 3));
+        assert_eq!(placed.slide_id, "p2");
+        assert_eq!(placed.slide_number, 2);
+
+        // Known ids are trusted as they are
+        let mut known = reported("mapping-offset", 3);
+        known.slide_id = "p4".to_string();
+        assert_eq!(place(known).slide_id, "p4");
+    }
+
+    #[test]
+    fn extreme_offsets_leave_the_update_alone() {
+        let _deck = crate::tests::DECK_LOCK.lock();
+        *SLIDE_ORDER.write() = (1..=4).map(|i| format!("p{}", i)).collect();
+        SLIDE_NUMBER_OFFSETS
+            .write()
+            .insert("mapping-max".to_string(), i32::MAX);
+        SLIDE_NUMBER_OFFSETS
+            .write()
+            .insert("mapping-min".to_string(), i32::MIN);
+
+        assert_eq!(place(reported("mapping-max", 2)).slide_id, "unknown-2");
+        assert_eq!(place(reported("mapping-min", -1)).slide_id, "unknown--1");
+        assert_eq!(place(reported("mapping-min", 0)).slide_id, "unknown-0");
+    }
+}
@@ -0,0 +1,226 @@
+//! Warnings for slides jumped over
+//!
+//! When the presenter moves forward by more than one slide in deck order,
+//! the slides in between that have notes are sent as `slides-skipped`, so
+//! the content can be picked up again during Q&A. Titles come from slides
+//! already shown or, for Google Slides decks fetched in full, from each
+//! slide's title placeholder.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::Emitter;
+
+use crate::{notes_masking, slide_mapping, SlideData, APP_HANDLE, SLIDE_NOTES};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedSlideNotes {
+    pub slide_id: String,
+    pub slide_number: i32,
+    pub title: String,
+    pub notes: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SlidesSkippedEvent {
+    pub presentation_id: String,
+    pub from_slide_number: i32,
+    pub to_slide_number: i32,
+    pub slides: Vec<SkippedSlideNotes>,
+}
+
+// Slide id -> title for the current presentation
+static SLIDE_TITLES: Lazy<Arc<RwLock<HashMap<String, String>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(HashMap::new())));
+
+/// Forget titles when the presentation changes
+pub fn reset() {
+    SLIDE_TITLES.write().clear();
+}
+
+fn placeholder_title(slide: &serde_json::Value) -> Option<(String, String)> {
+    let slide_id = slide.get("objectId")?.as_str()?;
+    let shape = slide
+        .get("pageElements")?
+        .as_array()?
+        .iter()
+        .filter_map(|element| element.get("shape"))
+        .find(|shape| {
+            shape
+                .get("placeholder")
+                .and_then(|p| p.get("type"))
+                .and_then(|t| t.as_str())
+                .is_some_and(|t| t == "TITLE" || t == "CENTERED_TITLE")
+        })?;
+
+    let title: String = shape
+        .get("text")?
+        .get("textElements")?
+        .as_array()?
+        .iter()
+        .filter_map(|e| e.get("textRun")?.get("content")?.as_str())
+        .collect();
+    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!title.is_empty()).then(|| (slide_id.to_string(), title))
+}
+
+/// A slide's title, if it has been shown or its deck was fetched in full
+pub fn slide_title(slide_id: &str) -> Option<String> {
+    SLIDE_TITLES.read().get(slide_id).cloned()
+}
+
+/// Titles from a full Slides API presentation response
+pub fn record_deck_titles(slides: &[serde_json::Value]) {
+    let titles: Vec<(String, String)> = slides.iter().filter_map(placeholder_title).collect();
+    SLIDE_TITLES.write().extend(titles);
+}
+
+/// Positions in deck `order` of a move forward by more than one slide
+fn forward_jump(order: &[String], from_id: &str, to_id: &str) -> Option<(usize, usize)> {
+    let position = |slide_id: &str| order.iter().position(|id| id == slide_id);
+    let (from, to) = (position(from_id)?, position(to_id)?);
+    (to > from + 1).then_some((from, to))
+}
+
+/// The slides of `between` that have notes, `first` being the position of
+/// the first of them in the deck
+fn skipped_slides(
+    presentation_id: &str,
+    between: &[String],
+    first: usize,
+    notes: &HashMap<String, String>,
+    titles: &HashMap<String, String>,
+) -> Vec<SkippedSlideNotes> {
+    between
+        .iter()
+        .enumerate()
+        .filter_map(|(offset, slide_id)| {
+            let text = notes
+                .get(&format!("{}:{}", presentation_id, slide_id))
+                .filter(|n| !n.trim().is_empty())?
+                .clone();
+            let slide_number = (first + offset) as i32 + 1;
+            Some(SkippedSlideNotes {
+                slide_id: slide_id.clone(),
+                slide_number,
+                title: titles
+                    .get(slide_id)
+                    .cloned()
+                    .unwrap_or_else(|| format!("Slide {}", slide_number)),
+                notes: notes_masking::mask_notes(presentation_id, Some(text)).unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+/// Remember the slide's title and warn if slides were jumped over to get here
+pub fn on_slide_shown(previous: Option<&SlideData>, current: &SlideData) {
+    if !current.title.trim().is_empty() {
+        SLIDE_TITLES
+            .write()
+            .insert(current.slide_id.clone(), current.title.clone());
+    }
+
+    let Some(previous) = previous.filter(|p| p.presentation_id == current.presentation_id) else {
+        return;
+    };
+    // Hidden slides aren't jumped over, they're never shown
+    let order = slide_mapping::shown_slides();
+    let Some((from, to)) = forward_jump(&order, &previous.slide_id, &current.slide_id) else {
+        return;
+    };
+
+    let slides = skipped_slides(
+        &current.presentation_id,
+        &order[from + 1..to],
+        from + 1,
+        &SLIDE_NOTES.read(),
+        &SLIDE_TITLES.read(),
+    );
+    if slides.is_empty() {
+        return;
+    }
+
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        let _ = app.emit(
+            "slides-skipped",
+            SlidesSkippedEvent {
+                presentation_id: current.presentation_id.clone(),
+                from_slide_number: from as i32 + 1,
+                to_slide_number: to as i32 + 1,
+                slides,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn only_forward_jumps_skip_slides() {
+        let order = ids(&["s1", "s2", "s3", "s4", "s5"]);
+        assert_eq!(forward_jump(&order, "s1", "s4"), Some((0, 3)));
+        assert_eq!(forward_jump(&order, "s1", "s2"), None);
+        assert_eq!(forward_jump(&order, "s4", "s1"), None);
+        assert_eq!(forward_jump(&order, "s1", "gone"), None);
+    }
+
+    #[test]
+    fn lists_skipped_slides_with_notes() {
+        let notes: HashMap<String, String> = [
+            ("deck:s2".to_string(), "Mention the pilot".to_string()),
+            ("deck:s3".to_string(), "  ".to_string()),
+            ("deck:s4".to_string(), "Pricing tiers".to_string()),
+        ]
+        .into_iter()
+        .collect();
+        let titles: HashMap<String, String> = [("s2".to_string(), "Pilot".to_string())]
+            .into_iter()
+            .collect();
+
+        let skipped = skipped_slides("deck", &ids(&["s2", "s3", "s4"]), 1, &notes, &titles);
+        let summary: Vec<_> = skipped
+            .iter()
+            .map(|s| (s.slide_number, s.title.as_str(), s.notes.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (2, "Pilot", "Mention the pilot"),
+                (4, "Slide 4", "Pricing tiers")
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_the_title_placeholder() {
+        let slide = serde_json::json!({
+            "objectId": "s1",
+            "pageElements": [
+                { "shape": { "placeholder": { "type": "BODY" }, "text": { "textElements": [
+                    { "textRun": { "content": "Body text" } }
+                ] } } },
+                { "shape": { "placeholder": { "type": "CENTERED_TITLE" }, "text": { "textElements": [
+                    { "textRun": { "content": "Quarterly\n" } },
+                    { "textRun": { "content": " review" } }
+                ] } } }
+            ]
+        });
+        assert_eq!(
+            placeholder_title(&slide),
+            Some(("s1".to_string(), "Quarterly review".to_string()))
+        );
+        assert_eq!(
+            placeholder_title(&serde_json::json!({ "objectId": "s2", "pageElements": [] })),
+            None
+        );
+    }
+}
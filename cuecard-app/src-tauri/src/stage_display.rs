@@ -0,0 +1,436 @@
+//! Stage display output for confidence monitors
+//!
+//! Venues often already have a confidence monitor driven by a stage display
+//! app or box that speaks ProPresenter's stage display protocol (version 6):
+//! the client connects over TCP, sends `<StageDisplayLogin>password</...>`,
+//! and is then sent a `<StageDisplayData>` frame of named fields whenever
+//! something on stage changes. `start_stage_display` serves that protocol on
+//! the LAN with CueCard's current and next slide notes, the clock in the
+//! event's time zone, the session's elapsed time and any running countdown,
+//! so those screens can show the notes without a custom integration.
+//!
+//! Clients have to log in with the output's password, which is generated
+//! when the output is first started without one. The port and password are
+//! kept in the store (and left out of data exports), and the output starts
+//! again with the app when it was left on.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use uuid::Uuid;
+
+use crate::error::CueCardError;
+use crate::{countdown_cues, event_time, timer, CURRENT_SLIDE};
+
+const STAGE_DISPLAY_KEY: &str = "stage_display";
+// ProPresenter's default stage display port
+const DEFAULT_PORT: u16 = 50002;
+const TICK_MS: u64 = 500;
+const LOGIN_TIMEOUT_SECS: u64 = 10;
+// Longer than any real login line, short enough not to matter
+const MAX_LOGIN_BYTES: u64 = 1024;
+const PASSWORD_LEN: usize = 10;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StageDisplaySettings {
+    pub enabled: bool,
+    pub port: Option<u16>,
+    /// Password stage display clients log in with; generated when the output
+    /// starts without one
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StageDisplayStatus {
+    #[serde(flatten)]
+    pub settings: StageDisplaySettings,
+    /// Port the output is listening on, while it runs
+    pub listening_port: Option<u16>,
+}
+
+struct RunningOutput {
+    port: u16,
+    stop: tokio::sync::watch::Sender<bool>,
+    // The accept loop, which holds the port until it ends
+    accepting: tauri::async_runtime::JoinHandle<()>,
+}
+
+static SETTINGS: Lazy<Arc<RwLock<StageDisplaySettings>>> =
+    Lazy::new(|| Arc::new(RwLock::new(StageDisplaySettings::default())));
+static RUNNING: Lazy<Arc<RwLock<Option<RunningOutput>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(None)));
+
+pub fn load_stage_display_from_store(app: &AppHandle) {
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Some(settings) = store
+            .get(STAGE_DISPLAY_KEY)
+            .and_then(|v| serde_json::from_value::<StageDisplaySettings>(v).ok())
+        {
+            *SETTINGS.write() = settings;
+        }
+    }
+}
+
+fn save_settings(app: &AppHandle) {
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Ok(json) = serde_json::to_value(&*SETTINGS.read()) {
+            store.set(STAGE_DISPLAY_KEY, json);
+            let _ = store.save();
+        }
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn format_duration(secs: i64) -> String {
+    let secs = secs.max(0);
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// The fields on stage now, in protocol order: (identifier, label, type, value)
+fn stage_fields() -> Vec<(&'static str, &'static str, &'static str, String)> {
+    let slide = CURRENT_SLIDE.read().clone();
+    let now = chrono::Utc::now();
+    let countdown = countdown_cues::get_countdown_cue().map(|cue| {
+        let remaining = (cue.ends_at_ms - now.timestamp_millis() + 999) / 1000;
+        format_duration(remaining)
+    });
+
+    vec![
+        (
+            "Clock",
+            "Clock",
+            "clock",
+            event_time::format_instant(now.timestamp(), "%H:%M:%S"),
+        ),
+        (
+            "CurrentSlide",
+            "Current Slide",
+            "slide",
+            slide
+                .as_ref()
+                .map(|s| format!("{} {}", s.slide_number, s.title))
+                .unwrap_or_default(),
+        ),
+        (
+            "CurrentSlideNotes",
+            "Current Slide Notes",
+            "slide",
+            crate::get_current_notes().unwrap_or_default(),
+        ),
+        (
+            "NextSlideNotes",
+            "Next Slide Notes",
+            "slide",
+            crate::get_next_notes().unwrap_or_default(),
+        ),
+        (
+            "ElapsedTime",
+            "Elapsed Time",
+            "elapsed",
+            timer::elapsed_secs()
+                .map(format_duration)
+                .unwrap_or_default(),
+        ),
+        (
+            "Countdown",
+            "Countdown",
+            "countdown",
+            countdown.unwrap_or_default(),
+        ),
+    ]
+}
+
+fn stage_frame() -> String {
+    let mut frame = String::from("<StageDisplayData><Fields>");
+    for (identifier, label, kind, value) in stage_fields() {
+        frame.push_str(&format!(
+            "<Field identifier=\"{}\" label=\"{}\" type=\"{}\" alpha=\"0\">{}</Field>",
+            identifier,
+            label,
+            kind,
+            escape_xml(&value)
+        ));
+    }
+    frame.push_str("</Fields></StageDisplayData>\r\n");
+    frame
+}
+
+/// Give the output a password if it has none; true when one was generated
+fn ensure_password() -> bool {
+    let mut settings = SETTINGS.write();
+    if settings.password.is_some() {
+        return false;
+    }
+    settings.password = Some(Uuid::new_v4().simple().to_string()[..PASSWORD_LEN].to_string());
+    true
+}
+
+/// The password from a `<StageDisplayLogin>` line
+fn login_password(line: &str) -> Option<&str> {
+    line.trim()
+        .strip_prefix("<StageDisplayLogin>")?
+        .strip_suffix("</StageDisplayLogin>")
+}
+
+async fn serve_client(
+    stream: TcpStream,
+    mut stop: tokio::sync::watch::Receiver<bool>,
+) -> Result<(), String> {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+
+    // Bounded, so a client that never sends a newline can't grow the buffer
+    let mut login = Vec::new();
+    tokio::time::timeout(
+        Duration::from_secs(LOGIN_TIMEOUT_SECS),
+        (&mut reader)
+            .take(MAX_LOGIN_BYTES)
+            .read_until(b'\n', &mut login),
+    )
+    .await
+    .map_err(|_| "Stage display client didn't log in".to_string())?
+    .map_err(|e| format!("Stage display read failed: {}", e))?;
+    let login = String::from_utf8_lossy(&login);
+    let expected = SETTINGS.read().password.clone();
+    let authorized = login_password(&login)
+        .zip(expected.as_deref())
+        .is_some_and(|(password, expected)| password == expected);
+    if !authorized {
+        let _ = writer
+            .write_all(b"<StageDisplayLoginFailure>Invalid Password</StageDisplayLoginFailure>\r\n")
+            .await;
+        return Err("Stage display client sent a wrong password".to_string());
+    }
+    writer
+        .write_all(b"<StageDisplayLoginSuccess />\r\n")
+        .await
+        .map_err(|e| format!("Stage display write failed: {}", e))?;
+
+    // Send a frame whenever a field changes; the clock alone does once a second
+    let mut last_frame = String::new();
+    let mut ignored = [0u8; 512];
+    loop {
+        let frame = stage_frame();
+        if frame != last_frame {
+            writer
+                .write_all(frame.as_bytes())
+                .await
+                .map_err(|e| format!("Stage display write failed: {}", e))?;
+            last_frame = frame;
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_millis(TICK_MS)) => {}
+            _ = stop.changed() => return Ok(()),
+            // The client hung up, or sent something we ignore
+            read = reader.read(&mut ignored) => {
+                if !matches!(read, Ok(n) if n > 0) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+async fn start_output() -> Result<u16, String> {
+    // The old listener has to be gone before its port can be bound again
+    stop_output().await;
+
+    if SETTINGS.read().password.is_none() {
+        return Err("The stage display needs a password".to_string());
+    }
+    let port = SETTINGS.read().port.unwrap_or(DEFAULT_PORT);
+    let listener = tokio::net::TcpListener::bind(("0.0.0.0", port))
+        .await
+        .map_err(|e| format!("Failed to bind to port {}: {}", port, e))?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to read stage display address: {}", e))?
+        .port();
+
+    let (stop, mut stopped) = tokio::sync::watch::channel(false);
+    let clients = stop.clone();
+    let accepting = tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::select! {
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, address)) => {
+                        let client_stop = clients.subscribe();
+                        tauri::async_runtime::spawn(async move {
+                            if let Err(e) = serve_client(stream, client_stop).await {
+                                eprintln!("Stage display client {}: {}", address, e);
+                            }
+                        });
+                    }
+                    Err(e) => eprintln!("Stage display accept failed: {}", e),
+                },
+                _ = stopped.changed() => break,
+            }
+        }
+    });
+    *RUNNING.write() = Some(RunningOutput {
+        port,
+        stop,
+        accepting,
+    });
+    Ok(port)
+}
+
+async fn stop_output() {
+    let running = RUNNING.write().take();
+    if let Some(running) = running {
+        let _ = running.stop.send(true);
+        let _ = running.accepting.await;
+    }
+}
+
+/// Start the output at launch if it was left on
+pub async fn restore() {
+    if !SETTINGS.read().enabled {
+        return;
+    }
+    // Left on by a version that allowed no password
+    if ensure_password() {
+        if let Some(app) = crate::APP_HANDLE.read().as_ref() {
+            save_settings(app);
+        }
+    }
+    if let Err(e) = start_output().await {
+        eprintln!("Failed to start stage display output: {}", e);
+    }
+}
+
+fn status() -> StageDisplayStatus {
+    StageDisplayStatus {
+        settings: SETTINGS.read().clone(),
+        listening_port: RUNNING.read().as_ref().map(|r| r.port),
+    }
+}
+
+/// Serve the stage display protocol on the LAN, replacing a running output.
+/// `port` and `password` replace the stored ones when given; an empty
+/// password, or none stored, gets a generated one.
+#[tauri::command]
+pub async fn start_stage_display(
+    app: AppHandle,
+    port: Option<u16>,
+    password: Option<String>,
+) -> Result<StageDisplayStatus, CueCardError> {
+    {
+        let mut settings = SETTINGS.write();
+        if port.is_some() {
+            settings.port = port;
+        }
+        if let Some(password) = password {
+            settings.password = Some(password).filter(|p| !p.is_empty());
+        }
+    }
+    ensure_password();
+    start_output().await?;
+    SETTINGS.write().enabled = true;
+    save_settings(&app);
+    Ok(status())
+}
+
+#[tauri::command]
+pub async fn stop_stage_display(app: AppHandle) {
+    stop_output().await;
+    SETTINGS.write().enabled = false;
+    save_settings(&app);
+}
+
+#[tauri::command]
+pub fn get_stage_display() -> StageDisplayStatus {
+    status()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// What the output answers a client that sends `login`, and how the
+    /// client's session ended
+    async fn log_in(login: Vec<u8>) -> (String, Result<(), String>) {
+        let listener = tokio::net::TcpListener::bind(("127.0.0.1", 0))
+            .await
+            .unwrap();
+        let address = listener.local_addr().unwrap();
+        let mut client = TcpStream::connect(address).await.unwrap();
+        let (stream, _) = listener.accept().await.unwrap();
+        let (stop, stopped) = tokio::sync::watch::channel(false);
+        let served = tokio::spawn(serve_client(stream, stopped));
+
+        client.write_all(&login).await.unwrap();
+        let mut reply = String::new();
+        BufReader::new(&mut client)
+            .read_line(&mut reply)
+            .await
+            .unwrap();
+        let _ = stop.send(true);
+        (reply, served.await.unwrap())
+    }
+
+    #[test]
+    fn refuses_wrong_passwords_and_oversized_logins() {
+        SETTINGS.write().password = Some("s3cret".to_string());
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let (reply, served) = runtime.block_on(log_in(
+            b"<StageDisplayLogin>guess</StageDisplayLogin>\r\n".to_vec(),
+        ));
+        assert!(reply.starts_with("<StageDisplayLoginFailure>"));
+        assert!(served.is_err());
+
+        // A login that runs to the read limit without ending is refused then,
+        // not when the login times out. It stops exactly at the limit, so
+        // nothing is left unread.
+        let mut oversized = b"<StageDisplayLogin>s3cret".to_vec();
+        oversized.resize(MAX_LOGIN_BYTES as usize, b'x');
+        let (reply, served) = runtime
+            .block_on(async {
+                tokio::time::timeout(
+                    Duration::from_secs(LOGIN_TIMEOUT_SECS / 2),
+                    log_in(oversized),
+                )
+                .await
+            })
+            .expect("refused before the login timeout");
+        assert!(reply.starts_with("<StageDisplayLoginFailure>"));
+        assert!(served.is_err());
+
+        let (reply, served) = runtime.block_on(log_in(
+            b"<StageDisplayLogin>s3cret</StageDisplayLogin>\r\n".to_vec(),
+        ));
+        assert_eq!(reply, "<StageDisplayLoginSuccess />\r\n");
+        assert!(served.is_ok());
+    }
+
+    #[test]
+    fn reads_the_login_line() {
+        assert_eq!(
+            login_password("<StageDisplayLogin>abc</StageDisplayLogin>\r\n"),
+            Some("abc")
+        );
+        assert_eq!(login_password("<StageDisplayLogin>abc"), None);
+        assert_eq!(login_password("abc"), None);
+    }
+}
@@ -0,0 +1,394 @@
+//! Session clock and reminder scheduling
+//!
+//! The panel's per-section countdowns stay in the frontend; this is the talk's
+//! overall elapsed time. It starts with the first slide of a session (or
+//! `timer_start`), can be paused and reset, and drives the session's
+//! reminders: a background loop checks every second and fires `reminder-fired`
+//! events, plus an OS notification for reminders that ask for one.
+//!
+//! Agenda sections get their own stopwatches (`start_section("demo")`), one
+//! running at a time, with laps. A section's timing is saved to the rehearsal
+//! analytics whenever it stops, so durations can be compared across deliveries.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::Emitter;
+use tauri_plugin_notification::NotificationExt;
+
+use crate::error::CueCardError;
+use crate::rehearsal::{self, SectionRecord};
+use crate::{clock, session, APP_HANDLE};
+
+const REMINDER_TICK_MS: u64 = 1000;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct TimerState {
+    /// Unix millis the clock was last started or resumed; `None` while paused
+    running_since: Option<i64>,
+    /// Elapsed millis before the last pause
+    accumulated_ms: i64,
+    /// Whether the clock has run at all this session
+    started: bool,
+}
+
+impl TimerState {
+    fn status(&self, now: i64) -> TimerStatus {
+        TimerStatus {
+            running: self.running_since.is_some(),
+            elapsed_ms: self.accumulated_ms + self.running_since.map_or(0, |since| now - since),
+        }
+    }
+
+    /// Start or resume; a running clock keeps its start
+    fn start(&mut self, now: i64) {
+        self.started = true;
+        if self.running_since.is_none() {
+            self.running_since = Some(now);
+        }
+    }
+
+    fn pause(&mut self, now: i64) {
+        if let Some(since) = self.running_since.take() {
+            self.accumulated_ms += now - since;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimerStatus {
+    pub running: bool,
+    pub elapsed_ms: i64,
+}
+
+#[derive(Debug, Clone)]
+struct SectionStopwatch {
+    name: String,
+    running_since: Option<i64>,
+    accumulated_ms: i64,
+    laps_ms: Vec<i64>,
+}
+
+impl SectionStopwatch {
+    fn elapsed_ms(&self, now: i64) -> i64 {
+        self.accumulated_ms + self.running_since.map_or(0, |since| now - since)
+    }
+
+    fn pause(&mut self, now: i64) {
+        if let Some(since) = self.running_since.take() {
+            self.accumulated_ms += now - since;
+        }
+    }
+
+    /// Split off the time since the previous lap (or the section start)
+    fn lap(&mut self, now: i64) {
+        let lapped: i64 = self.laps_ms.iter().sum();
+        let lap = self.elapsed_ms(now) - lapped;
+        self.laps_ms.push(lap);
+    }
+
+    fn status(&self, now: i64) -> SectionStatus {
+        SectionStatus {
+            name: self.name.clone(),
+            running: self.running_since.is_some(),
+            elapsed_ms: self.elapsed_ms(now),
+            laps_ms: self.laps_ms.clone(),
+        }
+    }
+
+    fn record(&self) -> SectionRecord {
+        SectionRecord {
+            name: self.name.clone(),
+            total_ms: self.accumulated_ms,
+            laps_ms: self.laps_ms.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SectionStatus {
+    pub name: String,
+    pub running: bool,
+    pub elapsed_ms: i64,
+    /// Split durations, each measured from the previous lap
+    pub laps_ms: Vec<i64>,
+}
+
+static TIMER: Lazy<Arc<RwLock<TimerState>>> =
+    Lazy::new(|| Arc::new(RwLock::new(TimerState::default())));
+// In the order sections were first started
+static SECTIONS: Lazy<Arc<RwLock<Vec<SectionStopwatch>>>> =
+    Lazy::new(|| Arc::new(RwLock::new(Vec::new())));
+
+fn now_ms() -> i64 {
+    clock::now_millis()
+}
+
+fn status() -> TimerStatus {
+    TIMER.read().status(now_ms())
+}
+
+pub fn elapsed_secs() -> Option<i64> {
+    if !TIMER.read().started {
+        return None;
+    }
+    Some(status().elapsed_ms / 1000)
+}
+
+fn emit_timer_state() {
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        let _ = app.emit("timer-state", status());
+    }
+}
+
+/// Start the clock on the session's first slide
+pub fn ensure_started() {
+    {
+        let mut timer = TIMER.write();
+        if timer.started {
+            return;
+        }
+        timer.start(now_ms());
+    }
+    emit_timer_state();
+}
+
+/// Back to zero and stopped, for a new session
+pub fn reset() {
+    *TIMER.write() = TimerState::default();
+    session::rearm_elapsed_reminders();
+    emit_timer_state();
+}
+
+fn section_statuses() -> Vec<SectionStatus> {
+    let now = now_ms();
+    SECTIONS.read().iter().map(|s| s.status(now)).collect()
+}
+
+fn emit_sections_changed() {
+    if let Some(app) = APP_HANDLE.read().as_ref() {
+        let _ = app.emit("sections-changed", section_statuses());
+    }
+}
+
+/// Save stopped sections to the rehearsal analytics for the current session
+fn record_sections(sections: Vec<SectionRecord>) {
+    if sections.is_empty() {
+        return;
+    }
+    let (session_id, started_at) = session::current_session();
+    for section in sections {
+        rehearsal::record_section(&session_id, started_at, section);
+    }
+}
+
+/// Drop all section stopwatches, for a new session
+pub fn reset_sections() {
+    SECTIONS.write().clear();
+    emit_sections_changed();
+}
+
+/// Fire due reminders; runs for the lifetime of the app
+pub async fn run_reminder_loop() {
+    loop {
+        tokio::time::sleep(Duration::from_millis(REMINDER_TICK_MS)).await;
+
+        let due = session::take_due_reminders(elapsed_secs(), clock::now_secs());
+        if due.is_empty() {
+            continue;
+        }
+
+        if let Some(app) = APP_HANDLE.read().as_ref() {
+            for reminder in due {
+                let _ = app.emit("reminder-fired", reminder.clone());
+                if reminder.notify {
+                    if let Err(e) = app
+                        .notification()
+                        .builder()
+                        .title("CueCard")
+                        .body(&reminder.message)
+                        .show()
+                    {
+                        eprintln!("Failed to show reminder notification: {}", e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub fn timer_start() -> TimerStatus {
+    TIMER.write().start(now_ms());
+    emit_timer_state();
+    status()
+}
+
+#[tauri::command]
+pub fn timer_pause() -> TimerStatus {
+    TIMER.write().pause(now_ms());
+    emit_timer_state();
+    status()
+}
+
+#[tauri::command]
+pub fn timer_reset() -> TimerStatus {
+    reset();
+    status()
+}
+
+#[tauri::command]
+pub fn get_timer_state() -> TimerStatus {
+    status()
+}
+
+/// Start or resume a section's stopwatch, pausing whichever section was running
+#[tauri::command]
+pub fn start_section(name: String) -> Result<SectionStatus, CueCardError> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Section name can't be empty".into());
+    }
+
+    let (status, paused) = switch_section(&mut SECTIONS.write(), &name, now_ms());
+    record_sections(paused);
+    emit_sections_changed();
+    Ok(status)
+}
+
+/// Run `name`'s stopwatch, adding it if it's new, and pause the others; the
+/// paused sections' timings are returned for recording
+fn switch_section(
+    sections: &mut Vec<SectionStopwatch>,
+    name: &str,
+    now: i64,
+) -> (SectionStatus, Vec<SectionRecord>) {
+    let mut paused = Vec::new();
+    for section in sections.iter_mut() {
+        if section.name != name && section.running_since.is_some() {
+            section.pause(now);
+            paused.push(section.record());
+        }
+    }
+
+    let index = match sections.iter().position(|s| s.name == name) {
+        Some(i) => i,
+        None => {
+            sections.push(SectionStopwatch {
+                name: name.to_string(),
+                running_since: None,
+                accumulated_ms: 0,
+                laps_ms: Vec::new(),
+            });
+            sections.len() - 1
+        }
+    };
+    let section = &mut sections[index];
+    if section.running_since.is_none() {
+        section.running_since = Some(now);
+    }
+    (section.status(now), paused)
+}
+
+/// Record a lap: the time since the previous lap (or the section start)
+#[tauri::command]
+pub fn lap_section(name: String) -> Result<SectionStatus, CueCardError> {
+    let now = now_ms();
+    let status = {
+        let mut sections = SECTIONS.write();
+        let section = sections
+            .iter_mut()
+            .find(|s| s.name == name)
+            .ok_or("Unknown section")?;
+        section.lap(now);
+        section.status(now)
+    };
+
+    emit_sections_changed();
+    Ok(status)
+}
+
+#[tauri::command]
+pub fn stop_section(name: String) -> Result<SectionStatus, CueCardError> {
+    let now = now_ms();
+    let (status, record) = {
+        let mut sections = SECTIONS.write();
+        let section = sections
+            .iter_mut()
+            .find(|s| s.name == name)
+            .ok_or("Unknown section")?;
+        section.pause(now);
+        (section.status(now), section.record())
+    };
+
+    record_sections(vec![record]);
+    emit_sections_changed();
+    Ok(status)
+}
+
+#[tauri::command]
+pub fn get_sections() -> Vec<SectionStatus> {
+    section_statuses()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_clock_counts_only_while_running() {
+        let mut timer = TimerState::default();
+        assert_eq!(timer.status(5_000).elapsed_ms, 0);
+
+        timer.start(1_000);
+        assert!(timer.started);
+        // Starting a running clock doesn't restart it
+        timer.start(2_000);
+        assert_eq!(timer.status(4_000).elapsed_ms, 3_000);
+
+        timer.pause(4_000);
+        let paused = timer.status(60_000);
+        assert!(!paused.running);
+        assert_eq!(paused.elapsed_ms, 3_000);
+
+        timer.start(70_000);
+        assert_eq!(timer.status(71_500).elapsed_ms, 4_500);
+    }
+
+    #[test]
+    fn one_section_runs_at_a_time() {
+        let mut sections = Vec::new();
+        let (intro, paused) = switch_section(&mut sections, "intro", 0);
+        assert!(intro.running && paused.is_empty());
+
+        let (demo, paused) = switch_section(&mut sections, "demo", 4_000);
+        assert!(demo.running);
+        assert_eq!(paused.len(), 1);
+        assert_eq!(
+            (paused[0].name.as_str(), paused[0].total_ms),
+            ("intro", 4_000)
+        );
+
+        // Back to a section resumes it where it left off
+        let (intro, paused) = switch_section(&mut sections, "intro", 10_000);
+        assert_eq!(paused[0].total_ms, 6_000);
+        assert_eq!(intro.elapsed_ms, 4_000);
+        assert_eq!(sections.len(), 2);
+    }
+
+    #[test]
+    fn laps_split_from_the_previous_lap() {
+        let mut sections = Vec::new();
+        switch_section(&mut sections, "q&a", 0);
+        let section = &mut sections[0];
+        section.lap(2_000);
+        section.pause(3_000);
+        // Paused time isn't part of the next lap
+        section.running_since = Some(10_000);
+        section.lap(12_500);
+        assert_eq!(section.laps_ms, vec![2_000, 3_500]);
+        assert_eq!(section.elapsed_ms(12_500), 5_500);
+    }
+}
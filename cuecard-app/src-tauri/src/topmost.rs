@@ -0,0 +1,181 @@
+//! Keeping the panel above fullscreen presentation apps (Windows)
+//!
+//! `alwaysOnTop` puts the panel in the topmost band next to the taskbar, but
+//! some presentation software going exclusive fullscreen ends up above it. With
+//! the "above everything" banding, a watcher polls the foreground window and,
+//! while a fullscreen window other than the panel has focus, puts the panel
+//! back on top without activating it. "Above taskbar" leaves fullscreen apps
+//! alone. macOS gets the same from the panel's collection behavior.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+const TOPMOST_BANDING_KEY: &str = "topmost_banding";
+#[cfg(target_os = "windows")]
+const WATCH_INTERVAL_MS: u64 = 500;
+
+/// How high the panel stays
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TopmostBanding {
+    /// Topmost like the taskbar; fullscreen apps may cover it
+    #[default]
+    AboveTaskbar,
+    /// Also re-asserted over fullscreen apps
+    AboveEverything,
+}
+
+static TOPMOST_BANDING: Lazy<Arc<RwLock<TopmostBanding>>> =
+    Lazy::new(|| Arc::new(RwLock::new(TopmostBanding::default())));
+
+pub fn load_banding_from_store(app: &AppHandle) {
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Some(banding) = store
+            .get(TOPMOST_BANDING_KEY)
+            .and_then(|v| serde_json::from_value::<TopmostBanding>(v).ok())
+        {
+            *TOPMOST_BANDING.write() = banding;
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod win {
+    use windows::Win32::Foundation::{HWND, RECT};
+    use windows::Win32::Graphics::Gdi::{
+        GetMonitorInfoW, MonitorFromWindow, MONITORINFO, MONITOR_DEFAULTTONEAREST,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetClassNameW, GetForegroundWindow, GetShellWindow, GetWindowRect, SetWindowPos,
+        HWND_TOPMOST, SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOSIZE,
+    };
+
+    /// Whether the window covers its whole monitor, leaving out the desktop itself
+    fn is_fullscreen(hwnd: HWND) -> bool {
+        unsafe {
+            if hwnd == GetShellWindow() {
+                return false;
+            }
+            let mut class = [0u16; 32];
+            let len = GetClassNameW(hwnd, &mut class) as usize;
+            if String::from_utf16_lossy(&class[..len]) == "WorkerW" {
+                return false;
+            }
+
+            let mut rect = RECT::default();
+            if GetWindowRect(hwnd, &mut rect).is_err() {
+                return false;
+            }
+            let monitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+            let mut info = MONITORINFO {
+                cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+                ..Default::default()
+            };
+            if !GetMonitorInfoW(monitor, &mut info).as_bool() {
+                return false;
+            }
+            covers(&rect, &info.rcMonitor)
+        }
+    }
+
+    /// Whether `window` reaches every edge of `monitor`
+    pub(super) fn covers(window: &RECT, monitor: &RECT) -> bool {
+        window.left <= monitor.left
+            && window.top <= monitor.top
+            && window.right >= monitor.right
+            && window.bottom >= monitor.bottom
+    }
+
+    /// Put the panel back on top if a fullscreen window has the foreground
+    pub fn reassert(panel: isize) {
+        let panel = HWND(panel as _);
+        unsafe {
+            let foreground = GetForegroundWindow();
+            if foreground.is_invalid() || foreground == panel || !is_fullscreen(foreground) {
+                return;
+            }
+            let _ = SetWindowPos(
+                panel,
+                HWND_TOPMOST,
+                0,
+                0,
+                0,
+                0,
+                SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE,
+            );
+        }
+    }
+}
+
+/// Start the foreground watcher for the main window
+#[cfg(target_os = "windows")]
+pub fn start_watcher(app: &AppHandle) {
+    use tauri::Manager;
+
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    let panel = match window.hwnd() {
+        Ok(hwnd) => hwnd.0 as isize,
+        Err(e) => {
+            eprintln!("Failed to get panel window handle: {}", e);
+            return;
+        }
+    };
+
+    std::thread::spawn(move || loop {
+        std::thread::sleep(std::time::Duration::from_millis(WATCH_INTERVAL_MS));
+        if *TOPMOST_BANDING.read() == TopmostBanding::AboveEverything {
+            win::reassert(panel);
+        }
+    });
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn start_watcher(_app: &AppHandle) {}
+
+#[tauri::command]
+pub fn get_topmost_banding() -> TopmostBanding {
+    *TOPMOST_BANDING.read()
+}
+
+#[tauri::command]
+pub fn set_topmost_banding(app: AppHandle, banding: TopmostBanding) {
+    *TOPMOST_BANDING.write() = banding;
+    if let Ok(store) = app.store("cuecard-store.json") {
+        if let Ok(json) = serde_json::to_value(banding) {
+            store.set(TOPMOST_BANDING_KEY, json);
+            let _ = store.save();
+        }
+    }
+}
+
+#[cfg(all(test, target_os = "windows"))]
+mod tests {
+    use super::*;
+    use windows::Win32::Foundation::RECT;
+
+    fn rect(left: i32, top: i32, right: i32, bottom: i32) -> RECT {
+        RECT {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    #[test]
+    fn fullscreen_means_covering_the_whole_monitor() {
+        let monitor = rect(1920, 0, 3840, 1080);
+        assert!(win::covers(&rect(1920, 0, 3840, 1080), &monitor));
+        // Borderless windows often overhang by a few pixels
+        assert!(win::covers(&rect(1912, -8, 3848, 1088), &monitor));
+        // Maximized, with the taskbar still showing
+        assert!(!win::covers(&rect(1920, 0, 3840, 1040), &monitor));
+        assert!(!win::covers(&rect(0, 0, 1920, 1080), &monitor));
+    }
+}
@@ -0,0 +1,393 @@
+//! Showing the notes natively while the panel's webview is down
+//!
+//! If the webview's content process dies mid-talk, the panel goes blank. The
+//! frontend calls `webview_heartbeat` every couple of seconds; once beats have
+//! started and then stop for `STALE_AFTER_MS`, the watchdog lays a plain
+//! native text view over the panel with the current slide's notes (an
+//! NSTextField in the panel on macOS, an owned popup on Windows), keeps it in
+//! step with slide changes and reloads the webview. The first beat after that
+//! hides it again. The text view follows the panel's screenshot protection.
+//! A hidden panel's timers are throttled and a sleeping machine's stopped, so
+//! no checks are made while the panel is hidden or `sleep_wake` has paused
+//! background work, and the deadline starts over once it's shown or awake.
+//! Sent as `webview-unresponsive` and `webview-recovered`; Linux gets the
+//! reload only.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+const CHECK_INTERVAL_MS: u64 = 1000;
+const STALE_AFTER_MS: u128 = 6000;
+const RELOAD_RETRY_SECS: u64 = 15;
+
+#[derive(Default)]
+struct Watchdog {
+    last_beat: Option<Instant>,
+    /// Text on the native view while it's up
+    fallback: Option<String>,
+    last_reload: Option<Instant>,
+}
+
+impl Watchdog {
+    /// A beat came in; whether it ends a fallback
+    fn beat(&mut self, now: Instant) -> bool {
+        self.last_beat = Some(now);
+        self.last_reload = None;
+        self.fallback.take().is_some()
+    }
+
+    /// Count the wait for the next beat from `now`, once beats have started
+    fn restart_deadline(&mut self, now: Instant) {
+        if self.last_beat.is_some() {
+            self.last_beat = Some(now);
+        }
+    }
+
+    fn is_stale(&self, now: Instant) -> bool {
+        self.last_beat
+            .is_some_and(|beat| now.duration_since(beat).as_millis() > STALE_AFTER_MS)
+    }
+
+    /// Put `text` on the native view: whether it was just shown, whether its
+    /// text changed, and whether the webview is due another reload
+    fn fall_back(&mut self, text: &str, now: Instant) -> (bool, bool, bool) {
+        let shown = self.fallback.is_none();
+        let changed = self.fallback.as_deref() != Some(text);
+        self.fallback = Some(text.to_string());
+        let reload_due = self
+            .last_reload
+            .is_none_or(|at| now.duration_since(at) >= Duration::from_secs(RELOAD_RETRY_SECS));
+        if reload_due {
+            self.last_reload = Some(now);
+        }
+        (shown, changed, reload_due)
+    }
+}
+
+static WATCHDOG: Lazy<Arc<RwLock<Watchdog>>> =
+    Lazy::new(|| Arc::new(RwLock::new(Watchdog::default())));
+
+/// Called by the frontend while it's alive
+#[tauri::command]
+pub fn webview_heartbeat(app: AppHandle) {
+    let recovered = WATCHDOG.write().beat(Instant::now());
+    if recovered {
+        native::hide(&app);
+        let _ = app.emit("webview-recovered", ());
+    }
+}
+
+fn fallback_text() -> String {
+    let slide_number = crate::CURRENT_SLIDE
+        .read()
+        .as_ref()
+        .map(|slide| slide.slide_number);
+    let notes = crate::get_current_notes().unwrap_or_default();
+    match slide_number {
+        Some(number) => format!("Slide {}\n\n{}", number, notes),
+        None => notes,
+    }
+}
+
+fn reload(app: &AppHandle) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    if let Err(e) = window.reload() {
+        eprintln!("Failed to reload the panel: {}", e);
+    }
+}
+
+/// Whether missed beats mean anything right now
+fn watching(app: &AppHandle) -> bool {
+    if crate::sleep_wake::is_paused() {
+        return false;
+    }
+    app.get_webview_window("main")
+        .and_then(|window| window.is_visible().ok())
+        .unwrap_or(false)
+}
+
+/// Watch for missed heartbeats; runs for the lifetime of the app
+pub async fn run_watchdog(app: AppHandle) {
+    loop {
+        tokio::time::sleep(Duration::from_millis(CHECK_INTERVAL_MS)).await;
+
+        if !watching(&app) {
+            WATCHDOG.write().restart_deadline(Instant::now());
+            continue;
+        }
+
+        if !WATCHDOG.read().is_stale(Instant::now()) {
+            continue;
+        }
+
+        let text = fallback_text();
+        let (shown, changed, reload_due) = WATCHDOG.write().fall_back(&text, Instant::now());
+
+        if shown {
+            eprintln!("Panel stopped responding; showing notes natively");
+            let _ = app.emit("webview-unresponsive", ());
+        }
+        if changed {
+            native::show(&app, text);
+        }
+        if reload_due {
+            reload(&app);
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod native {
+    use objc2::rc::Retained;
+    use objc2::MainThreadMarker;
+    use objc2_app_kit::{NSAutoresizingMaskOptions, NSColor, NSFont, NSTextField, NSWindow};
+    use objc2_foundation::NSString;
+    use std::cell::RefCell;
+    use tauri::{AppHandle, Manager};
+
+    thread_local! {
+        // Only touched on the main thread
+        static FIELD: RefCell<Option<Retained<NSTextField>>> = const { RefCell::new(None) };
+    }
+
+    fn create(window: &NSWindow, mtm: MainThreadMarker) -> Option<Retained<NSTextField>> {
+        let content = window.contentView()?;
+        let field = NSTextField::wrappingLabelWithString(&NSString::from_str(""), mtm);
+        field.setFrame(content.bounds());
+        field.setAutoresizingMask(
+            NSAutoresizingMaskOptions::ViewWidthSizable
+                | NSAutoresizingMaskOptions::ViewHeightSizable,
+        );
+        field.setDrawsBackground(true);
+        field.setBackgroundColor(Some(&NSColor::blackColor()));
+        field.setTextColor(Some(&NSColor::whiteColor()));
+        field.setFont(Some(&NSFont::systemFontOfSize(18.0)));
+        // Above the webview
+        content.addSubview(&field);
+        Some(field)
+    }
+
+    pub fn show(app: &AppHandle, text: String) {
+        let Some(window) = app.get_webview_window("main") else {
+            return;
+        };
+        let Ok(ns_window) = window.ns_window().map(|w| w as usize) else {
+            return;
+        };
+        let _ = app.run_on_main_thread(move || {
+            let Some(mtm) = MainThreadMarker::new() else {
+                return;
+            };
+            // SAFETY: the panel outlives the app's event loop
+            let window = unsafe { &*(ns_window as *const NSWindow) };
+            FIELD.with(|field| {
+                let mut field = field.borrow_mut();
+                if field.is_none() {
+                    *field = create(window, mtm);
+                }
+                if let Some(field) = field.as_ref() {
+                    field.setStringValue(&NSString::from_str(&text));
+                    field.setHidden(false);
+                }
+            });
+        });
+    }
+
+    pub fn hide(app: &AppHandle) {
+        let _ = app.run_on_main_thread(|| {
+            FIELD.with(|field| {
+                if let Some(field) = field.borrow().as_ref() {
+                    field.setHidden(true);
+                }
+            });
+        });
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod native {
+    use once_cell::sync::Lazy;
+    use parking_lot::RwLock;
+    use tauri::{AppHandle, Manager};
+    use windows::core::{w, HSTRING};
+    use windows::Win32::Foundation::{HWND, LPARAM, RECT, WPARAM};
+    use windows::Win32::Graphics::Gdi::{ClientToScreen, CreateFontW, FW_NORMAL};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        CreateWindowExW, GetClientRect, SendMessageW, SetWindowDisplayAffinity, SetWindowPos,
+        SetWindowTextW, ShowWindow, HWND_TOP, SWP_NOACTIVATE, SW_HIDE, SW_SHOWNOACTIVATE,
+        WDA_EXCLUDEFROMCAPTURE, WDA_NONE, WM_SETFONT, WS_EX_NOACTIVATE, WS_EX_TOOLWINDOW, WS_POPUP,
+    };
+
+    // The popup's handle once created; created and used on the main thread
+    static POPUP: Lazy<RwLock<Option<isize>>> = Lazy::new(|| RwLock::new(None));
+
+    /// The panel's client area in screen coordinates
+    fn client_rect(panel: HWND) -> Option<RECT> {
+        let mut rect = RECT::default();
+        unsafe { GetClientRect(panel, &mut rect).ok()? };
+        let mut origin = windows::Win32::Foundation::POINT::default();
+        unsafe { ClientToScreen(panel, &mut origin).ok().ok()? };
+        Some(RECT {
+            left: origin.x,
+            top: origin.y,
+            right: origin.x + rect.right,
+            bottom: origin.y + rect.bottom,
+        })
+    }
+
+    fn create(panel: HWND) -> Option<HWND> {
+        unsafe {
+            // Owned by the panel so it stays above it
+            let popup = CreateWindowExW(
+                WS_EX_NOACTIVATE | WS_EX_TOOLWINDOW,
+                w!("STATIC"),
+                w!(""),
+                WS_POPUP,
+                0,
+                0,
+                0,
+                0,
+                panel,
+                None,
+                None,
+                None,
+            )
+            .ok()?;
+            let font = CreateFontW(
+                -24,
+                0,
+                0,
+                0,
+                FW_NORMAL.0 as i32,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                w!("Segoe UI"),
+            );
+            SendMessageW(popup, WM_SETFONT, WPARAM(font.0 as usize), LPARAM(1));
+            Some(popup)
+        }
+    }
+
+    pub fn show(app: &AppHandle, text: String) {
+        let Some(window) = app.get_webview_window("main") else {
+            return;
+        };
+        let Ok(panel) = window.hwnd().map(|hwnd| hwnd.0 as isize) else {
+            return;
+        };
+        let _ = app.run_on_main_thread(move || {
+            let panel = HWND(panel as _);
+            let existing = *POPUP.read();
+            let popup = match existing {
+                Some(popup) => HWND(popup as _),
+                None => {
+                    let Some(popup) = create(panel) else {
+                        eprintln!("Failed to create the fallback notes window");
+                        return;
+                    };
+                    *POPUP.write() = Some(popup.0 as isize);
+                    popup
+                }
+            };
+            let affinity = if crate::screenshot_protection::get_screenshot_protection().enabled {
+                WDA_EXCLUDEFROMCAPTURE
+            } else {
+                WDA_NONE
+            };
+            unsafe {
+                let _ = SetWindowDisplayAffinity(popup, affinity);
+                let _ = SetWindowTextW(popup, &HSTRING::from(text.replace('\n', "\r\n")));
+                if let Some(rect) = client_rect(panel) {
+                    let _ = SetWindowPos(
+                        popup,
+                        HWND_TOP,
+                        rect.left,
+                        rect.top,
+                        rect.right - rect.left,
+                        rect.bottom - rect.top,
+                        SWP_NOACTIVATE,
+                    );
+                }
+                let _ = ShowWindow(popup, SW_SHOWNOACTIVATE);
+            }
+        });
+    }
+
+    pub fn hide(app: &AppHandle) {
+        let _ = app.run_on_main_thread(|| {
+            if let Some(popup) = *POPUP.read() {
+                unsafe {
+                    let _ = ShowWindow(HWND(popup as _), SW_HIDE);
+                }
+            }
+        });
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod native {
+    use tauri::AppHandle;
+
+    pub fn show(_app: &AppHandle, _text: String) {}
+
+    pub fn hide(_app: &AppHandle) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn falls_back_once_beats_stop_and_recovers_on_the_next() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut watchdog = Watchdog::default();
+
+        // Nothing to miss before the first beat
+        assert!(!watchdog.is_stale(at(60_000)));
+
+        assert!(!watchdog.beat(at(0)));
+        assert!(!watchdog.is_stale(at(6_000)));
+        assert!(watchdog.is_stale(at(6_001)));
+
+        assert_eq!(watchdog.fall_back("Slide 1", at(7_000)), (true, true, true));
+        // Same text, reload not yet due again
+        assert_eq!(
+            watchdog.fall_back("Slide 1", at(8_000)),
+            (false, false, false)
+        );
+        assert_eq!(
+            watchdog.fall_back("Slide 2", at(22_000)),
+            (false, true, true)
+        );
+
+        assert!(watchdog.beat(at(23_000)));
+        assert!(watchdog.fallback.is_none() && watchdog.last_reload.is_none());
+    }
+
+    #[test]
+    fn a_hidden_panel_starts_the_wait_over() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut watchdog = Watchdog::default();
+
+        watchdog.restart_deadline(at(10_000));
+        assert!(watchdog.last_beat.is_none());
+
+        watchdog.beat(at(0));
+        watchdog.restart_deadline(at(30_000));
+        assert!(!watchdog.is_stale(at(35_000)));
+        assert!(watchdog.is_stale(at(36_001)));
+    }
+}
@@ -603,7 +603,7 @@ let shortcutsLink;
 let refreshBtn;
 let notesInputHighlight;
 let btnStart, btnPause, btnReset;
-let opacitySlider, opacityValue, ghostModeToggle, shortcutsToggle;
+let opacitySlider, opacityValue, ghostModeToggle, shortcutsToggle, revalidateOnFocusToggle;
 let themeSystemBtn, themeLightBtn, themeDarkBtn;
 let speedSlider, speedValue;
 let editNoteBtn;
@@ -637,6 +637,10 @@ let remainingTimeSeconds = 0; // Current remaining time for countdown
 // Notes metadata
 let notesHasTimeTags = false;
 
+// Inline countdowns from [countdown mm:ss] tags, run by the backend
+let countdownCueEvents = new Map(); // Latest countdown-cue event per tag index, for one slide
+let countdownCueInterval = null; // Refreshes the running countdown's tag
+
 // Edit Mode State
 let isEditMode = false; // false = done mode (readonly, highlighted), true = edit mode (editable, not highlighted)
 let currentNoteId = null; // Track the ID of the currently loaded note for updates
@@ -696,6 +700,7 @@ window.addEventListener("DOMContentLoaded", async () => {
   opacityValue = document.getElementById("opacity-value");
   ghostModeToggle = document.getElementById("ghost-mode-toggle");
   shortcutsToggle = document.getElementById("shortcuts-toggle");
+  revalidateOnFocusToggle = document.getElementById("revalidate-on-focus-toggle");
   themeSystemBtn = document.getElementById("theme-system");
   themeLightBtn = document.getElementById("theme-light");
   themeDarkBtn = document.getElementById("theme-dark");
@@ -725,6 +730,7 @@ window.addEventListener("DOMContentLoaded", async () => {
 
   // Set up update checker
   setupUpdateChecker();
+  setupWebviewHeartbeat();
 
   // Set up refresh button handler
   setupRefreshButton();
@@ -770,6 +776,9 @@ window.addEventListener("DOMContentLoaded", async () => {
     await listen("slide-update", (event) => {
       handleSlideUpdate(event.payload);
     });
+    await listen("countdown-cue", (event) => {
+      handleCountdownCue(event.payload);
+    });
   }
 
   // Listen for auth status changes
@@ -1469,6 +1478,9 @@ function highlightNotesForInput(text) {
   // Pattern for [note ...] syntax
   const notePattern = /\[note\s+([^\]]+)\]/gi;
 
+  // Pattern for [countdown mm:ss] syntax
+  const countdownPattern = /\[countdown\s+\d{1,2}:\d{2}\]/gi;
+
   // Split by time markers to create sections
   const parts = safe.split(timePattern);
 
@@ -1482,6 +1494,9 @@ function highlightNotesForInput(text) {
     sectionContent = sectionContent.replace(notePattern, (match, note) => {
       return `<span class="action-tag">[${note}]</span>`;
     });
+    sectionContent = sectionContent.replace(countdownPattern, (match) => {
+      return `<span class="action-tag">${match}</span>`;
+    });
     // Convert newlines to <br>
     sectionContent = sectionContent.replace(/\n/g, '<br>');
 
@@ -1505,6 +1520,9 @@ function highlightNotesForInput(text) {
     sectionContent = sectionContent.replace(notePattern, (match, note) => {
       return `<span class="action-tag">[${note}]</span>`;
     });
+    sectionContent = sectionContent.replace(countdownPattern, (match) => {
+      return `<span class="action-tag">${match}</span>`;
+    });
     // Convert newlines to <br>
     sectionContent = sectionContent.replace(/\n/g, '<br>');
 
@@ -1691,6 +1709,10 @@ async function checkCurrentSlide() {
     if (slide) {
       const notes = await invoke("get_current_notes");
       handleSlideUpdate({ slide_data: slide, notes }, false); // Don't auto-show
+      const countdown = await invoke("get_countdown_cue");
+      if (countdown) {
+        handleCountdownCue(countdown);
+      }
     }
   } catch (error) {
     console.error("Error checking current slide:", error);
@@ -1945,6 +1967,7 @@ function truncateText(text, maxLength = 35) {
 function displayNotes(text, slideData = null) {
   const highlighted = highlightNotes(text);
   notesContent.innerHTML = highlighted;
+  updateCountdownTags();
   if (notesContent) {
     notesContent.scrollTop = 0;
   }
@@ -2002,6 +2025,16 @@ function highlightNotes(text) {
   // Pattern for "CueCard Extension" - replace with link
   const cuecardPattern = /CueCard Extension/gi;
 
+  // Numbers [countdown mm:ss] tags in order across sections, as the backend does
+  let countdownIndex = 0;
+  const renderCountdowns = (content) => content.replace(countdownTagPattern, (match, minutes, seconds) => {
+    const duration = parseInt(minutes) * 60 + parseInt(seconds);
+    if (parseInt(seconds) >= 60 || duration === 0) {
+      return match;
+    }
+    return `<span class="action-tag countdown-tag" data-countdown-index="${countdownIndex++}" data-duration="${duration}">[${formatCountdown(duration)}]</span>`;
+  });
+
   // Split by time markers to create sections
   const parts = safe.split(timePattern);
 
@@ -2019,6 +2052,7 @@ function highlightNotes(text) {
     sectionContent = sectionContent.replace(cuecardPattern, (match) => {
       return `<a href="https://cuecard.dev/#download" class="slides-link" target="_blank" rel="noopener noreferrer">${match}</a>`;
     });
+    sectionContent = renderCountdowns(sectionContent);
     // Convert newlines to <br>
     sectionContent = sectionContent.replace(/\n/g, '<br>');
 
@@ -2046,6 +2080,7 @@ function highlightNotes(text) {
     sectionContent = sectionContent.replace(cuecardPattern, (match) => {
       return `<a href="https://cuecard.dev/#download" class="slides-link" target="_blank" rel="noopener noreferrer">${match}</a>`;
     });
+    sectionContent = renderCountdowns(sectionContent);
     // Convert newlines to <br>
     sectionContent = sectionContent.replace(/\n/g, '<br>');
 
@@ -2074,6 +2109,60 @@ function highlightNotes(text) {
   return result;
 }
 
+// =============================================================================
+// COUNTDOWN CUES
+// =============================================================================
+
+// Pattern for [countdown mm:ss] syntax
+const countdownTagPattern = /\[countdown\s+(\d{1,2}):(\d{2})\]/gi;
+
+function formatCountdown(totalSeconds) {
+  const minutes = Math.floor(totalSeconds / 60);
+  const seconds = totalSeconds % 60;
+  return `${String(minutes).padStart(2, '0')}:${String(seconds).padStart(2, '0')}`;
+}
+
+// Keep the latest state of each countdown on the slide; the backend keeps the clock
+function handleCountdownCue(event) {
+  const [first] = countdownCueEvents.values();
+  if (first && first.slide_id !== event.slide_id) {
+    countdownCueEvents = new Map();
+  }
+  countdownCueEvents.set(event.cue.index, event);
+
+  const running = [...countdownCueEvents.values()].some((e) => e.state === 'running');
+  if (running && !countdownCueInterval) {
+    countdownCueInterval = setInterval(updateCountdownTags, 250);
+  } else if (!running && countdownCueInterval) {
+    clearInterval(countdownCueInterval);
+    countdownCueInterval = null;
+  }
+  updateCountdownTags();
+}
+
+// Show each countdown tag of the current slide's notes in its state
+function updateCountdownTags() {
+  if (!notesContent) {
+    return;
+  }
+  const onSlide = currentSlideData &&
+    [...countdownCueEvents.values()].every((e) => e.slide_id === currentSlideData.slideId);
+
+  notesContent.querySelectorAll('.countdown-tag').forEach((tag) => {
+    const event = onSlide ? countdownCueEvents.get(Number(tag.dataset.countdownIndex)) : null;
+    const state = event ? event.state : null;
+    let seconds = Number(tag.dataset.duration);
+    if (state === 'running') {
+      seconds = Math.max(0, Math.ceil((event.ends_at_ms - Date.now()) / 1000));
+    } else if (state === 'finished') {
+      seconds = 0;
+    }
+    tag.textContent = `[${formatCountdown(seconds)}]`;
+    tag.classList.toggle('countdown-running', state === 'running');
+    tag.classList.toggle('countdown-finished', state === 'finished');
+  });
+}
+
 // Escape HTML to prevent XSS (preserves newlines)
 function escapeHtml(text) {
   return text
@@ -2167,6 +2256,18 @@ function setupHeader() {
   });
 }
 
+// =============================================================================
+// WEBVIEW HEARTBEAT
+// =============================================================================
+
+// Let the backend know this webview is alive; if the beats stop it shows the
+// notes natively and reloads the page
+function setupWebviewHeartbeat() {
+  const beat = () => invoke('webview_heartbeat').catch(() => {});
+  beat();
+  setInterval(beat, 2000);
+}
+
 // =============================================================================
 // UPDATE CHECKER
 // =============================================================================
@@ -2464,6 +2565,22 @@ function setupSettings() {
     });
   }
 
+  // Refresh-on-focus toggle handler (the app keeps this setting in its store)
+  if (revalidateOnFocusToggle) {
+    revalidateOnFocusToggle.addEventListener("change", async (e) => {
+      const enabled = e.target.checked;
+      trackSettingChange('revalidate_on_focus', enabled);
+      if (invoke) {
+        try {
+          await invoke("set_revalidate_on_focus", { enabled });
+        } catch (error) {
+          console.error("Error setting refresh on focus:", error);
+          revalidateOnFocusToggle.checked = !enabled;
+        }
+      }
+    });
+  }
+
   // Auto-scroll speed slider handler
   let speedTrackingTimeout = null;
   if (speedSlider) {
@@ -2545,6 +2662,15 @@ async function loadCurrentSettings() {
     shortcutsToggle.checked = shortcutsEnabled;
   }
 
+  // Refresh-on-focus toggle
+  if (revalidateOnFocusToggle && invoke) {
+    try {
+      revalidateOnFocusToggle.checked = await invoke("get_revalidate_on_focus");
+    } catch (error) {
+      console.error("Error loading refresh on focus:", error);
+    }
+  }
+
   // Update speed slider and display
   if (speedSlider) {
     speedSlider.value = autoScrollSpeed;
@@ -1,32 +1,101 @@
 // CueCard Extension - Background Service Worker
 // Monitors connection status and sends slide data to the CueCard app
 
-const API_ENDPOINT = 'http://localhost:3642';
+// The app listens on the first free port of this range, so it's found by
+// probing /health on each in turn (keep in step with the app's server ports)
+const BASE_PORT = 3642;
+const PORT_CANDIDATES = 10;
+let apiEndpoint = null;
 let connectionStatus = 'unknown';
 
 // Get browser API (cross-browser compatibility)
 const browserAPI = typeof browser !== 'undefined' ? browser : chrome;
 
-// Check API connection status
-async function checkConnection() {
-  try {
-    const controller = new AbortController();
-    const timeoutId = setTimeout(() => controller.abort(), 5000);
+// Sent with every request so the app can check compatibility
+const EXTENSION_VERSION = browserAPI.runtime.getManifest().version;
+const VERSION_HEADERS = { 'X-CueCard-Extension-Version': EXTENSION_VERSION };
+
+// Each browser profile has its own extension storage, so a random ID kept there
+// lets the app tell profiles apart when the same deck is open in several
+let clientIdPromise = null;
+
+function getClientId() {
+  if (!clientIdPromise) {
+    clientIdPromise = (async () => {
+      try {
+        const stored = await browserAPI.storage.local.get('clientId');
+        if (stored && stored.clientId) {
+          return stored.clientId;
+        }
+        const clientId = crypto.randomUUID();
+        await browserAPI.storage.local.set({ clientId });
+        return clientId;
+      } catch (error) {
+        console.warn('[CueCard] Failed to load client ID:', error);
+        return null;
+      }
+    })();
+  }
+  return clientIdPromise;
+}
 
-    const response = await fetch(`${API_ENDPOINT}/health`, {
+// Probe one port's /health; resolves to the response if the CueCard app answers
+async function probePort(port, timeoutMs) {
+  const controller = new AbortController();
+  const timeoutId = setTimeout(() => controller.abort(), timeoutMs);
+  try {
+    const response = await fetch(`http://localhost:${port}/health`, {
       method: 'GET',
+      headers: VERSION_HEADERS,
       signal: controller.signal
     });
-
-    clearTimeout(timeoutId);
-    connectionStatus = response.ok ? 'connected' : 'error';
+    if (response.status === 426) {
+      return response;
+    }
+    if (!response.ok) {
+      return null;
+    }
+    const body = await response.json().catch(() => null);
+    return body && body.server === 'cuecard-app' ? response : null;
   } catch (error) {
-    if (error.name === 'AbortError') {
-      connectionStatus = 'timeout';
-    } else {
-      connectionStatus = 'disconnected';
+    return null;
+  } finally {
+    clearTimeout(timeoutId);
+  }
+}
+
+// Find the app, trying the last port it answered on first
+async function findServer() {
+  const ports = [];
+  for (let i = 0; i < PORT_CANDIDATES; i++) {
+    ports.push(BASE_PORT + i);
+  }
+  const lastPort = apiEndpoint ? Number(new URL(apiEndpoint).port) : null;
+  if (lastPort) {
+    ports.sort((a, b) => (b === lastPort) - (a === lastPort));
+  }
+
+  for (const port of ports) {
+    const response = await probePort(port, port === lastPort ? 5000 : 1000);
+    if (response) {
+      apiEndpoint = `http://localhost:${port}`;
+      return response;
     }
   }
+  apiEndpoint = null;
+  return null;
+}
+
+// Check API connection status
+async function checkConnection() {
+  const response = await findServer();
+  if (!response) {
+    connectionStatus = 'disconnected';
+  } else if (response.status === 426) {
+    connectionStatus = 'outdated';
+  } else {
+    connectionStatus = 'connected';
+  }
   updateBadge();
 }
 
@@ -36,6 +105,7 @@ function updateBadge() {
     connected: { text: '', color: '#4CAF50' },
     disconnected: { text: '!', color: '#F44336' },
     error: { text: 'E', color: '#FF9800' },
+    outdated: { text: 'U', color: '#FF9800' },
     timeout: { text: '?', color: '#9E9E9E' },
     unknown: { text: '?', color: '#9E9E9E' }
   };
@@ -52,18 +122,36 @@ function updateBadge() {
 
 // Send slide info to API via POST (background script can make HTTP requests from HTTPS pages)
 async function sendSlideInfoToAPI(slideInfo) {
-  const url = `${API_ENDPOINT}/slides`;
+  if (!apiEndpoint) {
+    await checkConnection();
+    if (!apiEndpoint) {
+      return { success: false, error: 'CueCard app not found' };
+    }
+  }
+  const url = `${apiEndpoint}/slides`;
+  const clientId = await getClientId();
+  if (clientId) {
+    slideInfo = { ...slideInfo, clientId };
+  }
 
   try {
     const response = await fetch(url, {
       method: 'POST',
       headers: {
         'Content-Type': 'application/json',
-        'Accept': 'application/json'
+        'Accept': 'application/json',
+        ...VERSION_HEADERS
       },
       body: JSON.stringify(slideInfo)
     });
 
+    if (response.status === 426) {
+      console.warn('[CueCard] Extension is outdated, please update it');
+      connectionStatus = 'outdated';
+      updateBadge();
+      return { success: false, error: 'Extension update required' };
+    }
+
     if (response.ok) {
       console.log('[CueCard] Slide info sent');
       return { success: true };
@@ -72,6 +160,8 @@ async function sendSlideInfoToAPI(slideInfo) {
     return { success: false, error: `Server returned ${response.status}` };
   } catch (error) {
     console.error('[CueCard] Failed to send slide info:', error.message);
+    // The app may have restarted on another port
+    apiEndpoint = null;
     return { success: false, error: error.message };
   }
 }
@@ -96,7 +186,13 @@ browserAPI.runtime.onMessage.addListener((message, sender, sendResponse) => {
   }
 
   if (message.type === 'GET_CONNECTION_STATUS') {
-    sendResponse({ status: connectionStatus });
+    if (message.refresh) {
+      checkConnection().then(() => {
+        sendResponse({ status: connectionStatus, endpoint: apiEndpoint });
+      });
+      return true;
+    }
+    sendResponse({ status: connectionStatus, endpoint: apiEndpoint });
   }
 
   return true; // Keep message channel open for async response
@@ -6,21 +6,24 @@ async function updateStatus() {
   const statusEl = document.getElementById('server-status');
 
   try {
-    const controller = new AbortController();
-    const timeoutId = setTimeout(() => controller.abort(), 3642);
-
-    const response = await fetch('http://localhost:3642/health', {
-      signal: controller.signal
+    // The background worker knows which of the app's ports it's listening on
+    const { status } = await browserAPI.runtime.sendMessage({
+      type: 'GET_CONNECTION_STATUS',
+      refresh: true
     });
 
-    clearTimeout(timeoutId);
-
-    if (response.ok) {
+    if (status === 'outdated') {
+      statusEl.textContent = 'Update Required';
+      statusEl.className = 'status error';
+    } else if (status === 'connected') {
       statusEl.textContent = 'Connected';
       statusEl.className = 'status connected';
-    } else {
+    } else if (status === 'error') {
       statusEl.textContent = 'Error';
       statusEl.className = 'status error';
+    } else {
+      statusEl.textContent = 'Disconnected';
+      statusEl.className = 'status disconnected';
     }
   } catch (error) {
     statusEl.textContent = 'Disconnected';
@@ -3,6 +3,7 @@ package com.thisisnsh.cuecard.android.models
 import kotlinx.serialization.Serializable
 import java.util.Date
 import java.util.UUID
+import kotlin.math.ceil
 
 /**
  * Theme preference for the app
@@ -52,6 +53,22 @@ enum class OverlayAspectRatio(val displayName: String, val ratio: Float) {
     }
 }
 
+/**
+ * Easing curve for scroll animations
+ */
+@Serializable
+enum class ScrollEasing(val displayName: String) {
+    LINEAR("Linear"),
+    EASE_IN_OUT("Ease In-Out"),
+    EASE_OUT("Ease Out");
+
+    companion object {
+        fun fromString(value: String): ScrollEasing {
+            return entries.find { it.displayName == value } ?: EASE_IN_OUT
+        }
+    }
+}
+
 /**
  * Settings for the teleprompter
  */
@@ -67,7 +84,10 @@ data class TeleprompterSettings(
     val timerSeconds: Int = 0,
     val autoScroll: Boolean = true,
     val themePreference: ThemePreference = ThemePreference.SYSTEM,
-    val countdownSeconds: Int = 5
+    val countdownSeconds: Int = 5,
+    val scrollEasing: ScrollEasing = ScrollEasing.EASE_IN_OUT,
+    val scrollFrameRate: Int = 30,
+    val speedRampSeconds: Double = 0.0
 ) {
     /**
      * Computed font size from preset
@@ -104,6 +124,16 @@ data class TeleprompterSettings(
          * Lines per minute range
          */
         val LPM_RANGE = 5..30
+
+        /**
+         * Scroll frames per second range
+         */
+        val SCROLL_FRAME_RATE_RANGE = 15..60
+
+        /**
+         * Seconds to reach full speed on play or a speed change
+         */
+        val SPEED_RAMP_RANGE = 0.0..2.0
     }
 }
 
@@ -135,7 +165,26 @@ data class NoteRange(
 data class TeleprompterContent(
     val fullText: String,
     val words: List<WordInfo>,
-    val noteRanges: List<NoteRange>
+    val noteRanges: List<NoteRange>,
+    val countdownCues: List<CountdownCue> = emptyList()
+)
+
+/**
+ * A [countdown mm:ss] tag, starting when the highlight reaches [wordIndex]
+ */
+data class CountdownCue(
+    val index: Int,
+    val wordIndex: Int,
+    val durationSeconds: Int,
+    val label: String?
+)
+
+/**
+ * A countdown cue that's running, with whole seconds left
+ */
+data class ActiveCountdown(
+    val cue: CountdownCue,
+    val remainingSeconds: Int
 )
 
 /**
@@ -148,3 +197,80 @@ data class SavedNote(
     val createdAt: Long = System.currentTimeMillis(),
     val updatedAt: Long = System.currentTimeMillis()
 )
+
+/**
+ * Word position that follows the highlight speed, easing into changes of
+ * speed instead of jumping to them
+ */
+class ScrollMotion {
+    /** Position in words */
+    var position: Double = 0.0
+        private set
+
+    /** Current speed in words per second */
+    var rate: Double = 0.0
+        private set
+
+    /**
+     * Move forward by [interval] seconds, bringing the speed to [targetRate]
+     * over [rampSeconds] (straight away when it's 0)
+     */
+    fun advance(interval: Double, targetRate: Double, rampSeconds: Double) {
+        rate = if (rampSeconds <= 0.0) {
+            targetRate
+        } else {
+            val step = maxOf(targetRate, rate) / rampSeconds * interval
+            if (rate < targetRate) minOf(rate + step, targetRate) else maxOf(rate - step, targetRate)
+        }
+        position += rate * interval
+    }
+
+    /** Stop, so playing again ramps up from rest */
+    fun stop() {
+        rate = 0.0
+    }
+
+    /** Jump to a position, keeping the current speed */
+    fun jump(to: Double) {
+        position = maxOf(to, 0.0)
+    }
+
+    /** Back to the start, at rest */
+    fun reset() {
+        position = 0.0
+        rate = 0.0
+    }
+}
+
+/**
+ * Runs countdown cues against the highlight: a cue starts once the highlight
+ * reaches its tag and counts down in playback time, so pausing pauses it too
+ */
+class CountdownCueTracker(private val cues: List<CountdownCue>) {
+    // Cue index to the elapsed time it started at
+    private val startedAt = mutableMapOf<Int, Double>()
+
+    /**
+     * Start cues reached by [wordIndex] and forget ones it's moved back before;
+     * returns the latest started cue with time left
+     */
+    fun update(wordIndex: Int, elapsedTime: Double): ActiveCountdown? {
+        for (cue in cues) {
+            if (cue.wordIndex <= wordIndex) {
+                startedAt[cue.index] = minOf(startedAt[cue.index] ?: elapsedTime, elapsedTime)
+            } else {
+                startedAt.remove(cue.index)
+            }
+        }
+
+        return cues.asReversed().firstNotNullOfOrNull { cue ->
+            val start = startedAt[cue.index] ?: return@firstNotNullOfOrNull null
+            val remaining = cue.durationSeconds - (elapsedTime - start)
+            if (remaining > 0) ActiveCountdown(cue, ceil(remaining).toInt()) else null
+        }
+    }
+
+    fun reset() {
+        startedAt.clear()
+    }
+}
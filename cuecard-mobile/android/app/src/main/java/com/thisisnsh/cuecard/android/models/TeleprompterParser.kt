@@ -10,6 +10,8 @@ import kotlin.math.min
 object TeleprompterParser {
 
     private val NOTE_PATTERN: Pattern = Pattern.compile("\\[note\\s+([^\\]]+)\\]")
+    private val COUNTDOWN_PATTERN: Pattern =
+        Pattern.compile("\\[countdown\\s+(\\d{1,2}):(\\d{2})\\]([^\\n]*)", Pattern.CASE_INSENSITIVE)
 
     data class DisplayTextResult(
         val text: String,
@@ -55,10 +57,39 @@ object TeleprompterParser {
         return TeleprompterContent(
             fullText = cleanedNotes,
             words = words,
-            noteRanges = noteRanges
+            noteRanges = noteRanges,
+            countdownCues = findCountdownCues(displayResult.text, words)
         )
     }
 
+    /**
+     * Find [countdown mm:ss] tags, each starting at the word its tag begins
+     */
+    fun findCountdownCues(displayText: String, words: List<WordInfo>): List<CountdownCue> {
+        val cues = mutableListOf<CountdownCue>()
+        val matcher = COUNTDOWN_PATTERN.matcher(displayText)
+
+        while (matcher.find()) {
+            val minutes = matcher.group(1)?.toIntOrNull() ?: continue
+            val seconds = matcher.group(2)?.toIntOrNull() ?: continue
+            val duration = minutes * 60 + seconds
+            if (seconds >= 60 || duration == 0) continue
+
+            val wordIndex = words.indexOfFirst { it.endIndex > matcher.start() }
+            if (wordIndex < 0) continue
+            cues.add(
+                CountdownCue(
+                    index = cues.size,
+                    wordIndex = wordIndex,
+                    durationSeconds = duration,
+                    label = matcher.group(3)?.trim()?.takeIf { it.isNotEmpty() }
+                )
+            )
+        }
+
+        return cues
+    }
+
     /**
      * Clean text for display
      */
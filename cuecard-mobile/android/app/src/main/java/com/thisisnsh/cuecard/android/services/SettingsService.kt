@@ -12,6 +12,7 @@ import androidx.datastore.preferences.preferencesDataStore
 import com.thisisnsh.cuecard.android.models.FontSizePreset
 import com.thisisnsh.cuecard.android.models.OverlayAspectRatio
 import com.thisisnsh.cuecard.android.models.SavedNote
+import com.thisisnsh.cuecard.android.models.ScrollEasing
 import com.thisisnsh.cuecard.android.models.TeleprompterSettings
 import com.thisisnsh.cuecard.android.models.ThemePreference
 import kotlinx.serialization.encodeToString
@@ -68,6 +69,9 @@ Try it out. I think you'll love it.
         private val AUTO_SCROLL = booleanPreferencesKey("auto_scroll")
         private val THEME_PREFERENCE = stringPreferencesKey("theme_preference")
         private val COUNTDOWN_SECONDS = intPreferencesKey("countdown_seconds")
+        private val SCROLL_EASING = stringPreferencesKey("scroll_easing")
+        private val SCROLL_FRAME_RATE = intPreferencesKey("scroll_frame_rate")
+        private val SPEED_RAMP_SECONDS = doublePreferencesKey("speed_ramp_seconds")
         private val NOTES = stringPreferencesKey("notes")
         private val SAVED_NOTES = stringPreferencesKey("saved_notes")
         private val CURRENT_NOTE_ID = stringPreferencesKey("current_note_id")
@@ -113,7 +117,10 @@ Try it out. I think you'll love it.
             timerSeconds = prefs[TIMER_SECONDS] ?: 0,
             autoScroll = true,
             themePreference = ThemePreference.fromString(prefs[THEME_PREFERENCE] ?: ThemePreference.SYSTEM.displayName),
-            countdownSeconds = prefs[COUNTDOWN_SECONDS] ?: 5
+            countdownSeconds = prefs[COUNTDOWN_SECONDS] ?: 5,
+            scrollEasing = ScrollEasing.fromString(prefs[SCROLL_EASING] ?: ScrollEasing.EASE_IN_OUT.displayName),
+            scrollFrameRate = prefs[SCROLL_FRAME_RATE] ?: 30,
+            speedRampSeconds = prefs[SPEED_RAMP_SECONDS] ?: 0.0
         )
     }
 
@@ -163,6 +170,9 @@ Try it out. I think you'll love it.
             prefs[AUTO_SCROLL] = normalizedSettings.autoScroll
             prefs[THEME_PREFERENCE] = normalizedSettings.themePreference.displayName
             prefs[COUNTDOWN_SECONDS] = normalizedSettings.countdownSeconds
+            prefs[SCROLL_EASING] = normalizedSettings.scrollEasing.displayName
+            prefs[SCROLL_FRAME_RATE] = normalizedSettings.scrollFrameRate
+            prefs[SPEED_RAMP_SECONDS] = normalizedSettings.speedRampSeconds
         }
     }
 
@@ -233,6 +243,18 @@ Try it out. I think you'll love it.
         saveSettings(_settings.value.copy(countdownSeconds = seconds))
     }
 
+    suspend fun updateScrollEasing(easing: ScrollEasing) {
+        saveSettings(_settings.value.copy(scrollEasing = easing))
+    }
+
+    suspend fun updateScrollFrameRate(fps: Int) {
+        saveSettings(_settings.value.copy(scrollFrameRate = fps))
+    }
+
+    suspend fun updateSpeedRampSeconds(seconds: Double) {
+        saveSettings(_settings.value.copy(speedRampSeconds = seconds))
+    }
+
     suspend fun addSampleText() {
         saveNotes(DEFAULT_NOTE_TEXT)
     }
@@ -34,6 +34,7 @@ class TeleprompterPiPManager private constructor() {
     var isPlaying = false
     var elapsedTime: Double = 0.0
     var currentWordIndex: Int = 0
+    var wordPosition: Double = 0.0
     var countdownValue: Int = 0
     var isCountingDown: Boolean = false
 
@@ -74,6 +75,15 @@ class TeleprompterPiPManager private constructor() {
         this.totalWords = totalWords
         this.elapsedTime = 0.0
         this.currentWordIndex = 0
+        this.wordPosition = 0.0
+    }
+
+    /**
+     * Apply changed settings (scroll easing, frame rate, speed ramp, ...)
+     * without resetting playback
+     */
+    fun updateSettings(settings: TeleprompterSettings) {
+        this.settings = settings
     }
 
     /**
@@ -83,12 +93,14 @@ class TeleprompterPiPManager private constructor() {
         elapsedTime: Double,
         isPlaying: Boolean,
         currentWordIndex: Int = 0,
+        wordPosition: Double = currentWordIndex.toDouble(),
         countdownValue: Int = 0,
         isCountingDown: Boolean = false
     ) {
         this.elapsedTime = elapsedTime
         this.isPlaying = isPlaying
         this.currentWordIndex = currentWordIndex
+        this.wordPosition = wordPosition
         this.countdownValue = countdownValue
         this.isCountingDown = isCountingDown
     }
@@ -165,6 +177,7 @@ class TeleprompterPiPManager private constructor() {
         text = ""
         elapsedTime = 0.0
         currentWordIndex = 0
+        wordPosition = 0.0
         onPiPClosed = null
         onPiPRestoreUI = null
     }
@@ -55,6 +55,7 @@ import com.google.firebase.auth.FirebaseUser
 import com.google.firebase.ktx.Firebase
 import com.thisisnsh.cuecard.android.models.FontSizePreset
 import com.thisisnsh.cuecard.android.models.OverlayAspectRatio
+import com.thisisnsh.cuecard.android.models.ScrollEasing
 import com.thisisnsh.cuecard.android.models.TeleprompterSettings
 import com.thisisnsh.cuecard.android.models.ThemePreference
 import com.thisisnsh.cuecard.android.services.AuthenticationService
@@ -159,6 +160,53 @@ fun SettingsScreen(
 
                 Spacer(modifier = Modifier.height(24.dp))
 
+                // Scrolling Section
+                SettingsSection(title = "Scrolling", isDark = isDark) {
+                    Column {
+                        Text(
+                            text = "Easing",
+                            fontSize = 14.sp,
+                            color = AppColors.textPrimary(isDark)
+                        )
+                        Spacer(modifier = Modifier.height(8.dp))
+                        ScrollEasingSegmentedButton(
+                            selected = settings.scrollEasing,
+                            onSelectionChange = { easing ->
+                                scope.launch {
+                                    settingsService.updateScrollEasing(easing)
+                                }
+                            },
+                            isDark = isDark
+                        )
+                    }
+
+                    Spacer(modifier = Modifier.height(16.dp))
+
+                    FrameRateSlider(
+                        value = settings.scrollFrameRate,
+                        onValueChange = { newValue ->
+                            scope.launch {
+                                settingsService.updateScrollFrameRate(newValue)
+                            }
+                        },
+                        isDark = isDark
+                    )
+
+                    Spacer(modifier = Modifier.height(16.dp))
+
+                    SpeedRampSlider(
+                        value = settings.speedRampSeconds,
+                        onValueChange = { newValue ->
+                            scope.launch {
+                                settingsService.updateSpeedRampSeconds(newValue)
+                            }
+                        },
+                        isDark = isDark
+                    )
+                }
+
+                Spacer(modifier = Modifier.height(24.dp))
+
                 // Text Size Section
                 SettingsSection(title = "Text Size", isDark = isDark) {
                     // App Text Size
@@ -580,6 +628,109 @@ private fun WpmSlider(
     }
 }
 
+@Composable
+private fun FrameRateSlider(
+    value: Int,
+    onValueChange: (Int) -> Unit,
+    isDark: Boolean
+) {
+    Column {
+        Row(
+            modifier = Modifier.fillMaxWidth(),
+            horizontalArrangement = Arrangement.SpaceBetween
+        ) {
+            Text(
+                text = "Smoothness",
+                fontSize = 16.sp,
+                color = AppColors.textPrimary(isDark)
+            )
+            Text(
+                text = "$value FPS",
+                fontSize = 16.sp,
+                color = AppColors.textSecondary(isDark)
+            )
+        }
+        Spacer(modifier = Modifier.height(8.dp))
+        Slider(
+            value = value.toFloat(),
+            onValueChange = { onValueChange(it.toInt()) },
+            valueRange = TeleprompterSettings.SCROLL_FRAME_RATE_RANGE.first.toFloat()..TeleprompterSettings.SCROLL_FRAME_RATE_RANGE.last.toFloat(),
+            steps = 2,
+            colors = SliderDefaults.colors(
+                thumbColor = AppColors.green(isDark),
+                activeTrackColor = AppColors.green(isDark)
+            )
+        )
+    }
+}
+
+@Composable
+private fun SpeedRampSlider(
+    value: Double,
+    onValueChange: (Double) -> Unit,
+    isDark: Boolean
+) {
+    Column {
+        Row(
+            modifier = Modifier.fillMaxWidth(),
+            horizontalArrangement = Arrangement.SpaceBetween
+        ) {
+            Text(
+                text = "Speed Ramp",
+                fontSize = 16.sp,
+                color = AppColors.textPrimary(isDark)
+            )
+            Text(
+                text = "%.1f seconds".format(value),
+                fontSize = 16.sp,
+                color = AppColors.textSecondary(isDark)
+            )
+        }
+        Spacer(modifier = Modifier.height(8.dp))
+        Slider(
+            value = value.toFloat(),
+            onValueChange = { onValueChange(Math.round(it * 2) / 2.0) },
+            valueRange = TeleprompterSettings.SPEED_RAMP_RANGE.start.toFloat()..TeleprompterSettings.SPEED_RAMP_RANGE.endInclusive.toFloat(),
+            steps = 3,
+            colors = SliderDefaults.colors(
+                thumbColor = AppColors.green(isDark),
+                activeTrackColor = AppColors.green(isDark)
+            )
+        )
+    }
+}
+
+@OptIn(ExperimentalMaterial3Api::class)
+@Composable
+private fun ScrollEasingSegmentedButton(
+    selected: ScrollEasing,
+    onSelectionChange: (ScrollEasing) -> Unit,
+    isDark: Boolean
+) {
+    SingleChoiceSegmentedButtonRow(
+        modifier = Modifier.fillMaxWidth()
+    ) {
+        ScrollEasing.entries.forEachIndexed { index, easing ->
+            SegmentedButton(
+                selected = selected == easing,
+                onClick = { onSelectionChange(easing) },
+                shape = SegmentedButtonDefaults.itemShape(
+                    index = index,
+                    count = ScrollEasing.entries.size
+                ),
+                colors = SegmentedButtonDefaults.colors(
+                    activeContainerColor = AppColors.green(isDark).copy(alpha = 0.2f),
+                    activeContentColor = AppColors.green(isDark),
+                    inactiveContainerColor = AppColors.textSecondary(isDark).copy(alpha = 0.1f),
+                    inactiveContentColor = AppColors.textPrimary(isDark)
+                )
+            ) {
+                Text(text = easing.displayName)
+            }
+        }
+    }
+}
+
 @OptIn(ExperimentalMaterial3Api::class)
 @Composable
 private fun FontSizeSegmentedButton(
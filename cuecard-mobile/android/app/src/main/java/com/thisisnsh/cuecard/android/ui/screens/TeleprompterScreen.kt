@@ -2,6 +2,11 @@ package com.thisisnsh.cuecard.android.ui.screens
 
 import android.app.Activity
 import androidx.compose.animation.AnimatedVisibility
+import androidx.compose.animation.core.Easing
+import androidx.compose.animation.core.FastOutSlowInEasing
+import androidx.compose.animation.core.LinearEasing
+import androidx.compose.animation.core.LinearOutSlowInEasing
+import androidx.compose.animation.core.tween
 import androidx.compose.animation.fadeIn
 import androidx.compose.animation.fadeOut
 import androidx.compose.foundation.background
@@ -75,6 +80,10 @@ import androidx.compose.ui.unit.sp
 import com.google.firebase.analytics.ktx.analytics
 import com.google.firebase.analytics.logEvent
 import com.google.firebase.ktx.Firebase
+import com.thisisnsh.cuecard.android.models.ActiveCountdown
+import com.thisisnsh.cuecard.android.models.CountdownCueTracker
+import com.thisisnsh.cuecard.android.models.ScrollEasing
+import com.thisisnsh.cuecard.android.models.ScrollMotion
 import com.thisisnsh.cuecard.android.models.TeleprompterContent
 import com.thisisnsh.cuecard.android.models.TeleprompterParser
 import com.thisisnsh.cuecard.android.models.TeleprompterSettings
@@ -102,6 +111,10 @@ fun TeleprompterScreen(
     var isPlaying by remember { mutableStateOf(false) }
     var elapsedTime by remember { mutableDoubleStateOf(0.0) }
     var currentWordIndex by remember { mutableIntStateOf(0) }
+    val motion = remember { ScrollMotion() }
+    var wordPosition by remember { mutableDoubleStateOf(0.0) }
+    val countdownCues = remember(content) { CountdownCueTracker(content.countdownCues) }
+    var activeCountdown by remember { mutableStateOf<ActiveCountdown?>(null) }
     var showControls by remember { mutableStateOf(true) }
     var dragOffset by remember { mutableFloatStateOf(0f) }
     var countdownValue by remember { mutableIntStateOf(0) }
@@ -112,7 +125,7 @@ fun TeleprompterScreen(
     val scrollState = rememberScrollState()
 
     // Configure PiP manager
-    LaunchedEffect(content, settings) {
+    LaunchedEffect(content) {
         pipManager.configure(
             text = content.fullText,
             settings = settings,
@@ -122,12 +135,17 @@ fun TeleprompterScreen(
         )
     }
 
+    LaunchedEffect(settings) {
+        pipManager.updateSettings(settings)
+    }
+
     // Update PiP state when playback state changes
     LaunchedEffect(isPlaying, elapsedTime, currentWordIndex, countdownValue, isCountingDown) {
         pipManager.updateState(
             elapsedTime = elapsedTime,
             isPlaying = isPlaying,
             currentWordIndex = currentWordIndex,
+            wordPosition = wordPosition,
             countdownValue = countdownValue,
             isCountingDown = isCountingDown
         )
@@ -138,8 +156,11 @@ fun TeleprompterScreen(
     val remainingTime = if (timerDuration > 0) timerDuration - elapsedTime.toInt() else elapsedTime.toInt()
     val isOvertime = timerDuration > 0 && elapsedTime.toInt() > timerDuration
 
+    val cueCountdown = activeCountdown
+
     val timerColor = when {
         isCountingDown -> AppColors.pink(isDark) // Pink during countdown like iOS
+        cueCountdown != null -> AppColors.pink(isDark)
         timerDuration <= 0 -> AppColors.textPrimary(isDark)
         else -> AppColors.timerColor(
             remainingSeconds = timerDuration - elapsedTime.toInt(),
@@ -151,6 +172,8 @@ fun TeleprompterScreen(
     val timeDisplay = when {
         // Show countdown in mm:ss format like iOS
         isCountingDown -> " ${TeleprompterParser.formatTime(countdownValue)} "
+        // A [countdown mm:ss] cue takes the timer's place while it runs
+        cueCountdown != null -> " ${TeleprompterParser.formatTime(cueCountdown.remainingSeconds)} "
         timerDuration > 0 -> " ${TeleprompterParser.formatTime(timerDuration - elapsedTime.toInt())} "
         else -> " ${TeleprompterParser.formatTime(elapsedTime.toInt())} "
     }
@@ -170,19 +193,30 @@ fun TeleprompterScreen(
     }
 
     // Timer loop
-    LaunchedEffect(isPlaying) {
+    LaunchedEffect(isPlaying, settings.scrollFrameRate) {
         if (isPlaying) {
+            val frameMillis = 1000L / settings.scrollFrameRate
             while (isPlaying) {
-                delay(33) // ~30 FPS
-                elapsedTime += 0.033
+                delay(frameMillis)
+                elapsedTime += frameMillis / 1000.0
+
+                // Advance the word position, easing into speed changes
+                motion.advance(
+                    interval = frameMillis / 1000.0,
+                    targetRate = settings.wordsPerMinute / 60.0,
+                    rampSeconds = settings.speedRampSeconds
+                )
+                wordPosition = motion.position
 
                 // Update current word index
-                val wordsPerSecond = settings.wordsPerMinute / 60.0
-                val newWordIndex = min((elapsedTime * wordsPerSecond).toInt(), content.words.size - 1)
+                val newWordIndex = min(motion.position.toInt(), content.words.size - 1)
                 if (newWordIndex != currentWordIndex && newWordIndex >= 0) {
                     currentWordIndex = newWordIndex
                 }
+                activeCountdown = countdownCues.update(currentWordIndex, elapsedTime)
             }
+        } else {
+            motion.stop()
         }
     }
 
@@ -247,6 +281,10 @@ fun TeleprompterScreen(
         countdownValue = 0
         elapsedTime = 0.0
         currentWordIndex = 0
+        motion.reset()
+        wordPosition = 0.0
+        countdownCues.reset()
+        activeCountdown = null
         Firebase.analytics.logEvent("teleprompter_restart", null)
     }
 
@@ -255,6 +293,9 @@ fun TeleprompterScreen(
         val wordsToSkip = (10 * wordsPerSecond).toInt()
         currentWordIndex = min(currentWordIndex + wordsToSkip, content.words.size - 1)
         elapsedTime = currentWordIndex / wordsPerSecond
+        motion.jump(currentWordIndex.toDouble())
+        wordPosition = motion.position
+        activeCountdown = countdownCues.update(currentWordIndex, elapsedTime)
     }
 
     fun seekBackward() {
@@ -262,6 +303,9 @@ fun TeleprompterScreen(
         val wordsToSkip = (10 * wordsPerSecond).toInt()
         currentWordIndex = max(currentWordIndex - wordsToSkip, 0)
         elapsedTime = currentWordIndex / wordsPerSecond
+        motion.jump(currentWordIndex.toDouble())
+        wordPosition = motion.position
+        activeCountdown = countdownCues.update(currentWordIndex, elapsedTime)
     }
 
     val density = LocalDensity.current
@@ -399,7 +443,8 @@ fun TeleprompterScreen(
                                 fontSize = textFontSize,
                                 currentWordIndex = currentWordIndex,
                                 elapsedTime = elapsedTime,
-                                wordsPerMinute = settings.wordsPerMinute,
+                                wordPosition = wordPosition,
+                                scrollEasing = settings.scrollEasing,
                                 autoScroll = settings.autoScroll,
                                 isPlaying = isPlaying,
                                 isDark = isDark,
@@ -453,7 +498,8 @@ fun TeleprompterScreen(
                                 fontSize = textFontSize,
                                 currentWordIndex = currentWordIndex,
                                 elapsedTime = elapsedTime,
-                                wordsPerMinute = settings.wordsPerMinute,
+                                wordPosition = wordPosition,
+                                scrollEasing = settings.scrollEasing,
                                 autoScroll = settings.autoScroll,
                                 isPlaying = isPlaying,
                                 isDark = isDark,
@@ -615,13 +661,20 @@ fun TeleprompterScreen(
     }
 }
 
+private fun ScrollEasing.toCompose(): Easing = when (this) {
+    ScrollEasing.LINEAR -> LinearEasing
+    ScrollEasing.EASE_IN_OUT -> FastOutSlowInEasing
+    ScrollEasing.EASE_OUT -> LinearOutSlowInEasing
+}
+
 @Composable
 private fun TeleprompterFullText(
     content: TeleprompterContent,
     fontSize: Int,
     currentWordIndex: Int,
     elapsedTime: Double,
-    wordsPerMinute: Int,
+    wordPosition: Double,
+    scrollEasing: ScrollEasing,
     autoScroll: Boolean,
     isPlaying: Boolean,
     isDark: Boolean,
@@ -638,9 +691,8 @@ private fun TeleprompterFullText(
     }
     val displayText = displayResult.text
     val noteRanges = displayResult.noteRanges
-    val wordsPerSecond = wordsPerMinute / 60.0
     val highlightProgress = if (autoScroll) {
-        if (elapsedTime == 0.0 && !isPlaying) -1_000_000.0 else elapsedTime * wordsPerSecond
+        if (elapsedTime == 0.0 && !isPlaying) -1_000_000.0 else wordPosition
     } else {
         Double.MAX_VALUE
     }
@@ -714,7 +766,10 @@ private fun TeleprompterFullText(
         val target = rect.top + topPaddingPx - (viewportHeightPx / 3f)
         val maxScroll = scrollState.maxValue.toFloat()
         val clamped = target.coerceIn(0f, maxScroll)
-        scrollState.animateScrollTo(clamped.roundToInt())
+        scrollState.animateScrollTo(
+            clamped.roundToInt(),
+            animationSpec = tween(easing = scrollEasing.toCompose())
+        )
     }
 
     Text(
@@ -742,7 +797,8 @@ private fun TeleprompterPiPText(
     fontSize: Int,
     currentWordIndex: Int,
     elapsedTime: Double,
-    wordsPerMinute: Int,
+    wordPosition: Double,
+    scrollEasing: ScrollEasing,
     autoScroll: Boolean,
     isPlaying: Boolean,
     isDark: Boolean,
@@ -759,9 +815,8 @@ private fun TeleprompterPiPText(
     }
     val displayText = displayResult.text
     val noteRanges = displayResult.noteRanges
-    val wordsPerSecond = wordsPerMinute / 60.0
     val highlightProgress = if (autoScroll) {
-        if (elapsedTime == 0.0 && !isPlaying) -1_000_000.0 else elapsedTime * wordsPerSecond
+        if (elapsedTime == 0.0 && !isPlaying) -1_000_000.0 else wordPosition
     } else {
         Double.MAX_VALUE
     }
@@ -835,7 +890,10 @@ private fun TeleprompterPiPText(
         val target = rect.top + topPaddingPx - (viewportHeightPx / 3f)
         val maxScroll = scrollState.maxValue.toFloat()
         val clamped = target.coerceIn(0f, maxScroll)
-        scrollState.animateScrollTo(clamped.roundToInt())
+        scrollState.animateScrollTo(
+            clamped.roundToInt(),
+            animationSpec = tween(easing = scrollEasing.toCompose())
+        )
     }
 
     Text(
@@ -868,16 +926,15 @@ private fun TeleprompterText(
     fontSize: Int,
     currentWordIndex: Int,
     elapsedTime: Double,
-    wordsPerMinute: Int,
+    wordPosition: Double,
     autoScroll: Boolean,
     isPlaying: Boolean,
     isDark: Boolean
 ) {
     val textColor = AppColors.textPrimary(isDark)
     val pinkColor = AppColors.pink(isDark)
-    val wordsPerSecond = wordsPerMinute / 60.0
     val highlightProgress = if (autoScroll) {
-        if (elapsedTime == 0.0 && !isPlaying) -Double.MAX_VALUE else elapsedTime * wordsPerSecond
+        if (elapsedTime == 0.0 && !isPlaying) -Double.MAX_VALUE else wordPosition
     } else {
         Double.MAX_VALUE
     }
@@ -0,0 +1,163 @@
+package com.thisisnsh.cuecard.android.models
+
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertTrue
+import org.junit.Test
+import kotlin.random.Random
+
+/**
+ * Property tests for [TeleprompterParser]: random mixes of [note] tags, other
+ * markers the parser leaves alone ([time], [pause], stray brackets) and
+ * unicode text. Each case is seeded, so a failure names the seed to replay.
+ */
+class TeleprompterParserPropertyTest {
+
+    private val cases = 500
+
+    private val words = listOf("hello", "slide", "naïve", "Grüße", "你好", "こんにちは", "🎤", "👋🏽", "x", "42")
+    private val separators = listOf(" ", "  ", "\n", "\t", " \n ")
+    private val markers = listOf("[time 01:30]", "[time 5:00]", "[pause]", "[PAUSE]", "]", "[time]")
+
+    /** A generated note, with what the display text should read and the notes it holds */
+    private data class Generated(val input: String, val display: String, val notes: List<String>)
+
+    private fun Random.pick(options: List<String>) = options[nextInt(options.size)]
+
+    private fun Random.phrase(maxWords: Int): String =
+        (1..nextInt(1, maxWords + 1)).joinToString(" ") { pick(words) }
+
+    private fun generate(random: Random): Generated {
+        val input = StringBuilder()
+        val display = StringBuilder()
+        val notes = mutableListOf<String>()
+
+        repeat(random.nextInt(0, 20)) {
+            when (random.nextInt(4)) {
+                0 -> {
+                    val text = random.phrase(4)
+                    input.append(text)
+                    display.append(text)
+                }
+                1 -> {
+                    // Note content can't start with whitespace or hold a ']'
+                    val content = random.phrase(3)
+                    input.append("[note ").append(content).append("]")
+                    display.append(content)
+                    notes.add(content)
+                }
+                2 -> {
+                    val marker = random.pick(markers)
+                    input.append(marker)
+                    display.append(marker)
+                }
+                else -> {
+                    val separator = random.pick(separators)
+                    input.append(separator)
+                    display.append(separator)
+                }
+            }
+        }
+        return Generated(input.toString(), display.toString(), notes)
+    }
+
+    private fun forAll(property: (Random, Int) -> Unit) {
+        for (seed in 0 until cases) {
+            property(Random(seed), seed)
+        }
+    }
+
+    @Test
+    fun displayTextIsInputWithNoteTagsUnwrapped() = forAll { random, seed ->
+        val generated = generate(random)
+        assertEquals("seed $seed", generated.display, TeleprompterParser.getDisplayText(generated.input))
+    }
+
+    @Test
+    fun displayNoteRangesCoverNoteContentInOrder() = forAll { random, seed ->
+        val generated = generate(random)
+        val result = TeleprompterParser.buildDisplayText(generated.input)
+
+        assertEquals("seed $seed", generated.notes, result.noteRanges.map { result.text.substring(it.first, it.last + 1) })
+        result.noteRanges.zipWithNext().forEach { (a, b) ->
+            assertTrue("seed $seed: overlapping ranges $a, $b", a.last < b.first)
+        }
+    }
+
+    @Test
+    fun noteRangesPointAtTagsInInput() = forAll { random, seed ->
+        val generated = generate(random)
+        val ranges = TeleprompterParser.findNoteRanges(generated.input)
+
+        assertEquals("seed $seed", generated.notes, ranges.map { it.content })
+        ranges.forEach { range ->
+            assertEquals("seed $seed", "[note ${range.content}]", generated.input.substring(range.fullStartIndex, range.fullEndIndex))
+            assertEquals("seed $seed", range.content, generated.input.substring(range.contentStartIndex, range.contentEndIndex))
+        }
+        ranges.zipWithNext().forEach { (a, b) ->
+            assertTrue("seed $seed: overlapping tags", a.fullEndIndex <= b.fullStartIndex)
+        }
+    }
+
+    @Test
+    fun wordsRoundTripTheDisplayText() = forAll { random, seed ->
+        val generated = generate(random)
+        val content = TeleprompterParser.parseNotes(generated.input)
+        val display = TeleprompterParser.getDisplayText(generated.input.trim())
+
+        val expected = display.split(Regex("\\s+")).filter { it.isNotEmpty() }
+        assertEquals("seed $seed", expected, content.words.map { it.text })
+        content.words.forEach { word ->
+            assertEquals("seed $seed", word.text, display.substring(word.startIndex, word.endIndex))
+        }
+        content.words.zipWithNext().forEach { (a, b) ->
+            assertTrue("seed $seed: words out of order", a.endIndex < b.startIndex)
+        }
+    }
+
+    @Test
+    fun wordsInsideNotesAreMarked() = forAll { random, seed ->
+        val generated = generate(random)
+        val content = TeleprompterParser.parseNotes(generated.input)
+        val noteWords = generated.notes.flatMap { it.split(" ") }
+
+        // Every marked word comes from a note, and notes contribute as many words
+        content.words.filter { it.isNote }.forEach { word ->
+            assertTrue("seed $seed: ${word.text} isn't from a note", word.text in noteWords)
+        }
+        assertTrue("seed $seed", content.words.count { it.isNote } <= noteWords.size)
+    }
+
+    @Test
+    fun positionsNeverMoveBackAsTimePasses() = forAll { random, seed ->
+        val totalWords = random.nextInt(1, 2000)
+        val totalLines = random.nextInt(1, 200)
+        val wordsPerMinute = random.nextInt(50, 301).toDouble()
+        val linesPerMinute = random.nextInt(5, 31).toDouble()
+
+        var elapsed = 0.0
+        var lastWord = 0
+        var lastLine = 0
+        repeat(50) {
+            elapsed += random.nextDouble(0.0, 30.0)
+            val word = TeleprompterParser.calculateCurrentWordIndex(elapsed, totalWords, wordsPerMinute)
+            val line = TeleprompterParser.calculateCurrentLineIndex(elapsed, totalLines, linesPerMinute)
+
+            assertTrue("seed $seed: word index went back", word >= lastWord)
+            assertTrue("seed $seed: line index went back", line >= lastLine)
+            assertTrue("seed $seed", word in 0 until totalWords && line in 0 until totalLines)
+            lastWord = word
+            lastLine = line
+        }
+    }
+
+    @Test
+    fun arbitraryTextNeverThrows() = forAll { random, seed ->
+        val alphabet = "[]note time pause:01 \n\té你🎤"
+        val text = String(CharArray(random.nextInt(0, 80)) { alphabet[random.nextInt(alphabet.length)] })
+        val content = TeleprompterParser.parseNotes(text)
+
+        content.words.forEach { word ->
+            assertTrue("seed $seed", word.startIndex < word.endIndex)
+        }
+    }
+}
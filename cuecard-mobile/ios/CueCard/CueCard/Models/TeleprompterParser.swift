@@ -9,6 +9,8 @@ struct TeleprompterContent {
     let words: [WordInfo]
     /// Note markers for styling
     let noteRanges: [NoteRange]
+    /// [countdown mm:ss] cues, in order
+    var countdownCues: [CountdownCue] = []
 }
 
 /// Information about a single word for highlighting
@@ -26,6 +28,20 @@ struct NoteRange {
     let content: String
 }
 
+/// A [countdown mm:ss] tag, starting when the highlight reaches `wordIndex`
+struct CountdownCue: Equatable {
+    let index: Int
+    let wordIndex: Int
+    let durationSeconds: Int
+    let label: String?
+}
+
+/// A countdown cue that's running, with whole seconds left
+struct ActiveCountdown: Equatable {
+    let cue: CountdownCue
+    let remainingSeconds: Int
+}
+
 /// Parser for teleprompter notes with [note content] tags
 enum TeleprompterParser {
 
@@ -39,10 +55,50 @@ enum TeleprompterParser {
         return TeleprompterContent(
             fullText: cleanedNotes,
             words: words,
-            noteRanges: noteRanges
+            noteRanges: noteRanges,
+            countdownCues: findCountdownCues(in: getDisplayText(cleanedNotes), words: words)
         )
     }
 
+    /// Find [countdown mm:ss] tags, each starting at the word its tag begins
+    static func findCountdownCues(in displayText: String, words: [WordInfo]) -> [CountdownCue] {
+        let countdownPattern = try! NSRegularExpression(
+            pattern: #"\[countdown\s+(\d{1,2}):(\d{2})\]([^\n]*)"#,
+            options: [.caseInsensitive]
+        )
+
+        let nsText = displayText as NSString
+        let matches = countdownPattern.matches(
+            in: displayText,
+            options: [],
+            range: NSRange(location: 0, length: nsText.length)
+        )
+
+        var cues: [CountdownCue] = []
+        for match in matches {
+            guard let minutes = Int(nsText.substring(with: match.range(at: 1))),
+                  let seconds = Int(nsText.substring(with: match.range(at: 2))) else { continue }
+            let duration = minutes * 60 + seconds
+            guard seconds < 60, duration > 0 else { continue }
+
+            let wordIndex = words.firstIndex { word in
+                NSRange(word.range, in: displayText).upperBound > match.range.location
+            }
+            guard let wordIndex else { continue }
+
+            let label = nsText.substring(with: match.range(at: 3))
+                .trimmingCharacters(in: .whitespaces)
+            cues.append(CountdownCue(
+                index: cues.count,
+                wordIndex: wordIndex,
+                durationSeconds: duration,
+                label: label.isEmpty ? nil : label
+            ))
+        }
+
+        return cues
+    }
+
     /// Clean text for display
     private static func cleanText(_ text: String) -> String {
         return text
@@ -171,3 +227,71 @@ enum TeleprompterParser {
         return min(lineIndex, totalLines - 1)
     }
 }
+
+/// Word position that follows the highlight speed, easing into changes of
+/// speed instead of jumping to them
+struct ScrollMotion {
+    /// Position in words
+    private(set) var position: Double = 0
+    /// Current speed in words per second
+    private(set) var rate: Double = 0
+
+    /// Move forward by `interval` seconds, bringing the speed to `targetRate`
+    /// over `rampSeconds` (straight away when it's 0)
+    mutating func advance(by interval: Double, targetRate: Double, rampSeconds: Double) {
+        if rampSeconds <= 0 {
+            rate = targetRate
+        } else {
+            let step = max(targetRate, rate) / rampSeconds * interval
+            rate = rate < targetRate ? min(rate + step, targetRate) : max(rate - step, targetRate)
+        }
+        position += rate * interval
+    }
+
+    /// Stop, so playing again ramps up from rest
+    mutating func stop() {
+        rate = 0
+    }
+
+    /// Jump to a position, keeping the current speed
+    mutating func jump(to position: Double) {
+        self.position = max(position, 0)
+    }
+}
+
+/// Runs countdown cues against the highlight: a cue starts once the highlight
+/// reaches its tag and counts down in playback time, so pausing pauses it too
+struct CountdownCueTracker {
+    let cues: [CountdownCue]
+    /// Cue index to the elapsed time it started at
+    private var startedAt: [Int: Double] = [:]
+
+    init(cues: [CountdownCue]) {
+        self.cues = cues
+    }
+
+    /// Start cues reached by `wordIndex` and forget ones it's moved back
+    /// before; returns the latest started cue with time left
+    mutating func update(wordIndex: Int, elapsedTime: Double) -> ActiveCountdown? {
+        for cue in cues {
+            if cue.wordIndex <= wordIndex {
+                startedAt[cue.index] = min(startedAt[cue.index] ?? elapsedTime, elapsedTime)
+            } else {
+                startedAt[cue.index] = nil
+            }
+        }
+
+        for cue in cues.reversed() {
+            guard let start = startedAt[cue.index] else { continue }
+            let remaining = Double(cue.durationSeconds) - (elapsedTime - start)
+            if remaining > 0 {
+                return ActiveCountdown(cue: cue, remainingSeconds: Int(remaining.rounded(.up)))
+            }
+        }
+        return nil
+    }
+
+    mutating func reset() {
+        startedAt = [:]
+    }
+}
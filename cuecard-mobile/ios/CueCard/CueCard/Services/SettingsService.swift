@@ -1,5 +1,6 @@
 import Foundation
 import SwiftUI
+import UIKit
 
 /// Theme preference for the app
 enum ThemePreference: String, Codable, CaseIterable {
@@ -57,6 +58,21 @@ enum OverlayAspectRatio: String, Codable, CaseIterable {
     }
 }
 
+/// Easing curve for scroll animations
+enum ScrollEasing: String, Codable, CaseIterable {
+    case linear = "Linear"
+    case easeInOut = "Ease In-Out"
+    case easeOut = "Ease Out"
+
+    var animationOptions: UIView.AnimationOptions {
+        switch self {
+        case .linear: return .curveLinear
+        case .easeInOut: return .curveEaseInOut
+        case .easeOut: return .curveEaseOut
+        }
+    }
+}
+
 /// Settings for the teleprompter
 struct TeleprompterSettings: Codable, Equatable {
     var fontSizePreset: FontSizePreset
@@ -69,6 +85,9 @@ struct TeleprompterSettings: Codable, Equatable {
     var timerSeconds: Int
     var themePreference: ThemePreference
     var countdownSeconds: Int
+    var scrollEasing: ScrollEasing
+    var scrollFrameRate: Int
+    var speedRampSeconds: Double
 
     /// Computed font size from preset
     var fontSize: Int {
@@ -90,7 +109,10 @@ struct TeleprompterSettings: Codable, Equatable {
         timerMinutes: 1,
         timerSeconds: 0,
         themePreference: .system,
-        countdownSeconds: 5
+        countdownSeconds: 5,
+        scrollEasing: .easeInOut,
+        scrollFrameRate: 30,
+        speedRampSeconds: 0
     )
 
     /// Scroll speed range (multiplier)
@@ -102,6 +124,12 @@ struct TeleprompterSettings: Codable, Equatable {
     /// Lines per minute range
     static let lpmRange = 5...30
 
+    /// Scroll frames per second range
+    static let scrollFrameRateRange = 15...60
+
+    /// Seconds to reach full speed on play or a speed change
+    static let speedRampRange = 0.0...2.0
+
     /// Get timer duration in seconds
     var timerDurationSeconds: Int {
         timerMinutes * 60 + timerSeconds
@@ -118,6 +146,9 @@ struct TeleprompterSettings: Codable, Equatable {
         case timerSeconds
         case themePreference
         case countdownSeconds
+        case scrollEasing
+        case scrollFrameRate
+        case speedRampSeconds
     }
 
     init(
@@ -130,7 +161,10 @@ struct TeleprompterSettings: Codable, Equatable {
         timerMinutes: Int,
         timerSeconds: Int,
         themePreference: ThemePreference,
-        countdownSeconds: Int
+        countdownSeconds: Int,
+        scrollEasing: ScrollEasing,
+        scrollFrameRate: Int,
+        speedRampSeconds: Double
     ) {
         self.fontSizePreset = fontSizePreset
         self.pipFontSizePreset = pipFontSizePreset
@@ -142,6 +176,9 @@ struct TeleprompterSettings: Codable, Equatable {
         self.timerSeconds = timerSeconds
         self.themePreference = themePreference
         self.countdownSeconds = countdownSeconds
+        self.scrollEasing = scrollEasing
+        self.scrollFrameRate = scrollFrameRate
+        self.speedRampSeconds = speedRampSeconds
     }
 
     init(from decoder: Decoder) throws {
@@ -156,6 +193,9 @@ struct TeleprompterSettings: Codable, Equatable {
         timerSeconds = try container.decode(Int.self, forKey: .timerSeconds)
         themePreference = try container.decode(ThemePreference.self, forKey: .themePreference)
         countdownSeconds = try container.decodeIfPresent(Int.self, forKey: .countdownSeconds) ?? 5
+        scrollEasing = try container.decodeIfPresent(ScrollEasing.self, forKey: .scrollEasing) ?? .easeInOut
+        scrollFrameRate = try container.decodeIfPresent(Int.self, forKey: .scrollFrameRate) ?? 30
+        speedRampSeconds = try container.decodeIfPresent(Double.self, forKey: .speedRampSeconds) ?? 0
     }
 
     func encode(to encoder: Encoder) throws {
@@ -170,6 +210,9 @@ struct TeleprompterSettings: Codable, Equatable {
         try container.encode(timerSeconds, forKey: .timerSeconds)
         try container.encode(themePreference, forKey: .themePreference)
         try container.encode(countdownSeconds, forKey: .countdownSeconds)
+        try container.encode(scrollEasing, forKey: .scrollEasing)
+        try container.encode(scrollFrameRate, forKey: .scrollFrameRate)
+        try container.encode(speedRampSeconds, forKey: .speedRampSeconds)
     }
 }
 
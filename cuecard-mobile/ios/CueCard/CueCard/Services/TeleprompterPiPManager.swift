@@ -20,6 +20,8 @@ class TeleprompterPiPManager: NSObject, ObservableObject {
     private(set) var timerDuration: Int = 0
     private(set) var elapsedTime: Double = 0
     private(set) var currentWordIndex: Int = 0
+    private(set) var motion = ScrollMotion()
+    private var countdownCues = CountdownCueTracker(cues: [])
     private(set) var isDarkMode: Bool = true
     private(set) var totalWords: Int = 0
     private(set) var countdownValue: Int = 0
@@ -62,19 +64,39 @@ class TeleprompterPiPManager: NSObject, ObservableObject {
         self.timerDuration = timerDuration
         self.elapsedTime = 0
         self.currentWordIndex = 0
+        self.motion = ScrollMotion()
         self.isDarkMode = colorScheme == .dark
 
         let parsedContent = TeleprompterParser.parseNotes(text)
         totalWords = parsedContent.words.count
+        countdownCues = CountdownCueTracker(cues: parsedContent.countdownCues)
 
         setupPiP()
     }
 
+    /// Apply changed settings, restarting the timers at the new frame rate
+    func updateSettings(_ settings: TeleprompterSettings) {
+        guard settings != self.settings else { return }
+        self.settings = settings
+        if displayLink != nil {
+            stopDisplayLink()
+            startDisplayLink()
+        }
+        if playbackTimer != nil {
+            startPlaybackTimer()
+        }
+        updateContentView()
+    }
+
     /// Update current state from TeleprompterView
-    func updateState(elapsedTime: Double, isPlaying: Bool, currentWordIndex: Int = 0, countdownValue: Int = 0, isCountingDown: Bool = false) {
+    func updateState(elapsedTime: Double, isPlaying: Bool, currentWordIndex: Int = 0, wordPosition: Double? = nil, countdownValue: Int = 0, isCountingDown: Bool = false) {
         self.elapsedTime = elapsedTime
         self.isPlaying = isPlaying
         self.currentWordIndex = currentWordIndex
+        motion.jump(to: wordPosition ?? Double(currentWordIndex))
+        if !isPlaying {
+            motion.stop()
+        }
         self.countdownValue = countdownValue
         self.isCountingDown = isCountingDown
         updateContentView()
@@ -118,6 +140,8 @@ class TeleprompterPiPManager: NSObject, ObservableObject {
         stopPlaybackTimer()
         elapsedTime = 0
         currentWordIndex = 0
+        motion = ScrollMotion()
+        countdownCues.reset()
         isPlaying = false
         onRestartFromPiP?()
         updateContentView()
@@ -130,6 +154,7 @@ class TeleprompterPiPManager: NSObject, ObservableObject {
             startPlaybackTimer()
         } else {
             stopPlaybackTimer()
+            motion.stop()
         }
         onPlayPauseFromPiP?(isPlaying)
         updateContentView()
@@ -139,11 +164,16 @@ class TeleprompterPiPManager: NSObject, ObservableObject {
 
     private func startPlaybackTimer() {
         stopPlaybackTimer()
-        let interval = 1.0 / 30.0
+        let interval = 1.0 / Double(settings.scrollFrameRate)
         playbackTimer = Timer.scheduledTimer(withTimeInterval: interval, repeats: true) { [weak self] _ in
             Task { @MainActor [weak self] in
                 guard let self = self, self.isPlaying else { return }
                 self.elapsedTime += interval
+                self.motion.advance(
+                    by: interval,
+                    targetRate: Double(self.settings.wordsPerMinute) / 60.0,
+                    rampSeconds: self.settings.speedRampSeconds
+                )
                 self.updateCurrentWordIndex()
                 self.updateContentView()
             }
@@ -169,6 +199,8 @@ class TeleprompterPiPManager: NSObject, ObservableObject {
     /// Seek forward 10 seconds
     func seekForward() {
         elapsedTime = min(elapsedTime + 10, timerDuration > 0 ? Double(timerDuration + 60) : 3600)
+        let wordsPerSecond = Double(settings.wordsPerMinute) / 60.0
+        motion.jump(to: min(motion.position + 10 * wordsPerSecond, Double(max(totalWords - 1, 0))))
         updateCurrentWordIndex()
         updateContentView()
     }
@@ -176,6 +208,8 @@ class TeleprompterPiPManager: NSObject, ObservableObject {
     /// Seek backward 10 seconds
     func seekBackward() {
         elapsedTime = max(elapsedTime - 10, 0)
+        let wordsPerSecond = Double(settings.wordsPerMinute) / 60.0
+        motion.jump(to: motion.position - 10 * wordsPerSecond)
         updateCurrentWordIndex()
         updateContentView()
     }
@@ -291,7 +325,11 @@ class TeleprompterPiPManager: NSObject, ObservableObject {
 
     private func startDisplayLink() {
         displayLink = CADisplayLink(target: self, selector: #selector(updateDisplay))
-        displayLink?.preferredFrameRateRange = CAFrameRateRange(minimum: 15, maximum: 30)
+        displayLink?.preferredFrameRateRange = CAFrameRateRange(
+            minimum: Float(TeleprompterSettings.scrollFrameRateRange.lowerBound),
+            maximum: Float(settings.scrollFrameRate),
+            preferred: Float(settings.scrollFrameRate)
+        )
         displayLink?.add(to: .main, forMode: .common)
     }
 
@@ -308,13 +346,22 @@ class TeleprompterPiPManager: NSObject, ObservableObject {
         let fontSize = CGFloat(settings.pipFontSize)
         let remainingTime = timerDuration > 0 ? timerDuration - Int(elapsedTime) : Int(elapsedTime)
 
-        // Show countdown value if counting down (in mm:ss format), otherwise show timer
-        let timerText = isCountingDown ? TeleprompterParser.formatTime(countdownValue) : TeleprompterParser.formatTime(remainingTime)
+        let cueCountdown = countdownCues.update(wordIndex: currentWordIndex, elapsedTime: elapsedTime)
+
+        // Show countdown value if counting down (in mm:ss format), then a running
+        // [countdown] cue, otherwise show timer
+        let timerText: String
+        if isCountingDown {
+            timerText = TeleprompterParser.formatTime(countdownValue)
+        } else if let cueCountdown {
+            timerText = TeleprompterParser.formatTime(cueCountdown.remainingSeconds)
+        } else {
+            timerText = TeleprompterParser.formatTime(remainingTime)
+        }
 
-        let wordsPerSecond = Double(settings.wordsPerMinute) / 60.0
         let highlightProgress = (elapsedTime == 0 && !isPlaying)
             ? -Double.greatestFiniteMagnitude
-            : (elapsedTime * wordsPerSecond)
+            : motion.position
 
         teleprompterContentView?.update(
             text: text,
@@ -325,7 +372,8 @@ class TeleprompterPiPManager: NSObject, ObservableObject {
             remainingTime: remainingTime,
             currentWordIndex: currentWordIndex,
             highlightProgress: highlightProgress,
-            isCountingDown: isCountingDown
+            scrollEasing: settings.scrollEasing,
+            isCountingDown: isCountingDown || cueCountdown != nil
         )
 
         pipContentView?.update(
@@ -337,7 +385,8 @@ class TeleprompterPiPManager: NSObject, ObservableObject {
             remainingTime: remainingTime,
             currentWordIndex: currentWordIndex,
             highlightProgress: highlightProgress,
-            isCountingDown: isCountingDown
+            scrollEasing: settings.scrollEasing,
+            isCountingDown: isCountingDown || cueCountdown != nil
         )
     }
 
@@ -346,8 +395,7 @@ class TeleprompterPiPManager: NSObject, ObservableObject {
             currentWordIndex = 0
             return
         }
-        let wordsPerSecond = Double(settings.wordsPerMinute) / 60.0
-        let newWordIndex = min(Int(Double(elapsedTime) * wordsPerSecond), totalWords - 1)
+        let newWordIndex = min(Int(motion.position), totalWords - 1)
         currentWordIndex = max(newWordIndex, 0)
     }
 
@@ -521,6 +569,7 @@ private class TeleprompterPiPContentView: UIView {
         remainingTime: Int,
         currentWordIndex: Int,
         highlightProgress: Double,
+        scrollEasing: ScrollEasing = .easeInOut,
         isCountingDown: Bool = false
     ) {
         let contentId = text
@@ -560,7 +609,7 @@ private class TeleprompterPiPContentView: UIView {
                 let maxY = textView.contentSize.height - textView.bounds.height
                 let scrollY = max(0, min(targetY, maxY))
 
-                UIView.animate(withDuration: 0.55, delay: 0, options: [.curveEaseInOut, .allowUserInteraction]) {
+                UIView.animate(withDuration: 0.55, delay: 0, options: [scrollEasing.animationOptions, .allowUserInteraction]) {
                     self.textView.contentOffset = CGPoint(x: 0, y: scrollY)
                 }
             }
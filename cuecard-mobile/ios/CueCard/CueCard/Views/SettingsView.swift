@@ -59,6 +59,7 @@ struct SettingsView: View {
             userInfoSection
             countdownSection
             teleprompterSection
+            scrollingSection
             textSizeSection
             overlaySection
             appearanceSection
@@ -146,6 +147,58 @@ struct SettingsView: View {
         }
     }
 
+    private var scrollingSection: some View {
+        Section("Scrolling") {
+            VStack(alignment: .leading, spacing: 8) {
+                Text("Easing")
+                Picker("Easing", selection: $settingsService.settings.scrollEasing) {
+                    ForEach(ScrollEasing.allCases, id: \.self) { easing in
+                        Text(easing.rawValue).tag(easing)
+                    }
+                }
+                .pickerStyle(.segmented)
+                .labelsHidden()
+            }
+
+            VStack(alignment: .leading, spacing: 8) {
+                HStack {
+                    Text("Smoothness")
+                    Spacer()
+                    Text("\(settingsService.settings.scrollFrameRate) FPS")
+                        .foregroundStyle(.secondary)
+                        .monospacedDigit()
+                }
+
+                Slider(
+                    value: Binding(
+                        get: { Double(settingsService.settings.scrollFrameRate) },
+                        set: { settingsService.settings.scrollFrameRate = Int($0) }
+                    ),
+                    in: Double(TeleprompterSettings.scrollFrameRateRange.lowerBound)...Double(TeleprompterSettings.scrollFrameRateRange.upperBound),
+                    step: 15
+                )
+            }
+            .padding(.vertical, 4)
+
+            VStack(alignment: .leading, spacing: 8) {
+                HStack {
+                    Text("Speed Ramp")
+                    Spacer()
+                    Text(String(format: "%.1f seconds", settingsService.settings.speedRampSeconds))
+                        .foregroundStyle(.secondary)
+                        .monospacedDigit()
+                }
+
+                Slider(
+                    value: $settingsService.settings.speedRampSeconds,
+                    in: TeleprompterSettings.speedRampRange,
+                    step: 0.5
+                )
+            }
+            .padding(.vertical, 4)
+        }
+    }
+
     private var textSizeSection: some View {
         Section("Text Size") {
             VStack(alignment: .leading, spacing: 8) {
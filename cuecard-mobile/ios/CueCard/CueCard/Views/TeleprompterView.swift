@@ -20,6 +20,9 @@ struct TeleprompterView: View {
     @State private var showControls = true
     @State private var controlsTimer: Timer?
     @State private var currentWordIndex: Int = 0
+    @State private var motion = ScrollMotion()
+    @State private var countdownCues = CountdownCueTracker(cues: [])
+    @State private var activeCountdown: ActiveCountdown?
     @State private var dragOffset: CGFloat = 0
     @State private var countdownValue: Int = 0
     @State private var isCountingDown = false
@@ -36,8 +39,8 @@ struct TeleprompterView: View {
     }
 
     private var timerColor: Color {
-        // Show pink color during countdown
-        if isCountingDown {
+        // Show pink color during countdown and [countdown] cues
+        if isCountingDown || activeCountdown != nil {
             return AppColors.pink(for: colorScheme)
         }
         guard timerDuration > 0 else {
@@ -55,6 +58,10 @@ struct TeleprompterView: View {
         if isCountingDown {
             return " \(TeleprompterParser.formatTime(countdownValue)) "
         }
+        // A [countdown mm:ss] cue takes the timer's place while it runs
+        if let activeCountdown {
+            return " \(TeleprompterParser.formatTime(activeCountdown.remainingSeconds)) "
+        }
         if timerDuration > 0 {
             let remaining = timerDuration - Int(elapsedTime)
             return " \(TeleprompterParser.formatTime(remaining)) "
@@ -71,16 +78,16 @@ struct TeleprompterView: View {
                         .ignoresSafeArea()
 
                     // Teleprompter content with attributed text
-                    let wordsPerSecond = Double(settings.wordsPerMinute) / 60.0
                     let highlightProgress = (elapsedTime == 0 && !isPlaying)
                         ? -Double.greatestFiniteMagnitude
-                        : (elapsedTime * wordsPerSecond)
+                        : motion.position
 
                     AttributedTextView(
                         content: content,
                         fontSize: CGFloat(settings.fontSize),
                         currentWordIndex: currentWordIndex,
                         highlightProgress: highlightProgress,
+                        scrollEasing: settings.scrollEasing,
                         colorScheme: colorScheme,
                         topPadding: geometry.size.height * 0.4,
                         bottomPadding: geometry.size.height * 0.6,
@@ -173,6 +180,7 @@ struct TeleprompterView: View {
                 }
                 .onAppear {
                     viewHeight = geometry.size.height
+                    countdownCues = CountdownCueTracker(cues: content.countdownCues)
                     setupPiP()
                     Analytics.logEvent("teleprompter_started", parameters: [
                         "word_count": content.words.count,
@@ -229,15 +237,20 @@ struct TeleprompterView: View {
             stopControlsTimer()
             stopCountdownTimer()
         }
+        .onChange(of: settings) { newSettings in
+            pipManager.updateSettings(newSettings)
+            // Pick up a new frame rate
+            if timer != nil {
+                startTimer()
+            }
+        }
         .onChange(of: scenePhase) { newPhase in
             if newPhase == .background && !pipManager.isPiPActive && pipManager.isPiPPossible {
                 // Auto-start PiP when app goes to background (like YouTube)
                 startPiP(minimizeApp: false)
             } else if newPhase == .active && pipManager.isPiPActive {
                 // Sync state when coming back to foreground
-                elapsedTime = pipManager.elapsedTime
-                isPlaying = pipManager.isPlaying
-                updateCurrentWord()
+                syncFromPiP()
                 if isPlaying && timer == nil {
                     startTimer()
                 }
@@ -247,6 +260,13 @@ struct TeleprompterView: View {
 
     // MARK: - PiP Setup
 
+    private func syncFromPiP() {
+        elapsedTime = pipManager.elapsedTime
+        isPlaying = pipManager.isPlaying
+        motion.jump(to: pipManager.motion.position)
+        updateCurrentWord()
+    }
+
     private func setupPiP() {
         pipManager.configure(
             text: content.fullText,
@@ -256,18 +276,14 @@ struct TeleprompterView: View {
         )
 
         pipManager.onPiPClosed = {
-            elapsedTime = pipManager.elapsedTime
-            isPlaying = pipManager.isPlaying
-            updateCurrentWord()
+            syncFromPiP()
             if isPlaying {
                 startTimer()
             }
         }
 
         pipManager.onPiPRestoreUI = {
-            elapsedTime = pipManager.elapsedTime
-            isPlaying = pipManager.isPlaying
-            updateCurrentWord()
+            syncFromPiP()
             if isPlaying {
                 startTimer()
             }
@@ -281,6 +297,7 @@ struct TeleprompterView: View {
             } else {
                 isPlaying = false
                 stopTimer()
+                motion.stop()
             }
         }
 
@@ -291,15 +308,16 @@ struct TeleprompterView: View {
             isCountingDown = false
             elapsedTime = 0
             currentWordIndex = 0
+            motion = ScrollMotion()
+            countdownCues.reset()
+            activeCountdown = nil
             scrollOffset = 0
             isPlaying = false
         }
 
         // Handle expand from PiP - app will come to foreground automatically
         pipManager.onExpandFromPiP = {
-            elapsedTime = pipManager.elapsedTime
-            isPlaying = pipManager.isPlaying
-            updateCurrentWord()
+            syncFromPiP()
             if isPlaying {
                 startTimer()
             }
@@ -311,6 +329,7 @@ struct TeleprompterView: View {
             elapsedTime: elapsedTime,
             isPlaying: isPlaying,
             currentWordIndex: currentWordIndex,
+            wordPosition: motion.position,
             countdownValue: countdownValue,
             isCountingDown: isCountingDown
         )
@@ -349,14 +368,14 @@ struct TeleprompterView: View {
         // Start countdown
         countdownValue = settings.countdownSeconds
         isCountingDown = true
-        pipManager.updateState(elapsedTime: elapsedTime, isPlaying: isPlaying, currentWordIndex: currentWordIndex, countdownValue: countdownValue, isCountingDown: true)
+        pipManager.updateState(elapsedTime: elapsedTime, isPlaying: isPlaying, currentWordIndex: currentWordIndex, wordPosition: motion.position, countdownValue: countdownValue, isCountingDown: true)
 
         countdownTimer = Timer.scheduledTimer(withTimeInterval: 1.0, repeats: true) { _ in
             Task { @MainActor in
                 withAnimation(.snappy) {
                     countdownValue -= 1
                 }
-                pipManager.updateState(elapsedTime: elapsedTime, isPlaying: isPlaying, currentWordIndex: currentWordIndex, countdownValue: countdownValue, isCountingDown: countdownValue > 0)
+                pipManager.updateState(elapsedTime: elapsedTime, isPlaying: isPlaying, currentWordIndex: currentWordIndex, wordPosition: motion.position, countdownValue: countdownValue, isCountingDown: countdownValue > 0)
 
                 if countdownValue <= 0 {
                     stopCountdownTimer()
@@ -375,7 +394,7 @@ struct TeleprompterView: View {
     private func play() {
         isPlaying = true
         startTimer()
-        pipManager.updateState(elapsedTime: elapsedTime, isPlaying: true, currentWordIndex: currentWordIndex)
+        pipManager.updateState(elapsedTime: elapsedTime, isPlaying: true, currentWordIndex: currentWordIndex, wordPosition: motion.position)
         Analytics.logEvent("teleprompter_play", parameters: nil)
         resetControlsTimer()
     }
@@ -385,12 +404,13 @@ struct TeleprompterView: View {
         if isCountingDown {
             stopCountdownTimer()
             isCountingDown = false
-            pipManager.updateState(elapsedTime: elapsedTime, isPlaying: false, currentWordIndex: currentWordIndex, countdownValue: 0, isCountingDown: false)
+            pipManager.updateState(elapsedTime: elapsedTime, isPlaying: false, currentWordIndex: currentWordIndex, wordPosition: motion.position, countdownValue: 0, isCountingDown: false)
             return
         }
         isPlaying = false
         stopTimer()
-        pipManager.updateState(elapsedTime: elapsedTime, isPlaying: false, currentWordIndex: currentWordIndex)
+        motion.stop()
+        pipManager.updateState(elapsedTime: elapsedTime, isPlaying: false, currentWordIndex: currentWordIndex, wordPosition: motion.position)
         Analytics.logEvent("teleprompter_pause", parameters: nil)
     }
 
@@ -400,6 +420,9 @@ struct TeleprompterView: View {
         isCountingDown = false
         elapsedTime = 0
         currentWordIndex = 0
+        motion = ScrollMotion()
+        countdownCues.reset()
+        activeCountdown = nil
         scrollOffset = 0
         isPlaying = false
         pipManager.updateState(elapsedTime: 0, isPlaying: false, currentWordIndex: 0)
@@ -412,7 +435,9 @@ struct TeleprompterView: View {
         let wordsToSkip = Int(10 * wordsPerSecond)
         currentWordIndex = min(currentWordIndex + wordsToSkip, content.words.count - 1)
         elapsedTime = Double(currentWordIndex) / wordsPerSecond
-        pipManager.updateState(elapsedTime: elapsedTime, isPlaying: isPlaying, currentWordIndex: currentWordIndex)
+        motion.jump(to: Double(currentWordIndex))
+        activeCountdown = countdownCues.update(wordIndex: currentWordIndex, elapsedTime: elapsedTime)
+        pipManager.updateState(elapsedTime: elapsedTime, isPlaying: isPlaying, currentWordIndex: currentWordIndex, wordPosition: motion.position)
     }
 
     private func seekBackward() {
@@ -421,7 +446,9 @@ struct TeleprompterView: View {
         let wordsToSkip = Int(10 * wordsPerSecond)
         currentWordIndex = max(currentWordIndex - wordsToSkip, 0)
         elapsedTime = Double(currentWordIndex) / wordsPerSecond
-        pipManager.updateState(elapsedTime: elapsedTime, isPlaying: isPlaying, currentWordIndex: currentWordIndex)
+        motion.jump(to: Double(currentWordIndex))
+        activeCountdown = countdownCues.update(wordIndex: currentWordIndex, elapsedTime: elapsedTime)
+        pipManager.updateState(elapsedTime: elapsedTime, isPlaying: isPlaying, currentWordIndex: currentWordIndex, wordPosition: motion.position)
     }
 
     private func stopAndDismiss() {
@@ -440,13 +467,14 @@ struct TeleprompterView: View {
         // Prevent multiple timers from running simultaneously
         stopTimer()
 
-        // Timer interval based on WPM setting
+        // Timer interval based on the scroll frame rate setting
         let wordsPerSecond = Double(settings.wordsPerMinute) / 60.0
-        let interval = 1.0 / 30.0
+        let interval = 1.0 / Double(settings.scrollFrameRate)
 
         timer = Timer.scheduledTimer(withTimeInterval: interval, repeats: true) { _ in
             Task { @MainActor in
                 elapsedTime += interval
+                motion.advance(by: interval, targetRate: wordsPerSecond, rampSeconds: settings.speedRampSeconds)
                 updateCurrentWord()
             }
         }
@@ -458,13 +486,13 @@ struct TeleprompterView: View {
     }
 
     private func updateCurrentWord() {
-        let wordsPerSecond = Double(settings.wordsPerMinute) / 60.0
-        let newWordIndex = min(Int(elapsedTime * wordsPerSecond), content.words.count - 1)
+        let newWordIndex = min(Int(motion.position), content.words.count - 1)
         if newWordIndex != currentWordIndex && newWordIndex >= 0 {
             currentWordIndex = newWordIndex
         }
+        activeCountdown = countdownCues.update(wordIndex: currentWordIndex, elapsedTime: elapsedTime)
 
-        pipManager.updateState(elapsedTime: elapsedTime, isPlaying: isPlaying, currentWordIndex: currentWordIndex)
+        pipManager.updateState(elapsedTime: elapsedTime, isPlaying: isPlaying, currentWordIndex: currentWordIndex, wordPosition: motion.position)
     }
 
     // MARK: - Controls Timer
@@ -494,6 +522,7 @@ struct AttributedTextView: UIViewRepresentable {
     let fontSize: CGFloat
     let currentWordIndex: Int
     let highlightProgress: Double
+    let scrollEasing: ScrollEasing
     let colorScheme: ColorScheme
     let topPadding: CGFloat
     let bottomPadding: CGFloat
@@ -576,7 +605,7 @@ struct AttributedTextView: UIViewRepresentable {
                 let maxY = textView.contentSize.height - textView.bounds.height
                 let scrollY = max(0, min(targetY, maxY))
 
-                UIView.animate(withDuration: 0.3, delay: 0, options: [.curveEaseInOut, .allowUserInteraction]) {
+                UIView.animate(withDuration: 0.3, delay: 0, options: [scrollEasing.animationOptions, .allowUserInteraction]) {
                     textView.contentOffset = CGPoint(x: 0, y: scrollY)
                 }
             }